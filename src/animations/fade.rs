@@ -0,0 +1,86 @@
+use super::{AnimationConfig, Easing};
+use std::time::Instant;
+use x11rb::protocol::xproto::Window;
+
+/// What happens to a window once its fade has finished.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FadeEnd {
+    Show,
+    Close,
+}
+
+struct Fade {
+    window: Window,
+    from: f64,
+    to: f64,
+    start_time: Instant,
+    duration_ms: u64,
+    easing: Easing,
+    end: FadeEnd,
+}
+
+/// Opacity fades of any number of windows, stepped from the event loop.
+pub struct FadeAnimations {
+    fades: Vec<Fade>,
+}
+
+impl FadeAnimations {
+    pub fn new() -> Self {
+        Self { fades: Vec::new() }
+    }
+
+    pub fn start(
+        &mut self,
+        window: Window,
+        from: f64,
+        to: f64,
+        end: FadeEnd,
+        config: &AnimationConfig,
+    ) {
+        self.cancel(window);
+        self.fades.push(Fade {
+            window,
+            from,
+            to,
+            start_time: Instant::now(),
+            duration_ms: config.duration.as_millis() as u64,
+            easing: config.easing,
+            end,
+        });
+    }
+
+    /// Returns the current opacity of every fading window, along with how
+    /// the fade ends for those that just finished.
+    pub fn update(&mut self) -> Vec<(Window, f64, Option<FadeEnd>)> {
+        let mut steps = Vec::with_capacity(self.fades.len());
+        self.fades.retain(|fade| {
+            let elapsed = fade.start_time.elapsed().as_millis() as u64;
+            if elapsed >= fade.duration_ms {
+                steps.push((fade.window, fade.to, Some(fade.end)));
+                return false;
+            }
+
+            let t = fade.easing.apply(elapsed as f64 / fade.duration_ms as f64);
+            steps.push((fade.window, fade.from + (fade.to - fade.from) * t, None));
+            true
+        });
+        steps
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.fades.is_empty()
+    }
+
+    /// Stops fading `window`, returning whether it was fading.
+    pub fn cancel(&mut self, window: Window) -> bool {
+        let count = self.fades.len();
+        self.fades.retain(|fade| fade.window != window);
+        self.fades.len() != count
+    }
+}
+
+impl Default for FadeAnimations {
+    fn default() -> Self {
+        Self::new()
+    }
+}
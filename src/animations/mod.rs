@@ -1,5 +1,7 @@
+mod fade;
 mod scroll;
 
+pub use fade::{FadeAnimations, FadeEnd};
 pub use scroll::ScrollAnimation;
 
 use std::time::Duration;
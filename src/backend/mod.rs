@@ -0,0 +1,38 @@
+//! Display server abstraction.
+//!
+//! Mapping, focus, stacking and border updates go through [`Backend`];
+//! the rest of the window manager still talks to X11 directly.
+
+use crate::layout::WindowGeometry;
+
+pub mod x11;
+
+pub use self::x11::X11Backend;
+
+/// Operations the window manager needs from a display server.
+pub trait Backend {
+    type Window: Copy + Eq + std::hash::Hash;
+    type Error: std::fmt::Display;
+
+    fn name(&self) -> &'static str;
+    fn root(&self) -> Self::Window;
+
+    // Window operations
+    fn map_window(&self, window: Self::Window) -> Result<(), Self::Error>;
+    fn unmap_window(&self, window: Self::Window) -> Result<(), Self::Error>;
+    fn configure_window(
+        &self,
+        window: Self::Window,
+        geometry: &WindowGeometry,
+        border_width: u32,
+    ) -> Result<(), Self::Error>;
+    fn raise_window(&self, window: Self::Window) -> Result<(), Self::Error>;
+    fn set_border_color(&self, window: Self::Window, color: u32) -> Result<(), Self::Error>;
+
+    // Input
+    fn set_input_focus(&self, window: Self::Window) -> Result<(), Self::Error>;
+    fn pointer_position(&self) -> Result<(i32, i32), Self::Error>;
+    fn warp_pointer(&self, window: Self::Window, x: i32, y: i32) -> Result<(), Self::Error>;
+
+    fn flush(&self) -> Result<(), Self::Error>;
+}
@@ -0,0 +1,97 @@
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::*;
+use x11rb::rust_connection::RustConnection;
+
+use super::Backend;
+use crate::errors::X11Error;
+use crate::layout::WindowGeometry;
+
+pub struct X11Backend<'a> {
+    connection: &'a RustConnection,
+    root: Window,
+}
+
+impl<'a> X11Backend<'a> {
+    pub fn new(connection: &'a RustConnection, root: Window) -> Self {
+        Self { connection, root }
+    }
+}
+
+impl Backend for X11Backend<'_> {
+    type Window = Window;
+    type Error = X11Error;
+
+    fn name(&self) -> &'static str {
+        "x11"
+    }
+
+    fn root(&self) -> Window {
+        self.root
+    }
+
+    fn map_window(&self, window: Window) -> Result<(), X11Error> {
+        self.connection.map_window(window)?;
+        Ok(())
+    }
+
+    fn unmap_window(&self, window: Window) -> Result<(), X11Error> {
+        self.connection.unmap_window(window)?;
+        Ok(())
+    }
+
+    fn configure_window(
+        &self,
+        window: Window,
+        geometry: &WindowGeometry,
+        border_width: u32,
+    ) -> Result<(), X11Error> {
+        self.connection.configure_window(
+            window,
+            &ConfigureWindowAux::new()
+                .x(geometry.x_coordinate)
+                .y(geometry.y_coordinate)
+                .width(geometry.width)
+                .height(geometry.height)
+                .border_width(border_width),
+        )?;
+        Ok(())
+    }
+
+    fn raise_window(&self, window: Window) -> Result<(), X11Error> {
+        self.connection.configure_window(
+            window,
+            &ConfigureWindowAux::new().stack_mode(StackMode::ABOVE),
+        )?;
+        Ok(())
+    }
+
+    fn set_border_color(&self, window: Window, color: u32) -> Result<(), X11Error> {
+        self.connection.change_window_attributes(
+            window,
+            &ChangeWindowAttributesAux::new().border_pixel(color),
+        )?;
+        Ok(())
+    }
+
+    fn set_input_focus(&self, window: Window) -> Result<(), X11Error> {
+        self.connection
+            .set_input_focus(InputFocus::POINTER_ROOT, window, x11rb::CURRENT_TIME)?;
+        Ok(())
+    }
+
+    fn pointer_position(&self) -> Result<(i32, i32), X11Error> {
+        let reply = self.connection.query_pointer(self.root)?.reply()?;
+        Ok((reply.root_x as i32, reply.root_y as i32))
+    }
+
+    fn warp_pointer(&self, window: Window, x: i32, y: i32) -> Result<(), X11Error> {
+        self.connection
+            .warp_pointer(x11rb::NONE, window, 0, 0, 0, 0, x as i16, y as i16)?;
+        Ok(())
+    }
+
+    fn flush(&self) -> Result<(), X11Error> {
+        self.connection.flush()?;
+        Ok(())
+    }
+}
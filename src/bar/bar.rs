@@ -1,8 +1,12 @@
-use super::blocks::Block;
-use super::font::{DrawingSurface, Font};
-use crate::Config;
+use super::font::Font;
+use super::renderer::{Renderer, XftRenderer, blend};
+use super::{
+    BarPosition, BlockAlign, BlockClicks, BlockConfig, BlockOutput, MinWidth, Shape, Urgency,
+    UrgentBlink,
+};
 use crate::errors::X11Error;
 use crate::monitor::ScreenInfo;
+use crate::{Config, TagStyle};
 use std::time::Instant;
 use x11::xlib::_XDisplay;
 use x11rb::COPY_DEPTH_FROM_PARENT;
@@ -10,15 +14,46 @@ use x11rb::connection::Connection;
 use x11rb::protocol::xproto::*;
 use x11rb::rust_connection::RustConnection;
 
-struct DrawElement {
-    display: *mut _XDisplay,
-    pixmap: x11::xlib::Pixmap,
-    window: Option<x11::xlib::Drawable>,
-    color: u32,
-    x: i32,
-    y: i32,
-    width: u32,
-    height: u32,
+/// How dark the last row of the bar shadow gets, as a fraction towards black.
+const SHADOW_STRENGTH: f32 = 0.5;
+
+/// Tag flashes alternate between the urgent and normal colors this often.
+const FLASH_INTERVAL_MS: u128 = 250;
+const FLASH_PHASES: u128 = 6;
+
+/// Outline colors cycled through by the hit region debug overlay.
+const DEBUG_COLORS: [u32; 4] = [0xff5555, 0x50fa7b, 0x8be9fd, 0xf1fa8c];
+
+/// A clickable part of the bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarRegion {
+    Tag(usize),
+    Layout,
+    Indicator,
+    Title,
+    Block(usize),
+}
+
+impl BarRegion {
+    pub fn kind(&self) -> super::RegionKind {
+        match self {
+            BarRegion::Tag(_) => super::RegionKind::Tag,
+            BarRegion::Layout => super::RegionKind::Layout,
+            BarRegion::Indicator => super::RegionKind::Indicator,
+            BarRegion::Title => super::RegionKind::Title,
+            BarRegion::Block(_) => super::RegionKind::Block,
+        }
+    }
+
+    fn debug_label(&self) -> String {
+        match self {
+            BarRegion::Tag(index) => format!("T{}", index),
+            BarRegion::Layout => "L".to_string(),
+            BarRegion::Indicator => "I".to_string(),
+            BarRegion::Title => "W".to_string(),
+            BarRegion::Block(index) => format!("B{}", index),
+        }
+    }
 }
 
 struct BarObject<'a> {
@@ -34,24 +69,61 @@ pub struct Bar {
     width: u16,
     height: u16,
     graphics_context: Gcontext,
-    surface: DrawingSurface,
+    renderer: XftRenderer,
 
     tag_widths: Vec<u16>,
     needs_redraw: bool,
-
-    blocks: Vec<Box<dyn Block>>,
-    block_last_updates: Vec<Instant>,
+    position: BarPosition,
+    /// Shows only blocks, as the second bar of a monitor.
+    status_only: bool,
+
+    /// The `BlockManager` set whose text this bar shows.
+    block_set: usize,
+    block_outputs: Vec<Option<BlockOutput>>,
+    block_colors: Vec<u32>,
     block_underlines: Vec<bool>,
+    block_popups: Vec<Option<String>>,
+    block_clicks: Vec<BlockClicks>,
+    /// Horizontal extent of each clickable region as last drawn.
+    regions: Vec<(i16, i16, BarRegion)>,
+    debug_regions: bool,
+    block_sensitive: Vec<bool>,
+    hide_sensitive: bool,
+    block_focused_only: Vec<bool>,
+    block_min_widths: Vec<Option<MinWidth>>,
+    block_aligns: Vec<BlockAlign>,
+    /// Whether this bar is on the focused monitor.
+    focused: bool,
+    block_backgrounds: Vec<Option<(u32, Option<u32>)>>,
     status_text: String,
 
     tags: Vec<String>,
+    tag_styles: Vec<TagStyle>,
     scheme_normal: crate::ColorScheme,
     scheme_occupied: crate::ColorScheme,
     scheme_selected: crate::ColorScheme,
     scheme_urgent: crate::ColorScheme,
     hide_vacant_tags: bool,
+    border_width: u16,
+    border_color: u32,
+    shadow_size: u16,
+    monitor_label: Option<String>,
+    label_width: u16,
+    /// Note of the viewed tag, drawn after the layout symbol.
+    tag_note: Option<String>,
+    window_count: Option<String>,
+    /// Class of the focused window, drawn before its title.
+    title_class: Option<String>,
+    title_class_color: Option<u32>,
     last_occupied_tags: u32,
     last_current_tags: u32,
+    flash_tags: u32,
+    flash_started: Option<Instant>,
+    drawn_flash_phase: Option<u128>,
+    urgent_blink: Option<UrgentBlink>,
+    /// When each tag became urgent, while it is.
+    urgent_since: [Option<Instant>; 32],
+    drawn_blink_phase: Option<u128>,
 }
 
 impl Bar {
@@ -97,12 +169,26 @@ impl Bar {
 
         define_cursor(display, window as u64, cursor as u64);
 
+        if config.bar_click_through {
+            crate::shape::set_input_passthrough(connection, window, true)?;
+        }
+        if config.bar_corner_radius > 0 {
+            crate::shape::set_rounded_corners(
+                connection,
+                window,
+                screen_info.width as u16,
+                height,
+                0,
+                config.bar_corner_radius,
+            )?;
+        }
+
         connection.map_window(window)?;
         connection.flush()?;
 
         let (visual, colormap) = get_visual_and_colormap(display, screen_num as i32);
 
-        let surface = DrawingSurface::new(
+        let renderer = XftRenderer::new(
             display,
             window as x11::xlib::Drawable,
             screen_info.width as u32,
@@ -111,22 +197,7 @@ impl Bar {
             colormap,
         )?;
 
-        let horizontal_padding = (font.height() as f32 * 0.4) as u16;
-
-        let tag_widths = config
-            .tags
-            .iter()
-            .map(|tag| {
-                let text_width = font.text_width(tag);
-                text_width + (horizontal_padding * 2)
-            })
-            .collect();
-
-        let blocks: Vec<Box<dyn Block>> = config
-            .status_blocks
-            .iter()
-            .map(|block_config| block_config.to_block())
-            .collect();
+        let tag_widths = tag_widths(&config.tags, &config.tag_styles, font);
 
         let block_underlines: Vec<bool> = config
             .status_blocks
@@ -134,28 +205,58 @@ impl Bar {
             .map(|block_config| block_config.underline)
             .collect();
 
-        let block_last_updates = vec![Instant::now(); blocks.len()];
+        let block_backgrounds = block_backgrounds(&config.status_blocks);
 
         Ok(Bar {
             window,
             width: screen_info.width as u16,
             height,
             graphics_context,
-            surface,
+            renderer,
             tag_widths,
             needs_redraw: true,
-            blocks,
-            block_last_updates,
+            position: BarPosition::Top,
+            status_only: false,
+            block_set: 0,
+            block_outputs: vec![None; config.status_blocks.len()],
+            block_colors: block_colors(&config.status_blocks),
             block_underlines,
+            block_popups: block_popups(&config.status_blocks),
+            block_clicks: block_clicks(&config.status_blocks),
+            block_sensitive: block_sensitive(&config.status_blocks),
+            hide_sensitive: false,
+            block_focused_only: block_focused_only(&config.status_blocks),
+            block_min_widths: block_min_widths(&config.status_blocks),
+            block_aligns: block_aligns(&config.status_blocks),
+            focused: true,
+            regions: Vec::new(),
+            debug_regions: false,
+            block_backgrounds,
             status_text: String::new(),
             tags: config.tags.clone(),
+            tag_styles: config.tag_styles.clone(),
             scheme_normal: config.scheme_normal,
             scheme_occupied: config.scheme_occupied,
             scheme_selected: config.scheme_selected,
             scheme_urgent: config.scheme_urgent,
             hide_vacant_tags: config.hide_vacant_tags,
+            border_width: config.bar_border_width,
+            border_color: config.bar_border_color,
+            shadow_size: config.bar_shadow_size,
+            monitor_label: None,
+            label_width: 0,
+            tag_note: None,
+            window_count: None,
+            title_class: None,
+            title_class_color: config.bar_title_class_color,
             last_occupied_tags: 0,
             last_current_tags: 0,
+            flash_tags: 0,
+            flash_started: None,
+            drawn_flash_phase: None,
+            urgent_blink: config.bar_urgent_blink,
+            urgent_since: [None; 32],
+            drawn_blink_phase: None,
         })
     }
 
@@ -167,48 +268,104 @@ impl Bar {
         self.height
     }
 
+    pub fn position(&self) -> BarPosition {
+        self.position
+    }
+
+    pub fn set_position(&mut self, position: BarPosition) {
+        self.position = position;
+    }
+
+    /// Leaves out the tags, layout symbol and title, for a bar that only
+    /// shows blocks.
+    pub fn set_status_only(&mut self) {
+        self.status_only = true;
+        self.tags.clear();
+        self.tag_styles.clear();
+        self.tag_widths.clear();
+        self.needs_redraw = true;
+    }
+
     pub fn invalidate(&mut self) {
         self.needs_redraw = true;
     }
 
-    pub fn update_blocks(&mut self) {
-        let now = Instant::now();
-        let mut changed = false;
+    pub fn block_set(&self) -> usize {
+        self.block_set
+    }
 
-        for (i, block) in self.blocks.iter_mut().enumerate() {
-            let elapsed = now.duration_since(self.block_last_updates[i]);
+    pub fn set_block_set(&mut self, set: usize) {
+        self.block_set = set;
+    }
 
-            if elapsed >= block.interval() && block.content().is_ok() {
-                self.block_last_updates[i] = now;
-                changed = true;
-            }
+    /// Shows the latest output of the bar's blocks.
+    pub fn set_block_outputs(&mut self, outputs: Vec<Option<BlockOutput>>) {
+        if self.block_outputs != outputs {
+            self.block_outputs = outputs;
+            self.rebuild_status_text();
         }
+    }
 
-        if changed {
-            let mut parts = Vec::new();
-            for block in &mut self.blocks {
-                if let Ok(text) = block.content() {
-                    parts.push(text);
-                }
+    /// Blocks marked `focused_only` are hidden while the bar's monitor
+    /// isn't focused.
+    pub fn set_focused(&mut self, focused: bool) {
+        if self.focused != focused {
+            self.focused = focused;
+            self.rebuild_status_text();
+        }
+    }
+
+    fn block_hidden(&self, index: usize) -> bool {
+        (self.hide_sensitive && self.block_sensitive[index])
+            || (!self.focused && self.block_focused_only[index])
+    }
+
+    /// Hides or shows the blocks marked sensitive.
+    pub fn set_hide_sensitive(&mut self, hide: bool) {
+        if self.hide_sensitive != hide {
+            self.hide_sensitive = hide;
+            self.rebuild_status_text();
+        }
+    }
+
+    fn rebuild_status_text(&mut self) {
+        let mut parts = Vec::new();
+        for (i, output) in self.block_outputs.iter().enumerate() {
+            if self.block_hidden(i) {
+                continue;
+            }
+            if let Some(output) = output {
+                parts.push(output.text());
             }
-            self.status_text = parts.join("");
-            self.needs_redraw = true;
         }
+        self.status_text = parts.join("");
+        self.needs_redraw = true;
     }
 
     pub fn update_tags(&mut self) {}
 
+    /// Replaces the tag labels when tags were added or removed, or their
+    /// styles changed.
+    pub fn set_tags(&mut self, tags: &[String], styles: &[TagStyle], font: &Font) {
+        if self.tags == tags && self.tag_styles == styles && self.tag_widths.len() == tags.len() {
+            return;
+        }
+        self.tag_widths = tag_widths(tags, styles, font);
+        self.tags = tags.to_vec();
+        self.tag_styles = styles.to_vec();
+        self.needs_redraw = true;
+    }
+
     pub fn draw(
         &mut self,
         connection: &RustConnection,
         font: &Font,
-        display: *mut x11::xlib::Display,
         current_tags: u32,
         occupied_tags: u32,
         urgent_tags: u32,
         draw_blocks: bool,
         layout_symbol: &str,
-        keychord_indicator: Option<&str>,
+        indicator: Option<&str>,
         focused_title: Option<String>,
     ) -> Result<(), X11Error> {
         if !self.needs_redraw {
@@ -221,34 +378,95 @@ impl Bar {
         )?;
         connection.flush()?;
 
-        draw_elements(DrawElement {
-            display,
-            pixmap: self.surface.pixmap(),
-            window: None,
-            color: self.scheme_normal.background,
-            x: 0,
-            y: 0,
-            width: self.width as u32,
-            height: self.height as u32,
-        });
+        fill_background(
+            &self.renderer,
+            self.scheme_normal.background,
+            self.scheme_normal.gradient,
+            0,
+            0,
+            self.width as u32,
+            self.height as u32,
+        );
+
+        if self.shadow_size > 0 {
+            let shadow_size = self.shadow_size.min(self.height);
+            let background = self.scheme_normal.background;
+            self.renderer.fill_vertical_gradient(
+                background,
+                blend(background, 0x000000, SHADOW_STRENGTH),
+                0,
+                (self.height - shadow_size) as i32,
+                self.width as u32,
+                shadow_size as u32,
+            );
+        }
 
         self.last_occupied_tags = occupied_tags;
         self.last_current_tags = current_tags;
 
+        let flash_phase = self.flash_phase();
+        self.drawn_flash_phase = flash_phase;
+        if flash_phase.is_none() {
+            self.flash_started = None;
+        }
+        let flashing_tags = match flash_phase {
+            Some(phase) if phase % 2 == 0 => self.flash_tags,
+            _ => 0,
+        };
+
+        let now = Instant::now();
+        for (tag_index, since) in self.urgent_since.iter_mut().enumerate() {
+            if urgent_tags & !current_tags & (1 << tag_index) == 0 {
+                *since = None;
+            } else if since.is_none() {
+                *since = Some(now);
+            }
+        }
+        let blink_phase = self.blink_phase();
+        self.drawn_blink_phase = blink_phase;
+        // Blinking tags show as urgent, background included, every other
+        // phase and as if they weren't urgent in between.
+        let (blink_on, blink_off) = match blink_phase {
+            Some(phase) if phase % 2 == 0 => (self.blinking_tags(), 0),
+            Some(_) => (0, self.blinking_tags()),
+            None => (0, 0),
+        };
+
         let mut x_position: i16 = 0;
         let mut bar_objects: Vec<BarObject> = Vec::new();
+        self.regions.clear();
+
+        self.label_width = 0;
+        if let Some(label) = &self.monitor_label {
+            let horizontal_padding = (font.height() as f32 * 0.4) as u16;
+            let top_padding = 4;
+            bar_objects.push(BarObject {
+                font,
+                color: self.scheme_normal.foreground,
+                x: horizontal_padding as i16,
+                y: top_padding + font.ascent(),
+                text: label.clone(),
+            });
+            self.label_width = font.text_width(label) + horizontal_padding * 3;
+            x_position += self.label_width as i16;
+        }
 
         for (tag_index, tag) in self.tags.iter().enumerate() {
             let tag_mask = 1 << tag_index;
             let is_selected = (current_tags & tag_mask) != 0;
             let is_occupied = (occupied_tags & tag_mask) != 0;
-            let is_urgent = (urgent_tags & tag_mask) != 0;
+            let is_urgent = (((urgent_tags & !blink_off) | flashing_tags) & tag_mask) != 0;
 
             if self.hide_vacant_tags && !is_occupied && !is_selected {
                 continue;
             }
 
             let tag_width = self.tag_widths[tag_index];
+            self.regions.push((
+                x_position,
+                x_position + tag_width as i16,
+                BarRegion::Tag(tag_index),
+            ));
 
             let scheme = if is_selected {
                 &self.scheme_selected
@@ -260,14 +478,37 @@ impl Bar {
                 &self.scheme_normal
             };
 
+            if let Some(gradient) = scheme.gradient {
+                self.renderer.fill_vertical_gradient(
+                    scheme.background,
+                    gradient,
+                    x_position as i32,
+                    0,
+                    tag_width as u32,
+                    self.height as u32,
+                );
+            } else if blink_on & tag_mask != 0 {
+                self.renderer.fill_rect(
+                    scheme.background,
+                    x_position as i32,
+                    0,
+                    tag_width as u32,
+                    self.height as u32,
+                );
+            }
+
             let text_width = font.text_width(tag);
-            let text_x = x_position + ((tag_width - text_width) / 2) as i16;
+            let text_x = x_position + (tag_width.saturating_sub(text_width) / 2) as i16;
+            let color = match self.tag_styles.get(tag_index).and_then(|style| style.color) {
+                Some(color) if !is_urgent => color,
+                _ => scheme.foreground,
+            };
 
             let top_padding = 4;
             let text_y = top_padding + font.ascent();
             bar_objects.push(BarObject {
                 font,
-                color: scheme.foreground,
+                color,
                 x: text_x,
                 y: text_y,
                 text: tag.to_string(),
@@ -283,16 +524,13 @@ impl Bar {
                 let underline_width = tag_width - underline_padding;
                 let underline_x = x_position + (underline_padding / 2) as i16;
 
-                draw_elements(DrawElement {
-                    display,
-                    pixmap: self.surface.pixmap(),
-                    window: None,
-                    color: scheme.underline,
-                    x: underline_x as i32,
-                    y: underline_y as i32,
-                    width: underline_width as u32,
-                    height: underline_height as u32,
-                });
+                self.renderer.fill_rect(
+                    scheme.underline,
+                    underline_x as i32,
+                    underline_y as i32,
+                    underline_width as u32,
+                    underline_height as u32,
+                );
             }
 
             x_position += tag_width as i16;
@@ -304,18 +542,50 @@ impl Bar {
         let top_padding = 4;
         let text_y = top_padding + font.ascent();
 
-        bar_objects.push(BarObject {
-            font,
-            color: self.scheme_normal.foreground,
-            x: text_x,
-            y: text_y,
-            text: layout_symbol.to_string(),
-        });
+        if !self.status_only {
+            bar_objects.push(BarObject {
+                font,
+                color: self.scheme_normal.foreground,
+                x: text_x,
+                y: text_y,
+                text: layout_symbol.to_string(),
+            });
 
-        x_position += font.text_width(layout_symbol) as i16;
+            x_position += font.text_width(layout_symbol) as i16;
+            self.regions.push((text_x, x_position, BarRegion::Layout));
+        }
 
-        if let Some(indicator) = keychord_indicator {
+        if let Some(count) = &self.window_count {
             x_position += 10;
+            bar_objects.push(BarObject {
+                font,
+                color: self.scheme_normal.foreground,
+                x: x_position,
+                y: text_y,
+                text: count.clone(),
+            });
+            x_position += font.text_width(count) as i16;
+        }
+
+        if let Some(note) = &self.tag_note {
+            x_position += 10;
+            bar_objects.push(BarObject {
+                font,
+                color: self.scheme_normal.foreground,
+                x: x_position,
+                y: text_y,
+                text: note.clone(),
+            });
+            x_position += font.text_width(note) as i16;
+        }
+
+        if let Some(indicator) = indicator {
+            x_position += 10;
+            self.regions.push((
+                x_position,
+                x_position + font.text_width(indicator) as i16,
+                BarRegion::Indicator,
+            ));
 
             let text_x = x_position;
             let text_y = top_padding + font.ascent();
@@ -335,23 +605,112 @@ impl Bar {
             let padding = 10;
             let mut x_position = self.width as i16 - padding;
 
-            for (i, block) in self.blocks.iter_mut().enumerate().rev() {
-                if let Ok(text) = block.content() {
-                    let text_width = font.text_width(&text);
+            for (i, output) in self.block_outputs.iter().enumerate().rev() {
+                if self.block_hidden(i) {
+                    continue;
+                }
+                if let Some(output) = output {
+                    let segment_widths: Vec<u16> = output
+                        .segments
+                        .iter()
+                        .map(|segment| match segment.shape {
+                            Some(Shape::Gap(width)) => width,
+                            Some(Shape::Rect { .. }) => 0,
+                            None => font.text_width(&segment.text),
+                        })
+                        .collect();
+                    let content_width: u16 = segment_widths.iter().sum();
+                    let min_width = match self.block_min_widths[i] {
+                        Some(MinWidth::Pixels(pixels)) => pixels,
+                        Some(MinWidth::Chars(chars)) => font.text_width("0") * chars,
+                        None => 0,
+                    };
+                    let text_width = content_width
+                        .max(min_width)
+                        .max(output.min_width.unwrap_or(0));
                     x_position -= text_width as i16;
+                    self.regions.push((
+                        x_position,
+                        x_position + text_width as i16,
+                        BarRegion::Block(i),
+                    ));
 
                     let top_padding = 4;
                     let text_y = top_padding + font.ascent();
 
-                    bar_objects.push(BarObject {
-                        font,
-                        color: block.color(),
-                        x: x_position,
-                        y: text_y,
-                        text,
-                    });
+                    let critical = output.urgency == Urgency::Critical;
+                    let block_color = if output.urgency > Urgency::Normal {
+                        self.scheme_urgent.foreground
+                    } else {
+                        self.block_colors[i]
+                    };
+                    let slack = (text_width - content_width) as i16;
+                    let mut segment_x = x_position
+                        + match self.block_aligns[i] {
+                            BlockAlign::Left => 0,
+                            BlockAlign::Center => slack / 2,
+                            BlockAlign::Right => slack,
+                        };
+                    // Drawn over the block's background, below the text.
+                    let mut fills: Vec<(u32, i32, i32, u32, u32)> = Vec::new();
+                    for (segment, width) in output.segments.iter().zip(&segment_widths) {
+                        let color = segment.color.unwrap_or(block_color);
+                        if let Some(background) = segment.background {
+                            fills.push((
+                                background,
+                                segment_x as i32,
+                                0,
+                                *width as u32,
+                                self.height as u32,
+                            ));
+                        }
+                        match segment.shape {
+                            Some(Shape::Rect {
+                                x,
+                                y,
+                                width,
+                                height,
+                            }) => fills.push((
+                                color,
+                                (segment_x + x) as i32,
+                                y as i32,
+                                width as u32,
+                                height as u32,
+                            )),
+                            Some(Shape::Gap(_)) => {}
+                            None => bar_objects.push(BarObject {
+                                font,
+                                color,
+                                x: segment_x,
+                                y: text_y,
+                                text: segment.text.clone(),
+                            }),
+                        }
+                        segment_x += *width as i16;
+                    }
+
+                    let background = if critical {
+                        Some((self.scheme_urgent.background, self.scheme_urgent.gradient))
+                    } else {
+                        self.block_backgrounds[i]
+                    };
+                    if let Some((background, gradient)) = background {
+                        let background_padding = 8;
+                        fill_background(
+                            &self.renderer,
+                            background,
+                            gradient,
+                            (x_position - (background_padding / 2) as i16) as i32,
+                            0,
+                            (text_width + background_padding) as u32,
+                            self.height as u32,
+                        );
+                    }
+                    for (color, x, y, width, height) in fills {
+                        self.renderer.fill_rect(color, x, y, width, height);
+                    }
 
-                    if self.block_underlines[i] {
+                    if self.block_underlines[i] || critical {
                         let font_height = font.height();
                         let underline_height = font_height / 8;
                         let bottom_gap = 3;
@@ -361,16 +720,18 @@ impl Bar {
                         let underline_width = text_width + underline_padding;
                         let underline_x = x_position - (underline_padding / 2) as i16;
 
-                        draw_elements(DrawElement {
-                            display,
-                            pixmap: self.surface.pixmap(),
-                            window: None,
-                            color: block.color(),
-                            x: underline_x as i32,
-                            y: underline_y as i32,
-                            width: underline_width as u32,
-                            height: underline_height as u32,
-                        });
+                        let underline_color = if critical {
+                            self.scheme_urgent.underline
+                        } else {
+                            self.block_colors[i]
+                        };
+                        self.renderer.fill_rect(
+                            underline_color,
+                            underline_x as i32,
+                            underline_y as i32,
+                            underline_width as u32,
+                            underline_height as u32,
+                        );
                     }
                 }
             }
@@ -380,32 +741,56 @@ impl Bar {
         if let Some(title) = focused_title {
             let end_of_layout_x = x_position + 10;
             let middle_remaining = (end_of_blocks_x - end_of_layout_x) / 2;
+            let prefix = self
+                .title_class
+                .as_ref()
+                .map(|class| format!("[{}] ", class));
+            let prefix_width = prefix
+                .as_deref()
+                .map_or(0, |prefix| font.text_width(prefix) as i16);
             let mut title_width = font.text_width(&title) as i16;
             let mut end_of_title = title.len();
 
-            let title_start = match (middle_remaining - title_width / 2) < end_of_layout_x {
+            let full_width = prefix_width + title_width;
+            let title_start = match (middle_remaining - full_width / 2) < end_of_layout_x {
                 true => end_of_layout_x + 10,
-                false => middle_remaining - title_width / 2,
+                false => middle_remaining - full_width / 2,
             };
 
             // possibly a better way to do this, but since not all fonts are monospace
             // I figured this was the safest and should rarely run more than one or two iterrations
-            while title_start + title_width > end_of_blocks_x {
+            while end_of_title > 0 && title_start + prefix_width + title_width > end_of_blocks_x {
                 end_of_title -= 1;
                 title_width = font.text_width(&title[..end_of_title]) as i16;
             }
 
+            if let Some(prefix) = prefix {
+                bar_objects.push(BarObject {
+                    font,
+                    color: self
+                        .title_class_color
+                        .unwrap_or(self.scheme_selected.foreground),
+                    x: title_start,
+                    y: text_y,
+                    text: prefix,
+                });
+            }
             bar_objects.push(BarObject {
                 font,
                 color: self.scheme_selected.foreground,
-                x: title_start,
+                x: title_start + prefix_width,
                 y: text_y,
                 text: title[..end_of_title].to_string(),
             });
+            self.regions.push((
+                title_start,
+                title_start + prefix_width + title_width,
+                BarRegion::Title,
+            ));
         }
 
         for object in bar_objects {
-            self.surface.font_draw().draw_text(
+            self.renderer.draw_text(
                 object.font,
                 object.color,
                 object.x,
@@ -414,52 +799,271 @@ impl Bar {
             );
         }
 
-        draw_elements(DrawElement {
-            display,
-            pixmap: self.surface.pixmap(),
-            window: Some(self.window as x11::xlib::Drawable),
-            color: 0,
-            x: 0,
-            y: 0,
-            width: self.width as u32,
-            height: self.height as u32,
-        });
+        if self.debug_regions {
+            self.draw_debug_regions(font);
+        }
+
+        self.draw_border();
+
+        self.renderer.copy(self.width as u32, self.height as u32);
 
         self.needs_redraw = false;
 
         Ok(())
     }
 
-    pub fn handle_click(&self, click_x: i16) -> Option<usize> {
-        let mut current_x_position = 0;
+    /// Outlines every clickable region and labels it with its kind and index.
+    fn draw_debug_regions(&self, font: &Font) {
+        let height = self.height as u32;
+        for (i, &(start, end, region)) in self.regions.iter().enumerate() {
+            let color = DEBUG_COLORS[i % DEBUG_COLORS.len()];
+            let width = (end - start).max(1) as u32;
+            let x = start as i32;
+
+            self.renderer.fill_rect(color, x, 0, width, 1);
+            self.renderer
+                .fill_rect(color, x, height as i32 - 1, width, 1);
+            self.renderer.fill_rect(color, x, 0, 1, height);
+            self.renderer
+                .fill_rect(color, x + width as i32 - 1, 0, 1, height);
+            self.renderer.draw_text(
+                font,
+                color,
+                start + 2,
+                self.height as i16 - 2,
+                &region.debug_label(),
+            );
+        }
+    }
+
+    fn draw_border(&self) {
+        if self.border_width == 0 {
+            return;
+        }
 
-        for (tag_index, &tag_width) in self.tag_widths.iter().enumerate() {
-            let tag_mask = 1 << tag_index;
-            let is_selected = (self.last_current_tags & tag_mask) != 0;
-            let is_occupied = (self.last_occupied_tags & tag_mask) != 0;
+        let border = self.border_width.min(self.height / 2).min(self.width / 2) as u32;
+        let (width, height) = (self.width as u32, self.height as u32);
+        let color = self.border_color;
 
-            if self.hide_vacant_tags && !is_occupied && !is_selected {
-                continue;
-            }
+        self.renderer.fill_rect(color, 0, 0, width, border);
+        self.renderer
+            .fill_rect(color, 0, (height - border) as i32, width, border);
+        self.renderer.fill_rect(color, 0, 0, border, height);
+        self.renderer
+            .fill_rect(color, (width - border) as i32, 0, border, height);
+    }
 
-            if click_x >= current_x_position && click_x < current_x_position + tag_width as i16 {
-                return Some(tag_index);
+    /// Returns the tag clicked at `click_x`. A click on a block that has a
+    /// command for `button` spawns it and lets the block react, so the
+    /// caller needs to do nothing more.
+    pub fn handle_click(&mut self, click_x: i16, button: u8) -> Option<usize> {
+        match self.region_at(click_x)? {
+            BarRegion::Tag(tag_index) => Some(tag_index),
+            BarRegion::Block(index) => {
+                if let Some(command) = self.block_click_command(index, button) {
+                    crate::signal::spawn_detached_with_options(
+                        command,
+                        &crate::signal::SpawnOptions {
+                            env: vec![("BLOCK_BUTTON".to_string(), button.to_string())],
+                            ..Default::default()
+                        },
+                    );
+                }
+                None
             }
-            current_x_position += tag_width as i16;
+            _ => None,
+        }
+    }
+
+    /// The command a block runs when clicked with `button`, if any.
+    pub fn block_click_command(&self, index: usize, button: u8) -> Option<&str> {
+        self.block_clicks.get(index)?.command(button)
+    }
+
+    /// Returns the region drawn at `x`, if any.
+    pub fn region_at(&self, x: i16) -> Option<BarRegion> {
+        self.regions
+            .iter()
+            .find(|&&(start, end, _)| x >= start && x < end)
+            .map(|&(_, _, region)| region)
+    }
+
+    /// Like `region_at`, but misses within `dead_zone` pixels of a region's
+    /// edges so clicks between two regions do nothing.
+    pub fn clicked_region(&self, x: i16, dead_zone: i16) -> Option<BarRegion> {
+        self.regions
+            .iter()
+            .find(|&&(start, end, _)| x >= start + dead_zone && x < end - dead_zone)
+            .map(|&(_, _, region)| region)
+    }
+
+    /// Returns the index of the block drawn at `click_x`, if any.
+    pub fn block_at(&self, click_x: i16) -> Option<usize> {
+        match self.region_at(click_x)? {
+            BarRegion::Block(index) => Some(index),
+            _ => None,
+        }
+    }
+
+    /// Clickable regions as last drawn, as start and end x and the region.
+    pub fn regions(&self) -> &[(i16, i16, BarRegion)] {
+        &self.regions
+    }
+
+    /// Shows or hides outlines around the clickable regions.
+    pub fn toggle_debug_regions(&mut self) -> bool {
+        self.debug_regions = !self.debug_regions;
+        self.needs_redraw = true;
+        self.debug_regions
+    }
+
+    pub fn block_popup(&self, index: usize) -> Option<&str> {
+        self.block_popups.get(index)?.as_deref()
+    }
+
+    pub fn set_monitor_label(&mut self, label: Option<String>) {
+        if self.monitor_label != label {
+            self.monitor_label = label;
+            self.needs_redraw = true;
+        }
+    }
+
+    pub fn set_title_class(&mut self, class: Option<String>) {
+        if self.title_class != class {
+            self.title_class = class;
+            self.needs_redraw = true;
+        }
+    }
+
+    pub fn set_window_count(&mut self, count: Option<String>) {
+        if self.window_count != count {
+            self.window_count = count;
+            self.needs_redraw = true;
+        }
+    }
+
+    pub fn set_tag_note(&mut self, note: Option<String>) {
+        if self.tag_note != note {
+            self.tag_note = note;
+            self.needs_redraw = true;
         }
-        None
     }
 
     pub fn needs_redraw(&self) -> bool {
         self.needs_redraw
+            || (self.flash_started.is_some() && self.flash_phase() != self.drawn_flash_phase)
+            || self.blink_phase() != self.drawn_blink_phase
     }
 
-    pub fn update_from_config(&mut self, config: &Config) {
-        self.blocks = config
-            .status_blocks
+    /// Briefly blinks the cells of `tags` in the urgent colors.
+    pub fn flash_tags(&mut self, tags: u32) {
+        self.flash_tags = tags;
+        self.flash_started = Some(Instant::now());
+        self.needs_redraw = true;
+    }
+
+    fn flash_phase(&self) -> Option<u128> {
+        let phase = self.flash_started?.elapsed().as_millis() / FLASH_INTERVAL_MS;
+        (phase < FLASH_PHASES).then_some(phase)
+    }
+
+    /// Urgent tags that haven't blinked for the whole duration yet.
+    fn blinking_tags(&self) -> u32 {
+        let Some(blink) = self.urgent_blink else {
+            return 0;
+        };
+        let mut tags = 0;
+        for (tag_index, since) in self.urgent_since.iter().enumerate() {
+            if let Some(since) = since
+                && (blink.duration_ms == 0
+                    || since.elapsed().as_millis() < blink.duration_ms as u128)
+            {
+                tags |= 1 << tag_index;
+            }
+        }
+        tags
+    }
+
+    /// Blink phase of the urgent tags, counted from the first of them to
+    /// become urgent; `None` while none blinks.
+    fn blink_phase(&self) -> Option<u128> {
+        let blink = self.urgent_blink?;
+        let blinking = self.blinking_tags();
+        let started = self
+            .urgent_since
             .iter()
-            .map(|block_config| block_config.to_block())
+            .enumerate()
+            .filter(|(tag_index, _)| blinking & (1 << tag_index) != 0)
+            .filter_map(|(_, since)| *since)
+            .min()?;
+        Some(started.elapsed().as_millis() / blink.interval_ms.max(1) as u128)
+    }
+
+    /// Makes the bar ignore pointer input so clicks reach the windows below it.
+    pub fn set_click_through(
+        &self,
+        connection: &RustConnection,
+        enabled: bool,
+    ) -> Result<(), X11Error> {
+        crate::shape::set_input_passthrough(connection, self.window, enabled)?;
+        connection.flush()?;
+        Ok(())
+    }
+
+    pub fn set_corner_radius(
+        &self,
+        connection: &RustConnection,
+        radius: u16,
+    ) -> Result<(), X11Error> {
+        crate::shape::set_rounded_corners(
+            connection,
+            self.window,
+            self.width,
+            self.height,
+            0,
+            radius,
+        )?;
+        connection.flush()?;
+        Ok(())
+    }
+
+    /// Replaces the bar's blocks, e.g. with the blocks of a monitor config.
+    pub fn set_blocks(&mut self, blocks: &[BlockConfig]) {
+        self.block_outputs = vec![None; blocks.len()];
+        self.block_colors = block_colors(blocks);
+        self.block_underlines = blocks
+            .iter()
+            .map(|block_config| block_config.underline)
             .collect();
+        self.block_backgrounds = block_backgrounds(blocks);
+        self.block_popups = block_popups(blocks);
+        self.block_clicks = block_clicks(blocks);
+        self.block_sensitive = block_sensitive(blocks);
+        self.block_focused_only = block_focused_only(blocks);
+        self.block_min_widths = block_min_widths(blocks);
+        self.block_aligns = block_aligns(blocks);
+        self.status_text.clear();
+        self.needs_redraw = true;
+    }
+
+    pub fn destroy(&self, connection: &RustConnection) -> Result<(), X11Error> {
+        connection.free_gc(self.graphics_context)?;
+        connection.destroy_window(self.window)?;
+        Ok(())
+    }
+
+    pub fn move_to(&self, connection: &RustConnection, x: i16, y: i16) -> Result<(), X11Error> {
+        connection.configure_window(
+            self.window,
+            &ConfigureWindowAux::new().x(x as i32).y(y as i32),
+        )?;
+        connection.flush()?;
+        Ok(())
+    }
+
+    pub fn update_from_config(&mut self, config: &Config) {
+        self.block_outputs = vec![None; config.status_blocks.len()];
+        self.block_colors = block_colors(&config.status_blocks);
 
         self.block_underlines = config
             .status_blocks
@@ -467,54 +1071,116 @@ impl Bar {
             .map(|block_config| block_config.underline)
             .collect();
 
-        self.block_last_updates = vec![Instant::now(); self.blocks.len()];
+        self.block_backgrounds = block_backgrounds(&config.status_blocks);
+        self.block_popups = block_popups(&config.status_blocks);
+        self.block_clicks = block_clicks(&config.status_blocks);
+        self.block_sensitive = block_sensitive(&config.status_blocks);
+        self.block_focused_only = block_focused_only(&config.status_blocks);
+        self.block_min_widths = block_min_widths(&config.status_blocks);
+        self.block_aligns = block_aligns(&config.status_blocks);
 
-        self.tags = config.tags.clone();
         self.scheme_normal = config.scheme_normal;
         self.scheme_occupied = config.scheme_occupied;
         self.scheme_selected = config.scheme_selected;
         self.scheme_urgent = config.scheme_urgent;
         self.hide_vacant_tags = config.hide_vacant_tags;
+        self.border_width = config.bar_border_width;
+        self.border_color = config.bar_border_color;
+        self.shadow_size = config.bar_shadow_size;
+        self.title_class_color = config.bar_title_class_color;
+        self.urgent_blink = config.bar_urgent_blink;
 
         self.status_text.clear();
         self.needs_redraw = true;
     }
 }
 
-fn draw_elements(element: DrawElement) {
-    unsafe {
-        let gc = x11::xlib::XCreateGC(element.display, element.pixmap, 0, std::ptr::null_mut());
-        match element.window {
-            Some(w) => {
-                x11::xlib::XCopyArea(
-                    element.display,
-                    element.pixmap,
-                    w,
-                    gc,
-                    element.x,
-                    element.y,
-                    element.width,
-                    element.height,
-                    0,
-                    0,
-                );
-                x11::xlib::XFreeGC(element.display, gc);
-                x11::xlib::XSync(element.display, 1);
-            }
-            None => {
-                x11::xlib::XSetForeground(element.display, gc, element.color as u64);
-                x11::xlib::XFillRectangle(
-                    element.display,
-                    element.pixmap,
-                    gc,
-                    element.x,
-                    element.y,
-                    element.width,
-                    element.height,
-                );
-                x11::xlib::XFreeGC(element.display, gc);
-            }
-        }
+fn tag_widths(tags: &[String], styles: &[TagStyle], font: &Font) -> Vec<u16> {
+    let horizontal_padding = (font.height() as f32 * 0.4) as u16;
+    tags.iter()
+        .enumerate()
+        .map(|(tag_index, tag)| {
+            styles
+                .get(tag_index)
+                .and_then(|style| style.width)
+                .unwrap_or_else(|| font.text_width(tag) + horizontal_padding * 2)
+        })
+        .collect()
+}
+
+fn block_colors(blocks: &[BlockConfig]) -> Vec<u32> {
+    blocks
+        .iter()
+        .map(|block_config| block_config.color)
+        .collect()
+}
+
+fn block_backgrounds(blocks: &[BlockConfig]) -> Vec<Option<(u32, Option<u32>)>> {
+    blocks
+        .iter()
+        .map(|block_config| {
+            block_config
+                .background
+                .map(|background| (background, block_config.gradient))
+        })
+        .collect()
+}
+
+fn block_min_widths(blocks: &[BlockConfig]) -> Vec<Option<MinWidth>> {
+    blocks
+        .iter()
+        .map(|block_config| block_config.min_width)
+        .collect()
+}
+
+fn block_aligns(blocks: &[BlockConfig]) -> Vec<BlockAlign> {
+    blocks
+        .iter()
+        .map(|block_config| block_config.align)
+        .collect()
+}
+
+fn block_focused_only(blocks: &[BlockConfig]) -> Vec<bool> {
+    blocks
+        .iter()
+        .map(|block_config| block_config.focused_only)
+        .collect()
+}
+
+fn block_sensitive(blocks: &[BlockConfig]) -> Vec<bool> {
+    blocks
+        .iter()
+        .map(|block_config| block_config.sensitive)
+        .collect()
+}
+
+fn block_clicks(blocks: &[BlockConfig]) -> Vec<BlockClicks> {
+    blocks
+        .iter()
+        .map(|block_config| block_config.clicks.clone())
+        .collect()
+}
+
+fn block_popups(blocks: &[BlockConfig]) -> Vec<Option<String>> {
+    blocks
+        .iter()
+        .map(|block_config| block_config.popup.clone())
+        .collect()
+}
+
+/// Fills with a flat color, or a vertical gradient when `gradient` is set.
+fn fill_background(
+    renderer: &impl Renderer,
+    background: u32,
+    gradient: Option<u32>,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+) {
+    match gradient {
+        Some(bottom) => renderer.fill_vertical_gradient(background, bottom, x, y, width, height),
+        None => renderer.fill_rect(background, x, y, width, height),
     }
 }
 
@@ -1,5 +1,6 @@
-use super::Block;
+use super::{BatteryAlerts, Block, BlockOutput, Urgency};
 use crate::errors::BlockError;
+use crate::signal::SpawnOptions;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::time::Duration;
@@ -11,6 +12,9 @@ pub struct Battery {
     interval: Duration,
     color: u32,
     battery_path: String,
+    alerts: BatteryAlerts,
+    /// `on_critical` ran and waits for the battery to charge again.
+    alerted: bool,
 }
 
 fn detect_battery_name() -> Option<String> {
@@ -54,6 +58,7 @@ impl Battery {
         interval_secs: u64,
         color: u32,
         battery_name: Option<String>,
+        alerts: BatteryAlerts,
     ) -> Self {
         let name = battery_name
             .or_else(detect_battery_name)
@@ -66,6 +71,8 @@ impl Battery {
             interval: Duration::from_secs(interval_secs),
             color,
             battery_path: format!("/sys/class/power_supply/{}", name),
+            alerts,
+            alerted: false,
         }
     }
 
@@ -81,20 +88,56 @@ impl Battery {
     fn get_status(&self) -> Result<String, BlockError> {
         self.read_file("status")
     }
+
+    /// Runs `on_critical` when a discharging battery first reaches the
+    /// critical charge, and again only after it charged above it.
+    fn alert(&mut self, capacity: u32, discharging: bool) {
+        let critical = discharging && capacity <= self.alerts.critical;
+        if !critical {
+            self.alerted = false;
+            return;
+        }
+        if self.alerted {
+            return;
+        }
+        self.alerted = true;
+
+        if let Some(command) = &self.alerts.on_critical {
+            let options = SpawnOptions {
+                cwd: None,
+                env: vec![("OXWM_BATTERY".to_string(), capacity.to_string())],
+            };
+            crate::signal::spawn_detached_with_options(command, &options);
+        }
+    }
 }
 
 impl Block for Battery {
-    fn content(&mut self) -> Result<String, BlockError> {
+    fn content(&mut self) -> Result<BlockOutput, BlockError> {
         let capacity = self.get_capacity()?;
         let status = self.get_status()?;
 
-        let format = match status.as_str() {
-            "Charging" => &self.format_charging,
-            "Full" => &self.format_full,
-            _ => &self.format_discharging,
+        let (format, discharging) = match status.as_str() {
+            "Charging" => (&self.format_charging, false),
+            "Full" => (&self.format_full, false),
+            _ => (&self.format_discharging, true),
         };
 
-        Ok(format.replace("{}", &capacity.to_string()))
+        let mut output = BlockOutput::from(format.replace("{}", &capacity.to_string()));
+        let low = discharging && capacity <= self.alerts.warning;
+        if low && let Some(low_color) = self.alerts.low_color {
+            for segment in &mut output.segments {
+                segment.color = Some(low_color);
+            }
+        }
+        if discharging && capacity <= self.alerts.critical {
+            output.urgency = Urgency::Critical;
+        } else if low && self.alerts.low_color.is_none() {
+            output.urgency = Urgency::Warning;
+        }
+
+        self.alert(capacity, discharging);
+        Ok(output)
     }
 
     fn interval(&self) -> Duration {
@@ -104,4 +147,8 @@ impl Block for Battery {
     fn color(&self) -> u32 {
         self.color
     }
+
+    fn follows_power_supply(&self) -> bool {
+        true
+    }
 }
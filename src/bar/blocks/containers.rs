@@ -0,0 +1,109 @@
+use super::{Block, BlockOutput};
+use crate::errors::BlockError;
+use crate::json::Json;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How long the daemon may take to answer before the block gives up.
+const SOCKET_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Counts Docker or Podman containers through the daemon's API socket.
+/// Hidden while no daemon is listening.
+pub struct Containers {
+    format: String,
+    /// Found again on every refresh when not configured, so a daemon
+    /// started later is picked up.
+    socket: Option<PathBuf>,
+    interval: Duration,
+    color: u32,
+}
+
+impl Containers {
+    pub fn new(format: &str, socket: Option<&str>, interval_secs: u64, color: u32) -> Self {
+        Self {
+            format: format.to_string(),
+            socket: socket.map(crate::signal::expand_home),
+            interval: Duration::from_secs(interval_secs),
+            color,
+        }
+    }
+
+    fn socket_path(&self) -> Option<PathBuf> {
+        if let Some(socket) = &self.socket {
+            return Some(socket.clone());
+        }
+
+        let mut candidates = Vec::new();
+        if let Ok(host) = std::env::var("DOCKER_HOST")
+            && let Some(path) = host.strip_prefix("unix://")
+        {
+            candidates.push(PathBuf::from(path));
+        }
+        candidates.push(PathBuf::from("/run/docker.sock"));
+        candidates.push(PathBuf::from("/var/run/docker.sock"));
+        if let Ok(runtime_dir) = std::env::var("XDG_RUNTIME_DIR") {
+            candidates.push(PathBuf::from(runtime_dir).join("podman/podman.sock"));
+        }
+        candidates.push(PathBuf::from("/run/podman/podman.sock"));
+
+        candidates.into_iter().find(|path| path.exists())
+    }
+}
+
+/// Sends a GET request over the socket and returns the response body.
+/// HTTP/1.0 keeps the daemon from chunking the body.
+fn http_get(socket: &Path, path: &str) -> Result<String, BlockError> {
+    let mut stream = UnixStream::connect(socket)?;
+    stream.set_read_timeout(Some(SOCKET_TIMEOUT))?;
+    stream.set_write_timeout(Some(SOCKET_TIMEOUT))?;
+    write!(stream, "GET {} HTTP/1.0\r\nHost: localhost\r\n\r\n", path)?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .ok_or_else(|| BlockError::InvalidData("malformed HTTP response".to_string()))?;
+    let status = head.lines().next().unwrap_or_default();
+    if status.split_whitespace().nth(1) != Some("200") {
+        return Err(BlockError::CommandFailed(format!(
+            "container daemon answered '{}'",
+            status
+        )));
+    }
+    Ok(body.to_string())
+}
+
+impl Block for Containers {
+    fn content(&mut self) -> Result<BlockOutput, BlockError> {
+        let socket = self
+            .socket_path()
+            .ok_or_else(|| BlockError::MissingFile("docker or podman socket".to_string()))?;
+        let body = http_get(&socket, "/containers/json?all=true")?;
+
+        let containers = Json::parse(&body).map_err(BlockError::InvalidData)?;
+        let containers = containers
+            .as_array()
+            .ok_or_else(|| BlockError::InvalidData("expected a list of containers".to_string()))?;
+        let running = containers
+            .iter()
+            .filter(|container| container.get("State").and_then(Json::as_str) == Some("running"))
+            .count();
+
+        Ok(self
+            .format
+            .replace("{total}", &containers.len().to_string())
+            .replace("{}", &running.to_string())
+            .into())
+    }
+
+    fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    fn color(&self) -> u32 {
+        self.color
+    }
+}
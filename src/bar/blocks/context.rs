@@ -0,0 +1,93 @@
+use super::shell::run_command;
+use super::{Block, BlockOutput, FocusContext};
+use crate::errors::BlockError;
+use std::time::Duration;
+
+/// Shows the output of a command run with details of the focused window in
+/// its environment. It runs when focus or the focused title changes rather
+/// than on an interval, and is hidden when the output is empty or the
+/// focused window's class is not listed.
+pub struct ContextBlock {
+    format: String,
+    command: String,
+    classes: Vec<String>,
+    timeout: Option<Duration>,
+    color: u32,
+    cached_output: Option<String>,
+}
+
+impl ContextBlock {
+    pub fn new(
+        format: &str,
+        command: &str,
+        classes: &[String],
+        timeout_secs: Option<u64>,
+        color: u32,
+    ) -> Self {
+        Self {
+            format: format.to_string(),
+            command: command.to_string(),
+            classes: classes.to_vec(),
+            timeout: timeout_secs.map(Duration::from_secs),
+            color,
+            cached_output: None,
+        }
+    }
+
+    fn evaluate(&self, context: &FocusContext) -> Option<String> {
+        if !self.classes.is_empty()
+            && !self
+                .classes
+                .iter()
+                .any(|class| class.eq_ignore_ascii_case(&context.class))
+        {
+            return None;
+        }
+
+        let envs = [
+            ("OXWM_CLASS", context.class.clone()),
+            ("OXWM_INSTANCE", context.instance.clone()),
+            ("OXWM_TITLE", context.title.clone()),
+            (
+                "OXWM_PID",
+                context.pid.map(|pid| pid.to_string()).unwrap_or_default(),
+            ),
+        ];
+        match run_command(&self.command, &envs, self.timeout) {
+            Ok(output) if !output.is_empty() => Some(self.format.replace("{}", &output)),
+            Ok(_) => None,
+            Err(e) => {
+                eprintln!("Block '{}': {}", self.command, e);
+                None
+            }
+        }
+    }
+}
+
+impl Block for ContextBlock {
+    fn content(&mut self) -> Result<BlockOutput, BlockError> {
+        self.cached_output
+            .clone()
+            .map(BlockOutput::from)
+            .ok_or_else(|| BlockError::InvalidData("nothing to show for this window".to_string()))
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(u64::MAX)
+    }
+
+    fn color(&self) -> u32 {
+        self.color
+    }
+
+    fn follows_focus(&self) -> bool {
+        true
+    }
+
+    fn set_focus(&mut self, context: &FocusContext) -> bool {
+        let output = self.evaluate(context);
+        let changed = output != self.cached_output;
+        self.cached_output = output;
+        changed
+    }
+}
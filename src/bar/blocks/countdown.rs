@@ -0,0 +1,208 @@
+use super::{Block, BlockOutput, Urgency};
+use crate::errors::BlockError;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+struct Event {
+    start: DateTime<Local>,
+    title: String,
+}
+
+/// Time left until the next event in an iCalendar file, or in a plain file
+/// with one `YYYY-MM-DD HH:MM Title` line per event.
+pub struct Countdown {
+    format: String,
+    path: PathBuf,
+    interval: Duration,
+    color: u32,
+    warning: chrono::Duration,
+    critical: chrono::Duration,
+    events: Vec<Event>,
+    /// Modification time of the file when `events` was read.
+    loaded: Option<SystemTime>,
+}
+
+impl Countdown {
+    pub fn new(
+        format: &str,
+        file: &str,
+        interval_secs: u64,
+        warning_minutes: u64,
+        critical_minutes: u64,
+        color: u32,
+    ) -> Self {
+        Self {
+            format: format.to_string(),
+            path: crate::signal::expand_home(file),
+            interval: Duration::from_secs(interval_secs),
+            color,
+            warning: chrono::Duration::minutes(warning_minutes as i64),
+            critical: chrono::Duration::minutes(critical_minutes as i64),
+            events: Vec::new(),
+            loaded: None,
+        }
+    }
+
+    /// Reads the file again if it changed since it was last read.
+    fn reload(&mut self) -> Result<(), BlockError> {
+        let modified = fs::metadata(&self.path)?.modified()?;
+        if self.loaded == Some(modified) {
+            return Ok(());
+        }
+
+        let text = fs::read_to_string(&self.path)?;
+        self.events = if text.trim_start().starts_with("BEGIN:VCALENDAR") {
+            parse_ics(&text)
+        } else {
+            parse_event_lines(&text)
+        };
+        self.loaded = Some(modified);
+        Ok(())
+    }
+}
+
+/// Reads the start and summary of each VEVENT. Recurrence rules aren't
+/// expanded, and times with a TZID are taken as local time.
+fn parse_ics(text: &str) -> Vec<Event> {
+    // Long lines are folded onto lines starting with a space or tab.
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    let mut events = Vec::new();
+    let mut start = None;
+    let mut title = String::new();
+    for line in &lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (property, params) = name.split_once(';').unwrap_or((name, ""));
+        match property {
+            "BEGIN" if value == "VEVENT" => {
+                start = None;
+                title.clear();
+            }
+            "DTSTART" => start = parse_ics_time(value, params),
+            "SUMMARY" => title = unescape_ics(value),
+            "END" if value == "VEVENT" => {
+                if let Some(start) = start.take() {
+                    events.push(Event {
+                        start,
+                        title: std::mem::take(&mut title),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    events
+}
+
+fn parse_ics_time(value: &str, params: &str) -> Option<DateTime<Local>> {
+    if params.contains("VALUE=DATE") && !params.contains("VALUE=DATE-TIME") {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        return Local
+            .from_local_datetime(&date.and_hms_opt(0, 0, 0)?)
+            .earliest();
+    }
+    if let Some(utc) = value.strip_suffix('Z') {
+        let time = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(Utc.from_utc_datetime(&time).with_timezone(&Local));
+    }
+    let time = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    Local.from_local_datetime(&time).earliest()
+}
+
+fn unescape_ics(value: &str) -> String {
+    let mut text = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => text.push(' '),
+            Some(escaped) => text.push(escaped),
+            None => {}
+        }
+    }
+    text
+}
+
+/// Reads `YYYY-MM-DD HH:MM Title` lines, skipping blank lines and `#`
+/// comments.
+fn parse_event_lines(text: &str) -> Vec<Event> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, ' ');
+            let date = parts.next()?;
+            let time = parts.next()?;
+            let title = parts.next().unwrap_or_default().trim();
+            let start =
+                NaiveDateTime::parse_from_str(&format!("{} {}", date, time), "%Y-%m-%d %H:%M")
+                    .ok()?;
+            Some(Event {
+                start: Local.from_local_datetime(&start).earliest()?,
+                title: title.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// "3d 4h", "2h 15m" or "12m".
+fn format_remaining(remaining: chrono::Duration) -> String {
+    // Rounded up, so an event a few seconds away doesn't show as 0m.
+    let minutes = (remaining.num_seconds() + 59) / 60;
+    let (days, hours, minutes) = (minutes / 1440, minutes / 60 % 24, minutes % 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+impl Block for Countdown {
+    fn content(&mut self) -> Result<BlockOutput, BlockError> {
+        self.reload()?;
+
+        let now = Local::now();
+        let next = self
+            .events
+            .iter()
+            .filter(|event| event.start > now)
+            .min_by_key(|event| event.start)
+            .ok_or_else(|| BlockError::InvalidData("no upcoming events".to_string()))?;
+
+        let remaining = next.start - now;
+        let mut output = BlockOutput::from(
+            self.format
+                .replace("{title}", &next.title)
+                .replace("{}", &format_remaining(remaining)),
+        );
+        if remaining <= self.critical {
+            output.urgency = Urgency::Critical;
+        } else if remaining <= self.warning {
+            output.urgency = Urgency::Warning;
+        }
+        Ok(output)
+    }
+
+    fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    fn color(&self) -> u32 {
+        self.color
+    }
+}
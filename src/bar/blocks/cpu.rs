@@ -0,0 +1,108 @@
+use super::{Block, BlockOutput, Thresholds};
+use crate::errors::BlockError;
+use std::fs;
+use std::time::Duration;
+
+/// Bars for `{cores}`, from idle to fully busy.
+const LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Idle and total jiffies of one line of /proc/stat.
+#[derive(Debug, Clone, Copy, Default)]
+struct Times {
+    idle: u64,
+    total: u64,
+}
+
+/// How busy the CPU was since the last update, from /proc/stat: in percent
+/// for all cores together, and as a bar per core.
+pub struct Cpu {
+    format: String,
+    thresholds: Thresholds,
+    interval: Duration,
+    color: u32,
+    /// All cores first, then each core.
+    last: Vec<Times>,
+}
+
+impl Cpu {
+    pub fn new(format: &str, thresholds: Thresholds, interval_secs: u64, color: u32) -> Self {
+        Self {
+            format: format.to_string(),
+            thresholds,
+            interval: Duration::from_secs(interval_secs),
+            color,
+            last: Vec::new(),
+        }
+    }
+}
+
+fn read_times() -> Result<Vec<Times>, BlockError> {
+    let stat = fs::read_to_string("/proc/stat")?;
+    let times: Vec<Times> = stat
+        .lines()
+        .take_while(|line| line.starts_with("cpu"))
+        .map(|line| {
+            let values: Vec<u64> = line
+                .split_whitespace()
+                .skip(1)
+                .filter_map(|value| value.parse().ok())
+                .collect();
+            // user nice system idle iowait irq softirq steal; guest time is
+            // already counted in user and nice.
+            let total = values.iter().take(8).sum();
+            let idle = values.get(3).copied().unwrap_or(0) + values.get(4).copied().unwrap_or(0);
+            Times { idle, total }
+        })
+        .collect();
+    if times.is_empty() {
+        return Err(BlockError::InvalidData(
+            "no cpu lines in /proc/stat".to_string(),
+        ));
+    }
+    Ok(times)
+}
+
+/// Busy percentage between two readings; since boot for the first one.
+fn usage(now: Times, last: Times) -> f64 {
+    let total = now.total.saturating_sub(last.total);
+    let idle = now.idle.saturating_sub(last.idle);
+    if total == 0 {
+        return 0.0;
+    }
+    (total.saturating_sub(idle)) as f64 / total as f64 * 100.0
+}
+
+impl Block for Cpu {
+    fn content(&mut self) -> Result<BlockOutput, BlockError> {
+        let times = read_times()?;
+        let last = |index: usize| self.last.get(index).copied().unwrap_or_default();
+
+        let total = usage(times[0], last(0));
+        let cores: String = times
+            .iter()
+            .enumerate()
+            .skip(1)
+            .map(|(index, &now)| {
+                let level = (usage(now, last(index)) / 100.0 * LEVELS.len() as f64) as usize;
+                LEVELS[level.min(LEVELS.len() - 1)]
+            })
+            .collect();
+        self.last = times;
+
+        let result = self
+            .format
+            .replace("{cores}", &cores)
+            .replace("{}", &format!("{:.0}", total));
+        let mut output = BlockOutput::from(result);
+        self.thresholds.apply(&mut output, total);
+        Ok(output)
+    }
+
+    fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    fn color(&self) -> u32 {
+        self.color
+    }
+}
@@ -1,31 +1,107 @@
-use super::Block;
+use super::{Block, BlockOutput, ClockZone};
 use crate::errors::BlockError;
 use chrono::Local;
+use std::fmt::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
 use std::time::Duration;
 
+const ZONEINFO_DIR: &str = "/usr/share/zoneinfo";
+
 pub struct DateTime {
     format_template: String,
     time_format: String,
+    zones: Vec<ClockZone>,
+    /// Index into `zones` of the zone shown.
+    current: usize,
+    locale: Option<String>,
     interval: Duration,
     color: u32,
 }
 
 impl DateTime {
-    pub fn new(format_template: &str, time_format: &str, interval_secs: u64, color: u32) -> Self {
+    pub fn new(
+        format_template: &str,
+        time_format: &str,
+        zones: Vec<ClockZone>,
+        locale: Option<String>,
+        interval_secs: u64,
+        color: u32,
+    ) -> Self {
+        // date(1) quietly falls back to UTC for zones it doesn't know.
+        if Path::new(ZONEINFO_DIR).is_dir() {
+            for tz in zones.iter().filter_map(|zone| zone.tz.as_deref()) {
+                if !Path::new(ZONEINFO_DIR).join(tz).is_file() {
+                    eprintln!("Clock block: unknown timezone '{}'", tz);
+                }
+            }
+        }
+
         Self {
             format_template: format_template.to_string(),
             time_format: time_format.to_string(),
+            zones,
+            current: 0,
+            locale,
             interval: Duration::from_secs(interval_secs),
             color,
         }
     }
 }
 
+/// Formats the local time in the default locale.
+fn format_local(time_format: &str) -> Result<String, BlockError> {
+    let mut text = String::new();
+    write!(text, "{}", Local::now().format(time_format))
+        .map_err(|_| BlockError::InvalidData(format!("invalid date format '{}'", time_format)))?;
+    Ok(text)
+}
+
+/// Has date(1) format the time, which knows every timezone and the
+/// system's locales.
+fn format_with_date(
+    time_format: &str,
+    tz: Option<&str>,
+    locale: Option<&str>,
+) -> Result<String, BlockError> {
+    let mut command = Command::new("date");
+    command
+        .arg(format!("+{}", time_format))
+        .stdin(Stdio::null())
+        .stderr(Stdio::null());
+    if let Some(tz) = tz {
+        command.env("TZ", tz);
+    }
+    if let Some(locale) = locale {
+        command.env_remove("LC_ALL").env("LC_TIME", locale);
+    }
+
+    let output = command
+        .output()
+        .map_err(|e| BlockError::CommandFailed(format!("Failed to run date: {}", e)))?;
+    if !output.status.success() {
+        return Err(BlockError::CommandFailed(format!(
+            "date exited with status: {}",
+            output.status
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
 impl Block for DateTime {
-    fn content(&mut self) -> Result<String, BlockError> {
-        let now = Local::now();
-        let time_str = now.format(&self.time_format).to_string();
-        Ok(self.format_template.replace("{}", &time_str))
+    fn content(&mut self) -> Result<BlockOutput, BlockError> {
+        let zone = self.zones.get(self.current);
+        let tz = zone.and_then(|zone| zone.tz.as_deref());
+        let time_str = match (tz, self.locale.as_deref()) {
+            (None, None) => format_local(&self.time_format)?,
+            (tz, locale) => format_with_date(&self.time_format, tz, locale)?,
+        };
+        let label = zone.map(|zone| zone.label.as_str()).unwrap_or_default();
+        Ok(self
+            .format_template
+            .replace("{zone}", label)
+            .replace("{}", &time_str)
+            .into())
     }
 
     fn interval(&self) -> Duration {
@@ -35,4 +111,19 @@ impl Block for DateTime {
     fn color(&self) -> u32 {
         self.color
     }
+
+    /// Shows the next zone on a left click or scroll up, the previous one
+    /// on a right click or scroll down, and the first on a middle click.
+    fn click(&mut self, button: u8) -> bool {
+        let count = self.zones.len();
+        if count < 2 {
+            return false;
+        }
+        self.current = match button {
+            2 => 0,
+            3 | 5 => (self.current + count - 1) % count,
+            _ => (self.current + 1) % count,
+        };
+        true
+    }
 }
@@ -0,0 +1,152 @@
+use super::shell::run_command;
+use super::{Block, BlockOutput};
+use crate::errors::BlockError;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How long nvidia-smi may take to answer.
+const NVIDIA_SMI_TIMEOUT: Duration = Duration::from_secs(3);
+
+const NVIDIA_QUERY: &str = "nvidia-smi --query-gpu=utilization.gpu,memory.used,memory.total \
+                            --format=csv,noheader,nounits --id=\"$OXWM_GPU\"";
+
+const BYTES_PER_GIB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+/// Where a GPU's counters are read from.
+enum Device {
+    /// An amdgpu card's directory in sysfs, e.g. `/sys/class/drm/card0/device`.
+    Amdgpu(PathBuf),
+    /// An NVIDIA GPU index as nvidia-smi counts them, which asks NVML.
+    Nvidia(String),
+}
+
+struct Usage {
+    /// Busy percentage.
+    utilization: f64,
+    vram_used: f64,
+    vram_total: f64,
+}
+
+/// Shows how busy a GPU is and how much of its memory is in use, from the
+/// amdgpu driver's sysfs counters or from NVIDIA's NVML through nvidia-smi.
+pub struct Gpu {
+    format: String,
+    device: Option<String>,
+    interval: Duration,
+    color: u32,
+}
+
+impl Gpu {
+    pub fn new(format: &str, device: Option<String>, interval_secs: u64, color: u32) -> Self {
+        Self {
+            format: format.to_string(),
+            device,
+            interval: Duration::from_secs(interval_secs),
+            color,
+        }
+    }
+
+    /// The configured device: "card1" is a DRM card, "nvidia1" or "1" an
+    /// NVIDIA GPU. Without one, the first amdgpu card, else the first NVIDIA
+    /// GPU.
+    fn device(&self) -> Device {
+        match self.device.as_deref() {
+            Some(card) if card.starts_with("card") => {
+                Device::Amdgpu(PathBuf::from("/sys/class/drm").join(card).join("device"))
+            }
+            Some(device) => Device::Nvidia(device.trim_start_matches("nvidia").to_string()),
+            None => first_amdgpu().map_or_else(|| Device::Nvidia("0".to_string()), Device::Amdgpu),
+        }
+    }
+}
+
+/// The first DRM card with amdgpu's busy counter.
+fn first_amdgpu() -> Option<PathBuf> {
+    let mut cards: Vec<PathBuf> = fs::read_dir("/sys/class/drm")
+        .ok()?
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.starts_with("card") && !name.contains('-')
+        })
+        .map(|entry| entry.path().join("device"))
+        .filter(|device| device.join("gpu_busy_percent").exists())
+        .collect();
+    cards.sort();
+    cards.into_iter().next()
+}
+
+fn read_number(device: &Path, file: &str) -> Result<f64, BlockError> {
+    let text = fs::read_to_string(device.join(file))?;
+    text.trim()
+        .parse()
+        .map_err(|_| BlockError::InvalidData(format!("unexpected {}: {}", file, text.trim())))
+}
+
+fn amdgpu_usage(device: &Path) -> Result<Usage, BlockError> {
+    Ok(Usage {
+        utilization: read_number(device, "gpu_busy_percent")?,
+        vram_used: read_number(device, "mem_info_vram_used")? / BYTES_PER_GIB,
+        vram_total: read_number(device, "mem_info_vram_total")? / BYTES_PER_GIB,
+    })
+}
+
+fn nvidia_usage(index: &str) -> Result<Usage, BlockError> {
+    let output = run_command(
+        NVIDIA_QUERY,
+        &[("OXWM_GPU", index.to_string())],
+        Some(NVIDIA_SMI_TIMEOUT),
+    )?;
+    let values: Vec<f64> = output
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|value| value.trim().parse().ok())
+        .collect();
+    let [utilization, used_mib, total_mib] = values[..] else {
+        return Err(BlockError::InvalidData(format!(
+            "unexpected nvidia-smi output: {}",
+            output.trim()
+        )));
+    };
+
+    Ok(Usage {
+        utilization,
+        vram_used: used_mib / 1024.0,
+        vram_total: total_mib / 1024.0,
+    })
+}
+
+impl Block for Gpu {
+    fn content(&mut self) -> Result<BlockOutput, BlockError> {
+        let usage = match self.device() {
+            Device::Amdgpu(device) => amdgpu_usage(&device)?,
+            Device::Nvidia(index) => nvidia_usage(&index)?,
+        };
+        let vram_percent = if usage.vram_total > 0.0 {
+            usage.vram_used / usage.vram_total * 100.0
+        } else {
+            0.0
+        };
+
+        let result = self
+            .format
+            .replace("{used}", &format!("{:.1}", usage.vram_used))
+            .replace("{total}", &format!("{:.1}", usage.vram_total))
+            .replace("{percent}", &format!("{:.0}", vram_percent))
+            .replace("{}", &format!("{:.0}", usage.utilization));
+
+        Ok(result.into())
+    }
+
+    fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    fn color(&self) -> u32 {
+        self.color
+    }
+}
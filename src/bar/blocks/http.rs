@@ -0,0 +1,141 @@
+use super::shell::run_command;
+use super::{Block, BlockOutput, HttpSource};
+use crate::errors::BlockError;
+use crate::json::Json;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Fetches `$OXWM_URL`. curl gives up after `$OXWM_TIMEOUT` seconds and is
+/// killed if it still runs at twice that.
+const FETCH_COMMAND: &str = "curl -fsSL --max-time \"$OXWM_TIMEOUT\" \"$OXWM_URL\"";
+
+/// Shows values picked out of JSON documents fetched over HTTP, such as
+/// prices from an exchange's API. Keeps showing the last values while a
+/// fetch fails.
+pub struct HttpBlock {
+    format: String,
+    sources: Vec<HttpSource>,
+    timeout: Duration,
+    decimals: Option<u8>,
+    interval: Duration,
+    color: u32,
+    last_output: Option<BlockOutput>,
+    last_error: Option<String>,
+}
+
+impl HttpBlock {
+    pub fn new(
+        format: &str,
+        sources: Vec<HttpSource>,
+        timeout_secs: u64,
+        decimals: Option<u8>,
+        interval_secs: u64,
+        color: u32,
+    ) -> Self {
+        Self {
+            format: format.to_string(),
+            sources,
+            timeout: Duration::from_secs(timeout_secs),
+            decimals,
+            interval: Duration::from_secs(interval_secs),
+            color,
+            last_output: None,
+            last_error: None,
+        }
+    }
+
+    fn fetch(&self) -> Result<String, BlockError> {
+        // Sources reading the same document fetch it once.
+        let mut documents: HashMap<&str, Json> = HashMap::new();
+        let mut text = self.format.clone();
+
+        for source in &self.sources {
+            if !documents.contains_key(source.url.as_str()) {
+                let envs = [
+                    ("OXWM_URL", source.url.clone()),
+                    ("OXWM_TIMEOUT", self.timeout.as_secs().max(1).to_string()),
+                ];
+                let body = run_command(FETCH_COMMAND, &envs, Some(self.timeout * 2))?;
+                let document = Json::parse(&body).map_err(|e| {
+                    BlockError::InvalidData(format!("{} did not return JSON: {}", source.url, e))
+                })?;
+                documents.insert(&source.url, document);
+            }
+
+            let value = documents
+                .get(source.url.as_str())
+                .and_then(|document| select(document, &source.path))
+                .ok_or_else(|| {
+                    BlockError::InvalidData(format!(
+                        "'{}' matches nothing in {}",
+                        source.path, source.url
+                    ))
+                })?;
+            let placeholder = if source.name.is_empty() {
+                "{}".to_string()
+            } else {
+                format!("{{{}}}", source.name)
+            };
+            text = text.replace(&placeholder, &self.display(value));
+        }
+        Ok(text)
+    }
+
+    fn display(&self, value: &Json) -> String {
+        match (value, self.decimals) {
+            (Json::String(text), _) => text.clone(),
+            (Json::Number(number), Some(decimals)) => {
+                format!("{:.*}", decimals as usize, number)
+            }
+            _ => value.to_string(),
+        }
+    }
+}
+
+/// Follows a path such as `$.data[0].price` or `bitcoin.usd` into a
+/// document: keys separated by dots, array indexes in brackets or as
+/// plain numbers.
+fn select<'a>(document: &'a Json, path: &str) -> Option<&'a Json> {
+    let path = path.strip_prefix('$').unwrap_or(path);
+    let mut value = document;
+    for part in path.replace('[', ".").replace(']', "").split('.') {
+        if part.is_empty() {
+            continue;
+        }
+        let key = part.trim_matches(|c| c == '"' || c == '\'');
+        value = match (value, key.parse::<usize>()) {
+            (Json::Array(items), Ok(index)) => items.get(index)?,
+            _ => value.get(key)?,
+        };
+    }
+    Some(value)
+}
+
+impl Block for HttpBlock {
+    fn content(&mut self) -> Result<BlockOutput, BlockError> {
+        match self.fetch() {
+            Ok(text) => {
+                self.last_error = None;
+                self.last_output = Some(text.into());
+            }
+            Err(e) => {
+                let message = e.to_string();
+                if self.last_error.as_ref() != Some(&message) {
+                    eprintln!("HTTP block: {}", message);
+                    self.last_error = Some(message);
+                }
+            }
+        }
+        self.last_output
+            .clone()
+            .ok_or_else(|| BlockError::CommandFailed("nothing fetched yet".to_string()))
+    }
+
+    fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    fn color(&self) -> u32 {
+        self.color
+    }
+}
@@ -0,0 +1,189 @@
+use super::{BlockConfig, BlockOutput, BlockUpdate, BlockWorker, FocusContext};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::Instant;
+
+/// The blocks of one bar configuration, shared by every bar showing them.
+struct BlockSet {
+    configs: Vec<BlockConfig>,
+    generation: u64,
+    workers: Vec<BlockWorker>,
+}
+
+/// Runs each distinct list of blocks once per interval, however many bars
+/// show it, and hands the output to the bars.
+pub struct BlockManager {
+    sets: Vec<BlockSet>,
+    results: Receiver<BlockUpdate>,
+    sender: Sender<BlockUpdate>,
+    /// Results from blocks that have since been stopped carry an older
+    /// generation and are dropped.
+    next_generation: u64,
+    /// Focused window last passed to context blocks.
+    focus_context: Option<FocusContext>,
+}
+
+impl Default for BlockManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BlockManager {
+    pub fn new() -> Self {
+        let (sender, results) = mpsc::channel();
+        Self {
+            sets: Vec::new(),
+            results,
+            sender,
+            next_generation: 0,
+            focus_context: None,
+        }
+    }
+
+    /// Stops every block, before the bars are given their blocks again.
+    pub fn clear(&mut self) {
+        self.sets.clear();
+        self.focus_context = None;
+    }
+
+    /// Returns the set running `blocks`, starting one unless a set with the
+    /// same blocks already runs.
+    pub fn acquire(&mut self, blocks: &[BlockConfig]) -> usize {
+        if let Some(index) = self
+            .sets
+            .iter()
+            .position(|set| same_blocks(&set.configs, blocks))
+        {
+            return index;
+        }
+
+        let generation = self.next_generation;
+        self.next_generation += 1;
+        let workers = blocks
+            .iter()
+            .enumerate()
+            .map(|(index, block_config)| {
+                BlockWorker::spawn(
+                    block_config.to_block(),
+                    index,
+                    generation,
+                    self.sender.clone(),
+                )
+            })
+            .collect();
+        crate::signal::listen_block_signals(blocks.iter().filter_map(|block| block.signal));
+        self.sets.push(BlockSet {
+            configs: blocks.to_vec(),
+            generation,
+            workers,
+        });
+        self.sets.len() - 1
+    }
+
+    /// Takes in output the blocks finished producing and asks blocks whose
+    /// interval passed, or whose signal arrived, for more; true if any output
+    /// changed. Blocks marked `focused_only` only run in the focused
+    /// monitor's sets. Never waits on a block.
+    pub fn update(&mut self, focused_sets: &[usize]) -> bool {
+        let mut changed = false;
+        for update in self.results.try_iter() {
+            if let Some(set) = self
+                .sets
+                .iter_mut()
+                .find(|set| set.generation == update.generation)
+                && let Some(worker) = set.workers.get_mut(update.index)
+            {
+                changed |= worker.receive(update);
+            }
+        }
+
+        let now = Instant::now();
+        let signals = crate::signal::take_block_signals();
+        for (set_index, set) in self.sets.iter_mut().enumerate() {
+            for (worker, block_config) in set.workers.iter_mut().zip(&set.configs) {
+                let skipped = block_config.focused_only && !focused_sets.contains(&set_index);
+                let signaled = block_config.signal.is_some_and(|n| signals & (1 << n) != 0);
+                if skipped {
+                    continue;
+                }
+                if signaled {
+                    worker.signal();
+                } else if worker.is_due(now) {
+                    worker.refresh();
+                }
+            }
+        }
+        changed
+    }
+
+    /// Last output of each block in a set; `None` hides a block.
+    pub fn outputs(&self, set: usize) -> Vec<Option<BlockOutput>> {
+        self.sets
+            .get(set)
+            .map(|set| {
+                set.workers
+                    .iter()
+                    .map(|worker| worker.output().cloned())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Re-evaluates context blocks when the focused window or its title
+    /// changed.
+    pub fn set_focus_context(&mut self, context: &FocusContext) {
+        if self.focus_context.as_ref() == Some(context) {
+            return;
+        }
+        self.focus_context = Some(context.clone());
+
+        for set in &self.sets {
+            for worker in &set.workers {
+                worker.set_focus(context);
+            }
+        }
+    }
+
+    /// Refreshes blocks showing power supply state right away instead of
+    /// waiting for their interval.
+    pub fn refresh_power_blocks(&mut self) {
+        for set in &mut self.sets {
+            for worker in &mut set.workers {
+                if worker.follows_power_supply() {
+                    worker.refresh();
+                }
+            }
+        }
+    }
+
+    pub fn has_context_blocks(&self) -> bool {
+        self.workers().any(BlockWorker::follows_focus)
+    }
+
+    pub fn has_power_blocks(&self) -> bool {
+        self.workers().any(BlockWorker::follows_power_supply)
+    }
+
+    pub fn click(&self, set: usize, index: usize, button: u8) {
+        if let Some(worker) = self.sets.get(set).and_then(|set| set.workers.get(index)) {
+            worker.click(button);
+        }
+    }
+
+    fn workers(&self) -> impl Iterator<Item = &BlockWorker> {
+        self.sets.iter().flat_map(|set| &set.workers)
+    }
+}
+
+/// Whether two lists of blocks produce the same output, so one set can serve
+/// both.
+fn same_blocks(a: &[BlockConfig], b: &[BlockConfig]) -> bool {
+    a.len() == b.len()
+        && a.iter().zip(b).all(|(a, b)| {
+            a.format == b.format
+                && a.command == b.command
+                && a.interval_secs == b.interval_secs
+                && a.focused_only == b.focused_only
+                && a.signal == b.signal
+        })
+}
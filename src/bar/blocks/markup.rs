@@ -0,0 +1,111 @@
+use super::{Segment, Shape};
+
+/// Splits text with status2d markup into segments:
+///
+/// - `^c#rrggbb^` colors the text after it, `^b#rrggbb^` its background
+///   (`#rgb` works too)
+/// - `^d^` goes back to the block's colors
+/// - `^r<x>,<y>,<w>,<h>^` draws a rectangle in the text color, `x` pixels
+///   right of where it appears and `y` pixels below the top of the bar,
+///   without taking any width
+/// - `^f<n>^` leaves `n` pixels empty
+///
+/// Anything else between carets is kept as text.
+pub(super) fn parse(text: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut current = Segment::default();
+    let mut rest = text;
+
+    while let Some(start) = rest.find('^') {
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('^') else {
+            break;
+        };
+        let Some(command) = parse_command(&after[..end]) else {
+            current.text.push_str(&rest[..start + 1]);
+            rest = after;
+            continue;
+        };
+
+        current.text.push_str(&rest[..start]);
+        rest = &after[end + 1..];
+
+        let (color, background) = (current.color, current.background);
+        if !current.text.is_empty() {
+            segments.push(std::mem::take(&mut current));
+        }
+        current.color = color;
+        current.background = background;
+
+        match command {
+            Command::Color(color) => current.color = Some(color),
+            Command::Background(background) => current.background = Some(background),
+            Command::Reset => {
+                current.color = None;
+                current.background = None;
+            }
+            Command::Shape(shape) => segments.push(Segment {
+                shape: Some(shape),
+                ..current.clone()
+            }),
+        }
+    }
+
+    current.text.push_str(rest);
+    if !current.text.is_empty() || segments.is_empty() {
+        segments.push(current);
+    }
+    segments
+}
+
+enum Command {
+    Color(u32),
+    Background(u32),
+    Reset,
+    Shape(Shape),
+}
+
+fn parse_command(command: &str) -> Option<Command> {
+    let mut chars = command.chars();
+    let kind = chars.next()?;
+    let argument = chars.as_str();
+    match kind {
+        'c' => parse_color(argument).map(Command::Color),
+        'b' => parse_color(argument).map(Command::Background),
+        'd' if argument.is_empty() => Some(Command::Reset),
+        'r' => {
+            let values: Vec<i32> = argument
+                .split(',')
+                .map(|value| value.trim().parse().ok())
+                .collect::<Option<_>>()?;
+            let [x, y, width, height] = values[..] else {
+                return None;
+            };
+            Some(Command::Shape(Shape::Rect {
+                x: x as i16,
+                y: y as i16,
+                width: u16::try_from(width).ok()?,
+                height: u16::try_from(height).ok()?,
+            }))
+        }
+        'f' => argument
+            .parse()
+            .ok()
+            .map(|width| Command::Shape(Shape::Gap(width))),
+        _ => None,
+    }
+}
+
+/// Reads `#rrggbb` or `#rgb`.
+fn parse_color(text: &str) -> Option<u32> {
+    let hex = text.strip_prefix('#')?;
+    match hex.len() {
+        6 => u32::from_str_radix(hex, 16).ok(),
+        3 => {
+            let short = u32::from_str_radix(hex, 16).ok()?;
+            let (r, g, b) = ((short >> 8) & 0xf, (short >> 4) & 0xf, short & 0xf);
+            Some(((r * 0x11) << 16) | ((g * 0x11) << 8) | (b * 0x11))
+        }
+        _ => None,
+    }
+}
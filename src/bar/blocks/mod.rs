@@ -2,19 +2,168 @@ use crate::errors::BlockError;
 use std::time::Duration;
 
 mod battery;
+mod containers;
+mod context;
+mod countdown;
+mod cpu;
 mod datetime;
+mod gpu;
+mod http;
+mod manager;
+mod markup;
+mod network;
+mod peripherals;
+mod privacy;
 mod ram;
+mod root_name;
 mod shell;
+mod systemd;
+mod temperature;
+mod updates;
+mod volume;
+mod vpn;
+mod worker;
 
 use battery::Battery;
+use containers::Containers;
+use context::ContextBlock;
+use countdown::Countdown;
+use cpu::Cpu;
 use datetime::DateTime;
+use gpu::Gpu;
+use http::HttpBlock;
+pub use manager::BlockManager;
+use network::Network;
+use peripherals::Peripherals;
+use privacy::Privacy;
 use ram::Ram;
+use root_name::RootName;
 use shell::ShellBlock;
+use systemd::SystemdBlock;
+use temperature::Temperature;
+use updates::Updates;
+use volume::Volume;
+use vpn::Vpn;
+use worker::{BlockNotifier, BlockUpdate, BlockWorker};
 
-pub trait Block {
-    fn content(&mut self) -> Result<String, BlockError>;
+/// Shown in a shell block whose command failed, unless configured otherwise.
+pub const DEFAULT_BLOCK_ERROR: &str = "!";
+
+/// A status bar block. Blocks run on worker threads, see `BlockWorker`.
+pub trait Block: Send {
+    fn content(&mut self) -> Result<BlockOutput, BlockError>;
     fn interval(&self) -> Duration;
     fn color(&self) -> u32;
+
+    /// Whether the block shows power supply state and should refresh as
+    /// soon as it changes.
+    fn follows_power_supply(&self) -> bool {
+        false
+    }
+
+    /// Whether the block shows details of the focused window.
+    fn follows_focus(&self) -> bool {
+        false
+    }
+
+    /// Re-evaluates a block showing details of the focused window; true if
+    /// its text changed.
+    fn set_focus(&mut self, _context: &FocusContext) -> bool {
+        false
+    }
+
+    /// Called when the block is clicked with `button` (1 left, 2 middle,
+    /// 3 right, 4 and 5 scroll); true if its text changed.
+    fn click(&mut self, _button: u8) -> bool {
+        false
+    }
+
+    /// Called once on the worker thread before the first refresh. Blocks
+    /// that learn of changes by themselves keep `notifier` to update right
+    /// away instead of on their interval.
+    fn subscribe(&mut self, _notifier: BlockNotifier) {}
+
+    /// Called when the block's signal arrived, right before its content is
+    /// asked for. Blocks that keep output between intervals drop it here.
+    fn signal(&mut self) {}
+}
+
+/// What a block shows: pieces of text in their own colors, drawn one
+/// after another.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BlockOutput {
+    pub segments: Vec<Segment>,
+    pub urgency: Urgency,
+    /// Width in pixels the block takes at least, on top of the configured
+    /// `min_width`.
+    pub min_width: Option<u16>,
+}
+
+impl BlockOutput {
+    /// All segments joined, without colors.
+    pub fn text(&self) -> String {
+        self.segments
+            .iter()
+            .map(|segment| segment.text.as_str())
+            .collect()
+    }
+}
+
+/// Plain text, with any status2d color markup split into segments.
+impl From<String> for BlockOutput {
+    fn from(text: String) -> Self {
+        Self {
+            segments: markup::parse(&text),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Segment {
+    pub text: String,
+    /// Falls back to the block's color.
+    pub color: Option<u32>,
+    /// Filled behind the segment.
+    pub background: Option<u32>,
+    /// Drawn instead of the text.
+    pub shape: Option<Shape>,
+}
+
+/// What status2d markup draws besides text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shape {
+    /// Rectangle in the segment's color, offset from where the segment
+    /// starts and from the top of the bar; takes no width.
+    Rect {
+        x: i16,
+        y: i16,
+        width: u16,
+        height: u16,
+    },
+    /// Empty space of this many pixels.
+    Gap(u16),
+}
+
+/// How much a block wants attention. Above normal it is drawn with the
+/// urgent scheme.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Urgency {
+    #[default]
+    Normal,
+    /// Uncolored segments in the urgent scheme's foreground.
+    Warning,
+    /// Also the urgent scheme's background and underline.
+    Critical,
+}
+
+/// The focused window, as seen by context blocks.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FocusContext {
+    pub class: String,
+    pub instance: String,
+    pub title: String,
+    pub pid: Option<u32>,
 }
 
 #[derive(Debug, Clone)]
@@ -24,34 +173,319 @@ pub struct BlockConfig {
     pub interval_secs: u64,
     pub color: u32,
     pub underline: bool,
+    pub background: Option<u32>,
+    pub gradient: Option<u32>,
+    /// Terminal program opened as a floating popup when the block is clicked.
+    pub popup: Option<String>,
+    /// Hidden while presentation mode is on.
+    pub sensitive: bool,
+    /// Only shown, and only run, on the focused monitor's bar.
+    pub focused_only: bool,
+    pub min_width: Option<MinWidth>,
+    pub align: BlockAlign,
+    pub clicks: BlockClicks,
+    /// Refreshed right away on `SIGRTMIN+signal`, as with dwmblocks.
+    pub signal: Option<u8>,
 }
 
-#[derive(Debug, Clone)]
+/// Least width of a block, so changing text doesn't shift the blocks to
+/// its left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MinWidth {
+    Pixels(u16),
+    /// This many digit widths in the bar font.
+    Chars(u16),
+}
+
+/// A timezone shown by a clock block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClockZone {
+    /// Replaces `{zone}` in the block's format.
+    pub label: String,
+    /// IANA name such as "America/New_York"; `None` is local time.
+    pub tz: Option<String>,
+}
+
+/// A value an HTTP block picks out of a JSON document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpSource {
+    /// Replaces `{name}` in the block's format; an empty name replaces `{}`.
+    pub name: String,
+    pub url: String,
+    /// Where the value is in the document, e.g. `$.data[0].price`.
+    pub path: String,
+}
+
+/// Levels at which a usage or temperature block draws attention.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Thresholds {
+    /// At or above this the block turns to a warning.
+    pub warning: u32,
+    /// At or above this the block turns critical.
+    pub critical: u32,
+    /// Color of the text at or above `warning`; the urgent scheme when
+    /// `None`.
+    pub warning_color: Option<u32>,
+}
+
+impl Thresholds {
+    /// Marks `output` by how `value` compares to the thresholds.
+    pub fn apply(&self, output: &mut BlockOutput, value: f64) {
+        let warning = value >= self.warning as f64;
+        if warning && let Some(warning_color) = self.warning_color {
+            for segment in &mut output.segments {
+                segment.color = Some(warning_color);
+            }
+        }
+        if value >= self.critical as f64 {
+            output.urgency = Urgency::Critical;
+        } else if warning && self.warning_color.is_none() {
+            output.urgency = Urgency::Warning;
+        }
+    }
+}
+
+/// How a battery block warns about a discharging battery running low.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatteryAlerts {
+    /// Charge in percent at or below which the block turns to a warning.
+    pub warning: u32,
+    /// Charge in percent at or below which the block turns critical.
+    pub critical: u32,
+    /// Color of the text at or below `warning`; the urgent scheme when
+    /// `None`.
+    pub low_color: Option<u32>,
+    /// Run once each time the charge falls to `critical`, with the charge in
+    /// `$OXWM_BATTERY`.
+    pub on_critical: Option<String>,
+}
+
+impl Default for BatteryAlerts {
+    fn default() -> Self {
+        Self {
+            warning: 20,
+            critical: 10,
+            low_color: None,
+            on_critical: None,
+        }
+    }
+}
+
+/// What a peripherals block shows for each kind of device.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeripheralIcons {
+    pub mouse: String,
+    pub keyboard: String,
+    /// Headsets and headphones.
+    pub headset: String,
+    pub gamepad: String,
+    pub other: String,
+}
+
+impl Default for PeripheralIcons {
+    fn default() -> Self {
+        Self {
+            mouse: "M".to_string(),
+            keyboard: "K".to_string(),
+            headset: "H".to_string(),
+            gamepad: "G".to_string(),
+            other: "B".to_string(),
+        }
+    }
+}
+
+/// The VPN a VPN block watches, and how clicking the block toggles it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VpnTarget {
+    /// Network interface such as `wg0`; any WireGuard or tun interface
+    /// counts when neither this nor `connection` is set.
+    pub interface: Option<String>,
+    /// NetworkManager connection, brought up or down on click.
+    pub connection: Option<String>,
+    /// Run on click instead, with `$VPN_STATE` set to "up" or "down".
+    pub toggle: Option<String>,
+}
+
+/// Where text narrower than the block's minimum width sits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BlockAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+/// Commands spawned when a block is clicked, with the button number in
+/// `$BLOCK_BUTTON` as in dwmblocks.
+#[derive(Debug, Clone, Default)]
+pub struct BlockClicks {
+    pub left: Option<String>,
+    pub middle: Option<String>,
+    pub right: Option<String>,
+    pub scroll_up: Option<String>,
+    pub scroll_down: Option<String>,
+}
+
+impl BlockClicks {
+    pub fn command(&self, button: u8) -> Option<&str> {
+        match button {
+            1 => self.left.as_deref(),
+            2 => self.middle.as_deref(),
+            3 => self.right.as_deref(),
+            4 => self.scroll_up.as_deref(),
+            5 => self.scroll_down.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum BlockCommand {
-    Shell(String),
-    DateTime(String),
+    Shell {
+        command: String,
+        /// Seconds the command may run before it is killed.
+        timeout_secs: Option<u64>,
+        /// Shown in place of the output when the command fails or times out.
+        error_text: String,
+    },
+    DateTime {
+        /// strftime format of the time.
+        date_format: String,
+        /// Cycled through by clicking the block; empty shows local time.
+        zones: Vec<ClockZone>,
+        /// Locale for month and day names, e.g. "de_DE.UTF-8".
+        locale: Option<String>,
+    },
     Battery {
         format_charging: String,
         format_discharging: String,
         format_full: String,
         battery_name: Option<String>,
+        alerts: BatteryAlerts,
+    },
+    Ram {
+        /// Percent of memory in use.
+        thresholds: Thresholds,
+    },
+    Cpu {
+        /// Percent of all cores busy.
+        thresholds: Thresholds,
+    },
+    Temperature {
+        /// Thermal zone number or type, e.g. "x86_pkg_temp"; the CPU's zone
+        /// when `None`.
+        zone: Option<String>,
+        /// Degrees Celsius.
+        thresholds: Thresholds,
+    },
+    Gpu {
+        /// "card0" for an amdgpu card, "nvidia0" or "0" for an NVIDIA GPU;
+        /// the first GPU found when `None`.
+        device: Option<String>,
     },
-    Ram,
     Static(String),
+    Countdown {
+        /// An .ics calendar, or lines of `YYYY-MM-DD HH:MM Title`.
+        file: String,
+        /// Minutes before the event the block turns to a warning.
+        warning_minutes: u64,
+        /// Minutes before the event the block turns critical.
+        critical_minutes: u64,
+    },
+    Privacy {
+        /// Shown while a microphone records.
+        microphone: String,
+        /// Shown while a webcam is open.
+        camera: String,
+    },
+    Http {
+        sources: Vec<HttpSource>,
+        /// Seconds a fetch may take.
+        timeout_secs: u64,
+        /// Digits after the decimal point of numbers; `None` shows them as
+        /// sent.
+        decimals: Option<u8>,
+    },
+    Vpn {
+        target: VpnTarget,
+        /// Shown while the VPN is down.
+        format_down: String,
+        /// Color while the VPN is down; the urgent scheme when `None`.
+        down_color: Option<u32>,
+    },
+    Network {
+        /// Interface such as `wlan0`; the one of the default route when
+        /// `None`.
+        interface: Option<String>,
+        /// Used instead of the format for links that aren't wireless.
+        format_ethernet: String,
+        /// Shown while there is no link; empty hides the block.
+        format_disconnected: String,
+    },
+    Volume {
+        /// Shown while the sink is muted.
+        format_muted: String,
+        /// Percent one scroll step changes the volume by.
+        step: u32,
+        /// Percent scrolling up stops at.
+        max_volume: u32,
+    },
+    Updates {
+        /// "pacman", "apt" or "dnf"; the first one installed when `None`.
+        backend: Option<String>,
+        /// Used instead of a backend, printing one line per pending update.
+        command: Option<String>,
+    },
+    Peripherals {
+        icons: PeripheralIcons,
+        /// Charge in percent at or below which the block turns to a warning.
+        warning: u32,
+    },
+    Containers {
+        /// Docker or Podman API socket; found by itself when `None`.
+        socket: Option<String>,
+    },
+    Systemd {
+        /// System units to watch; with no units on either bus, every failed
+        /// unit counts.
+        system_units: Vec<String>,
+        /// Units of the user's service manager.
+        user_units: Vec<String>,
+    },
+    Context {
+        command: String,
+        /// Classes the block shows for; empty shows it for every window.
+        classes: Vec<String>,
+        timeout_secs: Option<u64>,
+    },
+    /// The root window's name, for dwm status scripts.
+    RootName,
 }
 
 impl BlockConfig {
     pub fn to_block(&self) -> Box<dyn Block> {
         match &self.command {
-            BlockCommand::Shell(cmd) => Box::new(ShellBlock::new(
+            BlockCommand::Shell {
+                command,
+                timeout_secs,
+                error_text,
+            } => Box::new(ShellBlock::new(
                 &self.format,
-                cmd,
+                command,
                 self.interval_secs,
+                *timeout_secs,
+                error_text,
                 self.color,
             )),
-            BlockCommand::DateTime(fmt) => Box::new(DateTime::new(
+            BlockCommand::DateTime {
+                date_format,
+                zones,
+                locale,
+            } => Box::new(DateTime::new(
                 &self.format,
-                fmt,
+                date_format,
+                zones.clone(),
+                locale.clone(),
                 self.interval_secs,
                 self.color,
             )),
@@ -60,6 +494,7 @@ impl BlockConfig {
                 format_discharging,
                 format_full,
                 battery_name,
+                alerts,
             } => Box::new(Battery::new(
                 format_charging,
                 format_discharging,
@@ -67,8 +502,142 @@ impl BlockConfig {
                 self.interval_secs,
                 self.color,
                 battery_name.clone(),
+                alerts.clone(),
+            )),
+            BlockCommand::Context {
+                command,
+                classes,
+                timeout_secs,
+            } => Box::new(ContextBlock::new(
+                &self.format,
+                command,
+                classes,
+                *timeout_secs,
+                self.color,
+            )),
+            BlockCommand::Countdown {
+                file,
+                warning_minutes,
+                critical_minutes,
+            } => Box::new(Countdown::new(
+                &self.format,
+                file,
+                self.interval_secs,
+                *warning_minutes,
+                *critical_minutes,
+                self.color,
+            )),
+            BlockCommand::Privacy { microphone, camera } => Box::new(Privacy::new(
+                &self.format,
+                microphone,
+                camera,
+                self.interval_secs,
+                self.color,
+            )),
+            BlockCommand::Gpu { device } => Box::new(Gpu::new(
+                &self.format,
+                device.clone(),
+                self.interval_secs,
+                self.color,
+            )),
+            BlockCommand::Network {
+                interface,
+                format_ethernet,
+                format_disconnected,
+            } => Box::new(Network::new(
+                &self.format,
+                format_ethernet,
+                format_disconnected,
+                interface.clone(),
+                self.interval_secs,
+                self.color,
+            )),
+            BlockCommand::Volume {
+                format_muted,
+                step,
+                max_volume,
+            } => Box::new(Volume::new(
+                &self.format,
+                format_muted,
+                *step,
+                *max_volume,
+                self.interval_secs,
+                self.color,
+            )),
+            BlockCommand::Updates { backend, command } => Box::new(Updates::new(
+                &self.format,
+                backend.clone(),
+                command.clone(),
+                self.interval_secs,
+                self.color,
+            )),
+            BlockCommand::Peripherals { icons, warning } => Box::new(Peripherals::new(
+                &self.format,
+                icons.clone(),
+                *warning,
+                self.interval_secs,
+                self.color,
+            )),
+            BlockCommand::Vpn {
+                target,
+                format_down,
+                down_color,
+            } => Box::new(Vpn::new(
+                &self.format,
+                format_down,
+                target.clone(),
+                *down_color,
+                self.interval_secs,
+                self.color,
+            )),
+            BlockCommand::Http {
+                sources,
+                timeout_secs,
+                decimals,
+            } => Box::new(HttpBlock::new(
+                &self.format,
+                sources.clone(),
+                *timeout_secs,
+                *decimals,
+                self.interval_secs,
+                self.color,
+            )),
+            BlockCommand::Containers { socket } => Box::new(Containers::new(
+                &self.format,
+                socket.as_deref(),
+                self.interval_secs,
+                self.color,
+            )),
+            BlockCommand::Systemd {
+                system_units,
+                user_units,
+            } => Box::new(SystemdBlock::new(
+                &self.format,
+                system_units,
+                user_units,
+                self.interval_secs,
+                self.color,
+            )),
+            BlockCommand::Ram { thresholds } => Box::new(Ram::new(
+                &self.format,
+                thresholds.clone(),
+                self.interval_secs,
+                self.color,
+            )),
+            BlockCommand::Cpu { thresholds } => Box::new(Cpu::new(
+                &self.format,
+                thresholds.clone(),
+                self.interval_secs,
+                self.color,
+            )),
+            BlockCommand::Temperature { zone, thresholds } => Box::new(Temperature::new(
+                &self.format,
+                zone.clone(),
+                thresholds.clone(),
+                self.interval_secs,
+                self.color,
             )),
-            BlockCommand::Ram => Box::new(Ram::new(&self.format, self.interval_secs, self.color)),
+            BlockCommand::RootName => Box::new(RootName::new(self.interval_secs, self.color)),
             BlockCommand::Static(text) => Box::new(StaticBlock::new(
                 &format!("{}{}", self.format, text),
                 self.color,
@@ -92,8 +661,8 @@ impl StaticBlock {
 }
 
 impl Block for StaticBlock {
-    fn content(&mut self) -> Result<String, BlockError> {
-        Ok(self.text.clone())
+    fn content(&mut self) -> Result<BlockOutput, BlockError> {
+        Ok(self.text.clone().into())
     }
 
     fn interval(&self) -> Duration {
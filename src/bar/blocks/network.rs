@@ -0,0 +1,360 @@
+use super::{Block, BlockOutput};
+use crate::errors::BlockError;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Shows the state of a network interface: SSID and signal strength for
+/// Wi-Fi, and download and upload rates for any link. Counters come from
+/// sysfs and the SSID from nl80211, so nothing is spawned per update.
+pub struct Network {
+    format: String,
+    format_ethernet: String,
+    format_disconnected: String,
+    interface: Option<String>,
+    interval: Duration,
+    color: u32,
+    /// Byte counters of the last update, to compute rates from.
+    last: Option<Sample>,
+    nl80211: Option<nl80211::Nl80211>,
+}
+
+struct Sample {
+    interface: String,
+    received: u64,
+    sent: u64,
+    at: Instant,
+}
+
+impl Network {
+    pub fn new(
+        format: &str,
+        format_ethernet: &str,
+        format_disconnected: &str,
+        interface: Option<String>,
+        interval_secs: u64,
+        color: u32,
+    ) -> Self {
+        Self {
+            format: format.to_string(),
+            format_ethernet: format_ethernet.to_string(),
+            format_disconnected: format_disconnected.to_string(),
+            interface,
+            interval: Duration::from_secs(interval_secs),
+            color,
+            last: None,
+            nl80211: None,
+        }
+    }
+
+    /// Download and upload rate in bytes per second since the last update.
+    fn rates(&mut self, interface: &str) -> (f64, f64) {
+        let sample = Sample {
+            interface: interface.to_string(),
+            received: read_counter(interface, "rx_bytes"),
+            sent: read_counter(interface, "tx_bytes"),
+            at: Instant::now(),
+        };
+        let rates = match &self.last {
+            Some(last) if last.interface == interface => {
+                let seconds = sample.at.duration_since(last.at).as_secs_f64();
+                if seconds > 0.0 {
+                    (
+                        sample.received.saturating_sub(last.received) as f64 / seconds,
+                        sample.sent.saturating_sub(last.sent) as f64 / seconds,
+                    )
+                } else {
+                    (0.0, 0.0)
+                }
+            }
+            _ => (0.0, 0.0),
+        };
+        self.last = Some(sample);
+        rates
+    }
+
+    /// The SSID the interface is associated with, asked from nl80211 over a
+    /// socket kept open between updates.
+    fn ssid(&mut self, interface: &str) -> Option<String> {
+        let index: u32 =
+            fs::read_to_string(Path::new("/sys/class/net").join(interface).join("ifindex"))
+                .ok()?
+                .trim()
+                .parse()
+                .ok()?;
+        let socket = match &mut self.nl80211 {
+            Some(socket) => socket,
+            None => match nl80211::Nl80211::open() {
+                Ok(socket) => self.nl80211.insert(socket),
+                Err(e) => {
+                    eprintln!("Failed to open nl80211 socket: {}", e);
+                    return None;
+                }
+            },
+        };
+        match socket.ssid(index) {
+            Ok(ssid) => ssid,
+            Err(_) => {
+                self.nl80211 = None;
+                None
+            }
+        }
+    }
+}
+
+/// The interface of the default route with the lowest metric.
+fn default_interface() -> Option<String> {
+    let routes = fs::read_to_string("/proc/net/route").ok()?;
+    routes
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let metric: u32 = fields.get(6)?.parse().ok()?;
+            (fields.get(1) == Some(&"00000000")).then(|| (metric, fields[0].to_string()))
+        })
+        .min()
+        .map(|(_, interface)| interface)
+}
+
+fn is_up(interface: &str) -> bool {
+    let dir = Path::new("/sys/class/net").join(interface);
+    match fs::read_to_string(dir.join("operstate"))
+        .as_deref()
+        .map(str::trim)
+    {
+        Ok("up") => true,
+        // Drivers that don't report an operational state still report carrier.
+        Ok("unknown") => {
+            fs::read_to_string(dir.join("carrier")).is_ok_and(|carrier| carrier.trim() == "1")
+        }
+        _ => false,
+    }
+}
+
+fn is_wireless(interface: &str) -> bool {
+    Path::new("/sys/class/net")
+        .join(interface)
+        .join("wireless")
+        .exists()
+}
+
+fn read_counter(interface: &str, counter: &str) -> u64 {
+    fs::read_to_string(
+        Path::new("/sys/class/net")
+            .join(interface)
+            .join("statistics")
+            .join(counter),
+    )
+    .ok()
+    .and_then(|value| value.trim().parse().ok())
+    .unwrap_or(0)
+}
+
+/// Signal strength in percent from /proc/net/wireless, where drivers report
+/// the level in dBm, mapped as NetworkManager does from -100 to -50 dBm.
+fn signal(interface: &str) -> Option<u32> {
+    let wireless = fs::read_to_string("/proc/net/wireless").ok()?;
+    let line = wireless
+        .lines()
+        .skip(2)
+        .find(|line| line.trim_start().split(':').next() == Some(interface))?;
+    let level: f64 = line
+        .split_whitespace()
+        .nth(3)?
+        .trim_end_matches('.')
+        .parse()
+        .ok()?;
+    let percent = if level < 0.0 {
+        2.0 * (level + 100.0)
+    } else {
+        level
+    };
+    Some(percent.clamp(0.0, 100.0) as u32)
+}
+
+/// A rate in bytes per second, shortened to at most four characters.
+fn format_rate(bytes_per_sec: f64) -> String {
+    const KIB: f64 = 1024.0;
+    const MIB: f64 = 1024.0 * 1024.0;
+    if bytes_per_sec < KIB {
+        format!("{:.0}B", bytes_per_sec)
+    } else if bytes_per_sec < MIB {
+        format!("{:.0}K", bytes_per_sec / KIB)
+    } else {
+        format!("{:.1}M", bytes_per_sec / MIB)
+    }
+}
+
+impl Block for Network {
+    fn content(&mut self) -> Result<BlockOutput, BlockError> {
+        let interface = self.interface.clone().or_else(default_interface);
+        let Some(interface) = interface.filter(|interface| is_up(interface)) else {
+            self.last = None;
+            let text = self
+                .format_disconnected
+                .replace("{}", self.interface.as_deref().unwrap_or_default());
+            if text.is_empty() {
+                return Err(BlockError::InvalidData("not connected".to_string()));
+            }
+            return Ok(text.into());
+        };
+
+        let (down, up) = self.rates(&interface);
+        let text = if is_wireless(&interface) {
+            let ssid = self.ssid(&interface).unwrap_or_default();
+            let signal = signal(&interface).unwrap_or(0);
+            self.format
+                .replace("{ssid}", &ssid)
+                .replace("{signal}", &signal.to_string())
+        } else {
+            self.format_ethernet.clone()
+        };
+
+        let text = text
+            .replace("{down}", &format_rate(down))
+            .replace("{up}", &format_rate(up))
+            .replace("{}", &interface);
+        Ok(text.into())
+    }
+
+    fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    fn color(&self) -> u32 {
+        self.color
+    }
+}
+
+/// Just enough generic netlink to ask nl80211 for an interface's SSID.
+mod nl80211 {
+    use std::ffi::c_int;
+    use std::fs::File;
+    use std::io::{self, ErrorKind, Read, Write};
+    use std::os::fd::FromRawFd;
+
+    const AF_NETLINK: c_int = 16;
+    const SOCK_RAW: c_int = 3;
+    const SOCK_CLOEXEC: c_int = 0o2000000;
+    const NETLINK_GENERIC: c_int = 16;
+
+    const NLM_F_REQUEST: u16 = 0x1;
+    const NLMSG_ERROR: u16 = 0x2;
+    /// `struct nlmsghdr` followed by `struct genlmsghdr`.
+    const HEADER_LEN: usize = 16 + 4;
+
+    const GENL_ID_CTRL: u16 = 0x10;
+    const CTRL_CMD_GETFAMILY: u8 = 3;
+    const CTRL_ATTR_FAMILY_ID: u16 = 1;
+    const CTRL_ATTR_FAMILY_NAME: u16 = 2;
+
+    const NL80211_CMD_GET_INTERFACE: u8 = 5;
+    const NL80211_ATTR_IFINDEX: u16 = 3;
+    const NL80211_ATTR_SSID: u16 = 52;
+
+    unsafe extern "C" {
+        fn socket(domain: c_int, kind: c_int, protocol: c_int) -> c_int;
+    }
+
+    pub struct Nl80211 {
+        socket: File,
+        family: u16,
+        sequence: u32,
+    }
+
+    impl Nl80211 {
+        /// Opens a generic netlink socket and looks up nl80211's family id.
+        pub fn open() -> io::Result<Self> {
+            let fd = unsafe { socket(AF_NETLINK, SOCK_RAW | SOCK_CLOEXEC, NETLINK_GENERIC) };
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let mut nl80211 = Self {
+                socket: unsafe { File::from_raw_fd(fd) },
+                family: 0,
+                sequence: 0,
+            };
+
+            let name = attribute(CTRL_ATTR_FAMILY_NAME, b"nl80211\0");
+            let reply = nl80211.request(GENL_ID_CTRL, CTRL_CMD_GETFAMILY, &name)?;
+            let family = attributes(&reply)
+                .find(|(kind, _)| *kind == CTRL_ATTR_FAMILY_ID)
+                .and_then(|(_, value)| Some(u16::from_ne_bytes(value.get(..2)?.try_into().ok()?)))
+                .ok_or_else(|| io::Error::new(ErrorKind::NotFound, "no nl80211 family"))?;
+            nl80211.family = family;
+            Ok(nl80211)
+        }
+
+        /// The SSID of the interface with index `index`, `None` while it
+        /// isn't associated.
+        pub fn ssid(&mut self, index: u32) -> io::Result<Option<String>> {
+            let interface = attribute(NL80211_ATTR_IFINDEX, &index.to_ne_bytes());
+            let reply = self.request(self.family, NL80211_CMD_GET_INTERFACE, &interface)?;
+            Ok(attributes(&reply)
+                .find(|(kind, _)| *kind == NL80211_ATTR_SSID)
+                .map(|(_, ssid)| String::from_utf8_lossy(ssid).into_owned()))
+        }
+
+        /// Sends one request and returns the attributes of its reply.
+        fn request(&mut self, family: u16, command: u8, attributes: &[u8]) -> io::Result<Vec<u8>> {
+            self.sequence = self.sequence.wrapping_add(1);
+            let mut message = Vec::with_capacity(HEADER_LEN + attributes.len());
+            message.extend_from_slice(&((HEADER_LEN + attributes.len()) as u32).to_ne_bytes());
+            message.extend_from_slice(&family.to_ne_bytes());
+            message.extend_from_slice(&NLM_F_REQUEST.to_ne_bytes());
+            message.extend_from_slice(&self.sequence.to_ne_bytes());
+            message.extend_from_slice(&0u32.to_ne_bytes());
+            message.extend_from_slice(&[command, 1, 0, 0]);
+            message.extend_from_slice(attributes);
+            self.socket.write_all(&message)?;
+
+            let mut buffer = vec![0u8; 8192];
+            let read = self.socket.read(&mut buffer)?;
+            let reply = &buffer[..read];
+            if reply.len() < HEADER_LEN {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidData,
+                    "short netlink reply",
+                ));
+            }
+            let length =
+                (u32::from_ne_bytes(reply[0..4].try_into().unwrap()) as usize).min(reply.len());
+            let kind = u16::from_ne_bytes(reply[4..6].try_into().unwrap());
+            if kind == NLMSG_ERROR {
+                let errno = i32::from_ne_bytes(reply[16..20].try_into().unwrap());
+                return Err(io::Error::from_raw_os_error(-errno));
+            }
+            Ok(reply[HEADER_LEN..length].to_vec())
+        }
+    }
+
+    /// A netlink attribute: length, type, then the value padded to four
+    /// bytes.
+    fn attribute(kind: u16, value: &[u8]) -> Vec<u8> {
+        let mut attribute = Vec::new();
+        attribute.extend_from_slice(&((4 + value.len()) as u16).to_ne_bytes());
+        attribute.extend_from_slice(&kind.to_ne_bytes());
+        attribute.extend_from_slice(value);
+        attribute.resize(attribute.len().next_multiple_of(4), 0);
+        attribute
+    }
+
+    /// Type and value of each attribute in a reply.
+    fn attributes(mut data: &[u8]) -> impl Iterator<Item = (u16, &[u8])> {
+        std::iter::from_fn(move || {
+            if data.len() < 4 {
+                return None;
+            }
+            let length = u16::from_ne_bytes([data[0], data[1]]) as usize;
+            // The top bits flag nested and byte-order attributes.
+            let kind = u16::from_ne_bytes([data[2], data[3]]) & 0x3fff;
+            if length < 4 || length > data.len() {
+                return None;
+            }
+            let value = &data[4..length];
+            data = &data[length.next_multiple_of(4).min(data.len())..];
+            Some((kind, value))
+        })
+    }
+}
@@ -0,0 +1,276 @@
+use super::{Block, BlockOutput, PeripheralIcons, Urgency};
+use crate::errors::BlockError;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Mouse,
+    Keyboard,
+    Headset,
+    Gamepad,
+    Other,
+}
+
+impl Kind {
+    /// From UPower's `Type` property.
+    #[cfg(feature = "dbus")]
+    fn from_type(kind: u32) -> Self {
+        match kind {
+            5 => Kind::Mouse,
+            6 => Kind::Keyboard,
+            12 => Kind::Gamepad,
+            17 | 19 => Kind::Headset,
+            _ => Kind::Other,
+        }
+    }
+
+    /// From the type line `upower -i` prints.
+    #[cfg(not(feature = "dbus"))]
+    fn from_name(name: &str) -> Self {
+        match name {
+            "mouse" => Kind::Mouse,
+            "keyboard" => Kind::Keyboard,
+            "gaming-input" => Kind::Gamepad,
+            "headset" | "headphones" => Kind::Headset,
+            _ => Kind::Other,
+        }
+    }
+}
+
+/// A device powered by its own battery, such as a wireless mouse.
+struct Device {
+    kind: Kind,
+    model: String,
+    percentage: f64,
+}
+
+/// Battery levels of wireless mice, keyboards, headsets and other
+/// peripherals UPower knows about, as an icon per device. Clicking the
+/// block shows the device names too. Hidden while none are connected.
+pub struct Peripherals {
+    format: String,
+    icons: PeripheralIcons,
+    warning: u32,
+    interval: Duration,
+    color: u32,
+    expanded: bool,
+    upower: upower::UPower,
+}
+
+impl Peripherals {
+    pub fn new(
+        format: &str,
+        icons: PeripheralIcons,
+        warning: u32,
+        interval_secs: u64,
+        color: u32,
+    ) -> Self {
+        Self {
+            format: format.to_string(),
+            icons,
+            warning,
+            interval: Duration::from_secs(interval_secs),
+            color,
+            expanded: false,
+            upower: Default::default(),
+        }
+    }
+
+    fn icon(&self, kind: Kind) -> &str {
+        match kind {
+            Kind::Mouse => &self.icons.mouse,
+            Kind::Keyboard => &self.icons.keyboard,
+            Kind::Headset => &self.icons.headset,
+            Kind::Gamepad => &self.icons.gamepad,
+            Kind::Other => &self.icons.other,
+        }
+    }
+}
+
+impl Block for Peripherals {
+    fn content(&mut self) -> Result<BlockOutput, BlockError> {
+        let mut devices = self.upower.devices()?;
+        if devices.is_empty() {
+            return Err(BlockError::InvalidData(
+                "no peripherals with a battery".to_string(),
+            ));
+        }
+        devices.sort_by(|a, b| a.model.cmp(&b.model));
+
+        let entries: Vec<String> = devices
+            .iter()
+            .map(|device| {
+                let icon = self.icon(device.kind);
+                if self.expanded && !device.model.is_empty() {
+                    format!("{} {} {:.0}%", icon, device.model, device.percentage)
+                } else {
+                    format!("{} {:.0}%", icon, device.percentage)
+                }
+            })
+            .collect();
+
+        let mut output = BlockOutput::from(self.format.replace("{}", &entries.join("  ")));
+        if devices
+            .iter()
+            .any(|device| device.percentage <= self.warning as f64)
+        {
+            output.urgency = Urgency::Warning;
+        }
+        Ok(output)
+    }
+
+    fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    fn color(&self) -> u32 {
+        self.color
+    }
+
+    fn click(&mut self, button: u8) -> bool {
+        if button != 1 {
+            return false;
+        }
+        self.expanded = !self.expanded;
+        true
+    }
+}
+
+#[cfg(feature = "dbus")]
+mod upower {
+    use super::{Device, Kind};
+    use crate::errors::BlockError;
+    use zbus::blocking::{Connection, Proxy};
+    use zbus::zvariant::OwnedObjectPath;
+
+    const SERVICE: &str = "org.freedesktop.UPower";
+
+    /// UPower's `Type` of a mains supply.
+    const LINE_POWER: u32 = 1;
+
+    /// UPower on the system bus, connected on first use and again after a
+    /// call failed.
+    #[derive(Default)]
+    pub struct UPower {
+        bus: Option<Connection>,
+    }
+
+    impl UPower {
+        /// Present devices with a battery that don't power the computer.
+        pub fn devices(&mut self) -> Result<Vec<Device>, BlockError> {
+            let bus = match &mut self.bus {
+                Some(bus) => bus,
+                None => self.bus.insert(Connection::system().map_err(dbus_error)?),
+            };
+
+            let result = peripherals(bus);
+            if result.is_err() {
+                self.bus = None;
+            }
+            result
+        }
+    }
+
+    fn peripherals(bus: &Connection) -> Result<Vec<Device>, BlockError> {
+        let upower =
+            Proxy::new(bus, SERVICE, "/org/freedesktop/UPower", SERVICE).map_err(dbus_error)?;
+        let paths: Vec<OwnedObjectPath> =
+            upower.call("EnumerateDevices", &()).map_err(dbus_error)?;
+
+        let mut devices = Vec::new();
+        for path in &paths {
+            let device = Proxy::new(bus, SERVICE, path.as_str(), "org.freedesktop.UPower.Device")
+                .map_err(dbus_error)?;
+            let kind: u32 = device.get_property("Type").map_err(dbus_error)?;
+            let power_supply: bool = device.get_property("PowerSupply").map_err(dbus_error)?;
+            let present: bool = device.get_property("IsPresent").map_err(dbus_error)?;
+            if kind == LINE_POWER || power_supply || !present {
+                continue;
+            }
+
+            devices.push(Device {
+                kind: Kind::from_type(kind),
+                model: device.get_property("Model").map_err(dbus_error)?,
+                percentage: device.get_property("Percentage").map_err(dbus_error)?,
+            });
+        }
+        Ok(devices)
+    }
+
+    fn dbus_error(error: zbus::Error) -> BlockError {
+        BlockError::CommandFailed(format!("UPower D-Bus call failed: {}", error))
+    }
+}
+
+#[cfg(not(feature = "dbus"))]
+mod upower {
+    use super::{Device, Kind};
+    use crate::errors::BlockError;
+    use std::process::Command;
+
+    /// Without the `dbus` feature the upower tool is asked instead.
+    #[derive(Default)]
+    pub struct UPower;
+
+    impl UPower {
+        pub fn devices(&mut self) -> Result<Vec<Device>, BlockError> {
+            let paths = upower(&["-e"])?;
+            let mut devices = Vec::new();
+            for path in paths.lines().filter(|path| !path.contains("DisplayDevice")) {
+                if let Some(device) = parse_device(&upower(&["-i", path.trim()])?) {
+                    devices.push(device);
+                }
+            }
+            Ok(devices)
+        }
+    }
+
+    /// Reads a device from `upower -i`, where the type is a line of its own
+    /// among `name: value` lines.
+    fn parse_device(info: &str) -> Option<Device> {
+        let mut kind = None;
+        let mut model = String::new();
+        let mut percentage = None;
+        let mut power_supply = false;
+        let mut present = true;
+
+        for line in info.lines().map(str::trim) {
+            match line.split_once(':') {
+                Some((name, value)) => {
+                    let value = value.trim();
+                    match name.trim() {
+                        "model" => model = value.to_string(),
+                        "power supply" => power_supply = value == "yes",
+                        "present" => present = value == "yes",
+                        "percentage" => {
+                            percentage = value.trim_end_matches('%').trim().parse().ok()
+                        }
+                        _ => {}
+                    }
+                }
+                None if kind.is_none() && !line.is_empty() => kind = Some(line.to_string()),
+                None => {}
+            }
+        }
+
+        let kind = kind?;
+        if kind == "line-power" || power_supply || !present {
+            return None;
+        }
+        Some(Device {
+            kind: Kind::from_name(&kind),
+            model,
+            percentage: percentage?,
+        })
+    }
+
+    fn upower(args: &[&str]) -> Result<String, BlockError> {
+        let output = Command::new("upower").args(args).output()?;
+        if !output.status.success() {
+            return Err(BlockError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
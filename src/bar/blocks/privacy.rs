@@ -0,0 +1,126 @@
+use super::shell::run_command;
+use super::{Block, BlockOutput, Segment, Urgency};
+use crate::errors::BlockError;
+use std::fs;
+use std::time::Duration;
+
+/// How long pactl may take to list the recording streams.
+const PACTL_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Shows an indicator while a program records from a microphone or has a
+/// webcam open. Hidden while neither is in use.
+pub struct Privacy {
+    format: String,
+    microphone: String,
+    camera: String,
+    interval: Duration,
+    color: u32,
+}
+
+impl Privacy {
+    pub fn new(
+        format: &str,
+        microphone: &str,
+        camera: &str,
+        interval_secs: u64,
+        color: u32,
+    ) -> Self {
+        Self {
+            format: format.to_string(),
+            microphone: microphone.to_string(),
+            camera: camera.to_string(),
+            interval: Duration::from_secs(interval_secs),
+            color,
+        }
+    }
+}
+
+/// Programs recording audio, from the source outputs PulseAudio (or
+/// PipeWire's pulse server) lists. Empty when pactl is missing.
+fn microphone_users() -> Vec<String> {
+    let Ok(output) = run_command("pactl list source-outputs", &[], Some(PACTL_TIMEOUT)) else {
+        return Vec::new();
+    };
+
+    let mut users = Vec::new();
+    for stream in output.split("Source Output #").skip(1) {
+        let name = stream
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("application.name = "))
+            .map(|name| name.trim_matches('"').to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+        users.push(name);
+    }
+    users
+}
+
+/// Programs with a `/dev/video*` device open. Only processes readable by
+/// the user are seen, which covers the ones in the session.
+fn camera_users() -> Vec<String> {
+    let Ok(processes) = fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+
+    let mut users = Vec::new();
+    for process in processes.flatten() {
+        let path = process.path();
+        let Ok(fds) = fs::read_dir(path.join("fd")) else {
+            continue;
+        };
+        let uses_camera = fds.flatten().any(|fd| {
+            fs::read_link(fd.path())
+                .is_ok_and(|target| target.to_string_lossy().starts_with("/dev/video"))
+        });
+        if uses_camera {
+            let name = fs::read_to_string(path.join("comm")).unwrap_or_default();
+            users.push(name.trim().to_string());
+        }
+    }
+    users
+}
+
+impl Block for Privacy {
+    fn content(&mut self) -> Result<BlockOutput, BlockError> {
+        let mut users = microphone_users();
+        let recording = !users.is_empty();
+        let cameras = camera_users();
+        let filming = !cameras.is_empty();
+        if !recording && !filming {
+            return Err(BlockError::InvalidData(
+                "no microphone or camera in use".to_string(),
+            ));
+        }
+
+        let mut indicators = Vec::new();
+        if recording {
+            indicators.push(self.microphone.as_str());
+        }
+        if filming {
+            indicators.push(self.camera.as_str());
+        }
+
+        users.extend(cameras);
+        users.sort();
+        users.dedup();
+
+        Ok(BlockOutput {
+            segments: vec![Segment {
+                text: self
+                    .format
+                    .replace("{apps}", &users.join(", "))
+                    .replace("{}", &indicators.join(" ")),
+                ..Default::default()
+            }],
+            urgency: Urgency::Critical,
+            min_width: None,
+        })
+    }
+
+    fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    fn color(&self) -> u32 {
+        self.color
+    }
+}
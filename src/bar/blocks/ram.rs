@@ -1,70 +1,91 @@
-use super::Block;
+use super::{Block, BlockOutput, Thresholds};
 use crate::errors::BlockError;
 use std::fs;
 use std::time::Duration;
 
 pub struct Ram {
     format: String,
+    thresholds: Thresholds,
     interval: Duration,
     color: u32,
 }
 
+/// Used and total kibibytes of RAM and of swap, from /proc/meminfo.
+struct MemoryInfo {
+    used: u64,
+    total: u64,
+    swap_used: u64,
+    swap_total: u64,
+}
+
 impl Ram {
-    pub fn new(format: &str, interval_secs: u64, color: u32) -> Self {
+    pub fn new(format: &str, thresholds: Thresholds, interval_secs: u64, color: u32) -> Self {
         Self {
             format: format.to_string(),
+            thresholds,
             interval: Duration::from_secs(interval_secs),
             color,
         }
     }
 
-    fn get_memory_info(&self) -> Result<(u64, u64, f32), BlockError> {
+    fn get_memory_info(&self) -> Result<MemoryInfo, BlockError> {
         let meminfo = fs::read_to_string("/proc/meminfo")?;
         let mut total: u64 = 0;
         let mut available: u64 = 0;
+        let mut swap_total: u64 = 0;
+        let mut swap_free: u64 = 0;
 
         for line in meminfo.lines() {
-            if line.starts_with("MemTotal:") {
-                total = line
-                    .split_whitespace()
-                    .nth(1)
-                    .and_then(|s| s.parse().ok())
-                    .unwrap_or(0)
-            } else if line.starts_with("MemAvailable:") {
-                available = line
-                    .split_whitespace()
-                    .nth(1)
-                    .and_then(|s| s.parse().ok())
-                    .unwrap_or(0)
-            }
+            let mut fields = line.split_whitespace();
+            let field = match fields.next() {
+                Some("MemTotal:") => &mut total,
+                Some("MemAvailable:") => &mut available,
+                Some("SwapTotal:") => &mut swap_total,
+                Some("SwapFree:") => &mut swap_free,
+                _ => continue,
+            };
+            *field = fields.next().and_then(|s| s.parse().ok()).unwrap_or(0);
         }
 
-        let used = total.saturating_sub(available);
-        let percentage = if total > 0 {
-            (used as f32 / total as f32) * 100.0
-        } else {
-            0.0
-        };
+        Ok(MemoryInfo {
+            used: total.saturating_sub(available),
+            total,
+            swap_used: swap_total.saturating_sub(swap_free),
+            swap_total,
+        })
+    }
+}
 
-        Ok((used, total, percentage))
+fn percent(used: u64, total: u64) -> f32 {
+    if total > 0 {
+        (used as f32 / total as f32) * 100.0
+    } else {
+        0.0
     }
 }
 
 impl Block for Ram {
-    fn content(&mut self) -> Result<String, BlockError> {
-        let (used, total, percentage) = self.get_memory_info()?;
-
-        let used_gb = used as f32 / 1024.0 / 1024.0;
-        let total_gb = total as f32 / 1024.0 / 1024.0;
+    fn content(&mut self) -> Result<BlockOutput, BlockError> {
+        let info = self.get_memory_info()?;
+        let percentage = percent(info.used, info.total);
+        let gb = |kib: u64| format!("{:.1}", kib as f32 / 1024.0 / 1024.0);
 
         let result = self
             .format
-            .replace("{used}", &format!("{:.1}", used_gb))
-            .replace("{total}", &format!("{:.1}", total_gb))
+            .replace("{used}", &gb(info.used))
+            .replace("{total}", &gb(info.total))
             .replace("{percent}", &format!("{:.1}", percentage))
-            .replace("{}", &format!("{:.1}", used_gb));
+            .replace("{swap_used}", &gb(info.swap_used))
+            .replace("{swap_total}", &gb(info.swap_total))
+            .replace(
+                "{swap_percent}",
+                &format!("{:.1}", percent(info.swap_used, info.swap_total)),
+            )
+            .replace("{}", &gb(info.used));
 
-        Ok(result)
+        let mut output = BlockOutput::from(result);
+        self.thresholds.apply(&mut output, percentage as f64);
+        Ok(output)
     }
 
     fn interval(&self) -> Duration {
@@ -0,0 +1,148 @@
+use super::{Block, BlockNotifier, BlockOutput};
+use crate::errors::BlockError;
+use std::sync::Arc;
+use std::time::Duration;
+use x11rb::connection::Connection;
+use x11rb::errors::ReplyOrIdError;
+use x11rb::protocol::Event;
+use x11rb::protocol::xproto::{
+    AtomEnum, ChangeWindowAttributesAux, ConnectionExt, CreateWindowAux, EventMask, Window,
+    WindowClass,
+};
+use x11rb::rust_connection::RustConnection;
+
+/// Longest name read, in 32-bit units.
+const MAX_LENGTH: u32 = 1024;
+
+/// Name of the root window, as set by `xsetroot -name` or a dwm status
+/// script. Redrawn whenever the name changes.
+pub struct RootName {
+    interval: Duration,
+    color: u32,
+    connection: Option<(RustConnection, Window)>,
+}
+
+impl RootName {
+    pub fn new(interval_secs: u64, color: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(interval_secs),
+            color,
+            connection: None,
+        }
+    }
+}
+
+fn connect() -> Result<(RustConnection, Window), BlockError> {
+    let (connection, screen_num) = x11rb::connect(None)
+        .map_err(|e| BlockError::CommandFailed(format!("cannot connect to X: {}", e)))?;
+    let root = connection.setup().roots[screen_num].root;
+    Ok((connection, root))
+}
+
+fn read_name(connection: &RustConnection, root: Window) -> Result<String, BlockError> {
+    let failed = |e: &dyn std::fmt::Display| BlockError::CommandFailed(e.to_string());
+    let reply = connection
+        .get_property(false, root, AtomEnum::WM_NAME, AtomEnum::ANY, 0, MAX_LENGTH)
+        .map_err(|e| failed(&e))?
+        .reply()
+        .map_err(|e| failed(&e))?;
+    Ok(String::from_utf8_lossy(&reply.value).trim().to_string())
+}
+
+/// Wakes the block whenever the root window's name changes, until the
+/// block is stopped or the X connection is lost.
+fn watch(notifier: BlockNotifier) {
+    let (connection, root) = match connect() {
+        Ok(connection) => connection,
+        Err(e) => {
+            eprintln!("Failed to watch the root window name: {}", e);
+            return;
+        }
+    };
+    let connection = Arc::new(connection);
+    let Ok(wake) = listen(&connection, root) else {
+        return;
+    };
+
+    // Destroying the window sends a DestroyNotify, which ends the wait
+    // for the next event below.
+    let waker = connection.clone();
+    notifier.on_stop(move || {
+        let _ = waker.destroy_window(wake);
+        let _ = waker.flush();
+    });
+
+    while let Ok(event) = connection.wait_for_event() {
+        if notifier.is_stopped() {
+            return;
+        }
+        if let Event::PropertyNotify(event) = event
+            && event.window == root
+            && event.atom == u32::from(AtomEnum::WM_NAME)
+            && !notifier.notify()
+        {
+            return;
+        }
+    }
+}
+
+/// Selects name changes on the root window, and creates a window whose
+/// destruction wakes the watcher.
+fn listen(connection: &RustConnection, root: Window) -> Result<Window, ReplyOrIdError> {
+    connection.change_window_attributes(
+        root,
+        &ChangeWindowAttributesAux::new().event_mask(EventMask::PROPERTY_CHANGE),
+    )?;
+    let wake = connection.generate_id()?;
+    connection.create_window(
+        0,
+        wake,
+        root,
+        0,
+        0,
+        1,
+        1,
+        0,
+        WindowClass::INPUT_ONLY,
+        x11rb::COPY_FROM_PARENT,
+        &CreateWindowAux::new().event_mask(EventMask::STRUCTURE_NOTIFY),
+    )?;
+    connection.flush()?;
+    Ok(wake)
+}
+
+impl Block for RootName {
+    fn content(&mut self) -> Result<BlockOutput, BlockError> {
+        // Dropped on a failed read, to connect again on the next update.
+        let (connection, root) = match self.connection.take() {
+            Some(connection) => connection,
+            None => connect()?,
+        };
+        let name = read_name(&connection, root)?;
+        self.connection = Some((connection, root));
+
+        if name.is_empty() {
+            return Err(BlockError::InvalidData(
+                "root window has no name".to_string(),
+            ));
+        }
+        Ok(name.into())
+    }
+
+    fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    fn color(&self) -> u32 {
+        self.color
+    }
+
+    fn subscribe(&mut self, notifier: BlockNotifier) {
+        let spawned = std::thread::Builder::new()
+            .name("oxwm-root-name".to_string())
+            .spawn(move || watch(notifier));
+        if let Err(e) = spawned {
+            eprintln!("Failed to start root window name watcher: {}", e);
+        }
+    }
+}
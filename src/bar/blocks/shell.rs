@@ -1,62 +1,209 @@
-use super::Block;
+use super::{Block, BlockOutput, Segment, Urgency};
 use crate::errors::BlockError;
-use std::process::Command;
+use crate::json::Json;
+use std::io::Read;
+use std::process::{Command, Stdio};
 use std::time::{Duration, Instant};
 
+/// How often a command with a timeout is checked for completion.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
 pub struct ShellBlock {
     format: String,
     command: String,
     interval: Duration,
+    timeout: Option<Duration>,
+    error_text: String,
     color: u32,
-    cached_output: Option<String>,
+    cached_output: Option<BlockOutput>,
     last_run: Option<Instant>,
+    last_error: Option<String>,
 }
 
 impl ShellBlock {
-    pub fn new(format: &str, command: &str, interval_secs: u64, color: u32) -> Self {
+    pub fn new(
+        format: &str,
+        command: &str,
+        interval_secs: u64,
+        timeout_secs: Option<u64>,
+        error_text: &str,
+        color: u32,
+    ) -> Self {
         Self {
             format: format.to_string(),
             command: command.to_string(),
             interval: Duration::from_secs(interval_secs),
+            timeout: timeout_secs.map(Duration::from_secs),
+            error_text: error_text.to_string(),
             color,
             cached_output: None,
             last_run: None,
+            last_error: None,
         }
     }
 
-    fn execute(&mut self) -> Result<String, BlockError> {
-        let output = Command::new("sh")
-            .arg("-c")
-            .arg(&self.command)
-            .output()
-            .map_err(|e| BlockError::CommandFailed(format!("Failed to execute command: {}", e)))?;
+    /// Runs the command; on failure the block shows the error text in place
+    /// of the output, or is dropped if the error text is empty.
+    fn execute(&mut self, envs: &[(&str, String)]) -> Result<BlockOutput, BlockError> {
+        self.last_run = Some(Instant::now());
+
+        let result = match run_command(&self.command, envs, self.timeout) {
+            Ok(output) => {
+                self.last_error = None;
+                Some(parse_output(&self.format, &output))
+            }
+            Err(e) => {
+                let message = e.to_string();
+                if self.last_error.as_ref() != Some(&message) {
+                    eprintln!("Block '{}': {}", self.command, message);
+                    self.last_error = Some(message);
+                }
+                Some(self.format.replace("{}", &self.error_text))
+                    .filter(|_| !self.error_text.is_empty())
+                    .map(BlockOutput::from)
+            }
+        };
+
+        self.cached_output = result;
+        self.cached_output
+            .clone()
+            .ok_or_else(|| BlockError::CommandFailed(format!("'{}' failed", self.command)))
+    }
+}
+
+/// Reads a command's output. A JSON object is structured output:
+///
+/// ```json
+/// {"text": "42%", "color": "#ff0000", "urgency": "warning", "min_width": 60,
+///  "segments": [{"text": "CPU ", "color": "#888888"}, {"text": "42%"}]}
+/// ```
+///
+/// `text` goes through the block's format and is shown when there are no
+/// `segments`. Anything else is plain text.
+fn parse_output(format: &str, output: &str) -> BlockOutput {
+    let json = output
+        .starts_with('{')
+        .then(|| Json::parse(output).ok())
+        .flatten();
+    let Some(json) = json else {
+        return BlockOutput::from(format.replace("{}", output));
+    };
+
+    let segments = json
+        .get("segments")
+        .and_then(Json::as_array)
+        .map(|segments| {
+            segments
+                .iter()
+                .filter_map(|segment| {
+                    Some(Segment {
+                        text: segment.get("text")?.as_str()?.to_string(),
+                        color: segment.get("color").and_then(parse_json_color),
+                        ..Default::default()
+                    })
+                })
+                .collect::<Vec<_>>()
+        })
+        .filter(|segments| !segments.is_empty())
+        .unwrap_or_else(|| {
+            let text = json.get("text").and_then(Json::as_str).unwrap_or_default();
+            vec![Segment {
+                text: format.replace("{}", text),
+                color: json.get("color").and_then(parse_json_color),
+                ..Default::default()
+            }]
+        });
+
+    let urgency = match json.get("urgency").and_then(Json::as_str) {
+        Some("warning") => Urgency::Warning,
+        Some("critical") => Urgency::Critical,
+        _ => Urgency::Normal,
+    };
+
+    BlockOutput {
+        segments,
+        urgency,
+        min_width: json
+            .get("min_width")
+            .and_then(Json::as_i64)
+            .and_then(|width| u16::try_from(width).ok()),
+    }
+}
+
+/// Reads a `"#rrggbb"` or `"0xrrggbb"` color.
+fn parse_json_color(value: &Json) -> Option<u32> {
+    let text = value.as_str()?;
+    let hex = text.strip_prefix('#').or_else(|| text.strip_prefix("0x"))?;
+    u32::from_str_radix(hex, 16).ok()
+}
+
+/// Runs `command` with `sh -c` and returns its trimmed output, killing it
+/// once `timeout` passes.
+pub(super) fn run_command(
+    command: &str,
+    envs: &[(&str, String)],
+    timeout: Option<Duration>,
+) -> Result<String, BlockError> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .envs(envs.iter().map(|(key, value)| (key, value)))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| BlockError::CommandFailed(format!("Failed to execute command: {}", e)))?;
 
+    let Some(timeout) = timeout else {
+        let output = child.wait_with_output().map_err(BlockError::Io)?;
         if !output.status.success() {
             return Err(BlockError::CommandFailed(format!(
                 "Command exited with status: {}",
                 output.status
             )));
         }
+        return Ok(String::from_utf8_lossy(&output.stdout).trim().to_string());
+    };
 
-        let result = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        let formatted = self.format.replace("{}", &result);
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait().map_err(BlockError::Io)? {
+            break status;
+        }
+        if started.elapsed() >= timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(BlockError::CommandFailed(format!(
+                "Command timed out after {}s",
+                timeout.as_secs()
+            )));
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    };
 
-        self.cached_output = Some(formatted.clone());
-        self.last_run = Some(Instant::now());
+    if !status.success() {
+        return Err(BlockError::CommandFailed(format!(
+            "Command exited with status: {}",
+            status
+        )));
+    }
 
-        Ok(formatted)
+    let mut stdout = Vec::new();
+    if let Some(mut pipe) = child.stdout.take() {
+        pipe.read_to_end(&mut stdout).map_err(BlockError::Io)?;
     }
+    Ok(String::from_utf8_lossy(&stdout).trim().to_string())
 }
 
 impl Block for ShellBlock {
-    fn content(&mut self) -> Result<String, BlockError> {
+    fn content(&mut self) -> Result<BlockOutput, BlockError> {
         let should_refresh = match self.last_run {
             None => true,
             Some(last) => last.elapsed() >= self.interval,
         };
 
         if should_refresh {
-            return self.execute();
+            return self.execute(&[]);
         }
 
         self.cached_output
@@ -71,4 +218,16 @@ impl Block for ShellBlock {
     fn color(&self) -> u32 {
         self.color
     }
+
+    /// Runs the command again right away with the button in
+    /// `$BLOCK_BUTTON`, so scripts written for dwmblocks can react to it.
+    fn click(&mut self, button: u8) -> bool {
+        let previous = self.cached_output.clone();
+        let _ = self.execute(&[("BLOCK_BUTTON", button.to_string())]);
+        self.cached_output != previous
+    }
+
+    fn signal(&mut self) {
+        self.last_run = None;
+    }
 }
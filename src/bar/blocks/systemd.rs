@@ -0,0 +1,207 @@
+use super::{Block, BlockOutput, Urgency};
+use crate::errors::BlockError;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scope {
+    System,
+    User,
+}
+
+/// Shows the number of failed systemd units, asking systemd over D-Bus.
+/// Hidden while none have failed.
+pub struct SystemdBlock {
+    format: String,
+    interval: Duration,
+    color: u32,
+    system_units: Vec<String>,
+    user_units: Vec<String>,
+    buses: bus::Buses,
+}
+
+impl SystemdBlock {
+    pub fn new(
+        format: &str,
+        system_units: &[String],
+        user_units: &[String],
+        interval_secs: u64,
+        color: u32,
+    ) -> Self {
+        Self {
+            format: format.to_string(),
+            interval: Duration::from_secs(interval_secs),
+            color,
+            system_units: system_units.to_vec(),
+            user_units: user_units.to_vec(),
+            buses: Default::default(),
+        }
+    }
+}
+
+impl Block for SystemdBlock {
+    fn content(&mut self) -> Result<BlockOutput, BlockError> {
+        // Without a list of units every failed unit counts.
+        let watch_all = self.system_units.is_empty() && self.user_units.is_empty();
+
+        let mut failed = Vec::new();
+        if watch_all || !self.system_units.is_empty() {
+            failed.extend(self.buses.failed_units(Scope::System, &self.system_units)?);
+        }
+        if watch_all || !self.user_units.is_empty() {
+            failed.extend(self.buses.failed_units(Scope::User, &self.user_units)?);
+        }
+        if failed.is_empty() {
+            return Err(BlockError::InvalidData("no failed units".to_string()));
+        }
+
+        let mut output = BlockOutput::from(
+            self.format
+                .replace("{units}", &failed.join(", "))
+                .replace("{}", &failed.len().to_string()),
+        );
+        output.urgency = Urgency::Critical;
+        Ok(output)
+    }
+
+    fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    fn color(&self) -> u32 {
+        self.color
+    }
+}
+
+#[cfg(feature = "dbus")]
+mod bus {
+    use super::Scope;
+    use crate::errors::BlockError;
+    use zbus::blocking::{Connection, Proxy};
+    use zbus::zvariant::OwnedObjectPath;
+
+    /// Name, description, load state, active state, sub state, followed
+    /// unit, object path, job id, job type and job path.
+    type UnitRow = (
+        String,
+        String,
+        String,
+        String,
+        String,
+        String,
+        OwnedObjectPath,
+        u32,
+        String,
+        OwnedObjectPath,
+    );
+
+    /// Connections to the system and session bus, opened on first use and
+    /// again after a call failed.
+    #[derive(Default)]
+    pub struct Buses {
+        system: Option<Connection>,
+        user: Option<Connection>,
+    }
+
+    impl Buses {
+        /// Names of the failed units among `units`, or of every failed unit
+        /// when `units` is empty.
+        pub fn failed_units(
+            &mut self,
+            scope: Scope,
+            units: &[String],
+        ) -> Result<Vec<String>, BlockError> {
+            let slot = match scope {
+                Scope::System => &mut self.system,
+                Scope::User => &mut self.user,
+            };
+            let connection = match slot {
+                Some(connection) => connection,
+                None => {
+                    let connection = match scope {
+                        Scope::System => Connection::system(),
+                        Scope::User => Connection::session(),
+                    };
+                    slot.insert(connection.map_err(dbus_error)?)
+                }
+            };
+
+            let result = list_failed(connection, units);
+            if result.is_err() {
+                *slot = None;
+            }
+            result
+        }
+    }
+
+    fn list_failed(connection: &Connection, units: &[String]) -> Result<Vec<String>, BlockError> {
+        let manager = Proxy::new(
+            connection,
+            "org.freedesktop.systemd1",
+            "/org/freedesktop/systemd1",
+            "org.freedesktop.systemd1.Manager",
+        )
+        .map_err(dbus_error)?;
+
+        let rows: Vec<UnitRow> = if units.is_empty() {
+            manager.call("ListUnitsFiltered", &(vec!["failed"],))
+        } else {
+            manager.call("ListUnitsByNames", &(units,))
+        }
+        .map_err(dbus_error)?;
+
+        Ok(rows
+            .into_iter()
+            .filter(|row| row.3 == "failed")
+            .map(|row| row.0)
+            .collect())
+    }
+
+    fn dbus_error(error: zbus::Error) -> BlockError {
+        BlockError::CommandFailed(format!("systemd D-Bus call failed: {}", error))
+    }
+}
+
+#[cfg(not(feature = "dbus"))]
+mod bus {
+    use super::Scope;
+    use crate::errors::BlockError;
+    use std::process::Command;
+
+    /// Without the `dbus` feature systemctl is asked instead.
+    #[derive(Default)]
+    pub struct Buses;
+
+    impl Buses {
+        pub fn failed_units(
+            &mut self,
+            scope: Scope,
+            units: &[String],
+        ) -> Result<Vec<String>, BlockError> {
+            let mut command = Command::new("systemctl");
+            if scope == Scope::User {
+                command.arg("--user");
+            }
+            let output = command
+                .args([
+                    "list-units",
+                    "--state=failed",
+                    "--plain",
+                    "--no-legend",
+                    "--no-pager",
+                ])
+                .args(units)
+                .output()?;
+            if !output.status.success() {
+                return Err(BlockError::CommandFailed(
+                    String::from_utf8_lossy(&output.stderr).trim().to_string(),
+                ));
+            }
+
+            Ok(String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter_map(|line| line.split_whitespace().find(|word| *word != "●"))
+                .map(str::to_string)
+                .collect())
+        }
+    }
+}
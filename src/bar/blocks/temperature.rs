@@ -0,0 +1,110 @@
+use super::{Block, BlockOutput, Thresholds};
+use crate::errors::BlockError;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const THERMAL_DIR: &str = "/sys/class/thermal";
+
+/// Zone types of the CPU package on common platforms, in order of
+/// preference.
+const CPU_ZONES: [&str; 4] = ["x86_pkg_temp", "cpu-thermal", "cpu_thermal", "soc_thermal"];
+
+/// Temperature of a thermal zone in degrees Celsius.
+pub struct Temperature {
+    format: String,
+    zone: Option<String>,
+    thresholds: Thresholds,
+    interval: Duration,
+    color: u32,
+}
+
+impl Temperature {
+    pub fn new(
+        format: &str,
+        zone: Option<String>,
+        thresholds: Thresholds,
+        interval_secs: u64,
+        color: u32,
+    ) -> Self {
+        Self {
+            format: format.to_string(),
+            zone,
+            thresholds,
+            interval: Duration::from_secs(interval_secs),
+            color,
+        }
+    }
+
+    /// The configured zone by number or type; without one, the CPU's zone
+    /// or else the first zone.
+    fn zone_path(&self) -> Option<PathBuf> {
+        if let Some(zone) = &self.zone
+            && zone.chars().all(|c| c.is_ascii_digit())
+        {
+            return Some(Path::new(THERMAL_DIR).join(format!("thermal_zone{}", zone)));
+        }
+
+        let mut zones: Vec<(PathBuf, String)> = fs::read_dir(THERMAL_DIR)
+            .ok()?
+            .flatten()
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with("thermal_zone")
+            })
+            .map(|entry| {
+                let kind = fs::read_to_string(entry.path().join("type")).unwrap_or_default();
+                (entry.path(), kind.trim().to_string())
+            })
+            .collect();
+        zones.sort_by_key(|(path, _)| zone_number(path));
+
+        let find = |kind: &str| {
+            zones
+                .iter()
+                .find(|(_, zone_kind)| zone_kind == kind)
+                .map(|(path, _)| path.clone())
+        };
+        match &self.zone {
+            Some(kind) => find(kind),
+            None => CPU_ZONES
+                .iter()
+                .find_map(|kind| find(kind))
+                .or_else(|| zones.first().map(|(path, _)| path.clone())),
+        }
+    }
+}
+
+/// `thermal_zone10` sorts after `thermal_zone9`.
+fn zone_number(path: &Path) -> u32 {
+    path.file_name()
+        .and_then(|name| name.to_str()?.strip_prefix("thermal_zone")?.parse().ok())
+        .unwrap_or(u32::MAX)
+}
+
+impl Block for Temperature {
+    fn content(&mut self) -> Result<BlockOutput, BlockError> {
+        let path = self
+            .zone_path()
+            .ok_or_else(|| BlockError::InvalidData("no thermal zone found".to_string()))?;
+        let text = fs::read_to_string(path.join("temp"))?;
+        let millidegrees: f64 = text.trim().parse().map_err(|_| {
+            BlockError::InvalidData(format!("unexpected temperature: {}", text.trim()))
+        })?;
+        let degrees = millidegrees / 1000.0;
+
+        let mut output = BlockOutput::from(self.format.replace("{}", &format!("{:.0}", degrees)));
+        self.thresholds.apply(&mut output, degrees);
+        Ok(output)
+    }
+
+    fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    fn color(&self) -> u32 {
+        self.color
+    }
+}
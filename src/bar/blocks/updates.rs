@@ -0,0 +1,190 @@
+use super::shell::run_command;
+use super::{Block, BlockOutput};
+use crate::errors::BlockError;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+/// Checks ask mirrors for package lists, so they are never run more often
+/// than this, whatever the configured interval.
+const MIN_INTERVAL: Duration = Duration::from_secs(300);
+
+/// How long a check may take before it is given up.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Commands printing the number of pending updates. checkupdates syncs a
+/// copy of the pacman database, so it sees updates pacman -Qu would only
+/// see after the next `pacman -Sy`.
+const PACMAN: &str = "{ checkupdates 2>/dev/null || pacman -Qu 2>/dev/null; } | wc -l";
+const APT: &str = "apt list --upgradable 2>/dev/null | grep -F '[upgradable' | wc -l";
+const DNF: &str = "dnf -q check-update 2>/dev/null | awk '/^Obsoleting/ { exit } NF == 3 { n++ } END { print n + 0 }'";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Backend {
+    Pacman,
+    Apt,
+    Dnf,
+}
+
+impl Backend {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "pacman" => Some(Backend::Pacman),
+            "apt" => Some(Backend::Apt),
+            "dnf" => Some(Backend::Dnf),
+            _ => None,
+        }
+    }
+
+    /// The first package manager found in `$PATH`.
+    fn detect() -> Option<Self> {
+        let path = std::env::var_os("PATH")?;
+        [Backend::Pacman, Backend::Apt, Backend::Dnf]
+            .into_iter()
+            .find(|backend| {
+                std::env::split_paths(&path).any(|dir| dir.join(backend.name()).is_file())
+            })
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Backend::Pacman => "pacman",
+            Backend::Apt => "apt",
+            Backend::Dnf => "dnf",
+        }
+    }
+
+    fn command(self) -> &'static str {
+        match self {
+            Backend::Pacman => PACMAN,
+            Backend::Apt => APT,
+            Backend::Dnf => DNF,
+        }
+    }
+}
+
+/// Number of pending package updates, from pacman, apt, dnf or a custom
+/// command. The count is cached on disk so reloading the config doesn't
+/// check again. Hidden while the system is up to date.
+pub struct Updates {
+    format: String,
+    /// Prints one line per pending update.
+    command: Option<String>,
+    backend: Option<String>,
+    interval: Duration,
+    color: u32,
+    /// A signal asked to check now rather than trust the cached count.
+    recheck: bool,
+}
+
+impl Updates {
+    pub fn new(
+        format: &str,
+        backend: Option<String>,
+        command: Option<String>,
+        interval_secs: u64,
+        color: u32,
+    ) -> Self {
+        Self {
+            format: format.to_string(),
+            command,
+            backend,
+            interval: Duration::from_secs(interval_secs).max(MIN_INTERVAL),
+            color,
+            recheck: false,
+        }
+    }
+
+    /// Counts the pending updates now.
+    fn check(&self) -> Result<u32, BlockError> {
+        if let Some(command) = &self.command {
+            let output = run_command(command, &[], Some(CHECK_TIMEOUT))?;
+            return Ok(output
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .count() as u32);
+        }
+
+        let output = run_command(self.backend()?.command(), &[], Some(CHECK_TIMEOUT))?;
+        output
+            .trim()
+            .parse()
+            .map_err(|_| BlockError::InvalidData(format!("unexpected update count: {}", output)))
+    }
+
+    fn backend(&self) -> Result<Backend, BlockError> {
+        match self.backend.as_deref() {
+            Some(name) => Backend::from_name(name)
+                .ok_or_else(|| BlockError::InvalidData(format!("unknown backend '{}'", name))),
+            None => Backend::detect()
+                .ok_or_else(|| BlockError::InvalidData("no pacman, apt or dnf found".to_string())),
+        }
+    }
+
+    fn cache_path(&self) -> Option<PathBuf> {
+        let name = match (&self.command, self.backend()) {
+            (Some(_), _) => "custom",
+            (None, Ok(backend)) => backend.name(),
+            (None, Err(_)) => return None,
+        };
+        dirs::cache_dir().map(|dir| dir.join("oxwm").join(format!("updates-{}", name)))
+    }
+
+    /// The cached count and how old it is.
+    fn cached(&self) -> Option<(u32, Duration)> {
+        let path = self.cache_path()?;
+        let count = fs::read_to_string(&path).ok()?.trim().parse().ok()?;
+        let modified = fs::metadata(&path).ok()?.modified().ok()?;
+        let age = SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or_default();
+        Some((count, age))
+    }
+
+    fn save(&self, count: u32) {
+        let Some(path) = self.cache_path() else {
+            return;
+        };
+        if let Some(dir) = path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        if let Err(e) = fs::write(&path, count.to_string()) {
+            eprintln!("Failed to cache update count: {}", e);
+        }
+    }
+}
+
+impl Block for Updates {
+    fn content(&mut self) -> Result<BlockOutput, BlockError> {
+        let cached = self.cached();
+        let recheck = std::mem::take(&mut self.recheck);
+        let count = match cached {
+            Some((count, age)) if age < self.interval && !recheck => count,
+            _ => match self.check() {
+                Ok(count) => {
+                    self.save(count);
+                    count
+                }
+                // Keep showing the last count while offline.
+                Err(e) => cached.map(|(count, _)| count).ok_or(e)?,
+            },
+        };
+
+        if count == 0 {
+            return Err(BlockError::InvalidData("no pending updates".to_string()));
+        }
+        Ok(self.format.replace("{}", &count.to_string()).into())
+    }
+
+    fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    fn color(&self) -> u32 {
+        self.color
+    }
+
+    fn signal(&mut self) {
+        self.recheck = true;
+    }
+}
@@ -0,0 +1,176 @@
+use super::shell::run_command;
+use super::{Block, BlockNotifier, BlockOutput};
+use crate::errors::BlockError;
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How long pactl may take to read or change the volume.
+const PACTL_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Wait before running `pactl subscribe` again after it exited, e.g. while
+/// the sound server restarts.
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(5);
+
+const READ_VOLUME: &str =
+    "pactl get-sink-volume @DEFAULT_SINK@ && pactl get-sink-mute @DEFAULT_SINK@";
+
+/// Volume and mute state of the default PulseAudio or PipeWire sink.
+/// Updates as soon as the sound server reports a change; scrolling on the
+/// block changes the volume and a right click mutes it.
+pub struct Volume {
+    format: String,
+    format_muted: String,
+    step: u32,
+    max_volume: u32,
+    interval: Duration,
+    color: u32,
+}
+
+impl Volume {
+    pub fn new(
+        format: &str,
+        format_muted: &str,
+        step: u32,
+        max_volume: u32,
+        interval_secs: u64,
+        color: u32,
+    ) -> Self {
+        Self {
+            format: format.to_string(),
+            format_muted: format_muted.to_string(),
+            step,
+            max_volume,
+            interval: Duration::from_secs(interval_secs),
+            color,
+        }
+    }
+
+    /// Volume in percent of the first channel, and whether the sink is
+    /// muted.
+    fn read(&self) -> Result<(u32, bool), BlockError> {
+        let output = run_command(READ_VOLUME, &[], Some(PACTL_TIMEOUT))?;
+        let volume = output
+            .split('/')
+            .find_map(|part| part.trim().strip_suffix('%')?.trim().parse().ok())
+            .ok_or_else(|| {
+                BlockError::InvalidData(format!("unexpected pactl output: {}", output))
+            })?;
+        let muted = output.lines().any(|line| line.trim() == "Mute: yes");
+        Ok((volume, muted))
+    }
+
+    /// Runs `pactl <verb> @DEFAULT_SINK@ <value>`.
+    fn set(&self, verb: &str, value: &str) {
+        let command = format!("pactl {} @DEFAULT_SINK@ {}", verb, value);
+        if let Err(e) = run_command(&command, &[], Some(PACTL_TIMEOUT)) {
+            eprintln!("Failed to change the volume: {}", e);
+        }
+    }
+}
+
+/// Runs `pactl subscribe` and wakes the block on every sink or server
+/// change, until the block is stopped.
+fn watch(notifier: BlockNotifier) {
+    while !notifier.is_stopped() {
+        let child = Command::new("pactl")
+            .arg("subscribe")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(e) => {
+                eprintln!("Failed to watch the volume: {}", e);
+                return;
+            }
+        };
+        let stdout = child.stdout.take();
+        let child = Arc::new(Mutex::new(child));
+
+        // Ends the read below, which would otherwise wait for the next
+        // volume change.
+        let subscription = child.clone();
+        notifier.on_stop(move || {
+            if let Ok(mut child) = subscription.lock() {
+                let _ = child.kill();
+            }
+        });
+
+        if let Some(stdout) = stdout {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                // "Event 'change' on sink #0", or on server when the
+                // default sink changes.
+                if (line.contains(" on sink ") || line.contains(" on server")) && !notifier.notify()
+                {
+                    break;
+                }
+            }
+        }
+        if let Ok(mut child) = child.lock() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+
+        if notifier.is_stopped() {
+            return;
+        }
+        std::thread::sleep(RESUBSCRIBE_DELAY);
+        if !notifier.notify() {
+            return;
+        }
+    }
+}
+
+impl Block for Volume {
+    fn content(&mut self) -> Result<BlockOutput, BlockError> {
+        let (volume, muted) = self.read()?;
+        let format = if muted {
+            &self.format_muted
+        } else {
+            &self.format
+        };
+        Ok(format.replace("{}", &volume.to_string()).into())
+    }
+
+    fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    fn color(&self) -> u32 {
+        self.color
+    }
+
+    fn click(&mut self, button: u8) -> bool {
+        match button {
+            3 => self.set("set-sink-mute", "toggle"),
+            4 | 5 => {
+                let Ok((volume, _)) = self.read() else {
+                    return false;
+                };
+                let volume = if button == 4 {
+                    (volume + self.step).min(self.max_volume.max(volume))
+                } else {
+                    volume.saturating_sub(self.step)
+                };
+                self.set("set-sink-volume", &format!("{}%", volume));
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn subscribe(&mut self, notifier: BlockNotifier) {
+        let spawned = std::thread::Builder::new()
+            .name("oxwm-volume".to_string())
+            .spawn(move || watch(notifier));
+        if let Err(e) = spawned {
+            eprintln!("Failed to start volume watcher: {}", e);
+        }
+    }
+}
@@ -0,0 +1,335 @@
+use super::shell::run_command;
+use super::{Block, BlockOutput, Segment, Urgency, VpnTarget};
+use crate::errors::BlockError;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+/// How long a toggle command may take to bring the VPN up or down.
+const TOGGLE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// `IFF_UP` in an interface's flags.
+const INTERFACE_UP: u32 = 0x1;
+
+/// Shows whether a VPN is connected: a network interface such as `wg0`, a
+/// NetworkManager connection, or else any WireGuard or tun interface that is
+/// up. Clicking it brings the VPN up or down.
+pub struct Vpn {
+    format: String,
+    format_down: String,
+    target: VpnTarget,
+    down_color: Option<u32>,
+    interval: Duration,
+    color: u32,
+    network_manager: nm::NetworkManager,
+}
+
+impl Vpn {
+    pub fn new(
+        format: &str,
+        format_down: &str,
+        target: VpnTarget,
+        down_color: Option<u32>,
+        interval_secs: u64,
+        color: u32,
+    ) -> Self {
+        Self {
+            format: format.to_string(),
+            format_down: format_down.to_string(),
+            target,
+            down_color,
+            interval: Duration::from_secs(interval_secs),
+            color,
+            network_manager: Default::default(),
+        }
+    }
+
+    /// The name of the VPN while it is connected.
+    fn active(&mut self) -> Result<Option<String>, BlockError> {
+        if let Some(interface) = &self.target.interface {
+            return Ok(interface_up(interface).then(|| interface.clone()));
+        }
+        if let Some(connection) = &self.target.connection {
+            let active = self.network_manager.is_active(connection)?;
+            return Ok(active.then(|| connection.clone()));
+        }
+        Ok(tunnel_interfaces()
+            .into_iter()
+            .find(|interface| interface_up(interface)))
+    }
+
+    /// The configured interface or connection, whichever is set.
+    fn name(&self) -> String {
+        self.target
+            .interface
+            .clone()
+            .or_else(|| self.target.connection.clone())
+            .unwrap_or_default()
+    }
+}
+
+fn interface_up(interface: &str) -> bool {
+    fs::read_to_string(Path::new("/sys/class/net").join(interface).join("flags"))
+        .ok()
+        .and_then(|flags| u32::from_str_radix(flags.trim().trim_start_matches("0x"), 16).ok())
+        .is_some_and(|flags| flags & INTERFACE_UP != 0)
+}
+
+/// WireGuard interfaces, and tun or tap interfaces as OpenVPN and most
+/// other VPN clients create.
+fn tunnel_interfaces() -> Vec<String> {
+    let Ok(entries) = fs::read_dir("/sys/class/net") else {
+        return Vec::new();
+    };
+
+    let mut interfaces: Vec<String> = entries
+        .flatten()
+        .filter(|entry| {
+            let path = entry.path();
+            path.join("tun_flags").exists()
+                || fs::read_to_string(path.join("uevent"))
+                    .is_ok_and(|uevent| uevent.lines().any(|line| line == "DEVTYPE=wireguard"))
+        })
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    interfaces.sort();
+    interfaces
+}
+
+impl Block for Vpn {
+    fn content(&mut self) -> Result<BlockOutput, BlockError> {
+        let (text, color, urgency) = match self.active()? {
+            Some(name) => (self.format.replace("{}", &name), None, Urgency::Normal),
+            None => {
+                let text = self.format_down.replace("{}", &self.name());
+                match self.down_color {
+                    Some(down_color) => (text, Some(down_color), Urgency::Normal),
+                    None => (text, None, Urgency::Warning),
+                }
+            }
+        };
+
+        Ok(BlockOutput {
+            segments: vec![Segment {
+                text,
+                color,
+                ..Default::default()
+            }],
+            urgency,
+            min_width: None,
+        })
+    }
+
+    fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    fn color(&self) -> u32 {
+        self.color
+    }
+
+    fn click(&mut self, button: u8) -> bool {
+        if button != 1 {
+            return false;
+        }
+        let connected = match self.active() {
+            Ok(active) => active.is_some(),
+            Err(e) => {
+                eprintln!("Failed to read VPN state: {}", e);
+                return false;
+            }
+        };
+
+        let result = if let Some(toggle) = &self.target.toggle {
+            let state = if connected { "up" } else { "down" };
+            run_command(
+                toggle,
+                &[("VPN_STATE", state.to_string()), ("VPN_NAME", self.name())],
+                Some(TOGGLE_TIMEOUT),
+            )
+            .map(|_| ())
+        } else if let Some(connection) = &self.target.connection {
+            self.network_manager.set_active(connection, !connected)
+        } else {
+            return false;
+        };
+
+        if let Err(e) = result {
+            eprintln!("Failed to toggle VPN: {}", e);
+        }
+        true
+    }
+}
+
+#[cfg(feature = "dbus")]
+mod nm {
+    use crate::errors::BlockError;
+    use std::collections::HashMap;
+    use zbus::blocking::{Connection, Proxy};
+    use zbus::zvariant::{ObjectPath, OwnedObjectPath, OwnedValue};
+
+    const SERVICE: &str = "org.freedesktop.NetworkManager";
+
+    /// `NM_ACTIVE_CONNECTION_STATE_ACTIVATED`.
+    const ACTIVATED: u32 = 2;
+
+    /// NetworkManager on the system bus, connected on first use and again
+    /// after a call failed.
+    #[derive(Default)]
+    pub struct NetworkManager {
+        bus: Option<Connection>,
+    }
+
+    impl NetworkManager {
+        /// Whether the connection named `id` is up.
+        pub fn is_active(&mut self, id: &str) -> Result<bool, BlockError> {
+            self.with_bus(|bus| Ok(active_connection(bus, id)?.is_some()))
+        }
+
+        /// Activates or deactivates the connection named `id`.
+        pub fn set_active(&mut self, id: &str, active: bool) -> Result<(), BlockError> {
+            self.with_bus(|bus| {
+                let manager = proxy(
+                    bus,
+                    "/org/freedesktop/NetworkManager",
+                    "org.freedesktop.NetworkManager",
+                )?;
+                if !active {
+                    if let Some(path) = active_connection(bus, id)? {
+                        let _: () = manager
+                            .call("DeactivateConnection", &(path,))
+                            .map_err(dbus_error)?;
+                    }
+                    return Ok(());
+                }
+
+                let settings = saved_connection(bus, id)?.ok_or_else(|| {
+                    BlockError::InvalidData(format!("no NetworkManager connection '{}'", id))
+                })?;
+                let none = ObjectPath::from_static_str_unchecked("/");
+                let _: OwnedObjectPath = manager
+                    .call("ActivateConnection", &(settings, &none, &none))
+                    .map_err(dbus_error)?;
+                Ok(())
+            })
+        }
+
+        fn with_bus<T>(
+            &mut self,
+            call: impl FnOnce(&Connection) -> Result<T, BlockError>,
+        ) -> Result<T, BlockError> {
+            let bus = match &mut self.bus {
+                Some(bus) => bus,
+                None => self.bus.insert(Connection::system().map_err(dbus_error)?),
+            };
+
+            let result = call(bus);
+            if result.is_err() {
+                self.bus = None;
+            }
+            result
+        }
+    }
+
+    fn proxy<'a>(
+        bus: &Connection,
+        path: &'a str,
+        interface: &'a str,
+    ) -> Result<Proxy<'a>, BlockError> {
+        Proxy::new(bus, SERVICE, path, interface).map_err(dbus_error)
+    }
+
+    /// The activated connection named `id`.
+    fn active_connection(
+        bus: &Connection,
+        id: &str,
+    ) -> Result<Option<OwnedObjectPath>, BlockError> {
+        let manager = proxy(
+            bus,
+            "/org/freedesktop/NetworkManager",
+            "org.freedesktop.NetworkManager",
+        )?;
+        let paths: Vec<OwnedObjectPath> = manager
+            .get_property("ActiveConnections")
+            .map_err(dbus_error)?;
+
+        for path in &paths {
+            let active = proxy(
+                bus,
+                path.as_str(),
+                "org.freedesktop.NetworkManager.Connection.Active",
+            )?;
+            let name: String = active.get_property("Id").map_err(dbus_error)?;
+            let state: u32 = active.get_property("State").map_err(dbus_error)?;
+            if name == id && state == ACTIVATED {
+                return Ok(Some(path.clone()));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The saved connection named `id`.
+    fn saved_connection(bus: &Connection, id: &str) -> Result<Option<OwnedObjectPath>, BlockError> {
+        let settings = proxy(
+            bus,
+            "/org/freedesktop/NetworkManager/Settings",
+            "org.freedesktop.NetworkManager.Settings",
+        )?;
+        let paths: Vec<OwnedObjectPath> =
+            settings.call("ListConnections", &()).map_err(dbus_error)?;
+
+        for path in &paths {
+            let connection = proxy(
+                bus,
+                path.as_str(),
+                "org.freedesktop.NetworkManager.Settings.Connection",
+            )?;
+            let values: HashMap<String, HashMap<String, OwnedValue>> =
+                connection.call("GetSettings", &()).map_err(dbus_error)?;
+            let name = values
+                .get("connection")
+                .and_then(|section| section.get("id"))
+                .and_then(|value| String::try_from(&**value).ok());
+            if name.as_deref() == Some(id) {
+                return Ok(Some(path.clone()));
+            }
+        }
+        Ok(None)
+    }
+
+    fn dbus_error(error: zbus::Error) -> BlockError {
+        BlockError::CommandFailed(format!("NetworkManager D-Bus call failed: {}", error))
+    }
+}
+
+#[cfg(not(feature = "dbus"))]
+mod nm {
+    use crate::errors::BlockError;
+    use std::process::Command;
+
+    /// Without the `dbus` feature nmcli is asked instead.
+    #[derive(Default)]
+    pub struct NetworkManager;
+
+    impl NetworkManager {
+        pub fn is_active(&mut self, id: &str) -> Result<bool, BlockError> {
+            let output = nmcli(&["-g", "NAME", "connection", "show", "--active"])?;
+            Ok(output.lines().any(|name| name.replace("\\:", ":") == id))
+        }
+
+        pub fn set_active(&mut self, id: &str, active: bool) -> Result<(), BlockError> {
+            let action = if active { "up" } else { "down" };
+            nmcli(&["connection", action, "id", id]).map(|_| ())
+        }
+    }
+
+    fn nmcli(args: &[&str]) -> Result<String, BlockError> {
+        let output = Command::new("nmcli").args(args).output()?;
+        if !output.status.success() {
+            return Err(BlockError::CommandFailed(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
@@ -0,0 +1,228 @@
+use super::{Block, BlockOutput, FocusContext};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Output a block produced on its worker thread. `generation` tells results
+/// of blocks that have since been replaced apart from current ones.
+pub struct BlockUpdate {
+    pub generation: u64,
+    pub index: usize,
+    pub output: Option<BlockOutput>,
+    /// Answers a refresh rather than a focus change or click.
+    pub refreshed: bool,
+}
+
+enum BlockRequest {
+    Refresh,
+    Signal,
+    Focus(FocusContext),
+    Click(u8),
+    Changed,
+    Stop,
+}
+
+type StopHook = Box<dyn FnOnce() + Send>;
+
+/// Set when a block's worker is dropped, so the threads the block started
+/// end along with it.
+#[derive(Default)]
+struct StopSignal {
+    stopped: AtomicBool,
+    hook: Mutex<Option<StopHook>>,
+}
+
+impl StopSignal {
+    fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::SeqCst)
+    }
+
+    fn stop(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        let hook = self.hook.lock().ok().and_then(|mut hook| hook.take());
+        if let Some(hook) = hook {
+            hook();
+        }
+    }
+}
+
+/// Lets a block that watches for changes itself ask its worker for new
+/// output.
+pub struct BlockNotifier {
+    requests: Sender<BlockRequest>,
+    stop: Arc<StopSignal>,
+}
+
+impl BlockNotifier {
+    /// Asks for new output; false once the block was stopped.
+    pub fn notify(&self) -> bool {
+        !self.is_stopped() && self.requests.send(BlockRequest::Changed).is_ok()
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stop.is_stopped()
+    }
+
+    /// Runs `hook` when the block is stopped, or right away if it already
+    /// is, to wake a watcher blocked waiting for changes. Replaces any
+    /// earlier hook.
+    pub fn on_stop(&self, hook: impl FnOnce() + Send + 'static) {
+        let Ok(mut slot) = self.stop.hook.lock() else {
+            return;
+        };
+        if self.stop.is_stopped() {
+            drop(slot);
+            hook();
+        } else {
+            *slot = Some(Box::new(hook));
+        }
+    }
+}
+
+/// Runs a block on its own thread so a slow command can't stall the event
+/// loop. The bar only ever reads the last output the block sent back.
+pub struct BlockWorker {
+    requests: Sender<BlockRequest>,
+    stop: Arc<StopSignal>,
+    interval: Duration,
+    follows_focus: bool,
+    follows_power_supply: bool,
+    output: Option<BlockOutput>,
+    last_refresh: Instant,
+    /// A refresh was requested and hasn't been answered yet.
+    pending: bool,
+}
+
+impl BlockWorker {
+    pub fn spawn(
+        mut block: Box<dyn Block>,
+        index: usize,
+        generation: u64,
+        results: Sender<BlockUpdate>,
+    ) -> Self {
+        let (requests, receiver) = mpsc::channel();
+        let interval = block.interval();
+        let follows_focus = block.follows_focus();
+        let follows_power_supply = block.follows_power_supply();
+        let stop = Arc::new(StopSignal::default());
+        let notifier = BlockNotifier {
+            requests: requests.clone(),
+            stop: stop.clone(),
+        };
+
+        let stopped = stop.clone();
+        let spawned = std::thread::Builder::new()
+            .name(format!("oxwm-block-{}", index))
+            .spawn(move || {
+                block.subscribe(notifier);
+                for request in receiver {
+                    // Requests queued before the worker was dropped.
+                    if stopped.is_stopped() {
+                        break;
+                    }
+                    let refreshed = matches!(request, BlockRequest::Refresh | BlockRequest::Signal);
+                    let changed = match request {
+                        BlockRequest::Refresh => true,
+                        BlockRequest::Signal => {
+                            block.signal();
+                            true
+                        }
+                        BlockRequest::Focus(context) => block.set_focus(&context),
+                        BlockRequest::Click(button) => block.click(button),
+                        BlockRequest::Changed => true,
+                        BlockRequest::Stop => break,
+                    };
+                    if !changed {
+                        continue;
+                    }
+                    let update = BlockUpdate {
+                        generation,
+                        index,
+                        output: block.content().ok(),
+                        refreshed,
+                    };
+                    if results.send(update).is_err() {
+                        break;
+                    }
+                }
+            });
+        if let Err(e) = spawned {
+            eprintln!("Failed to start block thread: {}", e);
+        }
+
+        let mut worker = Self {
+            requests,
+            stop,
+            interval,
+            follows_focus,
+            follows_power_supply,
+            output: None,
+            last_refresh: Instant::now(),
+            pending: false,
+        };
+        worker.refresh();
+        worker
+    }
+
+    /// Asks the block for fresh text unless it is still working on the
+    /// last request.
+    pub fn refresh(&mut self) {
+        if self.pending {
+            return;
+        }
+        self.pending = self.requests.send(BlockRequest::Refresh).is_ok();
+        self.last_refresh = Instant::now();
+    }
+
+    /// Asks for fresh text bypassing any output the block keeps, even
+    /// while an earlier refresh is still running.
+    pub fn signal(&mut self) {
+        self.pending = self.requests.send(BlockRequest::Signal).is_ok();
+        self.last_refresh = Instant::now();
+    }
+
+    pub fn is_due(&self, now: Instant) -> bool {
+        !self.pending && now.duration_since(self.last_refresh) >= self.interval
+    }
+
+    pub fn set_focus(&self, context: &FocusContext) {
+        if self.follows_focus {
+            let _ = self.requests.send(BlockRequest::Focus(context.clone()));
+        }
+    }
+
+    pub fn click(&self, button: u8) {
+        let _ = self.requests.send(BlockRequest::Click(button));
+    }
+
+    /// Stores output sent back by the block; true if it changed.
+    pub fn receive(&mut self, update: BlockUpdate) -> bool {
+        if update.refreshed {
+            self.pending = false;
+        }
+        let changed = self.output != update.output;
+        self.output = update.output;
+        changed
+    }
+
+    pub fn output(&self) -> Option<&BlockOutput> {
+        self.output.as_ref()
+    }
+
+    pub fn follows_focus(&self) -> bool {
+        self.follows_focus
+    }
+
+    pub fn follows_power_supply(&self) -> bool {
+        self.follows_power_supply
+    }
+}
+
+impl Drop for BlockWorker {
+    fn drop(&mut self) {
+        self.stop.stop();
+        // Wakes the thread, which the block's notifier keeps waiting.
+        let _ = self.requests.send(BlockRequest::Stop);
+    }
+}
@@ -0,0 +1,66 @@
+use crate::errors::X11Error;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::*;
+use x11rb::rust_connection::RustConnection;
+
+/// Height of the strip along the screen edge.
+const EDGE_HEIGHT: u16 = 1;
+
+/// Invisible strip along a screen edge that reports the pointer entering
+/// it, so auto-hidden bars can be revealed.
+pub struct EdgeTrigger {
+    window: Window,
+}
+
+impl EdgeTrigger {
+    /// A strip at the top of `y`, or ending at it when `bottom` is set.
+    pub fn new(
+        connection: &RustConnection,
+        root: Window,
+        x: i16,
+        y: i16,
+        width: u16,
+        bottom: bool,
+    ) -> Result<Self, X11Error> {
+        let window = connection.generate_id()?;
+        let y = if bottom { y - EDGE_HEIGHT as i16 } else { y };
+
+        connection.create_window(
+            0,
+            window,
+            root,
+            x,
+            y,
+            width,
+            EDGE_HEIGHT,
+            0,
+            WindowClass::INPUT_ONLY,
+            x11rb::COPY_FROM_PARENT,
+            &CreateWindowAux::new()
+                .event_mask(EventMask::ENTER_WINDOW)
+                .override_redirect(1),
+        )?;
+        connection.map_window(window)?;
+        connection.flush()?;
+
+        Ok(Self { window })
+    }
+
+    pub fn window(&self) -> Window {
+        self.window
+    }
+
+    /// Keeps the strip above clients, which would otherwise cover it.
+    pub fn raise(&self, connection: &RustConnection) -> Result<(), X11Error> {
+        connection.configure_window(
+            self.window,
+            &ConfigureWindowAux::new().stack_mode(StackMode::ABOVE),
+        )?;
+        Ok(())
+    }
+
+    pub fn destroy(&self, connection: &RustConnection) -> Result<(), X11Error> {
+        connection.destroy_window(self.window)?;
+        Ok(())
+    }
+}
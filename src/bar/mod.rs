@@ -1,12 +1,70 @@
 mod bar;
 mod blocks;
+mod edge;
 pub mod font;
+pub mod renderer;
 
-pub use bar::Bar;
-pub use blocks::{BlockCommand, BlockConfig};
+pub use bar::{Bar, BarRegion};
+pub use blocks::{
+    BatteryAlerts, BlockAlign, BlockClicks, BlockCommand, BlockConfig, BlockManager, BlockOutput,
+    ClockZone, DEFAULT_BLOCK_ERROR, FocusContext, HttpSource, MinWidth, PeripheralIcons, Segment,
+    Shape, Thresholds, Urgency, VpnTarget,
+};
+pub use edge::EdgeTrigger;
 
-// Bar position (for future use)
-#[derive(Debug, Clone, Copy)]
+use crate::keyboard::{Arg, KeyAction};
+
+/// Kind of bar region a click binding applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegionKind {
+    Tag,
+    Layout,
+    Indicator,
+    Title,
+    Block,
+}
+
+/// Action run when a bar region is clicked or double-clicked with the left
+/// button. Tag actions on the tag region act on the clicked tag.
+#[derive(Debug, Clone)]
+pub struct BarClick {
+    pub region: RegionKind,
+    pub double: bool,
+    pub action: KeyAction,
+    pub arg: Arg,
+}
+
+/// What identifies the monitor at the left of each bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MonitorLabel {
+    None,
+    /// Monitor index, starting at 1.
+    Number,
+    /// RandR output name, falling back to the number.
+    Name,
+}
+
+/// How tags with urgent windows blink their background until viewed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UrgentBlink {
+    /// Time the background stays on, and then off.
+    pub interval_ms: u64,
+    /// Time after which a tag stops blinking and stays urgent; 0 blinks
+    /// until the tag is viewed.
+    pub duration_ms: u64,
+}
+
+/// What precedes the focused window's title in the bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TitleClass {
+    None,
+    /// WM_CLASS class, e.g. "firefox".
+    Class,
+    /// WM_CLASS instance.
+    Instance,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BarPosition {
     Top,
     Bottom,
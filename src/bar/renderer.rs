@@ -0,0 +1,106 @@
+use super::font::{DrawingSurface, Font};
+use crate::errors::X11Error;
+use x11::xlib::{_XDisplay, Colormap, Drawable, Visual};
+
+/// Drawing primitives used by the bar and tab bar.
+///
+/// All drawing goes to an off-screen buffer; `copy` presents it.
+pub trait Renderer {
+    fn fill_rect(&self, color: u32, x: i32, y: i32, width: u32, height: u32);
+    fn draw_text(&self, font: &Font, color: u32, x: i16, y: i16, text: &str);
+    fn copy(&self, width: u32, height: u32);
+
+    /// Fills a rectangle row by row, blending from `top` to `bottom`.
+    fn fill_vertical_gradient(
+        &self,
+        top: u32,
+        bottom: u32,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+    ) {
+        for row in 0..height {
+            let t = if height > 1 {
+                row as f32 / (height - 1) as f32
+            } else {
+                0.0
+            };
+            self.fill_rect(blend(top, bottom, t), x, y + row as i32, width, 1);
+        }
+    }
+}
+
+/// Linearly interpolates between two `0xRRGGBB` colors; `t` is clamped to 0..=1.
+pub fn blend(from: u32, to: u32, t: f32) -> u32 {
+    let t = t.clamp(0.0, 1.0);
+    let channel = |shift: u32| {
+        let a = ((from >> shift) & 0xff) as f32;
+        let b = ((to >> shift) & 0xff) as f32;
+        ((a + (b - a) * t).round() as u32) << shift
+    };
+    channel(16) | channel(8) | channel(0)
+}
+
+/// Xlib/Xft renderer drawing into a pixmap that is copied onto `window`.
+pub struct XftRenderer {
+    display: *mut _XDisplay,
+    window: Drawable,
+    surface: DrawingSurface,
+}
+
+impl XftRenderer {
+    pub fn new(
+        display: *mut _XDisplay,
+        window: Drawable,
+        width: u32,
+        height: u32,
+        visual: *mut Visual,
+        colormap: Colormap,
+    ) -> Result<Self, X11Error> {
+        let surface = DrawingSurface::new(display, window, width, height, visual, colormap)?;
+
+        Ok(Self {
+            display,
+            window,
+            surface,
+        })
+    }
+}
+
+impl Renderer for XftRenderer {
+    fn fill_rect(&self, color: u32, x: i32, y: i32, width: u32, height: u32) {
+        let pixmap = self.surface.pixmap();
+        unsafe {
+            let gc = x11::xlib::XCreateGC(self.display, pixmap, 0, std::ptr::null_mut());
+            x11::xlib::XSetForeground(self.display, gc, color as u64);
+            x11::xlib::XFillRectangle(self.display, pixmap, gc, x, y, width, height);
+            x11::xlib::XFreeGC(self.display, gc);
+        }
+    }
+
+    fn draw_text(&self, font: &Font, color: u32, x: i16, y: i16, text: &str) {
+        self.surface.font_draw().draw_text(font, color, x, y, text);
+    }
+
+    fn copy(&self, width: u32, height: u32) {
+        let pixmap = self.surface.pixmap();
+        unsafe {
+            let gc = x11::xlib::XCreateGC(self.display, pixmap, 0, std::ptr::null_mut());
+            x11::xlib::XCopyArea(
+                self.display,
+                pixmap,
+                self.window,
+                gc,
+                0,
+                0,
+                width,
+                height,
+                0,
+                0,
+            );
+            x11::xlib::XFreeGC(self.display, gc);
+            x11::xlib::XSync(self.display, 1);
+        }
+    }
+}
@@ -19,6 +19,12 @@ fn main() -> Result<(), MainError> {
         Args::Error(e) => return Err(e),
     };
 
+    // Autostart entries and systemd user units look at the desktop name.
+    if std::env::var_os("XDG_CURRENT_DESKTOP").is_none() {
+        // SAFETY: no other threads have been started yet.
+        unsafe { std::env::set_var("XDG_CURRENT_DESKTOP", oxwm::xdg_autostart::DESKTOP_NAME) };
+    }
+
     let (config, config_warning) = load_config(arguments.get(2))?;
 
     let mut window_manager = match oxwm::window_manager::WindowManager::new(config) {
@@ -0,0 +1,80 @@
+use oxwm::ipc;
+use oxwm::json::Json;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        None | Some("--help") => {
+            print_help();
+            return ExitCode::SUCCESS;
+        }
+        Some("--version") => {
+            println!("oxwm-msg {}", env!("CARGO_PKG_VERSION"));
+            return ExitCode::SUCCESS;
+        }
+        _ => {}
+    }
+
+    let response = match send(&args.join(" ")) {
+        Ok(response) => response,
+        Err(e) => {
+            eprintln!("oxwm-msg: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if response.get("success").and_then(Json::as_bool) == Some(true) {
+        match response.get("result") {
+            None | Some(Json::Null) => {}
+            Some(result) => println!("{}", result),
+        }
+        ExitCode::SUCCESS
+    } else {
+        let error = response
+            .get("error")
+            .and_then(Json::as_str)
+            .unwrap_or("request failed");
+        eprintln!("oxwm-msg: {}", error);
+        ExitCode::FAILURE
+    }
+}
+
+/// Sends one request line and reads the response line.
+fn send(request: &str) -> Result<Json, String> {
+    let path = ipc::socket_path();
+    let mut stream = UnixStream::connect(&path)
+        .map_err(|e| format!("can't connect to {}: {}", path.display(), e))?;
+    stream
+        .write_all(format!("{}\n", request).as_bytes())
+        .map_err(|e| format!("can't send request: {}", e))?;
+
+    let mut line = String::new();
+    BufReader::new(stream)
+        .read_line(&mut line)
+        .map_err(|e| format!("can't read response: {}", e))?;
+    if line.is_empty() {
+        return Err("oxwm closed the connection".to_string());
+    }
+    Json::parse(line.trim()).map_err(|e| format!("invalid response: {}", e))
+}
+
+fn print_help() {
+    println!("oxwm-msg - Send a command to a running oxwm\n");
+    println!("USAGE:");
+    println!("    oxwm-msg <COMMAND> [ARGS...]");
+    println!("    oxwm-msg '<JSON REQUEST>'\n");
+    println!("COMMANDS:");
+    println!("    get_tree                Print monitors, tags and clients as JSON");
+    println!("    view_tag <INDEX>        View a tag, counting from 0");
+    println!("    create_tag [NAME]       Create a tag and view it");
+    println!("    set_layout <NAME>       Switch the layout, e.g. tiling or monocle");
+    println!("    focus_window <ID>       Focus a window, viewing its tag if needed");
+    println!("    set_tag_note <INDEX> [NOTE]");
+    println!("                            Note a tag beside the layout symbol; no note clears it");
+    println!("    reload                  Reload the config file\n");
+    println!("SOCKET:");
+    println!("    $OXWM_SOCKET, else $XDG_RUNTIME_DIR/oxwm.sock");
+}
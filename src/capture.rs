@@ -0,0 +1,115 @@
+use std::ffi::OsStr;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{ConnectionExt, EventMask, GrabMode, GrabStatus, KeyButMask, Window};
+use x11rb::rust_connection::RustConnection;
+
+/// Screen recorders looked for by default when deciding whether to show the
+/// recording indicator.
+pub const DEFAULT_PROCESSES: &[&str] = &[
+    "obs",
+    "simplescreenrecorder",
+    "gpu-screen-recorder",
+    "peek",
+    "kazam",
+    "vokoscreenNG",
+    "recordmydesktop",
+];
+
+/// Returns true when a process whose name is in `processes` is running.
+pub fn is_capture_active(processes: &[String]) -> bool {
+    if processes.is_empty() {
+        return false;
+    }
+
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return false;
+    };
+
+    entries.flatten().any(|entry| {
+        let is_pid = entry
+            .file_name()
+            .to_str()
+            .is_some_and(|name| name.bytes().all(|b| b.is_ascii_digit()));
+        if !is_pid {
+            return false;
+        }
+
+        let program = program_name(&entry.path());
+        let comm = std::fs::read_to_string(entry.path().join("comm")).unwrap_or_default();
+        processes
+            .iter()
+            .any(|p| program.as_deref() == Some(p.as_str()) || p == comm.trim_end())
+    })
+}
+
+/// File name of the program a process was started as. Unlike `comm` it
+/// isn't cut to 15 characters.
+fn program_name(process: &Path) -> Option<String> {
+    let cmdline = std::fs::read(process.join("cmdline")).ok()?;
+    let program = cmdline.split(|&b| b == 0).next()?;
+    let name = Path::new(OsStr::from_bytes(program)).file_name()?;
+    Some(name.to_string_lossy().into_owned())
+}
+
+/// Returns true when another client actively grabs the keyboard or pointer.
+/// X has no way to ask, so this grabs both itself and lets go right away;
+/// the grab fails while someone else holds one.
+///
+/// The probe isn't free: the focused client sees a FocusOut and FocusIn,
+/// keys pressed during it go to oxwm, and an open menu or drag counts as a
+/// grab. Held buttons are skipped since they grab the pointer implicitly.
+pub fn is_input_grabbed(connection: &RustConnection, root: Window) -> bool {
+    let buttons = KeyButMask::BUTTON1
+        | KeyButMask::BUTTON2
+        | KeyButMask::BUTTON3
+        | KeyButMask::BUTTON4
+        | KeyButMask::BUTTON5;
+    let button_held = connection
+        .query_pointer(root)
+        .ok()
+        .and_then(|cookie| cookie.reply().ok())
+        .is_none_or(|pointer| pointer.mask.intersects(buttons));
+    if button_held {
+        return false;
+    }
+
+    let keyboard = connection
+        .grab_keyboard(
+            false,
+            root,
+            x11rb::CURRENT_TIME,
+            GrabMode::ASYNC,
+            GrabMode::ASYNC,
+        )
+        .ok()
+        .and_then(|cookie| cookie.reply().ok())
+        .map(|reply| reply.status);
+    if keyboard == Some(GrabStatus::SUCCESS) {
+        let _ = connection.ungrab_keyboard(x11rb::CURRENT_TIME);
+    }
+
+    let grabbed = keyboard == Some(GrabStatus::ALREADY_GRABBED) || {
+        let pointer = connection
+            .grab_pointer(
+                false,
+                root,
+                EventMask::NO_EVENT,
+                GrabMode::ASYNC,
+                GrabMode::ASYNC,
+                x11rb::NONE,
+                x11rb::NONE,
+                x11rb::CURRENT_TIME,
+            )
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .map(|reply| reply.status);
+        if pointer == Some(GrabStatus::SUCCESS) {
+            let _ = connection.ungrab_pointer(x11rb::CURRENT_TIME);
+        }
+        pointer == Some(GrabStatus::ALREADY_GRABBED)
+    };
+    let _ = connection.flush();
+    grabbed
+}
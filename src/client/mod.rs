@@ -5,6 +5,8 @@ pub type TagMask = u32;
 #[derive(Debug, Clone)]
 pub struct Client {
     pub name: String,
+    pub class: String,
+    pub instance: String,
     pub min_aspect: f32,
     pub max_aspect: f32,
     pub x_position: i16,
@@ -30,9 +32,26 @@ pub struct Client {
     pub is_fixed: bool,
     pub is_floating: bool,
     pub is_urgent: bool,
+    /// Title changed or bell rang while unfocused; cleared on focus.
+    pub has_activity: bool,
     pub never_focus: bool,
     pub old_state: bool,
     pub is_fullscreen: bool,
+    pub is_maximized_vertical: bool,
+    pub is_maximized_horizontal: bool,
+    /// Geometry from before the first maximize.
+    pub maximize_restore: Option<(i16, i16, u16, u16)>,
+    /// Whether the client floated before the first maximize.
+    pub maximize_was_floating: bool,
+    /// `_NET_WM_STATE_BELOW`: stacked under every other client.
+    pub is_below: bool,
+    pub is_shaped: bool,
+    pub float_preset: Option<usize>,
+    /// Indices of `retag_on_title` rules matching the current title.
+    pub title_rules: Vec<usize>,
+    /// Programs started from this terminal swallow it.
+    pub is_terminal: bool,
+    pub no_swallow: bool,
     pub next: Option<Window>,
     pub stack_next: Option<Window>,
     pub monitor_index: usize,
@@ -43,6 +62,8 @@ impl Client {
     pub fn new(window: Window, monitor_index: usize, tags: TagMask) -> Self {
         Self {
             name: String::new(),
+            class: String::new(),
+            instance: String::new(),
             min_aspect: 0.0,
             max_aspect: 0.0,
             x_position: 0,
@@ -68,9 +89,20 @@ impl Client {
             is_fixed: false,
             is_floating: false,
             is_urgent: false,
+            has_activity: false,
             never_focus: false,
             old_state: false,
             is_fullscreen: false,
+            is_maximized_vertical: false,
+            is_maximized_horizontal: false,
+            maximize_restore: None,
+            maximize_was_floating: false,
+            is_below: false,
+            is_shaped: false,
+            float_preset: None,
+            title_rules: Vec::new(),
+            is_terminal: false,
+            no_swallow: false,
             next: None,
             stack_next: None,
             monitor_index,
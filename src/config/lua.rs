@@ -24,7 +24,36 @@ pub fn parse_lua_config(
         .exec()
         .map_err(|e| ConfigError::LuaError(format!("{}", e)))?;
 
-    let builder_data = builder.borrow().clone();
+    let mut builder_data = builder.borrow().clone();
+    let modkey = builder_data.modkey;
+    for binding in builder_data.keybindings.iter_mut().chain(
+        builder_data
+            .modes
+            .iter_mut()
+            .flat_map(|m| m.keybindings.iter_mut()),
+    ) {
+        binding.set_modkey(modkey);
+    }
+
+    // The root window's name stands in for the blocks, as dwm's status.
+    if builder_data.bar_root_status {
+        builder_data.status_blocks = vec![crate::bar::BlockConfig {
+            format: String::new(),
+            command: crate::bar::BlockCommand::RootName,
+            interval_secs: 60,
+            color: builder_data.scheme_normal.foreground,
+            underline: false,
+            background: None,
+            gradient: None,
+            popup: None,
+            sensitive: false,
+            focused_only: false,
+            min_width: None,
+            align: Default::default(),
+            clicks: Default::default(),
+            signal: None,
+        }];
+    }
 
     Ok(crate::Config {
         border_width: builder_data.border_width,
@@ -52,6 +81,63 @@ pub fn parse_lua_config(
         autostart: builder_data.autostart,
         auto_tile: builder_data.auto_tile,
         hide_vacant_tags: builder_data.hide_vacant_tags,
+        systemd_notify: builder_data.systemd_notify,
+        systemd_autostart_units: builder_data.systemd_autostart_units,
+        xdg_autostart: builder_data.xdg_autostart,
+        recording_indicator: builder_data.recording_indicator,
+        recording_processes: builder_data.recording_processes,
+        recording_grabs: builder_data.recording_grabs,
+        warp_pointer_on_tag_switch: builder_data.warp_pointer_on_tag_switch,
+        float_presets: builder_data.float_presets,
+        remember_floating_geometry: builder_data.remember_floating_geometry,
+        status_file: builder_data.status_file,
+        remote_control: builder_data.remote_control,
+        bar_click_through: builder_data.bar_click_through,
+        bar_corner_radius: builder_data.bar_corner_radius,
+        window_corner_radius: builder_data.window_corner_radius,
+        bar_border_width: builder_data.bar_border_width,
+        bar_border_color: builder_data.bar_border_color,
+        bar_shadow_size: builder_data.bar_shadow_size,
+        bar_monitor_label: builder_data.bar_monitor_label,
+        monitor_configs: builder_data.monitor_configs,
+        monitor_profiles: builder_data.monitor_profiles,
+        display_setup: builder_data.display_setup,
+        assigned_tag_focus: builder_data.assigned_tag_focus,
+        tag_links: builder_data.tag_links,
+        modes: builder_data.modes,
+        focus_on_map: builder_data.focus_on_map,
+        hidden_tag_action: builder_data.hidden_tag_action,
+        bar_tag_flash: builder_data.bar_tag_flash,
+        tag_client_limit: builder_data.tag_client_limit,
+        tag_overflow: builder_data.tag_overflow,
+        float_fixed_size: builder_data.float_fixed_size,
+        float_utility: builder_data.float_utility,
+        float_below_area: builder_data.float_below_area,
+        lock_command: builder_data.lock_command,
+        lock_blank: builder_data.lock_blank,
+        power_hooks: builder_data.power_hooks,
+        activity_color: builder_data.activity_color,
+        visual_bell: builder_data.visual_bell,
+        bell_urgent: builder_data.bell_urgent,
+        presentation: builder_data.presentation,
+        fade_duration_ms: builder_data.fade_duration_ms,
+        pinned_tags: builder_data.pinned_tags,
+        bar_clicks: builder_data.bar_clicks,
+        bar_dead_zone: builder_data.bar_dead_zone,
+        double_click_ms: builder_data.double_click_ms,
+        bar_scroll_skip_empty: builder_data.bar_scroll_skip_empty,
+        scratchpads: builder_data.scratchpads,
+        auto_reload: builder_data.auto_reload,
+        snap_distance: builder_data.snap_distance,
+        swallow: builder_data.swallow,
+        bar_title_class: builder_data.bar_title_class,
+        bar_title_class_color: builder_data.bar_title_class_color,
+        bar_root_status: builder_data.bar_root_status,
+        bar_window_count: builder_data.bar_window_count,
+        tag_styles: builder_data.tag_styles,
+        second_bar_blocks: builder_data.second_bar_blocks,
+        bar_autohide_ms: builder_data.bar_autohide_ms,
+        bar_urgent_blink: builder_data.bar_urgent_blink,
         path: None,
     })
 }
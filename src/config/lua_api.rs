@@ -5,7 +5,7 @@ use std::rc::Rc;
 use crate::ColorScheme;
 use crate::bar::BlockConfig;
 use crate::errors::ConfigError;
-use crate::keyboard::handlers::{Arg, KeyAction, KeyBinding, KeyPress};
+use crate::keyboard::handlers::{Arg, Condition, KeyAction, KeyBinding, KeyPress};
 use crate::keyboard::keysyms::{self, Keysym};
 use x11rb::protocol::xproto::KeyButMask;
 
@@ -36,6 +36,63 @@ pub struct ConfigBuilder {
     pub autostart: Vec<String>,
     pub auto_tile: bool,
     pub hide_vacant_tags: bool,
+    pub systemd_notify: bool,
+    pub systemd_autostart_units: bool,
+    pub xdg_autostart: bool,
+    pub recording_indicator: String,
+    pub recording_processes: Vec<String>,
+    pub recording_grabs: bool,
+    pub warp_pointer_on_tag_switch: bool,
+    pub float_presets: Vec<crate::FloatPreset>,
+    pub remember_floating_geometry: bool,
+    pub status_file: Option<String>,
+    pub remote_control: Option<crate::RemoteControl>,
+    pub bar_click_through: bool,
+    pub bar_corner_radius: u16,
+    pub window_corner_radius: u16,
+    pub bar_border_width: u16,
+    pub bar_border_color: u32,
+    pub bar_shadow_size: u16,
+    pub bar_monitor_label: crate::bar::MonitorLabel,
+    pub monitor_configs: Vec<crate::MonitorConfig>,
+    pub monitor_profiles: Vec<crate::MonitorProfile>,
+    pub display_setup: Vec<crate::OutputSetup>,
+    pub assigned_tag_focus: bool,
+    pub tag_links: Vec<crate::TagLink>,
+    pub modes: Vec<crate::KeyMode>,
+    pub focus_on_map: crate::FocusOnMap,
+    pub hidden_tag_action: crate::HiddenTagAction,
+    pub bar_tag_flash: bool,
+    pub tag_client_limit: usize,
+    pub tag_overflow: crate::TagOverflow,
+    pub float_fixed_size: bool,
+    pub float_utility: bool,
+    pub float_below_area: u32,
+    pub lock_command: Option<String>,
+    pub lock_blank: bool,
+    pub power_hooks: crate::PowerHooks,
+    pub activity_color: Option<u32>,
+    pub visual_bell: crate::VisualBell,
+    pub bell_urgent: bool,
+    pub presentation: crate::PresentationMode,
+    pub fade_duration_ms: u64,
+    pub pinned_tags: Vec<crate::PinnedTag>,
+    pub bar_clicks: Vec<crate::bar::BarClick>,
+    pub bar_dead_zone: u16,
+    pub double_click_ms: u64,
+    pub bar_scroll_skip_empty: bool,
+    pub scratchpads: Vec<crate::Scratchpad>,
+    pub auto_reload: bool,
+    pub snap_distance: u32,
+    pub swallow: bool,
+    pub bar_title_class: crate::bar::TitleClass,
+    pub bar_title_class_color: Option<u32>,
+    pub bar_root_status: bool,
+    pub bar_window_count: Option<String>,
+    pub tag_styles: Vec<crate::TagStyle>,
+    pub second_bar_blocks: Option<Vec<crate::bar::BlockConfig>>,
+    pub bar_autohide_ms: u64,
+    pub bar_urgent_blink: Option<crate::bar::UrgentBlink>,
 }
 
 impl Default for ConfigBuilder {
@@ -63,25 +120,89 @@ impl Default for ConfigBuilder {
                 foreground: 0xffffff,
                 background: 0x000000,
                 underline: 0x444444,
+                gradient: None,
             },
             scheme_occupied: ColorScheme {
                 foreground: 0xffffff,
                 background: 0x000000,
                 underline: 0x444444,
+                gradient: None,
             },
             scheme_selected: ColorScheme {
                 foreground: 0xffffff,
                 background: 0x000000,
                 underline: 0x444444,
+                gradient: None,
             },
             scheme_urgent: ColorScheme {
                 foreground: 0xff5555,
                 background: 0x000000,
                 underline: 0xff5555,
+                gradient: None,
             },
             autostart: Vec::new(),
             auto_tile: false,
             hide_vacant_tags: false,
+            systemd_notify: false,
+            systemd_autostart_units: false,
+            xdg_autostart: false,
+            recording_indicator: "REC".to_string(),
+            recording_processes: crate::capture::DEFAULT_PROCESSES
+                .iter()
+                .map(|p| p.to_string())
+                .collect(),
+            recording_grabs: false,
+            warp_pointer_on_tag_switch: false,
+            float_presets: crate::FloatPreset::defaults(),
+            remember_floating_geometry: false,
+            status_file: None,
+            remote_control: None,
+            bar_click_through: false,
+            bar_corner_radius: 0,
+            window_corner_radius: 0,
+            bar_border_width: 0,
+            bar_border_color: 0x444444,
+            bar_shadow_size: 0,
+            bar_monitor_label: crate::bar::MonitorLabel::None,
+            monitor_configs: Vec::new(),
+            monitor_profiles: Vec::new(),
+            display_setup: Vec::new(),
+            assigned_tag_focus: true,
+            tag_links: Vec::new(),
+            modes: Vec::new(),
+            focus_on_map: crate::FocusOnMap::Always,
+            hidden_tag_action: crate::HiddenTagAction::Silent,
+            bar_tag_flash: false,
+            tag_client_limit: 0,
+            tag_overflow: crate::TagOverflow::NextTag,
+            float_fixed_size: true,
+            float_utility: true,
+            float_below_area: 0,
+            lock_command: None,
+            lock_blank: false,
+            power_hooks: crate::PowerHooks::default(),
+            activity_color: None,
+            visual_bell: crate::VisualBell::Off,
+            bell_urgent: false,
+            presentation: crate::PresentationMode::default(),
+            fade_duration_ms: 0,
+            pinned_tags: Vec::new(),
+            bar_clicks: Vec::new(),
+            bar_dead_zone: 0,
+            double_click_ms: 300,
+            bar_scroll_skip_empty: false,
+            scratchpads: Vec::new(),
+            auto_reload: true,
+            snap_distance: 32,
+            swallow: true,
+            bar_title_class: crate::bar::TitleClass::None,
+            bar_title_class_color: None,
+            bar_root_status: false,
+            bar_window_count: None,
+            tag_styles: Vec::new(),
+            second_bar_blocks: None,
+            bar_autohide_ms: 0,
+            bar_urgent_blink: None,
         }
     }
 }
@@ -95,14 +216,18 @@ pub fn register_api(lua: &Lua) -> Result<SharedBuilder, ConfigError> {
 
     register_spawn(lua, &oxwm_table, builder.clone())?;
     register_key_module(lua, &oxwm_table, builder.clone())?;
+    register_mode_module(lua, &oxwm_table, builder.clone())?;
     register_gaps_module(lua, &oxwm_table, builder.clone())?;
     register_border_module(lua, &oxwm_table, builder.clone())?;
     register_client_module(lua, &oxwm_table)?;
     register_layout_module(lua, &oxwm_table)?;
     register_tag_module(lua, &oxwm_table, builder.clone())?;
-    register_monitor_module(lua, &oxwm_table)?;
+    register_monitor_module(lua, &oxwm_table, builder.clone())?;
     register_rule_module(lua, &oxwm_table, builder.clone())?;
+    register_scratchpad_module(lua, &oxwm_table, builder.clone())?;
     register_bar_module(lua, &oxwm_table, builder.clone())?;
+    register_systemd_module(lua, &oxwm_table, builder.clone())?;
+    register_power_module(lua, &oxwm_table, builder.clone())?;
     register_misc(lua, &oxwm_table, builder.clone())?;
 
     lua.globals().set("oxwm", oxwm_table)?;
@@ -111,11 +236,24 @@ pub fn register_api(lua: &Lua) -> Result<SharedBuilder, ConfigError> {
 }
 
 fn register_spawn(lua: &Lua, parent: &Table, _builder: SharedBuilder) -> Result<(), ConfigError> {
-    let spawn = lua.create_function(|lua, cmd: Value| create_action_table(lua, "Spawn", cmd))?;
+    let spawn = lua.create_function(|lua, (cmd, opts): (Value, Option<Table>)| {
+        let Some(opts) = opts else {
+            return create_action_table(lua, "Spawn", cmd);
+        };
+
+        let arg = lua.create_table()?;
+        arg.set("__command", cmd)?;
+        arg.set("cwd", opts.get::<Option<String>>("cwd")?)?;
+        arg.set("env", opts.get::<Option<Table>>("env")?)?;
+        create_action_table(lua, "Spawn", Value::Table(arg))
+    })?;
     let spawn_terminal =
         lua.create_function(|lua, ()| create_action_table(lua, "SpawnTerminal", Value::Nil))?;
+    let spawn_terminal_in_cwd =
+        lua.create_function(|lua, ()| create_action_table(lua, "SpawnTerminalInCwd", Value::Nil))?;
     parent.set("spawn", spawn)?;
     parent.set("spawn_terminal", spawn_terminal)?;
+    parent.set("spawn_terminal_in_cwd", spawn_terminal_in_cwd)?;
     Ok(())
 }
 
@@ -128,11 +266,16 @@ fn register_key_module(
 
     let builder_clone = builder.clone();
     let bind = lua.create_function(move |lua, (mods, key, action): (Value, String, Value)| {
-        let modifiers = parse_modifiers_value(lua, mods)?;
+        let (modifiers, modkey) = parse_modifiers_value(lua, mods)?;
         let keysym = parse_keysym(&key)?;
         let (key_action, arg) = parse_action_value(lua, action)?;
 
-        let binding = KeyBinding::single_key(modifiers, keysym, key_action, arg);
+        let key_press = KeyPress {
+            modifiers,
+            modkey,
+            keysym,
+        };
+        let binding = KeyBinding::new(vec![key_press], key_action, arg);
         builder_clone.borrow_mut().keybindings.push(binding);
 
         Ok(())
@@ -147,10 +290,14 @@ fn register_key_module(
             let mods: Value = key_spec.get(1)?;
             let key: String = key_spec.get(2)?;
 
-            let modifiers = parse_modifiers_value(lua, mods)?;
+            let (modifiers, modkey) = parse_modifiers_value(lua, mods)?;
             let keysym = parse_keysym(&key)?;
 
-            key_presses.push(KeyPress { modifiers, keysym });
+            key_presses.push(KeyPress {
+                modifiers,
+                modkey,
+                keysym,
+            });
         }
 
         let (key_action, arg) = parse_action_value(lua, action)?;
@@ -166,6 +313,52 @@ fn register_key_module(
     Ok(())
 }
 
+fn register_mode_module(
+    lua: &Lua,
+    parent: &Table,
+    builder: SharedBuilder,
+) -> Result<(), ConfigError> {
+    let mode_table = lua.create_table()?;
+
+    let builder_clone = builder.clone();
+    let bind = lua.create_function(
+        move |lua, (name, mods, key, action): (String, Value, String, Value)| {
+            let (modifiers, modkey) = parse_modifiers_value(lua, mods)?;
+            let keysym = parse_keysym(&key)?;
+            let (key_action, arg) = parse_action_value(lua, action)?;
+
+            let key_press = KeyPress {
+                modifiers,
+                modkey,
+                keysym,
+            };
+            let binding = KeyBinding::new(vec![key_press], key_action, arg);
+
+            let mut builder = builder_clone.borrow_mut();
+            match builder.modes.iter_mut().find(|mode| mode.name == name) {
+                Some(mode) => mode.keybindings.push(binding),
+                None => builder.modes.push(crate::KeyMode {
+                    name,
+                    keybindings: vec![binding],
+                }),
+            }
+            Ok(())
+        },
+    )?;
+
+    let enter = lua.create_function(|lua, name: String| {
+        create_action_table(lua, "EnterMode", Value::String(lua.create_string(&name)?))
+    })?;
+
+    let exit = lua.create_function(|lua, ()| create_action_table(lua, "ExitMode", Value::Nil))?;
+
+    mode_table.set("bind", bind)?;
+    mode_table.set("enter", enter)?;
+    mode_table.set("exit", exit)?;
+    parent.set("mode", mode_table)?;
+    Ok(())
+}
+
 fn register_gaps_module(
     lua: &Lua,
     parent: &Table,
@@ -250,9 +443,16 @@ fn register_border_module(
         Ok(())
     })?;
 
+    let builder_clone = builder.clone();
+    let set_corner_radius = lua.create_function(move |_, radius: u16| {
+        builder_clone.borrow_mut().window_corner_radius = radius;
+        Ok(())
+    })?;
+
     border_table.set("set_width", set_width)?;
     border_table.set("set_focused_color", set_focused_color)?;
     border_table.set("set_unfocused_color", set_unfocused_color)?;
+    border_table.set("set_corner_radius", set_corner_radius)?;
     parent.set("border", border_table)?;
     Ok(())
 }
@@ -268,6 +468,12 @@ fn register_client_module(lua: &Lua, parent: &Table) -> Result<(), ConfigError>
     let toggle_floating =
         lua.create_function(|lua, ()| create_action_table(lua, "ToggleFloating", Value::Nil))?;
 
+    let stash =
+        lua.create_function(|lua, ()| create_action_table(lua, "StashWindow", Value::Nil))?;
+
+    let pop_stash =
+        lua.create_function(|lua, ()| create_action_table(lua, "PopStash", Value::Nil))?;
+
     let focus_stack = lua.create_function(|lua, dir: i32| {
         create_action_table(lua, "FocusStack", Value::Integer(dir as i64))
     })?;
@@ -276,11 +482,63 @@ fn register_client_module(lua: &Lua, parent: &Table) -> Result<(), ConfigError>
         create_action_table(lua, "MoveStack", Value::Integer(dir as i64))
     })?;
 
+    let toggle_maximize = lua.create_function(|lua, direction: Option<String>| {
+        let direction = direction.unwrap_or_else(|| "both".to_string());
+        if !matches!(direction.as_str(), "vertical" | "horizontal" | "both") {
+            return Err(mlua::Error::RuntimeError(format!(
+                "oxwm.client.toggle_maximize: unknown direction '{}'. use 'vertical', 'horizontal' or 'both'",
+                direction
+            )));
+        }
+        create_action_table(
+            lua,
+            "ToggleMaximize",
+            Value::String(lua.create_string(&direction)?),
+        )
+    })?;
+
+    let cycle_float_preset =
+        lua.create_function(|lua, ()| create_action_table(lua, "CycleFloatPreset", Value::Nil))?;
+
+    let inspect = lua.create_function(|lua, target: Option<String>| {
+        let target = target.unwrap_or_else(|| "focused".to_string());
+        if !matches!(target.as_str(), "focused" | "click") {
+            return Err(mlua::Error::RuntimeError(format!(
+                "oxwm.client.inspect: unknown target '{}'. use 'focused' or 'click'",
+                target
+            )));
+        }
+        create_action_table(
+            lua,
+            "InspectWindow",
+            Value::String(lua.create_string(&target)?),
+        )
+    })?;
+
+    let send_key = lua.create_function(|lua, (mods, key): (Table, String)| {
+        let keys = lua.create_table()?;
+        for i in 1..=mods.len()? {
+            let modifier: String = mods.get(i)?;
+            parse_modkey_string(&modifier)
+                .map_err(|e| mlua::Error::RuntimeError(format!("oxwm.client.send_key: {}", e)))?;
+            keys.push(modifier)?;
+        }
+        parse_keysym(&key)?;
+        keys.push(key)?;
+        create_action_table(lua, "SendKey", Value::Table(keys))
+    })?;
+
     client_table.set("kill", kill)?;
     client_table.set("toggle_fullscreen", toggle_fullscreen)?;
     client_table.set("toggle_floating", toggle_floating)?;
     client_table.set("focus_stack", focus_stack)?;
     client_table.set("move_stack", move_stack)?;
+    client_table.set("cycle_float_preset", cycle_float_preset)?;
+    client_table.set("inspect", inspect)?;
+    client_table.set("toggle_maximize", toggle_maximize)?;
+    client_table.set("send_key", send_key)?;
+    client_table.set("stash", stash)?;
+    client_table.set("pop_stash", pop_stash)?;
 
     parent.set("client", client_table)?;
     Ok(())
@@ -343,10 +601,19 @@ fn register_tag_module(
         create_action_table(lua, "ToggleView", Value::Integer(idx as i64))
     })?;
 
-    let move_to = lua.create_function(|lua, idx: i32| {
-        create_action_table(lua, "MoveToTag", Value::Integer(idx as i64))
+    let move_to = lua.create_function(|lua, target: Value| match target {
+        Value::String(s) if s.to_str()? == "+" => {
+            create_action_table(lua, "MoveToNewTag", Value::Nil)
+        }
+        Value::Integer(idx) => create_action_table(lua, "MoveToTag", Value::Integer(idx)),
+        _ => Err(mlua::Error::RuntimeError(
+            "oxwm.tag.move_to: expected a tag index or \"+\"".into(),
+        )),
     })?;
 
+    let move_to_new =
+        lua.create_function(|lua, ()| create_action_table(lua, "MoveToNewTag", Value::Nil))?;
+
     let toggletag = lua.create_function(|lua, idx: i32| {
         create_action_table(lua, "ToggleTag", Value::Integer(idx as i64))
     })?;
@@ -356,20 +623,88 @@ fn register_tag_module(
         Ok(())
     })?;
 
+    let builder_clone = builder.clone();
+    let set_warp_pointer = lua.create_function(move |_, enabled: bool| {
+        builder_clone.borrow_mut().warp_pointer_on_tag_switch = enabled;
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let pin = lua.create_function(
+        move |_, (tag, apps, options): (usize, Table, Option<Table>)| {
+            let mut pinned_apps = Vec::new();
+            for app in apps.sequence_values::<Table>() {
+                let app = app?;
+                let command: String = app.get("command")?;
+                let class: String = app.get("class")?;
+                pinned_apps.push(crate::PinnedApp { command, class });
+            }
+
+            let (layout, spawn_on_view) = match options {
+                Some(options) => (
+                    options.get::<Option<String>>("layout")?,
+                    options.get::<Option<bool>>("on_view")?.unwrap_or(false),
+                ),
+                None => (None, false),
+            };
+
+            let mut builder = builder_clone.borrow_mut();
+            builder.pinned_tags.retain(|pinned| pinned.tag != tag);
+            builder.pinned_tags.push(crate::PinnedTag {
+                tag,
+                apps: pinned_apps,
+                layout,
+                spawn_on_view,
+            });
+            Ok(())
+        },
+    )?;
+
+    let spawn_pinned =
+        lua.create_function(|lua, ()| create_action_table(lua, "SpawnPinned", Value::Nil))?;
+
+    let save_snapshot = lua.create_function(|lua, name: String| {
+        create_action_table(
+            lua,
+            "SaveSnapshot",
+            Value::String(lua.create_string(&name)?),
+        )
+    })?;
+
+    let restore_snapshot = lua.create_function(|lua, name: String| {
+        create_action_table(
+            lua,
+            "RestoreSnapshot",
+            Value::String(lua.create_string(&name)?),
+        )
+    })?;
+
     tag_table.set("view", view)?;
     tag_table.set("view_next", view_next)?;
     tag_table.set("view_previous", view_previous)?;
+    tag_table.set("view_next_occupied", view_next_nonempty.clone())?;
+    tag_table.set("view_previous_occupied", view_previous_nonempty.clone())?;
     tag_table.set("view_next_nonempty", view_next_nonempty)?;
     tag_table.set("view_previous_nonempty", view_previous_nonempty)?;
     tag_table.set("toggleview", toggleview)?;
     tag_table.set("move_to", move_to)?;
+    tag_table.set("move_to_new", move_to_new)?;
     tag_table.set("toggletag", toggletag)?;
     tag_table.set("set_back_and_forth", set_back_and_forth)?;
+    tag_table.set("set_warp_pointer", set_warp_pointer)?;
+    tag_table.set("pin", pin)?;
+    tag_table.set("spawn_pinned", spawn_pinned)?;
+    tag_table.set("save_snapshot", save_snapshot)?;
+    tag_table.set("restore_snapshot", restore_snapshot)?;
     parent.set("tag", tag_table)?;
     Ok(())
 }
 
-fn register_monitor_module(lua: &Lua, parent: &Table) -> Result<(), ConfigError> {
+fn register_monitor_module(
+    lua: &Lua,
+    parent: &Table,
+    builder: SharedBuilder,
+) -> Result<(), ConfigError> {
     let monitor_table = lua.create_table()?;
 
     let focus = lua.create_function(|lua, direction: i64| {
@@ -380,8 +715,160 @@ fn register_monitor_module(lua: &Lua, parent: &Table) -> Result<(), ConfigError>
         create_action_table(lua, "TagMonitor", Value::Integer(direction))
     })?;
 
+    let builder_clone = builder.clone();
+    let configure = lua.create_function(move |_, (output, config): (String, Table)| {
+        use crate::bar::BarPosition;
+
+        let tag = match config.get::<Option<usize>>("tag")? {
+            Some(0) => {
+                return Err(mlua::Error::RuntimeError(
+                    "oxwm.monitor.configure: tag indices start at 1".into(),
+                ));
+            }
+            tag => tag.map(|t| t - 1),
+        };
+
+        let bar_position = match config.get::<Option<String>>("bar_position")?.as_deref() {
+            None => None,
+            Some("top") => Some(BarPosition::Top),
+            Some("bottom") => Some(BarPosition::Bottom),
+            Some(other) => {
+                return Err(mlua::Error::RuntimeError(format!(
+                    "oxwm.monitor.configure: unknown bar_position '{}'. use 'top' or 'bottom'",
+                    other
+                )));
+            }
+        };
+
+        let blocks = match config.get::<Option<Table>>("blocks")? {
+            Some(blocks) => Some(parse_block_list(blocks)?),
+            None => None,
+        };
+
+        let (second_bar, second_bar_blocks) = match config.get::<Value>("second_bar")? {
+            Value::Nil => (None, None),
+            Value::Boolean(enabled) => (Some(enabled), None),
+            Value::Table(blocks) => (Some(true), Some(parse_block_list(blocks)?)),
+            _ => {
+                return Err(mlua::Error::RuntimeError(
+                    "oxwm.monitor.configure: second_bar must be a boolean or a list of blocks"
+                        .into(),
+                ));
+            }
+        };
+
+        let assigned_tags = config
+            .get::<Option<Vec<usize>>>("tags")?
+            .unwrap_or_default()
+            .iter()
+            .filter(|&&tag| (1..=32).contains(&tag))
+            .fold(0u32, |mask, tag| mask | 1 << (tag - 1));
+
+        let monitor_config = crate::MonitorConfig {
+            tag,
+            layout: config.get("layout")?,
+            bar_position,
+            blocks,
+            second_bar,
+            second_bar_blocks,
+            assigned_tags,
+            output,
+        };
+
+        let mut b = builder_clone.borrow_mut();
+        b.monitor_configs
+            .retain(|existing| existing.output != monitor_config.output);
+        b.monitor_configs.push(monitor_config);
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_assigned_tag_focus = lua.create_function(move |_, enabled: bool| {
+        builder_clone.borrow_mut().assigned_tag_focus = enabled;
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let link_tags = lua.create_function(move |_, views: Table| {
+        let mut link = crate::TagLink { views: Vec::new() };
+        for pair in views.pairs::<String, usize>() {
+            let (output, tag) = pair?;
+            if tag == 0 {
+                return Err(mlua::Error::RuntimeError(
+                    "oxwm.monitor.link_tags: tag indices start at 1".into(),
+                ));
+            }
+            link.views.push((output, tag - 1));
+        }
+        builder_clone.borrow_mut().tag_links.push(link);
+        Ok(())
+    })?;
+
+    let toggle_tag_links =
+        lua.create_function(|lua, ()| create_action_table(lua, "ToggleTagLinks", Value::Nil))?;
+
     monitor_table.set("focus", focus)?;
     monitor_table.set("tag", tag)?;
+    monitor_table.set("link_tags", link_tags)?;
+    monitor_table.set("toggle_tag_links", toggle_tag_links)?;
+    let builder_clone = builder.clone();
+    let add_profile = lua.create_function(move |_, config: Table| {
+        let name: String = config.get("name")?;
+        let lid_closed = match config.get::<Option<String>>("lid")?.as_deref() {
+            None => None,
+            Some("open") => Some(false),
+            Some("closed") => Some(true),
+            Some(other) => {
+                return Err(mlua::Error::RuntimeError(format!(
+                    "oxwm.monitor.add_profile: unknown lid state '{}'. use 'open' or 'closed'",
+                    other
+                )));
+            }
+        };
+
+        let mut outputs = Vec::new();
+        let mut tags = Vec::new();
+        for entry in config.get::<Table>("outputs")?.sequence_values::<Table>() {
+            let entry = entry?;
+            let setup = parse_output_setup(&entry)?;
+            if let Some(tag_list) = entry.get::<Option<Vec<usize>>>("tags")? {
+                let mask = tag_list
+                    .iter()
+                    .filter(|&&tag| tag > 0)
+                    .fold(0u32, |mask, tag| mask | 1 << (tag - 1));
+                tags.push((setup.output.clone(), mask));
+            }
+            outputs.push(setup);
+        }
+
+        let profile = crate::MonitorProfile {
+            name,
+            lid_closed,
+            outputs,
+            tags,
+        };
+
+        let mut b = builder_clone.borrow_mut();
+        b.monitor_profiles
+            .retain(|existing| existing.name != profile.name);
+        b.monitor_profiles.push(profile);
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_outputs = lua.create_function(move |_, outputs: Vec<Table>| {
+        let setups = outputs
+            .iter()
+            .map(parse_output_setup)
+            .collect::<mlua::Result<Vec<_>>>()?;
+        builder_clone.borrow_mut().display_setup = setups;
+        Ok(())
+    })?;
+
+    monitor_table.set("configure", configure)?;
+    monitor_table.set("set_assigned_tag_focus", set_assigned_tag_focus)?;
+    monitor_table.set("add_profile", add_profile)?;
+    monitor_table.set("set_outputs", set_outputs)?;
     parent.set("monitor", monitor_table)?;
     Ok(())
 }
@@ -398,9 +885,23 @@ fn register_rule_module(
         let class: Option<String> = config.get("class").ok();
         let instance: Option<String> = config.get("instance").ok();
         let title: Option<String> = config.get("title").ok();
+        let title_pattern = match config.get::<Option<String>>("title_match")? {
+            Some(pattern) => Some(regex::Regex::new(&pattern).map_err(|e| {
+                mlua::Error::RuntimeError(format!(
+                    "oxwm.rule.add: invalid title_match '{}': {}",
+                    pattern, e
+                ))
+            })?),
+            None => None,
+        };
+        let retag_on_title: bool = config.get("retag_on_title").unwrap_or(false);
         let is_floating: Option<bool> = config.get("floating").ok();
         let monitor: Option<usize> = config.get("monitor").ok();
         let focus: Option<bool> = config.get("focus").ok();
+        let float_preset: Option<String> = config.get("preset").ok();
+        let take_focus: Option<bool> = config.get("take_focus").ok();
+        let is_terminal: Option<bool> = config.get("terminal").ok();
+        let swallow: Option<bool> = config.get("swallow").ok();
 
         let tags: Option<u32> = if let Ok(tag_index) = config.get::<i32>("tag") {
             if tag_index > 0 {
@@ -416,21 +917,109 @@ fn register_rule_module(
             class,
             instance,
             title,
+            title_pattern,
+            retag_on_title,
             tags,
             focus,
             is_floating,
             monitor,
+            float_preset,
+            take_focus,
+            is_terminal,
+            swallow,
         };
 
         builder_clone.borrow_mut().window_rules.push(rule);
         Ok(())
     })?;
 
+    let builder_clone = builder.clone();
+    let set_hidden_tag_action = lua.create_function(move |_, action: String| {
+        builder_clone.borrow_mut().hidden_tag_action = match action.as_str() {
+            "silent" => crate::HiddenTagAction::Silent,
+            "switch" => crate::HiddenTagAction::Switch,
+            "urgent" => crate::HiddenTagAction::Urgent,
+            _ => {
+                return Err(mlua::Error::RuntimeError(format!(
+                    "oxwm.rule.set_hidden_tag_action: unknown action '{}'. use 'silent', 'switch' or 'urgent'",
+                    action
+                )));
+            }
+        };
+        Ok(())
+    })?;
+
     rule_table.set("add", add)?;
+    rule_table.set("set_hidden_tag_action", set_hidden_tag_action)?;
     parent.set("rule", rule_table)?;
     Ok(())
 }
 
+fn register_scratchpad_module(
+    lua: &Lua,
+    parent: &Table,
+    builder: SharedBuilder,
+) -> Result<(), ConfigError> {
+    let scratchpad_table = lua.create_table()?;
+
+    let builder_clone = builder.clone();
+    let add = lua.create_function(move |_, config: Table| {
+        let name: String = config.get("name").map_err(|_| {
+            mlua::Error::RuntimeError("oxwm.scratchpad.add: 'name' is required".into())
+        })?;
+        let command: String = config.get("command").map_err(|_| {
+            mlua::Error::RuntimeError(format!(
+                "oxwm.scratchpad.add: scratchpad '{}' needs a 'command'",
+                name
+            ))
+        })?;
+        let class: Option<String> = config.get("class")?;
+        let instance: Option<String> = config.get("instance")?;
+        let title: Option<String> = config.get("title")?;
+        if class.is_none() && instance.is_none() && title.is_none() {
+            return Err(mlua::Error::RuntimeError(format!(
+                "oxwm.scratchpad.add: scratchpad '{}' needs a class, instance or title to recognize its window",
+                name
+            )));
+        }
+        let width: f32 = config.get::<Option<f32>>("width")?.unwrap_or(60.0);
+        let height: f32 = config.get::<Option<f32>>("height")?.unwrap_or(60.0);
+        if !(1.0..=100.0).contains(&width) || !(1.0..=100.0).contains(&height) {
+            return Err(mlua::Error::RuntimeError(format!(
+                "oxwm.scratchpad.add: scratchpad '{}' width and height must be percentages between 1 and 100",
+                name
+            )));
+        }
+
+        let scratchpad = crate::Scratchpad {
+            name,
+            command,
+            class,
+            instance,
+            title,
+            width: width / 100.0,
+            height: height / 100.0,
+        };
+        let mut builder = builder_clone.borrow_mut();
+        builder.scratchpads.retain(|s| s.name != scratchpad.name);
+        builder.scratchpads.push(scratchpad);
+        Ok(())
+    })?;
+
+    let toggle = lua.create_function(|lua, name: String| {
+        create_action_table(
+            lua,
+            "ToggleScratchpad",
+            Value::String(lua.create_string(&name)?),
+        )
+    })?;
+
+    scratchpad_table.set("add", add)?;
+    scratchpad_table.set("toggle", toggle)?;
+    parent.set("scratchpad", scratchpad_table)?;
+    Ok(())
+}
+
 fn register_bar_module(
     lua: &Lua,
     parent: &Table,
@@ -446,8 +1035,47 @@ fn register_bar_module(
 
     let block_table = lua.create_table()?;
 
-    let ram =
-        lua.create_function(|lua, config: Table| create_block_config(lua, config, "Ram", None))?;
+    let ram = lua.create_function(|lua, config: Table| {
+        let options_table = threshold_options(lua, &config, RAM_THRESHOLDS)?;
+        create_block_config(lua, config, "Ram", Some(Value::Table(options_table)))
+    })?;
+
+    let cpu = lua.create_function(|lua, config: Table| {
+        if config.get::<Value>("interval")?.is_nil() {
+            config.set("interval", 2)?;
+        }
+        if config.get::<Value>("format")?.is_nil() {
+            config.set("format", "CPU {}%")?;
+        }
+
+        let options_table = threshold_options(lua, &config, (80, 95))?;
+        create_block_config(lua, config, "Cpu", Some(Value::Table(options_table)))
+    })?;
+
+    let temperature = lua.create_function(|lua, config: Table| {
+        let zone: Value = config.get("zone")?;
+        if config.get::<Value>("interval")?.is_nil() {
+            config.set("interval", 5)?;
+        }
+        if config.get::<Value>("format")?.is_nil() {
+            config.set("format", "{}°C")?;
+        }
+
+        let options_table = threshold_options(lua, &config, (75, 90))?;
+        // A zone number may be given as a number or a string.
+        let zone = match zone {
+            Value::Integer(number) => Some(number.to_string()),
+            Value::String(name) => Some(name.to_str()?.to_string()),
+            _ => None,
+        };
+        options_table.set("zone", zone)?;
+        create_block_config(
+            lua,
+            config,
+            "Temperature",
+            Some(Value::Table(options_table)),
+        )
+    })?;
 
     let datetime = lua.create_function(|lua, config: Table| {
         let date_format: String = config.get("date_format").map_err(|_| {
@@ -455,24 +1083,49 @@ fn register_bar_module(
                 "oxwm.bar.block.datetime: 'date_format' field is required (e.g., '%H:%M')".into(),
             )
         })?;
-        create_block_config(
-            lua,
-            config,
-            "DateTime",
-            Some(Value::String(lua.create_string(&date_format)?)),
-        )
+        let zones: Option<Table> = config.get("zones").unwrap_or(None);
+        let locale: Option<String> = config.get("locale").unwrap_or(None);
+
+        let options_table = lua.create_table()?;
+        options_table.set("date_format", date_format)?;
+        options_table.set("zones", zones)?;
+        options_table.set("locale", locale)?;
+
+        create_block_config(lua, config, "DateTime", Some(Value::Table(options_table)))
     })?;
 
     let shell = lua.create_function(|lua, config: Table| {
         let command: String = config.get("command").map_err(|_| {
             mlua::Error::RuntimeError("oxwm.bar.block.shell: 'command' field is required".into())
         })?;
-        create_block_config(
-            lua,
-            config,
-            "Shell",
-            Some(Value::String(lua.create_string(&command)?)),
-        )
+        let timeout: Option<u64> = config.get("timeout").unwrap_or(None);
+        let error: Option<String> = config.get("error").unwrap_or(None);
+
+        let options_table = lua.create_table()?;
+        options_table.set("command", command)?;
+        options_table.set("timeout", timeout)?;
+        options_table.set("error", error)?;
+
+        create_block_config(lua, config, "Shell", Some(Value::Table(options_table)))
+    })?;
+
+    let context = lua.create_function(|lua, config: Table| {
+        let command: String = config.get("command").map_err(|_| {
+            mlua::Error::RuntimeError("oxwm.bar.block.context: 'command' field is required".into())
+        })?;
+        let classes: Option<Vec<String>> = config.get("classes").unwrap_or(None);
+        let timeout: Option<u64> = config.get("timeout").unwrap_or(None);
+        // Context blocks follow focus rather than an interval.
+        if config.get::<Value>("interval")?.is_nil() {
+            config.set("interval", 0)?;
+        }
+
+        let options_table = lua.create_table()?;
+        options_table.set("command", command)?;
+        options_table.set("classes", classes)?;
+        options_table.set("timeout", timeout)?;
+
+        create_block_config(lua, config, "Context", Some(Value::Table(options_table)))
     })?;
 
     let static_block = lua.create_function(|lua, config: Table| {
@@ -500,181 +1153,368 @@ fn register_bar_module(
             mlua::Error::RuntimeError("oxwm.bar.block.battery: 'full' field is required".into())
         })?;
         let battery_name: Option<String> = config.get("battery_name").unwrap_or(None);
+        let warning: Option<u32> = config.get("warning").unwrap_or(None);
+        let critical: Option<u32> = config.get("critical").unwrap_or(None);
+        let low_color: Value = config.get("low_color")?;
+        let on_critical: Option<String> = config.get("on_critical").unwrap_or(None);
 
         let formats_table = lua.create_table()?;
         formats_table.set("charging", charging)?;
         formats_table.set("discharging", discharging)?;
         formats_table.set("full", full)?;
         formats_table.set("battery_name", battery_name)?;
+        formats_table.set("warning", warning)?;
+        formats_table.set("critical", critical)?;
+        formats_table.set("low_color", low_color)?;
+        formats_table.set("on_critical", on_critical)?;
 
         create_block_config(lua, config, "Battery", Some(Value::Table(formats_table)))
     })?;
 
     block_table.set("ram", ram)?;
+    block_table.set("cpu", cpu)?;
+    block_table.set("temperature", temperature)?;
     block_table.set("datetime", datetime)?;
     block_table.set("shell", shell)?;
+    block_table.set("context", context)?;
     block_table.set("static", static_block)?;
-    block_table.set("battery", battery)?;
+    let countdown = lua.create_function(|lua, config: Table| {
+        let file: String = config.get("file").map_err(|_| {
+            mlua::Error::RuntimeError("oxwm.bar.block.countdown: 'file' field is required".into())
+        })?;
+        let warning: Option<u64> = config.get("warning").unwrap_or(None);
+        let critical: Option<u64> = config.get("critical").unwrap_or(None);
+        if config.get::<Value>("interval")?.is_nil() {
+            config.set("interval", 60)?;
+        }
 
-    // Deprecated add_block() function for backwards compatibility
-    // This allows old configs to still work, but users should migrate to set_blocks()
-    let builder_clone = builder.clone();
-    let add_block = lua.create_function(move |_, (format, block_type, arg, interval, color, underline): (String, String, Value, u64, Value, Option<bool>)| -> mlua::Result<()> {
-        eprintln!("WARNING: oxwm.bar.add_block() is deprecated. Please migrate to oxwm.bar.set_blocks() with block constructors.");
-        eprintln!("See the migration guide for details.");
+        let options_table = lua.create_table()?;
+        options_table.set("file", file)?;
+        options_table.set("warning", warning.unwrap_or(15))?;
+        options_table.set("critical", critical.unwrap_or(5))?;
 
-        let cmd = match block_type.as_str() {
-            "DateTime" => {
-                let fmt = if let Value::String(s) = arg {
-                    s.to_str()?.to_string()
-                } else {
-                    return Err(mlua::Error::RuntimeError("DateTime block requires format string as third argument".into()));
-                };
-                crate::bar::BlockCommand::DateTime(fmt)
-            }
-            "Shell" => {
-                let cmd_str = if let Value::String(s) = arg {
-                    s.to_str()?.to_string()
-                } else {
-                    return Err(mlua::Error::RuntimeError("Shell block requires command string as third argument".into()));
-                };
-                crate::bar::BlockCommand::Shell(cmd_str)
-            }
-            "Ram" => crate::bar::BlockCommand::Ram,
-            "Static" => {
-                let text = if let Value::String(s) = arg {
-                    s.to_str()?.to_string()
-                } else {
-                    String::new()
-                };
-                crate::bar::BlockCommand::Static(text)
-            }
-            "Battery" => {
-                return Err(mlua::Error::RuntimeError(
-                    "Battery block is not supported with add_block(). Please use oxwm.bar.set_blocks() with oxwm.bar.block.battery()".into()
-                ));
-            }
-            _ => return Err(mlua::Error::RuntimeError(format!("Unknown block type '{}'", block_type))),
-        };
+        create_block_config(lua, config, "Countdown", Some(Value::Table(options_table)))
+    })?;
 
-        let color_u32 = parse_color_value(color)?;
+    let systemd = lua.create_function(|lua, config: Table| {
+        let units: Vec<String> = config
+            .get::<Option<Vec<String>>>("units")?
+            .unwrap_or_default();
+        let user_units: Vec<String> = config
+            .get::<Option<Vec<String>>>("user_units")?
+            .unwrap_or_default();
+        if config.get::<Value>("interval")?.is_nil() {
+            config.set("interval", 10)?;
+        }
+        if config.get::<Value>("popup")?.is_nil() {
+            config.set("popup", failed_units_popup(&units, &user_units))?;
+        }
 
-        let block = crate::bar::BlockConfig {
-            format,
-            command: cmd,
-            interval_secs: interval,
-            color: color_u32,
-            underline: underline.unwrap_or(false),
-        };
+        let options_table = lua.create_table()?;
+        options_table.set("units", units)?;
+        options_table.set("user_units", user_units)?;
 
-        builder_clone.borrow_mut().status_blocks.push(block);
-        Ok(())
+        create_block_config(lua, config, "Systemd", Some(Value::Table(options_table)))
     })?;
 
-    let builder_clone = builder.clone();
-    let set_blocks = lua.create_function(move |_, blocks: Table| {
-        use crate::bar::BlockCommand;
-
-        let mut block_configs = Vec::new();
-
-        for i in 1..=blocks.len()? {
-            let block_table: Table = blocks.get(i)?;
-            let block_type: String = block_table.get("__block_type")?;
-            let format: String = block_table.get("format").unwrap_or_default();
-            let interval: u64 = block_table.get("interval")?;
-            let color_val: Value = block_table.get("color")?;
-            let underline: bool = block_table.get("underline").unwrap_or(false);
-            let arg: Option<Value> = block_table.get("__arg").ok();
-
-            let cmd = match block_type.as_str() {
-                "DateTime" => {
-                    let fmt = arg
-                        .and_then(|v| {
-                            if let Value::String(s) = v {
-                                s.to_str().ok().map(|s| s.to_string())
-                            } else {
-                                None
-                            }
-                        })
-                        .ok_or_else(|| {
-                            mlua::Error::RuntimeError("DateTime block missing format".into())
-                        })?;
-                    BlockCommand::DateTime(fmt)
-                }
-                "Shell" => {
-                    let cmd_str = arg
-                        .and_then(|v| {
-                            if let Value::String(s) = v {
-                                s.to_str().ok().map(|s| s.to_string())
-                            } else {
-                                None
-                            }
-                        })
-                        .ok_or_else(|| {
-                            mlua::Error::RuntimeError("Shell block missing command".into())
-                        })?;
-                    BlockCommand::Shell(cmd_str)
-                }
-                "Ram" => BlockCommand::Ram,
-                "Static" => {
-                    let text = arg
-                        .and_then(|v| {
-                            if let Value::String(s) = v {
-                                s.to_str().ok().map(|s| s.to_string())
-                            } else {
-                                None
-                            }
-                        })
-                        .unwrap_or_default();
-                    BlockCommand::Static(text)
-                }
-                "Battery" => {
-                    let formats = arg
-                        .and_then(|v| {
-                            if let Value::Table(t) = v {
-                                Some(t)
-                            } else {
-                                None
-                            }
-                        })
-                        .ok_or_else(|| {
-                            mlua::Error::RuntimeError("Battery block missing formats".into())
-                        })?;
-
-                    let charging: String = formats.get("charging")?;
-                    let discharging: String = formats.get("discharging")?;
-                    let full: String = formats.get("full")?;
-                    let battery_name: Option<String> = formats.get("battery_name").unwrap_or(None);
-
-                    BlockCommand::Battery {
-                        format_charging: charging,
-                        format_discharging: discharging,
-                        format_full: full,
-                        battery_name,
-                    }
+    block_table.set("battery", battery)?;
+    block_table.set("countdown", countdown)?;
+    block_table.set("systemd", systemd)?;
+
+    let containers = lua.create_function(|lua, config: Table| {
+        let socket: Option<String> = config.get("socket").unwrap_or(None);
+        if config.get::<Value>("interval")?.is_nil() {
+            config.set("interval", 10)?;
+        }
+
+        let options_table = lua.create_table()?;
+        options_table.set("socket", socket)?;
+
+        create_block_config(lua, config, "Containers", Some(Value::Table(options_table)))
+    })?;
+
+    block_table.set("containers", containers)?;
+
+    let http = lua.create_function(|lua, config: Table| {
+        let url: Option<String> = config.get("url").unwrap_or(None);
+        let path: Option<String> = config.get("path").unwrap_or(None);
+        let sources: Option<Table> = config.get("sources").unwrap_or(None);
+        if url.is_none() && sources.is_none() {
+            return Err(mlua::Error::RuntimeError(
+                "oxwm.bar.block.http: 'url' and 'path', or 'sources', are required".into(),
+            ));
+        }
+        let timeout: Option<u64> = config.get("timeout").unwrap_or(None);
+        let decimals: Option<u8> = config.get("decimals").unwrap_or(None);
+        if config.get::<Value>("interval")?.is_nil() {
+            config.set("interval", 300)?;
+        }
+
+        let options_table = lua.create_table()?;
+        options_table.set("url", url)?;
+        options_table.set("path", path)?;
+        options_table.set("sources", sources)?;
+        options_table.set("timeout", timeout.unwrap_or(10))?;
+        options_table.set("decimals", decimals)?;
+
+        create_block_config(lua, config, "Http", Some(Value::Table(options_table)))
+    })?;
+
+    block_table.set("http", http)?;
+
+    let privacy = lua.create_function(|lua, config: Table| {
+        let microphone: Option<String> = config.get("microphone").unwrap_or(None);
+        let camera: Option<String> = config.get("camera").unwrap_or(None);
+        if config.get::<Value>("interval")?.is_nil() {
+            config.set("interval", 2)?;
+        }
+        if config.get::<Value>("format")?.is_nil() {
+            config.set("format", "{}")?;
+        }
+
+        let options_table = lua.create_table()?;
+        options_table.set(
+            "microphone",
+            microphone.unwrap_or_else(|| "MIC".to_string()),
+        )?;
+        options_table.set("camera", camera.unwrap_or_else(|| "CAM".to_string()))?;
+
+        create_block_config(lua, config, "Privacy", Some(Value::Table(options_table)))
+    })?;
+
+    block_table.set("privacy", privacy)?;
+
+    let vpn = lua.create_function(|lua, config: Table| {
+        let interface: Option<String> = config.get("interface").unwrap_or(None);
+        let connection: Option<String> = config.get("connection").unwrap_or(None);
+        let toggle: Option<String> = config.get("toggle").unwrap_or(None);
+        let format_down: Option<String> = config.get("format_down").unwrap_or(None);
+        let down_color: Value = config.get("down_color")?;
+        if config.get::<Value>("interval")?.is_nil() {
+            config.set("interval", 5)?;
+        }
+        if config.get::<Value>("format")?.is_nil() {
+            config.set("format", "VPN {}")?;
+        }
+
+        let options_table = lua.create_table()?;
+        options_table.set("interface", interface)?;
+        options_table.set("connection", connection)?;
+        options_table.set("toggle", toggle)?;
+        options_table.set(
+            "format_down",
+            format_down.unwrap_or_else(|| "VPN off".to_string()),
+        )?;
+        options_table.set("down_color", down_color)?;
+
+        create_block_config(lua, config, "Vpn", Some(Value::Table(options_table)))
+    })?;
+
+    block_table.set("vpn", vpn)?;
+
+    let gpu = lua.create_function(|lua, config: Table| {
+        let device: Option<String> = config.get("device").unwrap_or(None);
+        if config.get::<Value>("interval")?.is_nil() {
+            config.set("interval", 2)?;
+        }
+        if config.get::<Value>("format")?.is_nil() {
+            config.set("format", "GPU {}%")?;
+        }
+
+        let options_table = lua.create_table()?;
+        options_table.set("device", device)?;
+
+        create_block_config(lua, config, "Gpu", Some(Value::Table(options_table)))
+    })?;
+
+    block_table.set("gpu", gpu)?;
+
+    let peripherals = lua.create_function(|lua, config: Table| {
+        let icons: Option<Table> = config.get("icons").unwrap_or(None);
+        let warning: Option<u32> = config.get("warning").unwrap_or(None);
+        if config.get::<Value>("interval")?.is_nil() {
+            config.set("interval", 60)?;
+        }
+        if config.get::<Value>("format")?.is_nil() {
+            config.set("format", "{}")?;
+        }
+
+        let options_table = lua.create_table()?;
+        options_table.set("icons", icons)?;
+        options_table.set("warning", warning.unwrap_or(20))?;
+
+        create_block_config(
+            lua,
+            config,
+            "Peripherals",
+            Some(Value::Table(options_table)),
+        )
+    })?;
+
+    block_table.set("peripherals", peripherals)?;
+
+    let updates = lua.create_function(|lua, config: Table| {
+        let backend: Option<String> = config.get("backend").unwrap_or(None);
+        let command: Option<String> = config.get("command").unwrap_or(None);
+        if let Some(name) = &backend
+            && !matches!(name.as_str(), "pacman" | "apt" | "dnf")
+        {
+            return Err(mlua::Error::RuntimeError(format!(
+                "oxwm.bar.block.updates: unknown backend '{}', expected \"pacman\", \"apt\" or \"dnf\"",
+                name
+            )));
+        }
+        if config.get::<Value>("interval")?.is_nil() {
+            config.set("interval", 3600)?;
+        }
+        if config.get::<Value>("format")?.is_nil() {
+            config.set("format", "UPD {}")?;
+        }
+
+        let options_table = lua.create_table()?;
+        options_table.set("backend", backend)?;
+        options_table.set("command", command)?;
+
+        create_block_config(lua, config, "Updates", Some(Value::Table(options_table)))
+    })?;
+
+    block_table.set("updates", updates)?;
+
+    let volume = lua.create_function(|lua, config: Table| {
+        let muted: Option<String> = config.get("muted").unwrap_or(None);
+        let step: Option<u32> = config.get("step").unwrap_or(None);
+        let max: Option<u32> = config.get("max").unwrap_or(None);
+        if config.get::<Value>("interval")?.is_nil() {
+            config.set("interval", 60)?;
+        }
+        if config.get::<Value>("format")?.is_nil() {
+            config.set("format", "VOL {}%")?;
+        }
+
+        let options_table = lua.create_table()?;
+        options_table.set("muted", muted.unwrap_or_else(|| "MUTE".to_string()))?;
+        options_table.set("step", step.unwrap_or(5))?;
+        options_table.set("max", max.unwrap_or(100))?;
+
+        create_block_config(lua, config, "Volume", Some(Value::Table(options_table)))
+    })?;
+
+    block_table.set("volume", volume)?;
+
+    let network = lua.create_function(|lua, config: Table| {
+        let interface: Option<String> = config.get("interface").unwrap_or(None);
+        let ethernet: Option<String> = config.get("ethernet").unwrap_or(None);
+        let disconnected: Option<String> = config.get("disconnected").unwrap_or(None);
+        if config.get::<Value>("interval")?.is_nil() {
+            config.set("interval", 5)?;
+        }
+        if config.get::<Value>("format")?.is_nil() {
+            config.set("format", "{ssid} {signal}%")?;
+        }
+
+        let options_table = lua.create_table()?;
+        options_table.set("interface", interface)?;
+        options_table.set("ethernet", ethernet.unwrap_or_else(|| "{}".to_string()))?;
+        options_table.set(
+            "disconnected",
+            disconnected.unwrap_or_else(|| "offline".to_string()),
+        )?;
+
+        create_block_config(lua, config, "Network", Some(Value::Table(options_table)))
+    })?;
+
+    block_table.set("network", network)?;
+
+    // Deprecated add_block() function for backwards compatibility
+    // This allows old configs to still work, but users should migrate to set_blocks()
+    let builder_clone = builder.clone();
+    let add_block = lua.create_function(move |_, (format, block_type, arg, interval, color, underline): (String, String, Value, u64, Value, Option<bool>)| -> mlua::Result<()> {
+        eprintln!("WARNING: oxwm.bar.add_block() is deprecated. Please migrate to oxwm.bar.set_blocks() with block constructors.");
+        eprintln!("See the migration guide for details.");
+
+        let cmd = match block_type.as_str() {
+            "DateTime" => {
+                let fmt = if let Value::String(s) = arg {
+                    s.to_str()?.to_string()
+                } else {
+                    return Err(mlua::Error::RuntimeError("DateTime block requires format string as third argument".into()));
+                };
+                crate::bar::BlockCommand::DateTime {
+                    date_format: fmt,
+                    zones: Vec::new(),
+                    locale: None,
                 }
-                _ => {
-                    return Err(mlua::Error::RuntimeError(format!(
-                        "Unknown block type '{}'",
-                        block_type
-                    )));
+            }
+            "Shell" => {
+                let cmd_str = if let Value::String(s) = arg {
+                    s.to_str()?.to_string()
+                } else {
+                    return Err(mlua::Error::RuntimeError("Shell block requires command string as third argument".into()));
+                };
+                crate::bar::BlockCommand::Shell {
+                    command: cmd_str,
+                    timeout_secs: None,
+                    error_text: crate::bar::DEFAULT_BLOCK_ERROR.to_string(),
                 }
-            };
+            }
+            "Ram" => crate::bar::BlockCommand::Ram {
+                thresholds: crate::bar::Thresholds {
+                    warning: RAM_THRESHOLDS.0,
+                    critical: RAM_THRESHOLDS.1,
+                    warning_color: None,
+                },
+            },
+            "Static" => {
+                let text = if let Value::String(s) = arg {
+                    s.to_str()?.to_string()
+                } else {
+                    String::new()
+                };
+                crate::bar::BlockCommand::Static(text)
+            }
+            "Battery" => {
+                return Err(mlua::Error::RuntimeError(
+                    "Battery block is not supported with add_block(). Please use oxwm.bar.set_blocks() with oxwm.bar.block.battery()".into()
+                ));
+            }
+            _ => return Err(mlua::Error::RuntimeError(format!("Unknown block type '{}'", block_type))),
+        };
 
-            let color_u32 = parse_color_value(color_val)?;
+        let color_u32 = parse_color_value(color)?;
 
-            let block = crate::bar::BlockConfig {
-                format,
-                command: cmd,
-                interval_secs: interval,
-                color: color_u32,
-                underline,
-            };
+        let block = crate::bar::BlockConfig {
+            format,
+            command: cmd,
+            interval_secs: interval,
+            color: color_u32,
+            underline: underline.unwrap_or(false),
+            background: None,
+            gradient: None,
+            popup: None,
+            sensitive: false,
+            focused_only: false,
+            min_width: None,
+            align: Default::default(),
+            clicks: Default::default(),
+            signal: None,
+        };
 
-            block_configs.push(block);
-        }
+        builder_clone.borrow_mut().status_blocks.push(block);
+        Ok(())
+    })?;
 
-        builder_clone.borrow_mut().status_blocks = block_configs;
+    let builder_clone = builder.clone();
+    let set_blocks = lua.create_function(move |_, blocks: Table| {
+        builder_clone.borrow_mut().status_blocks = parse_block_list(blocks)?;
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_second_bar = lua.create_function(move |_, blocks: Option<Table>| {
+        builder_clone.borrow_mut().second_bar_blocks = match blocks {
+            Some(blocks) => Some(parse_block_list(blocks)?),
+            None => None,
+        };
         Ok(())
     })?;
 
@@ -682,13 +1522,14 @@ fn register_bar_module(
     let set_scheme_normal =
         lua.create_function(move |_, (fg, bg, ul): (Value, Value, Value)| {
             let foreground = parse_color_value(fg)?;
-            let background = parse_color_value(bg)?;
+            let (background, gradient) = parse_background_value(bg)?;
             let underline = parse_color_value(ul)?;
 
             builder_clone.borrow_mut().scheme_normal = ColorScheme {
                 foreground,
                 background,
                 underline,
+                gradient,
             };
             Ok(())
         })?;
@@ -697,13 +1538,14 @@ fn register_bar_module(
     let set_scheme_occupied =
         lua.create_function(move |_, (fg, bg, ul): (Value, Value, Value)| {
             let foreground = parse_color_value(fg)?;
-            let background = parse_color_value(bg)?;
+            let (background, gradient) = parse_background_value(bg)?;
             let underline = parse_color_value(ul)?;
 
             builder_clone.borrow_mut().scheme_occupied = ColorScheme {
                 foreground,
                 background,
                 underline,
+                gradient,
             };
             Ok(())
         })?;
@@ -712,13 +1554,14 @@ fn register_bar_module(
     let set_scheme_selected =
         lua.create_function(move |_, (fg, bg, ul): (Value, Value, Value)| {
             let foreground = parse_color_value(fg)?;
-            let background = parse_color_value(bg)?;
+            let (background, gradient) = parse_background_value(bg)?;
             let underline = parse_color_value(ul)?;
 
             builder_clone.borrow_mut().scheme_selected = ColorScheme {
                 foreground,
                 background,
                 underline,
+                gradient,
             };
             Ok(())
         })?;
@@ -727,33 +1570,337 @@ fn register_bar_module(
     let set_scheme_urgent =
         lua.create_function(move |_, (fg, bg, ul): (Value, Value, Value)| {
             let foreground = parse_color_value(fg)?;
-            let background = parse_color_value(bg)?;
+            let (background, gradient) = parse_background_value(bg)?;
             let underline = parse_color_value(ul)?;
 
             builder_clone.borrow_mut().scheme_urgent = ColorScheme {
                 foreground,
                 background,
                 underline,
+                gradient,
             };
             Ok(())
         })?;
 
+    let builder_clone = builder.clone();
+    let set_root_status = lua.create_function(move |_, enabled: bool| {
+        builder_clone.borrow_mut().bar_root_status = enabled;
+        Ok(())
+    })?;
+
     let builder_clone = builder.clone();
     let set_hide_vacant_tags = lua.create_function(move |_, hide: bool| {
         builder_clone.borrow_mut().hide_vacant_tags = hide;
         Ok(())
     })?;
 
-    bar_table.set("set_font", set_font)?;
-    bar_table.set("block", block_table)?;
-    bar_table.set("add_block", add_block)?; // Deprecated, for backwards compatibility
-    bar_table.set("set_blocks", set_blocks)?;
-    bar_table.set("set_scheme_normal", set_scheme_normal)?;
-    bar_table.set("set_scheme_occupied", set_scheme_occupied)?;
-    bar_table.set("set_scheme_selected", set_scheme_selected)?;
-    bar_table.set("set_scheme_urgent", set_scheme_urgent)?;
-    bar_table.set("set_hide_vacant_tags", set_hide_vacant_tags)?;
-    parent.set("bar", bar_table)?;
+    let builder_clone = builder.clone();
+    let set_recording_indicator = lua.create_function(move |_, config: Table| {
+        let mut b = builder_clone.borrow_mut();
+        if let Some(text) = config.get::<Option<String>>("text")? {
+            b.recording_indicator = text;
+        }
+        if let Some(processes) = config.get::<Option<Vec<String>>>("processes")? {
+            b.recording_processes = processes;
+        }
+        if let Some(grabs) = config.get::<Option<bool>>("grabs")? {
+            b.recording_grabs = grabs;
+        }
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_click_through = lua.create_function(move |_, enabled: bool| {
+        builder_clone.borrow_mut().bar_click_through = enabled;
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_bar_corner_radius = lua.create_function(move |_, radius: u16| {
+        builder_clone.borrow_mut().bar_corner_radius = radius;
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_border = lua.create_function(move |_, (width, color): (u16, Option<Value>)| {
+        let mut b = builder_clone.borrow_mut();
+        b.bar_border_width = width;
+        if let Some(color) = color {
+            b.bar_border_color = parse_color_value(color)?;
+        }
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_shadow = lua.create_function(move |_, size: u16| {
+        builder_clone.borrow_mut().bar_shadow_size = size;
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_tag_flash = lua.create_function(move |_, enabled: bool| {
+        builder_clone.borrow_mut().bar_tag_flash = enabled;
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_urgent_blink = lua.create_function(move |_, config: Value| {
+        builder_clone.borrow_mut().bar_urgent_blink = match config {
+            Value::Nil | Value::Boolean(false) => None,
+            Value::Boolean(true) => Some(crate::bar::UrgentBlink {
+                interval_ms: 500,
+                duration_ms: 0,
+            }),
+            Value::Table(t) => Some(crate::bar::UrgentBlink {
+                interval_ms: t.get::<Option<u64>>("interval")?.unwrap_or(500),
+                duration_ms: t.get::<Option<u64>>("duration")?.unwrap_or(0),
+            }),
+            _ => {
+                return Err(mlua::Error::RuntimeError(
+                    "oxwm.bar.set_urgent_blink: expected a table or a boolean".into(),
+                ));
+            }
+        };
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_activity_color = lua.create_function(move |_, color: Value| {
+        builder_clone.borrow_mut().activity_color = match color {
+            Value::Nil => None,
+            color => Some(parse_color_value(color)?),
+        };
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_monitor_label = lua.create_function(move |_, label: String| {
+        use crate::bar::MonitorLabel;
+        builder_clone.borrow_mut().bar_monitor_label = match label.as_str() {
+            "none" => MonitorLabel::None,
+            "number" => MonitorLabel::Number,
+            "name" => MonitorLabel::Name,
+            _ => {
+                return Err(mlua::Error::RuntimeError(format!(
+                    "oxwm.bar.set_monitor_label: unknown label '{}'. use 'none', 'number' or 'name'",
+                    label
+                )));
+            }
+        };
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_title_class =
+        lua.create_function(move |_, (show, color): (String, Option<Value>)| {
+            use crate::bar::TitleClass;
+            let mut b = builder_clone.borrow_mut();
+            b.bar_title_class = match show.as_str() {
+                "none" => TitleClass::None,
+                "class" => TitleClass::Class,
+                "instance" => TitleClass::Instance,
+                _ => {
+                    return Err(mlua::Error::RuntimeError(format!(
+                        "oxwm.bar.set_title_class: unknown value '{}'. use 'none', 'class' or 'instance'",
+                        show
+                    )));
+                }
+            };
+            b.bar_title_class_color = match color {
+                None | Some(Value::Nil) => None,
+                Some(color) => Some(parse_color_value(color)?),
+            };
+            Ok(())
+        })?;
+
+    let builder_clone = builder.clone();
+    let set_window_count = lua.create_function(move |_, format: Value| {
+        builder_clone.borrow_mut().bar_window_count = match format {
+            Value::Nil | Value::Boolean(false) => None,
+            Value::Boolean(true) => Some("[{}/{total}]".to_string()),
+            Value::String(s) => Some(s.to_str()?.to_string()),
+            _ => {
+                return Err(mlua::Error::RuntimeError(
+                    "oxwm.bar.set_window_count: expected a format string or a boolean".into(),
+                ));
+            }
+        };
+        Ok(())
+    })?;
+
+    let toggle_debug =
+        lua.create_function(|lua, ()| create_action_table(lua, "ToggleBarDebug", Value::Nil))?;
+
+    let builder_clone = builder.clone();
+    let on_click = lua.create_function(move |lua, (region, action): (String, Value)| {
+        let region = parse_bar_region(&region, "oxwm.bar.on_click")?;
+        let (action, arg) = parse_action_value(lua, action)?;
+        builder_clone
+            .borrow_mut()
+            .bar_clicks
+            .push(crate::bar::BarClick {
+                region,
+                double: false,
+                action,
+                arg,
+            });
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let on_double_click = lua.create_function(move |lua, (region, action): (String, Value)| {
+        let region = parse_bar_region(&region, "oxwm.bar.on_double_click")?;
+        let (action, arg) = parse_action_value(lua, action)?;
+        builder_clone
+            .borrow_mut()
+            .bar_clicks
+            .push(crate::bar::BarClick {
+                region,
+                double: true,
+                action,
+                arg,
+            });
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_dead_zone = lua.create_function(move |_, pixels: u16| {
+        builder_clone.borrow_mut().bar_dead_zone = pixels;
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_scroll_skip_empty = lua.create_function(move |_, enabled: bool| {
+        builder_clone.borrow_mut().bar_scroll_skip_empty = enabled;
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_autohide = lua.create_function(move |_, ms: u64| {
+        builder_clone.borrow_mut().bar_autohide_ms = ms;
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_double_click_time = lua.create_function(move |_, ms: u64| {
+        builder_clone.borrow_mut().double_click_ms = ms;
+        Ok(())
+    })?;
+
+    bar_table.set("set_font", set_font)?;
+    bar_table.set("block", block_table)?;
+    bar_table.set("add_block", add_block)?; // Deprecated, for backwards compatibility
+    bar_table.set("set_blocks", set_blocks)?;
+    bar_table.set("set_scheme_normal", set_scheme_normal)?;
+    bar_table.set("set_scheme_occupied", set_scheme_occupied)?;
+    bar_table.set("set_scheme_selected", set_scheme_selected)?;
+    bar_table.set("set_scheme_urgent", set_scheme_urgent)?;
+    bar_table.set("set_hide_vacant_tags", set_hide_vacant_tags)?;
+    bar_table.set("set_root_status", set_root_status)?;
+    bar_table.set("set_second_bar", set_second_bar)?;
+    bar_table.set("set_recording_indicator", set_recording_indicator)?;
+    bar_table.set("set_click_through", set_click_through)?;
+    bar_table.set("set_corner_radius", set_bar_corner_radius)?;
+    bar_table.set("set_border", set_border)?;
+    bar_table.set("set_shadow", set_shadow)?;
+    bar_table.set("set_monitor_label", set_monitor_label)?;
+    bar_table.set("set_title_class", set_title_class)?;
+    bar_table.set("set_window_count", set_window_count)?;
+    bar_table.set("set_tag_flash", set_tag_flash)?;
+    bar_table.set("set_urgent_blink", set_urgent_blink)?;
+    bar_table.set("set_activity_color", set_activity_color)?;
+    bar_table.set("toggle_debug", toggle_debug)?;
+    bar_table.set("on_click", on_click)?;
+    bar_table.set("on_double_click", on_double_click)?;
+    bar_table.set("set_dead_zone", set_dead_zone)?;
+    bar_table.set("set_double_click_time", set_double_click_time)?;
+    bar_table.set("set_autohide", set_autohide)?;
+    bar_table.set("set_scroll_skip_empty", set_scroll_skip_empty)?;
+    parent.set("bar", bar_table)?;
+    Ok(())
+}
+
+fn register_systemd_module(
+    lua: &Lua,
+    parent: &Table,
+    builder: SharedBuilder,
+) -> Result<(), ConfigError> {
+    let systemd_table = lua.create_table()?;
+
+    let builder_clone = builder.clone();
+    let set_notify = lua.create_function(move |_, enabled: bool| {
+        builder_clone.borrow_mut().systemd_notify = enabled;
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_autostart_units = lua.create_function(move |_, enabled: bool| {
+        builder_clone.borrow_mut().systemd_autostart_units = enabled;
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_locker = lua.create_function(move |_, (command, blank): (String, Option<bool>)| {
+        let mut builder = builder_clone.borrow_mut();
+        builder.lock_command = if command.trim().is_empty() {
+            None
+        } else {
+            Some(command)
+        };
+        builder.lock_blank = blank.unwrap_or(false);
+        Ok(())
+    })?;
+
+    let lock =
+        lua.create_function(|lua, ()| create_action_table(lua, "LockSession", Value::Nil))?;
+
+    systemd_table.set("set_notify", set_notify)?;
+    systemd_table.set("set_autostart_units", set_autostart_units)?;
+    systemd_table.set("set_locker", set_locker)?;
+    systemd_table.set("lock", lock)?;
+    parent.set("systemd", systemd_table)?;
+    Ok(())
+}
+
+fn register_power_module(
+    lua: &Lua,
+    parent: &Table,
+    builder: SharedBuilder,
+) -> Result<(), ConfigError> {
+    let power_table = lua.create_table()?;
+
+    let builder_clone = builder.clone();
+    let on_ac = lua.create_function(move |_, command: String| {
+        builder_clone.borrow_mut().power_hooks.on_ac = Some(command);
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let on_battery = lua.create_function(move |_, command: String| {
+        builder_clone.borrow_mut().power_hooks.on_battery = Some(command);
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let on_low_battery = lua.create_function(move |_, (percent, command): (u32, String)| {
+        if !(1..=100).contains(&percent) {
+            return Err(mlua::Error::RuntimeError(format!(
+                "oxwm.power.on_low_battery: percent must be between 1 and 100, got {}",
+                percent
+            )));
+        }
+        builder_clone
+            .borrow_mut()
+            .power_hooks
+            .low_battery
+            .push((percent, command));
+        Ok(())
+    })?;
+
+    power_table.set("on_ac", on_ac)?;
+    power_table.set("on_battery", on_battery)?;
+    power_table.set("on_low_battery", on_low_battery)?;
+    parent.set("power", power_table)?;
     Ok(())
 }
 
@@ -773,8 +1920,36 @@ fn register_misc(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Result<()
     })?;
 
     let builder_clone = builder.clone();
-    let set_tags = lua.create_function(move |_, tags: Vec<String>| {
-        builder_clone.borrow_mut().tags = tags;
+    let set_tags = lua.create_function(move |lua, tags: Vec<Value>| {
+        let mut labels = Vec::with_capacity(tags.len());
+        let mut styles = Vec::with_capacity(tags.len());
+        for tag in tags {
+            match tag {
+                Value::Table(t) => {
+                    let label: String = t.get("label").map_err(|_| {
+                        mlua::Error::RuntimeError(
+                            "oxwm.set_tags: a tag table needs a 'label' field".into(),
+                        )
+                    })?;
+                    let color = match t.get::<Value>("color")? {
+                        Value::Nil => None,
+                        color => Some(parse_color_value(color)?),
+                    };
+                    labels.push(label);
+                    styles.push(crate::TagStyle {
+                        color,
+                        width: t.get("width")?,
+                    });
+                }
+                other => {
+                    labels.push(lua.unpack(other)?);
+                    styles.push(crate::TagStyle::default());
+                }
+            }
+        }
+        let mut b = builder_clone.borrow_mut();
+        b.tags = labels;
+        b.tag_styles = styles;
         Ok(())
     })?;
 
@@ -785,6 +1960,64 @@ fn register_misc(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Result<()
     let toggle_gaps =
         lua.create_function(|lua, ()| create_action_table(lua, "ToggleGaps", Value::Nil))?;
 
+    let inc_gaps = lua.create_function(|lua, delta: i32| {
+        create_action_table(lua, "IncGaps", Value::Integer(delta as i64))
+    })?;
+
+    let inc_inner_gaps = lua.create_function(|lua, delta: i32| {
+        create_action_table(lua, "IncInnerGaps", Value::Integer(delta as i64))
+    })?;
+
+    let inc_outer_gaps = lua.create_function(|lua, delta: i32| {
+        create_action_table(lua, "IncOuterGaps", Value::Integer(delta as i64))
+    })?;
+
+    let toggle_smart_gaps =
+        lua.create_function(|lua, ()| create_action_table(lua, "ToggleSmartGaps", Value::Nil))?;
+
+    let toggle_show_desktop =
+        lua.create_function(|lua, ()| create_action_table(lua, "ToggleShowDesktop", Value::Nil))?;
+
+    let pick_color =
+        lua.create_function(|lua, ()| create_action_table(lua, "PickColor", Value::Nil))?;
+
+    let toggle_passthrough =
+        lua.create_function(|lua, ()| create_action_table(lua, "TogglePassthrough", Value::Nil))?;
+
+    let chain = lua.create_function(|lua, actions: Table| {
+        for i in 1..=actions.len()? {
+            nested_action(actions.get(i)?, "oxwm.chain")?;
+        }
+        let arg = lua.create_table()?;
+        arg.set("__actions", actions)?;
+        create_action_table(lua, "Chain", Value::Table(arg))
+    })?;
+
+    let when = lua.create_function(
+        |lua, (condition, then, otherwise): (String, Value, Option<Value>)| {
+            parse_condition(&condition)?;
+            nested_action(then.clone(), "oxwm.when")?;
+            if let Some(otherwise) = &otherwise {
+                nested_action(otherwise.clone(), "oxwm.when")?;
+            }
+            let arg = lua.create_table()?;
+            arg.set("__condition", condition)?;
+            arg.set("__then", then)?;
+            arg.set("__else", otherwise)?;
+            create_action_table(lua, "Conditional", Value::Table(arg))
+        },
+    )?;
+
+    let swap_modkey = lua.create_function(|lua, modifier: String| {
+        parse_modkey_string(&modifier)
+            .map_err(|e| mlua::Error::RuntimeError(format!("oxwm.swap_modkey: {}", e)))?;
+        create_action_table(
+            lua,
+            "SwapModkey",
+            Value::String(lua.create_string(&modifier)?),
+        )
+    })?;
+
     let set_master_factor = lua.create_function(|lua, delta: i32| {
         create_action_table(lua, "SetMasterFactor", Value::Integer(delta as i64))
     })?;
@@ -815,53 +2048,271 @@ fn register_misc(lua: &Lua, parent: &Table, builder: SharedBuilder) -> Result<()
         Ok(())
     })?;
 
+    let builder_clone = builder.clone();
+    let set_xdg_autostart = lua.create_function(move |_, enabled: bool| {
+        builder_clone.borrow_mut().xdg_autostart = enabled;
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_status_file = lua.create_function(move |_, path: Option<String>| {
+        builder_clone.borrow_mut().status_file = path.filter(|p| !p.is_empty());
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_remote_control = lua.create_function(move |_, options: Option<Table>| {
+        let Some(options) = options else {
+            builder_clone.borrow_mut().remote_control = None;
+            return Ok(());
+        };
+        let token: String = options.get::<Option<String>>("token")?.unwrap_or_default();
+        if token.is_empty() {
+            return Err(mlua::Error::RuntimeError(
+                "oxwm.set_remote_control: 'token' is required".into(),
+            ));
+        }
+        let address = options
+            .get::<Option<String>>("address")?
+            .unwrap_or_else(|| "127.0.0.1:7878".to_string());
+        builder_clone.borrow_mut().remote_control = Some(crate::RemoteControl { address, token });
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_float_presets = lua.create_function(move |_, presets: Vec<Table>| {
+        let mut parsed = Vec::new();
+        for preset in presets {
+            let name: String = preset.get("name").map_err(|_| {
+                mlua::Error::RuntimeError(
+                    "oxwm.set_float_presets: each preset needs a 'name' field".into(),
+                )
+            })?;
+            let width: f32 = preset.get("width")?;
+            let height: f32 = preset.get("height")?;
+            if !(1.0..=100.0).contains(&width) || !(1.0..=100.0).contains(&height) {
+                return Err(mlua::Error::RuntimeError(format!(
+                    "oxwm.set_float_presets: preset '{}' width and height must be percentages between 1 and 100",
+                    name
+                )));
+            }
+            parsed.push(crate::FloatPreset {
+                name,
+                width: width / 100.0,
+                height: height / 100.0,
+            });
+        }
+        builder_clone.borrow_mut().float_presets = parsed;
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_tag_limit =
+        lua.create_function(move |_, (limit, overflow): (usize, Option<String>)| {
+            let overflow = match overflow.as_deref().unwrap_or("next") {
+                "next" => crate::TagOverflow::NextTag,
+                "float" => crate::TagOverflow::Float,
+                other => {
+                    return Err(mlua::Error::RuntimeError(format!(
+                        "oxwm.set_tag_limit: unknown overflow '{}'. use 'next' or 'float'",
+                        other
+                    )));
+                }
+            };
+            let mut builder = builder_clone.borrow_mut();
+            builder.tag_client_limit = limit;
+            builder.tag_overflow = overflow;
+            Ok(())
+        })?;
+
+    let builder_clone = builder.clone();
+    let set_focus_on_map = lua.create_function(move |_, policy: String| {
+        builder_clone.borrow_mut().focus_on_map = match policy.as_str() {
+            "always" => crate::FocusOnMap::Always,
+            "never" => crate::FocusOnMap::Never,
+            "same_tag" => crate::FocusOnMap::SameTag,
+            _ => {
+                return Err(mlua::Error::RuntimeError(format!(
+                    "oxwm.set_focus_on_map: unknown policy '{}'. use 'always', 'never' or 'same_tag'",
+                    policy
+                )));
+            }
+        };
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_remember_floating = lua.create_function(move |_, enabled: bool| {
+        builder_clone.borrow_mut().remember_floating_geometry = enabled;
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_float_heuristics = lua.create_function(move |_, options: Table| {
+        let mut builder = builder_clone.borrow_mut();
+        if let Some(fixed_size) = options.get::<Option<bool>>("fixed_size")? {
+            builder.float_fixed_size = fixed_size;
+        }
+        if let Some(utility) = options.get::<Option<bool>>("utility")? {
+            builder.float_utility = utility;
+        }
+        if let Some(min_area) = options.get::<Option<u32>>("min_area")? {
+            builder.float_below_area = min_area;
+        }
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_bell = lua.create_function(move |_, options: Table| {
+        let mut builder = builder_clone.borrow_mut();
+        if let Some(visual) = options.get::<Option<String>>("visual")? {
+            builder.visual_bell = match visual.as_str() {
+                "none" => crate::VisualBell::Off,
+                "border" => crate::VisualBell::Border,
+                "bar" => crate::VisualBell::Bar,
+                _ => {
+                    return Err(mlua::Error::RuntimeError(format!(
+                        "oxwm.set_bell: unknown visual bell '{}'. use 'none', 'border' or 'bar'",
+                        visual
+                    )));
+                }
+            };
+        }
+        if let Some(urgent) = options.get::<Option<bool>>("urgent")? {
+            builder.bell_urgent = urgent;
+        }
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_presentation = lua.create_function(move |_, options: Table| {
+        let mut builder = builder_clone.borrow_mut();
+        if let Some(indicator) = options.get::<Option<String>>("indicator")? {
+            builder.presentation.indicator = indicator;
+        }
+        if let Some(on_enter) = options.get::<Option<String>>("on_enter")? {
+            builder.presentation.on_enter = Some(on_enter).filter(|cmd| !cmd.is_empty());
+        }
+        if let Some(on_exit) = options.get::<Option<String>>("on_exit")? {
+            builder.presentation.on_exit = Some(on_exit).filter(|cmd| !cmd.is_empty());
+        }
+        Ok(())
+    })?;
+
+    let toggle_presentation =
+        lua.create_function(|lua, ()| create_action_table(lua, "TogglePresentation", Value::Nil))?;
+
+    let builder_clone = builder.clone();
+    let set_fade = lua.create_function(move |_, duration_ms: u64| {
+        builder_clone.borrow_mut().fade_duration_ms = duration_ms;
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_auto_reload = lua.create_function(move |_, enabled: bool| {
+        builder_clone.borrow_mut().auto_reload = enabled;
+        Ok(())
+    })?;
+
     let builder_clone = builder.clone();
     let auto_tile = lua.create_function(move |_, enabled: bool| {
         builder_clone.borrow_mut().auto_tile = enabled;
         Ok(())
     })?;
 
+    let builder_clone = builder.clone();
+    let set_swallow = lua.create_function(move |_, enabled: bool| {
+        builder_clone.borrow_mut().swallow = enabled;
+        Ok(())
+    })?;
+
+    let builder_clone = builder.clone();
+    let set_snap = lua.create_function(move |_, pixels: u32| {
+        builder_clone.borrow_mut().snap_distance = pixels;
+        Ok(())
+    })?;
+
     parent.set("set_terminal", set_terminal)?;
     parent.set("set_modkey", set_modkey)?;
     parent.set("set_tags", set_tags)?;
     parent.set("set_layout_symbol", set_layout_symbol)?;
     parent.set("autostart", autostart)?;
+    parent.set("set_xdg_autostart", set_xdg_autostart)?;
+    parent.set("set_status_file", set_status_file)?;
+    parent.set("set_remote_control", set_remote_control)?;
     parent.set("quit", quit)?;
     parent.set("restart", restart)?;
     parent.set("toggle_gaps", toggle_gaps)?;
+    parent.set("inc_gaps", inc_gaps)?;
+    parent.set("inc_inner_gaps", inc_inner_gaps)?;
+    parent.set("inc_outer_gaps", inc_outer_gaps)?;
+    parent.set("toggle_smart_gaps", toggle_smart_gaps)?;
+    parent.set("toggle_passthrough", toggle_passthrough)?;
+    parent.set("toggle_show_desktop", toggle_show_desktop)?;
+    parent.set("pick_color", pick_color)?;
+    parent.set("swap_modkey", swap_modkey)?;
+    parent.set("chain", chain)?;
+    parent.set("when", when)?;
     parent.set("set_master_factor", set_master_factor)?;
     parent.set("inc_num_master", inc_num_master)?;
     parent.set("show_keybinds", show_keybinds)?;
     parent.set("focus_monitor", focus_monitor)?;
     parent.set("auto_tile", auto_tile)?;
+    parent.set("set_snap", set_snap)?;
+    parent.set("set_swallow", set_swallow)?;
+    parent.set("set_float_heuristics", set_float_heuristics)?;
+    parent.set("set_float_presets", set_float_presets)?;
+    parent.set("set_remember_floating", set_remember_floating)?;
+    parent.set("set_focus_on_map", set_focus_on_map)?;
+    parent.set("set_tag_limit", set_tag_limit)?;
+    parent.set("set_bell", set_bell)?;
+    parent.set("set_presentation", set_presentation)?;
+    parent.set("toggle_presentation", toggle_presentation)?;
+    parent.set("set_fade", set_fade)?;
+    parent.set("set_auto_reload", set_auto_reload)?;
     Ok(())
 }
 
-fn parse_modifiers_value(_lua: &Lua, value: Value) -> mlua::Result<Vec<KeyButMask>> {
-    match value {
+/// Parses a binding's modifier list. The generic "Mod" is returned separately
+/// as a placeholder; it is pointed at the configured modkey once the whole
+/// config has been read.
+fn parse_modifiers_value(
+    _lua: &Lua,
+    value: Value,
+) -> mlua::Result<(Vec<KeyButMask>, Option<KeyButMask>)> {
+    let mod_strings = match value {
         Value::Table(t) => {
-            let mut mods = Vec::new();
+            let mut mod_strings = Vec::new();
             for i in 1..=t.len()? {
                 let mod_str: String = t.get(i)?;
-                let mask = parse_modkey_string(&mod_str)
-                    .map_err(|e| mlua::Error::RuntimeError(format!("oxwm.key.bind: invalid modifier - {}", e)))?;
-                mods.push(mask);
+                mod_strings.push(mod_str);
             }
-            Ok(mods)
+            mod_strings
         }
-        Value::String(s) => {
-            let s_str = s.to_str()?;
-            let mask = parse_modkey_string(&s_str)
-                .map_err(|e| mlua::Error::RuntimeError(format!("oxwm.key.bind: invalid modifier - {}", e)))?;
-            Ok(vec![mask])
+        Value::String(s) => vec![s.to_str()?.to_string()],
+        _ => {
+            return Err(mlua::Error::RuntimeError(
+                "oxwm.key.bind: first argument must be a table of modifiers like {\"Mod\"} or {\"Mod4\", \"Shift\"}".into(),
+            ));
         }
-        _ => Err(mlua::Error::RuntimeError(
-            "oxwm.key.bind: first argument must be a table of modifiers like {\"Mod4\"} or {\"Mod4\", \"Shift\"}".into(),
-        )),
+    };
+
+    let mut mods = Vec::new();
+    let mut modkey = None;
+    for mod_str in mod_strings {
+        if mod_str == "Mod" {
+            modkey = Some(KeyButMask::MOD4);
+            continue;
+        }
+        let mask = parse_modkey_string(&mod_str).map_err(|e| {
+            mlua::Error::RuntimeError(format!("oxwm.key.bind: invalid modifier - {}", e))
+        })?;
+        mods.push(mask);
     }
+    Ok((mods, modkey))
 }
 
-fn parse_modkey_string(s: &str) -> Result<KeyButMask, ConfigError> {
+pub fn parse_modkey_string(s: &str) -> Result<KeyButMask, ConfigError> {
     match s {
         "Mod1" => Ok(KeyButMask::MOD1),
         "Mod2" => Ok(KeyButMask::MOD2),
@@ -914,6 +2365,7 @@ fn string_to_action(s: &str) -> mlua::Result<KeyAction> {
     match s {
         "Spawn" => Ok(KeyAction::Spawn),
         "SpawnTerminal" => Ok(KeyAction::SpawnTerminal),
+        "SpawnTerminalInCwd" => Ok(KeyAction::SpawnTerminalInCwd),
         "KillClient" => Ok(KeyAction::KillClient),
         "FocusStack" => Ok(KeyAction::FocusStack),
         "MoveStack" => Ok(KeyAction::MoveStack),
@@ -939,6 +2391,33 @@ fn string_to_action(s: &str) -> mlua::Result<KeyAction> {
         "ShowKeybindOverlay" => Ok(KeyAction::ShowKeybindOverlay),
         "ScrollLeft" => Ok(KeyAction::ScrollLeft),
         "ScrollRight" => Ok(KeyAction::ScrollRight),
+        "TogglePassthrough" => Ok(KeyAction::TogglePassthrough),
+        "CycleFloatPreset" => Ok(KeyAction::CycleFloatPreset),
+        "ToggleMaximize" => Ok(KeyAction::ToggleMaximize),
+        "SwapModkey" => Ok(KeyAction::SwapModkey),
+        "SendKey" => Ok(KeyAction::SendKey),
+        "Chain" => Ok(KeyAction::Chain),
+        "Conditional" => Ok(KeyAction::Conditional),
+        "EnterMode" => Ok(KeyAction::EnterMode),
+        "ExitMode" => Ok(KeyAction::ExitMode),
+        "LockSession" => Ok(KeyAction::LockSession),
+        "ToggleShowDesktop" => Ok(KeyAction::ToggleShowDesktop),
+        "StashWindow" => Ok(KeyAction::StashWindow),
+        "PopStash" => Ok(KeyAction::PopStash),
+        "SpawnPinned" => Ok(KeyAction::SpawnPinned),
+        "SaveSnapshot" => Ok(KeyAction::SaveSnapshot),
+        "RestoreSnapshot" => Ok(KeyAction::RestoreSnapshot),
+        "TogglePresentation" => Ok(KeyAction::TogglePresentation),
+        "PickColor" => Ok(KeyAction::PickColor),
+        "InspectWindow" => Ok(KeyAction::InspectWindow),
+        "ToggleBarDebug" => Ok(KeyAction::ToggleBarDebug),
+        "MoveToNewTag" => Ok(KeyAction::MoveToNewTag),
+        "ToggleTagLinks" => Ok(KeyAction::ToggleTagLinks),
+        "ToggleScratchpad" => Ok(KeyAction::ToggleScratchpad),
+        "IncGaps" => Ok(KeyAction::IncGaps),
+        "IncInnerGaps" => Ok(KeyAction::IncInnerGaps),
+        "IncOuterGaps" => Ok(KeyAction::IncOuterGaps),
+        "ToggleSmartGaps" => Ok(KeyAction::ToggleSmartGaps),
         _ => Err(mlua::Error::RuntimeError(format!(
             "unknown action '{}'. this is an internal error, please report it",
             s
@@ -952,6 +2431,43 @@ fn value_to_arg(value: Value) -> mlua::Result<Arg> {
         Value::String(s) => Ok(Arg::Str(s.to_str()?.to_string())),
         Value::Integer(i) => Ok(Arg::Int(i as i32)),
         Value::Number(n) => Ok(Arg::Int(n as i32)),
+        Value::Table(t) if t.contains_key("__command")? => {
+            let command = value_to_arg(t.get("__command")?)?;
+            let cwd = t
+                .get::<Option<String>>("cwd")?
+                .map(|cwd| crate::signal::expand_home(&cwd));
+            let mut env = Vec::new();
+            if let Some(env_table) = t.get::<Option<Table>>("env")? {
+                for pair in env_table.pairs::<String, String>() {
+                    env.push(pair?);
+                }
+            }
+            env.sort();
+            Ok(Arg::Spawn {
+                command: Box::new(command),
+                options: crate::signal::SpawnOptions { cwd, env },
+            })
+        }
+        Value::Table(t) if t.contains_key("__actions")? => {
+            let actions: Table = t.get("__actions")?;
+            let mut parsed = Vec::new();
+            for i in 1..=actions.len()? {
+                parsed.push(nested_action(actions.get(i)?, "oxwm.chain")?);
+            }
+            Ok(Arg::Actions(parsed))
+        }
+        Value::Table(t) if t.contains_key("__condition")? => {
+            let condition: String = t.get("__condition")?;
+            let otherwise = match t.get::<Value>("__else")? {
+                Value::Nil => (KeyAction::None, Arg::None),
+                value => nested_action(value, "oxwm.when")?,
+            };
+            Ok(Arg::Conditional {
+                condition: parse_condition(&condition)?,
+                then: Box::new(nested_action(t.get("__then")?, "oxwm.when")?),
+                otherwise: Box::new(otherwise),
+            })
+        }
         Value::Table(t) => {
             let mut arr = Vec::new();
             for i in 1..=t.len()? {
@@ -964,6 +2480,49 @@ fn value_to_arg(value: Value) -> mlua::Result<Arg> {
     }
 }
 
+/// Parses an action used inside `oxwm.chain` or `oxwm.when`. Quit and restart
+/// are only allowed as bindings of their own.
+fn nested_action(value: Value, context: &str) -> mlua::Result<(KeyAction, Arg)> {
+    let Value::Table(t) = &value else {
+        return Err(mlua::Error::RuntimeError(format!(
+            "{}: expected an action table like oxwm.tag.view(0)",
+            context
+        )));
+    };
+    let Ok(action_name) = t.get::<String>("__action") else {
+        return Err(mlua::Error::RuntimeError(format!(
+            "{}: expected an action table like oxwm.tag.view(0)",
+            context
+        )));
+    };
+    let action = string_to_action(&action_name)?;
+    if matches!(action, KeyAction::Quit | KeyAction::Restart) {
+        return Err(mlua::Error::RuntimeError(format!(
+            "{}: quit and restart cannot be combined with other actions",
+            context
+        )));
+    }
+    let arg = value_to_arg(t.get("__arg")?)?;
+    Ok((action, arg))
+}
+
+fn parse_condition(condition: &str) -> mlua::Result<Condition> {
+    match condition {
+        "focused" => Ok(Condition::Focused),
+        "floating" => Ok(Condition::Floating),
+        "fullscreen" => Ok(Condition::Fullscreen),
+        "maximized" => Ok(Condition::Maximized),
+        "empty" => Ok(Condition::TagEmpty),
+        _ => match condition.strip_prefix("class:") {
+            Some(class) if !class.is_empty() => Ok(Condition::Class(class.to_string())),
+            _ => Err(mlua::Error::RuntimeError(format!(
+                "oxwm.when: unknown condition '{}'. use 'focused', 'floating', 'fullscreen', 'maximized', 'empty' or 'class:<name>'",
+                condition
+            ))),
+        },
+    }
+}
+
 fn create_action_table(lua: &Lua, action_name: &str, arg: Value) -> mlua::Result<Table> {
     let table = lua.create_table()?;
     table.set("__action", action_name)?;
@@ -971,6 +2530,170 @@ fn create_action_table(lua: &Lua, action_name: &str, arg: Value) -> mlua::Result
     Ok(table)
 }
 
+/// Parses a background color, or a `{ top, bottom }` table for a vertical
+/// gradient.
+fn parse_bar_region(region: &str, context: &str) -> mlua::Result<crate::bar::RegionKind> {
+    use crate::bar::RegionKind;
+    match region {
+        "tag" => Ok(RegionKind::Tag),
+        "layout" => Ok(RegionKind::Layout),
+        "indicator" => Ok(RegionKind::Indicator),
+        "title" => Ok(RegionKind::Title),
+        "block" => Ok(RegionKind::Block),
+        _ => Err(mlua::Error::RuntimeError(format!(
+            "{}: unknown region '{}'. use 'tag', 'layout', 'indicator', 'title' or 'block'",
+            context, region
+        ))),
+    }
+}
+
+/// Each zone is an IANA name ("local" for local time), labelled with its
+/// last part, or a table `{ tz = "...", label = "..." }`.
+fn parse_clock_zones(zones: Table) -> mlua::Result<Vec<crate::bar::ClockZone>> {
+    let mut parsed = Vec::new();
+    for i in 1..=zones.len()? {
+        let (tz, label) = match zones.get::<Value>(i)? {
+            Value::String(s) => (s.to_str()?.to_string(), None),
+            Value::Table(t) => (
+                t.get::<Option<String>>("tz")?
+                    .unwrap_or_else(|| "local".to_string()),
+                t.get::<Option<String>>("label")?,
+            ),
+            other => {
+                return Err(mlua::Error::RuntimeError(format!(
+                    "invalid clock zone {:?}. use a name like \"UTC\" or {{ tz = \"Europe/Berlin\", label = \"BER\" }}",
+                    other
+                )));
+            }
+        };
+        let label = label.unwrap_or_else(|| {
+            let name = tz.rsplit('/').next().unwrap_or(&tz);
+            name.replace('_', " ")
+        });
+        parsed.push(crate::bar::ClockZone {
+            label,
+            tz: Some(tz).filter(|tz| tz != "local"),
+        });
+    }
+    Ok(parsed)
+}
+
+/// `url` and `path` give the value shown for `{}`; each of `sources`,
+/// `{ name = "btc", url = "...", path = "..." }`, one shown for `{btc}`.
+fn parse_http_sources(options: &Table) -> mlua::Result<Vec<crate::bar::HttpSource>> {
+    let missing = |field: &str| {
+        mlua::Error::RuntimeError(format!("oxwm.bar.block.http: '{}' is required", field))
+    };
+
+    let mut sources = Vec::new();
+    if let Some(url) = options.get::<Option<String>>("url")? {
+        sources.push(crate::bar::HttpSource {
+            name: String::new(),
+            url,
+            path: options
+                .get::<Option<String>>("path")?
+                .ok_or_else(|| missing("path"))?,
+        });
+    }
+    if let Some(entries) = options.get::<Option<Table>>("sources")? {
+        for i in 1..=entries.len()? {
+            let entry: Table = entries.get(i)?;
+            sources.push(crate::bar::HttpSource {
+                name: entry
+                    .get::<Option<String>>("name")?
+                    .ok_or_else(|| missing("sources[].name"))?,
+                url: entry
+                    .get::<Option<String>>("url")?
+                    .ok_or_else(|| missing("sources[].url"))?,
+                path: entry
+                    .get::<Option<String>>("path")?
+                    .ok_or_else(|| missing("sources[].path"))?,
+            });
+        }
+    }
+    Ok(sources)
+}
+
+/// Default warning and critical percent of memory in use.
+const RAM_THRESHOLDS: (u32, u32) = (85, 95);
+
+/// Copies a block's `warning`, `critical` and `warning_color` into a new
+/// options table, with `defaults` for the levels left out.
+fn threshold_options(lua: &Lua, config: &Table, defaults: (u32, u32)) -> mlua::Result<Table> {
+    let warning: Option<u32> = config.get("warning").unwrap_or(None);
+    let critical: Option<u32> = config.get("critical").unwrap_or(None);
+    let warning_color: Value = config.get("warning_color")?;
+
+    let options_table = lua.create_table()?;
+    options_table.set("warning", warning.unwrap_or(defaults.0))?;
+    options_table.set("critical", critical.unwrap_or(defaults.1))?;
+    options_table.set("warning_color", warning_color)?;
+    Ok(options_table)
+}
+
+fn parse_thresholds(options: &Table) -> mlua::Result<crate::bar::Thresholds> {
+    let warning_color = match options.get::<Value>("warning_color")? {
+        Value::Nil => None,
+        value => Some(parse_color_value(value)?),
+    };
+    Ok(crate::bar::Thresholds {
+        warning: options.get("warning")?,
+        critical: options.get("critical")?,
+        warning_color,
+    })
+}
+
+/// A number is a width in pixels, a string like "4ch" a width in digits.
+fn parse_min_width(value: Value) -> mlua::Result<Option<crate::bar::MinWidth>> {
+    use crate::bar::MinWidth;
+
+    let invalid = |value: &str| {
+        mlua::Error::RuntimeError(format!(
+            "invalid min_width '{}'. use pixels like 40 or characters like \"4ch\"",
+            value
+        ))
+    };
+    match value {
+        Value::Nil => Ok(None),
+        Value::Integer(i) => u16::try_from(i)
+            .map(|pixels| Some(MinWidth::Pixels(pixels)))
+            .map_err(|_| invalid(&i.to_string())),
+        Value::String(s) => {
+            let s = s.to_str()?;
+            s.strip_suffix("ch")
+                .and_then(|chars| chars.trim().parse().ok())
+                .map(|chars| Some(MinWidth::Chars(chars)))
+                .ok_or_else(|| invalid(&s))
+        }
+        other => Err(invalid(&format!("{:?}", other))),
+    }
+}
+
+fn parse_block_align(align: Option<String>) -> mlua::Result<crate::bar::BlockAlign> {
+    use crate::bar::BlockAlign;
+
+    match align.as_deref() {
+        None | Some("left") => Ok(BlockAlign::Left),
+        Some("center") => Ok(BlockAlign::Center),
+        Some("right") => Ok(BlockAlign::Right),
+        Some(other) => Err(mlua::Error::RuntimeError(format!(
+            "invalid align '{}'. use \"left\", \"center\" or \"right\"",
+            other
+        ))),
+    }
+}
+
+fn parse_background_value(value: Value) -> mlua::Result<(u32, Option<u32>)> {
+    match value {
+        Value::Table(t) => {
+            let top = parse_color_value(t.get(1)?)?;
+            let bottom = parse_color_value(t.get(2)?)?;
+            Ok((top, Some(bottom)))
+        }
+        other => Ok((parse_color_value(other)?, None)),
+    }
+}
+
 fn parse_color_value(value: Value) -> mlua::Result<u32> {
     match value {
         Value::Integer(i) => Ok(i as u32),
@@ -1006,6 +2729,497 @@ fn parse_color_value(value: Value) -> mlua::Result<u32> {
     }
 }
 
+/// Parses `{ output = "DP-2", enabled = true, mode = "2560x1440", position = { 0, 0 },
+/// rotation = "left", scale = 1.5, primary = true }`.
+fn parse_output_setup(entry: &Table) -> mlua::Result<crate::OutputSetup> {
+    let output: String = entry.get("output")?;
+
+    let mode = match entry.get::<Option<String>>("mode")? {
+        Some(mode) => {
+            let parsed = mode
+                .split_once('x')
+                .and_then(|(w, h)| Some((w.trim().parse().ok()?, h.trim().parse().ok()?)));
+            match parsed {
+                Some(size) => Some(size),
+                None => {
+                    return Err(mlua::Error::RuntimeError(format!(
+                        "invalid mode '{}' for output '{}'. use format like \"1920x1080\"",
+                        mode, output
+                    )));
+                }
+            }
+        }
+        None => None,
+    };
+
+    let position = match entry.get::<Option<Vec<i32>>>("position")? {
+        Some(position) if position.len() == 2 => Some((position[0], position[1])),
+        Some(_) => {
+            return Err(mlua::Error::RuntimeError(format!(
+                "invalid position for output '{}'. use {{ x, y }}",
+                output
+            )));
+        }
+        None => None,
+    };
+
+    let rotation = match entry.get::<Option<String>>("rotation")?.as_deref() {
+        None | Some("normal") => crate::OutputRotation::Normal,
+        Some("left") => crate::OutputRotation::Left,
+        Some("right") => crate::OutputRotation::Right,
+        Some("inverted") => crate::OutputRotation::Inverted,
+        Some(other) => {
+            return Err(mlua::Error::RuntimeError(format!(
+                "invalid rotation '{}' for output '{}'. use 'normal', 'left', 'right' or 'inverted'",
+                other, output
+            )));
+        }
+    };
+
+    let scale = entry.get::<Option<f32>>("scale")?.unwrap_or(1.0);
+    if scale <= 0.0 {
+        return Err(mlua::Error::RuntimeError(format!(
+            "invalid scale {} for output '{}'. scale must be positive",
+            scale, output
+        )));
+    }
+
+    Ok(crate::OutputSetup {
+        rotation,
+        scale,
+        enabled: entry.get::<Option<bool>>("enabled")?.unwrap_or(true),
+        primary: entry.get::<Option<bool>>("primary")?.unwrap_or(false),
+        mode,
+        position,
+        output,
+    })
+}
+
+fn parse_block_list(blocks: Table) -> mlua::Result<Vec<crate::bar::BlockConfig>> {
+    use crate::bar::BlockCommand;
+
+    let mut block_configs = Vec::new();
+
+    for i in 1..=blocks.len()? {
+        let block_table: Table = blocks.get(i)?;
+        let block_type: String = block_table.get("__block_type")?;
+        let format: String = block_table.get("format").unwrap_or_default();
+        let interval: u64 = block_table.get("interval")?;
+        let color_val: Value = block_table.get("color")?;
+        let underline: bool = block_table.get("underline").unwrap_or(false);
+        let background_val: Value = block_table.get("background")?;
+        let popup: Option<String> = block_table.get("popup").unwrap_or(None);
+        let sensitive: bool = block_table.get("sensitive").unwrap_or(false);
+        let focused_only: bool = block_table.get("focused_only").unwrap_or(false);
+        let min_width = parse_min_width(block_table.get("min_width")?)?;
+        let align = parse_block_align(block_table.get("align").unwrap_or(None))?;
+        let signal: Option<u8> = block_table.get("signal").unwrap_or(None);
+        let clicks = crate::bar::BlockClicks {
+            left: block_table.get("on_click").unwrap_or(None),
+            middle: block_table.get("on_middle_click").unwrap_or(None),
+            right: block_table.get("on_right_click").unwrap_or(None),
+            scroll_up: block_table.get("on_scroll_up").unwrap_or(None),
+            scroll_down: block_table.get("on_scroll_down").unwrap_or(None),
+        };
+        let arg: Option<Value> = block_table.get("__arg").ok();
+
+        let cmd = match block_type.as_str() {
+            "DateTime" => {
+                let options = arg
+                    .and_then(|v| {
+                        if let Value::Table(t) = v {
+                            Some(t)
+                        } else {
+                            None
+                        }
+                    })
+                    .ok_or_else(|| {
+                        mlua::Error::RuntimeError("DateTime block missing format".into())
+                    })?;
+
+                let zones = match options.get::<Option<Table>>("zones")? {
+                    Some(zones) => parse_clock_zones(zones)?,
+                    None => Vec::new(),
+                };
+                BlockCommand::DateTime {
+                    date_format: options.get("date_format")?,
+                    zones,
+                    locale: options.get("locale").unwrap_or(None),
+                }
+            }
+            "Shell" => {
+                let options = arg
+                    .and_then(|v| {
+                        if let Value::Table(t) = v {
+                            Some(t)
+                        } else {
+                            None
+                        }
+                    })
+                    .ok_or_else(|| {
+                        mlua::Error::RuntimeError("Shell block missing command".into())
+                    })?;
+
+                let timeout_secs: Option<u64> = options.get("timeout").unwrap_or(None);
+                let error_text: Option<String> = options.get("error").unwrap_or(None);
+                BlockCommand::Shell {
+                    command: options.get("command")?,
+                    timeout_secs: timeout_secs.filter(|&secs| secs > 0),
+                    error_text: error_text
+                        .unwrap_or_else(|| crate::bar::DEFAULT_BLOCK_ERROR.to_string()),
+                }
+            }
+            "Context" => {
+                let options = arg
+                    .and_then(|v| {
+                        if let Value::Table(t) = v {
+                            Some(t)
+                        } else {
+                            None
+                        }
+                    })
+                    .ok_or_else(|| {
+                        mlua::Error::RuntimeError("Context block missing command".into())
+                    })?;
+
+                let classes: Option<Vec<String>> = options.get("classes").unwrap_or(None);
+                let timeout_secs: Option<u64> = options.get("timeout").unwrap_or(None);
+                BlockCommand::Context {
+                    command: options.get("command")?,
+                    classes: classes.unwrap_or_default(),
+                    timeout_secs: timeout_secs.filter(|&secs| secs > 0),
+                }
+            }
+            "Ram" | "Cpu" | "Temperature" => {
+                let options = arg
+                    .and_then(|v| {
+                        if let Value::Table(t) = v {
+                            Some(t)
+                        } else {
+                            None
+                        }
+                    })
+                    .ok_or_else(|| {
+                        mlua::Error::RuntimeError(format!("{} block missing options", block_type))
+                    })?;
+
+                let thresholds = parse_thresholds(&options)?;
+                match block_type.as_str() {
+                    "Ram" => BlockCommand::Ram { thresholds },
+                    "Cpu" => BlockCommand::Cpu { thresholds },
+                    _ => BlockCommand::Temperature {
+                        zone: options.get("zone")?,
+                        thresholds,
+                    },
+                }
+            }
+            "Gpu" => {
+                let device = match arg {
+                    Some(Value::Table(options)) => options.get("device")?,
+                    _ => None,
+                };
+                BlockCommand::Gpu { device }
+            }
+            "Privacy" => {
+                let options = arg
+                    .and_then(|v| {
+                        if let Value::Table(t) = v {
+                            Some(t)
+                        } else {
+                            None
+                        }
+                    })
+                    .ok_or_else(|| {
+                        mlua::Error::RuntimeError("Privacy block missing indicators".into())
+                    })?;
+
+                BlockCommand::Privacy {
+                    microphone: options.get("microphone")?,
+                    camera: options.get("camera")?,
+                }
+            }
+            "Http" => {
+                let options = arg
+                    .and_then(|v| {
+                        if let Value::Table(t) = v {
+                            Some(t)
+                        } else {
+                            None
+                        }
+                    })
+                    .ok_or_else(|| mlua::Error::RuntimeError("Http block missing url".into()))?;
+
+                BlockCommand::Http {
+                    sources: parse_http_sources(&options)?,
+                    timeout_secs: options.get("timeout")?,
+                    decimals: options.get("decimals")?,
+                }
+            }
+            "Network" => {
+                let options = arg
+                    .and_then(|v| {
+                        if let Value::Table(t) = v {
+                            Some(t)
+                        } else {
+                            None
+                        }
+                    })
+                    .ok_or_else(|| {
+                        mlua::Error::RuntimeError("Network block missing options".into())
+                    })?;
+
+                BlockCommand::Network {
+                    interface: options.get("interface")?,
+                    format_ethernet: options.get("ethernet")?,
+                    format_disconnected: options.get("disconnected")?,
+                }
+            }
+            "Volume" => {
+                let options = arg
+                    .and_then(|v| {
+                        if let Value::Table(t) = v {
+                            Some(t)
+                        } else {
+                            None
+                        }
+                    })
+                    .ok_or_else(|| {
+                        mlua::Error::RuntimeError("Volume block missing options".into())
+                    })?;
+
+                BlockCommand::Volume {
+                    format_muted: options.get("muted")?,
+                    step: options.get("step")?,
+                    max_volume: options.get("max")?,
+                }
+            }
+            "Updates" => {
+                let (backend, command) = match arg {
+                    Some(Value::Table(options)) => {
+                        (options.get("backend")?, options.get("command")?)
+                    }
+                    _ => (None, None),
+                };
+                BlockCommand::Updates { backend, command }
+            }
+            "Peripherals" => {
+                let options = arg
+                    .and_then(|v| {
+                        if let Value::Table(t) = v {
+                            Some(t)
+                        } else {
+                            None
+                        }
+                    })
+                    .ok_or_else(|| {
+                        mlua::Error::RuntimeError("Peripherals block missing options".into())
+                    })?;
+
+                let mut icons = crate::bar::PeripheralIcons::default();
+                if let Some(table) = options.get::<Option<Table>>("icons")? {
+                    for (kind, icon) in [
+                        ("mouse", &mut icons.mouse),
+                        ("keyboard", &mut icons.keyboard),
+                        ("headset", &mut icons.headset),
+                        ("gamepad", &mut icons.gamepad),
+                        ("other", &mut icons.other),
+                    ] {
+                        if let Some(text) = table.get::<Option<String>>(kind)? {
+                            *icon = text;
+                        }
+                    }
+                }
+                BlockCommand::Peripherals {
+                    icons,
+                    warning: options.get("warning")?,
+                }
+            }
+            "Vpn" => {
+                let options = arg
+                    .and_then(|v| {
+                        if let Value::Table(t) = v {
+                            Some(t)
+                        } else {
+                            None
+                        }
+                    })
+                    .ok_or_else(|| mlua::Error::RuntimeError("Vpn block missing options".into()))?;
+
+                let down_color = match options.get::<Value>("down_color")? {
+                    Value::Nil => None,
+                    value => Some(parse_color_value(value)?),
+                };
+                BlockCommand::Vpn {
+                    target: crate::bar::VpnTarget {
+                        interface: options.get("interface")?,
+                        connection: options.get("connection")?,
+                        toggle: options.get("toggle")?,
+                    },
+                    format_down: options.get("format_down")?,
+                    down_color,
+                }
+            }
+            "Containers" => {
+                let socket = match arg {
+                    Some(Value::Table(options)) => options.get("socket")?,
+                    _ => None,
+                };
+                BlockCommand::Containers { socket }
+            }
+            "Systemd" => {
+                let options = arg
+                    .and_then(|v| {
+                        if let Value::Table(t) = v {
+                            Some(t)
+                        } else {
+                            None
+                        }
+                    })
+                    .ok_or_else(|| {
+                        mlua::Error::RuntimeError("Systemd block missing units".into())
+                    })?;
+
+                BlockCommand::Systemd {
+                    system_units: options.get("units")?,
+                    user_units: options.get("user_units")?,
+                }
+            }
+            "Countdown" => {
+                let options = arg
+                    .and_then(|v| {
+                        if let Value::Table(t) = v {
+                            Some(t)
+                        } else {
+                            None
+                        }
+                    })
+                    .ok_or_else(|| {
+                        mlua::Error::RuntimeError("Countdown block missing file".into())
+                    })?;
+
+                BlockCommand::Countdown {
+                    file: options.get("file")?,
+                    warning_minutes: options.get("warning")?,
+                    critical_minutes: options.get("critical")?,
+                }
+            }
+            "Static" => {
+                let text = arg
+                    .and_then(|v| {
+                        if let Value::String(s) = v {
+                            s.to_str().ok().map(|s| s.to_string())
+                        } else {
+                            None
+                        }
+                    })
+                    .unwrap_or_default();
+                BlockCommand::Static(text)
+            }
+            "Battery" => {
+                let formats = arg
+                    .and_then(|v| {
+                        if let Value::Table(t) = v {
+                            Some(t)
+                        } else {
+                            None
+                        }
+                    })
+                    .ok_or_else(|| {
+                        mlua::Error::RuntimeError("Battery block missing formats".into())
+                    })?;
+
+                let charging: String = formats.get("charging")?;
+                let discharging: String = formats.get("discharging")?;
+                let full: String = formats.get("full")?;
+                let battery_name: Option<String> = formats.get("battery_name").unwrap_or(None);
+
+                let defaults = crate::bar::BatteryAlerts::default();
+                let low_color = match formats.get::<Value>("low_color")? {
+                    Value::Nil => None,
+                    value => Some(parse_color_value(value)?),
+                };
+                let alerts = crate::bar::BatteryAlerts {
+                    warning: formats
+                        .get::<Option<u32>>("warning")?
+                        .unwrap_or(defaults.warning),
+                    critical: formats
+                        .get::<Option<u32>>("critical")?
+                        .unwrap_or(defaults.critical),
+                    low_color,
+                    on_critical: formats.get("on_critical")?,
+                };
+
+                BlockCommand::Battery {
+                    format_charging: charging,
+                    format_discharging: discharging,
+                    format_full: full,
+                    battery_name,
+                    alerts,
+                }
+            }
+            _ => {
+                return Err(mlua::Error::RuntimeError(format!(
+                    "Unknown block type '{}'",
+                    block_type
+                )));
+            }
+        };
+
+        let color_u32 = parse_color_value(color_val)?;
+        let (background, gradient) = match background_val {
+            Value::Nil => (None, None),
+            value => {
+                let (background, gradient) = parse_background_value(value)?;
+                (Some(background), gradient)
+            }
+        };
+
+        let block = crate::bar::BlockConfig {
+            format,
+            command: cmd,
+            interval_secs: interval,
+            color: color_u32,
+            underline,
+            background,
+            gradient,
+            popup,
+            sensitive,
+            focused_only,
+            min_width,
+            align,
+            clicks,
+            signal,
+        };
+
+        block_configs.push(block);
+    }
+
+    Ok(block_configs)
+}
+
+/// Terminal command listing the failed units among those a systemd block
+/// watches, kept open until Enter is pressed.
+fn failed_units_popup(units: &[String], user_units: &[String]) -> String {
+    let list = |scope: &str, units: &[String]| {
+        let mut command = format!("systemctl {}--no-pager list-units --state=failed", scope);
+        for unit in units {
+            command.push(' ');
+            command.push_str(unit);
+        }
+        command
+    };
+    let watch_all = units.is_empty() && user_units.is_empty();
+
+    let mut commands = Vec::new();
+    if watch_all || !units.is_empty() {
+        commands.push(list("", units));
+    }
+    if watch_all || !user_units.is_empty() {
+        commands.push(list("--user ", user_units));
+    }
+    format!("sh -c '{}; read -r _'", commands.join("; "))
+}
+
 fn create_block_config(
     lua: &Lua,
     config: Table,
@@ -1019,11 +3233,43 @@ fn create_block_config(
     let interval: u64 = config.get("interval")?;
     let color: Value = config.get("color")?;
     let underline: bool = config.get("underline").unwrap_or(false);
+    let background: Value = config.get("background")?;
+    let popup: Option<String> = config.get("popup").unwrap_or(None);
+    let sensitive: bool = config.get("sensitive").unwrap_or(false);
+    let focused_only: bool = config.get("focused_only").unwrap_or(false);
+    let min_width: Value = config.get("min_width")?;
+    let align: Option<String> = config.get("align").unwrap_or(None);
+    let signal: Option<u8> = config.get("signal").unwrap_or(None);
+    if let Some(n) = signal
+        && !(1..=crate::signal::MAX_BLOCK_SIGNAL).contains(&n)
+    {
+        return Err(mlua::Error::RuntimeError(format!(
+            "oxwm.bar.block: signal must be between 1 and {}",
+            crate::signal::MAX_BLOCK_SIGNAL
+        )));
+    }
 
     table.set("format", format)?;
     table.set("interval", interval)?;
     table.set("color", color)?;
     table.set("underline", underline)?;
+    table.set("background", background)?;
+    table.set("popup", popup)?;
+    table.set("sensitive", sensitive)?;
+    table.set("focused_only", focused_only)?;
+    table.set("min_width", min_width)?;
+    table.set("align", align)?;
+    table.set("signal", signal)?;
+    for key in [
+        "on_click",
+        "on_middle_click",
+        "on_right_click",
+        "on_scroll_up",
+        "on_scroll_down",
+    ] {
+        let command: Option<String> = config.get(key).unwrap_or(None);
+        table.set(key, command)?;
+    }
 
     if let Some(arg_val) = arg {
         table.set("__arg", arg_val)?;
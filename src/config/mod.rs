@@ -1,4 +1,7 @@
 mod lua;
 mod lua_api;
+mod watch;
 
 pub use lua::parse_lua_config;
+pub use lua_api::parse_modkey_string;
+pub use watch::ConfigWatcher;
@@ -0,0 +1,100 @@
+use std::ffi::{CString, c_char, c_int};
+use std::fs::File;
+use std::io::{self, ErrorKind, Read};
+use std::os::fd::FromRawFd;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver};
+
+const IN_CLOEXEC: c_int = 0o2000000;
+const IN_CLOSE_WRITE: u32 = 0x08;
+const IN_MOVED_TO: u32 = 0x80;
+/// `struct inotify_event` without its name: wd, mask, cookie and len.
+const EVENT_HEADER_LEN: usize = 16;
+
+unsafe extern "C" {
+    fn inotify_init1(flags: c_int) -> c_int;
+    fn inotify_add_watch(fd: c_int, pathname: *const c_char, mask: u32) -> c_int;
+}
+
+/// Wakes up when a Lua file in the config's directory is saved. The
+/// directory is watched rather than the file since many editors save by
+/// renaming a new file over the old one.
+pub struct ConfigWatcher {
+    changes: Receiver<()>,
+}
+
+impl ConfigWatcher {
+    pub fn spawn(config_path: &Path) -> io::Result<Self> {
+        let directory = config_path
+            .parent()
+            .filter(|directory| !directory.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        let directory = CString::new(directory.as_os_str().as_bytes())
+            .map_err(|e| io::Error::new(ErrorKind::InvalidInput, e))?;
+
+        let fd = unsafe { inotify_init1(IN_CLOEXEC) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // Closes the descriptor if adding the watch fails.
+        let mut inotify = unsafe { File::from_raw_fd(fd) };
+        if unsafe { inotify_add_watch(fd, directory.as_ptr(), IN_CLOSE_WRITE | IN_MOVED_TO) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let (sender, changes) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("oxwm-config-watch".to_string())
+            .spawn(move || {
+                let mut buffer = [0u8; 4096];
+                loop {
+                    let read = match inotify.read(&mut buffer) {
+                        Ok(0) => break,
+                        Ok(read) => read,
+                        Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                        Err(_) => break,
+                    };
+                    if names_lua_file(&buffer[..read]) && sender.send(()).is_err() {
+                        break;
+                    }
+                }
+            })?;
+
+        Ok(Self { changes })
+    }
+
+    /// Returns true if a Lua file was saved since the last call.
+    pub fn changed(&self) -> bool {
+        let mut changed = false;
+        while self.changes.try_recv().is_ok() {
+            changed = true;
+        }
+        changed
+    }
+}
+
+/// Whether any event in a buffer read from inotify is about a `.lua` file.
+fn names_lua_file(events: &[u8]) -> bool {
+    let mut offset = 0;
+    while offset + EVENT_HEADER_LEN <= events.len() {
+        let len_bytes = [
+            events[offset + 12],
+            events[offset + 13],
+            events[offset + 14],
+            events[offset + 15],
+        ];
+        let name_start = offset + EVENT_HEADER_LEN;
+        let name_end = (name_start + u32::from_ne_bytes(len_bytes) as usize).min(events.len());
+        // Names are padded with NULs.
+        let name = events[name_start..name_end]
+            .split(|&byte| byte == 0)
+            .next()
+            .unwrap_or_default();
+        if name.ends_with(b".lua") {
+            return true;
+        }
+        offset = name_end;
+    }
+    false
+}
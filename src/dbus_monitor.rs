@@ -0,0 +1,54 @@
+use std::io::{self, BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+
+/// Follows `dbus-monitor` on the system bus and reports when a signal
+/// matching `rule` with the given member arrives.
+pub struct SignalWatcher {
+    monitor: Child,
+    signals: Receiver<()>,
+}
+
+impl SignalWatcher {
+    pub fn spawn(rule: &str, member: &str) -> io::Result<Self> {
+        let mut monitor = Command::new("dbus-monitor")
+            .args(["--system", rule])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        let stdout = monitor.stdout.take().expect("stdout is piped");
+        let needle = format!("member={}", member);
+        let (sender, signals) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if line.starts_with("signal") && line.contains(&needle) && sender.send(()).is_err()
+                {
+                    break;
+                }
+            }
+        });
+
+        Ok(Self { monitor, signals })
+    }
+
+    /// Returns true if a signal arrived since the last call.
+    pub fn fired(&self) -> bool {
+        let mut fired = false;
+        while self.signals.try_recv().is_ok() {
+            fired = true;
+        }
+        fired
+    }
+}
+
+impl Drop for SignalWatcher {
+    fn drop(&mut self) {
+        let _ = self.monitor.kill();
+        let _ = self.monitor.wait();
+    }
+}
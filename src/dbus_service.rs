@@ -0,0 +1,157 @@
+use crate::json::Json;
+use std::io;
+use std::sync::mpsc::{Receiver, Sender};
+
+pub const BUS_NAME: &str = "org.oxwm.WM";
+pub const OBJECT_PATH: &str = "/org/oxwm/WM";
+
+/// A method call forwarded to the event loop, answered with the same
+/// response object the IPC socket would send.
+pub struct DbusRequest {
+    pub body: Result<Json, String>,
+    reply: Sender<Json>,
+}
+
+impl DbusRequest {
+    pub fn respond(self, response: Json) {
+        let _ = self.reply.send(response);
+    }
+}
+
+#[cfg(feature = "dbus")]
+mod service {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::Duration;
+    use zbus::object_server::SignalEmitter;
+
+    /// How long a D-Bus caller waits for the event loop to answer.
+    const REPLY_TIMEOUT: Duration = Duration::from_secs(2);
+
+    /// Forwards a request to the event loop and waits for its response.
+    fn call(requests: &Sender<DbusRequest>, body: Result<Json, String>) -> Json {
+        let (reply, response) = mpsc::channel();
+        if requests.send(DbusRequest { body, reply }).is_err() {
+            return crate::ipc::failure("window manager is shutting down");
+        }
+        response
+            .recv_timeout(REPLY_TIMEOUT)
+            .unwrap_or_else(|_| crate::ipc::failure("window manager did not respond"))
+    }
+
+    struct WmInterface {
+        requests: Sender<DbusRequest>,
+    }
+
+    #[zbus::interface(name = "org.oxwm.WM")]
+    impl WmInterface {
+        /// Runs an IPC request line and returns the JSON response.
+        #[zbus(out_args("response"))]
+        fn command(&self, request: &str) -> String {
+            call(&self.requests, crate::ipc::parse_request(request.trim())).to_string()
+        }
+
+        #[zbus(out_args("tree"))]
+        fn get_tree(&self) -> String {
+            let request = Json::object().with("command", "get_tree");
+            call(&self.requests, Ok(request)).to_string()
+        }
+
+        fn view_tag(&self, tag: u32) -> zbus::fdo::Result<()> {
+            let request = Json::object().with("command", "view_tag").with("tag", tag);
+            let response = call(&self.requests, Ok(request));
+            match response.get("error").and_then(Json::as_str) {
+                Some(error) => Err(zbus::fdo::Error::Failed(error.to_string())),
+                None => Ok(()),
+            }
+        }
+
+        #[zbus(signal)]
+        async fn focus_changed(
+            emitter: &SignalEmitter<'_>,
+            window: u32,
+            title: &str,
+        ) -> zbus::Result<()>;
+
+        #[zbus(signal)]
+        async fn tags_changed(
+            emitter: &SignalEmitter<'_>,
+            monitor: u32,
+            tags: u32,
+        ) -> zbus::Result<()>;
+    }
+
+    /// Owns `org.oxwm.WM` on the session bus. Method calls are queued for
+    /// the event loop, which answers them from `poll`.
+    pub struct DbusService {
+        connection: zbus::blocking::Connection,
+        requests: Receiver<DbusRequest>,
+    }
+
+    impl DbusService {
+        pub fn spawn() -> io::Result<Self> {
+            let (sender, requests) = mpsc::channel();
+            let connection = zbus::blocking::connection::Builder::session()
+                .and_then(|builder| builder.name(BUS_NAME))
+                .and_then(|builder| builder.serve_at(OBJECT_PATH, WmInterface { requests: sender }))
+                .and_then(|builder| builder.build())
+                .map_err(io::Error::other)?;
+            Ok(Self {
+                connection,
+                requests,
+            })
+        }
+
+        pub fn poll(&self) -> Vec<DbusRequest> {
+            self.requests.try_iter().collect()
+        }
+
+        pub fn emit_focus_changed(&self, window: u32, title: &str) {
+            self.emit(|emitter| zbus::block_on(WmInterface::focus_changed(emitter, window, title)));
+        }
+
+        pub fn emit_tags_changed(&self, monitor: u32, tags: u32) {
+            self.emit(|emitter| zbus::block_on(WmInterface::tags_changed(emitter, monitor, tags)));
+        }
+
+        fn emit(&self, signal: impl FnOnce(&SignalEmitter<'static>) -> zbus::Result<()>) {
+            let result = self
+                .connection
+                .object_server()
+                .interface::<_, WmInterface>(OBJECT_PATH)
+                .and_then(|iface| signal(iface.signal_emitter()));
+            if let Err(e) = result {
+                eprintln!("Failed to emit D-Bus signal: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "dbus"))]
+mod service {
+    use super::*;
+
+    /// Stand-in when oxwm is built without the `dbus` feature.
+    pub struct DbusService {
+        requests: Receiver<DbusRequest>,
+    }
+
+    impl DbusService {
+        pub fn spawn() -> io::Result<Self> {
+            Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "oxwm was built without the dbus feature",
+            ))
+        }
+
+        pub fn poll(&self) -> Vec<DbusRequest> {
+            self.requests.try_iter().collect()
+        }
+
+        pub fn emit_focus_changed(&self, _window: u32, _title: &str) {}
+
+        pub fn emit_tags_changed(&self, _monitor: u32, _tags: u32) {}
+    }
+}
+
+pub use service::DbusService;
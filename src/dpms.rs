@@ -0,0 +1,31 @@
+use crate::errors::X11Error;
+use x11rb::connection::RequestConnection;
+use x11rb::protocol::dpms::{self, ConnectionExt as _};
+use x11rb::rust_connection::RustConnection;
+
+fn is_available(connection: &RustConnection) -> Result<bool, X11Error> {
+    Ok(connection
+        .extension_information(dpms::X11_EXTENSION_NAME)?
+        .is_some())
+}
+
+/// Returns whether DPMS is enabled; false when the server lacks the
+/// extension.
+pub fn is_enabled(connection: &RustConnection) -> Result<bool, X11Error> {
+    if !is_available(connection)? {
+        return Ok(false);
+    }
+    Ok(connection.dpms_info()?.reply()?.state)
+}
+
+pub fn set_enabled(connection: &RustConnection, enabled: bool) -> Result<(), X11Error> {
+    if !is_available(connection)? {
+        return Ok(());
+    }
+    if enabled {
+        connection.dpms_enable()?;
+    } else {
+        connection.dpms_disable()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,337 @@
+use crate::json::Json;
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Connections sending a longer line are dropped.
+const MAX_REQUEST_BYTES: usize = 64 * 1024;
+/// Longest line a TCP client may send before it authenticated.
+const MAX_AUTH_BYTES: usize = 1024;
+/// TCP clients that haven't authenticated within this are dropped.
+const AUTH_TIMEOUT: Duration = Duration::from_secs(10);
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Where the IPC socket lives: `$OXWM_SOCKET`, else `oxwm.sock` in
+/// `$XDG_RUNTIME_DIR`, else a per-display socket in /tmp.
+pub fn socket_path() -> PathBuf {
+    if let Some(path) = std::env::var_os("OXWM_SOCKET") {
+        return PathBuf::from(path);
+    }
+    if let Some(dir) = std::env::var_os("XDG_RUNTIME_DIR") {
+        return PathBuf::from(dir).join("oxwm.sock");
+    }
+    let display = std::env::var("DISPLAY").unwrap_or_default();
+    PathBuf::from(format!("/tmp/oxwm{}.sock", display.replace('/', "_")))
+}
+
+/// A request line read from a client. Lines are either a JSON object such
+/// as `{"command": "get_tree"}`, or a bare command followed by
+/// whitespace-separated arguments, which becomes
+/// `{"command": ..., "args": [...]}`.
+pub struct Request {
+    pub client: u64,
+    pub body: Result<Json, String>,
+}
+
+enum Stream {
+    Unix(UnixStream),
+    Tcp(TcpStream),
+}
+
+impl Stream {
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        match self {
+            Stream::Unix(stream) => stream.set_nonblocking(nonblocking),
+            Stream::Tcp(stream) => stream.set_nonblocking(nonblocking),
+        }
+    }
+
+    fn set_write_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        match self {
+            Stream::Unix(stream) => stream.set_write_timeout(timeout),
+            Stream::Tcp(stream) => stream.set_write_timeout(timeout),
+        }
+    }
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Stream::Unix(stream) => stream.read(buf),
+            Stream::Tcp(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Stream::Unix(stream) => stream.write(buf),
+            Stream::Tcp(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Stream::Unix(stream) => stream.flush(),
+            Stream::Tcp(stream) => stream.flush(),
+        }
+    }
+}
+
+struct Connection {
+    id: u64,
+    stream: Stream,
+    buffer: Vec<u8>,
+    /// Unix socket clients are trusted; TCP clients must send
+    /// `auth <token>` before anything else.
+    authenticated: bool,
+    connected: Instant,
+}
+
+impl Connection {
+    fn max_line(&self) -> usize {
+        if self.authenticated {
+            MAX_REQUEST_BYTES
+        } else {
+            MAX_AUTH_BYTES
+        }
+    }
+}
+
+struct TcpServer {
+    listener: TcpListener,
+    address: String,
+    token: String,
+}
+
+/// Line-based JSON IPC over a Unix socket, and optionally a token-guarded
+/// TCP port, polled from the event loop.
+pub struct IpcServer {
+    listener: UnixListener,
+    path: PathBuf,
+    tcp: Option<TcpServer>,
+    connections: Vec<Connection>,
+    next_id: u64,
+}
+
+impl IpcServer {
+    pub fn bind() -> io::Result<Self> {
+        let path = socket_path();
+        if path.exists() {
+            if UnixStream::connect(&path).is_ok() {
+                return Err(io::Error::new(
+                    ErrorKind::AddrInUse,
+                    format!("{} is in use by another instance", path.display()),
+                ));
+            }
+            std::fs::remove_file(&path)?;
+        }
+
+        let listener = UnixListener::bind(&path)?;
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            listener,
+            path,
+            tcp: None,
+            connections: Vec::new(),
+            next_id: 0,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Also accepts clients on a TCP address. Keeps listening if already
+    /// there; a new token disconnects the TCP clients that used the old one.
+    pub fn listen_tcp(&mut self, address: &str, token: &str) -> io::Result<()> {
+        if let Some(tcp) = &mut self.tcp
+            && tcp.address == address
+        {
+            if tcp.token != token {
+                tcp.token = token.to_string();
+                self.drop_tcp_clients();
+            }
+            return Ok(());
+        }
+
+        self.stop_tcp();
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        self.tcp = Some(TcpServer {
+            listener,
+            address: address.to_string(),
+            token: token.to_string(),
+        });
+        Ok(())
+    }
+
+    /// Stops listening on TCP and disconnects TCP clients.
+    pub fn stop_tcp(&mut self) {
+        self.tcp = None;
+        self.drop_tcp_clients();
+    }
+
+    fn drop_tcp_clients(&mut self) {
+        self.connections
+            .retain(|connection| matches!(connection.stream, Stream::Unix(_)));
+    }
+
+    fn accept(&mut self, stream: Stream, authenticated: bool) {
+        if stream.set_nonblocking(true).is_ok() {
+            self.connections.push(Connection {
+                id: self.next_id,
+                stream,
+                buffer: Vec::new(),
+                authenticated,
+                connected: Instant::now(),
+            });
+            self.next_id += 1;
+        }
+    }
+
+    /// Accepts pending connections and returns every complete request line.
+    pub fn poll(&mut self) -> Vec<Request> {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, _)) => self.accept(Stream::Unix(stream), true),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => {
+                    eprintln!("IPC accept failed: {}", e);
+                    break;
+                }
+            }
+        }
+
+        while let Some(tcp) = &self.tcp {
+            match tcp.listener.accept() {
+                Ok((stream, _)) => self.accept(Stream::Tcp(stream), false),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => {
+                    eprintln!("IPC accept failed: {}", e);
+                    break;
+                }
+            }
+        }
+
+        let token = self.tcp.as_ref().map(|tcp| tcp.token.as_str());
+        let mut requests = Vec::new();
+        self.connections.retain_mut(|connection| {
+            (connection.authenticated || connection.connected.elapsed() < AUTH_TIMEOUT)
+                && receive(connection, token, &mut requests)
+        });
+        requests
+    }
+
+    /// Sends a response line to a client, dropping it if it can't keep up.
+    pub fn respond(&mut self, client: u64, response: &Json) {
+        let Some(index) = self.connections.iter().position(|c| c.id == client) else {
+            return;
+        };
+        if send(&mut self.connections[index].stream, response).is_err() {
+            self.connections.remove(index);
+        }
+    }
+}
+
+fn send(stream: &mut Stream, response: &Json) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    stream.write_all(format!("{}\n", response).as_bytes())?;
+    stream.set_nonblocking(true)
+}
+
+/// Checks a TCP client's `auth <token>` line, which is never parsed as
+/// JSON; false drops the connection.
+fn authenticate(connection: &mut Connection, line: &str, token: Option<&str>) -> bool {
+    let mut words = line.split_whitespace();
+    let given = match (words.next(), words.next(), words.next()) {
+        (Some("auth"), Some(given), None) => Some(given),
+        _ => None,
+    };
+
+    let accepted = token.is_some_and(|token| given.is_some_and(|given| tokens_match(given, token)));
+    let response = if accepted {
+        success(Json::Null)
+    } else {
+        failure("authentication required")
+    };
+    connection.authenticated = accepted;
+    send(&mut connection.stream, &response).is_ok() && accepted
+}
+
+/// Compares without bailing out at the first differing byte.
+fn tokens_match(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+impl Drop for IpcServer {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Reads whatever the client has sent so far and collects its complete
+/// request lines; false once it hung up or has to be dropped.
+fn receive(connection: &mut Connection, token: Option<&str>, requests: &mut Vec<Request>) -> bool {
+    let mut chunk = [0u8; 4096];
+    loop {
+        match connection.stream.read(&mut chunk) {
+            Ok(0) => return false,
+            Ok(n) => connection.buffer.extend_from_slice(&chunk[..n]),
+            Err(e) if e.kind() == ErrorKind::WouldBlock => return true,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(_) => return false,
+        }
+
+        while let Some(end) = connection.buffer.iter().position(|&b| b == b'\n') {
+            if end > connection.max_line() {
+                return false;
+            }
+            let line: Vec<u8> = connection.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if connection.authenticated {
+                requests.push(Request {
+                    client: connection.id,
+                    body: parse_request(line),
+                });
+            } else if !authenticate(connection, line, token) {
+                return false;
+            }
+        }
+        if connection.buffer.len() > connection.max_line() {
+            return false;
+        }
+    }
+}
+
+pub fn parse_request(line: &str) -> Result<Json, String> {
+    if line.starts_with('{') {
+        return Json::parse(line);
+    }
+
+    let mut words = line.split_whitespace();
+    let command = words.next().unwrap_or_default();
+    let args: Vec<Json> = words.map(Json::from).collect();
+    Ok(Json::object().with("command", command).with("args", args))
+}
+
+pub fn success(result: Json) -> Json {
+    Json::object().with("success", true).with("result", result)
+}
+
+pub fn failure(error: &str) -> Json {
+    Json::object().with("success", false).with("error", error)
+}
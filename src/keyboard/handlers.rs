@@ -6,6 +6,7 @@ use x11rb::protocol::xproto::*;
 
 use crate::errors::X11Error;
 use crate::keyboard::keysyms::{self, Keysym, format_keysym};
+use crate::signal::SpawnOptions;
 
 /// When adding a new action, update:
 /// 1. Add variant here
@@ -18,6 +19,7 @@ use crate::keyboard::keysyms::{self, Keysym, format_keysym};
 pub enum KeyAction {
     Spawn,
     SpawnTerminal,
+    SpawnTerminalInCwd,
     KillClient,
     FocusStack,
     MoveStack,
@@ -43,6 +45,33 @@ pub enum KeyAction {
     IncNumMaster,
     ScrollLeft,
     ScrollRight,
+    TogglePassthrough,
+    CycleFloatPreset,
+    ToggleMaximize,
+    SwapModkey,
+    SendKey,
+    Chain,
+    Conditional,
+    EnterMode,
+    ExitMode,
+    LockSession,
+    ToggleShowDesktop,
+    StashWindow,
+    PopStash,
+    SpawnPinned,
+    SaveSnapshot,
+    RestoreSnapshot,
+    TogglePresentation,
+    PickColor,
+    InspectWindow,
+    ToggleBarDebug,
+    MoveToNewTag,
+    ToggleTagLinks,
+    ToggleScratchpad,
+    IncGaps,
+    IncInnerGaps,
+    IncOuterGaps,
+    ToggleSmartGaps,
     None,
 }
 
@@ -52,6 +81,28 @@ pub enum Arg {
     Int(i32),
     Str(String),
     Array(Vec<String>),
+    Spawn {
+        command: Box<Arg>,
+        options: SpawnOptions,
+    },
+    Actions(Vec<(KeyAction, Arg)>),
+    Conditional {
+        condition: Condition,
+        then: Box<(KeyAction, Arg)>,
+        otherwise: Box<(KeyAction, Arg)>,
+    },
+}
+
+/// State of the focused window or current tag checked by a conditional
+/// action.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    Focused,
+    Floating,
+    Fullscreen,
+    Maximized,
+    TagEmpty,
+    Class(String),
 }
 
 impl Arg {
@@ -63,13 +114,23 @@ impl Arg {
 #[derive(Clone)]
 pub struct KeyPress {
     pub(crate) modifiers: Vec<KeyButMask>,
+    /// Set when the key was bound with the generic "Mod" modifier. Holds the
+    /// mask it currently stands for, which follows `Config::modkey`.
+    pub(crate) modkey: Option<KeyButMask>,
     pub(crate) keysym: Keysym,
 }
 
+impl KeyPress {
+    pub fn mask(&self) -> u16 {
+        modifiers_to_mask(&self.modifiers) | self.modkey.map_or(0, u16::from)
+    }
+}
+
 impl std::fmt::Debug for KeyPress {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("KeyPress")
             .field("modifiers", &self.modifiers)
+            .field("modkey", &self.modkey)
             .field("keysym", &format_keysym(self.keysym))
             .finish()
     }
@@ -94,11 +155,25 @@ impl KeyBinding {
         arg: Arg,
     ) -> Self {
         Self {
-            keys: vec![KeyPress { modifiers, keysym }],
+            keys: vec![KeyPress {
+                modifiers,
+                modkey: None,
+                keysym,
+            }],
             func,
             arg,
         }
     }
+
+    /// Points every generic "Mod" in this binding at `modkey`. Keys bound
+    /// with an explicit modifier are left alone.
+    pub fn set_modkey(&mut self, modkey: KeyButMask) {
+        for key in &mut self.keys {
+            if key.modkey.is_some() {
+                key.modkey = Some(modkey);
+            }
+        }
+    }
 }
 
 pub type Key = KeyBinding;
@@ -181,6 +256,7 @@ pub fn grab_keys(
     root: Window,
     keybindings: &[KeyBinding],
     current_key: usize,
+    grab_escape: bool,
 ) -> std::result::Result<KeyboardMapping, X11Error> {
     let setup = connection.setup();
     let min_keycode = setup.min_keycode;
@@ -205,7 +281,7 @@ pub fn grab_keys(
 
             let key = &keybinding.keys[current_key];
             if key.keysym == mapping.keycode_to_keysym(keycode) {
-                let modifier_mask = modifiers_to_mask(&key.modifiers);
+                let modifier_mask = key.mask();
                 for &ignore_mask in &modifiers {
                     connection.grab_key(
                         true,
@@ -220,7 +296,7 @@ pub fn grab_keys(
         }
     }
 
-    if current_key > 0
+    if (current_key > 0 || grab_escape)
         && let Some(escape_keycode) =
             mapping.find_keycode(keysyms::XK_ESCAPE, min_keycode, max_keycode)
     {
@@ -277,7 +353,7 @@ fn handle_first_key(
         }
 
         let first_key = &keybinding.keys[0];
-        let modifier_mask = modifiers_to_mask(&first_key.modifiers);
+        let modifier_mask = first_key.mask();
 
         if event_keysym == first_key.keysym && clean_state == modifier_mask.into() {
             if keybinding.keys.len() == 1 {
@@ -314,9 +390,9 @@ fn handle_next_key(
         }
 
         let next_key = &keybinding.keys[keys_pressed];
-        let required_mask = modifiers_to_mask(&next_key.modifiers);
+        let required_mask = next_key.mask();
 
-        let modifiers_match = if next_key.modifiers.is_empty() {
+        let modifiers_match = if required_mask == 0 {
             true
         } else {
             (clean_state & required_mask) == required_mask.into()
@@ -340,31 +416,38 @@ fn handle_next_key(
 
 pub fn handle_spawn_action(action: KeyAction, arg: &Arg, selected_monitor: usize) -> Result<()> {
     if let KeyAction::Spawn = action {
-        match arg {
-            Arg::Str(command) => {
-                crate::signal::spawn_detached(command);
-            }
-            Arg::Array(command) => {
-                let Some((cmd, args)) = command.split_first() else {
-                    return Ok(());
-                };
+        spawn_command(arg, &SpawnOptions::default(), selected_monitor);
+    }
 
-                let mut args_vec: Vec<String> = args.to_vec();
+    Ok(())
+}
 
-                let is_dmenu = cmd.contains("dmenu");
-                let has_monitor_flag = args.iter().any(|arg| arg == "-m");
+fn spawn_command(arg: &Arg, options: &SpawnOptions, selected_monitor: usize) {
+    match arg {
+        Arg::Str(command) => {
+            crate::signal::spawn_detached_with_options(command, options);
+        }
+        Arg::Array(command) => {
+            let Some((cmd, args)) = command.split_first() else {
+                return;
+            };
 
-                if is_dmenu && !has_monitor_flag {
-                    args_vec.insert(0, selected_monitor.to_string());
-                    args_vec.insert(0, "-m".to_string());
-                }
+            let mut args_vec: Vec<String> = args.to_vec();
 
-                let args_str: Vec<&str> = args_vec.iter().map(|s| s.as_str()).collect();
-                crate::signal::spawn_detached_with_args(cmd, &args_str);
+            let is_dmenu = cmd.contains("dmenu");
+            let has_monitor_flag = args.iter().any(|arg| arg == "-m");
+
+            if is_dmenu && !has_monitor_flag {
+                args_vec.insert(0, selected_monitor.to_string());
+                args_vec.insert(0, "-m".to_string());
             }
-            _ => {}
+
+            let args_str: Vec<&str> = args_vec.iter().map(|s| s.as_str()).collect();
+            crate::signal::spawn_detached_with_args(cmd, &args_str, options);
+        }
+        Arg::Spawn { command, options } => {
+            spawn_command(command, options, selected_monitor);
         }
+        _ => {}
     }
-
-    Ok(())
 }
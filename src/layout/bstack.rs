@@ -0,0 +1,70 @@
+use super::{GapConfig, Layout, WindowGeometry, outer_gaps, split_span};
+use x11rb::protocol::xproto::Window;
+
+pub struct BottomStackLayout;
+
+impl Layout for BottomStackLayout {
+    fn name(&self) -> &'static str {
+        super::LayoutType::BottomStack.as_str()
+    }
+
+    fn symbol(&self) -> &'static str {
+        "TTT"
+    }
+
+    fn arrange(
+        &self,
+        windows: &[Window],
+        screen_width: u32,
+        screen_height: u32,
+        gaps: &GapConfig,
+        master_factor: f32,
+        num_master: i32,
+        smartgaps_enabled: bool,
+    ) -> Vec<WindowGeometry> {
+        let window_count = windows.len();
+        if window_count == 0 {
+            return Vec::new();
+        }
+
+        let (outer_horizontal, outer_vertical) = outer_gaps(gaps, window_count, smartgaps_enabled);
+        let inner_horizontal = gaps.inner_horizontal as i32;
+        let inner_vertical = gaps.inner_vertical as i32;
+
+        let x = outer_horizontal;
+        let y = outer_vertical;
+        let width = screen_width as i32 - 2 * outer_horizontal;
+        let height = screen_height as i32 - 2 * outer_vertical;
+
+        let master_count = window_count.min(num_master.max(0) as usize);
+        let stack_count = window_count - master_count;
+
+        // Masters share a row across the top, the stack a row below them.
+        let master_height = if stack_count == 0 {
+            height
+        } else if master_count == 0 {
+            0
+        } else {
+            ((height - inner_vertical) as f32 * master_factor) as i32
+        };
+        let stack_y = if master_count == 0 {
+            y
+        } else {
+            y + master_height + inner_vertical
+        };
+        let stack_height = height - (stack_y - y);
+
+        let masters = split_span(x, width, master_count, inner_horizontal)
+            .into_iter()
+            .map(|(column_x, column_width)| {
+                WindowGeometry::new(column_x, y, column_width, master_height)
+            });
+        let stack = split_span(x, width, stack_count, inner_horizontal)
+            .into_iter()
+            .map(|(column_x, column_width)| {
+                WindowGeometry::new(column_x, stack_y, column_width, stack_height)
+            });
+
+        masters.chain(stack).collect()
+    }
+}
@@ -0,0 +1,107 @@
+use super::{GapConfig, Layout, WindowGeometry, outer_gaps, split_span};
+use x11rb::protocol::xproto::Window;
+
+pub struct CenteredMasterLayout;
+
+impl Layout for CenteredMasterLayout {
+    fn name(&self) -> &'static str {
+        super::LayoutType::CenteredMaster.as_str()
+    }
+
+    fn symbol(&self) -> &'static str {
+        "|M|"
+    }
+
+    fn arrange(
+        &self,
+        windows: &[Window],
+        screen_width: u32,
+        screen_height: u32,
+        gaps: &GapConfig,
+        master_factor: f32,
+        num_master: i32,
+        smartgaps_enabled: bool,
+    ) -> Vec<WindowGeometry> {
+        let window_count = windows.len();
+        if window_count == 0 {
+            return Vec::new();
+        }
+
+        let (outer_horizontal, outer_vertical) = outer_gaps(gaps, window_count, smartgaps_enabled);
+        let inner_horizontal = gaps.inner_horizontal as i32;
+        let inner_vertical = gaps.inner_vertical as i32;
+
+        let x = outer_horizontal;
+        let y = outer_vertical;
+        let width = screen_width as i32 - 2 * outer_horizontal;
+        let height = screen_height as i32 - 2 * outer_vertical;
+
+        let master_count = window_count.min(num_master.max(0) as usize);
+        let stack_count = window_count - master_count;
+
+        // The stack alternates between a right and a left column around the
+        // masters; with a single stack window the masters keep the left.
+        let left_count = if master_count > 0 && stack_count > 1 {
+            stack_count / 2
+        } else {
+            0
+        };
+        let right_count = stack_count - left_count;
+
+        let (left_width, master_width, right_width) = if master_count == 0 {
+            (0, 0, width)
+        } else if stack_count == 0 {
+            (0, width, 0)
+        } else if left_count == 0 {
+            let master_width = ((width - inner_horizontal) as f32 * master_factor) as i32;
+            (0, master_width, width - master_width - inner_horizontal)
+        } else {
+            let master_width = ((width - 2 * inner_horizontal) as f32 * master_factor) as i32;
+            let side_width = width - master_width - 2 * inner_horizontal;
+            let left_width = side_width / 2;
+            (left_width, master_width, side_width - left_width)
+        };
+
+        let master_x = if left_count > 0 {
+            x + left_width + inner_horizontal
+        } else {
+            x
+        };
+        let right_x = if master_count > 0 {
+            master_x + master_width + inner_horizontal
+        } else {
+            x
+        };
+
+        let mut masters = split_span(y, height, master_count, inner_vertical).into_iter();
+        let mut right = split_span(y, height, right_count, inner_vertical).into_iter();
+        let mut left = split_span(y, height, left_count, inner_vertical).into_iter();
+
+        let mut geometries = Vec::with_capacity(window_count);
+        for index in 0..window_count {
+            let placed = if index < master_count {
+                masters
+                    .next()
+                    .map(|(row_y, row_height)| (master_x, row_y, master_width, row_height))
+            } else if (index - master_count).is_multiple_of(2) || left_count == 0 {
+                right
+                    .next()
+                    .map(|(row_y, row_height)| (right_x, row_y, right_width, row_height))
+            } else {
+                left.next()
+                    .map(|(row_y, row_height)| (x, row_y, left_width, row_height))
+            };
+
+            if let Some((window_x, window_y, window_width, window_height)) = placed {
+                geometries.push(WindowGeometry::new(
+                    window_x,
+                    window_y,
+                    window_width,
+                    window_height,
+                ));
+            }
+        }
+
+        geometries
+    }
+}
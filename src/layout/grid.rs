@@ -20,7 +20,7 @@ impl Layout for GridLayout {
         gaps: &GapConfig,
         _master_factor: f32,
         _num_master: i32,
-        _smartgaps_enabled: bool,
+        smartgaps_enabled: bool,
     ) -> Vec<WindowGeometry> {
         let window_count = windows.len();
         if window_count == 0 {
@@ -28,10 +28,15 @@ impl Layout for GridLayout {
         }
 
         if window_count == 1 {
-            let x = gaps.outer_horizontal as i32;
-            let y = gaps.outer_vertical as i32;
-            let width = screen_width.saturating_sub(2 * gaps.outer_horizontal);
-            let height = screen_height.saturating_sub(2 * gaps.outer_vertical);
+            let (outer_horizontal, outer_vertical) = if smartgaps_enabled {
+                (0, 0)
+            } else {
+                (gaps.outer_horizontal, gaps.outer_vertical)
+            };
+            let x = outer_horizontal as i32;
+            let y = outer_vertical as i32;
+            let width = screen_width.saturating_sub(2 * outer_horizontal);
+            let height = screen_height.saturating_sub(2 * outer_vertical);
 
             return vec![WindowGeometry {
                 x_coordinate: x,
@@ -1,7 +1,10 @@
+pub mod bstack;
+pub mod centered_master;
 pub mod grid;
 pub mod monocle;
 pub mod normie;
 pub mod scrolling;
+pub mod spiral;
 pub mod tabbed;
 pub mod tiling;
 
@@ -25,6 +28,9 @@ pub enum LayoutType {
     Monocle,
     Tabbed,
     Scrolling,
+    Spiral,
+    BottomStack,
+    CenteredMaster,
 }
 
 impl LayoutType {
@@ -36,6 +42,9 @@ impl LayoutType {
             Self::Monocle => Box::new(monocle::MonocleLayout),
             Self::Tabbed => Box::new(tabbed::TabbedLayout),
             Self::Scrolling => Box::new(scrolling::ScrollingLayout),
+            Self::Spiral => Box::new(spiral::SpiralLayout),
+            Self::BottomStack => Box::new(bstack::BottomStackLayout),
+            Self::CenteredMaster => Box::new(centered_master::CenteredMasterLayout),
         }
     }
 
@@ -46,7 +55,10 @@ impl LayoutType {
             Self::Grid => Self::Monocle,
             Self::Monocle => Self::Tabbed,
             Self::Tabbed => Self::Scrolling,
-            Self::Scrolling => Self::Tiling,
+            Self::Scrolling => Self::Spiral,
+            Self::Spiral => Self::BottomStack,
+            Self::BottomStack => Self::CenteredMaster,
+            Self::CenteredMaster => Self::Tiling,
         }
     }
 
@@ -58,6 +70,9 @@ impl LayoutType {
             Self::Monocle => "monocle",
             Self::Tabbed => "tabbed",
             Self::Scrolling => "scrolling",
+            Self::Spiral => "spiral",
+            Self::BottomStack => "bstack",
+            Self::CenteredMaster => "centeredmaster",
         }
     }
 }
@@ -73,6 +88,9 @@ impl FromStr for LayoutType {
             "monocle" => Ok(Self::Monocle),
             "tabbed" => Ok(Self::Tabbed),
             "scrolling" => Ok(Self::Scrolling),
+            "spiral" | "fibonacci" => Ok(Self::Spiral),
+            "bstack" | "bottomstack" => Ok(Self::BottomStack),
+            "centeredmaster" | "centered" => Ok(Self::CenteredMaster),
             _ => Err(format!("Invalid Layout Type: {}", s)),
         }
     }
@@ -91,6 +109,37 @@ pub fn next_layout(current_name: &str) -> &'static str {
         .as_str()
 }
 
+/// Outer gaps as (horizontal, vertical), dropped for a lone window when
+/// smart gaps are on.
+fn outer_gaps(gaps: &GapConfig, window_count: usize, smartgaps_enabled: bool) -> (i32, i32) {
+    if smartgaps_enabled && window_count == 1 {
+        (0, 0)
+    } else {
+        (gaps.outer_horizontal as i32, gaps.outer_vertical as i32)
+    }
+}
+
+/// Splits `length` pixels starting at `start` into `count` spans separated by
+/// `gap`, as (offset, size) pairs. Leftover pixels go to the first spans.
+fn split_span(start: i32, length: i32, count: usize, gap: i32) -> Vec<(i32, i32)> {
+    if count == 0 {
+        return Vec::new();
+    }
+    let available = (length - gap * (count as i32 - 1)).max(count as i32);
+    let size = available / count as i32;
+    let remainder = available % count as i32;
+
+    let mut offset = start;
+    (0..count as i32)
+        .map(|index| {
+            let span = size + i32::from(index < remainder);
+            let placed = (offset, span);
+            offset += span + gap;
+            placed
+        })
+        .collect()
+}
+
 pub trait Layout {
     fn arrange(
         &self,
@@ -113,3 +162,14 @@ pub struct WindowGeometry {
     pub width: u32,
     pub height: u32,
 }
+
+impl WindowGeometry {
+    fn new(x: i32, y: i32, width: i32, height: i32) -> Self {
+        Self {
+            x_coordinate: x,
+            y_coordinate: y,
+            width: width.max(1) as u32,
+            height: height.max(1) as u32,
+        }
+    }
+}
@@ -20,17 +20,23 @@ impl Layout for MonocleLayout {
         gaps: &GapConfig,
         _master_factor: f32,
         _num_master: i32,
-        _smartgaps_enabled: bool,
+        smartgaps_enabled: bool,
     ) -> Vec<WindowGeometry> {
         let window_count = windows.len();
         if window_count == 0 {
             return Vec::new();
         }
 
-        let x = gaps.outer_horizontal as i32;
-        let y = gaps.outer_vertical as i32;
-        let width = screen_width.saturating_sub(2 * gaps.outer_horizontal);
-        let height = screen_height.saturating_sub(2 * gaps.outer_vertical);
+        // Only one client is ever visible.
+        let (outer_horizontal, outer_vertical) = if smartgaps_enabled {
+            (0, 0)
+        } else {
+            (gaps.outer_horizontal, gaps.outer_vertical)
+        };
+        let x = outer_horizontal as i32;
+        let y = outer_vertical as i32;
+        let width = screen_width.saturating_sub(2 * outer_horizontal);
+        let height = screen_height.saturating_sub(2 * outer_vertical);
 
         let geometry = WindowGeometry {
             x_coordinate: x,
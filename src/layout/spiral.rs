@@ -0,0 +1,84 @@
+use super::{GapConfig, Layout, WindowGeometry, outer_gaps};
+use x11rb::protocol::xproto::Window;
+
+pub struct SpiralLayout;
+
+impl Layout for SpiralLayout {
+    fn name(&self) -> &'static str {
+        super::LayoutType::Spiral.as_str()
+    }
+
+    fn symbol(&self) -> &'static str {
+        "[@]"
+    }
+
+    fn arrange(
+        &self,
+        windows: &[Window],
+        screen_width: u32,
+        screen_height: u32,
+        gaps: &GapConfig,
+        master_factor: f32,
+        _num_master: i32,
+        smartgaps_enabled: bool,
+    ) -> Vec<WindowGeometry> {
+        let window_count = windows.len();
+        if window_count == 0 {
+            return Vec::new();
+        }
+
+        let (outer_horizontal, outer_vertical) = outer_gaps(gaps, window_count, smartgaps_enabled);
+        let inner_horizontal = gaps.inner_horizontal as i32;
+        let inner_vertical = gaps.inner_vertical as i32;
+
+        let mut x = outer_horizontal;
+        let mut y = outer_vertical;
+        let mut width = screen_width as i32 - 2 * outer_horizontal;
+        let mut height = screen_height as i32 - 2 * outer_vertical;
+
+        let mut geometries = Vec::with_capacity(window_count);
+        for index in 0..window_count {
+            if index == window_count - 1 {
+                geometries.push(WindowGeometry::new(x, y, width, height));
+                break;
+            }
+
+            // Each window takes part of the space left by the previous one,
+            // turning clockwise: left, top, right, bottom.
+            let factor = if index == 0 { master_factor } else { 0.5 };
+            if index.is_multiple_of(2) {
+                let window_width = ((width - inner_horizontal) as f32 * factor) as i32;
+                let rest_width = width - window_width - inner_horizontal;
+                if index % 4 == 0 {
+                    geometries.push(WindowGeometry::new(x, y, window_width, height));
+                    x += window_width + inner_horizontal;
+                } else {
+                    geometries.push(WindowGeometry::new(
+                        x + rest_width + inner_horizontal,
+                        y,
+                        window_width,
+                        height,
+                    ));
+                }
+                width = rest_width;
+            } else {
+                let window_height = ((height - inner_vertical) as f32 * factor) as i32;
+                let rest_height = height - window_height - inner_vertical;
+                if index % 4 == 1 {
+                    geometries.push(WindowGeometry::new(x, y, width, window_height));
+                    y += window_height + inner_vertical;
+                } else {
+                    geometries.push(WindowGeometry::new(
+                        x,
+                        y + rest_height + inner_vertical,
+                        width,
+                        window_height,
+                    ));
+                }
+                height = rest_height;
+            }
+        }
+
+        geometries
+    }
+}
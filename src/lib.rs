@@ -1,22 +1,50 @@
 use std::path::PathBuf;
 
 pub mod animations;
+pub mod backend;
 pub mod bar;
+pub mod capture;
 pub mod client;
 pub mod config;
+pub mod dbus_monitor;
+pub mod dbus_service;
+pub mod dpms;
 pub mod errors;
+pub mod ipc;
+pub mod json;
 pub mod keyboard;
 pub mod layout;
 pub mod monitor;
 pub mod overlay;
+pub mod power;
+pub mod randr;
+pub mod session_lock;
+pub mod shape;
 pub mod signal;
 pub mod size_hints;
+pub mod snapshot;
+pub mod status_fifo;
+pub mod systemd;
 pub mod tab_bar;
+pub mod tag_notes;
 pub mod window_manager;
+pub mod window_memory;
+pub mod xdg_autostart;
+pub mod xkb;
+pub mod xtest;
 
 pub mod prelude {
     pub use crate::ColorScheme;
+    pub use crate::FloatPreset;
+    pub use crate::FocusOnMap;
+    pub use crate::HiddenTagAction;
+    pub use crate::KeyMode;
     pub use crate::LayoutSymbolOverride;
+    pub use crate::MonitorConfig;
+    pub use crate::MonitorProfile;
+    pub use crate::OutputRotation;
+    pub use crate::OutputSetup;
+    pub use crate::TagOverflow;
     pub use crate::WindowRule;
     pub use crate::bar::{BlockCommand, BlockConfig};
     pub use crate::keyboard::{Arg, KeyAction, handlers::KeyBinding, keysyms};
@@ -34,10 +62,127 @@ pub struct WindowRule {
     pub class: Option<String>,
     pub instance: Option<String>,
     pub title: Option<String>,
+    pub title_pattern: Option<regex::Regex>,
+    /// Re-applies the rule's tags when a title change makes it match.
+    pub retag_on_title: bool,
     pub tags: Option<u32>,
     pub focus: Option<bool>,
     pub is_floating: Option<bool>,
     pub monitor: Option<usize>,
+    pub float_preset: Option<String>,
+    pub take_focus: Option<bool>,
+    /// Marks the window as a terminal that programs it starts can swallow.
+    pub is_terminal: Option<bool>,
+    /// `Some(false)` keeps the window from swallowing its terminal.
+    pub swallow: Option<bool>,
+}
+
+/// Whether a newly mapped window takes the focus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusOnMap {
+    Always,
+    Never,
+    /// Only when the window opens on a tag shown on the focused monitor.
+    SameTag,
+}
+
+/// What happens when a window rule sends a new window to a tag that isn't
+/// shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HiddenTagAction {
+    Silent,
+    Switch,
+    Urgent,
+}
+
+/// Where a new window goes when its tag already holds `tag_client_limit`
+/// tiled clients.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagOverflow {
+    /// The next tag with room, wrapping around; floats when every tag is full.
+    NextTag,
+    Float,
+}
+
+/// An application kept on a tag, matched by its WM_CLASS class.
+#[derive(Debug, Clone)]
+pub struct PinnedApp {
+    pub command: String,
+    pub class: String,
+}
+
+/// Overrides for how a tag label is drawn, e.g. for icon font glyphs.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TagStyle {
+    /// Label color, except while the tag is urgent.
+    pub color: Option<u32>,
+    /// Width of the tag in pixels, for glyphs whose measured width is off.
+    pub width: Option<u16>,
+}
+
+/// Applications pinned to a tag, in layout order, started when missing.
+#[derive(Debug, Clone)]
+pub struct PinnedTag {
+    pub tag: usize,
+    pub apps: Vec<PinnedApp>,
+    pub layout: Option<String>,
+    /// Start missing apps the first time the tag is viewed.
+    pub spawn_on_view: bool,
+}
+
+/// How the bell is shown on screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VisualBell {
+    Off,
+    /// Briefly draws the focused window's border in the urgent color.
+    Border,
+    /// Blinks the selected tags in the bar.
+    Bar,
+}
+
+/// Settings for presentation mode, which quiets the session for screen
+/// sharing.
+#[derive(Debug, Clone)]
+pub struct PresentationMode {
+    /// Text shown in the bar while presenting.
+    pub indicator: String,
+    /// Run when presentation mode starts, e.g. to pause notifications.
+    pub on_enter: Option<String>,
+    pub on_exit: Option<String>,
+}
+
+impl Default for PresentationMode {
+    fn default() -> Self {
+        Self {
+            indicator: "PRES".to_string(),
+            on_enter: None,
+            on_exit: None,
+        }
+    }
+}
+
+/// Exposes the IPC protocol on a TCP address; clients must send
+/// `auth <token>` first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteControl {
+    pub address: String,
+    pub token: String,
+}
+
+/// Commands run when the power supply changes.
+#[derive(Debug, Clone, Default)]
+pub struct PowerHooks {
+    pub on_ac: Option<String>,
+    pub on_battery: Option<String>,
+    /// `(percent, command)` pairs, run once when the battery drops to
+    /// `percent` while discharging.
+    pub low_battery: Vec<(u32, String)>,
+}
+
+impl PowerHooks {
+    pub fn is_empty(&self) -> bool {
+        self.on_ac.is_none() && self.on_battery.is_none() && self.low_battery.is_empty()
+    }
 }
 
 impl WindowRule {
@@ -54,7 +199,138 @@ impl WindowRule {
             .title
             .as_ref()
             .is_none_or(|t| title.contains(t.as_str()));
-        class_matches && instance_matches && title_matches
+        let pattern_matches = self
+            .title_pattern
+            .as_ref()
+            .is_none_or(|p| p.is_match(title));
+        class_matches && instance_matches && title_matches && pattern_matches
+    }
+}
+
+/// A named keymap that replaces the normal bindings while active, until
+/// Escape is pressed or an action leaves it.
+#[derive(Debug, Clone)]
+pub struct KeyMode {
+    pub name: String,
+    pub keybindings: Vec<crate::keyboard::handlers::Key>,
+}
+
+/// Settings for the monitor driven by a RandR output, matched by name so
+/// they follow the output when monitors are reordered.
+#[derive(Debug, Clone)]
+pub struct MonitorConfig {
+    pub output: String,
+    /// Tag index viewed at startup.
+    pub tag: Option<usize>,
+    pub layout: Option<String>,
+    pub bar_position: Option<crate::bar::BarPosition>,
+    pub blocks: Option<Vec<crate::bar::BlockConfig>>,
+    /// Whether the monitor has a second bar; unset follows
+    /// `Config::second_bar_blocks`.
+    pub second_bar: Option<bool>,
+    pub second_bar_blocks: Option<Vec<crate::bar::BlockConfig>>,
+    /// Tags that always live on this monitor.
+    pub assigned_tags: u32,
+}
+
+/// Tags viewed together: viewing one of them on its monitor switches the
+/// other monitors to their tag in the group.
+#[derive(Debug, Clone)]
+pub struct TagLink {
+    /// Output name and tag index.
+    pub views: Vec<(String, usize)>,
+}
+
+/// RandR settings for one output.
+#[derive(Debug, Clone)]
+pub struct OutputSetup {
+    pub output: String,
+    pub enabled: bool,
+    /// Resolution in pixels; the current or preferred mode when unset.
+    pub mode: Option<(u16, u16)>,
+    pub position: Option<(i32, i32)>,
+    pub rotation: OutputRotation,
+    /// Framebuffer pixels per output pixel, like `xrandr --scale`.
+    pub scale: f32,
+    pub primary: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputRotation {
+    Normal,
+    Left,
+    Right,
+    Inverted,
+}
+
+/// Output layout activated when exactly `outputs` are connected (and the lid
+/// is in the given state, if set).
+#[derive(Debug, Clone)]
+pub struct MonitorProfile {
+    pub name: String,
+    pub lid_closed: Option<bool>,
+    pub outputs: Vec<OutputSetup>,
+    /// Clients on these tags are moved to the named output's monitor.
+    pub tags: Vec<(String, u32)>,
+}
+
+impl MonitorProfile {
+    pub fn matches(&self, connected: &[String], lid_closed: Option<bool>) -> bool {
+        let lid_matches = self.lid_closed.is_none() || self.lid_closed == lid_closed;
+        lid_matches
+            && self.outputs.len() == connected.len()
+            && self
+                .outputs
+                .iter()
+                .all(|setup| connected.contains(&setup.output))
+    }
+}
+
+/// A window summoned and dismissed with a key, shown centered and floating
+/// on whatever tags are being viewed. The command is run the first time
+/// and its window is recognized by class, instance or title.
+#[derive(Debug, Clone)]
+pub struct Scratchpad {
+    pub name: String,
+    pub command: String,
+    pub class: Option<String>,
+    pub instance: Option<String>,
+    pub title: Option<String>,
+    /// Size as fractions of the monitor's window area.
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Scratchpad {
+    pub fn matches(&self, class: &str, instance: &str, title: &str) -> bool {
+        self.class.as_ref().is_none_or(|c| class == c)
+            && self.instance.as_ref().is_none_or(|i| instance == i)
+            && self
+                .title
+                .as_ref()
+                .is_none_or(|t| title.contains(t.as_str()))
+    }
+}
+
+/// Size of a centered floating window, as fractions of the monitor's
+/// window area.
+#[derive(Debug, Clone)]
+pub struct FloatPreset {
+    pub name: String,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl FloatPreset {
+    pub fn defaults() -> Vec<Self> {
+        [("small", 0.4), ("medium", 0.6), ("large", 0.8)]
+            .into_iter()
+            .map(|(name, size)| Self {
+                name: name.to_string(),
+                width: size,
+                height: size,
+            })
+            .collect()
     }
 }
 
@@ -67,6 +343,9 @@ pub struct Config {
     pub border_width: u32,
     pub border_focused: u32,
     pub border_unfocused: u32,
+    pub window_corner_radius: u16,
+    /// Fade windows in on map and tag switches and out on close; 0 disables.
+    pub fade_duration_ms: u64,
     pub font: String,
 
     // Gaps
@@ -83,19 +362,70 @@ pub struct Config {
 
     // Tags
     pub tags: Vec<String>,
+    pub pinned_tags: Vec<PinnedTag>,
 
     // Layout symbol overrides
     pub layout_symbols: Vec<LayoutSymbolOverride>,
 
     // Keybindings
     pub keybindings: Vec<crate::keyboard::handlers::Key>,
+    pub modes: Vec<KeyMode>,
     pub tag_back_and_forth: bool,
+    pub warp_pointer_on_tag_switch: bool,
 
     // Window rules
     pub window_rules: Vec<WindowRule>,
+    pub float_presets: Vec<FloatPreset>,
+    pub remember_floating_geometry: bool,
+    pub focus_on_map: FocusOnMap,
+    pub hidden_tag_action: HiddenTagAction,
+    /// Tiled clients allowed per tag before new windows overflow; 0 is unlimited.
+    pub tag_client_limit: usize,
+    pub tag_overflow: TagOverflow,
+    pub float_fixed_size: bool,
+    pub float_utility: bool,
+    /// New windows smaller than this many pixels float; 0 disables the check.
+    pub float_below_area: u32,
+    pub visual_bell: VisualBell,
+    /// Marks an unfocused window urgent when it rings the bell.
+    pub bell_urgent: bool,
+    pub presentation: PresentationMode,
+
+    // Monitors
+    pub monitor_configs: Vec<MonitorConfig>,
+    pub monitor_profiles: Vec<MonitorProfile>,
+    pub display_setup: Vec<OutputSetup>,
+    /// Viewing a tag assigned to another monitor focuses that monitor;
+    /// otherwise the tag is shown there and focus stays put.
+    pub assigned_tag_focus: bool,
+    pub tag_links: Vec<TagLink>,
 
     // Status bar
     pub status_blocks: Vec<crate::bar::BlockConfig>,
+    pub recording_indicator: String,
+    pub recording_processes: Vec<String>,
+    /// Also show the recording indicator while a client grabs the keyboard
+    /// or pointer. Off by default, see `capture::is_input_grabbed`.
+    pub recording_grabs: bool,
+    pub status_file: Option<String>,
+    pub remote_control: Option<RemoteControl>,
+    pub bar_click_through: bool,
+    pub bar_corner_radius: u16,
+    pub bar_border_width: u16,
+    pub bar_border_color: u32,
+    pub bar_shadow_size: u16,
+    pub bar_monitor_label: crate::bar::MonitorLabel,
+    pub bar_tag_flash: bool,
+    pub bar_clicks: Vec<crate::bar::BarClick>,
+    /// Clicks this close to the edge of a bar region are ignored.
+    pub bar_dead_zone: u16,
+    /// Longest gap between the clicks of a double click.
+    pub double_click_ms: u64,
+    /// Scrolling over the tags skips tags without windows.
+    pub bar_scroll_skip_empty: bool,
+    /// Marks tabs of unfocused windows whose title changed or that rang
+    /// the bell.
+    pub activity_color: Option<u32>,
 
     // Bar color schemes
     pub scheme_normal: ColorScheme,
@@ -104,8 +434,37 @@ pub struct Config {
     pub scheme_urgent: ColorScheme,
 
     pub autostart: Vec<String>,
+    pub xdg_autostart: bool,
     pub auto_tile: bool,
     pub hide_vacant_tags: bool,
+
+    // Session integration
+    pub systemd_notify: bool,
+    pub systemd_autostart_units: bool,
+    pub lock_command: Option<String>,
+    pub lock_blank: bool,
+    pub power_hooks: PowerHooks,
+    pub scratchpads: Vec<Scratchpad>,
+    pub auto_reload: bool,
+    pub snap_distance: u32,
+    pub swallow: bool,
+    pub bar_title_class: crate::bar::TitleClass,
+    pub bar_title_class_color: Option<u32>,
+    /// Shows the root window's name instead of the status blocks.
+    pub bar_root_status: bool,
+    /// Shown beside the layout symbol: `{}` is the number of windows on the
+    /// viewed tags and `{total}` the number on the monitor.
+    pub bar_window_count: Option<String>,
+    /// Indexed like the tags; tags past the end use the defaults.
+    pub tag_styles: Vec<TagStyle>,
+    /// Blocks of a second bar on the edge opposite the main bar, shown on
+    /// every monitor unless its `MonitorConfig` turns it off.
+    pub second_bar_blocks: Option<Vec<crate::bar::BlockConfig>>,
+    /// Hides the bars this long after the pointer leaves them; 0 keeps
+    /// them shown.
+    pub bar_autohide_ms: u64,
+    /// Blinks urgent tags instead of showing them in steady urgent colors.
+    pub bar_urgent_blink: Option<crate::bar::UrgentBlink>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -113,6 +472,17 @@ pub struct ColorScheme {
     pub foreground: u32,
     pub background: u32,
     pub underline: u32,
+    /// Bottom color of a vertical background gradient starting at `background`.
+    pub gradient: Option<u32>,
+}
+
+impl Config {
+    pub fn monitor_config(&self, output: &str) -> Option<&MonitorConfig> {
+        if output.is_empty() {
+            return None;
+        }
+        self.monitor_configs.iter().find(|c| c.output == output)
+    }
 }
 
 impl Default for Config {
@@ -144,6 +514,7 @@ impl Default for Config {
                 .into_iter()
                 .map(String::from)
                 .collect(),
+            pinned_tags: Vec::new(),
             layout_symbols: vec![],
             keybindings: vec![
                 KeyBinding::single_key(
@@ -325,38 +696,114 @@ impl Default for Config {
                     Arg::Int(8),
                 ),
             ],
+            modes: Vec::new(),
             tag_back_and_forth: false,
             window_rules: vec![],
             status_blocks: vec![crate::bar::BlockConfig {
                 format: "{}".to_string(),
-                command: crate::bar::BlockCommand::DateTime("%a, %b %d - %-I:%M %P".to_string()),
+                command: crate::bar::BlockCommand::DateTime {
+                    date_format: "%a, %b %d - %-I:%M %P".to_string(),
+                    zones: Vec::new(),
+                    locale: None,
+                },
                 interval_secs: 1,
                 color: 0x0db9d7,
                 underline: true,
+                background: None,
+                gradient: None,
+                popup: None,
+                sensitive: false,
+                focused_only: false,
+                min_width: None,
+                align: Default::default(),
+                clicks: Default::default(),
+                signal: None,
             }],
             scheme_normal: ColorScheme {
                 foreground: 0xbbbbbb,
                 background: 0x1a1b26,
                 underline: 0x444444,
+                gradient: None,
             },
             scheme_occupied: ColorScheme {
                 foreground: 0x0db9d7,
                 background: 0x1a1b26,
                 underline: 0x0db9d7,
+                gradient: None,
             },
             scheme_selected: ColorScheme {
                 foreground: 0x0db9d7,
                 background: 0x1a1b26,
                 underline: 0xad8ee6,
+                gradient: None,
             },
             scheme_urgent: ColorScheme {
                 foreground: 0xff5555,
                 background: 0x1a1b26,
                 underline: 0xff5555,
+                gradient: None,
             },
             autostart: vec![],
             auto_tile: false,
             hide_vacant_tags: false,
+            systemd_notify: false,
+            systemd_autostart_units: false,
+            lock_command: None,
+            lock_blank: false,
+            power_hooks: PowerHooks::default(),
+            xdg_autostart: false,
+            recording_indicator: "REC".to_string(),
+            recording_processes: crate::capture::DEFAULT_PROCESSES
+                .iter()
+                .map(|p| p.to_string())
+                .collect(),
+            recording_grabs: false,
+            warp_pointer_on_tag_switch: false,
+            float_presets: FloatPreset::defaults(),
+            remember_floating_geometry: false,
+            focus_on_map: FocusOnMap::Always,
+            hidden_tag_action: HiddenTagAction::Silent,
+            tag_client_limit: 0,
+            tag_overflow: TagOverflow::NextTag,
+            float_fixed_size: true,
+            float_utility: true,
+            float_below_area: 0,
+            visual_bell: VisualBell::Off,
+            bell_urgent: false,
+            presentation: PresentationMode::default(),
+            status_file: None,
+            remote_control: None,
+            bar_click_through: false,
+            bar_corner_radius: 0,
+            window_corner_radius: 0,
+            fade_duration_ms: 0,
+            bar_border_width: 0,
+            bar_border_color: 0x444444,
+            bar_shadow_size: 0,
+            bar_monitor_label: crate::bar::MonitorLabel::None,
+            bar_tag_flash: false,
+            bar_clicks: Vec::new(),
+            bar_dead_zone: 0,
+            double_click_ms: 300,
+            bar_scroll_skip_empty: false,
+            activity_color: None,
+            monitor_configs: vec![],
+            assigned_tag_focus: true,
+            tag_links: Vec::new(),
+            monitor_profiles: vec![],
+            display_setup: vec![],
+            scratchpads: Vec::new(),
+            auto_reload: true,
+            snap_distance: 32,
+            swallow: true,
+            bar_title_class: crate::bar::TitleClass::None,
+            bar_title_class_color: None,
+            bar_root_status: false,
+            bar_window_count: None,
+            tag_styles: Vec::new(),
+            second_bar_blocks: None,
+            bar_autohide_ms: 0,
+            bar_urgent_blink: None,
         }
     }
 }
@@ -1,11 +1,110 @@
 use crate::client::TagMask;
 use crate::errors::WmError;
+use crate::layout::GapConfig;
+use crate::{Config, MonitorConfig};
 use x11rb::protocol::xinerama::ConnectionExt as _;
 use x11rb::protocol::xproto::{Screen, Window};
 use x11rb::rust_connection::RustConnection;
 
 type WmResult<T> = Result<T, WmError>;
 
+/// Gaps around and between tiled windows.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Gaps {
+    pub enabled: bool,
+    /// Drops the outer gaps while a single window is tiled.
+    pub smart: bool,
+    pub inner_horizontal: u32,
+    pub inner_vertical: u32,
+    pub outer_horizontal: u32,
+    pub outer_vertical: u32,
+}
+
+impl Gaps {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            enabled: config.gaps_enabled,
+            smart: config.smartgaps_enabled,
+            inner_horizontal: config.gap_inner_horizontal,
+            inner_vertical: config.gap_inner_vertical,
+            outer_horizontal: config.gap_outer_horizontal,
+            outer_vertical: config.gap_outer_vertical,
+        }
+    }
+
+    /// The gaps layouts leave, none while gaps are off.
+    pub fn active(&self) -> GapConfig {
+        if self.enabled {
+            GapConfig {
+                inner_horizontal: self.inner_horizontal,
+                inner_vertical: self.inner_vertical,
+                outer_horizontal: self.outer_horizontal,
+                outer_vertical: self.outer_vertical,
+            }
+        } else {
+            GapConfig {
+                inner_horizontal: 0,
+                inner_vertical: 0,
+                outer_horizontal: 0,
+                outer_vertical: 0,
+            }
+        }
+    }
+}
+
+/// Space a dock reserves along the edges of the root window, from its
+/// `_NET_WM_STRUT_PARTIAL`. Each edge's range runs along that edge.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Strut {
+    pub left: u32,
+    pub right: u32,
+    pub top: u32,
+    pub bottom: u32,
+    pub left_start_y: u32,
+    pub left_end_y: u32,
+    pub right_start_y: u32,
+    pub right_end_y: u32,
+    pub top_start_x: u32,
+    pub top_end_x: u32,
+    pub bottom_start_x: u32,
+    pub bottom_end_x: u32,
+}
+
+impl Strut {
+    /// Reads the twelve values of `_NET_WM_STRUT_PARTIAL`, or the four of
+    /// `_NET_WM_STRUT`, which reserve whole edges.
+    pub fn from_values(values: &[u32]) -> Option<Self> {
+        match *values {
+            [left, right, top, bottom, ls, le, rs, re, ts, te, bs, be, ..] => Some(Self {
+                left,
+                right,
+                top,
+                bottom,
+                left_start_y: ls,
+                left_end_y: le,
+                right_start_y: rs,
+                right_end_y: re,
+                top_start_x: ts,
+                top_end_x: te,
+                bottom_start_x: bs,
+                bottom_end_x: be,
+            }),
+            [left, right, top, bottom, ..] => Some(Self {
+                left,
+                right,
+                top,
+                bottom,
+                left_end_y: u32::MAX,
+                right_end_y: u32::MAX,
+                top_end_x: u32::MAX,
+                bottom_end_x: u32::MAX,
+                ..Default::default()
+            }),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Pertag {
     pub current_tag: usize,
@@ -14,6 +113,7 @@ pub struct Pertag {
     pub master_factors: Vec<f32>,
     pub layouts: Vec<String>,
     pub show_bars: Vec<bool>,
+    pub gaps: Vec<Gaps>,
 }
 
 impl Pertag {
@@ -23,6 +123,7 @@ impl Pertag {
         default_master_factor: f32,
         default_show_bar: bool,
         default_layout: &str,
+        default_gaps: Gaps,
     ) -> Self {
         let len = num_tags + 1;
         Self {
@@ -32,6 +133,60 @@ impl Pertag {
             master_factors: vec![default_master_factor; len],
             layouts: vec![default_layout.to_string(); len],
             show_bars: vec![default_show_bar; len],
+            gaps: vec![default_gaps; len],
+        }
+    }
+
+    /// Adds state for a tag appended at runtime, starting from the all-tags
+    /// slot's settings.
+    pub fn push_tag(&mut self) {
+        self.num_masters.push(self.num_masters[0]);
+        self.master_factors.push(self.master_factors[0]);
+        self.layouts.push(self.layouts[0].clone());
+        self.show_bars.push(self.show_bars[0]);
+        self.gaps.push(self.gaps[0]);
+    }
+
+    /// Adds state for a tag inserted at `tag_index`, shifting the tags from
+    /// there up.
+    pub fn insert_tag(&mut self, tag_index: usize) {
+        let slot = tag_index + 1;
+        if slot > self.layouts.len() {
+            return;
+        }
+        self.num_masters.insert(slot, self.num_masters[0]);
+        self.master_factors.insert(slot, self.master_factors[0]);
+        self.layouts.insert(slot, self.layouts[0].clone());
+        self.show_bars.insert(slot, self.show_bars[0]);
+        self.gaps.insert(slot, self.gaps[0]);
+
+        if self.current_tag >= slot {
+            self.current_tag += 1;
+        }
+        if self.previous_tag >= slot {
+            self.previous_tag += 1;
+        }
+    }
+
+    /// Drops the state of a removed tag, shifting the tags after it down.
+    pub fn remove_tag(&mut self, tag_index: usize) {
+        let slot = tag_index + 1;
+        if slot >= self.layouts.len() {
+            return;
+        }
+        self.num_masters.remove(slot);
+        self.master_factors.remove(slot);
+        self.layouts.remove(slot);
+        self.show_bars.remove(slot);
+        self.gaps.remove(slot);
+
+        if self.current_tag > slot {
+            self.current_tag -= 1;
+        }
+        if self.previous_tag == slot {
+            self.previous_tag = self.current_tag;
+        } else if self.previous_tag > slot {
+            self.previous_tag -= 1;
         }
     }
 }
@@ -46,6 +201,8 @@ pub struct ScreenInfo {
 
 #[derive(Debug, Clone)]
 pub struct Monitor {
+    /// RandR output name (e.g. `eDP-1`), empty when unknown.
+    pub name: String,
     pub layout_symbol: String,
     pub master_factor: f32,
     pub num_master: i32,
@@ -56,10 +213,8 @@ pub struct Monitor {
     pub window_area_y: i32,
     pub window_area_width: i32,
     pub window_area_height: i32,
-    pub gap_inner_horizontal: i32,
-    pub gap_inner_vertical: i32,
-    pub gap_outer_horizontal: i32,
-    pub gap_outer_vertical: i32,
+    /// Gaps of the viewed tag, kept for each tag in `pertag`.
+    pub gaps: Gaps,
     pub selected_tags_index: usize,
     pub selected_layout_index: usize,
     pub tagset: [u32; 2],
@@ -72,11 +227,16 @@ pub struct Monitor {
     pub layout_indices: [usize; 2],
     pub scroll_offset: i32,
     pub pertag: Option<Pertag>,
+    /// Clients hidden to show the desktop, with the tags they go back to.
+    pub desktop_hidden: Vec<(Window, u32)>,
+    /// Windows stashed out of the layout, most recent last.
+    pub stash: Vec<Window>,
 }
 
 impl Monitor {
     pub fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
         Self {
+            name: String::new(),
             layout_symbol: String::from("[]"),
             master_factor: 0.55,
             num_master: 1,
@@ -92,10 +252,7 @@ impl Monitor {
             window_area_y: y,
             window_area_width: width as i32,
             window_area_height: height as i32,
-            gap_inner_horizontal: 3,
-            gap_inner_vertical: 3,
-            gap_outer_horizontal: 3,
-            gap_outer_vertical: 3,
+            gaps: Gaps::default(),
             selected_tags_index: 0,
             selected_layout_index: 0,
             tagset: [1, 1],
@@ -108,19 +265,91 @@ impl Monitor {
             layout_indices: [0, 1],
             scroll_offset: 0,
             pertag: None,
+            desktop_hidden: Vec::new(),
+            stash: Vec::new(),
         }
     }
 
-    pub fn init_pertag(&mut self, num_tags: usize, default_layout: &str) {
+    pub fn init_pertag(&mut self, num_tags: usize, default_layout: &str, gaps: Gaps) {
+        self.gaps = gaps;
         self.pertag = Some(Pertag::new(
             num_tags,
             self.num_master,
             self.master_factor,
             self.show_bar,
             default_layout,
+            gaps,
         ));
     }
 
+    /// Gives the monitor and every one of its tags the same gaps.
+    pub fn reset_gaps(&mut self, gaps: Gaps) {
+        self.gaps = gaps;
+        if let Some(ref mut pertag) = self.pertag {
+            pertag.gaps.fill(gaps);
+        }
+    }
+
+    /// Applies the startup parts of a monitor config: the viewed tag and the
+    /// default layout of every tag.
+    pub fn apply_startup_config(&mut self, config: &MonitorConfig, num_tags: usize) {
+        if let Some(tag) = config.tag
+            && tag < num_tags
+        {
+            self.tagset = [1 << tag, 1 << tag];
+            if let Some(ref mut pertag) = self.pertag {
+                pertag.current_tag = tag + 1;
+                pertag.previous_tag = tag + 1;
+            }
+        }
+
+        if let Some(ref layout) = config.layout
+            && let Some(ref mut pertag) = self.pertag
+        {
+            pertag.layouts.fill(layout.clone());
+        }
+    }
+
+    /// Sets the window area to the screen less what `struts` reserve on it.
+    /// `root_width` and `root_height` are the size of the root window the
+    /// struts are measured from.
+    pub fn apply_struts(&mut self, struts: &[Strut], root_width: i32, root_height: i32) {
+        let screen = &self.screen_info;
+        let (x0, y0) = (screen.x, screen.y);
+        let (x1, y1) = (screen.x + screen.width, screen.y + screen.height);
+        let spans = |start: u32, end: u32, low: i32, high: i32| {
+            (start as i64) < high as i64 && end as i64 >= low as i64
+        };
+
+        let (mut left, mut right, mut top, mut bottom) = (0, 0, 0, 0);
+        for strut in struts {
+            if strut.left > 0 && spans(strut.left_start_y, strut.left_end_y, y0, y1) {
+                left = left.max(strut.left as i32 - x0);
+            }
+            if strut.right > 0 && spans(strut.right_start_y, strut.right_end_y, y0, y1) {
+                right = right.max(x1 - (root_width - strut.right as i32));
+            }
+            if strut.top > 0 && spans(strut.top_start_x, strut.top_end_x, x0, x1) {
+                top = top.max(strut.top as i32 - y0);
+            }
+            if strut.bottom > 0 && spans(strut.bottom_start_x, strut.bottom_end_x, x0, x1) {
+                bottom = bottom.max(y1 - (root_height - strut.bottom as i32));
+            }
+        }
+
+        // A strut past the middle of the screen is more likely a bug than
+        // a dock.
+        let left = left.clamp(0, screen.width / 2);
+        let right = right.clamp(0, screen.width / 2);
+        let top = top.clamp(0, screen.height / 2);
+        let bottom = bottom.clamp(0, screen.height / 2);
+
+        self.window_area_x = x0 + left;
+        self.window_area_y = y0 + top;
+        self.window_area_width = screen.width - left - right;
+        self.window_area_height = screen.height - top - bottom;
+    }
+
     pub fn contains_point(&self, x: i32, y: i32) -> bool {
         x >= self.screen_info.x
             && x < self.screen_info.x + self.screen_info.width
@@ -136,7 +365,7 @@ impl Monitor {
 pub fn detect_monitors(
     connection: &RustConnection,
     screen: &Screen,
-    _root: Window,
+    root: Window,
 ) -> WmResult<Vec<Monitor>> {
     let fallback_monitors = || {
         vec![Monitor::new(
@@ -201,5 +430,9 @@ pub fn detect_monitors(
         other => other,
     });
 
+    if let Err(e) = crate::randr::assign_output_names(connection, root, &mut monitors) {
+        eprintln!("Failed to query RandR output names: {:?}", e);
+    }
+
     Ok(monitors)
 }
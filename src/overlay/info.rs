@@ -0,0 +1,126 @@
+use super::{Overlay, OverlayBase};
+use crate::bar::font::Font;
+use crate::errors::X11Error;
+use crate::monitor::ScreenInfo;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::*;
+use x11rb::rust_connection::RustConnection;
+
+const PADDING: i16 = 20;
+const LINE_SPACING: i16 = 5;
+const TITLE_BOTTOM_MARGIN: i16 = 10;
+const BORDER_WIDTH: u16 = 2;
+const BORDER_COLOR: u32 = 0x7aa2f7;
+const TITLE_COLOR: u32 = 0x7aa2f7;
+
+/// Centered popup listing lines of text under a title, dismissed by a click.
+pub struct InfoOverlay {
+    base: OverlayBase,
+    title: String,
+    lines: Vec<String>,
+}
+
+impl InfoOverlay {
+    pub fn new(
+        connection: &RustConnection,
+        screen: &Screen,
+        screen_num: usize,
+        display: *mut x11::xlib::Display,
+    ) -> Result<Self, X11Error> {
+        let base = OverlayBase::new(
+            connection,
+            screen,
+            screen_num,
+            display,
+            400,
+            200,
+            BORDER_WIDTH,
+            BORDER_COLOR,
+            0x1a1a1a,
+            0xffffff,
+        )?;
+
+        Ok(InfoOverlay {
+            base,
+            title: String::new(),
+            lines: Vec::new(),
+        })
+    }
+
+    pub fn show(
+        &mut self,
+        connection: &RustConnection,
+        font: &Font,
+        title: &str,
+        lines: Vec<String>,
+        screen: &ScreenInfo,
+    ) -> Result<(), X11Error> {
+        let screen_width = screen.width as u16;
+        let screen_height = screen.height as u16;
+        self.title = title.to_string();
+        self.lines = lines;
+
+        let content_width = self
+            .lines
+            .iter()
+            .map(|line| font.text_width(line))
+            .chain(std::iter::once(font.text_width(title)))
+            .max()
+            .unwrap_or(0)
+            .min(screen_width.saturating_sub(PADDING as u16 * 4));
+
+        let width = content_width + (PADDING as u16 * 2);
+        let line_height = font.height() + LINE_SPACING as u16;
+        let title_height = font.height() + TITLE_BOTTOM_MARGIN as u16;
+        let height = title_height + (self.lines.len() as u16 * line_height) + (PADDING as u16 * 2);
+
+        let x = screen.x as i16 + (screen_width.saturating_sub(width) / 2) as i16;
+        let y = screen.y as i16 + (screen_height.saturating_sub(height) / 2) as i16;
+
+        self.base.configure(connection, x, y, width, height)?;
+        self.base.is_visible = true;
+        self.draw(connection, font)?;
+        self.base.show(connection)?;
+        Ok(())
+    }
+}
+
+impl Overlay for InfoOverlay {
+    fn window(&self) -> Window {
+        self.base.window
+    }
+
+    fn is_visible(&self) -> bool {
+        self.base.is_visible
+    }
+
+    fn hide(&mut self, connection: &RustConnection) -> Result<(), X11Error> {
+        self.base.hide(connection)?;
+        self.lines.clear();
+        Ok(())
+    }
+
+    fn draw(&self, connection: &RustConnection, font: &Font) -> Result<(), X11Error> {
+        if !self.base.is_visible {
+            return Ok(());
+        }
+        self.base.draw_background(connection)?;
+
+        let mut y = PADDING + font.ascent();
+        self.base
+            .font_draw
+            .draw_text(font, TITLE_COLOR, PADDING, y, &self.title);
+        y += font.height() as i16 + TITLE_BOTTOM_MARGIN;
+
+        let line_height = font.height() + LINE_SPACING as u16;
+        for line in &self.lines {
+            self.base
+                .font_draw
+                .draw_text(font, self.base.foreground_color, PADDING, y, line);
+            y += line_height as i16;
+        }
+        connection.flush()?;
+        self.base.font_draw.sync();
+        Ok(())
+    }
+}
@@ -56,6 +56,10 @@ impl KeybindOverlay {
         })
     }
 
+    pub fn set_modkey(&mut self, modkey: KeyButMask) {
+        self.modkey = modkey;
+    }
+
     pub fn show(
         &mut self,
         connection: &RustConnection,
@@ -183,6 +187,10 @@ impl KeybindOverlay {
     fn format_key_combo(&self, key: &KeyPress) -> String {
         let mut parts = Vec::new();
 
+        if key.modkey.is_some() {
+            parts.push("Mod".to_string());
+        }
+
         for modifier in &key.modifiers {
             let mod_str = match *modifier {
                 m if m == self.modkey => "Mod",
@@ -208,12 +216,19 @@ impl KeybindOverlay {
             KeyAction::Quit => "Quit Window Manager".to_string(),
             KeyAction::Restart => "Restart Window Manager".to_string(),
             KeyAction::KillClient => "Close Focused Window".to_string(),
-            KeyAction::Spawn => match &binding.arg {
-                Arg::Str(cmd) => format!("Launch: {}", cmd),
-                Arg::Array(arr) if !arr.is_empty() => format!("Launch: {}", arr[0]),
-                _ => "Launch Program".to_string(),
-            },
+            KeyAction::Spawn => {
+                let command = match &binding.arg {
+                    Arg::Spawn { command, .. } => command.as_ref(),
+                    arg => arg,
+                };
+                match command {
+                    Arg::Str(cmd) => format!("Launch: {}", cmd),
+                    Arg::Array(arr) if !arr.is_empty() => format!("Launch: {}", arr[0]),
+                    _ => "Launch Program".to_string(),
+                }
+            }
             KeyAction::SpawnTerminal => "Launch Terminal".to_string(),
+            KeyAction::SpawnTerminalInCwd => "Launch Terminal in Current Directory".to_string(),
             KeyAction::FocusStack => "Focus Next/Previous Window".to_string(),
             KeyAction::MoveStack => "Move Window Up/Down Stack".to_string(),
             KeyAction::ViewTag => match &binding.arg {
@@ -241,6 +256,33 @@ impl KeybindOverlay {
             KeyAction::IncNumMaster => "Adjust Number of Master Windows".to_string(),
             KeyAction::ScrollLeft => "Scroll Layout Left".to_string(),
             KeyAction::ScrollRight => "Scroll Layout Right".to_string(),
+            KeyAction::TogglePassthrough => "Toggle Keybinding Passthrough".to_string(),
+            KeyAction::CycleFloatPreset => "Cycle Floating Size Preset".to_string(),
+            KeyAction::ToggleMaximize => "Toggle Maximized".to_string(),
+            KeyAction::SwapModkey => "Swap Modkey".to_string(),
+            KeyAction::SendKey => "Send Key".to_string(),
+            KeyAction::Chain => "Run Action Chain".to_string(),
+            KeyAction::Conditional => "Conditional Action".to_string(),
+            KeyAction::EnterMode => "Enter Mode".to_string(),
+            KeyAction::ExitMode => "Exit Mode".to_string(),
+            KeyAction::LockSession => "Lock Session".to_string(),
+            KeyAction::ToggleShowDesktop => "Toggle Show Desktop".to_string(),
+            KeyAction::StashWindow => "Stash Window".to_string(),
+            KeyAction::PopStash => "Pop Stashed Window".to_string(),
+            KeyAction::SpawnPinned => "Spawn Pinned Apps".to_string(),
+            KeyAction::SaveSnapshot => "Save Tag Snapshot".to_string(),
+            KeyAction::RestoreSnapshot => "Restore Tag Snapshot".to_string(),
+            KeyAction::TogglePresentation => "Toggle Presentation Mode".to_string(),
+            KeyAction::PickColor => "Pick Screen Color".to_string(),
+            KeyAction::InspectWindow => "Inspect Window".to_string(),
+            KeyAction::ToggleBarDebug => "Toggle Bar Hit Regions".to_string(),
+            KeyAction::MoveToNewTag => "Move Window To New Tag".to_string(),
+            KeyAction::ToggleTagLinks => "Toggle Linked Tags".to_string(),
+            KeyAction::ToggleScratchpad => "Toggle Scratchpad".to_string(),
+            KeyAction::IncGaps => "Grow/Shrink All Gaps".to_string(),
+            KeyAction::IncInnerGaps => "Grow/Shrink Inner Gaps".to_string(),
+            KeyAction::IncOuterGaps => "Grow/Shrink Outer Gaps".to_string(),
+            KeyAction::ToggleSmartGaps => "Toggle Smart Gaps".to_string(),
             KeyAction::None => "No Action".to_string(),
         }
     }
@@ -7,9 +7,11 @@ use x11rb::protocol::xproto::*;
 use x11rb::rust_connection::RustConnection;
 
 pub mod error;
+pub mod info;
 pub mod keybind;
 
 pub use error::ErrorOverlay;
+pub use info::InfoOverlay;
 pub use keybind::KeybindOverlay;
 
 pub trait Overlay {
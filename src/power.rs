@@ -0,0 +1,85 @@
+use crate::dbus_monitor::SignalWatcher;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Wakes up when UPower reports a property change on any power device,
+/// e.g. the charger being plugged in or the battery level dropping.
+pub struct PowerListener {
+    watcher: SignalWatcher,
+}
+
+impl PowerListener {
+    pub fn spawn() -> io::Result<Self> {
+        let rule = "type='signal',sender='org.freedesktop.UPower',interface='org.freedesktop.DBus.Properties',member='PropertiesChanged'";
+        Ok(Self {
+            watcher: SignalWatcher::spawn(rule, "PropertiesChanged")?,
+        })
+    }
+
+    /// Returns true if the power state may have changed since the last call.
+    pub fn changed(&self) -> bool {
+        self.watcher.fired()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PowerState {
+    /// Whether a mains adapter is online; None when the machine has none.
+    pub on_ac: Option<bool>,
+    pub capacity: Option<u32>,
+}
+
+impl PowerState {
+    /// Reads the adapter and system battery from sysfs, which UPower itself
+    /// reports from.
+    pub fn read() -> Self {
+        let mut state = Self::default();
+        let Ok(entries) = fs::read_dir("/sys/class/power_supply") else {
+            return state;
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            match read_trimmed(&path.join("type")).as_deref() {
+                Some("Mains") => {
+                    let online = read_trimmed(&path.join("online")).is_some_and(|s| s == "1");
+                    state.on_ac = Some(state.on_ac.unwrap_or(false) || online);
+                }
+                Some("Battery") if state.capacity.is_none() => {
+                    let is_device_scope = read_trimmed(&path.join("scope"))
+                        .is_some_and(|s| s.eq_ignore_ascii_case("device"));
+                    if !is_device_scope {
+                        state.capacity =
+                            read_trimmed(&path.join("capacity")).and_then(|s| s.parse().ok());
+                    }
+                }
+                _ => {}
+            }
+        }
+        state
+    }
+
+    pub fn discharging(&self) -> bool {
+        self.on_ac == Some(false)
+    }
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+}
+
+/// Runs a power hook with the new state in `OXWM_POWER_SOURCE` ("ac" or
+/// "battery") and `OXWM_BATTERY` (the charge in percent).
+pub fn run_hook(command: &str, state: &PowerState) {
+    let mut env = Vec::new();
+    if let Some(on_ac) = state.on_ac {
+        let source = if on_ac { "ac" } else { "battery" };
+        env.push(("OXWM_POWER_SOURCE".to_string(), source.to_string()));
+    }
+    if let Some(capacity) = state.capacity {
+        env.push(("OXWM_BATTERY".to_string(), capacity.to_string()));
+    }
+    let options = crate::signal::SpawnOptions { cwd: None, env };
+    crate::signal::spawn_detached_with_options(command, &options);
+}
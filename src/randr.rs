@@ -0,0 +1,427 @@
+use crate::errors::X11Error;
+use crate::monitor::Monitor;
+use crate::{OutputRotation, OutputSetup};
+use std::collections::{HashMap, HashSet};
+use x11rb::connection::{Connection, RequestConnection};
+use x11rb::protocol::randr::{self, ConnectionExt as _};
+use x11rb::protocol::render;
+use x11rb::protocol::xproto::{ConnectionExt as _, Window};
+use x11rb::rust_connection::RustConnection;
+
+/// An output that is connected and driven by a CRTC.
+#[derive(Debug, Clone)]
+pub struct Output {
+    pub name: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+fn is_available(connection: &RustConnection) -> Result<bool, X11Error> {
+    if connection
+        .extension_information(randr::X11_EXTENSION_NAME)?
+        .is_none()
+    {
+        return Ok(false);
+    }
+
+    let version = connection.randr_query_version(1, 5)?.reply()?;
+    Ok(version.major_version > 1 || version.minor_version >= 3)
+}
+
+/// Lists the active outputs, or nothing when RandR 1.3 is not available.
+pub fn active_outputs(connection: &RustConnection, root: Window) -> Result<Vec<Output>, X11Error> {
+    if !is_available(connection)? {
+        return Ok(Vec::new());
+    }
+
+    let resources = connection
+        .randr_get_screen_resources_current(root)?
+        .reply()?;
+
+    let mut outputs = Vec::new();
+    for &output in &resources.outputs {
+        let info = connection
+            .randr_get_output_info(output, resources.config_timestamp)?
+            .reply()?;
+        if info.connection != randr::Connection::CONNECTED || info.crtc == x11rb::NONE {
+            continue;
+        }
+
+        let crtc = connection
+            .randr_get_crtc_info(info.crtc, resources.config_timestamp)?
+            .reply()?;
+        if crtc.width == 0 || crtc.height == 0 {
+            continue;
+        }
+
+        outputs.push(Output {
+            name: String::from_utf8_lossy(&info.name).into_owned(),
+            x: crtc.x as i32,
+            y: crtc.y as i32,
+            width: crtc.width as i32,
+            height: crtc.height as i32,
+        });
+    }
+
+    Ok(outputs)
+}
+
+/// Names each monitor after the output whose CRTC starts at the monitor's
+/// origin. Monitors without a match keep their name.
+pub fn assign_output_names(
+    connection: &RustConnection,
+    root: Window,
+    monitors: &mut [Monitor],
+) -> Result<(), X11Error> {
+    let outputs = active_outputs(connection, root)?;
+
+    for monitor in monitors {
+        if let Some(output) = outputs
+            .iter()
+            .find(|o| o.x == monitor.screen_info.x && o.y == monitor.screen_info.y)
+        {
+            monitor.name = output.name.clone();
+        }
+    }
+
+    Ok(())
+}
+
+/// Subscribes `root` to output and screen change events, used to follow
+/// hotplugging.
+pub fn select_change_events(connection: &RustConnection, root: Window) -> Result<(), X11Error> {
+    if !is_available(connection)? {
+        return Ok(());
+    }
+
+    connection.randr_select_input(
+        root,
+        randr::NotifyMask::SCREEN_CHANGE | randr::NotifyMask::OUTPUT_CHANGE,
+    )?;
+    Ok(())
+}
+
+/// Names of all connected outputs, whether or not they are enabled.
+pub fn connected_output_names(
+    connection: &RustConnection,
+    root: Window,
+) -> Result<Vec<String>, X11Error> {
+    if !is_available(connection)? {
+        return Ok(Vec::new());
+    }
+
+    let resources = connection
+        .randr_get_screen_resources_current(root)?
+        .reply()?;
+
+    let mut names = Vec::new();
+    for &output in &resources.outputs {
+        let info = connection
+            .randr_get_output_info(output, resources.config_timestamp)?
+            .reply()?;
+        if info.connection == randr::Connection::CONNECTED {
+            names.push(String::from_utf8_lossy(&info.name).into_owned());
+        }
+    }
+
+    Ok(names)
+}
+
+struct CrtcTarget {
+    output: randr::Output,
+    crtc: randr::Crtc,
+    mode: randr::ModeInfo,
+    x: i32,
+    y: i32,
+    rotation: randr::Rotation,
+    scale: f32,
+    primary: bool,
+}
+
+impl CrtcTarget {
+    /// Size covered in the framebuffer after rotation and scaling.
+    fn size(&self) -> (u16, u16) {
+        let (width, height) = match self.rotation {
+            randr::Rotation::ROTATE90 | randr::Rotation::ROTATE270 => {
+                (self.mode.height, self.mode.width)
+            }
+            _ => (self.mode.width, self.mode.height),
+        };
+        (
+            (width as f32 * self.scale).round() as u16,
+            (height as f32 * self.scale).round() as u16,
+        )
+    }
+}
+
+/// Configures outputs like `xrandr --output ... --mode ... --pos ... --rotate ... --scale ...`.
+///
+/// Outputs not mentioned in `setups` are left alone. Enabled outputs without
+/// a position are placed to the right of the others.
+pub fn apply_output_setups(
+    connection: &RustConnection,
+    root: Window,
+    setups: &[OutputSetup],
+) -> Result<(), X11Error> {
+    if setups.is_empty() || !is_available(connection)? {
+        return Ok(());
+    }
+
+    let resources = connection
+        .randr_get_screen_resources_current(root)?
+        .reply()?;
+
+    let mut outputs = HashMap::new();
+    for &output in &resources.outputs {
+        let info = connection
+            .randr_get_output_info(output, resources.config_timestamp)?
+            .reply()?;
+        outputs.insert(
+            String::from_utf8_lossy(&info.name).into_owned(),
+            (output, info),
+        );
+    }
+
+    let mut crtcs = HashMap::new();
+    for &crtc in &resources.crtcs {
+        let info = connection
+            .randr_get_crtc_info(crtc, resources.config_timestamp)?
+            .reply()?;
+        crtcs.insert(crtc, info);
+    }
+
+    let mut disabled = Vec::new();
+    let mut used_crtcs = HashSet::new();
+    let mut targets = Vec::new();
+
+    for setup in setups {
+        let Some((output, info)) = outputs.get(&setup.output) else {
+            eprintln!("RandR: unknown output '{}'", setup.output);
+            continue;
+        };
+
+        if !setup.enabled {
+            if info.crtc != x11rb::NONE {
+                disabled.push(info.crtc);
+            }
+            continue;
+        }
+
+        if info.connection != randr::Connection::CONNECTED {
+            continue;
+        }
+
+        let current_mode = crtcs.get(&info.crtc).map(|c| c.mode);
+        let Some(mode) = find_mode(&resources.modes, info, setup.mode, current_mode) else {
+            eprintln!("RandR: no matching mode for output '{}'", setup.output);
+            continue;
+        };
+
+        let crtc = if info.crtc != x11rb::NONE && !used_crtcs.contains(&info.crtc) {
+            Some(info.crtc)
+        } else {
+            info.crtcs.iter().copied().find(|crtc| {
+                !used_crtcs.contains(crtc) && crtcs.get(crtc).is_some_and(|c| c.outputs.is_empty())
+            })
+        };
+        let Some(crtc) = crtc else {
+            eprintln!("RandR: no free CRTC for output '{}'", setup.output);
+            continue;
+        };
+        used_crtcs.insert(crtc);
+
+        let (x, y) = setup.position.unwrap_or((-1, -1));
+        targets.push(CrtcTarget {
+            output: *output,
+            crtc,
+            mode,
+            x,
+            y,
+            rotation: match setup.rotation {
+                OutputRotation::Normal => randr::Rotation::ROTATE0,
+                OutputRotation::Left => randr::Rotation::ROTATE90,
+                OutputRotation::Inverted => randr::Rotation::ROTATE180,
+                OutputRotation::Right => randr::Rotation::ROTATE270,
+            },
+            scale: if setup.scale > 0.0 { setup.scale } else { 1.0 },
+            primary: setup.primary,
+        });
+    }
+
+    let mut next_x = targets
+        .iter()
+        .filter(|t| t.x >= 0)
+        .map(|t| t.x + t.size().0 as i32)
+        .max()
+        .unwrap_or(0);
+    for target in targets.iter_mut().filter(|t| t.x < 0) {
+        target.x = next_x;
+        target.y = 0;
+        next_x += target.size().0 as i32;
+    }
+
+    // Untouched enabled CRTCs keep their place and must stay inside the screen.
+    let kept = crtcs.iter().filter(|(crtc, info)| {
+        info.mode != x11rb::NONE && !used_crtcs.contains(*crtc) && !disabled.contains(*crtc)
+    });
+    let extent = |x: i32, width: u16| x + width as i32;
+    let screen_width = targets
+        .iter()
+        .map(|t| extent(t.x, t.size().0))
+        .chain(kept.clone().map(|(_, c)| extent(c.x as i32, c.width)))
+        .max()
+        .unwrap_or(0);
+    let screen_height = targets
+        .iter()
+        .map(|t| extent(t.y, t.size().1))
+        .chain(kept.map(|(_, c)| extent(c.y as i32, c.height)))
+        .max()
+        .unwrap_or(0);
+    if screen_width <= 0 || screen_height <= 0 {
+        return Ok(());
+    }
+
+    connection.grab_server()?;
+    let result = (|| -> Result<(), X11Error> {
+        for (&crtc, info) in &crtcs {
+            let changes = disabled.contains(&crtc) || used_crtcs.contains(&crtc);
+            let overflows = extent(info.x as i32, info.width) > screen_width
+                || extent(info.y as i32, info.height) > screen_height;
+            if info.mode != x11rb::NONE && (changes || overflows) {
+                connection
+                    .randr_set_crtc_config(
+                        crtc,
+                        x11rb::CURRENT_TIME,
+                        resources.config_timestamp,
+                        0,
+                        0,
+                        x11rb::NONE,
+                        randr::Rotation::ROTATE0,
+                        &[],
+                    )?
+                    .reply()?;
+            }
+        }
+
+        let range = connection.randr_get_screen_size_range(root)?.reply()?;
+        let width = (screen_width as u16).clamp(range.min_width, range.max_width);
+        let height = (screen_height as u16).clamp(range.min_height, range.max_height);
+        connection.randr_set_screen_size(
+            root,
+            width,
+            height,
+            pixels_to_mm(width),
+            pixels_to_mm(height),
+        )?;
+
+        for target in &targets {
+            let filter: &[u8] = if target.scale == 1.0 {
+                b"nearest"
+            } else {
+                b"bilinear"
+            };
+            connection.randr_set_crtc_transform(
+                target.crtc,
+                scale_transform(target.scale),
+                filter,
+                &[],
+            )?;
+
+            let reply = connection
+                .randr_set_crtc_config(
+                    target.crtc,
+                    x11rb::CURRENT_TIME,
+                    resources.config_timestamp,
+                    target.x as i16,
+                    target.y as i16,
+                    target.mode.id,
+                    target.rotation,
+                    &[target.output],
+                )?
+                .reply()?;
+            if reply.status != randr::SetConfig::SUCCESS {
+                eprintln!(
+                    "RandR: failed to configure CRTC {}: {:?}",
+                    target.crtc, reply.status
+                );
+            }
+            if target.primary {
+                connection.randr_set_output_primary(root, target.output)?;
+            }
+        }
+        Ok(())
+    })();
+    connection.ungrab_server()?;
+    connection.flush()?;
+
+    result
+}
+
+/// Picks the mode for an output: the requested size at its highest refresh
+/// rate, otherwise the mode in use, otherwise the preferred mode.
+fn find_mode(
+    modes: &[randr::ModeInfo],
+    info: &randr::GetOutputInfoReply,
+    size: Option<(u16, u16)>,
+    current: Option<randr::Mode>,
+) -> Option<randr::ModeInfo> {
+    let supported = || {
+        info.modes
+            .iter()
+            .filter_map(|id| modes.iter().find(|m| m.id == *id))
+    };
+
+    if let Some((width, height)) = size {
+        return supported()
+            .filter(|m| m.width == width && m.height == height)
+            .max_by(|a, b| refresh_rate(a).total_cmp(&refresh_rate(b)))
+            .copied();
+    }
+
+    current
+        .filter(|&mode| mode != x11rb::NONE)
+        .and_then(|mode| supported().find(|m| m.id == mode))
+        .or_else(|| supported().next())
+        .copied()
+}
+
+fn refresh_rate(mode: &randr::ModeInfo) -> f64 {
+    let total = mode.htotal as f64 * mode.vtotal as f64;
+    if total == 0.0 {
+        0.0
+    } else {
+        mode.dot_clock as f64 / total
+    }
+}
+
+fn scale_transform(scale: f32) -> render::Transform {
+    let fixed = |value: f32| (value * 65536.0).round() as render::Fixed;
+    render::Transform {
+        matrix11: fixed(scale),
+        matrix12: 0,
+        matrix13: 0,
+        matrix21: 0,
+        matrix22: fixed(scale),
+        matrix23: 0,
+        matrix31: 0,
+        matrix32: 0,
+        matrix33: fixed(1.0),
+    }
+}
+
+/// Physical size reported for the screen, assuming 96 DPI.
+fn pixels_to_mm(pixels: u16) -> u32 {
+    (pixels as f64 * 25.4 / 96.0).round() as u32
+}
+
+/// Returns whether the laptop lid is closed, or `None` without an ACPI lid.
+pub fn lid_closed() -> Option<bool> {
+    let lid = std::fs::read_dir("/proc/acpi/button/lid")
+        .ok()?
+        .flatten()
+        .next()?;
+    let state = std::fs::read_to_string(lid.path().join("state")).ok()?;
+    Some(state.contains("closed"))
+}
@@ -0,0 +1,77 @@
+use crate::dbus_monitor::SignalWatcher;
+use std::io;
+use std::process::{Child, Command, Stdio};
+
+/// Watches logind for `Lock` requests on the current session, as sent by
+/// `loginctl lock-session` or idle managers.
+pub struct LockListener {
+    watcher: SignalWatcher,
+}
+
+impl LockListener {
+    pub fn spawn() -> io::Result<Self> {
+        let mut rule = String::from(
+            "type='signal',sender='org.freedesktop.login1',interface='org.freedesktop.login1.Session',member='Lock'",
+        );
+        if let Ok(session_id) = std::env::var("XDG_SESSION_ID") {
+            rule.push_str(&format!(
+                ",path='/org/freedesktop/login1/session/{}'",
+                bus_label_escape(&session_id)
+            ));
+        }
+
+        Ok(Self {
+            watcher: SignalWatcher::spawn(&rule, "Lock")?,
+        })
+    }
+
+    /// Returns true if a lock was requested since the last call.
+    pub fn lock_requested(&self) -> bool {
+        self.watcher.fired()
+    }
+}
+
+/// Runs the configured screen locker, unless the previous one is still up.
+#[derive(Default)]
+pub struct Locker {
+    running: Option<Child>,
+}
+
+impl Locker {
+    pub fn is_running(&mut self) -> bool {
+        match &mut self.running {
+            Some(child) => matches!(child.try_wait(), Ok(None)),
+            None => false,
+        }
+    }
+
+    pub fn lock(&mut self, command: &str) -> io::Result<bool> {
+        if self.is_running() {
+            return Ok(false);
+        }
+
+        let child = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        self.running = Some(child);
+        Ok(true)
+    }
+}
+
+/// Escapes a session id the way logind does for object paths: anything but
+/// ASCII letters and digits, and a leading digit, becomes `_xx` in hex.
+fn bus_label_escape(label: &str) -> String {
+    let mut escaped = String::new();
+    for (i, byte) in label.bytes().enumerate() {
+        if byte.is_ascii_alphabetic() || (byte.is_ascii_digit() && i > 0) {
+            escaped.push(byte as char);
+        } else {
+            escaped.push_str(&format!("_{:02x}", byte));
+        }
+    }
+    escaped
+}
@@ -0,0 +1,137 @@
+use crate::errors::X11Error;
+use x11rb::connection::RequestConnection;
+use x11rb::protocol::shape::{self, ConnectionExt as _};
+use x11rb::protocol::xproto::{ClipOrdering, Rectangle, Window};
+use x11rb::rust_connection::RustConnection;
+
+fn is_available(connection: &RustConnection) -> Result<bool, X11Error> {
+    Ok(connection
+        .extension_information(shape::X11_EXTENSION_NAME)?
+        .is_some())
+}
+
+/// Empties the input region of `window` so pointer events fall through to
+/// whatever is below it, or restores the default region.
+pub fn set_input_passthrough(
+    connection: &RustConnection,
+    window: Window,
+    enabled: bool,
+) -> Result<(), X11Error> {
+    if !is_available(connection)? {
+        eprintln!("SHAPE extension not available, click-through disabled");
+        return Ok(());
+    }
+
+    if enabled {
+        connection.shape_rectangles(
+            shape::SO::SET,
+            shape::SK::INPUT,
+            ClipOrdering::UNSORTED,
+            window,
+            0,
+            0,
+            &[],
+        )?;
+    } else {
+        connection.shape_mask(shape::SO::SET, shape::SK::INPUT, window, 0, 0, x11rb::NONE)?;
+    }
+    Ok(())
+}
+
+/// Clips the bounding region of `window` (border included) to a rectangle
+/// with rounded corners. A radius of 0 restores the default rectangular shape.
+///
+/// Only the shape is changed, so compositors keep drawing the window as usual;
+/// leave the radius at 0 when the compositor already rounds corners.
+pub fn set_rounded_corners(
+    connection: &RustConnection,
+    window: Window,
+    width: u16,
+    height: u16,
+    border_width: u16,
+    radius: u16,
+) -> Result<(), X11Error> {
+    if !is_available(connection)? {
+        return Ok(());
+    }
+
+    let offset = -(border_width as i16);
+    if radius == 0 {
+        connection.shape_mask(
+            shape::SO::SET,
+            shape::SK::BOUNDING,
+            window,
+            offset,
+            offset,
+            x11rb::NONE,
+        )?;
+        return Ok(());
+    }
+
+    let outer_width = width.saturating_add(2 * border_width);
+    let outer_height = height.saturating_add(2 * border_width);
+    let rectangles = rounded_rectangles(outer_width, outer_height, radius);
+
+    connection.shape_rectangles(
+        shape::SO::SET,
+        shape::SK::BOUNDING,
+        ClipOrdering::YX_BANDED,
+        window,
+        offset,
+        offset,
+        &rectangles,
+    )?;
+    Ok(())
+}
+
+/// Covers a `width` x `height` area with corners cut along a circle of
+/// `radius`: one row per pixel in the corner bands plus a single middle band.
+fn rounded_rectangles(width: u16, height: u16, radius: u16) -> Vec<Rectangle> {
+    let radius = radius.min(width / 2).min(height / 2);
+    if radius == 0 {
+        return vec![Rectangle {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        }];
+    }
+
+    let r = radius as f32;
+    let inset_at = |row: u16| {
+        let dy = r - row as f32 - 0.5;
+        (r - (r * r - dy * dy).max(0.0).sqrt()).round() as u16
+    };
+
+    let mut rectangles = Vec::with_capacity(2 * radius as usize + 1);
+    for row in 0..radius {
+        let inset = inset_at(row);
+        rectangles.push(Rectangle {
+            x: inset as i16,
+            y: row as i16,
+            width: width - 2 * inset,
+            height: 1,
+        });
+    }
+
+    if height > 2 * radius {
+        rectangles.push(Rectangle {
+            x: 0,
+            y: radius as i16,
+            width,
+            height: height - 2 * radius,
+        });
+    }
+
+    for row in (0..radius).rev() {
+        let inset = inset_at(row);
+        rectangles.push(Rectangle {
+            x: inset as i16,
+            y: (height - 1 - row) as i16,
+            width: width - 2 * inset,
+            height: 1,
+        });
+    }
+
+    rectangles
+}
@@ -1,26 +1,156 @@
+use std::ffi::c_int;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicI32, AtomicU32, Ordering};
+
+/// Highest `n` a block can be refreshed with as `SIGRTMIN+n`.
+pub const MAX_BLOCK_SIGNAL: u8 = 30;
+
+/// `SIG_ERR`, returned by signal() when the handler can't be installed.
+const SIG_ERR: usize = usize::MAX;
+
+unsafe extern "C" {
+    fn signal(signum: c_int, handler: extern "C" fn(c_int)) -> usize;
+    fn __libc_current_sigrtmin() -> c_int;
+}
+
+/// Bit `n` is set once `SIGRTMIN+n` arrived, until taken.
+static BLOCK_SIGNALS: AtomicU32 = AtomicU32::new(0);
+static SIGRTMIN: AtomicI32 = AtomicI32::new(0);
+
+#[derive(Debug, Clone, Default)]
+pub struct SpawnOptions {
+    pub cwd: Option<PathBuf>,
+    pub env: Vec<(String, String)>,
+}
 
 pub fn spawn_detached(cmd: &str) {
-    if let Ok(mut child) = Command::new("sh")
+    spawn_detached_with_options(cmd, &SpawnOptions::default())
+}
+
+pub fn spawn_detached_with_options(cmd: &str, options: &SpawnOptions) {
+    let mut command = Command::new("sh");
+    command
         .arg("-c")
         .arg(format!("({}) &", cmd))
         .stdin(Stdio::null())
         .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .spawn()
-    {
+        .stderr(Stdio::null());
+
+    if let Some(cwd) = &options.cwd {
+        if cwd.is_dir() {
+            command.current_dir(cwd);
+        } else {
+            eprintln!("[spawn] working directory {} does not exist", cwd.display());
+        }
+    }
+
+    command.envs(options.env.iter().map(|(key, value)| (key, value)));
+
+    if let Ok(mut child) = command.spawn() {
         let _ = child.wait();
     }
 }
 
-pub fn spawn_detached_with_args(program: &str, args: &[&str]) {
+pub fn spawn_detached_with_args(program: &str, args: &[&str], options: &SpawnOptions) {
     let escaped_args: Vec<String> = args.iter().map(|a| shell_escape(a)).collect();
     let full_cmd = if escaped_args.is_empty() {
         program.to_string()
     } else {
         format!("{} {}", program, escaped_args.join(" "))
     };
-    spawn_detached(&full_cmd)
+    spawn_detached_with_options(&full_cmd, options)
+}
+
+/// Runs `command` in `terminal` and returns the terminal's pid, so its
+/// window can be recognized through `_NET_WM_PID` once it maps.
+pub fn spawn_in_terminal(terminal: &str, command: &str) -> std::io::Result<u32> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(format!("exec {} -e {}", terminal, command))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    let pid = child.id();
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(pid)
+}
+
+/// Expands a leading `~` to the user's home directory.
+pub fn expand_home(path: &str) -> PathBuf {
+    if path == "~" {
+        return dirs::home_dir().unwrap_or_else(|| PathBuf::from(path));
+    }
+    if let Some(rest) = path.strip_prefix("~/")
+        && let Some(home) = dirs::home_dir()
+    {
+        return home.join(rest);
+    }
+    PathBuf::from(path)
+}
+
+/// Returns the working directory of the innermost descendant of `pid`.
+///
+/// Terminals run a shell as a child, and the shell is the process whose
+/// directory changes, so we follow the newest child down the tree.
+pub fn process_cwd(pid: u32) -> Option<PathBuf> {
+    let mut current = pid;
+    while let Some(child) = newest_child(current) {
+        current = child;
+    }
+    std::fs::read_link(Path::new("/proc").join(current.to_string()).join("cwd")).ok()
+}
+
+/// Returns the parent of `pid`, from the fourth field of `/proc/<pid>/stat`.
+pub fn parent_pid(pid: u32) -> Option<u32> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // The command name before it is in parentheses and may hold spaces.
+    let (_, fields) = stat.rsplit_once(')')?;
+    fields.split_whitespace().nth(1)?.parse().ok()
+}
+
+extern "C" fn on_block_signal(signum: c_int) {
+    let offset = signum - SIGRTMIN.load(Ordering::Relaxed);
+    if (1..=MAX_BLOCK_SIGNAL as c_int).contains(&offset) {
+        BLOCK_SIGNALS.fetch_or(1 << offset, Ordering::Relaxed);
+    }
+}
+
+/// Catches `SIGRTMIN+n` for each `n`, as dwmblocks does, so scripts can
+/// refresh a block with `pkill -RTMIN+n oxwm`. Handlers stay installed once
+/// set, since an uncaught real-time signal would end the process.
+pub fn listen_block_signals(signals: impl IntoIterator<Item = u8>) {
+    let sigrtmin = unsafe { __libc_current_sigrtmin() };
+    SIGRTMIN.store(sigrtmin, Ordering::Relaxed);
+    for n in signals {
+        if !(1..=MAX_BLOCK_SIGNAL).contains(&n) {
+            continue;
+        }
+        if unsafe { signal(sigrtmin + n as c_int, on_block_signal) } == SIG_ERR {
+            eprintln!(
+                "Failed to catch SIGRTMIN+{}: {}",
+                n,
+                std::io::Error::last_os_error()
+            );
+        }
+    }
+}
+
+/// Bits of the block signals received since the last call.
+pub fn take_block_signals() -> u32 {
+    BLOCK_SIGNALS.swap(0, Ordering::Relaxed)
+}
+
+fn newest_child(pid: u32) -> Option<u32> {
+    let path = format!("/proc/{}/task/{}/children", pid, pid);
+    let children = std::fs::read_to_string(path).ok()?;
+    children
+        .split_whitespace()
+        .filter_map(|c| c.parse().ok())
+        .next_back()
 }
 
 fn shell_escape(s: &str) -> String {
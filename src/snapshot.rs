@@ -0,0 +1,139 @@
+use std::io;
+use std::path::PathBuf;
+
+const SNAPSHOT_DIR: &str = "snapshots";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotClient {
+    pub class: String,
+    pub floating: bool,
+    pub x: i16,
+    pub y: i16,
+    pub width: u16,
+    pub height: u16,
+}
+
+/// A tag's arrangement: its layout parameters and the clients in stack
+/// order, persisted as a `layout<TAB>name<TAB>mfact<TAB>nmaster` line
+/// followed by one `class<TAB>tiled|floating<TAB>x<TAB>y<TAB>width<TAB>height`
+/// line per client. Tabs, newlines and backslashes in a class are escaped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub layout: String,
+    pub master_factor: f32,
+    pub num_master: i32,
+    pub clients: Vec<SnapshotClient>,
+}
+
+impl Snapshot {
+    pub fn load(name: &str) -> io::Result<Self> {
+        let contents = std::fs::read_to_string(snapshot_path(name)?)?;
+        let mut lines = contents.lines();
+
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "malformed snapshot");
+        let mut header = lines.next().ok_or_else(invalid)?.split('\t');
+        if header.next() != Some("layout") {
+            return Err(invalid());
+        }
+        let layout = header.next().ok_or_else(invalid)?.to_string();
+        let master_factor = header
+            .next()
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(invalid)?;
+        let num_master = header
+            .next()
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(invalid)?;
+
+        Ok(Self {
+            layout,
+            master_factor,
+            num_master,
+            clients: lines.filter_map(parse_client).collect(),
+        })
+    }
+
+    pub fn save(&self, name: &str) -> io::Result<()> {
+        let path = snapshot_path(name)?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut contents = format!(
+            "layout\t{}\t{}\t{}\n",
+            self.layout, self.master_factor, self.num_master
+        );
+        for c in &self.clients {
+            contents.push_str(&format!(
+                "{}\t{}\t{}\t{}\t{}\t{}\n",
+                escape(&c.class),
+                if c.floating { "floating" } else { "tiled" },
+                c.x,
+                c.y,
+                c.width,
+                c.height
+            ));
+        }
+
+        std::fs::write(path, contents)
+    }
+}
+
+fn snapshot_path(name: &str) -> io::Result<PathBuf> {
+    if name.is_empty() || name.contains('/') || name.starts_with('.') {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid snapshot name '{}'", name),
+        ));
+    }
+
+    dirs::state_dir()
+        .or_else(dirs::cache_dir)
+        .map(|dir| dir.join("oxwm").join(SNAPSHOT_DIR).join(name))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no state directory"))
+}
+
+/// Reads a client line; None for lines with missing fields or a geometry
+/// out of range.
+fn parse_client(line: &str) -> Option<SnapshotClient> {
+    let mut fields = line.split('\t');
+    Some(SnapshotClient {
+        class: unescape(fields.next()?),
+        floating: fields.next()? == "floating",
+        x: fields.next()?.parse().ok()?,
+        y: fields.next()?.parse().ok()?,
+        width: fields.next()?.parse().ok()?,
+        height: fields.next()?.parse().ok()?,
+    })
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => unescaped.push('\t'),
+            Some('n') => unescaped.push('\n'),
+            Some(other) => unescaped.push(other),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
@@ -0,0 +1,141 @@
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::os::unix::fs::{FileTypeExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+
+// `O_NONBLOCK` differs between architectures: 0o4000 is right for Linux
+// on x86, ARM, RISC-V, PowerPC, s390x and LoongArch.
+#[cfg(all(
+    target_os = "linux",
+    not(any(
+        target_arch = "mips",
+        target_arch = "mips64",
+        target_arch = "mips32r6",
+        target_arch = "mips64r6",
+        target_arch = "sparc",
+        target_arch = "sparc64",
+    ))
+))]
+const O_NONBLOCK: i32 = 0o4000;
+#[cfg(all(
+    target_os = "linux",
+    any(
+        target_arch = "mips",
+        target_arch = "mips64",
+        target_arch = "mips32r6",
+        target_arch = "mips64r6",
+    )
+))]
+const O_NONBLOCK: i32 = 0x80;
+#[cfg(all(
+    target_os = "linux",
+    any(target_arch = "sparc", target_arch = "sparc64")
+))]
+const O_NONBLOCK: i32 = 0x4000;
+// The BSDs and macOS.
+#[cfg(not(target_os = "linux"))]
+const O_NONBLOCK: i32 = 0x4;
+
+/// Opening a FIFO for writing without a reader fails with `ENXIO`, which is
+/// 6 on every Linux architecture, the BSDs and macOS.
+const ENXIO: i32 = 6;
+
+/// State of one monitor as written to the status file.
+pub struct MonitorStatus<'a> {
+    pub index: usize,
+    pub focused: bool,
+    pub tags: &'a [String],
+    pub selected_tags: u32,
+    pub occupied_tags: u32,
+    pub urgent_tags: u32,
+    pub layout_symbol: &'a str,
+    pub title: &'a str,
+}
+
+impl MonitorStatus<'_> {
+    /// Formats the monitor as a single tab-separated line:
+    /// `<monitor>\t<tags>\t<layout>\t<title>`.
+    ///
+    /// The monitor is prefixed with `*` when it has focus. Each tag is
+    /// prefixed with `#` (viewed), `!` (urgent), `:` (occupied) or `.` (empty),
+    /// and tags are separated by spaces.
+    pub fn format(&self) -> String {
+        let tags: Vec<String> = self
+            .tags
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let mask = 1 << i;
+                let prefix = if self.selected_tags & mask != 0 {
+                    '#'
+                } else if self.urgent_tags & mask != 0 {
+                    '!'
+                } else if self.occupied_tags & mask != 0 {
+                    ':'
+                } else {
+                    '.'
+                };
+                format!("{}{}", prefix, name)
+            })
+            .collect();
+
+        format!(
+            "{}{}\t{}\t{}\t{}",
+            if self.focused { "*" } else { "" },
+            self.index,
+            tags.join(" "),
+            self.layout_symbol,
+            self.title.replace(['\t', '\n'], " ")
+        )
+    }
+}
+
+/// Writes WM state to a FIFO or regular file whenever it changes.
+///
+/// FIFOs receive the new lines appended and are skipped while nobody is
+/// reading; regular files are rewritten to hold only the current state.
+pub struct StatusWriter {
+    path: PathBuf,
+    last: String,
+}
+
+impl StatusWriter {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            last: String::new(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn write(&mut self, contents: String) -> io::Result<()> {
+        if contents == self.last {
+            return Ok(());
+        }
+
+        let is_fifo = std::fs::metadata(&self.path).is_ok_and(|m| m.file_type().is_fifo());
+        if is_fifo {
+            let file = OpenOptions::new()
+                .write(true)
+                .custom_flags(O_NONBLOCK)
+                .open(&self.path);
+            match file {
+                Ok(mut file) => file.write_all(contents.as_bytes())?,
+                // No reader on the other end yet.
+                Err(e) if e.raw_os_error() == Some(ENXIO) => return Ok(()),
+                Err(e) => return Err(e),
+            }
+        } else {
+            if let Some(parent) = self.path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&self.path, &contents)?;
+        }
+
+        self.last = contents;
+        Ok(())
+    }
+}
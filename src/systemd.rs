@@ -0,0 +1,74 @@
+use std::io;
+use std::os::linux::net::SocketAddrExt;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::net::{SocketAddr, UnixDatagram};
+
+/// Environment variables pushed into the systemd user manager so that user
+/// units can talk to the X server oxwm is running on. oxwm sets
+/// `XDG_CURRENT_DESKTOP` on startup when the session didn't.
+const SESSION_ENVIRONMENT: &[&str] = &["DISPLAY", "XAUTHORITY", "XDG_CURRENT_DESKTOP"];
+
+/// Sends `READY=1` to the socket in `$NOTIFY_SOCKET`.
+///
+/// Returns `Ok(false)` when oxwm was not started by systemd.
+pub fn notify_ready() -> io::Result<bool> {
+    let Some(path) = std::env::var_os("NOTIFY_SOCKET") else {
+        return Ok(false);
+    };
+
+    let socket = UnixDatagram::unbound()?;
+    let bytes = path.as_bytes();
+    let addr = match bytes.strip_prefix(b"@") {
+        Some(name) => SocketAddr::from_abstract_name(name)?,
+        None => SocketAddr::from_pathname(&path)?,
+    };
+    socket.send_to_addr(b"READY=1", &addr)?;
+    Ok(true)
+}
+
+pub fn import_environment() {
+    crate::signal::spawn_detached_with_args(
+        "systemctl",
+        &[&["--user", "import-environment"], SESSION_ENVIRONMENT].concat(),
+        &Default::default(),
+    );
+}
+
+/// Runs `command` as a transient systemd user service so its output ends up
+/// in the journal and it can be managed with `systemctl --user`.
+pub fn spawn_transient_unit(command: &str, index: usize) {
+    let unit = format!("--unit=oxwm-autostart-{}-{}", unit_name(command), index);
+    crate::signal::spawn_detached_with_args(
+        "systemd-run",
+        &[
+            "--user",
+            "--collect",
+            "--quiet",
+            "--slice=app.slice",
+            &unit,
+            "sh",
+            "-c",
+            command,
+        ],
+        &Default::default(),
+    );
+}
+
+fn unit_name(command: &str) -> String {
+    let program = command
+        .split_whitespace()
+        .next()
+        .and_then(|p| p.rsplit('/').next())
+        .unwrap_or("command");
+
+    program
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
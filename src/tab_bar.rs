@@ -1,5 +1,6 @@
 use crate::ColorScheme;
-use crate::bar::font::{DrawingSurface, Font};
+use crate::bar::font::Font;
+use crate::bar::renderer::{Renderer, XftRenderer};
 use crate::errors::X11Error;
 use crate::layout::tabbed::TAB_BAR_HEIGHT;
 use x11::xlib::_XDisplay;
@@ -8,17 +9,6 @@ use x11rb::connection::Connection;
 use x11rb::protocol::xproto::*;
 use x11rb::rust_connection::RustConnection;
 
-struct DrawElement {
-    display: *mut _XDisplay,
-    pixmap: x11::xlib::Pixmap,
-    window: Option<x11::xlib::Drawable>,
-    color: u32,
-    x: i32,
-    y: i32,
-    width: u32,
-    height: u32,
-}
-
 pub struct TabBar {
     window: Window,
     width: u16,
@@ -27,9 +17,10 @@ pub struct TabBar {
     y_offset: i16,
     graphics_context: Gcontext,
     display: *mut x11::xlib::Display,
-    surface: DrawingSurface,
+    renderer: XftRenderer,
     scheme_normal: ColorScheme,
     scheme_selected: ColorScheme,
+    corner_radius: u16,
 }
 
 impl TabBar {
@@ -83,7 +74,7 @@ impl TabBar {
 
         let (visual, colormap) = get_visual_and_colormap(display, screen_num as i32);
 
-        let surface = DrawingSurface::new(
+        let renderer = XftRenderer::new(
             display,
             window as x11::xlib::Drawable,
             width as u32,
@@ -100,9 +91,10 @@ impl TabBar {
             y_offset: y,
             graphics_context,
             display,
-            surface,
+            renderer,
             scheme_normal,
             scheme_selected,
+            corner_radius: 0,
         })
     }
 
@@ -110,12 +102,32 @@ impl TabBar {
         self.window
     }
 
+    pub fn set_corner_radius(
+        &mut self,
+        connection: &RustConnection,
+        radius: u16,
+    ) -> Result<(), X11Error> {
+        self.corner_radius = radius;
+        crate::shape::set_rounded_corners(
+            connection,
+            self.window,
+            self.width,
+            self.height,
+            0,
+            radius,
+        )?;
+        connection.flush()?;
+        Ok(())
+    }
+
     pub fn draw(
         &mut self,
         connection: &RustConnection,
         font: &Font,
         windows: &[(Window, String)],
         focused_window: Option<Window>,
+        active_windows: &[Window],
+        activity_color: u32,
     ) -> Result<(), X11Error> {
         connection.change_gc(
             self.graphics_context,
@@ -123,16 +135,13 @@ impl TabBar {
         )?;
         connection.flush()?;
 
-        draw_elements(DrawElement {
-            display: self.display,
-            pixmap: self.surface.pixmap(),
-            window: None,
-            color: self.scheme_normal.background,
-            x: 0,
-            y: 0,
-            width: self.width as u32,
-            height: self.height as u32,
-        });
+        self.renderer.fill_rect(
+            self.scheme_normal.background,
+            0,
+            0,
+            self.width as u32,
+            self.height as u32,
+        );
 
         if windows.is_empty() {
             self.copy_pixmap_to_window();
@@ -162,7 +171,7 @@ impl TabBar {
             let top_padding = 6;
             let text_y = top_padding + font.ascent();
 
-            self.surface.font_draw().draw_text(
+            self.renderer.draw_text(
                 font,
                 scheme.foreground,
                 text_x,
@@ -170,20 +179,28 @@ impl TabBar {
                 &display_title,
             );
 
+            if active_windows.contains(&window) {
+                let dot_size = 4;
+                self.renderer.fill_rect(
+                    activity_color,
+                    x_position as i32 + dot_size,
+                    (self.height as i32 - dot_size) / 2,
+                    dot_size as u32,
+                    dot_size as u32,
+                );
+            }
+
             if is_focused {
                 let underline_height = 3;
                 let underline_y = self.height as i16 - underline_height;
 
-                draw_elements(DrawElement {
-                    display: self.display,
-                    pixmap: self.surface.pixmap(),
-                    window: None,
-                    color: scheme.underline,
-                    x: x_position as i32,
-                    y: underline_y as i32,
-                    width: tab_width as u32,
-                    height: underline_height as u32,
-                });
+                self.renderer.fill_rect(
+                    scheme.underline,
+                    x_position as i32,
+                    underline_y as i32,
+                    tab_width as u32,
+                    underline_height as u32,
+                );
             }
 
             x_position += tab_width as i16;
@@ -194,16 +211,7 @@ impl TabBar {
     }
 
     fn copy_pixmap_to_window(&self) {
-        draw_elements(DrawElement {
-            display: self.display,
-            pixmap: self.surface.pixmap(),
-            window: Some(self.window as u64),
-            color: 0,
-            x: 0,
-            y: 0,
-            width: self.width as u32,
-            height: self.height as u32,
-        });
+        self.renderer.copy(self.width as u32, self.height as u32);
     }
 
     pub fn get_clicked_window(&self, windows: &[(Window, String)], click_x: i16) -> Option<Window> {
@@ -238,7 +246,7 @@ impl TabBar {
 
         let (visual, colormap) = get_visual_and_colormap(self.display, 0);
 
-        self.surface = DrawingSurface::new(
+        self.renderer = XftRenderer::new(
             self.display,
             self.window as x11::xlib::Drawable,
             width as u32,
@@ -247,10 +255,27 @@ impl TabBar {
             colormap,
         )?;
 
+        if self.corner_radius > 0 {
+            crate::shape::set_rounded_corners(
+                connection,
+                self.window,
+                width,
+                self.height,
+                0,
+                self.corner_radius,
+            )?;
+        }
+
         connection.flush()?;
         Ok(())
     }
 
+    pub fn destroy(&self, connection: &RustConnection) -> Result<(), X11Error> {
+        connection.free_gc(self.graphics_context)?;
+        connection.destroy_window(self.window)?;
+        Ok(())
+    }
+
     pub fn hide(&self, connection: &RustConnection) -> Result<(), X11Error> {
         connection.unmap_window(self.window)?;
         connection.flush()?;
@@ -264,43 +289,6 @@ impl TabBar {
     }
 }
 
-fn draw_elements(element: DrawElement) {
-    unsafe {
-        let gc = x11::xlib::XCreateGC(element.display, element.pixmap, 0, std::ptr::null_mut());
-        match element.window {
-            Some(w) => {
-                x11::xlib::XCopyArea(
-                    element.display,
-                    element.pixmap,
-                    w,
-                    gc,
-                    element.x,
-                    element.y,
-                    element.width,
-                    element.height,
-                    0,
-                    0,
-                );
-                x11::xlib::XFreeGC(element.display, gc);
-                x11::xlib::XSync(element.display, 1);
-            }
-            None => {
-                x11::xlib::XSetForeground(element.display, gc, element.color as u64);
-                x11::xlib::XFillRectangle(
-                    element.display,
-                    element.pixmap,
-                    gc,
-                    element.x,
-                    element.y,
-                    element.width,
-                    element.height,
-                );
-                x11::xlib::XFreeGC(element.display, gc);
-            }
-        }
-    }
-}
-
 fn define_cursor(display: *mut _XDisplay, window: u64, cursor: u64) {
     unsafe {
         x11::xlib::XDefineCursor(display, window, cursor);
@@ -0,0 +1,77 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::path::PathBuf;
+
+const STATE_FILE: &str = "tag-notes";
+
+/// Short notes attached to tags over IPC, such as "deploy in progress",
+/// persisted as one `tag<TAB>note` line per tag so they survive a restart.
+#[derive(Debug, Default)]
+pub struct TagNotes {
+    path: Option<PathBuf>,
+    notes: BTreeMap<usize, String>,
+}
+
+impl TagNotes {
+    pub fn load() -> Self {
+        let path = dirs::state_dir()
+            .or_else(dirs::cache_dir)
+            .map(|dir| dir.join("oxwm").join(STATE_FILE));
+
+        let notes = path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .map(|contents| contents.lines().filter_map(parse_line).collect())
+            .unwrap_or_default();
+
+        Self { path, notes }
+    }
+
+    pub fn get(&self, tag_index: usize) -> Option<&str> {
+        self.notes.get(&tag_index).map(String::as_str)
+    }
+
+    /// The note of the lowest tag in `tags` that has one.
+    pub fn first_in(&self, tags: u32) -> Option<&str> {
+        self.notes
+            .iter()
+            .find(|(tag_index, _)| **tag_index < 32 && tags & (1 << **tag_index) != 0)
+            .map(|(_, note)| note.as_str())
+    }
+
+    /// Sets the note of a tag; an empty note removes it.
+    pub fn set(&mut self, tag_index: usize, note: &str) -> io::Result<()> {
+        // Tabs and newlines would break the file format and the bar.
+        let note = note.split_whitespace().collect::<Vec<_>>().join(" ");
+        if note.is_empty() {
+            if self.notes.remove(&tag_index).is_none() {
+                return Ok(());
+            }
+        } else if self.notes.insert(tag_index, note.clone()).as_ref() == Some(&note) {
+            return Ok(());
+        }
+        self.save()
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut contents = String::new();
+        for (tag_index, note) in &self.notes {
+            contents.push_str(&format!("{}\t{}\n", tag_index, note));
+        }
+
+        std::fs::write(path, contents)
+    }
+}
+
+fn parse_line(line: &str) -> Option<(usize, String)> {
+    let (tag_index, note) = line.split_once('\t')?;
+    Some((tag_index.parse().ok()?, note.to_string()))
+}
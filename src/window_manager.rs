@@ -1,14 +1,24 @@
-use crate::Config;
-use crate::animations::{AnimationConfig, ScrollAnimation};
-use crate::bar::Bar;
+use crate::animations::{AnimationConfig, FadeAnimations, FadeEnd, ScrollAnimation};
+use crate::backend::{Backend, X11Backend};
+use crate::bar::{
+    Bar, BarPosition, BarRegion, BlockManager, EdgeTrigger, FocusContext, MonitorLabel, TitleClass,
+};
 use crate::client::{Client, TagMask};
+use crate::dbus_service::DbusService;
 use crate::errors::{ConfigError, WmError};
+use crate::ipc::{self, IpcServer};
+use crate::json::Json;
+use crate::keyboard::handlers::Condition;
 use crate::keyboard::{self, Arg, KeyAction, handlers};
-use crate::layout::GapConfig;
 use crate::layout::tiling::TilingLayout;
 use crate::layout::{Layout, LayoutBox, LayoutType, layout_from_str, next_layout};
-use crate::monitor::{Monitor, detect_monitors};
-use crate::overlay::{ErrorOverlay, KeybindOverlay, Overlay};
+use crate::monitor::{Gaps, Monitor, Strut, detect_monitors};
+use crate::overlay::{ErrorOverlay, InfoOverlay, KeybindOverlay, Overlay};
+use crate::snapshot::{Snapshot, SnapshotClient};
+use crate::status_fifo::{MonitorStatus, StatusWriter};
+use crate::tag_notes::TagNotes;
+use crate::window_memory::{SavedGeometry, WindowMemory};
+use crate::{Config, FocusOnMap, HiddenTagAction, TagOverflow, VisualBell};
 use std::collections::{HashMap, HashSet};
 
 use x11::xlib::_XDisplay;
@@ -22,32 +32,109 @@ enum Control {
     Quit,
 }
 
+/// WM_CLASS prefixes (lowercase) of input method frameworks whose candidate
+/// and preedit windows oxwm leaves unmanaged.
+const INPUT_METHOD_CLASSES: &[&str] = &["fcitx", "ibus", "scim", "uim", "kime", "nimf"];
+
+// Cursor font shapes, from X11/cursorfont.h.
+const XC_CROSSHAIR: u32 = 34;
+const XC_LEFT_PTR: u32 = 68;
+
 pub fn tag_mask(tag: usize) -> TagMask {
     1 << tag
 }
 
+/// Mask of the first `count` tags; also right for every tag bit in use.
+pub fn all_tags_mask(count: usize) -> TagMask {
+    TagMask::MAX
+        .checked_shr(TagMask::BITS.saturating_sub(count as u32))
+        .unwrap_or(0)
+}
+
+/// Tags are bits of a TagMask.
+const MAX_TAGS: usize = TagMask::BITS as usize;
+
 pub fn unmask_tag(mask: TagMask) -> usize {
     mask.trailing_zeros() as usize
 }
 
+/// Tags with windows and tags with urgent windows on a monitor.
+fn tag_occupancy(clients: &HashMap<Window, Client>, monitor_index: usize) -> (TagMask, TagMask) {
+    let mut occupied_tags: TagMask = 0;
+    let mut urgent_tags: TagMask = 0;
+    for client in clients.values() {
+        if client.monitor_index == monitor_index {
+            occupied_tags |= client.tags;
+            if client.is_urgent {
+                urgent_tags |= client.tags;
+            }
+        }
+    }
+    (occupied_tags, urgent_tags)
+}
+
+/// Windows on `tags` of a monitor, and all windows on it.
+fn window_counts(
+    clients: &HashMap<Window, Client>,
+    monitor_index: usize,
+    tags: TagMask,
+) -> (usize, usize) {
+    let mut visible = 0;
+    let mut total = 0;
+    for client in clients.values() {
+        if client.monitor_index == monitor_index {
+            total += 1;
+            if client.tags & tags != 0 {
+                visible += 1;
+            }
+        }
+    }
+    (visible, total)
+}
+
+fn geometry(x: i32, y: i32, width: i32, height: i32) -> Json {
+    Json::object()
+        .with("x", x)
+        .with("y", y)
+        .with("width", width)
+        .with("height", height)
+}
+
 struct AtomCache {
     net_supported: Atom,
     net_supporting_wm_check: Atom,
     net_current_desktop: Atom,
+    net_number_of_desktops: Atom,
+    net_desktop_names: Atom,
+    net_showing_desktop: Atom,
     net_client_info: Atom,
     wm_state: Atom,
     wm_protocols: Atom,
     wm_delete_window: Atom,
     net_wm_state: Atom,
     net_wm_state_fullscreen: Atom,
+    net_wm_state_maximized_vert: Atom,
+    net_wm_state_maximized_horz: Atom,
+    net_wm_state_below: Atom,
     net_wm_window_type: Atom,
     net_wm_window_type_dialog: Atom,
+    net_wm_window_type_utility: Atom,
+    net_wm_window_type_desktop: Atom,
+    net_wm_window_type_dock: Atom,
+    net_wm_strut: Atom,
+    net_wm_strut_partial: Atom,
+    net_wm_window_type_popup_menu: Atom,
     wm_name: Atom,
     net_wm_name: Atom,
     utf8_string: Atom,
     net_active_window: Atom,
     wm_take_focus: Atom,
     net_client_list: Atom,
+    net_wm_desktop: Atom,
+    net_wm_pid: Atom,
+    net_wm_window_opacity: Atom,
+    clipboard: Atom,
+    targets: Atom,
 }
 
 impl AtomCache {
@@ -67,6 +154,21 @@ impl AtomCache {
             .reply()?
             .atom;
 
+        let net_number_of_desktops = connection
+            .intern_atom(false, b"_NET_NUMBER_OF_DESKTOPS")?
+            .reply()?
+            .atom;
+
+        let net_desktop_names = connection
+            .intern_atom(false, b"_NET_DESKTOP_NAMES")?
+            .reply()?
+            .atom;
+
+        let net_showing_desktop = connection
+            .intern_atom(false, b"_NET_SHOWING_DESKTOP")?
+            .reply()?
+            .atom;
+
         let net_client_info = connection
             .intern_atom(false, b"_NET_CLIENT_INFO")?
             .reply()?
@@ -94,6 +196,21 @@ impl AtomCache {
             .reply()?
             .atom;
 
+        let net_wm_state_maximized_vert = connection
+            .intern_atom(false, b"_NET_WM_STATE_MAXIMIZED_VERT")?
+            .reply()?
+            .atom;
+
+        let net_wm_state_maximized_horz = connection
+            .intern_atom(false, b"_NET_WM_STATE_MAXIMIZED_HORZ")?
+            .reply()?
+            .atom;
+
+        let net_wm_state_below = connection
+            .intern_atom(false, b"_NET_WM_STATE_BELOW")?
+            .reply()?
+            .atom;
+
         let net_wm_window_type = connection
             .intern_atom(false, b"_NET_WM_WINDOW_TYPE")?
             .reply()?
@@ -104,6 +221,36 @@ impl AtomCache {
             .reply()?
             .atom;
 
+        let net_wm_window_type_desktop = connection
+            .intern_atom(false, b"_NET_WM_WINDOW_TYPE_DESKTOP")?
+            .reply()?
+            .atom;
+
+        let net_wm_window_type_dock = connection
+            .intern_atom(false, b"_NET_WM_WINDOW_TYPE_DOCK")?
+            .reply()?
+            .atom;
+
+        let net_wm_strut = connection
+            .intern_atom(false, b"_NET_WM_STRUT")?
+            .reply()?
+            .atom;
+
+        let net_wm_strut_partial = connection
+            .intern_atom(false, b"_NET_WM_STRUT_PARTIAL")?
+            .reply()?
+            .atom;
+
+        let net_wm_window_type_utility = connection
+            .intern_atom(false, b"_NET_WM_WINDOW_TYPE_UTILITY")?
+            .reply()?
+            .atom;
+
+        let net_wm_window_type_popup_menu = connection
+            .intern_atom(false, b"_NET_WM_WINDOW_TYPE_POPUP_MENU")?
+            .reply()?
+            .atom;
+
         let wm_name = AtomEnum::WM_NAME.into();
         let net_wm_name = connection
             .intern_atom(false, b"_NET_WM_NAME")?
@@ -125,24 +272,59 @@ impl AtomCache {
             .reply()?
             .atom;
 
+        let net_wm_desktop = connection
+            .intern_atom(false, b"_NET_WM_DESKTOP")?
+            .reply()?
+            .atom;
+
+        let net_wm_pid = connection
+            .intern_atom(false, b"_NET_WM_PID")?
+            .reply()?
+            .atom;
+
+        let net_wm_window_opacity = connection
+            .intern_atom(false, b"_NET_WM_WINDOW_OPACITY")?
+            .reply()?
+            .atom;
+
+        let clipboard = connection.intern_atom(false, b"CLIPBOARD")?.reply()?.atom;
+        let targets = connection.intern_atom(false, b"TARGETS")?.reply()?.atom;
+
         Ok(Self {
             net_supported,
             net_supporting_wm_check,
             net_current_desktop,
+            net_number_of_desktops,
+            net_desktop_names,
+            net_showing_desktop,
             net_client_info,
             wm_state,
             wm_protocols,
             wm_delete_window,
             net_wm_state,
             net_wm_state_fullscreen,
+            net_wm_state_maximized_vert,
+            net_wm_state_maximized_horz,
+            net_wm_state_below,
             net_wm_window_type,
             net_wm_window_type_dialog,
+            net_wm_window_type_utility,
+            net_wm_window_type_desktop,
+            net_wm_window_type_dock,
+            net_wm_strut,
+            net_wm_strut_partial,
+            net_wm_window_type_popup_menu,
             wm_name,
             net_wm_name,
             utf8_string,
             net_active_window,
             wm_take_focus,
             net_client_list,
+            net_wm_desktop,
+            net_wm_pid,
+            net_wm_window_opacity,
+            clipboard,
+            targets,
         })
     }
 }
@@ -152,31 +334,107 @@ pub struct WindowManager {
     connection: RustConnection,
     screen_number: usize,
     root: Window,
-    _wm_check_window: Window,
+    wm_check_window: Window,
     screen: Screen,
     windows: Vec<Window>,
     clients: HashMap<Window, Client>,
     layout: LayoutBox,
-    gaps_enabled: bool,
     floating_windows: HashSet<Window>,
+    input_method_windows: HashSet<Window>,
+    /// `_NET_WM_WINDOW_TYPE_DESKTOP` windows: mapped on every tag, kept
+    /// under all clients, and not managed otherwise.
+    desktop_windows: HashSet<Window>,
+    /// `_NET_WM_WINDOW_TYPE_DOCK` windows such as external bars: not
+    /// managed, and kept clear of tiled windows by their struts.
+    dock_windows: HashMap<Window, Strut>,
     fullscreen_windows: HashSet<Window>,
     bars: Vec<Bar>,
+    /// Status-only bars on the edge opposite each monitor's bar.
+    second_bars: Vec<Option<Bar>>,
+    /// Screen edge strips revealing auto-hidden bars, with their monitor.
+    bar_triggers: Vec<(usize, EdgeTrigger)>,
+    /// When the revealed auto-hidden bars of a monitor hide again.
+    bars_revealed: HashMap<usize, std::time::Instant>,
+    /// Runs the status blocks once for all bars showing the same ones.
+    block_manager: BlockManager,
     tab_bars: Vec<crate::tab_bar::TabBar>,
     show_bar: bool,
     monitors: Vec<Monitor>,
     selected_monitor: usize,
     atoms: AtomCache,
     previous_focused: Option<Window>,
-    display: *mut x11::xlib::Display,
     font: crate::bar::font::Font,
     keychord_state: keyboard::handlers::KeychordState,
     current_key: usize,
     keyboard_mapping: Option<keyboard::KeyboardMapping>,
+    keymap_changed: bool,
+    bell_flash: Option<(Window, std::time::Instant)>,
+    lock_listener: Option<crate::session_lock::LockListener>,
+    config_watcher: Option<crate::config::ConfigWatcher>,
+    /// When a config file was last saved, while waiting for the editor to
+    /// finish writing before reloading.
+    pending_reload: Option<std::time::Instant>,
+    locker: crate::session_lock::Locker,
+    /// Screen saver settings and DPMS state to restore when presentation
+    /// mode ends; Some while presenting.
+    presentation_saved: Option<(GetScreenSaverReply, bool)>,
+    power_listener: Option<crate::power::PowerListener>,
+    power_state: crate::power::PowerState,
+    active_mode: Option<usize>,
+    configured_modkey: KeyButMask,
     error_message: Option<String>,
+    /// Tags from the config; tags after these were created at runtime and
+    /// are removed again once empty.
+    static_tag_count: usize,
+    /// Whether `tag_links` switch companion monitors along.
+    tag_links_enabled: bool,
+    /// Text served to other clients while we own the CLIPBOARD selection.
+    clipboard_text: Option<String>,
+    /// Last left click on a bar region, to recognize double clicks.
+    last_bar_click: Option<(usize, BarRegion, std::time::Instant)>,
+    /// Single click held back until a double click can be ruled out.
+    pending_bar_click: Option<(usize, BarRegion, std::time::Instant)>,
+    /// Last picked color, shown in the bar for a moment.
+    picked_color: Option<(String, std::time::Instant)>,
+    /// Pointer grab waiting for the click of a color pick or inspect.
+    click_pick: Option<ClickPick>,
+    ipc: Option<IpcServer>,
+    dbus: Option<DbusService>,
+    /// Focus and per-monitor tags last announced on D-Bus.
+    dbus_announced: (Option<Window>, Vec<TagMask>),
     overlay: ErrorOverlay,
     keybind_overlay: KeybindOverlay,
+    info_overlay: InfoOverlay,
     scroll_animation: ScrollAnimation,
+    fade_animations: FadeAnimations,
+    /// Windows asked to close after fading out, with the time of the request.
+    closing_windows: HashMap<Window, std::time::Instant>,
+    /// Terminals hidden by a program they started, keyed by its window.
+    swallowed: HashMap<Window, Client>,
+    /// Popups opened by block clicks, keyed by monitor and block index.
+    block_popups: HashMap<(usize, usize), Window>,
+    /// `_NET_WM_DESKTOP` last set on each client.
+    client_desktops: HashMap<Window, u32>,
+    /// Popup terminals spawned but not mapped yet, with their pid.
+    pending_block_popups: Vec<(u32, (usize, usize), std::time::Instant)>,
+    /// Window of each scratchpad by name, once it has one.
+    scratchpad_windows: HashMap<String, Window>,
+    /// Scratchpads whose command was run and whose window hasn't mapped yet.
+    pending_scratchpads: Vec<(String, std::time::Instant)>,
+    /// Pinned apps started but not mapped yet, as pinned tag and app index.
+    pending_pinned: Vec<(usize, usize, std::time::Instant)>,
+    /// Tags whose pinned apps were already started on first view.
+    pinned_viewed: TagMask,
     animation_config: AnimationConfig,
+    passthrough: bool,
+    capture_active: bool,
+    window_memory: WindowMemory,
+    tag_notes: TagNotes,
+    status_writer: Option<StatusWriter>,
+    display: *mut _XDisplay,
+    normal_cursor: u32,
+    output_state: OutputState,
+    lid_closed: Option<bool>,
 }
 
 type WmResult<T> = Result<T, WmError>;
@@ -201,44 +459,22 @@ impl WindowManager {
             )?
             .check()?;
 
-        let ignore_modifiers = [
-            0,
-            u16::from(ModMask::LOCK),
-            u16::from(ModMask::M2),
-            u16::from(ModMask::LOCK | ModMask::M2),
-        ];
-
-        for &ignore_mask in &ignore_modifiers {
-            let grab_mask = u16::from(config.modkey) | ignore_mask;
-
-            connection.grab_button(
-                false,
-                root,
-                EventMask::BUTTON_PRESS | EventMask::BUTTON_RELEASE,
-                GrabMode::SYNC,
-                GrabMode::ASYNC,
-                x11rb::NONE,
-                x11rb::NONE,
-                ButtonIndex::M1,
-                grab_mask.into(),
-            )?;
+        let modkey = config.modkey;
+        grab_modkey_buttons(&connection, root, modkey)?;
 
-            connection.grab_button(
-                false,
-                root,
-                EventMask::BUTTON_PRESS | EventMask::BUTTON_RELEASE,
-                GrabMode::SYNC,
-                GrabMode::ASYNC,
-                x11rb::NONE,
-                x11rb::NONE,
-                ButtonIndex::M3,
-                grab_mask.into(),
-            )?;
+        if let Err(e) = crate::randr::select_change_events(&connection, root) {
+            eprintln!("Failed to select RandR events: {:?}", e);
         }
+        if let Err(e) = crate::xkb::select_events(&connection) {
+            eprintln!("Failed to select XKB events: {:?}", e);
+        }
+        let lid_closed = crate::randr::lid_closed();
+        let mut output_state = OutputState::default();
+        configure_outputs(&connection, root, &config, lid_closed, &mut output_state);
 
         let mut monitors = detect_monitors(&connection, &screen, root)?;
         for monitor in monitors.iter_mut() {
-            monitor.init_pertag(config.tags.len(), "tiling");
+            monitor.init_pertag(config.tags.len(), "tiling", Gaps::from_config(&config));
         }
 
         let display = open_display();
@@ -247,7 +483,7 @@ impl WindowManager {
         }
 
         // C has better C interop than rust.
-        let normal_cursor = create_cursor(display);
+        let normal_cursor = create_cursor(display, XC_LEFT_PTR);
 
         define_cursor(display, root as u64, normal_cursor);
 
@@ -271,7 +507,7 @@ impl WindowManager {
         let bar_height = font.height() as f32 * 1.4;
         let mut tab_bars = Vec::new();
         for monitor in monitors.iter() {
-            let tab_bar = crate::tab_bar::TabBar::new(
+            let mut tab_bar = crate::tab_bar::TabBar::new(
                 &connection,
                 &screen,
                 screen_number,
@@ -288,10 +524,11 @@ impl WindowManager {
                 config.scheme_selected,
                 normal_cursor as u32,
             )?;
+            tab_bar.set_corner_radius(&connection, config.bar_corner_radius)?;
             tab_bars.push(tab_bar);
         }
 
-        let gaps_enabled = config.gaps_enabled;
+        let static_tag_count = config.tags.len();
 
         let atoms = AtomCache::new(&connection)?;
 
@@ -300,13 +537,25 @@ impl WindowManager {
             atoms.net_supporting_wm_check,
             atoms.net_wm_state,
             atoms.net_wm_state_fullscreen,
+            atoms.net_wm_state_maximized_vert,
+            atoms.net_wm_state_maximized_horz,
+            atoms.net_wm_state_below,
             atoms.net_wm_window_type,
             atoms.net_wm_window_type_dialog,
+            atoms.net_wm_window_type_utility,
+            atoms.net_wm_window_type_desktop,
+            atoms.net_wm_window_type_dock,
+            atoms.net_wm_strut,
+            atoms.net_wm_strut_partial,
             atoms.net_active_window,
             atoms.net_wm_name,
             atoms.net_current_desktop,
+            atoms.net_number_of_desktops,
+            atoms.net_desktop_names,
+            atoms.net_showing_desktop,
             atoms.net_client_info,
             atoms.net_client_list,
+            atoms.net_wm_desktop,
         ];
         let supported_bytes: Vec<u8> = supported_atoms
             .iter()
@@ -378,46 +627,96 @@ impl WindowManager {
 
         let keybind_overlay =
             KeybindOverlay::new(&connection, &screen, screen_number, display, config.modkey)?;
+        let info_overlay = InfoOverlay::new(&connection, &screen, screen_number, display)?;
 
         let mut window_manager = Self {
             config,
             connection,
             screen_number,
             root,
-            _wm_check_window: wm_check_window,
+            wm_check_window,
             screen,
             windows: Vec::new(),
             clients: HashMap::new(),
             layout: Box::new(TilingLayout),
-            gaps_enabled,
             floating_windows: HashSet::new(),
+            input_method_windows: HashSet::new(),
+            desktop_windows: HashSet::new(),
+            dock_windows: HashMap::new(),
             fullscreen_windows: HashSet::new(),
             bars,
+            second_bars: Vec::new(),
+            bar_triggers: Vec::new(),
+            bars_revealed: HashMap::new(),
+            block_manager: BlockManager::new(),
             tab_bars,
             show_bar: true,
             monitors,
             selected_monitor: 0,
             atoms,
             previous_focused: None,
-            display,
             font,
             keychord_state: keyboard::handlers::KeychordState::Idle,
             current_key: 0,
             keyboard_mapping: None,
+            keymap_changed: false,
+            bell_flash: None,
+            lock_listener: None,
+            config_watcher: None,
+            pending_reload: None,
+            locker: crate::session_lock::Locker::default(),
+            presentation_saved: None,
+            power_listener: None,
+            power_state: crate::power::PowerState::read(),
+            active_mode: None,
+            configured_modkey: modkey,
             error_message: None,
+            static_tag_count,
+            tag_links_enabled: true,
+            clipboard_text: None,
+            last_bar_click: None,
+            pending_bar_click: None,
+            picked_color: None,
+            click_pick: None,
+            ipc: None,
+            dbus: None,
+            dbus_announced: (None, Vec::new()),
             overlay,
             keybind_overlay,
+            info_overlay,
             scroll_animation: ScrollAnimation::new(),
+            fade_animations: FadeAnimations::new(),
+            closing_windows: HashMap::new(),
+            swallowed: HashMap::new(),
+            block_popups: HashMap::new(),
+            client_desktops: HashMap::new(),
+            pending_block_popups: Vec::new(),
+            scratchpad_windows: HashMap::new(),
+            pending_scratchpads: Vec::new(),
+            pending_pinned: Vec::new(),
+            pinned_viewed: 0,
             animation_config: AnimationConfig::default(),
+            passthrough: false,
+            capture_active: false,
+            window_memory: WindowMemory::load(),
+            tag_notes: TagNotes::load(),
+            status_writer: None,
+            display,
+            normal_cursor: normal_cursor as u32,
+            output_state,
+            lid_closed,
         };
 
         for tab_bar in &window_manager.tab_bars {
             tab_bar.hide(&window_manager.connection)?;
         }
 
+        window_manager.update_monitor_labels();
+        window_manager.apply_monitor_configs(true)?;
         window_manager.scan_existing_windows()?;
         window_manager.update_bar()?;
         window_manager.run_autostart_commands();
+        window_manager.notify_session_ready();
 
         Ok(window_manager)
     }
@@ -442,6 +741,66 @@ impl WindowManager {
         }
     }
 
+    /// Reloads the config file: rebinds keys, recreates the bars with the
+    /// new font and blocks, and repaints client borders. Shows the error in
+    /// the overlay when the config fails to load, keeping the old one.
+    pub fn reload(&mut self) -> WmResult<Result<(), String>> {
+        let previous_font = self.config.font.clone();
+        match self.try_reload_config() {
+            Ok(()) => {
+                if self.config.font != previous_font {
+                    match crate::bar::font::Font::new(
+                        self.display,
+                        self.screen_number as i32,
+                        &self.config.font,
+                    ) {
+                        Ok(font) => self.font = font,
+                        Err(e) => eprintln!("Failed to load font '{}': {:?}", self.config.font, e),
+                    }
+                }
+                self.recreate_bars()?;
+                self.update_monitor_labels();
+                self.apply_monitor_configs(false)?;
+                self.repaint_borders()?;
+
+                let gaps = Gaps::from_config(&self.config);
+                for monitor in &mut self.monitors {
+                    monitor.reset_gaps(gaps);
+                }
+                self.error_message = None;
+                if let Err(error) = self.overlay.hide(&self.connection) {
+                    eprintln!("Failed to hide overlay after config reload: {:?}", error);
+                }
+                self.apply_layout()?;
+                self.update_bar()?;
+                Ok(Ok(()))
+            }
+            Err(err) => {
+                eprintln!("Config reload error: {}", err);
+                let message = err.to_string();
+                self.error_message = Some(message.clone());
+                let monitor = &self.monitors[self.selected_monitor];
+                let monitor_x = monitor.screen_info.x as i16;
+                let monitor_y = monitor.screen_info.y as i16;
+                let screen_width = monitor.screen_info.width as u16;
+                let screen_height = monitor.screen_info.height as u16;
+                match self.overlay.show_error(
+                    &self.connection,
+                    &self.font,
+                    err,
+                    monitor_x,
+                    monitor_y,
+                    screen_width,
+                    screen_height,
+                ) {
+                    Ok(()) => eprintln!("Error modal displayed"),
+                    Err(e) => eprintln!("Failed to show error modal: {:?}", e),
+                }
+                Ok(Err(message))
+            }
+        }
+    }
+
     fn try_reload_config(&mut self) -> Result<(), ConfigError> {
         let lua_path = self
             .config
@@ -460,26 +819,99 @@ impl WindowManager {
 
         let new_config = crate::config::parse_lua_config(&config_str, config_dir)?;
 
+        // Windows may still live on runtime tags, and on static tags the new
+        // config drops; those are kept after the new static tags until
+        // they are empty.
+        let static_tag_count = new_config.tags.len();
+        if static_tag_count > self.static_tag_count {
+            self.insert_tags(
+                self.static_tag_count,
+                static_tag_count - self.static_tag_count,
+            );
+        }
         let lua_path = self.config.path.take();
+        let kept_tags = self
+            .config
+            .tags
+            .split_off(static_tag_count.min(self.config.tags.len()));
 
         self.config = new_config;
         self.config.path = lua_path;
+        self.static_tag_count = static_tag_count;
+        self.config.tags.extend(kept_tags);
+        self.collect_dynamic_tags();
+        self.update_desktop_hints();
         self.error_message = None;
 
-        for bar in &mut self.bars {
-            bar.update_from_config(&self.config);
+        self.active_mode = None;
+        self.configured_modkey = self.config.modkey;
+        if let Err(e) = self.set_modkey(self.config.modkey) {
+            eprintln!("Failed to grab modkey bindings: {:?}", e);
+        }
+
+        let geometries: Vec<(Window, u16, u16, u16)> = self
+            .clients
+            .values()
+            .map(|c| (c.window, c.width, c.height, c.border_width))
+            .collect();
+        for (window, width, height, border_width) in geometries {
+            if let Err(e) = self.update_window_shape(window, width, height, border_width) {
+                eprintln!("Failed to update window shape: {:?}", e);
+            }
         }
 
+        self.update_lock_listener();
+        self.update_power_listener();
+        self.update_remote_control();
+        self.update_config_watcher();
+
+        Ok(())
+    }
+
+    /// Sets every client's border to the focused or unfocused color.
+    fn repaint_borders(&mut self) -> WmResult<()> {
+        let focused = self
+            .monitors
+            .get(self.selected_monitor)
+            .and_then(|m| m.selected_client);
+        for &window in self.clients.keys() {
+            let color = if Some(window) == focused {
+                self.config.border_focused
+            } else {
+                self.config.border_unfocused
+            };
+            self.backend().set_border_color(window, color)?;
+        }
         Ok(())
     }
 
+    /// Watches the config file while `auto_reload` is on.
+    fn update_config_watcher(&mut self) {
+        let Some(path) = self
+            .config
+            .path
+            .as_ref()
+            .filter(|_| self.config.auto_reload)
+        else {
+            self.config_watcher = None;
+            return;
+        };
+        if self.config_watcher.is_some() {
+            return;
+        }
+        match crate::config::ConfigWatcher::spawn(path) {
+            Ok(watcher) => self.config_watcher = Some(watcher),
+            Err(e) => eprintln!("Failed to watch {}: {}", path.display(), e),
+        }
+    }
+
     fn scan_existing_windows(&mut self) -> WmResult<()> {
         let tree = self.connection.query_tree(self.root)?.reply()?;
         let net_client_info = self.atoms.net_client_info;
         let wm_state_atom = self.atoms.wm_state;
 
         for &window in &tree.children {
-            if self.bars.iter().any(|bar| bar.window() == window) {
+            if self.is_bar_window(window) {
                 continue;
             }
 
@@ -491,6 +923,20 @@ impl WindowManager {
                 continue;
             }
 
+            if self.is_desktop_window(window) {
+                if attrs.map_state == MapState::VIEWABLE {
+                    self.desktop_windows.insert(window);
+                }
+                continue;
+            }
+
+            if self.is_dock_window(window) {
+                if attrs.map_state == MapState::VIEWABLE {
+                    self.add_dock(window)?;
+                }
+                continue;
+            }
+
             if attrs.map_state == MapState::VIEWABLE {
                 let _tag = self.get_saved_tag(window, net_client_info)?;
                 self.windows.push(window);
@@ -516,12 +962,16 @@ impl WindowManager {
 
                 if has_wm_class {
                     let _tag = self.get_saved_tag(window, net_client_info)?;
-                    self.connection.map_window(window)?;
+                    self.backend().map_window(window)?;
                     self.windows.push(window);
                 }
             }
         }
 
+        if !self.dock_windows.is_empty() {
+            self.place_bars()?;
+        }
+
         if let Some(&first) = self.windows.first() {
             self.focus(Some(first))?;
         }
@@ -544,7 +994,7 @@ impl WindowManager {
                     prop.value[3],
                 ]);
 
-                if tags != 0 && tags < (1 << self.config.tags.len()) {
+                if tags != 0 && tags & !all_tags_mask(self.config.tags.len()) == 0 {
                     return Ok(tags);
                 }
             }
@@ -624,30 +1074,166 @@ impl WindowManager {
         println!("oxwm started on display {}", self.screen_number);
 
         self.grab_keys()?;
+        self.update_lock_listener();
+        self.update_power_listener();
+        self.update_config_watcher();
+        self.update_desktop_hints();
+        self.save_selected_tags()?;
         self.update_bar()?;
 
+        match IpcServer::bind() {
+            Ok(server) => {
+                println!("IPC listening on {}", server.path().display());
+                self.ipc = Some(server);
+            }
+            Err(e) => eprintln!("Failed to start IPC: {}", e),
+        }
+        self.update_remote_control();
+
+        match DbusService::spawn() {
+            Ok(service) => self.dbus = Some(service),
+            Err(e) => eprintln!("Failed to start D-Bus service: {}", e),
+        }
+
         let mut last_bar_update = std::time::Instant::now();
         const BAR_UPDATE_INTERVAL_MS: u64 = 100;
+        let mut last_capture_check = std::time::Instant::now();
+        const CAPTURE_CHECK_INTERVAL_MS: u64 = 2000;
+        let mut last_lid_check = std::time::Instant::now();
+        const LID_CHECK_INTERVAL_MS: u64 = 2000;
+        const BELL_FLASH_MS: u128 = 150;
+        const PICKED_COLOR_MS: u128 = 3000;
+        let mut last_power_check = std::time::Instant::now();
+        const POWER_CHECK_INTERVAL_MS: u64 = 30000;
+        // Editors write a file in several steps; reload once they settle.
+        const RELOAD_SETTLE_MS: u128 = 200;
 
         loop {
             match self.connection.poll_for_event_with_sequence()? {
                 Some((event, _sequence)) => {
                     if matches!(self.handle_event(event)?, Control::Quit) {
+                        // Leave the screen saver and DPMS as they were found.
+                        if self.presentation_saved.is_some() {
+                            self.toggle_presentation()?;
+                        }
+                        for terminal in self.swallowed.values() {
+                            self.backend().map_window(terminal.window)?;
+                        }
+                        self.connection.flush()?;
                         return Ok(());
                     }
                 }
                 None => {
+                    // A single setxkbmap run sends a burst of mapping events;
+                    // re-grab once the queue has drained.
+                    if self.keymap_changed {
+                        self.keymap_changed = false;
+                        self.grab_keys()?;
+                    }
+
+                    if let Some((window, started)) = self.bell_flash
+                        && started.elapsed().as_millis() >= BELL_FLASH_MS
+                    {
+                        self.bell_flash = None;
+                        self.restore_border(window)?;
+                    }
+
+                    if let Some((monitor_index, region, clicked)) = self.pending_bar_click
+                        && clicked.elapsed().as_millis() >= self.config.double_click_ms as u128
+                    {
+                        self.pending_bar_click = None;
+                        self.bar_single_click(monitor_index, region)?;
+                    }
+
+                    if let Some((_, picked)) = &self.picked_color
+                        && picked.elapsed().as_millis() >= PICKED_COLOR_MS
+                    {
+                        self.picked_color = None;
+                        self.update_bar()?;
+                    }
+
+                    if self
+                        .config_watcher
+                        .as_ref()
+                        .is_some_and(|watcher| watcher.changed())
+                    {
+                        self.pending_reload = Some(std::time::Instant::now());
+                    }
+                    if let Some(saved) = self.pending_reload
+                        && saved.elapsed().as_millis() >= RELOAD_SETTLE_MS
+                    {
+                        self.pending_reload = None;
+                        let _ = self.reload()?;
+                    }
+
+                    if self
+                        .lock_listener
+                        .as_ref()
+                        .is_some_and(|listener| listener.lock_requested())
+                        && self.presentation_saved.is_none()
+                    {
+                        self.lock_session()?;
+                    }
+
+                    // Hooks are also polled in case dbus-monitor is missing.
+                    if self
+                        .power_listener
+                        .as_ref()
+                        .is_some_and(|listener| listener.changed())
+                        || (!self.config.power_hooks.is_empty()
+                            && last_power_check.elapsed().as_millis()
+                                >= POWER_CHECK_INTERVAL_MS as u128)
+                    {
+                        self.check_power_state();
+                        last_power_check = std::time::Instant::now();
+                    }
+
                     if last_bar_update.elapsed().as_millis() >= BAR_UPDATE_INTERVAL_MS as u128 {
-                        if let Some(bar) = self.bars.get_mut(self.selected_monitor) {
-                            bar.update_blocks();
+                        // Blocks marked focused_only only run on the focused
+                        // monitor's bars.
+                        let focused_sets: Vec<usize> = self
+                            .monitor_bars(self.selected_monitor)
+                            .map(|bar| bar.block_set())
+                            .collect();
+                        let changed = self.block_manager.update(&focused_sets);
+                        let second_bars = self.second_bars.iter_mut().enumerate().filter_map(
+                            |(monitor_index, bar)| Some((monitor_index, bar.as_mut()?)),
+                        );
+                        for (monitor_index, bar) in
+                            self.bars.iter_mut().enumerate().chain(second_bars)
+                        {
+                            bar.set_focused(monitor_index == self.selected_monitor);
+                            if changed {
+                                bar.set_block_outputs(self.block_manager.outputs(bar.block_set()));
+                            }
                         }
-                        if self.bars.iter().any(|bar| bar.needs_redraw()) {
+                        if self
+                            .bars
+                            .iter()
+                            .chain(self.second_bars.iter().flatten())
+                            .any(|bar| bar.needs_redraw())
+                        {
                             self.update_bar()?;
                         }
                         last_bar_update = std::time::Instant::now();
                     }
 
+                    if last_capture_check.elapsed().as_millis() >= CAPTURE_CHECK_INTERVAL_MS as u128
+                    {
+                        self.check_capture_active()?;
+                        last_capture_check = std::time::Instant::now();
+                    }
+
+                    if last_lid_check.elapsed().as_millis() >= LID_CHECK_INTERVAL_MS as u128 {
+                        self.check_lid_state()?;
+                        last_lid_check = std::time::Instant::now();
+                    }
+
+                    self.handle_ipc()?;
+                    self.handle_dbus()?;
+
                     self.tick_animations()?;
+                    self.tick_bar_autohide()?;
 
                     self.connection.flush()?;
                     std::thread::sleep(std::time::Duration::from_millis(16));
@@ -656,6 +1242,32 @@ impl WindowManager {
         }
     }
 
+    /// Grows or shrinks the viewed tag's gaps by the given pixels until the
+    /// next config reload, turning them on if they were off.
+    fn adjust_gaps(&mut self, inner_delta: i32, outer_delta: i32) -> WmResult<()> {
+        self.change_gaps(|gaps| {
+            for gap in [&mut gaps.inner_horizontal, &mut gaps.inner_vertical] {
+                *gap = gap.saturating_add_signed(inner_delta);
+            }
+            for gap in [&mut gaps.outer_horizontal, &mut gaps.outer_vertical] {
+                *gap = gap.saturating_add_signed(outer_delta);
+            }
+            gaps.enabled = true;
+        })
+    }
+
+    /// Changes the gaps of the selected monitor's viewed tag.
+    fn change_gaps(&mut self, change: impl FnOnce(&mut Gaps)) -> WmResult<()> {
+        if let Some(monitor) = self.monitors.get_mut(self.selected_monitor) {
+            change(&mut monitor.gaps);
+            if let Some(ref mut pertag) = monitor.pertag {
+                pertag.gaps[pertag.current_tag] = monitor.gaps;
+            }
+        }
+        self.apply_layout()?;
+        self.restack()
+    }
+
     fn toggle_floating(&mut self) -> WmResult<()> {
         let focused = self
             .monitors
@@ -713,52 +1325,244 @@ impl WindowManager {
         Ok(())
     }
 
-    fn set_master_factor(&mut self, delta: f32) -> WmResult<()> {
-        if let Some(monitor) = self.monitors.get_mut(self.selected_monitor) {
-            let new_mfact = (monitor.master_factor + delta).clamp(0.05, 0.95);
-            monitor.master_factor = new_mfact;
-            if let Some(ref mut pertag) = monitor.pertag {
-                pertag.master_factors[pertag.current_tag] = new_mfact;
-            }
-            self.apply_layout()?;
+    fn remember_floating_geometry(&mut self, window: Window) {
+        if !self.config.remember_floating_geometry {
+            return;
         }
-        Ok(())
-    }
 
-    fn inc_num_master(&mut self, delta: i32) -> WmResult<()> {
-        if let Some(monitor) = self.monitors.get_mut(self.selected_monitor) {
-            let new_nmaster = (monitor.num_master + delta).max(0);
-            monitor.num_master = new_nmaster;
-            if let Some(ref mut pertag) = monitor.pertag {
-                pertag.num_masters[pertag.current_tag] = new_nmaster;
-            }
-            self.apply_layout()?;
+        let Some(client) = self.clients.get(&window) else {
+            return;
+        };
+        if !client.is_floating || client.is_fullscreen {
+            return;
         }
-        Ok(())
-    }
 
-    fn tick_animations(&mut self) -> WmResult<()> {
-        if self.scroll_animation.is_active()
-            && let Some(new_offset) = self.scroll_animation.update()
-        {
-            if let Some(m) = self.monitors.get_mut(self.selected_monitor) {
-                m.scroll_offset = new_offset;
-            }
-            self.apply_layout()?;
-            self.update_bar()?;
+        let geometry = SavedGeometry {
+            x: client.x_position as i32,
+            y: client.y_position as i32,
+            width: client.width as u32,
+            height: client.height as u32,
+        };
+        if let Err(e) = self.window_memory.remember(&client.class, geometry) {
+            eprintln!(
+                "Failed to save floating geometry for {}: {}",
+                client.class, e
+            );
         }
-        Ok(())
     }
 
-    fn scroll_layout(&mut self, direction: i32) -> WmResult<()> {
-        if self.layout.name() != "scrolling" {
-            return Ok(());
+    fn restore_floating_geometry(&mut self, window: Window) {
+        if !self.config.remember_floating_geometry {
+            return;
         }
 
-        let monitor_index = self.selected_monitor;
-        let monitor = match self.monitors.get(monitor_index) {
-            Some(m) => m.clone(),
-            None => return Ok(()),
+        let Some(client) = self.clients.get_mut(&window) else {
+            return;
+        };
+        let Some(saved) = self.window_memory.get(&client.class) else {
+            return;
+        };
+        let Some(monitor) = self.monitors.get(client.monitor_index) else {
+            return;
+        };
+
+        let border = 2 * client.border_width as i32;
+        let width = (saved.width as i32)
+            .min(monitor.window_area_width - border)
+            .max(1);
+        let height = (saved.height as i32)
+            .min(monitor.window_area_height - border)
+            .max(1);
+        let max_x = monitor.window_area_x + monitor.window_area_width - width - border;
+        let max_y = monitor.window_area_y + monitor.window_area_height - height - border;
+        let x = saved.x.min(max_x).max(monitor.window_area_x);
+        let y = saved.y.min(max_y).max(monitor.window_area_y);
+
+        client.x_position = x as i16;
+        client.y_position = y as i16;
+        client.width = width as u16;
+        client.height = height as u16;
+    }
+
+    fn cycle_float_preset(&mut self) -> WmResult<()> {
+        if self.config.float_presets.is_empty() {
+            return Ok(());
+        }
+
+        let Some(focused) = self
+            .monitors
+            .get(self.selected_monitor)
+            .and_then(|m| m.selected_client)
+        else {
+            return Ok(());
+        };
+
+        let Some(client) = self.clients.get_mut(&focused) else {
+            return Ok(());
+        };
+        if !client.is_floating || client.is_fullscreen {
+            return Ok(());
+        }
+        let Some(monitor) = self.monitors.get(client.monitor_index) else {
+            return Ok(());
+        };
+
+        let preset_index = client
+            .float_preset
+            .map(|index| (index + 1) % self.config.float_presets.len())
+            .unwrap_or(0);
+        let (x, y, width, height) = float_preset_geometry(
+            &self.config.float_presets[preset_index],
+            monitor,
+            client.border_width as i32,
+        );
+        client.x_position = x as i16;
+        client.y_position = y as i16;
+        client.width = width as u16;
+        client.height = height as u16;
+        client.float_preset = Some(preset_index);
+
+        self.connection.configure_window(
+            focused,
+            &ConfigureWindowAux::new()
+                .x(x)
+                .y(y)
+                .width(width as u32)
+                .height(height as u32)
+                .stack_mode(StackMode::ABOVE),
+        )?;
+        self.connection.flush()?;
+        Ok(())
+    }
+
+    fn set_master_factor(&mut self, delta: f32) -> WmResult<()> {
+        if let Some(monitor) = self.monitors.get_mut(self.selected_monitor) {
+            let new_mfact = (monitor.master_factor + delta).clamp(0.05, 0.95);
+            monitor.master_factor = new_mfact;
+            if let Some(ref mut pertag) = monitor.pertag {
+                pertag.master_factors[pertag.current_tag] = new_mfact;
+            }
+            self.apply_layout()?;
+        }
+        Ok(())
+    }
+
+    fn inc_num_master(&mut self, delta: i32) -> WmResult<()> {
+        if let Some(monitor) = self.monitors.get_mut(self.selected_monitor) {
+            let new_nmaster = (monitor.num_master + delta).max(0);
+            monitor.num_master = new_nmaster;
+            if let Some(ref mut pertag) = monitor.pertag {
+                pertag.num_masters[pertag.current_tag] = new_nmaster;
+            }
+            self.apply_layout()?;
+        }
+        Ok(())
+    }
+
+    fn tick_animations(&mut self) -> WmResult<()> {
+        const CLOSE_GRACE_MS: u128 = 1000;
+
+        if self.scroll_animation.is_active()
+            && let Some(new_offset) = self.scroll_animation.update()
+        {
+            if let Some(m) = self.monitors.get_mut(self.selected_monitor) {
+                m.scroll_offset = new_offset;
+            }
+            self.apply_layout()?;
+            self.update_bar()?;
+        }
+
+        if self.fade_animations.is_active() {
+            for (window, opacity, end) in self.fade_animations.update() {
+                if !self.clients.contains_key(&window) {
+                    continue;
+                }
+                self.set_opacity(window, opacity)?;
+                if end == Some(FadeEnd::Close) {
+                    self.kill_client(window)?;
+                    self.closing_windows
+                        .insert(window, std::time::Instant::now());
+                }
+            }
+        }
+
+        // A window that ignored the close request, e.g. to ask about unsaved
+        // changes, must not stay invisible.
+        if !self.closing_windows.is_empty() {
+            let expired: Vec<Window> = self
+                .closing_windows
+                .iter()
+                .filter(|(_, requested)| requested.elapsed().as_millis() >= CLOSE_GRACE_MS)
+                .map(|(&window, _)| window)
+                .collect();
+            for window in expired {
+                self.closing_windows.remove(&window);
+                if self.clients.contains_key(&window) {
+                    self.set_opacity(window, 1.0)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn fade_config(&self) -> Option<AnimationConfig> {
+        (self.config.fade_duration_ms > 0).then(|| AnimationConfig {
+            duration: std::time::Duration::from_millis(self.config.fade_duration_ms),
+            easing: crate::animations::Easing::EaseOut,
+        })
+    }
+
+    /// Sets `_NET_WM_WINDOW_OPACITY` for a compositor to honor; full opacity
+    /// removes the property.
+    fn set_opacity(&self, window: Window, opacity: f64) -> WmResult<()> {
+        if opacity >= 1.0 {
+            self.connection
+                .delete_property(window, self.atoms.net_wm_window_opacity)?;
+        } else {
+            let value = (opacity.max(0.0) * u32::MAX as f64) as u32;
+            self.connection.change_property(
+                PropMode::REPLACE,
+                window,
+                self.atoms.net_wm_window_opacity,
+                AtomEnum::CARDINAL,
+                32,
+                1,
+                &value.to_ne_bytes(),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Makes a window transparent and fades it in, when fading is enabled.
+    fn fade_in(&mut self, window: Window) -> WmResult<()> {
+        let Some(config) = self.fade_config() else {
+            return Ok(());
+        };
+        self.set_opacity(window, 0.0)?;
+        self.fade_animations
+            .start(window, 0.0, 1.0, FadeEnd::Show, &config);
+        Ok(())
+    }
+
+    /// Closes a window, fading it out first when fading is enabled.
+    fn close_client(&mut self, window: Window) -> WmResult<()> {
+        let Some(config) = self.fade_config() else {
+            return self.kill_client(window);
+        };
+        self.fade_animations
+            .start(window, 1.0, 0.0, FadeEnd::Close, &config);
+        Ok(())
+    }
+
+    fn scroll_layout(&mut self, direction: i32) -> WmResult<()> {
+        if self.layout.name() != "scrolling" {
+            return Ok(());
+        }
+
+        let monitor_index = self.selected_monitor;
+        let monitor = match self.monitors.get(monitor_index) {
+            Some(m) => m.clone(),
+            None => return Ok(()),
         };
 
         let visible_count = if monitor.num_master > 0 {
@@ -785,18 +1589,11 @@ impl WindowManager {
             return Ok(());
         }
 
-        let outer_gap = if self.gaps_enabled {
-            self.config.gap_outer_vertical
-        } else {
-            0
-        };
-        let inner_gap = if self.gaps_enabled {
-            self.config.gap_inner_vertical
-        } else {
-            0
-        };
+        let gaps = monitor.gaps.active();
+        let outer_gap = gaps.outer_vertical;
+        let inner_gap = gaps.inner_vertical;
 
-        let available_width = monitor.screen_info.width - 2 * outer_gap as i32;
+        let available_width = monitor.window_area_width - 2 * outer_gap as i32;
         let total_inner_gaps = inner_gap as i32 * (visible_count - 1) as i32;
         let window_width = (available_width - total_inner_gaps) / visible_count as i32;
         let scroll_amount = window_width + inner_gap as i32;
@@ -836,16 +1633,9 @@ impl WindowManager {
             2
         };
 
-        let outer_gap = if self.gaps_enabled {
-            self.config.gap_outer_vertical
-        } else {
-            0
-        };
-        let inner_gap = if self.gaps_enabled {
-            self.config.gap_inner_vertical
-        } else {
-            0
-        };
+        let gaps = monitor.gaps.active();
+        let outer_gap = gaps.outer_vertical;
+        let inner_gap = gaps.inner_vertical;
 
         let mut tiled_windows = Vec::new();
         let mut current = self.next_tiled(monitor.clients_head, &monitor);
@@ -875,7 +1665,7 @@ impl WindowManager {
             return Ok(());
         }
 
-        let available_width = monitor.screen_info.width - 2 * outer_gap as i32;
+        let available_width = monitor.window_area_width - 2 * outer_gap as i32;
         let total_inner_gaps = inner_gap as i32 * (visible_count - 1) as i32;
         let window_width = (available_width - total_inner_gaps) / visible_count as i32;
         let scroll_step = window_width + inner_gap as i32;
@@ -937,18 +1727,11 @@ impl WindowManager {
             }
 
             if tiled_count > 0 {
-                let outer_gap = if self.gaps_enabled {
-                    self.config.gap_outer_vertical
-                } else {
-                    0
-                };
-                let inner_gap = if self.gaps_enabled {
-                    self.config.gap_inner_vertical
-                } else {
-                    0
-                };
+                let gaps = monitor.gaps.active();
+                let outer_gap = gaps.outer_vertical;
+                let inner_gap = gaps.inner_vertical;
 
-                let available_width = monitor.screen_info.width - 2 * outer_gap as i32;
+                let available_width = monitor.window_area_width - 2 * outer_gap as i32;
                 let total_inner_gaps =
                     inner_gap as i32 * (visible_count.min(tiled_count) - 1) as i32;
                 let window_width = if tiled_count <= visible_count {
@@ -990,7 +1773,7 @@ impl WindowManager {
                     return None;
                 }
 
-                let binding = &self.config.keybindings[candidates[0]];
+                let binding = &self.active_keybindings()[candidates[0]];
                 let mut indicator = String::new();
 
                 for (i, key_press) in binding.keys.iter().take(*keys_pressed).enumerate() {
@@ -998,6 +1781,11 @@ impl WindowManager {
                         indicator.push(' ');
                     }
 
+                    if let Some(modkey) = key_press.modkey {
+                        indicator.push_str(Self::format_modifier(modkey));
+                        indicator.push('+');
+                    }
+
                     for modifier in &key_press.modifiers {
                         indicator.push_str(Self::format_modifier(*modifier));
                         indicator.push('+');
@@ -1012,6 +1800,54 @@ impl WindowManager {
         }
     }
 
+    fn get_bar_indicator(&self) -> Option<String> {
+        let mut parts = Vec::new();
+
+        if self.capture_active && !self.config.recording_indicator.is_empty() {
+            parts.push(self.config.recording_indicator.clone());
+        }
+        if self.passthrough {
+            parts.push("PASS".to_string());
+        }
+        if let Some((color, _)) = &self.picked_color {
+            parts.push(color.clone());
+        }
+        if self.presentation_saved.is_some() && !self.config.presentation.indicator.is_empty() {
+            parts.push(self.config.presentation.indicator.clone());
+        }
+        if let Some(mode) = self
+            .active_mode
+            .and_then(|index| self.config.modes.get(index))
+        {
+            parts.push(mode.name.clone());
+        }
+        if let Some(keychord) = self.get_keychord_indicator() {
+            parts.push(keychord);
+        }
+
+        if parts.is_empty() {
+            None
+        } else {
+            Some(parts.join(" "))
+        }
+    }
+
+    fn check_capture_active(&mut self) -> WmResult<()> {
+        if self.config.recording_indicator.is_empty() {
+            return Ok(());
+        }
+
+        let active = crate::capture::is_capture_active(&self.config.recording_processes)
+            || (self.config.recording_grabs
+                && self.click_pick.is_none()
+                && crate::capture::is_input_grabbed(&self.connection, self.root));
+        if active != self.capture_active {
+            self.capture_active = active;
+            self.update_bar()?;
+        }
+        Ok(())
+    }
+
     fn format_modifier(modifier: KeyButMask) -> &'static str {
         match modifier {
             KeyButMask::MOD1 => "Alt",
@@ -1022,1133 +1858,4455 @@ impl WindowManager {
         }
     }
 
-    fn update_bar(&mut self) -> WmResult<()> {
-        let layout_symbol = self.get_layout_symbol();
-        let keychord_indicator = self.get_keychord_indicator();
+    /// Reconfigures outputs after a hotplug or lid change and follows
+    /// the resulting monitor layout.
+    fn handle_output_change(&mut self) -> WmResult<()> {
+        let previous_profile = self.output_state.profile.clone();
+        configure_outputs(
+            &self.connection,
+            self.root,
+            &self.config,
+            self.lid_closed,
+            &mut self.output_state,
+        );
+        let profile_changed = self.output_state.profile != previous_profile;
 
-        for (monitor_index, monitor) in self.monitors.iter().enumerate() {
-            if let Some(bar) = self.bars.get_mut(monitor_index) {
-                let mut occupied_tags: TagMask = 0;
-                let mut urgent_tags: TagMask = 0;
-                for client in self.clients.values() {
-                    if client.monitor_index == monitor_index {
-                        occupied_tags |= client.tags;
-                        if client.is_urgent {
-                            urgent_tags |= client.tags;
-                        }
-                    }
-                }
+        self.update_geometry()?;
 
-                let mut focused_title = None;
-                if let Some(focused_window) = monitor.selected_client
-                    && let Some(focused_client) = self.clients.get(&focused_window)
-                {
-                    focused_title = Some(focused_client.name.clone());
-                };
+        if profile_changed {
+            self.move_profile_tags()?;
+        }
+        Ok(())
+    }
 
-                let draw_blocks = monitor_index == self.selected_monitor;
-                bar.invalidate();
-                bar.draw(
-                    &self.connection,
-                    &self.font,
-                    self.display,
-                    monitor.tagset[monitor.selected_tags_index],
-                    occupied_tags,
-                    urgent_tags,
-                    draw_blocks,
-                    &layout_symbol,
-                    keychord_indicator.as_deref(),
-                    focused_title,
-                )?;
-            }
+    fn check_lid_state(&mut self) -> WmResult<()> {
+        if !self
+            .config
+            .monitor_profiles
+            .iter()
+            .any(|profile| profile.lid_closed.is_some())
+        {
+            return Ok(());
+        }
+
+        let lid_closed = crate::randr::lid_closed();
+        if lid_closed != self.lid_closed {
+            self.lid_closed = lid_closed;
+            self.handle_output_change()?;
         }
         Ok(())
     }
 
-    fn update_tab_bars(&mut self) -> WmResult<()> {
-        for (monitor_index, monitor) in self.monitors.iter().enumerate() {
-            if let Some(tab_bar) = self.tab_bars.get_mut(monitor_index) {
-                let visible_windows: Vec<(Window, String)> = self
-                    .windows
+    /// Moves clients to the monitors the active profile assigns their tags to.
+    fn move_profile_tags(&mut self) -> WmResult<()> {
+        let Some(profile) = self.output_state.profile.as_ref().and_then(|name| {
+            self.config
+                .monitor_profiles
+                .iter()
+                .find(|p| &p.name == name)
+        }) else {
+            return Ok(());
+        };
+
+        let assignments: Vec<(usize, u32)> = profile
+            .tags
+            .iter()
+            .filter_map(|(output, tags)| {
+                self.monitors
                     .iter()
-                    .filter_map(|&window| {
-                        if let Some(client) = self.clients.get(&window) {
-                            if client.monitor_index != monitor_index
-                                || self.floating_windows.contains(&window)
-                                || self.fullscreen_windows.contains(&window)
-                            {
-                                return None;
-                            }
-                            if (client.tags & monitor.tagset[monitor.selected_tags_index]) != 0 {
-                                return Some((window, client.name.clone()));
-                            }
-                        }
-                        None
-                    })
-                    .collect();
+                    .position(|m| &m.name == output)
+                    .map(|index| (index, *tags))
+            })
+            .collect();
+        if assignments.is_empty() {
+            return Ok(());
+        }
 
-                let focused_window = monitor.selected_client;
+        let windows: Vec<Window> = self.windows.clone();
+        for window in windows {
+            let Some(client) = self.clients.get(&window) else {
+                continue;
+            };
+            let Some(&(target, _)) = assignments.iter().find(|(_, tags)| client.tags & tags != 0)
+            else {
+                continue;
+            };
+            if client.monitor_index == target {
+                continue;
+            }
 
-                tab_bar.draw(
-                    &self.connection,
-                    &self.font,
-                    &visible_windows,
-                    focused_window,
-                )?;
+            self.detach(window);
+            self.detach_stack(window);
+            if let Some(client) = self.clients.get_mut(&window) {
+                client.monitor_index = target;
             }
+            self.attach(window, target);
+            self.attach_stack(window, target);
         }
+
+        self.focus(None)?;
+        self.apply_layout()?;
+        self.update_bar()?;
         Ok(())
     }
 
-    fn handle_key_action(&mut self, action: KeyAction, arg: &Arg) -> WmResult<()> {
-        match action {
-            KeyAction::Spawn => handlers::handle_spawn_action(action, arg, self.selected_monitor)?,
-            KeyAction::SpawnTerminal => {
-                crate::signal::spawn_detached(&self.config.terminal);
-            }
-            KeyAction::KillClient => {
-                if let Some(focused) = self
-                    .monitors
-                    .get(self.selected_monitor)
-                    .and_then(|m| m.selected_client)
-                {
-                    self.kill_client(focused)?;
-                }
-            }
-            KeyAction::ToggleFullScreen => {
-                self.fullscreen()?;
-                self.restack()?;
-            }
-            KeyAction::ChangeLayout => {
-                if let Arg::Str(layout_name) = arg {
-                    match layout_from_str(layout_name) {
-                        Ok(layout) => {
-                            self.layout = layout;
-                            if let Some(monitor) = self.monitors.get_mut(self.selected_monitor)
-                                && let Some(ref mut pertag) = monitor.pertag
-                            {
-                                pertag.layouts[pertag.current_tag] = layout_name.to_string();
-                            }
-                            if layout_name != "normie" && layout_name != "floating" {
-                                self.floating_windows.clear();
-                            }
-                            self.apply_layout()?;
-                            self.update_bar()?;
-                            self.restack()?;
-                        }
-                        Err(e) => eprintln!("Failed to change layout: {}", e),
-                    }
-                }
-            }
-            KeyAction::CycleLayout => {
-                let current_name = self.layout.name();
-                let next_name = next_layout(current_name);
-                match layout_from_str(next_name) {
-                    Ok(layout) => {
-                        self.layout = layout;
-                        if let Some(monitor) = self.monitors.get_mut(self.selected_monitor)
-                            && let Some(ref mut pertag) = monitor.pertag
-                        {
-                            pertag.layouts[pertag.current_tag] = next_name.to_string();
-                        }
-                        if next_name != "normie" && next_name != "floating" {
-                            self.floating_windows.clear();
-                        }
-                        self.apply_layout()?;
-                        self.update_bar()?;
-                        self.restack()?;
-                    }
-                    Err(e) => eprintln!("Failed to cycle layout: {}", e),
-                }
-            }
-            KeyAction::ToggleFloating => {
-                self.toggle_floating()?;
-                self.restack()?;
+    /// Re-detects monitors and rebuilds bars when the monitor layout changed.
+    ///
+    /// Monitors keep their tags and clients by index; clients of monitors that
+    /// disappeared move to the first monitor.
+    fn update_geometry(&mut self) -> WmResult<()> {
+        let geometry = self.connection.get_geometry(self.root)?.reply()?;
+        self.screen.width_in_pixels = geometry.width;
+        self.screen.height_in_pixels = geometry.height;
+
+        let detected = detect_monitors(&self.connection, &self.screen, self.root)?;
+        let unchanged = detected.len() == self.monitors.len()
+            && detected.iter().zip(&self.monitors).all(|(new, old)| {
+                new.name == old.name
+                    && new.screen_info.x == old.screen_info.x
+                    && new.screen_info.y == old.screen_info.y
+                    && new.screen_info.width == old.screen_info.width
+                    && new.screen_info.height == old.screen_info.height
+            });
+        if unchanged {
+            return Ok(());
+        }
+
+        let removed: Vec<Window> = self
+            .windows
+            .iter()
+            .copied()
+            .filter(|w| {
+                self.clients
+                    .get(w)
+                    .is_some_and(|c| c.monitor_index >= detected.len())
+            })
+            .collect();
+        for &window in &removed {
+            self.detach(window);
+            self.detach_stack(window);
+        }
+
+        // Clients that disappearing monitors hid to show the desktop or
+        // stashed would be left on no tag. Stashed ones come back on the
+        // tags the monitor was viewing.
+        for monitor in self.monitors.iter_mut().skip(detected.len()) {
+            let viewed = monitor.get_selected_tag();
+            let stashed = std::mem::take(&mut monitor.stash)
+                .into_iter()
+                .map(|window| (window, viewed));
+            for (window, tags) in std::mem::take(&mut monitor.desktop_hidden)
+                .into_iter()
+                .chain(stashed)
+            {
+                if let Some(client) = self.clients.get_mut(&window)
+                    && client.tags == 0
+                {
+                    client.tags = tags;
+                }
             }
+        }
 
-            KeyAction::FocusStack => {
-                if let Arg::Int(direction) = arg {
-                    self.focusstack(*direction)?;
-                    self.restack()?;
-                }
-            }
-            KeyAction::MoveStack => {
-                if let Arg::Int(direction) = arg {
-                    self.move_stack(*direction)?;
-                    self.restack()?;
-                }
-            }
-            KeyAction::Quit | KeyAction::Restart => {}
-            KeyAction::ViewTag => {
-                if let Arg::Int(tag_index) = arg {
-                    self.view_tag(*tag_index as usize)?;
+        let num_tags = self.config.tags.len();
+        let old_count = self.monitors.len();
+        self.monitors.truncate(detected.len());
+        for (index, new) in detected.into_iter().enumerate() {
+            if let Some(monitor) = self.monitors.get_mut(index) {
+                monitor.name = new.name;
+                monitor.screen_info = new.screen_info;
+                monitor.window_area_x = new.window_area_x;
+                monitor.window_area_y = new.window_area_y;
+                monitor.window_area_width = new.window_area_width;
+                monitor.window_area_height = new.window_area_height;
+            } else {
+                let mut monitor = new;
+                monitor.init_pertag(num_tags, "tiling", Gaps::from_config(&self.config));
+                if let Some(monitor_config) = self.config.monitor_config(&monitor.name) {
+                    monitor.apply_startup_config(monitor_config, num_tags);
                 }
+                self.monitors.push(monitor);
             }
-            KeyAction::ViewNextTag => {
-                let monitor = self.get_selected_monitor();
-                let current_tag_index = unmask_tag(monitor.get_selected_tag()) as i32;
-                let len = self.config.tags.len() as i32;
-                self.view_tag((current_tag_index + 1).rem_euclid(len) as usize)?;
-            }
-            KeyAction::ViewPreviousTag => {
-                let monitor = self.get_selected_monitor();
-                let current_tag_index = unmask_tag(monitor.get_selected_tag()) as i32;
-                let len = self.config.tags.len() as i32;
-                self.view_tag((current_tag_index - 1).rem_euclid(len) as usize)?;
-            }
-            KeyAction::ViewNextNonEmptyTag => {
-                let monitor = self.get_selected_monitor();
-                let current = unmask_tag(monitor.get_selected_tag()) as i32;
-                let len = self.config.tags.len() as i32;
-                let mon_num = monitor.monitor_number;
+        }
+        self.update_struts();
 
-                for offset in 1..len {
-                    let next = (current + offset).rem_euclid(len) as usize;
-                    if self.has_windows_on_tag(mon_num, next) {
-                        self.view_tag(next)?;
-                        break;
-                    }
-                }
+        for &window in &removed {
+            if let Some(client) = self.clients.get_mut(&window) {
+                client.monitor_index = 0;
             }
-            KeyAction::ViewPreviousNonEmptyTag => {
-                let monitor = self.get_selected_monitor();
-                let current = unmask_tag(monitor.get_selected_tag()) as i32;
-                let len = self.config.tags.len() as i32;
-                let mon_num = monitor.monitor_number;
+            self.attach(window, 0);
+            self.attach_stack(window, 0);
+        }
 
-                for offset in 1..len {
-                    let prev = (current - offset).rem_euclid(len) as usize;
-                    if self.has_windows_on_tag(mon_num, prev) {
-                        self.view_tag(prev)?;
-                        break;
-                    }
-                }
-            }
-            KeyAction::ToggleView => {
-                if let Arg::Int(tag_index) = arg {
-                    self.toggleview(*tag_index as usize)?;
-                }
-            }
-            KeyAction::MoveToTag => {
-                if let Arg::Int(tag_index) = arg {
-                    self.move_to_tag(*tag_index as usize)?;
-                }
-            }
-            KeyAction::ToggleTag => {
-                if let Arg::Int(tag_index) = arg {
-                    self.toggletag(*tag_index as usize)?;
-                }
-            }
-            KeyAction::ToggleGaps => {
-                self.gaps_enabled = !self.gaps_enabled;
-                self.apply_layout()?;
-                self.restack()?;
-            }
-            KeyAction::FocusMonitor => {
-                if let Arg::Int(direction) = arg {
-                    self.focus_monitor(*direction)?;
-                }
+        if self.selected_monitor >= self.monitors.len() {
+            self.selected_monitor = 0;
+        }
+        eprintln!(
+            "Monitor layout changed: {} -> {} monitors",
+            old_count,
+            self.monitors.len()
+        );
+
+        self.recreate_bars()?;
+        self.update_monitor_labels();
+        self.apply_monitor_configs(false)?;
+        self.focus(None)?;
+        self.apply_layout()?;
+        self.update_bar()?;
+        Ok(())
+    }
+
+    fn recreate_bars(&mut self) -> WmResult<()> {
+        for bar in self.bars.drain(..) {
+            bar.destroy(&self.connection)?;
+        }
+        for bar in self.second_bars.drain(..).flatten() {
+            bar.destroy(&self.connection)?;
+        }
+        for (_, trigger) in self.bar_triggers.drain(..) {
+            trigger.destroy(&self.connection)?;
+        }
+        self.bars_revealed.clear();
+        for tab_bar in self.tab_bars.drain(..) {
+            tab_bar.destroy(&self.connection)?;
+        }
+
+        for monitor in &self.monitors {
+            let bar = Bar::new(
+                &self.connection,
+                &self.screen,
+                self.screen_number,
+                &self.config,
+                self.display,
+                &self.font,
+                &monitor.screen_info,
+                self.normal_cursor,
+            )?;
+
+            let mut tab_bar = crate::tab_bar::TabBar::new(
+                &self.connection,
+                &self.screen,
+                self.screen_number,
+                self.display,
+                &self.font,
+                monitor.screen_info.x as i16,
+                (monitor.screen_info.y + bar.height() as i32) as i16,
+                monitor.screen_info.width as u16,
+                self.config.scheme_occupied,
+                self.config.scheme_selected,
+                self.normal_cursor,
+            )?;
+            tab_bar.set_corner_radius(&self.connection, self.config.bar_corner_radius)?;
+            tab_bar.hide(&self.connection)?;
+
+            self.bars.push(bar);
+            self.tab_bars.push(tab_bar);
+        }
+        Ok(())
+    }
+
+    /// Applies `monitor_configs` to the monitors whose output names match.
+    ///
+    /// Bar position and blocks are reapplied on every call; the viewed tag and
+    /// default layout only at startup so a config reload keeps the session.
+    fn apply_monitor_configs(&mut self, startup: bool) -> WmResult<()> {
+        let num_tags = self.config.tags.len();
+        self.block_manager.clear();
+
+        for monitor_index in 0..self.monitors.len() {
+            let monitor_config = self
+                .config
+                .monitor_config(&self.monitors[monitor_index].name)
+                .cloned();
+            let monitor = &mut self.monitors[monitor_index];
+
+            monitor.top_bar = monitor_config
+                .as_ref()
+                .and_then(|c| c.bar_position)
+                .is_none_or(|position| position == BarPosition::Top);
+            if startup && let Some(ref monitor_config) = monitor_config {
+                monitor.apply_startup_config(monitor_config, num_tags);
             }
-            KeyAction::TagMonitor => {
-                if let Arg::Int(direction) = arg {
-                    self.send_window_to_adjacent_monitor(*direction)?;
+
+            let Some(bar) = self.bars.get_mut(monitor_index) else {
+                continue;
+            };
+            bar.set_position(if monitor.top_bar {
+                BarPosition::Top
+            } else {
+                BarPosition::Bottom
+            });
+            let blocks = monitor_config
+                .as_ref()
+                .and_then(|c| c.blocks.as_ref())
+                .unwrap_or(&self.config.status_blocks);
+            bar.set_blocks(blocks);
+            bar.set_block_set(self.block_manager.acquire(blocks));
+        }
+        self.apply_second_bars()?;
+        self.place_bars()?;
+        self.update_bar_triggers()?;
+
+        if startup
+            && let Some(monitor) = self.monitors.get(self.selected_monitor)
+            && let Some(ref pertag) = monitor.pertag
+            && let Ok(layout) = layout_from_str(&pertag.layouts[pertag.current_tag])
+        {
+            self.layout = layout;
+        }
+
+        Ok(())
+    }
+
+    /// Creates, updates or removes the second bar of each monitor, on the
+    /// edge opposite its main bar.
+    fn apply_second_bars(&mut self) -> WmResult<()> {
+        self.second_bars.resize_with(self.monitors.len(), || None);
+
+        for monitor_index in 0..self.monitors.len() {
+            let monitor = &self.monitors[monitor_index];
+            let monitor_config = self.config.monitor_config(&monitor.name);
+            let enabled = monitor_config
+                .and_then(|c| c.second_bar)
+                .unwrap_or(self.config.second_bar_blocks.is_some());
+            if !enabled {
+                if let Some(bar) = self.second_bars[monitor_index].take() {
+                    bar.destroy(&self.connection)?;
                 }
+                continue;
             }
-            KeyAction::ShowKeybindOverlay => {
-                let monitor = &self.monitors[self.selected_monitor];
-                self.keybind_overlay.toggle(
+
+            let blocks = monitor_config
+                .and_then(|c| c.second_bar_blocks.as_ref())
+                .or(self.config.second_bar_blocks.as_ref())
+                .cloned()
+                .unwrap_or_default();
+            let position = if monitor.top_bar {
+                BarPosition::Bottom
+            } else {
+                BarPosition::Top
+            };
+
+            if self.second_bars[monitor_index].is_none() {
+                let mut bar = Bar::new(
                     &self.connection,
+                    &self.screen,
+                    self.screen_number,
+                    &self.config,
+                    self.display,
                     &self.font,
-                    &self.config.keybindings,
-                    monitor.screen_info.x as i16,
-                    monitor.screen_info.y as i16,
-                    monitor.screen_info.width as u16,
-                    monitor.screen_info.height as u16,
+                    &monitor.screen_info,
+                    self.normal_cursor,
                 )?;
+                bar.set_status_only();
+                self.second_bars[monitor_index] = Some(bar);
             }
-            KeyAction::SetMasterFactor => {
-                if let Arg::Int(delta) = arg {
-                    self.set_master_factor(*delta as f32 / 100.0)?;
-                }
-            }
-            KeyAction::IncNumMaster => {
-                if let Arg::Int(delta) = arg {
-                    self.inc_num_master(*delta)?;
-                }
-            }
-            KeyAction::ScrollLeft => {
-                self.scroll_layout(-1)?;
-            }
-            KeyAction::ScrollRight => {
-                self.scroll_layout(1)?;
+            if let Some(bar) = &mut self.second_bars[monitor_index] {
+                bar.set_position(position);
+                bar.set_blocks(&blocks);
+                bar.set_block_set(self.block_manager.acquire(&blocks));
             }
-            KeyAction::None => {}
         }
         Ok(())
     }
 
-    fn is_window_visible(&self, window: Window) -> bool {
-        if let Some(client) = self.clients.get(&window) {
-            let monitor = self.monitors.get(client.monitor_index);
-            let selected_tags = monitor
-                .map(|m| m.tagset[m.selected_tags_index])
-                .unwrap_or(0);
-            (client.tags & selected_tags) != 0
-        } else {
-            false
+    /// The bar of a monitor and its second bar, if any.
+    fn monitor_bars(&self, monitor_index: usize) -> impl Iterator<Item = &Bar> {
+        self.bars
+            .get(monitor_index)
+            .into_iter()
+            .chain(self.second_bars.get(monitor_index).and_then(Option::as_ref))
+    }
+
+    fn is_bar_window(&self, window: Window) -> bool {
+        self.bars
+            .iter()
+            .chain(self.second_bars.iter().flatten())
+            .any(|bar| bar.window() == window)
+    }
+
+    /// Height the bars of a monitor take from the top and from the bottom
+    /// of its window area. Auto-hidden bars are drawn over the windows.
+    fn bar_space(&self, monitor_index: usize) -> (u32, u32) {
+        if !self.show_bar || self.config.bar_autohide_ms > 0 {
+            return (0, 0);
+        }
+        let mut space = (0, 0);
+        for bar in self.monitor_bars(monitor_index) {
+            match bar.position() {
+                BarPosition::Top => space.0 += bar.height() as u32,
+                BarPosition::Bottom => space.1 += bar.height() as u32,
+            }
         }
+        space
     }
 
-    fn visible_windows(&self) -> Vec<Window> {
-        let mut result = Vec::new();
-        for monitor in &self.monitors {
-            let mut current = monitor.clients_head;
-            while let Some(window) = current {
-                if let Some(client) = self.clients.get(&window) {
-                    let visible_tags = client.tags & monitor.tagset[monitor.selected_tags_index];
-                    if visible_tags != 0 {
-                        result.push(window);
-                    }
-                    current = client.next;
-                } else {
-                    break;
-                }
+    /// Puts a strip along the screen edge of every bar while auto-hide is
+    /// on, and removes them when it is off.
+    fn update_bar_triggers(&mut self) -> WmResult<()> {
+        for (_, trigger) in self.bar_triggers.drain(..) {
+            trigger.destroy(&self.connection)?;
+        }
+        if self.config.bar_autohide_ms == 0 {
+            self.bars_revealed.clear();
+            return Ok(());
+        }
+
+        let mut triggers = Vec::new();
+        for (monitor_index, monitor) in self.monitors.iter().enumerate() {
+            let screen = &monitor.screen_info;
+            for bar in self.monitor_bars(monitor_index) {
+                let (y, bottom) = match bar.position() {
+                    BarPosition::Top => (screen.y, false),
+                    BarPosition::Bottom => (screen.y + screen.height, true),
+                };
+                let trigger = EdgeTrigger::new(
+                    &self.connection,
+                    self.root,
+                    screen.x as i16,
+                    y as i16,
+                    screen.width as u16,
+                    bottom,
+                )?;
+                triggers.push((monitor_index, trigger));
             }
         }
-        result
+        self.bar_triggers = triggers;
+        Ok(())
     }
 
-    fn visible_windows_on_monitor(&self, monitor_index: usize) -> Vec<Window> {
-        let mut result = Vec::new();
-        if let Some(monitor) = self.monitors.get(monitor_index) {
-            let mut current = monitor.clients_head;
-            while let Some(window) = current {
-                if let Some(client) = self.clients.get(&window) {
-                    let visible_tags = client.tags & monitor.tagset[monitor.selected_tags_index];
-                    if visible_tags != 0 {
-                        result.push(window);
+    /// Whether the bars of a monitor are auto-hidden right now.
+    fn bars_autohidden(&self, monitor_index: usize) -> bool {
+        self.config.bar_autohide_ms > 0 && !self.bars_revealed.contains_key(&monitor_index)
+    }
+
+    /// Shows the auto-hidden bars of a monitor above the windows, until
+    /// the auto-hide delay passes again.
+    fn reveal_bars(&mut self, monitor_index: usize) -> WmResult<()> {
+        let hide_at = std::time::Instant::now()
+            + std::time::Duration::from_millis(self.config.bar_autohide_ms);
+        let was_hidden = self.bars_revealed.insert(monitor_index, hide_at).is_none();
+        let shown = self
+            .monitors
+            .get(monitor_index)
+            .is_some_and(|monitor| monitor.show_bar);
+        if !was_hidden || !shown || self.has_visible_fullscreen(monitor_index) {
+            return Ok(());
+        }
+        for bar in self.monitor_bars(monitor_index) {
+            self.backend().map_window(bar.window())?;
+            self.backend().raise_window(bar.window())?;
+        }
+        self.update_bar()
+    }
+
+    /// Hides revealed bars whose delay passed, unless the pointer is on
+    /// them or they need attention: a pending keychord or an urgent tag.
+    fn tick_bar_autohide(&mut self) -> WmResult<()> {
+        if self.config.bar_autohide_ms == 0 {
+            return Ok(());
+        }
+
+        let keychord_pending =
+            !matches!(self.keychord_state, keyboard::handlers::KeychordState::Idle)
+                || self.active_mode.is_some();
+        for monitor_index in 0..self.monitors.len() {
+            let (_, urgent_tags) = tag_occupancy(&self.clients, monitor_index);
+            let attention =
+                urgent_tags != 0 || (keychord_pending && monitor_index == self.selected_monitor);
+            if attention {
+                self.reveal_bars(monitor_index)?;
+                continue;
+            }
+
+            let Some(&hide_at) = self.bars_revealed.get(&monitor_index) else {
+                continue;
+            };
+            if std::time::Instant::now() < hide_at {
+                continue;
+            }
+            if self.pointer_on_bars(monitor_index)? {
+                self.reveal_bars(monitor_index)?;
+                continue;
+            }
+
+            self.bars_revealed.remove(&monitor_index);
+            for bar in self.monitor_bars(monitor_index) {
+                self.backend().unmap_window(bar.window())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn pointer_on_bars(&self, monitor_index: usize) -> WmResult<bool> {
+        let pointer = self.connection.query_pointer(self.root)?.reply()?;
+        let Some(monitor) = self.monitors.get(monitor_index) else {
+            return Ok(false);
+        };
+        let screen = &monitor.screen_info;
+        let (x, y) = (pointer.root_x as i32, pointer.root_y as i32);
+        if x < screen.x || x >= screen.x + screen.width {
+            return Ok(false);
+        }
+        Ok(self.monitor_bars(monitor_index).any(|bar| {
+            let top = match bar.position() {
+                BarPosition::Top => monitor.window_area_y,
+                BarPosition::Bottom => {
+                    monitor.window_area_y + monitor.window_area_height - bar.height() as i32
+                }
+            };
+            y >= top && y < top + bar.height() as i32
+        }))
+    }
+
+    /// Whether a fullscreen window is shown on a monitor, hiding its bars.
+    fn has_visible_fullscreen(&self, monitor_index: usize) -> bool {
+        let Some(monitor) = self.monitors.get(monitor_index) else {
+            return false;
+        };
+        let tags = monitor.tagset[monitor.selected_tags_index];
+        self.fullscreen_windows.iter().any(|&w| {
+            self.clients
+                .get(&w)
+                .is_some_and(|c| c.monitor_index == monitor_index && (c.tags & tags) != 0)
+        })
+    }
+
+    /// Keeps the edge strips, and bars shown over the windows, on top.
+    fn raise_autohide_windows(&self) -> WmResult<()> {
+        for (_, trigger) in &self.bar_triggers {
+            trigger.raise(&self.connection)?;
+        }
+        for &monitor_index in self.bars_revealed.keys() {
+            for bar in self.monitor_bars(monitor_index) {
+                self.backend().raise_window(bar.window())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves each bar to the top or bottom of its monitor's window area, so
+    /// it sits next to any dock on the same edge.
+    fn place_bars(&self) -> WmResult<()> {
+        for (monitor_index, monitor) in self.monitors.iter().enumerate() {
+            for bar in self.monitor_bars(monitor_index) {
+                let bar_y = match bar.position() {
+                    BarPosition::Top => monitor.window_area_y,
+                    BarPosition::Bottom => {
+                        monitor.window_area_y + monitor.window_area_height - bar.height() as i32
                     }
-                    current = client.next;
-                } else {
-                    break;
+                };
+                bar.move_to(&self.connection, monitor.screen_info.x as i16, bar_y as i16)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets the monitor label of every bar; labels are only shown when more
+    /// than one monitor is connected.
+    fn update_monitor_labels(&mut self) {
+        let show = self.monitors.len() > 1;
+        for (monitor_index, monitor) in self.monitors.iter().enumerate() {
+            let Some(bar) = self.bars.get_mut(monitor_index) else {
+                continue;
+            };
+            let label = match self.config.bar_monitor_label {
+                _ if !show => None,
+                MonitorLabel::None => None,
+                MonitorLabel::Name if !monitor.name.is_empty() => Some(monitor.name.clone()),
+                MonitorLabel::Name | MonitorLabel::Number => Some((monitor_index + 1).to_string()),
+            };
+            bar.set_monitor_label(label);
+        }
+    }
+
+    fn update_bar(&mut self) -> WmResult<()> {
+        self.collect_dynamic_tags();
+        self.update_client_desktops();
+
+        let layout_symbol = self.get_layout_symbol();
+        let indicator = self.get_bar_indicator();
+        let is_tabbed = self.layout.name() == LayoutType::Tabbed.as_str();
+
+        let presenting = self.presentation_saved.is_some();
+        if self.block_manager.has_context_blocks() {
+            let context = self.focus_context();
+            self.block_manager.set_focus_context(&context);
+        }
+        for (monitor_index, monitor) in self.monitors.iter().enumerate() {
+            let tabs = if is_tabbed {
+                self.tab_windows(monitor_index)
+            } else {
+                Vec::new()
+            };
+            if let Some(bar) = self.bars.get_mut(monitor_index) {
+                bar.set_tags(&self.config.tags, &self.config.tag_styles, &self.font);
+                bar.set_hide_sensitive(presenting);
+                bar.set_focused(monitor_index == self.selected_monitor);
+                bar.set_tag_note(
+                    self.tag_notes
+                        .first_in(monitor.tagset[monitor.selected_tags_index])
+                        .map(str::to_string),
+                );
+                let (occupied_tags, urgent_tags) = tag_occupancy(&self.clients, monitor_index);
+                bar.set_window_count(self.config.bar_window_count.as_ref().map(|format| {
+                    let (visible, total) = window_counts(
+                        &self.clients,
+                        monitor_index,
+                        monitor.tagset[monitor.selected_tags_index],
+                    );
+                    format
+                        .replace("{total}", &total.to_string())
+                        .replace("{}", &visible.to_string())
+                }));
+
+                let mut focused_title = None;
+                let mut title_class = None;
+                if let Some(focused_window) = monitor.selected_client
+                    && let Some(focused_client) = self.clients.get(&focused_window)
+                {
+                    focused_title = Some(focused_client.name.clone());
+                    // Where the focused tab is, since the tab bar may be
+                    // out of sight.
+                    if let Some(position) = tabs.iter().position(|&w| w == focused_window) {
+                        focused_title = Some(format!(
+                            "{} ({}/{})",
+                            focused_client.name,
+                            position + 1,
+                            tabs.len()
+                        ));
+                    }
+                    title_class = match self.config.bar_title_class {
+                        TitleClass::None => None,
+                        TitleClass::Class => Some(focused_client.class.clone()),
+                        TitleClass::Instance => Some(focused_client.instance.clone()),
+                    }
+                    .filter(|class| !class.is_empty());
+                };
+                bar.set_title_class(title_class);
+
+                let has_own_blocks = self
+                    .config
+                    .monitor_config(&monitor.name)
+                    .is_some_and(|c| c.blocks.is_some());
+                let draw_blocks = monitor_index == self.selected_monitor || has_own_blocks;
+                bar.invalidate();
+                bar.draw(
+                    &self.connection,
+                    &self.font,
+                    monitor.tagset[monitor.selected_tags_index],
+                    occupied_tags,
+                    urgent_tags,
+                    draw_blocks,
+                    &layout_symbol,
+                    indicator.as_deref(),
+                    focused_title,
+                )?;
+            }
+            if let Some(bar) = self
+                .second_bars
+                .get_mut(monitor_index)
+                .and_then(Option::as_mut)
+            {
+                bar.set_hide_sensitive(presenting);
+                bar.set_focused(monitor_index == self.selected_monitor);
+                bar.invalidate();
+                bar.draw(
+                    &self.connection,
+                    &self.font,
+                    0,
+                    0,
+                    0,
+                    true,
+                    &layout_symbol,
+                    None,
+                    None,
+                )?;
+            }
+        }
+
+        self.write_status_file(&layout_symbol);
+        Ok(())
+    }
+
+    /// Appends a tag, named after its position unless a name is given.
+    /// Returns None once every tag bit is in use.
+    fn create_tag(&mut self, name: Option<String>) -> Option<usize> {
+        if self.config.tags.len() >= MAX_TAGS {
+            return None;
+        }
+
+        let tag_index = self.config.tags.len();
+        self.config
+            .tags
+            .push(name.unwrap_or_else(|| (tag_index + 1).to_string()));
+        for monitor in &mut self.monitors {
+            if let Some(pertag) = &mut monitor.pertag {
+                pertag.push_tag();
+            }
+        }
+        self.update_desktop_hints();
+        Some(tag_index)
+    }
+
+    /// Removes runtime tags that hold no windows and are not viewed on any
+    /// monitor.
+    fn collect_dynamic_tags(&mut self) {
+        let mut removed = false;
+        for tag_index in (self.static_tag_count..self.config.tags.len()).rev() {
+            let mask = tag_mask(tag_index);
+            let in_use = self.clients.values().any(|c| c.tags & mask != 0)
+                || self.monitors.iter().any(|m| {
+                    m.tagset[m.selected_tags_index] & mask != 0
+                        || m.desktop_hidden.iter().any(|&(_, tags)| tags & mask != 0)
+                });
+            if !in_use {
+                self.remove_tag(tag_index);
+                removed = true;
+            }
+        }
+        if removed {
+            self.update_desktop_hints();
+        }
+    }
+
+    /// Removes a tag, shifting the tags after it down by one.
+    fn remove_tag(&mut self, tag_index: usize) {
+        let below = tag_mask(tag_index) - 1;
+        let shift = |mask: TagMask| {
+            let above = mask.checked_shr(tag_index as u32 + 1).unwrap_or(0) << tag_index;
+            (mask & below) | above
+        };
+
+        for client in self.clients.values_mut() {
+            client.tags = shift(client.tags);
+        }
+        for monitor in &mut self.monitors {
+            for tags in &mut monitor.tagset {
+                *tags = shift(*tags);
+                if *tags == 0 {
+                    *tags = tag_mask(0);
                 }
             }
+            for (_, tags) in &mut monitor.desktop_hidden {
+                *tags = shift(*tags);
+            }
+            if let Some(pertag) = &mut monitor.pertag {
+                pertag.remove_tag(tag_index);
+            }
+        }
+        self.config.tags.remove(tag_index);
+        if tag_index < self.config.tag_styles.len() {
+            self.config.tag_styles.remove(tag_index);
         }
-        result
     }
 
-    fn get_monitor_at_point(&self, x: i32, y: i32) -> Option<usize> {
-        self.monitors
+    /// Inserts `count` tags named after their position at `tag_index`,
+    /// shifting the tags from there up. Tags pushed past the last tag bit
+    /// are dropped, and windows left on no tag move to the first one.
+    fn insert_tags(&mut self, tag_index: usize, count: usize) {
+        let below = tag_mask(tag_index) - 1;
+        let shift = |mask: TagMask| {
+            let above = (mask & !below).checked_shl(count as u32).unwrap_or(0);
+            (mask & below) | above
+        };
+        // Windows hidden on purpose have no tags and keep it that way.
+        let shift_window = |mask: TagMask| match shift(mask) {
+            0 if mask != 0 => tag_mask(0),
+            shifted => shifted,
+        };
+
+        for client in self.clients.values_mut() {
+            client.tags = shift_window(client.tags);
+        }
+        let dropped = (self.config.tags.len() + count).saturating_sub(MAX_TAGS);
+        for monitor in &mut self.monitors {
+            for tags in &mut monitor.tagset {
+                *tags = shift(*tags);
+                if *tags == 0 {
+                    *tags = tag_mask(0);
+                }
+            }
+            for (_, tags) in &mut monitor.desktop_hidden {
+                *tags = shift_window(*tags);
+            }
+            if let Some(pertag) = &mut monitor.pertag {
+                for _ in 0..count {
+                    pertag.insert_tag(tag_index);
+                }
+                for _ in 0..dropped {
+                    pertag.remove_tag(pertag.layouts.len() - 2);
+                }
+            }
+        }
+        for index in tag_index..tag_index + count {
+            self.config.tags.insert(index, (index + 1).to_string());
+        }
+        self.config.tags.truncate(MAX_TAGS);
+        if tag_index < self.config.tag_styles.len() {
+            let styles = std::iter::repeat_n(Default::default(), count);
+            self.config.tag_styles.splice(tag_index..tag_index, styles);
+        }
+    }
+
+    /// Sets `_NET_WM_DESKTOP` on clients whose tags changed: the first of
+    /// their tags, or 0xFFFFFFFF on every tag. Hidden clients keep theirs.
+    fn update_client_desktops(&mut self) {
+        let all_tags = all_tags_mask(self.config.tags.len());
+        self.client_desktops
+            .retain(|window, _| self.clients.contains_key(window));
+
+        for (&window, client) in &self.clients {
+            if client.tags == 0 {
+                continue;
+            }
+            let desktop = if client.tags & all_tags == all_tags {
+                u32::MAX
+            } else {
+                client.tags.trailing_zeros()
+            };
+            if self.client_desktops.get(&window) == Some(&desktop) {
+                continue;
+            }
+
+            let result = self.connection.change_property(
+                PropMode::REPLACE,
+                window,
+                self.atoms.net_wm_desktop,
+                AtomEnum::CARDINAL,
+                32,
+                1,
+                &desktop.to_ne_bytes(),
+            );
+            match result {
+                Ok(_) => {
+                    self.client_desktops.insert(window, desktop);
+                }
+                Err(e) => eprintln!("Failed to set _NET_WM_DESKTOP: {:?}", e),
+            }
+        }
+    }
+
+    /// Publishes the number and names of tags as EWMH desktops.
+    fn update_desktop_hints(&self) {
+        let count = (self.config.tags.len() as u32).to_ne_bytes();
+        let mut names = Vec::new();
+        for tag in &self.config.tags {
+            names.extend_from_slice(tag.as_bytes());
+            names.push(0);
+        }
+
+        let result = self
+            .connection
+            .change_property(
+                PropMode::REPLACE,
+                self.root,
+                self.atoms.net_number_of_desktops,
+                AtomEnum::CARDINAL,
+                32,
+                1,
+                &count,
+            )
+            .and_then(|_| {
+                self.connection.change_property(
+                    PropMode::REPLACE,
+                    self.root,
+                    self.atoms.net_desktop_names,
+                    self.atoms.utf8_string,
+                    8,
+                    names.len() as u32,
+                    &names,
+                )
+            });
+        if let Err(e) = result {
+            eprintln!("Failed to update desktop hints: {:?}", e);
+        }
+    }
+
+    /// The focused window as seen by context blocks.
+    fn focus_context(&self) -> FocusContext {
+        let Some(client) = self
+            .monitors
+            .get(self.selected_monitor)
+            .and_then(|m| m.selected_client)
+            .and_then(|window| self.clients.get(&window))
+        else {
+            return FocusContext::default();
+        };
+
+        FocusContext {
+            class: client.class.clone(),
+            instance: client.instance.clone(),
+            title: client.name.clone(),
+            pid: self.get_window_pid(client.window),
+        }
+    }
+
+    /// Mirrors tags, layout and focused title of every monitor into the
+    /// configured status file, one line per monitor.
+    fn write_status_file(&mut self, layout_symbol: &str) {
+        let Some(path) = self.config.status_file.as_deref() else {
+            self.status_writer = None;
+            return;
+        };
+        let path = crate::signal::expand_home(path);
+
+        let mut contents = String::new();
+        for (monitor_index, monitor) in self.monitors.iter().enumerate() {
+            let (occupied_tags, urgent_tags) = tag_occupancy(&self.clients, monitor_index);
+
+            let title = monitor
+                .selected_client
+                .and_then(|window| self.clients.get(&window))
+                .map(|client| client.name.as_str())
+                .unwrap_or_default();
+
+            let status = MonitorStatus {
+                index: monitor_index,
+                focused: monitor_index == self.selected_monitor,
+                tags: &self.config.tags,
+                selected_tags: monitor.tagset[monitor.selected_tags_index],
+                occupied_tags,
+                urgent_tags,
+                layout_symbol,
+                title,
+            };
+            contents.push_str(&status.format());
+            contents.push('\n');
+        }
+
+        let writer = match &mut self.status_writer {
+            Some(writer) if writer.path() == path => writer,
+            slot => slot.insert(StatusWriter::new(path)),
+        };
+        if let Err(e) = writer.write(contents) {
+            eprintln!("Failed to write status file: {:?}", e);
+        }
+    }
+
+    fn handle_ipc(&mut self) -> WmResult<()> {
+        let Some(server) = self.ipc.as_mut() else {
+            return Ok(());
+        };
+
+        for request in server.poll() {
+            let response = match request.body {
+                Ok(body) => match self.ipc_command(&body)? {
+                    Ok(result) => ipc::success(result),
+                    Err(e) => ipc::failure(&e),
+                },
+                Err(e) => ipc::failure(&format!("invalid request: {}", e)),
+            };
+            if let Some(server) = self.ipc.as_mut() {
+                server.respond(request.client, &response);
+            }
+        }
+        Ok(())
+    }
+
+    /// Answers D-Bus method calls and announces focus and tag changes.
+    fn handle_dbus(&mut self) -> WmResult<()> {
+        let Some(service) = self.dbus.as_ref() else {
+            return Ok(());
+        };
+
+        for request in service.poll() {
+            let response = match &request.body {
+                Ok(body) => match self.ipc_command(body)? {
+                    Ok(result) => ipc::success(result),
+                    Err(e) => ipc::failure(&e),
+                },
+                Err(e) => ipc::failure(&format!("invalid request: {}", e)),
+            };
+            request.respond(response);
+        }
+
+        let Some(service) = self.dbus.as_ref() else {
+            return Ok(());
+        };
+        let focused = self
+            .monitors
+            .get(self.selected_monitor)
+            .and_then(|m| m.selected_client);
+        if focused != self.dbus_announced.0 {
+            let title = focused
+                .and_then(|window| self.clients.get(&window))
+                .map(|client| client.name.as_str())
+                .unwrap_or_default();
+            service.emit_focus_changed(focused.unwrap_or(0), title);
+            self.dbus_announced.0 = focused;
+        }
+
+        let tags: Vec<TagMask> = self
+            .monitors
             .iter()
-            .position(|mon| mon.contains_point(x, y))
+            .map(|m| m.tagset[m.selected_tags_index])
+            .collect();
+        for (monitor_index, &mask) in tags.iter().enumerate() {
+            if self.dbus_announced.1.get(monitor_index) != Some(&mask) {
+                service.emit_tags_changed(monitor_index as u32, mask);
+            }
+        }
+        self.dbus_announced.1 = tags;
+        Ok(())
+    }
+
+    fn ipc_command(&mut self, request: &Json) -> WmResult<Result<Json, String>> {
+        let Some(command) = request.get("command").and_then(Json::as_str) else {
+            return Ok(Err("missing command".to_string()));
+        };
+
+        match command {
+            "get_tree" => Ok(Ok(self.tree())),
+            "create_tag" => {
+                let name = request
+                    .get("name")
+                    .or_else(|| request.get("args")?.as_array()?.first())
+                    .and_then(Json::as_str)
+                    .map(str::to_string);
+                // Viewed right away, or it would be collected as empty.
+                match self.create_tag(name) {
+                    Some(tag_index) => {
+                        self.view_tag(tag_index)?;
+                        Ok(Ok(Json::from(tag_index)))
+                    }
+                    None => Ok(Err(format!("at most {} tags are supported", MAX_TAGS))),
+                }
+            }
+            "view_tag" => {
+                let tag_index = request
+                    .get("tag")
+                    .or_else(|| request.get("args")?.as_array()?.first())
+                    .and_then(|tag| tag.as_i64().or_else(|| tag.as_str()?.parse().ok()));
+                match tag_index {
+                    Some(tag_index) if (0..self.config.tags.len() as i64).contains(&tag_index) => {
+                        self.view_tag(tag_index as usize)?;
+                        Ok(Ok(Json::Null))
+                    }
+                    _ => Ok(Err("view_tag needs a valid tag index".to_string())),
+                }
+            }
+            "set_layout" => {
+                let name = request
+                    .get("layout")
+                    .or_else(|| request.get("args")?.as_array()?.first())
+                    .and_then(Json::as_str)
+                    .unwrap_or_default()
+                    .to_string();
+                if let Err(e) = layout_from_str(&name) {
+                    return Ok(Err(e));
+                }
+                self.handle_key_action(KeyAction::ChangeLayout, &Arg::Str(name))?;
+                Ok(Ok(Json::Null))
+            }
+            "focus_window" => {
+                let window = request
+                    .get("window")
+                    .or_else(|| request.get("args")?.as_array()?.first())
+                    .and_then(|window| window.as_i64().or_else(|| window.as_str()?.parse().ok()))
+                    .and_then(|window| Window::try_from(window).ok());
+                match window {
+                    Some(window) => self.reveal_window(window),
+                    None => Ok(Err("focus_window needs a window id".to_string())),
+                }
+            }
+            "set_tag_note" => {
+                let args = request.get("args").and_then(Json::as_array);
+                let tag_index = request
+                    .get("tag")
+                    .or_else(|| args?.first())
+                    .and_then(|tag| tag.as_i64().or_else(|| tag.as_str()?.parse().ok()));
+                // The bare form splits the note into words.
+                let note = match request.get("note") {
+                    Some(note) => note.as_str().unwrap_or_default().to_string(),
+                    None => args
+                        .map(|args| {
+                            args.iter()
+                                .skip(1)
+                                .filter_map(Json::as_str)
+                                .collect::<Vec<_>>()
+                                .join(" ")
+                        })
+                        .unwrap_or_default(),
+                };
+                match tag_index {
+                    Some(tag_index) if (0..self.config.tags.len() as i64).contains(&tag_index) => {
+                        if let Err(e) = self.tag_notes.set(tag_index as usize, &note) {
+                            eprintln!("Failed to save tag notes: {}", e);
+                        }
+                        self.update_bar()?;
+                        Ok(Ok(Json::Null))
+                    }
+                    _ => Ok(Err("set_tag_note needs a valid tag index".to_string())),
+                }
+            }
+            "reload" => Ok(self.reload()?.map(|()| Json::Null)),
+            _ => Ok(Err(format!("unknown command '{}'", command))),
+        }
+    }
+
+    /// Focuses a client, first viewing one of its tags on its monitor if it
+    /// isn't visible.
+    fn reveal_window(&mut self, window: Window) -> WmResult<Result<Json, String>> {
+        let Some(client) = self.clients.get(&window) else {
+            return Ok(Err(format!("no client with id {}", window)));
+        };
+        let (monitor_index, tags) = (client.monitor_index, client.tags);
+        if tags == 0 {
+            return Ok(Err("the window is hidden".to_string()));
+        }
+
+        if monitor_index != self.selected_monitor {
+            if let Some(focused) = self
+                .monitors
+                .get(self.selected_monitor)
+                .and_then(|m| m.selected_client)
+            {
+                self.unfocus(focused, true)?;
+            }
+            self.selected_monitor = monitor_index;
+        }
+        if !self.is_visible(window) {
+            self.view_tag(tags.trailing_zeros() as usize)?;
+        }
+        self.focus(Some(window))?;
+        self.restack()?;
+        self.update_bar()?;
+        Ok(Ok(Json::Null))
+    }
+
+    /// The monitor → tag → client hierarchy. Clients on several tags are
+    /// listed under each of them; `version` is bumped on incompatible
+    /// changes to the schema.
+    fn tree(&self) -> Json {
+        let focused_window = self
+            .monitors
+            .get(self.selected_monitor)
+            .and_then(|m| m.selected_client);
+
+        let monitors: Vec<Json> = self
+            .monitors
+            .iter()
+            .enumerate()
+            .map(|(monitor_index, monitor)| {
+                let selected_tags = monitor.tagset[monitor.selected_tags_index];
+                let (occupied_tags, urgent_tags) = tag_occupancy(&self.clients, monitor_index);
+                let layout = monitor
+                    .pertag
+                    .as_ref()
+                    .map(|pertag| pertag.layouts[pertag.current_tag].clone())
+                    .unwrap_or_else(|| self.layout.name().to_string());
+
+                let mut clients = Vec::new();
+                let mut current = monitor.clients_head;
+                while let Some(window) = current {
+                    let Some(client) = self.clients.get(&window) else {
+                        break;
+                    };
+                    clients.push(client);
+                    current = client.next;
+                }
+
+                let tags: Vec<Json> = self
+                    .config
+                    .tags
+                    .iter()
+                    .enumerate()
+                    .map(|(tag_index, name)| {
+                        let mask = tag_mask(tag_index);
+                        let tag_clients: Vec<Json> = clients
+                            .iter()
+                            .filter(|client| client.tags & mask != 0)
+                            .map(|client| self.client_tree(client, focused_window))
+                            .collect();
+                        Json::object()
+                            .with("index", tag_index)
+                            .with("name", name.as_str())
+                            .with("dynamic", tag_index >= self.static_tag_count)
+                            .with("selected", selected_tags & mask != 0)
+                            .with("occupied", occupied_tags & mask != 0)
+                            .with("urgent", urgent_tags & mask != 0)
+                            .with("note", self.tag_notes.get(tag_index))
+                            .with("clients", tag_clients)
+                    })
+                    .collect();
+
+                let info = &monitor.screen_info;
+                Json::object()
+                    .with("index", monitor_index)
+                    .with("name", monitor.name.as_str())
+                    .with("focused", monitor_index == self.selected_monitor)
+                    .with(
+                        "geometry",
+                        geometry(info.x, info.y, info.width, info.height),
+                    )
+                    .with(
+                        "workarea",
+                        geometry(
+                            monitor.window_area_x,
+                            monitor.window_area_y,
+                            monitor.window_area_width,
+                            monitor.window_area_height,
+                        ),
+                    )
+                    .with("layout", layout)
+                    .with("master_factor", monitor.master_factor)
+                    .with("num_master", monitor.num_master)
+                    .with("tags", tags)
+            })
+            .collect();
+
+        Json::object()
+            .with("version", 1)
+            .with("focused_monitor", self.selected_monitor)
+            .with("monitors", monitors)
+    }
+
+    fn client_tree(&self, client: &Client, focused_window: Option<Window>) -> Json {
+        Json::object()
+            .with("id", client.window)
+            .with("title", client.name.as_str())
+            .with("class", client.class.as_str())
+            .with("instance", client.instance.as_str())
+            .with(
+                "geometry",
+                geometry(
+                    client.x_position as i32,
+                    client.y_position as i32,
+                    client.width as i32,
+                    client.height as i32,
+                ),
+            )
+            .with("border_width", client.border_width)
+            .with("floating", client.is_floating)
+            .with("fullscreen", client.is_fullscreen)
+            .with("below", client.is_below)
+            .with("urgent", client.is_urgent)
+            .with("focused", focused_window == Some(client.window))
+            .with("tags", client.tags)
+            .with("pid", self.get_window_pid(client.window))
+    }
+
+    /// Windows with a tab on a monitor's tab bar, in tab order: the tiled
+    /// clients on its viewed tags.
+    fn tab_windows(&self, monitor_index: usize) -> Vec<Window> {
+        let Some(monitor) = self.monitors.get(monitor_index) else {
+            return Vec::new();
+        };
+        self.windows
+            .iter()
+            .copied()
+            .filter(|window| {
+                self.clients.get(window).is_some_and(|client| {
+                    client.monitor_index == monitor_index
+                        && !self.floating_windows.contains(window)
+                        && !self.fullscreen_windows.contains(window)
+                        && (client.tags & monitor.tagset[monitor.selected_tags_index]) != 0
+                })
+            })
+            .collect()
+    }
+
+    fn update_tab_bars(&mut self) -> WmResult<()> {
+        for (monitor_index, monitor) in self.monitors.iter().enumerate() {
+            let visible_windows: Vec<(Window, String)> = self
+                .tab_windows(monitor_index)
+                .into_iter()
+                .filter_map(|window| Some((window, self.clients.get(&window)?.name.clone())))
+                .collect();
+            if let Some(tab_bar) = self.tab_bars.get_mut(monitor_index) {
+                let focused_window = monitor.selected_client;
+
+                let active_windows: Vec<Window> = match self.config.activity_color {
+                    Some(_) => visible_windows
+                        .iter()
+                        .map(|&(window, _)| window)
+                        .filter(|window| self.clients.get(window).is_some_and(|c| c.has_activity))
+                        .collect(),
+                    None => Vec::new(),
+                };
+
+                tab_bar.draw(
+                    &self.connection,
+                    &self.font,
+                    &visible_windows,
+                    focused_window,
+                    &active_windows,
+                    self.config.activity_color.unwrap_or(0),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_key_action(&mut self, action: KeyAction, arg: &Arg) -> WmResult<()> {
+        match action {
+            KeyAction::Spawn => handlers::handle_spawn_action(action, arg, self.selected_monitor)?,
+            KeyAction::SpawnTerminal => {
+                crate::signal::spawn_detached(&self.config.terminal);
+            }
+            KeyAction::SpawnTerminalInCwd => {
+                let cwd = self
+                    .monitors
+                    .get(self.selected_monitor)
+                    .and_then(|m| m.selected_client)
+                    .and_then(|window| self.get_window_pid(window))
+                    .and_then(crate::signal::process_cwd);
+                let options = crate::signal::SpawnOptions {
+                    cwd,
+                    ..Default::default()
+                };
+                crate::signal::spawn_detached_with_options(&self.config.terminal, &options);
+            }
+            KeyAction::KillClient => {
+                if let Some(focused) = self
+                    .monitors
+                    .get(self.selected_monitor)
+                    .and_then(|m| m.selected_client)
+                {
+                    self.close_client(focused)?;
+                }
+            }
+            KeyAction::ToggleFullScreen => {
+                self.fullscreen()?;
+                self.restack()?;
+            }
+            KeyAction::ChangeLayout => {
+                if let Arg::Str(layout_name) = arg {
+                    match layout_from_str(layout_name) {
+                        Ok(layout) => {
+                            self.layout = layout;
+                            if let Some(monitor) = self.monitors.get_mut(self.selected_monitor)
+                                && let Some(ref mut pertag) = monitor.pertag
+                            {
+                                pertag.layouts[pertag.current_tag] = layout_name.to_string();
+                            }
+                            if layout_name != "normie" && layout_name != "floating" {
+                                self.floating_windows.clear();
+                            }
+                            self.apply_layout()?;
+                            self.update_bar()?;
+                            self.restack()?;
+                        }
+                        Err(e) => eprintln!("Failed to change layout: {}", e),
+                    }
+                }
+            }
+            KeyAction::CycleLayout => {
+                let current_name = self.layout.name();
+                let next_name = next_layout(current_name);
+                match layout_from_str(next_name) {
+                    Ok(layout) => {
+                        self.layout = layout;
+                        if let Some(monitor) = self.monitors.get_mut(self.selected_monitor)
+                            && let Some(ref mut pertag) = monitor.pertag
+                        {
+                            pertag.layouts[pertag.current_tag] = next_name.to_string();
+                        }
+                        if next_name != "normie" && next_name != "floating" {
+                            self.floating_windows.clear();
+                        }
+                        self.apply_layout()?;
+                        self.update_bar()?;
+                        self.restack()?;
+                    }
+                    Err(e) => eprintln!("Failed to cycle layout: {}", e),
+                }
+            }
+            KeyAction::ToggleFloating => {
+                self.toggle_floating()?;
+                self.restack()?;
+            }
+
+            KeyAction::FocusStack => {
+                if let Arg::Int(direction) = arg {
+                    self.focusstack(*direction)?;
+                    self.restack()?;
+                }
+            }
+            KeyAction::MoveStack => {
+                if let Arg::Int(direction) = arg {
+                    self.move_stack(*direction)?;
+                    self.restack()?;
+                }
+            }
+            KeyAction::Quit | KeyAction::Restart => {}
+            KeyAction::ViewTag => {
+                if let Arg::Int(tag_index) = arg {
+                    self.view_tag(*tag_index as usize)?;
+                }
+            }
+            KeyAction::ViewNextTag => {
+                let monitor = self.get_selected_monitor();
+                let current_tag_index = unmask_tag(monitor.get_selected_tag()) as i32;
+                let len = self.config.tags.len() as i32;
+                self.view_tag((current_tag_index + 1).rem_euclid(len) as usize)?;
+            }
+            KeyAction::ViewPreviousTag => {
+                let monitor = self.get_selected_monitor();
+                let current_tag_index = unmask_tag(monitor.get_selected_tag()) as i32;
+                let len = self.config.tags.len() as i32;
+                self.view_tag((current_tag_index - 1).rem_euclid(len) as usize)?;
+            }
+            KeyAction::ViewNextNonEmptyTag => {
+                let monitor = self.get_selected_monitor();
+                let current = unmask_tag(monitor.get_selected_tag()) as i32;
+                let len = self.config.tags.len() as i32;
+                let mon_num = monitor.monitor_number;
+
+                for offset in 1..len {
+                    let next = (current + offset).rem_euclid(len) as usize;
+                    if self.has_windows_on_tag(mon_num, next) {
+                        self.view_tag(next)?;
+                        break;
+                    }
+                }
+            }
+            KeyAction::ViewPreviousNonEmptyTag => {
+                let monitor = self.get_selected_monitor();
+                let current = unmask_tag(monitor.get_selected_tag()) as i32;
+                let len = self.config.tags.len() as i32;
+                let mon_num = monitor.monitor_number;
+
+                for offset in 1..len {
+                    let prev = (current - offset).rem_euclid(len) as usize;
+                    if self.has_windows_on_tag(mon_num, prev) {
+                        self.view_tag(prev)?;
+                        break;
+                    }
+                }
+            }
+            KeyAction::ToggleView => {
+                if let Arg::Int(tag_index) = arg {
+                    self.toggleview(*tag_index as usize)?;
+                }
+            }
+            KeyAction::MoveToTag => {
+                if let Arg::Int(tag_index) = arg {
+                    self.move_to_tag(*tag_index as usize)?;
+                }
+            }
+            KeyAction::ToggleTag => {
+                if let Arg::Int(tag_index) = arg {
+                    self.toggletag(*tag_index as usize)?;
+                }
+            }
+            KeyAction::ToggleGaps => {
+                self.change_gaps(|gaps| gaps.enabled = !gaps.enabled)?;
+            }
+            KeyAction::FocusMonitor => {
+                if let Arg::Int(direction) = arg {
+                    self.focus_monitor(*direction)?;
+                }
+            }
+            KeyAction::TagMonitor => {
+                if let Arg::Int(direction) = arg {
+                    self.send_window_to_adjacent_monitor(*direction)?;
+                }
+            }
+            KeyAction::ShowKeybindOverlay => {
+                let monitor = &self.monitors[self.selected_monitor];
+                self.keybind_overlay.toggle(
+                    &self.connection,
+                    &self.font,
+                    &self.config.keybindings,
+                    monitor.screen_info.x as i16,
+                    monitor.screen_info.y as i16,
+                    monitor.screen_info.width as u16,
+                    monitor.screen_info.height as u16,
+                )?;
+            }
+            KeyAction::SetMasterFactor => {
+                if let Arg::Int(delta) = arg {
+                    self.set_master_factor(*delta as f32 / 100.0)?;
+                }
+            }
+            KeyAction::IncNumMaster => {
+                if let Arg::Int(delta) = arg {
+                    self.inc_num_master(*delta)?;
+                }
+            }
+            KeyAction::ScrollLeft => {
+                self.scroll_layout(-1)?;
+            }
+            KeyAction::ScrollRight => {
+                self.scroll_layout(1)?;
+            }
+            KeyAction::TogglePassthrough => {
+                self.toggle_passthrough()?;
+            }
+            KeyAction::CycleFloatPreset => {
+                self.cycle_float_preset()?;
+            }
+            KeyAction::ToggleMaximize => {
+                let (vertical, horizontal) = match arg {
+                    Arg::Str(direction) if direction == "vertical" => (true, false),
+                    Arg::Str(direction) if direction == "horizontal" => (false, true),
+                    _ => (true, true),
+                };
+                if let Some(focused) = self
+                    .monitors
+                    .get(self.selected_monitor)
+                    .and_then(|m| m.selected_client)
+                    && let Some(client) = self.clients.get(&focused)
+                {
+                    let is_maximized = (!vertical || client.is_maximized_vertical)
+                        && (!horizontal || client.is_maximized_horizontal);
+                    let target = Some(!is_maximized);
+                    self.set_window_maximized(
+                        focused,
+                        if vertical { target } else { None },
+                        if horizontal { target } else { None },
+                    )?;
+                }
+            }
+            KeyAction::SwapModkey => {
+                if let Arg::Str(modifier) = arg {
+                    self.swap_modkey(modifier)?;
+                }
+            }
+            KeyAction::SendKey => {
+                if let Arg::Array(keys) = arg {
+                    self.send_key(keys)?;
+                }
+            }
+            KeyAction::Chain => {
+                if let Arg::Actions(actions) = arg {
+                    for (action, arg) in actions {
+                        self.handle_key_action(*action, arg)?;
+                    }
+                }
+            }
+            KeyAction::Conditional => {
+                if let Arg::Conditional {
+                    condition,
+                    then,
+                    otherwise,
+                } = arg
+                {
+                    let (action, arg) = if self.check_condition(condition) {
+                        then.as_ref()
+                    } else {
+                        otherwise.as_ref()
+                    };
+                    self.handle_key_action(*action, arg)?;
+                }
+            }
+            KeyAction::EnterMode => {
+                if let Arg::Str(name) = arg {
+                    self.enter_mode(name)?;
+                }
+            }
+            KeyAction::ExitMode => {
+                self.exit_mode()?;
+            }
+            KeyAction::LockSession => {
+                self.lock_session()?;
+            }
+            KeyAction::ToggleShowDesktop => {
+                self.toggle_show_desktop()?;
+            }
+            KeyAction::StashWindow => {
+                self.stash_window()?;
+            }
+            KeyAction::PopStash => {
+                self.pop_stash()?;
+            }
+            KeyAction::SpawnPinned => {
+                let tag_index = self
+                    .monitors
+                    .get(self.selected_monitor)
+                    .map(|m| unmask_tag(m.get_selected_tag()))
+                    .unwrap_or(0);
+                self.spawn_pinned(tag_index)?;
+            }
+            KeyAction::SaveSnapshot => {
+                if let Arg::Str(name) = arg {
+                    self.save_snapshot(name);
+                }
+            }
+            KeyAction::RestoreSnapshot => {
+                if let Arg::Str(name) = arg {
+                    self.restore_snapshot(name)?;
+                }
+            }
+            KeyAction::TogglePresentation => {
+                self.toggle_presentation()?;
+            }
+            KeyAction::PickColor => {
+                self.pick_color()?;
+            }
+            KeyAction::InspectWindow => {
+                if let Arg::Str(target) = arg {
+                    self.inspect_window(target == "click")?;
+                }
+            }
+            KeyAction::ToggleBarDebug => {
+                self.toggle_bar_debug()?;
+            }
+            KeyAction::MoveToNewTag => {
+                if let Some(tag_index) = self.create_tag(None) {
+                    self.move_to_tag(tag_index)?;
+                }
+            }
+            KeyAction::ToggleTagLinks => {
+                self.tag_links_enabled = !self.tag_links_enabled;
+            }
+            KeyAction::ToggleScratchpad => {
+                if let Arg::Str(name) = arg {
+                    self.toggle_scratchpad(name)?;
+                }
+            }
+            KeyAction::IncGaps => {
+                if let Arg::Int(delta) = arg {
+                    self.adjust_gaps(*delta, *delta)?;
+                }
+            }
+            KeyAction::IncInnerGaps => {
+                if let Arg::Int(delta) = arg {
+                    self.adjust_gaps(*delta, 0)?;
+                }
+            }
+            KeyAction::IncOuterGaps => {
+                if let Arg::Int(delta) = arg {
+                    self.adjust_gaps(0, *delta)?;
+                }
+            }
+            KeyAction::ToggleSmartGaps => {
+                self.change_gaps(|gaps| gaps.smart = !gaps.smart)?;
+            }
+            KeyAction::None => {}
+        }
+        Ok(())
+    }
+
+    fn is_window_visible(&self, window: Window) -> bool {
+        if let Some(client) = self.clients.get(&window) {
+            let monitor = self.monitors.get(client.monitor_index);
+            let selected_tags = monitor
+                .map(|m| m.tagset[m.selected_tags_index])
+                .unwrap_or(0);
+            (client.tags & selected_tags) != 0
+        } else {
+            false
+        }
+    }
+
+    fn visible_windows(&self) -> Vec<Window> {
+        let mut result = Vec::new();
+        for monitor in &self.monitors {
+            let mut current = monitor.clients_head;
+            while let Some(window) = current {
+                if let Some(client) = self.clients.get(&window) {
+                    let visible_tags = client.tags & monitor.tagset[monitor.selected_tags_index];
+                    if visible_tags != 0 {
+                        result.push(window);
+                    }
+                    current = client.next;
+                } else {
+                    break;
+                }
+            }
+        }
+        result
+    }
+
+    fn visible_windows_on_monitor(&self, monitor_index: usize) -> Vec<Window> {
+        let mut result = Vec::new();
+        if let Some(monitor) = self.monitors.get(monitor_index) {
+            let mut current = monitor.clients_head;
+            while let Some(window) = current {
+                if let Some(client) = self.clients.get(&window) {
+                    let visible_tags = client.tags & monitor.tagset[monitor.selected_tags_index];
+                    if visible_tags != 0 {
+                        result.push(window);
+                    }
+                    current = client.next;
+                } else {
+                    break;
+                }
+            }
+        }
+        result
+    }
+
+    fn get_monitor_at_point(&self, x: i32, y: i32) -> Option<usize> {
+        self.monitors
+            .iter()
+            .position(|mon| mon.contains_point(x, y))
+    }
+
+    fn get_monitor_for_rect(&self, x: i32, y: i32, w: i32, h: i32) -> usize {
+        let mut best_monitor = self.selected_monitor;
+        let mut max_area = 0;
+
+        for (idx, monitor) in self.monitors.iter().enumerate() {
+            let intersect_width = 0.max(
+                (x + w).min(monitor.window_area_x + monitor.window_area_width)
+                    - x.max(monitor.window_area_x),
+            );
+            let intersect_height = 0.max(
+                (y + h).min(monitor.window_area_y + monitor.window_area_height)
+                    - y.max(monitor.window_area_y),
+            );
+            let area = intersect_width * intersect_height;
+
+            if area > max_area {
+                max_area = area;
+                best_monitor = idx;
+            }
+        }
+
+        best_monitor
+    }
+
+    fn move_window_to_monitor(
+        &mut self,
+        window: Window,
+        target_monitor_index: usize,
+    ) -> WmResult<()> {
+        let current_monitor_index = self.clients.get(&window).map(|c| c.monitor_index);
+
+        if let Some(current_idx) = current_monitor_index
+            && current_idx == target_monitor_index
+        {
+            return Ok(());
+        }
+
+        self.unfocus(window, false)?;
+        self.detach(window);
+        self.detach_stack(window);
+
+        if let Some(client) = self.clients.get_mut(&window) {
+            client.monitor_index = target_monitor_index;
+            if let Some(target_monitor) = self.monitors.get(target_monitor_index) {
+                client.tags = target_monitor.tagset[target_monitor.selected_tags_index];
+            }
+        }
+
+        self.attach_aside(window, target_monitor_index);
+        self.attach_stack(window, target_monitor_index);
+
+        self.focus(None)?;
+        self.apply_layout()?;
+
+        Ok(())
+    }
+
+    fn get_adjacent_monitor(&self, direction: i32) -> Option<usize> {
+        if self.monitors.len() <= 1 {
+            return None;
+        }
+
+        if direction > 0 {
+            if self.selected_monitor + 1 < self.monitors.len() {
+                Some(self.selected_monitor + 1)
+            } else {
+                Some(0)
+            }
+        } else if self.selected_monitor == 0 {
+            Some(self.monitors.len() - 1)
+        } else {
+            Some(self.selected_monitor - 1)
+        }
+    }
+
+    fn check_condition(&self, condition: &Condition) -> bool {
+        let focused = self
+            .monitors
+            .get(self.selected_monitor)
+            .and_then(|m| m.selected_client)
+            .and_then(|window| self.clients.get(&window));
+
+        match condition {
+            Condition::Focused => focused.is_some(),
+            Condition::Floating => focused.is_some_and(|c| c.is_floating),
+            Condition::Fullscreen => focused.is_some_and(|c| c.is_fullscreen),
+            Condition::Maximized => {
+                focused.is_some_and(|c| c.is_maximized_vertical || c.is_maximized_horizontal)
+            }
+            Condition::TagEmpty => !self
+                .clients
+                .values()
+                .any(|c| c.monitor_index == self.selected_monitor && self.is_visible(c.window)),
+            Condition::Class(class) => focused.is_some_and(|c| {
+                c.class.eq_ignore_ascii_case(class) || c.instance.eq_ignore_ascii_case(class)
+            }),
+        }
+    }
+
+    fn is_visible(&self, window: Window) -> bool {
+        let Some(client) = self.clients.get(&window) else {
+            return false;
+        };
+
+        let Some(monitor) = self.monitors.get(client.monitor_index) else {
+            return false;
+        };
+
+        (client.tags & monitor.tagset[monitor.selected_tags_index]) != 0
+    }
+
+    fn showhide(&mut self, window: Option<Window>) -> WmResult<()> {
+        let Some(window) = window else {
+            return Ok(());
+        };
+
+        let Some(client) = self.clients.get(&window).cloned() else {
+            return Ok(());
+        };
+
+        let monitor = match self.monitors.get(client.monitor_index) {
+            Some(m) => m,
+            None => return Ok(()),
+        };
+
+        let is_visible = (client.tags & monitor.tagset[monitor.selected_tags_index]) != 0;
+
+        if is_visible {
+            self.connection.configure_window(
+                window,
+                &ConfigureWindowAux::new()
+                    .x(client.x_position as i32)
+                    .y(client.y_position as i32),
+            )?;
+
+            let is_floating = client.is_floating;
+            let is_fullscreen = client.is_fullscreen;
+            let has_no_layout = self.layout.name() == LayoutType::Normie.as_str();
+
+            if (has_no_layout || is_floating) && !is_fullscreen {
+                let (x, y, w, h, changed) = self.apply_size_hints(
+                    window,
+                    client.x_position as i32,
+                    client.y_position as i32,
+                    client.width as i32,
+                    client.height as i32,
+                );
+                if changed {
+                    if let Some(c) = self.clients.get_mut(&window) {
+                        c.old_x_position = c.x_position;
+                        c.old_y_position = c.y_position;
+                        c.old_width = c.width;
+                        c.old_height = c.height;
+                        c.x_position = x as i16;
+                        c.y_position = y as i16;
+                        c.width = w as u16;
+                        c.height = h as u16;
+                    }
+                    self.connection.configure_window(
+                        window,
+                        &ConfigureWindowAux::new()
+                            .x(x)
+                            .y(y)
+                            .width(w as u32)
+                            .height(h as u32)
+                            .border_width(self.config.border_width),
+                    )?;
+                    self.send_configure_notify(window)?;
+                    self.connection.flush()?;
+                }
+            }
+
+            self.showhide(client.stack_next)?;
+        } else {
+            self.showhide(client.stack_next)?;
+
+            let width = client.width_with_border() as i32;
+            self.connection.configure_window(
+                window,
+                &ConfigureWindowAux::new()
+                    .x(width * -2)
+                    .y(client.y_position as i32),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    pub fn view_tag(&mut self, tag_index: usize) -> WmResult<()> {
+        if tag_index >= self.config.tags.len() {
+            return Ok(());
+        }
+
+        if let Some(owner) = self.tag_owner(tag_index)
+            && owner != self.selected_monitor
+        {
+            return self.view_tag_on(owner, tag_index);
+        }
+
+        let new_tagset = tag_mask(tag_index);
+        let mut layout_name: Option<String> = None;
+        let mut toggle_bar = false;
+        let old_tagset = self
+            .monitors
+            .get(self.selected_monitor)
+            .map(|monitor| monitor.get_selected_tag())
+            .unwrap_or(0);
+
+        if let Some(monitor) = self.monitors.get_mut(self.selected_monitor) {
+            if new_tagset == monitor.tagset[monitor.selected_tags_index] {
+                if !self.config.tag_back_and_forth {
+                    return Ok(());
+                }
+                monitor.tagset.swap(0, 1);
+                if let Some(ref mut pertag) = monitor.pertag {
+                    std::mem::swap(&mut pertag.previous_tag, &mut pertag.current_tag);
+                }
+            } else {
+                monitor.selected_tags_index ^= 1;
+                monitor.tagset[monitor.selected_tags_index] = new_tagset;
+                if let Some(ref mut pertag) = monitor.pertag {
+                    pertag.previous_tag = pertag.current_tag;
+                    pertag.current_tag = tag_index + 1;
+                }
+            }
+
+            if let Some(ref pertag) = monitor.pertag {
+                monitor.num_master = pertag.num_masters[pertag.current_tag];
+                monitor.master_factor = pertag.master_factors[pertag.current_tag];
+                monitor.gaps = pertag.gaps[pertag.current_tag];
+                layout_name = Some(pertag.layouts[pertag.current_tag].clone());
+                if monitor.show_bar != pertag.show_bars[pertag.current_tag] {
+                    toggle_bar = true;
+                }
+            }
+        }
+
+        if let Some(name) = layout_name
+            && let Ok(layout) = layout_from_str(&name)
+        {
+            self.layout = layout;
+        }
+
+        if toggle_bar {
+            self.toggle_bar()?;
+        }
+
+        self.sync_linked_tags();
+
+        if self.pinned_viewed & new_tagset == 0
+            && self
+                .config
+                .pinned_tags
+                .iter()
+                .any(|pinned| pinned.tag == tag_index && pinned.spawn_on_view)
+        {
+            self.pinned_viewed |= new_tagset;
+            self.spawn_pinned(tag_index)?;
+        }
+
+        if self.fade_config().is_some() {
+            let shown_tagset = self
+                .monitors
+                .get(self.selected_monitor)
+                .map(|monitor| monitor.get_selected_tag())
+                .unwrap_or(0);
+            let appearing: Vec<Window> = self
+                .clients
+                .values()
+                .filter(|c| {
+                    c.monitor_index == self.selected_monitor
+                        && c.tags & shown_tagset != 0
+                        && c.tags & old_tagset == 0
+                })
+                .map(|c| c.window)
+                .collect();
+            for window in appearing {
+                self.fade_in(window)?;
+            }
+        }
+
+        self.save_selected_tags()?;
+        self.focus(None)?;
+        self.apply_layout()?;
+        self.update_bar()?;
+        self.warp_pointer_to_focused()?;
+
+        Ok(())
+    }
+
+    /// Switches the other monitors of a tag link containing the tag just
+    /// viewed on the selected monitor.
+    fn sync_linked_tags(&mut self) {
+        if !self.tag_links_enabled || self.config.tag_links.is_empty() {
+            return;
+        }
+        let Some(monitor) = self.monitors.get(self.selected_monitor) else {
+            return;
+        };
+        let viewed = monitor.get_selected_tag();
+        if viewed.count_ones() != 1 {
+            return;
+        }
+        let (name, tag_index) = (monitor.name.clone(), unmask_tag(viewed));
+
+        let companions: Vec<(usize, usize)> = self
+            .config
+            .tag_links
+            .iter()
+            .filter(|link| {
+                link.views
+                    .iter()
+                    .any(|(output, tag)| *output == name && *tag == tag_index)
+            })
+            .flat_map(|link| link.views.iter())
+            .filter(|(output, tag)| *output != name && *tag < self.config.tags.len())
+            .filter_map(|(output, tag)| {
+                let monitor_index = self.monitors.iter().position(|m| m.name == *output)?;
+                Some((monitor_index, *tag))
+            })
+            .collect();
+
+        for (monitor_index, tag) in companions {
+            let monitor = &mut self.monitors[monitor_index];
+            let mask = tag_mask(tag);
+            if monitor.tagset[monitor.selected_tags_index] == mask {
+                continue;
+            }
+            monitor.selected_tags_index ^= 1;
+            monitor.tagset[monitor.selected_tags_index] = mask;
+            if let Some(pertag) = monitor.pertag.as_mut() {
+                pertag.previous_tag = pertag.current_tag;
+                pertag.current_tag = tag + 1;
+                monitor.num_master = pertag.num_masters[pertag.current_tag];
+                monitor.master_factor = pertag.master_factors[pertag.current_tag];
+                monitor.gaps = pertag.gaps[pertag.current_tag];
+            }
+        }
+    }
+
+    /// Monitor a tag is assigned to through its monitor config.
+    fn tag_owner(&self, tag_index: usize) -> Option<usize> {
+        let mask = tag_mask(tag_index);
+        self.monitors.iter().position(|monitor| {
+            self.config
+                .monitor_config(&monitor.name)
+                .is_some_and(|c| c.assigned_tags & mask != 0)
+        })
+    }
+
+    /// Views an assigned tag on its monitor, focusing that monitor unless
+    /// configured to keep focus where it is.
+    fn view_tag_on(&mut self, monitor_index: usize, tag_index: usize) -> WmResult<()> {
+        let previous_monitor = self.selected_monitor;
+        if let Some(window) = self
+            .monitors
+            .get(previous_monitor)
+            .and_then(|m| m.selected_client)
+        {
+            self.unfocus(window, true)?;
+        }
+
+        self.selected_monitor = monitor_index;
+        let already_viewed = self
+            .monitors
+            .get(monitor_index)
+            .is_some_and(|m| m.tagset[m.selected_tags_index] == tag_mask(tag_index));
+        if already_viewed {
+            self.focus(None)?;
+            self.update_bar()?;
+        } else {
+            self.view_tag(tag_index)?;
+        }
+
+        if !self.config.assigned_tag_focus {
+            if let Some(window) = self
+                .monitors
+                .get(monitor_index)
+                .and_then(|m| m.selected_client)
+            {
+                self.unfocus(window, true)?;
+            }
+            self.selected_monitor = previous_monitor;
+            self.focus(None)?;
+            self.update_bar()?;
+        }
+        Ok(())
+    }
+
+    pub fn toggleview(&mut self, tag_index: usize) -> WmResult<()> {
+        if tag_index >= self.config.tags.len() {
+            return Ok(());
+        }
+
+        let num_tags = self.config.tags.len();
+        let all_tags_mask = all_tags_mask(num_tags);
+        let mut layout_name: Option<String> = None;
+        let mut toggle_bar = false;
+
+        if let Some(monitor) = self.monitors.get_mut(self.selected_monitor) {
+            let mask = tag_mask(tag_index);
+            let new_tagset = monitor.tagset[monitor.selected_tags_index] ^ mask;
+
+            if new_tagset == 0 {
+                return Ok(());
+            }
+
+            monitor.tagset[monitor.selected_tags_index] = new_tagset;
+
+            if let Some(ref mut pertag) = monitor.pertag {
+                if new_tagset == all_tags_mask {
+                    pertag.previous_tag = pertag.current_tag;
+                    pertag.current_tag = 0;
+                }
+
+                if pertag.current_tag > 0 && (new_tagset & (1 << (pertag.current_tag - 1))) == 0 {
+                    pertag.previous_tag = pertag.current_tag;
+                    pertag.current_tag = (new_tagset.trailing_zeros() as usize) + 1;
+                }
+
+                monitor.num_master = pertag.num_masters[pertag.current_tag];
+                monitor.master_factor = pertag.master_factors[pertag.current_tag];
+                monitor.gaps = pertag.gaps[pertag.current_tag];
+                layout_name = Some(pertag.layouts[pertag.current_tag].clone());
+                if monitor.show_bar != pertag.show_bars[pertag.current_tag] {
+                    toggle_bar = true;
+                }
+            }
+        }
+
+        if let Some(name) = layout_name
+            && let Ok(layout) = layout_from_str(&name)
+        {
+            self.layout = layout;
+        }
+
+        if toggle_bar {
+            self.toggle_bar()?;
+        }
+
+        self.save_selected_tags()?;
+        self.focus(None)?;
+        self.apply_layout()?;
+        self.update_bar()?;
+        self.warp_pointer_to_focused()?;
+
+        Ok(())
+    }
+
+    fn backend(&self) -> X11Backend<'_> {
+        X11Backend::new(&self.connection, self.root)
+    }
+
+    /// Moves the pointer to the center of the focused client after a tag
+    /// switch, unless it is already inside it.
+    fn warp_pointer_to_focused(&self) -> WmResult<()> {
+        if !self.config.warp_pointer_on_tag_switch {
+            return Ok(());
+        }
+
+        let Some(client) = self
+            .monitors
+            .get(self.selected_monitor)
+            .and_then(|m| m.selected_client)
+            .and_then(|window| self.clients.get(&window))
+        else {
+            return Ok(());
+        };
+
+        let backend = self.backend();
+        let (pointer_x, pointer_y) = backend.pointer_position()?;
+        let left = client.x_position as i32;
+        let top = client.y_position as i32;
+        let inside = pointer_x >= left
+            && pointer_x < left + client.width as i32
+            && pointer_y >= top
+            && pointer_y < top + client.height as i32;
+
+        if !inside {
+            backend.warp_pointer(
+                client.window,
+                client.width as i32 / 2,
+                client.height as i32 / 2,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn toggle_show_desktop(&mut self) -> WmResult<()> {
+        let showing = self
+            .monitors
+            .get(self.selected_monitor)
+            .is_some_and(|m| !m.desktop_hidden.is_empty());
+        self.set_showing_desktop(!showing)
+    }
+
+    /// Hides every client shown on the selected monitor so the desktop is
+    /// visible, or puts them back on their tags.
+    fn set_showing_desktop(&mut self, show: bool) -> WmResult<()> {
+        let monitor_index = self.selected_monitor;
+        let Some(showing) = self
+            .monitors
+            .get(monitor_index)
+            .map(|m| !m.desktop_hidden.is_empty())
+        else {
+            return Ok(());
+        };
+        if show == showing {
+            return Ok(());
+        }
+
+        if show {
+            let hidden: Vec<(Window, TagMask)> = self
+                .visible_windows_on_monitor(monitor_index)
+                .into_iter()
+                .filter_map(|window| self.clients.get(&window).map(|c| (window, c.tags)))
+                .collect();
+            if hidden.is_empty() {
+                return Ok(());
+            }
+            for &(window, _) in &hidden {
+                if let Some(client) = self.clients.get_mut(&window) {
+                    client.tags = 0;
+                }
+            }
+            self.monitors[monitor_index].desktop_hidden = hidden;
+        } else {
+            let hidden = std::mem::take(&mut self.monitors[monitor_index].desktop_hidden);
+            for (window, tags) in hidden {
+                if let Some(client) = self.clients.get_mut(&window)
+                    && client.tags == 0
+                {
+                    client.tags = tags;
+                }
+            }
+        }
+
+        self.connection.change_property(
+            PropMode::REPLACE,
+            self.root,
+            self.atoms.net_showing_desktop,
+            AtomEnum::CARDINAL,
+            32,
+            1,
+            &(show as u32).to_ne_bytes(),
+        )?;
+
+        self.focus(None)?;
+        self.apply_layout()?;
+        self.update_bar()?;
+        Ok(())
+    }
+
+    /// Takes the focused window out of the layout onto the monitor's stash.
+    fn stash_window(&mut self) -> WmResult<()> {
+        let monitor_index = self.selected_monitor;
+        let Some(window) = self
+            .monitors
+            .get(monitor_index)
+            .and_then(|m| m.selected_client)
+        else {
+            return Ok(());
+        };
+
+        if let Some(client) = self.clients.get_mut(&window) {
+            client.tags = 0;
+        }
+        self.monitors[monitor_index].stash.push(window);
+
+        self.focus(None)?;
+        self.apply_layout()?;
+        self.update_bar()?;
+        Ok(())
+    }
+
+    /// Brings back the most recently stashed window in front of the focused
+    /// one, on the tags being viewed.
+    fn pop_stash(&mut self) -> WmResult<()> {
+        let monitor_index = self.selected_monitor;
+        let window = loop {
+            let Some(window) = self
+                .monitors
+                .get_mut(monitor_index)
+                .and_then(|m| m.stash.pop())
+            else {
+                return Ok(());
+            };
+            if self.clients.get(&window).is_some_and(|c| c.tags == 0) {
+                break window;
+            }
+        };
+
+        let focused = self.monitors[monitor_index].selected_client;
+        let tags = self.monitors[monitor_index].get_selected_tag();
+
+        self.detach(window);
+        if let Some(client) = self.clients.get_mut(&window) {
+            client.tags = tags;
+            client.monitor_index = monitor_index;
+        }
+        match focused {
+            Some(target) if target != window => self.insert_before(window, target, monitor_index),
+            _ => self.attach(window, monitor_index),
+        }
+
+        self.focus(Some(window))?;
+        self.apply_layout()?;
+        self.update_bar()?;
+        Ok(())
+    }
+
+    /// Saves the current tag's layout parameters and client arrangement
+    /// under `name`.
+    fn save_snapshot(&self, name: &str) {
+        let Some(monitor) = self.monitors.get(self.selected_monitor) else {
+            return;
+        };
+
+        let clients = self
+            .visible_windows_on_monitor(self.selected_monitor)
+            .into_iter()
+            .filter_map(|window| self.clients.get(&window))
+            .map(|client| SnapshotClient {
+                class: client.class.clone(),
+                floating: client.is_floating,
+                x: client.x_position,
+                y: client.y_position,
+                width: client.width,
+                height: client.height,
+            })
+            .collect();
+
+        let snapshot = Snapshot {
+            layout: self.layout.name().to_string(),
+            master_factor: monitor.master_factor,
+            num_master: monitor.num_master,
+            clients,
+        };
+        if let Err(e) = snapshot.save(name) {
+            eprintln!("Failed to save snapshot '{}': {}", name, e);
+        }
+    }
+
+    /// Restores a saved arrangement on the current tag, reordering and
+    /// resizing the visible clients whose class matches a saved client.
+    fn restore_snapshot(&mut self, name: &str) -> WmResult<()> {
+        let snapshot = match Snapshot::load(name) {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                eprintln!("Failed to load snapshot '{}': {}", name, e);
+                return Ok(());
+            }
+        };
+
+        match layout_from_str(&snapshot.layout) {
+            Ok(layout) => self.layout = layout,
+            Err(e) => eprintln!("Snapshot '{}' has an unknown layout: {}", name, e),
+        }
+        if let Some(monitor) = self.monitors.get_mut(self.selected_monitor) {
+            monitor.master_factor = snapshot.master_factor.clamp(0.05, 0.95);
+            monitor.num_master = snapshot.num_master.max(0);
+            if let Some(ref mut pertag) = monitor.pertag {
+                pertag.layouts[pertag.current_tag] = snapshot.layout.clone();
+                pertag.master_factors[pertag.current_tag] = monitor.master_factor;
+                pertag.num_masters[pertag.current_tag] = monitor.num_master;
+            }
+        }
+
+        let mut unmatched = self.visible_windows_on_monitor(self.selected_monitor);
+        let mut matched = Vec::new();
+        for saved in &snapshot.clients {
+            let Some(position) = unmatched.iter().position(|window| {
+                self.clients
+                    .get(window)
+                    .is_some_and(|client| client.class == saved.class)
+            }) else {
+                continue;
+            };
+            matched.push((unmatched.remove(position), saved));
+        }
+
+        for &(window, saved) in matched.iter().rev() {
+            let Some(client) = self.clients.get_mut(&window) else {
+                continue;
+            };
+            if client.is_fullscreen {
+                continue;
+            }
+            client.is_floating = saved.floating;
+            if saved.floating {
+                client.x_position = saved.x;
+                client.y_position = saved.y;
+                client.width = saved.width.max(1);
+                client.height = saved.height.max(1);
+                self.floating_windows.insert(window);
+                self.connection.configure_window(
+                    window,
+                    &ConfigureWindowAux::new()
+                        .x(i32::from(saved.x))
+                        .y(i32::from(saved.y))
+                        .width(u32::from(client.width))
+                        .height(u32::from(client.height)),
+                )?;
+            } else {
+                self.floating_windows.remove(&window);
+            }
+
+            let monitor_index = client.monitor_index;
+            self.detach(window);
+            self.attach(window, monitor_index);
+        }
+
+        self.apply_layout()?;
+        self.update_bar()?;
+        self.restack()?;
+        Ok(())
+    }
+
+    /// Starts the pinned apps of a tag that have no window on it yet and
+    /// applies the tag's pinned layout.
+    fn spawn_pinned(&mut self, tag_index: usize) -> WmResult<()> {
+        const PINNED_MAP_TIMEOUT_SECS: u64 = 30;
+        let Some(pin_index) = self
+            .config
+            .pinned_tags
+            .iter()
+            .position(|pinned| pinned.tag == tag_index)
+        else {
+            return Ok(());
+        };
+
+        self.pending_pinned
+            .retain(|(_, _, spawned)| spawned.elapsed().as_secs() < PINNED_MAP_TIMEOUT_SECS);
+
+        let mask = tag_mask(tag_index);
+        let pinned = &self.config.pinned_tags[pin_index];
+        for (app_index, app) in pinned.apps.iter().enumerate() {
+            let running = self
+                .clients
+                .values()
+                .any(|c| c.tags & mask != 0 && c.class.contains(app.class.as_str()));
+            let pending = self
+                .pending_pinned
+                .iter()
+                .any(|&(p, a, _)| p == pin_index && a == app_index);
+            if running || pending {
+                continue;
+            }
+            crate::signal::spawn_detached(&app.command);
+            self.pending_pinned
+                .push((pin_index, app_index, std::time::Instant::now()));
+        }
+
+        if let Some(name) = &pinned.layout {
+            match layout_from_str(name) {
+                Ok(layout) => {
+                    if let Some(monitor) = self.monitors.get_mut(self.selected_monitor) {
+                        if let Some(ref mut pertag) = monitor.pertag
+                            && let Some(slot) = pertag.layouts.get_mut(tag_index + 1)
+                        {
+                            *slot = name.clone();
+                        }
+                        if monitor.get_selected_tag() & mask != 0 {
+                            self.layout = layout;
+                        }
+                    }
+                }
+                Err(_) => eprintln!("Unknown layout '{}' for pinned tag", name),
+            }
+        }
+
+        self.arrange_pinned(pin_index);
+        self.apply_layout()?;
+        self.update_bar()?;
+        Ok(())
+    }
+
+    /// Sends a new window to its pinned tag when it is a pinned app that
+    /// was just started, returning the pinned tag's index.
+    fn adopt_pinned_app(&mut self, window: Window) -> Option<usize> {
+        if self.pending_pinned.is_empty() {
+            return None;
+        }
+        let client = self.clients.get(&window)?;
+        let position = self.pending_pinned.iter().position(|&(p, a, _)| {
+            client
+                .class
+                .contains(self.config.pinned_tags[p].apps[a].class.as_str())
+        })?;
+        let (pin_index, _, _) = self.pending_pinned.remove(position);
+        let tags = tag_mask(self.config.pinned_tags[pin_index].tag);
+        if let Some(client) = self.clients.get_mut(&window) {
+            client.tags = tags;
+        }
+        Some(pin_index)
+    }
+
+    /// Orders the pinned apps on their tag as listed, first app as master.
+    fn arrange_pinned(&mut self, pin_index: usize) {
+        let pinned = &self.config.pinned_tags[pin_index];
+        let mask = tag_mask(pinned.tag);
+        let mut order: Vec<(Window, usize)> = Vec::new();
+        for app in &pinned.apps {
+            if let Some(client) = self.clients.values().find(|c| {
+                c.tags & mask != 0
+                    && !c.is_floating
+                    && c.class.contains(app.class.as_str())
+                    && !order.iter().any(|&(w, _)| w == c.window)
+            }) {
+                order.push((client.window, client.monitor_index));
+            }
+        }
+
+        for &(window, monitor_index) in order.iter().rev() {
+            self.detach(window);
+            self.attach(window, monitor_index);
+        }
+    }
+
+    /// Scrolling over the tags views the next or previous tag, and over the
+    /// title focuses the next or previous window.
+    fn handle_bar_scroll(
+        &mut self,
+        monitor_index: usize,
+        region: BarRegion,
+        direction: i32,
+    ) -> WmResult<()> {
+        if monitor_index != self.selected_monitor {
+            self.selected_monitor = monitor_index;
+        }
+
+        match region {
+            BarRegion::Tag(_) => {
+                let action = match (self.config.bar_scroll_skip_empty, direction > 0) {
+                    (true, true) => KeyAction::ViewNextNonEmptyTag,
+                    (true, false) => KeyAction::ViewPreviousNonEmptyTag,
+                    (false, true) => KeyAction::ViewNextTag,
+                    (false, false) => KeyAction::ViewPreviousTag,
+                };
+                self.handle_key_action(action, &Arg::None)
+            }
+            BarRegion::Title => self.handle_key_action(KeyAction::FocusStack, &Arg::Int(direction)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether a click on a block goes to the block itself. Left clicks
+    /// without an `on_click` command are left to the block's popup and to
+    /// bar click bindings when there are any.
+    fn block_handles_click(&self, monitor_index: usize, block_index: usize, button: u8) -> bool {
+        let Some(bar) = self.bars.get(monitor_index) else {
+            return false;
+        };
+        if button != u8::from(ButtonIndex::M1)
+            || bar.block_click_command(block_index, button).is_some()
+        {
+            return true;
+        }
+        bar.block_popup(block_index).is_none()
+            && !self
+                .config
+                .bar_clicks
+                .iter()
+                .any(|click| click.region == crate::bar::RegionKind::Block)
+    }
+
+    /// Runs a left click on a bar region, holding it back while a double
+    /// click bound to the region is still possible.
+    fn handle_bar_click(&mut self, monitor_index: usize, region: BarRegion) -> WmResult<()> {
+        let now = std::time::Instant::now();
+        let has_double = self
+            .config
+            .bar_clicks
+            .iter()
+            .any(|click| click.double && click.region == region.kind());
+        if !has_double {
+            return self.bar_single_click(monitor_index, region);
+        }
+
+        let is_double = self.last_bar_click.is_some_and(|(m, r, clicked)| {
+            m == monitor_index
+                && r == region
+                && clicked.elapsed().as_millis() < self.config.double_click_ms as u128
+        });
+        if is_double {
+            self.last_bar_click = None;
+            self.pending_bar_click = None;
+            if let Some(click) = self
+                .config
+                .bar_clicks
+                .iter()
+                .find(|click| click.double && click.region == region.kind())
+                .cloned()
+            {
+                self.run_bar_action(monitor_index, region, click.action, &click.arg)?;
+            }
+        } else {
+            self.last_bar_click = Some((monitor_index, region, now));
+            self.pending_bar_click = Some((monitor_index, region, now));
+        }
+        Ok(())
+    }
+
+    /// Runs the single click binding of a region, or the default: viewing
+    /// a clicked tag and toggling a clicked block's popup.
+    fn bar_single_click(&mut self, monitor_index: usize, region: BarRegion) -> WmResult<()> {
+        if let Some(click) = self
+            .config
+            .bar_clicks
+            .iter()
+            .find(|click| !click.double && click.region == region.kind())
+            .cloned()
+        {
+            return self.run_bar_action(monitor_index, region, click.action, &click.arg);
+        }
+
+        match region {
+            BarRegion::Tag(tag_index) => {
+                if monitor_index != self.selected_monitor {
+                    self.selected_monitor = monitor_index;
+                }
+                self.view_tag(tag_index)?;
+            }
+            BarRegion::Block(block_index) => {
+                if let Some(command) = self
+                    .bars
+                    .get(monitor_index)
+                    .and_then(|bar| bar.block_popup(block_index))
+                {
+                    let command = command.to_string();
+                    self.toggle_block_popup(monitor_index, block_index, &command)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn run_bar_action(
+        &mut self,
+        monitor_index: usize,
+        region: BarRegion,
+        action: KeyAction,
+        arg: &Arg,
+    ) -> WmResult<()> {
+        if monitor_index != self.selected_monitor {
+            self.selected_monitor = monitor_index;
+        }
+
+        let is_tag_action = matches!(
+            action,
+            KeyAction::ViewTag
+                | KeyAction::ToggleView
+                | KeyAction::MoveToTag
+                | KeyAction::ToggleTag
+        );
+        match region {
+            BarRegion::Tag(tag_index) if is_tag_action => {
+                self.handle_key_action(action, &Arg::Int(tag_index as i32))
+            }
+            _ => self.handle_key_action(action, arg),
+        }
+    }
+
+    /// Opens the block's popup terminal, or closes it when it is open.
+    fn toggle_block_popup(
+        &mut self,
+        monitor_index: usize,
+        block_index: usize,
+        command: &str,
+    ) -> WmResult<()> {
+        const POPUP_MAP_TIMEOUT_SECS: u64 = 10;
+        let key = (monitor_index, block_index);
+
+        if let Some(&window) = self.block_popups.get(&key)
+            && self.clients.contains_key(&window)
+        {
+            self.block_popups.remove(&key);
+            return self.close_client(window);
+        }
+
+        self.pending_block_popups
+            .retain(|(_, _, spawned)| spawned.elapsed().as_secs() < POPUP_MAP_TIMEOUT_SECS);
+        if self.pending_block_popups.iter().any(|(_, k, _)| *k == key) {
+            return Ok(());
+        }
+
+        match crate::signal::spawn_in_terminal(&self.config.terminal, command) {
+            Ok(pid) => self
+                .pending_block_popups
+                .push((pid, key, std::time::Instant::now())),
+            Err(e) => eprintln!("Failed to open block popup '{}': {}", command, e),
+        }
+        Ok(())
+    }
+
+    /// Floats and centers a new window when it is a block popup terminal,
+    /// on the tags shown on the block's monitor.
+    fn adopt_block_popup(&mut self, window: Window) {
+        if self.pending_block_popups.is_empty() {
+            return;
+        }
+        let Some(pid) = self.get_window_pid(window) else {
+            return;
+        };
+        let Some(position) = self
+            .pending_block_popups
+            .iter()
+            .position(|&(popup_pid, _, _)| popup_pid == pid)
+        else {
+            return;
+        };
+        let (_, key, _) = self.pending_block_popups.remove(position);
+        let (monitor_index, _) = key;
+        let Some(monitor) = self.monitors.get(monitor_index) else {
+            return;
+        };
+
+        let preset = crate::FloatPreset {
+            name: String::new(),
+            width: 0.6,
+            height: 0.6,
+        };
+        let tags = monitor.get_selected_tag();
+        if let Some(client) = self.clients.get_mut(&window) {
+            let (x, y, width, height) =
+                float_preset_geometry(&preset, monitor, client.border_width as i32);
+            client.x_position = x as i16;
+            client.y_position = y as i16;
+            client.width = width as u16;
+            client.height = height as u16;
+            client.monitor_index = monitor_index;
+            client.tags = tags;
+            client.is_floating = true;
+            self.floating_windows.insert(window);
+        }
+        self.block_popups.insert(key, window);
+    }
+
+    /// Shows a scratchpad on the focused monitor, or hides it when it is
+    /// already shown there. Runs its command when it has no window yet.
+    fn toggle_scratchpad(&mut self, name: &str) -> WmResult<()> {
+        const SCRATCHPAD_MAP_TIMEOUT_SECS: u64 = 10;
+        let Some(scratchpad) = self
+            .config
+            .scratchpads
+            .iter()
+            .find(|scratchpad| scratchpad.name == name)
+            .cloned()
+        else {
+            eprintln!("No scratchpad named '{}'", name);
+            return Ok(());
+        };
+
+        let window = match self.scratchpad_windows.get(name) {
+            Some(&window) if self.clients.contains_key(&window) => Some(window),
+            _ => self
+                .clients
+                .values()
+                .find(|c| scratchpad.matches(&c.class, &c.instance, &c.name))
+                .map(|c| c.window),
+        };
+        let Some(window) = window else {
+            self.pending_scratchpads
+                .retain(|(_, spawned)| spawned.elapsed().as_secs() < SCRATCHPAD_MAP_TIMEOUT_SECS);
+            if !self
+                .pending_scratchpads
+                .iter()
+                .any(|(pending, _)| pending == name)
+            {
+                crate::signal::spawn_detached(&scratchpad.command);
+                self.pending_scratchpads
+                    .push((name.to_string(), std::time::Instant::now()));
+            }
+            return Ok(());
+        };
+        self.scratchpad_windows.insert(name.to_string(), window);
+
+        let monitor_index = self.selected_monitor;
+        let shown_here = self
+            .clients
+            .get(&window)
+            .is_some_and(|c| c.monitor_index == monitor_index)
+            && self.is_visible(window);
+        if shown_here {
+            if let Some(client) = self.clients.get_mut(&window) {
+                client.tags = 0;
+            }
+            self.focus(None)?;
+        } else {
+            let moved = self
+                .clients
+                .get(&window)
+                .is_some_and(|c| c.monitor_index != monitor_index);
+            if moved {
+                self.detach(window);
+                self.detach_stack(window);
+            }
+            self.place_scratchpad(window, &scratchpad);
+            if moved {
+                self.attach(window, monitor_index);
+                self.attach_stack(window, monitor_index);
+            }
+            if let Some(client) = self.clients.get(&window) {
+                self.connection.configure_window(
+                    window,
+                    &ConfigureWindowAux::new()
+                        .x(client.x_position as i32)
+                        .y(client.y_position as i32)
+                        .width(client.width as u32)
+                        .height(client.height as u32),
+                )?;
+            }
+            self.focus(Some(window))?;
+        }
+
+        self.apply_layout()?;
+        self.restack()?;
+        self.update_bar()?;
+        Ok(())
+    }
+
+    /// Floats and centers a scratchpad window on the tags viewed on the
+    /// focused monitor.
+    fn place_scratchpad(&mut self, window: Window, scratchpad: &crate::Scratchpad) {
+        let monitor_index = self.selected_monitor;
+        let Some(monitor) = self.monitors.get(monitor_index) else {
+            return;
+        };
+        let Some(client) = self.clients.get_mut(&window) else {
+            return;
+        };
+
+        let preset = crate::FloatPreset {
+            name: scratchpad.name.clone(),
+            width: scratchpad.width,
+            height: scratchpad.height,
+        };
+        let (x, y, width, height) =
+            float_preset_geometry(&preset, monitor, client.border_width as i32);
+        client.x_position = x as i16;
+        client.y_position = y as i16;
+        client.width = width as u16;
+        client.height = height as u16;
+        client.monitor_index = monitor_index;
+        client.tags = monitor.get_selected_tag();
+        client.is_floating = true;
+        self.floating_windows.insert(window);
+    }
+
+    /// Takes in a new window as the scratchpad that was just launched for
+    /// it, if any.
+    fn adopt_scratchpad(&mut self, window: Window) {
+        if self.pending_scratchpads.is_empty() {
+            return;
+        }
+        let Some(client) = self.clients.get(&window) else {
+            return;
+        };
+        let Some(scratchpad) = self
+            .config
+            .scratchpads
+            .iter()
+            .find(|scratchpad| {
+                self.pending_scratchpads
+                    .iter()
+                    .any(|(name, _)| *name == scratchpad.name)
+                    && scratchpad.matches(&client.class, &client.instance, &client.name)
+            })
+            .cloned()
+        else {
+            return;
+        };
+
+        self.pending_scratchpads
+            .retain(|(name, _)| *name != scratchpad.name);
+        self.place_scratchpad(window, &scratchpad);
+        self.scratchpad_windows.insert(scratchpad.name, window);
+    }
+
+    fn save_selected_tags(&self) -> WmResult<()> {
+        let net_current_desktop = self.atoms.net_current_desktop;
+
+        let selected_tags = self
+            .monitors
+            .get(self.selected_monitor)
+            .map(|m| m.tagset[m.selected_tags_index])
+            .unwrap_or(tag_mask(0));
+        let desktop = selected_tags.trailing_zeros();
+
+        let bytes = (desktop as u32).to_ne_bytes();
+        self.connection.change_property(
+            PropMode::REPLACE,
+            self.root,
+            net_current_desktop,
+            AtomEnum::CARDINAL,
+            32,
+            1,
+            &bytes,
+        )?;
+
+        self.connection.flush()?;
+        Ok(())
+    }
+
+    /// Moves a client to the tag a `_NET_WM_DESKTOP` request names, or onto
+    /// every tag for 0xFFFFFFFF.
+    fn set_client_desktop(&mut self, window: Window, desktop: u32) -> WmResult<()> {
+        let num_tags = self.config.tags.len();
+        let tags = if desktop == u32::MAX {
+            all_tags_mask(num_tags)
+        } else if (desktop as usize) < num_tags {
+            tag_mask(desktop as usize)
+        } else {
+            return Ok(());
+        };
+
+        let Some(client) = self.clients.get_mut(&window) else {
+            return Ok(());
+        };
+        client.tags = tags;
+
+        if let Err(error) = self.save_client_tag(window, tags) {
+            eprintln!("Failed to save client tag: {:?}", error);
+        }
+
+        self.focus(None)?;
+        self.apply_layout()?;
+        self.update_bar()
+    }
+
+    pub fn move_to_tag(&mut self, tag_index: usize) -> WmResult<()> {
+        if tag_index >= self.config.tags.len() {
+            return Ok(());
+        }
+
+        let focused = match self
+            .monitors
+            .get(self.selected_monitor)
+            .and_then(|m| m.selected_client)
+        {
+            Some(win) => win,
+            None => return Ok(()),
+        };
+
+        // Windows follow a tag assigned to another monitor.
+        if let Some(owner) = self.tag_owner(tag_index)
+            && owner != self.selected_monitor
+        {
+            self.move_window_to_monitor(focused, owner)?;
+        }
+
+        let mask = tag_mask(tag_index);
+
+        if let Some(client) = self.clients.get_mut(&focused) {
+            client.tags = mask;
+        }
+
+        if let Err(error) = self.save_client_tag(focused, mask) {
+            eprintln!("Failed to save client tag: {:?}", error);
+        }
+
+        self.focus(None)?;
+        self.apply_layout()?;
+        self.update_bar()?;
+
+        Ok(())
+    }
+
+    pub fn toggletag(&mut self, tag_index: usize) -> WmResult<()> {
+        if tag_index >= self.config.tags.len() {
+            return Ok(());
+        }
+
+        let focused = match self
+            .monitors
+            .get(self.selected_monitor)
+            .and_then(|m| m.selected_client)
+        {
+            Some(win) => win,
+            None => return Ok(()),
+        };
+
+        let mask = tag_mask(tag_index);
+        let current_tags = self.clients.get(&focused).map(|c| c.tags).unwrap_or(0);
+        let new_tags = current_tags ^ mask;
+
+        if new_tags == 0 {
+            return Ok(());
+        }
+
+        if let Some(client) = self.clients.get_mut(&focused) {
+            client.tags = new_tags;
+        }
+
+        if let Err(error) = self.save_client_tag(focused, new_tags) {
+            eprintln!("Failed to save client tag: {:?}", error);
+        }
+
+        self.focus(None)?;
+        self.apply_layout()?;
+        self.update_bar()?;
+
+        Ok(())
+    }
+
+    pub fn cycle_focus(&mut self, direction: i32) -> WmResult<()> {
+        let mut visible = self.visible_windows();
+        visible.retain(|window| !self.clients.get(window).is_some_and(|c| c.is_below));
+
+        if visible.is_empty() {
+            return Ok(());
+        }
+
+        let current = self
+            .monitors
+            .get(self.selected_monitor)
+            .and_then(|m| m.selected_client);
+
+        let next_window = if let Some(current) = current {
+            if let Some(current_index) = visible.iter().position(|&w| w == current) {
+                let next_index = if direction > 0 {
+                    (current_index + 1) % visible.len()
+                } else {
+                    (current_index + visible.len() - 1) % visible.len()
+                };
+                visible[next_index]
+            } else {
+                visible[0]
+            }
+        } else {
+            visible[0]
+        };
+
+        let is_tabbed = self.layout.name() == "tabbed";
+        if is_tabbed {
+            self.backend().raise_window(next_window)?;
+        }
+
+        self.focus(Some(next_window))?;
+
+        if is_tabbed {
+            self.update_tab_bars()?;
+        }
+
+        Ok(())
+    }
+
+    /// Bindings of the active mode, or the normal keybindings outside a mode.
+    fn active_keybindings(&self) -> &[keyboard::handlers::KeyBinding] {
+        self.active_mode
+            .and_then(|index| self.config.modes.get(index))
+            .map_or(&self.config.keybindings, |mode| &mode.keybindings)
+    }
+
+    fn enter_mode(&mut self, name: &str) -> WmResult<()> {
+        let Some(index) = self.config.modes.iter().position(|mode| mode.name == name) else {
+            eprintln!("No mode named '{}'", name);
+            return Ok(());
+        };
+        self.active_mode = Some(index);
+        self.keychord_state = keyboard::handlers::KeychordState::Idle;
+        self.current_key = 0;
+        self.grab_keys()?;
+        self.update_bar()?;
+        Ok(())
+    }
+
+    fn exit_mode(&mut self) -> WmResult<()> {
+        if self.active_mode.take().is_some() {
+            self.grab_keys()?;
+            self.update_bar()?;
+        }
+        Ok(())
+    }
+
+    fn grab_keys(&mut self) -> WmResult<()> {
+        let passthrough_bindings: Vec<_>;
+        let keybindings = if self.passthrough {
+            passthrough_bindings = self
+                .config
+                .keybindings
+                .iter()
+                .filter(|binding| binding.func == KeyAction::TogglePassthrough)
+                .cloned()
+                .collect();
+            &passthrough_bindings
+        } else {
+            self.active_keybindings()
+        };
+
+        self.keyboard_mapping = Some(keyboard::grab_keys(
+            &self.connection,
+            self.root,
+            keybindings,
+            self.current_key,
+            self.active_mode.is_some(),
+        )?);
+        Ok(())
+    }
+
+    /// Switches bindings written with "Mod" to `modifier`, or back to the
+    /// configured modkey when `modifier` is already active.
+    fn swap_modkey(&mut self, modifier: &str) -> WmResult<()> {
+        let Ok(requested) = crate::config::parse_modkey_string(modifier) else {
+            return Ok(());
+        };
+        let modkey = if self.config.modkey == requested {
+            self.configured_modkey
+        } else {
+            requested
+        };
+        self.set_modkey(modkey)
+    }
+
+    fn set_modkey(&mut self, modkey: KeyButMask) -> WmResult<()> {
+        self.config.modkey = modkey;
+        let mode_bindings = self
+            .config
+            .modes
+            .iter_mut()
+            .flat_map(|mode| mode.keybindings.iter_mut());
+        for binding in self.config.keybindings.iter_mut().chain(mode_bindings) {
+            binding.set_modkey(modkey);
+        }
+        self.keybind_overlay.set_modkey(modkey);
+
+        grab_modkey_buttons(&self.connection, self.root, modkey)?;
+        let windows: Vec<Window> = self.clients.keys().copied().collect();
+        let focused = self
+            .monitors
+            .get(self.selected_monitor)
+            .and_then(|m| m.selected_client);
+        for window in windows {
+            self.grabbuttons(window, Some(window) == focused)?;
+        }
+        self.grab_keys()
+    }
+
+    /// Sends `keys` (modifier names followed by a key name) to the focused
+    /// window.
+    fn send_key(&self, keys: &[String]) -> WmResult<()> {
+        let Some((key, modifier_names)) = keys.split_last() else {
+            return Ok(());
+        };
+        let Some(keysym) = keyboard::keysyms::keysym_from_str(key) else {
+            return Ok(());
+        };
+        let modifiers: Vec<KeyButMask> = modifier_names
+            .iter()
+            .filter_map(|name| crate::config::parse_modkey_string(name).ok())
+            .collect();
+        let Some(mapping) = &self.keyboard_mapping else {
+            return Ok(());
+        };
+
+        if !crate::xtest::send_key(&self.connection, self.root, mapping, &modifiers, keysym)? {
+            eprintln!("No keycode for '{}' in the current keyboard mapping", key);
+        }
+        Ok(())
+    }
+
+    /// Follows logind lock requests only while a locker is configured.
+    fn update_lock_listener(&mut self) {
+        if self.config.lock_command.is_none() {
+            self.lock_listener = None;
+            return;
+        }
+        if self.lock_listener.is_some() {
+            return;
+        }
+        match crate::session_lock::LockListener::spawn() {
+            Ok(listener) => self.lock_listener = Some(listener),
+            Err(e) => eprintln!("Failed to watch for session lock requests: {}", e),
+        }
+    }
+
+    fn update_remote_control(&mut self) {
+        let Some(server) = self.ipc.as_mut() else {
+            return;
+        };
+        match &self.config.remote_control {
+            Some(remote) => {
+                if let Err(e) = server.listen_tcp(&remote.address, &remote.token) {
+                    eprintln!("Failed to listen on {}: {}", remote.address, e);
+                }
+            }
+            None => server.stop_tcp(),
+        }
+    }
+
+    /// Follows UPower while power hooks or battery blocks need it.
+    fn update_power_listener(&mut self) {
+        let needed = !self.config.power_hooks.is_empty() || self.block_manager.has_power_blocks();
+        if !needed {
+            self.power_listener = None;
+            return;
+        }
+        if self.power_listener.is_some() {
+            return;
+        }
+        match crate::power::PowerListener::spawn() {
+            Ok(listener) => self.power_listener = Some(listener),
+            Err(e) => eprintln!("Failed to watch for power supply changes: {}", e),
+        }
+    }
+
+    fn check_power_state(&mut self) {
+        let state = crate::power::PowerState::read();
+        let previous = std::mem::replace(&mut self.power_state, state);
+        if state == previous {
+            return;
+        }
+
+        let hooks = &self.config.power_hooks;
+        if previous.on_ac.is_some() && state.on_ac != previous.on_ac {
+            let hook = if state.on_ac == Some(true) {
+                &hooks.on_ac
+            } else {
+                &hooks.on_battery
+            };
+            if let Some(command) = hook {
+                crate::power::run_hook(command, &state);
+            }
+        }
+
+        if state.discharging()
+            && let (Some(before), Some(now)) = (previous.capacity, state.capacity)
+        {
+            for (percent, command) in &hooks.low_battery {
+                if before > *percent && now <= *percent {
+                    crate::power::run_hook(command, &state);
+                }
+            }
+        }
+
+        self.block_manager.refresh_power_blocks();
+    }
+
+    fn lock_session(&mut self) -> WmResult<()> {
+        let Some(command) = self.config.lock_command.clone() else {
+            return Ok(());
+        };
+
+        match self.locker.lock(&command) {
+            Ok(false) => return Ok(()),
+            Ok(true) => {}
+            Err(e) => {
+                eprintln!("Failed to run locker '{}': {}", command, e);
+                return Ok(());
+            }
+        }
+
+        if self.config.lock_blank {
+            self.connection.force_screen_saver(ScreenSaver::ACTIVE)?;
+            self.connection.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Outlines the clickable regions of every bar and prints those of the
+    /// selected monitor's bar.
+    fn toggle_bar_debug(&mut self) -> WmResult<()> {
+        let mut enabled = false;
+        for bar in &mut self.bars {
+            enabled = bar.toggle_debug_regions();
+        }
+        self.update_bar()?;
+
+        if enabled && let Some(bar) = self.bars.get(self.selected_monitor) {
+            eprintln!("Bar hit regions on monitor {}:", self.selected_monitor);
+            for (start, end, region) in bar.regions() {
+                eprintln!("  {:?}: {}..{}", region, start, end);
+            }
+        }
+        Ok(())
     }
 
-    fn get_monitor_for_rect(&self, x: i32, y: i32, w: i32, h: i32) -> usize {
-        let mut best_monitor = self.selected_monitor;
-        let mut max_area = 0;
+    /// Lets the user click any pixel on screen, then copies its color as
+    /// `#rrggbb` to the clipboard and shows it in the bar. Any other button
+    /// cancels.
+    fn pick_color(&mut self) -> WmResult<()> {
+        self.start_click_pick(ClickPurpose::PickColor)
+    }
 
-        for (idx, monitor) in self.monitors.iter().enumerate() {
-            let intersect_width = 0.max(
-                (x + w).min(monitor.window_area_x + monitor.window_area_width)
-                    - x.max(monitor.window_area_x),
-            );
-            let intersect_height = 0.max(
-                (y + h).min(monitor.window_area_y + monitor.window_area_height)
-                    - y.max(monitor.window_area_y),
-            );
-            let area = intersect_width * intersect_height;
+    fn pick_color_at(&mut self, click: &ButtonPressEvent) -> WmResult<()> {
+        let (x, y, time) = (click.root_x, click.root_y, click.time);
 
-            if area > max_area {
-                max_area = area;
-                best_monitor = idx;
-            }
+        let image = self
+            .connection
+            .get_image(ImageFormat::Z_PIXMAP, self.root, x, y, 1, 1, !0)?
+            .reply()?;
+        let mut bytes = [0u8; 4];
+        for (byte, value) in bytes.iter_mut().zip(&image.data) {
+            *byte = *value;
         }
+        let color = format!("#{:06x}", u32::from_ne_bytes(bytes) & 0xffffff);
 
-        best_monitor
+        self.connection
+            .set_selection_owner(self.wm_check_window, self.atoms.clipboard, time)?;
+        self.clipboard_text = Some(color.clone());
+        self.picked_color = Some((color, std::time::Instant::now()));
+        self.update_bar()?;
+        Ok(())
     }
 
-    fn move_window_to_monitor(
-        &mut self,
-        window: Window,
-        target_monitor_index: usize,
-    ) -> WmResult<()> {
-        let current_monitor_index = self.clients.get(&window).map(|c| c.monitor_index);
+    /// Grabs the pointer with a crosshair until a left click, which
+    /// `handle_click_pick` then hands on for `purpose`. The main loop keeps
+    /// handling every other event meanwhile.
+    fn start_click_pick(&mut self, purpose: ClickPurpose) -> WmResult<()> {
+        if self.click_pick.is_some() {
+            return Ok(());
+        }
+        let cursor = create_cursor(self.display, XC_CROSSHAIR);
+        // The cursor is made over Xlib; it has to reach the server before
+        // the grab below refers to it.
+        unsafe {
+            x11::xlib::XFlush(self.display);
+        }
 
-        if let Some(current_idx) = current_monitor_index
-            && current_idx == target_monitor_index
-        {
+        let grab = self
+            .connection
+            .grab_pointer(
+                false,
+                self.root,
+                EventMask::BUTTON_PRESS | EventMask::BUTTON_RELEASE,
+                GrabMode::ASYNC,
+                GrabMode::ASYNC,
+                x11rb::NONE,
+                cursor as u32,
+                x11rb::CURRENT_TIME,
+            )?
+            .reply()?;
+        if grab.status != GrabStatus::SUCCESS {
+            free_cursor(self.display, cursor);
             return Ok(());
         }
 
-        self.unfocus(window, false)?;
-        self.detach(window);
-        self.detach_stack(window);
+        self.click_pick = Some(ClickPick {
+            purpose,
+            cursor,
+            click: None,
+        });
+        Ok(())
+    }
 
-        if let Some(client) = self.clients.get_mut(&window) {
-            client.monitor_index = target_monitor_index;
-            if let Some(target_monitor) = self.monitors.get(target_monitor_index) {
-                client.tags = target_monitor.tagset[target_monitor.selected_tags_index];
+    /// Handles a button event while a click pick holds the pointer. The grab
+    /// is kept until the click is released so it doesn't reach the window
+    /// under the pointer; any button but the left one cancels.
+    fn handle_click_pick(&mut self, event: &Event) -> WmResult<()> {
+        let Some(pick) = &mut self.click_pick else {
+            return Ok(());
+        };
+        match event {
+            Event::ButtonPress(e) if e.detail == u8::from(ButtonIndex::M1) => {
+                pick.click = Some(*e);
+                return Ok(());
             }
+            Event::ButtonPress(_) => pick.click = None,
+            Event::ButtonRelease(_) if pick.click.is_some() => {}
+            _ => return Ok(()),
         }
 
-        self.attach_aside(window, target_monitor_index);
-        self.attach_stack(window, target_monitor_index);
+        let Some(pick) = self.click_pick.take() else {
+            return Ok(());
+        };
+        self.connection
+            .ungrab_pointer(x11rb::CURRENT_TIME)?
+            .check()?;
+        free_cursor(self.display, pick.cursor);
 
-        self.focus(None)?;
-        self.apply_layout()?;
+        let Some(click) = pick.click else {
+            return Ok(());
+        };
+        match pick.purpose {
+            ClickPurpose::PickColor => self.pick_color_at(&click),
+            ClickPurpose::Inspect if click.child != x11rb::NONE => {
+                self.show_window_info(click.child)
+            }
+            ClickPurpose::Inspect => Ok(()),
+        }
+    }
 
-        Ok(())
+    /// Prints the rule-relevant details of the focused window, or of the
+    /// window clicked next, and shows them in a popup.
+    fn inspect_window(&mut self, pick: bool) -> WmResult<()> {
+        if pick {
+            return self.start_click_pick(ClickPurpose::Inspect);
+        }
+        match self
+            .monitors
+            .get(self.selected_monitor)
+            .and_then(|m| m.selected_client)
+        {
+            Some(window) => self.show_window_info(window),
+            None => Ok(()),
+        }
     }
 
-    fn get_adjacent_monitor(&self, direction: i32) -> Option<usize> {
-        if self.monitors.len() <= 1 {
-            return None;
+    fn show_window_info(&mut self, window: Window) -> WmResult<()> {
+        let lines = self.window_info(window)?;
+        println!("Window 0x{:x}:", window);
+        for line in &lines {
+            println!("  {}", line);
         }
 
-        if direction > 0 {
-            if self.selected_monitor + 1 < self.monitors.len() {
-                Some(self.selected_monitor + 1)
-            } else {
-                Some(0)
-            }
-        } else if self.selected_monitor == 0 {
-            Some(self.monitors.len() - 1)
-        } else {
-            Some(self.selected_monitor - 1)
+        if let Err(error) = self.info_overlay.show(
+            &self.connection,
+            &self.font,
+            &format!("Window 0x{:x}", window),
+            lines,
+            &self.monitors[self.selected_monitor].screen_info,
+        ) {
+            eprintln!("Failed to show window info: {:?}", error);
         }
+        Ok(())
     }
 
-    fn is_visible(&self, window: Window) -> bool {
-        let Some(client) = self.clients.get(&window) else {
-            return false;
+    fn window_info(&self, window: Window) -> WmResult<Vec<String>> {
+        let (class, instance) = self.get_window_class_instance(window);
+        let mut lines = vec![
+            format!("class: {}", class),
+            format!("instance: {}", instance),
+        ];
+
+        let window_type = match self.get_window_atom_property(window, self.atoms.net_wm_window_type)
+        {
+            Ok(Some(atom)) => {
+                let name = self.connection.get_atom_name(atom)?.reply()?.name;
+                String::from_utf8_lossy(&name).into_owned()
+            }
+            _ => "none".to_string(),
         };
 
-        let Some(monitor) = self.monitors.get(client.monitor_index) else {
-            return false;
+        let Some(client) = self.clients.get(&window) else {
+            lines.push(format!("type: {}", window_type));
+            lines.push("not managed".to_string());
+            return Ok(lines);
         };
 
-        (client.tags & monitor.tagset[monitor.selected_tags_index]) != 0
+        lines.insert(0, format!("title: {}", client.name));
+        lines.push(format!("type: {}", window_type));
+
+        let tags: Vec<String> = (0..self.config.tags.len())
+            .filter(|&i| client.tags & tag_mask(i) != 0)
+            .map(|i| (i + 1).to_string())
+            .collect();
+        lines.push(format!(
+            "tags: {} (mask 0x{:x})",
+            tags.join(", "),
+            client.tags
+        ));
+        let monitor_name = self
+            .monitors
+            .get(client.monitor_index)
+            .map(|m| m.name.as_str())
+            .unwrap_or("");
+        lines.push(format!(
+            "monitor: {} {}",
+            client.monitor_index, monitor_name
+        ));
+        lines.push(format!(
+            "geometry: {}x{}+{}+{}, border {}",
+            client.width, client.height, client.x_position, client.y_position, client.border_width
+        ));
+
+        let mut state = Vec::new();
+        for (set, name) in [
+            (client.is_floating, "floating"),
+            (client.is_fullscreen, "fullscreen"),
+            (client.is_below, "below"),
+            (client.is_fixed, "fixed"),
+            (client.is_urgent, "urgent"),
+            (client.never_focus, "never-focus"),
+        ] {
+            if set {
+                state.push(name);
+            }
+        }
+        if state.is_empty() {
+            state.push("tiled");
+        }
+        lines.push(format!("state: {}", state.join(", ")));
+
+        if client.hints_valid {
+            lines.push(format!(
+                "size hints: min {}x{}, max {}x{}, base {}x{}, inc {}x{}",
+                client.min_width,
+                client.min_height,
+                client.max_width,
+                client.max_height,
+                client.base_width,
+                client.base_height,
+                client.increment_width,
+                client.increment_height
+            ));
+        }
+        if let Some(pid) = self.get_window_pid(window) {
+            lines.push(format!("pid: {}", pid));
+        }
+
+        lines.push(String::new());
+        lines.push(format!(
+            "oxwm.rule.add({{ class = \"{}\", instance = \"{}\" }})",
+            class, instance
+        ));
+        Ok(lines)
     }
 
-    fn showhide(&mut self, window: Option<Window>) -> WmResult<()> {
-        let Some(window) = window else {
-            return Ok(());
+    /// Hands the clipboard text to a client asking for it.
+    fn handle_selection_request(&mut self, event: SelectionRequestEvent) -> WmResult<()> {
+        let property = if event.property == x11rb::NONE {
+            event.target
+        } else {
+            event.property
         };
 
-        let Some(client) = self.clients.get(&window).cloned() else {
-            return Ok(());
+        let served = match &self.clipboard_text {
+            Some(text) if event.selection == self.atoms.clipboard => {
+                if event.target == self.atoms.targets {
+                    let targets = [
+                        self.atoms.targets,
+                        self.atoms.utf8_string,
+                        AtomEnum::STRING.into(),
+                    ];
+                    let bytes: Vec<u8> = targets.iter().flat_map(|a| a.to_ne_bytes()).collect();
+                    self.connection.change_property(
+                        PropMode::REPLACE,
+                        event.requestor,
+                        property,
+                        AtomEnum::ATOM,
+                        32,
+                        targets.len() as u32,
+                        &bytes,
+                    )?;
+                    true
+                } else if event.target == self.atoms.utf8_string
+                    || event.target == u32::from(AtomEnum::STRING)
+                {
+                    self.connection.change_property(
+                        PropMode::REPLACE,
+                        event.requestor,
+                        property,
+                        event.target,
+                        8,
+                        text.len() as u32,
+                        text.as_bytes(),
+                    )?;
+                    true
+                } else {
+                    false
+                }
+            }
+            _ => false,
         };
 
-        let monitor = match self.monitors.get(client.monitor_index) {
-            Some(m) => m,
-            None => return Ok(()),
+        let notify = SelectionNotifyEvent {
+            response_type: SELECTION_NOTIFY_EVENT,
+            sequence: 0,
+            time: event.time,
+            requestor: event.requestor,
+            selection: event.selection,
+            target: event.target,
+            property: if served { property } else { x11rb::NONE },
         };
+        self.connection
+            .send_event(false, event.requestor, EventMask::NO_EVENT, notify)?;
+        self.connection.flush()?;
+        Ok(())
+    }
 
-        let is_visible = (client.tags & monitor.tagset[monitor.selected_tags_index]) != 0;
+    /// Turns presentation mode on or off: the screen saver and DPMS are
+    /// suspended, lock requests from logind and bell/tag flashes are ignored
+    /// and sensitive blocks are hidden.
+    fn toggle_presentation(&mut self) -> WmResult<()> {
+        if let Some((saver, dpms_enabled)) = self.presentation_saved.take() {
+            self.connection.set_screen_saver(
+                saver.timeout as i16,
+                saver.interval as i16,
+                saver.prefer_blanking,
+                saver.allow_exposures,
+            )?;
+            if dpms_enabled {
+                crate::dpms::set_enabled(&self.connection, true)?;
+            }
+            if let Some(command) = &self.config.presentation.on_exit {
+                crate::signal::spawn_detached(command);
+            }
+        } else {
+            let saver = self.connection.get_screen_saver()?.reply()?;
+            let dpms_enabled = crate::dpms::is_enabled(&self.connection)?;
+            self.connection.set_screen_saver(
+                0,
+                saver.interval as i16,
+                saver.prefer_blanking,
+                saver.allow_exposures,
+            )?;
+            if dpms_enabled {
+                crate::dpms::set_enabled(&self.connection, false)?;
+            }
+            self.presentation_saved = Some((saver, dpms_enabled));
+            if let Some(command) = &self.config.presentation.on_enter {
+                crate::signal::spawn_detached(command);
+            }
+        }
 
-        if is_visible {
-            self.connection.configure_window(
+        self.connection.flush()?;
+        self.update_bar()?;
+        Ok(())
+    }
+
+    /// Releases every key grab except the passthrough toggle itself, so
+    /// applications that need raw global hotkeys receive them.
+    fn toggle_passthrough(&mut self) -> WmResult<()> {
+        self.passthrough = !self.passthrough;
+        self.grab_keys()?;
+        self.update_bar()?;
+        Ok(())
+    }
+
+    fn kill_client(&self, window: Window) -> WmResult<()> {
+        if self.send_event(window, self.atoms.wm_delete_window)? {
+            self.connection.flush()?;
+        } else {
+            eprintln!(
+                "Window {} doesn't support WM_DELETE_WINDOW, killing forcefully",
+                window
+            );
+            self.connection.kill_client(window)?;
+            self.connection.flush()?;
+        }
+        Ok(())
+    }
+
+    fn send_event(&self, window: Window, protocol: Atom) -> WmResult<bool> {
+        let protocols_reply = self
+            .connection
+            .get_property(
+                false,
                 window,
-                &ConfigureWindowAux::new()
-                    .x(client.x_position as i32)
-                    .y(client.y_position as i32),
-            )?;
+                self.atoms.wm_protocols,
+                AtomEnum::ATOM,
+                0,
+                100,
+            )?
+            .reply();
 
-            let is_floating = client.is_floating;
-            let is_fullscreen = client.is_fullscreen;
-            let has_no_layout = self.layout.name() == LayoutType::Normie.as_str();
+        let protocols_reply = match protocols_reply {
+            Ok(reply) => reply,
+            Err(_) => return Ok(false),
+        };
 
-            if (has_no_layout || is_floating) && !is_fullscreen {
-                let (x, y, w, h, changed) = self.apply_size_hints(
-                    window,
-                    client.x_position as i32,
-                    client.y_position as i32,
-                    client.width as i32,
-                    client.height as i32,
-                );
-                if changed {
-                    if let Some(c) = self.clients.get_mut(&window) {
-                        c.old_x_position = c.x_position;
-                        c.old_y_position = c.y_position;
-                        c.old_width = c.width;
-                        c.old_height = c.height;
-                        c.x_position = x as i16;
-                        c.y_position = y as i16;
-                        c.width = w as u16;
-                        c.height = h as u16;
-                    }
-                    self.connection.configure_window(
-                        window,
-                        &ConfigureWindowAux::new()
-                            .x(x)
-                            .y(y)
-                            .width(w as u32)
-                            .height(h as u32)
-                            .border_width(self.config.border_width),
-                    )?;
-                    self.send_configure_notify(window)?;
-                    self.connection.flush()?;
-                }
+        let protocols: Vec<Atom> = protocols_reply
+            .value
+            .chunks_exact(4)
+            .map(|chunk| u32::from_ne_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect();
+
+        if !protocols.contains(&protocol) {
+            return Ok(false);
+        }
+
+        let event = x11rb::protocol::xproto::ClientMessageEvent {
+            response_type: x11rb::protocol::xproto::CLIENT_MESSAGE_EVENT,
+            format: 32,
+            sequence: 0,
+            window,
+            type_: self.atoms.wm_protocols,
+            data: x11rb::protocol::xproto::ClientMessageData::from([
+                protocol,
+                x11rb::CURRENT_TIME,
+                0,
+                0,
+                0,
+            ]),
+        };
+
+        self.connection
+            .send_event(false, window, EventMask::NO_EVENT, event)?;
+        self.connection.flush()?;
+        Ok(true)
+    }
+
+    fn set_urgent(&mut self, window: Window, urgent: bool) -> WmResult<()> {
+        if let Some(client) = self.clients.get_mut(&window) {
+            client.is_urgent = urgent;
+        }
+
+        let hints_reply = self
+            .connection
+            .get_property(false, window, AtomEnum::WM_HINTS, AtomEnum::WM_HINTS, 0, 9)?
+            .reply();
+
+        if let Ok(hints) = hints_reply
+            && hints.value.len() >= 4
+        {
+            let mut flags = u32::from_ne_bytes([
+                hints.value[0],
+                hints.value[1],
+                hints.value[2],
+                hints.value[3],
+            ]);
+
+            if urgent {
+                flags |= 256;
+            } else {
+                flags &= !256;
             }
 
-            self.showhide(client.stack_next)?;
-        } else {
-            self.showhide(client.stack_next)?;
+            let mut new_hints = hints.value.clone();
+            new_hints[0..4].copy_from_slice(&flags.to_ne_bytes());
 
-            let width = client.width_with_border() as i32;
-            self.connection.configure_window(
+            self.connection.change_property(
+                PropMode::REPLACE,
                 window,
-                &ConfigureWindowAux::new()
-                    .x(width * -2)
-                    .y(client.y_position as i32),
+                AtomEnum::WM_HINTS,
+                AtomEnum::WM_HINTS,
+                32,
+                new_hints.len() as u32 / 4,
+                &new_hints,
             )?;
         }
 
         Ok(())
     }
 
-    pub fn view_tag(&mut self, tag_index: usize) -> WmResult<()> {
-        if tag_index >= self.config.tags.len() {
-            return Ok(());
-        }
-
-        let new_tagset = tag_mask(tag_index);
-        let mut layout_name: Option<String> = None;
-        let mut toggle_bar = false;
-
-        if let Some(monitor) = self.monitors.get_mut(self.selected_monitor) {
-            if new_tagset == monitor.tagset[monitor.selected_tags_index] {
-                if !self.config.tag_back_and_forth {
-                    return Ok(());
-                }
-                monitor.tagset.swap(0, 1);
-                if let Some(ref mut pertag) = monitor.pertag {
-                    std::mem::swap(&mut pertag.previous_tag, &mut pertag.current_tag);
-                }
-            } else {
-                monitor.selected_tags_index ^= 1;
-                monitor.tagset[monitor.selected_tags_index] = new_tagset;
-                if let Some(ref mut pertag) = monitor.pertag {
-                    pertag.previous_tag = pertag.current_tag;
-                    pertag.current_tag = tag_index + 1;
-                }
-            }
+    fn get_window_atom_property(&self, window: Window, property: Atom) -> WmResult<Option<Atom>> {
+        let reply = self
+            .connection
+            .get_property(false, window, property, AtomEnum::ATOM, 0, 1)?
+            .reply();
 
-            if let Some(ref pertag) = monitor.pertag {
-                monitor.num_master = pertag.num_masters[pertag.current_tag];
-                monitor.master_factor = pertag.master_factors[pertag.current_tag];
-                layout_name = Some(pertag.layouts[pertag.current_tag].clone());
-                if monitor.show_bar != pertag.show_bars[pertag.current_tag] {
-                    toggle_bar = true;
-                }
+        match reply {
+            Ok(prop) if !prop.value.is_empty() && prop.value.len() >= 4 => {
+                let atom = u32::from_ne_bytes([
+                    prop.value[0],
+                    prop.value[1],
+                    prop.value[2],
+                    prop.value[3],
+                ]);
+                Ok(Some(atom))
             }
+            _ => Ok(None),
         }
+    }
 
-        if let Some(name) = layout_name
-            && let Ok(layout) = layout_from_str(&name)
-        {
-            self.layout = layout;
-        }
+    fn get_window_pid(&self, window: Window) -> Option<u32> {
+        let prop = self
+            .connection
+            .get_property(false, window, self.atoms.net_wm_pid, AtomEnum::CARDINAL, 0, 1)
+            .ok()?
+            .reply()
+            .ok()?;
+        prop.value32()?.next()
+    }
 
-        if toggle_bar {
-            self.toggle_bar()?;
+    fn get_window_atom_list_property(&self, window: Window, property: Atom) -> WmResult<Vec<Atom>> {
+        let reply = self
+            .connection
+            .get_property(false, window, property, AtomEnum::ATOM, 0, 32)?
+            .reply();
+
+        match reply {
+            Ok(prop) if !prop.value.is_empty() => {
+                let atoms: Vec<Atom> = prop
+                    .value
+                    .chunks_exact(4)
+                    .map(|chunk| u32::from_ne_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+                    .collect();
+                Ok(atoms)
+            }
+            _ => Ok(Vec::new()),
         }
+    }
 
-        self.save_selected_tags()?;
-        self.focus(None)?;
-        self.apply_layout()?;
-        self.update_bar()?;
+    fn fullscreen(&mut self) -> WmResult<()> {
+        let Some(focused_window) = self
+            .monitors
+            .get(self.selected_monitor)
+            .and_then(|m| m.selected_client)
+        else {
+            return Ok(());
+        };
 
+        let is_fullscreen = self.fullscreen_windows.contains(&focused_window);
+        self.set_window_fullscreen(focused_window, !is_fullscreen)?;
         Ok(())
     }
 
-    pub fn toggleview(&mut self, tag_index: usize) -> WmResult<()> {
-        if tag_index >= self.config.tags.len() {
-            return Ok(());
-        }
+    fn set_window_fullscreen(&mut self, window: Window, fullscreen: bool) -> WmResult<()> {
+        let monitor_idx = self
+            .clients
+            .get(&window)
+            .map(|c| c.monitor_index)
+            .unwrap_or(self.selected_monitor);
+        let monitor = &self.monitors[monitor_idx];
 
-        let num_tags = self.config.tags.len();
-        let all_tags_mask = (1u32 << num_tags) - 1;
-        let mut layout_name: Option<String> = None;
-        let mut toggle_bar = false;
+        if fullscreen && !self.fullscreen_windows.contains(&window) {
+            if let Some(client) = self.clients.get_mut(&window) {
+                client.is_fullscreen = true;
+                client.old_state = client.is_floating;
+                client.old_border_width = client.border_width;
+                client.old_x_position = client.x_position;
+                client.old_y_position = client.y_position;
+                client.old_width = client.width;
+                client.old_height = client.height;
+                client.border_width = 0;
+                client.is_floating = true;
+                // Synthetic ConfigureNotify events must report the size the
+                // client actually has while fullscreen.
+                client.x_position = monitor.screen_info.x as i16;
+                client.y_position = monitor.screen_info.y as i16;
+                client.width = monitor.screen_info.width as u16;
+                client.height = monitor.screen_info.height as u16;
+            }
 
-        if let Some(monitor) = self.monitors.get_mut(self.selected_monitor) {
-            let mask = tag_mask(tag_index);
-            let new_tagset = monitor.tagset[monitor.selected_tags_index] ^ mask;
+            self.fullscreen_windows.insert(window);
+            self.floating_windows.insert(window);
+            self.update_net_wm_state(window)?;
 
-            if new_tagset == 0 {
-                return Ok(());
-            }
+            self.connection.configure_window(
+                window,
+                &x11rb::protocol::xproto::ConfigureWindowAux::new()
+                    .border_width(0)
+                    .x(monitor.screen_info.x)
+                    .y(monitor.screen_info.y)
+                    .width(monitor.screen_info.width as u32)
+                    .height(monitor.screen_info.height as u32)
+                    .stack_mode(x11rb::protocol::xproto::StackMode::ABOVE),
+            )?;
+            self.update_window_shape(
+                window,
+                monitor.screen_info.width as u16,
+                monitor.screen_info.height as u16,
+                0,
+            )?;
 
-            monitor.tagset[monitor.selected_tags_index] = new_tagset;
+            // Hides the monitor's bar and tiles the remaining clients.
+            self.apply_layout()?;
+        } else if !fullscreen && self.fullscreen_windows.contains(&window) {
+            self.fullscreen_windows.remove(&window);
 
-            if let Some(ref mut pertag) = monitor.pertag {
-                if new_tagset == all_tags_mask {
-                    pertag.previous_tag = pertag.current_tag;
-                    pertag.current_tag = 0;
-                }
+            let (
+                was_floating,
+                restored_x,
+                restored_y,
+                restored_width,
+                restored_height,
+                restored_border,
+            ) = self
+                .clients
+                .get(&window)
+                .map(|client| {
+                    (
+                        client.old_state,
+                        client.old_x_position,
+                        client.old_y_position,
+                        client.old_width,
+                        client.old_height,
+                        client.old_border_width,
+                    )
+                })
+                .unwrap_or((false, 0, 0, 100, 100, 0));
 
-                if pertag.current_tag > 0 && (new_tagset & (1 << (pertag.current_tag - 1))) == 0 {
-                    pertag.previous_tag = pertag.current_tag;
-                    pertag.current_tag = (new_tagset.trailing_zeros() as usize) + 1;
-                }
+            if !was_floating {
+                self.floating_windows.remove(&window);
+            }
 
-                monitor.num_master = pertag.num_masters[pertag.current_tag];
-                monitor.master_factor = pertag.master_factors[pertag.current_tag];
-                layout_name = Some(pertag.layouts[pertag.current_tag].clone());
-                if monitor.show_bar != pertag.show_bars[pertag.current_tag] {
-                    toggle_bar = true;
-                }
+            if let Some(client) = self.clients.get_mut(&window) {
+                client.is_fullscreen = false;
+                client.is_floating = client.old_state;
+                client.border_width = client.old_border_width;
+                client.x_position = client.old_x_position;
+                client.y_position = client.old_y_position;
+                client.width = client.old_width;
+                client.height = client.old_height;
             }
-        }
+            self.update_net_wm_state(window)?;
 
-        if let Some(name) = layout_name
-            && let Ok(layout) = layout_from_str(&name)
-        {
-            self.layout = layout;
-        }
+            self.connection.configure_window(
+                window,
+                &ConfigureWindowAux::new()
+                    .x(restored_x as i32)
+                    .y(restored_y as i32)
+                    .width(restored_width as u32)
+                    .height(restored_height as u32)
+                    .border_width(restored_border as u32),
+            )?;
 
-        if toggle_bar {
-            self.toggle_bar()?;
+            self.apply_layout()?;
         }
 
-        self.save_selected_tags()?;
-        self.focus(None)?;
-        self.apply_layout()?;
-        self.update_bar()?;
+        Ok(())
+    }
+
+    /// Rounds the corners of a client per `window_corner_radius`; fullscreen
+    /// clients keep a rectangular shape.
+    fn update_window_shape(
+        &mut self,
+        window: Window,
+        width: u16,
+        height: u16,
+        border_width: u16,
+    ) -> WmResult<()> {
+        let Some(client) = self.clients.get_mut(&window) else {
+            return Ok(());
+        };
+
+        let radius = if client.is_fullscreen {
+            0
+        } else {
+            self.config.window_corner_radius
+        };
+        if radius == 0 && !client.is_shaped {
+            return Ok(());
+        }
+        client.is_shaped = radius > 0;
 
+        crate::shape::set_rounded_corners(
+            &self.connection,
+            window,
+            width,
+            height,
+            border_width,
+            radius,
+        )?;
         Ok(())
     }
 
-    fn save_selected_tags(&self) -> WmResult<()> {
-        let net_current_desktop = self.atoms.net_current_desktop;
-
-        let selected_tags = self
-            .monitors
-            .get(self.selected_monitor)
-            .map(|m| m.tagset[m.selected_tags_index])
-            .unwrap_or(tag_mask(0));
-        let desktop = selected_tags.trailing_zeros();
+    /// Writes `_NET_WM_STATE` from the client's fullscreen, maximized and
+    /// keep-below flags.
+    fn update_net_wm_state(&self, window: Window) -> WmResult<()> {
+        let Some(client) = self.clients.get(&window) else {
+            return Ok(());
+        };
 
-        let bytes = (desktop as u32).to_ne_bytes();
+        // Only the states oxwm tracks are changed; others set by the client,
+        // such as skip-taskbar or above, are kept.
+        let managed = [
+            (self.atoms.net_wm_state_fullscreen, client.is_fullscreen),
+            (
+                self.atoms.net_wm_state_maximized_vert,
+                client.is_maximized_vertical,
+            ),
+            (
+                self.atoms.net_wm_state_maximized_horz,
+                client.is_maximized_horizontal,
+            ),
+            (self.atoms.net_wm_state_below, client.is_below),
+        ];
+        let mut states = self.get_window_atom_list_property(window, self.atoms.net_wm_state)?;
+        states.retain(|state| !managed.iter().any(|(atom, _)| atom == state));
+        states.extend(
+            managed
+                .iter()
+                .filter(|(_, active)| *active)
+                .map(|(atom, _)| *atom),
+        );
+
+        let bytes: Vec<u8> = states.iter().flat_map(|a| a.to_ne_bytes()).collect();
         self.connection.change_property(
             PropMode::REPLACE,
-            self.root,
-            net_current_desktop,
-            AtomEnum::CARDINAL,
+            window,
+            self.atoms.net_wm_state,
+            AtomEnum::ATOM,
             32,
-            1,
+            states.len() as u32,
             &bytes,
         )?;
-
-        self.connection.flush()?;
         Ok(())
     }
 
-    pub fn move_to_tag(&mut self, tag_index: usize) -> WmResult<()> {
-        if tag_index >= self.config.tags.len() {
+    /// Keeps a client under all other clients, for desktop widgets such as
+    /// conky. Such clients float and are skipped when cycling focus.
+    fn set_window_below(&mut self, window: Window, below: bool) -> WmResult<()> {
+        let Some(client) = self.clients.get_mut(&window) else {
             return Ok(());
-        }
-
-        let focused = match self
-            .monitors
-            .get(self.selected_monitor)
-            .and_then(|m| m.selected_client)
-        {
-            Some(win) => win,
-            None => return Ok(()),
         };
-
-        let mask = tag_mask(tag_index);
-
-        if let Some(client) = self.clients.get_mut(&focused) {
-            client.tags = mask;
+        if client.is_below == below {
+            return Ok(());
         }
-
-        if let Err(error) = self.save_client_tag(focused, mask) {
-            eprintln!("Failed to save client tag: {:?}", error);
+        client.is_below = below;
+        if below && !client.is_floating {
+            client.is_floating = true;
+            self.floating_windows.insert(window);
         }
+        self.update_net_wm_state(window)?;
 
-        self.focus(None)?;
-        self.apply_layout()?;
-        self.update_bar()?;
-
+        if self.windows.contains(&window) {
+            self.apply_layout()?;
+            self.restack()?;
+        }
         Ok(())
     }
 
-    pub fn toggletag(&mut self, tag_index: usize) -> WmResult<()> {
-        if tag_index >= self.config.tags.len() {
+    /// Sets the maximized states of a client; `None` leaves a direction as is.
+    ///
+    /// Maximizing floats the client and stretches it over the monitor's work
+    /// area in that direction. The geometry and floating state from before
+    /// the first maximize are restored once neither direction is maximized.
+    fn set_window_maximized(
+        &mut self,
+        window: Window,
+        vertical: Option<bool>,
+        horizontal: Option<bool>,
+    ) -> WmResult<()> {
+        let Some(client) = self.clients.get_mut(&window) else {
+            return Ok(());
+        };
+        if client.is_fullscreen {
             return Ok(());
         }
-
-        let focused = match self
-            .monitors
-            .get(self.selected_monitor)
-            .and_then(|m| m.selected_client)
-        {
-            Some(win) => win,
-            None => return Ok(()),
+        let Some(monitor) = self.monitors.get(client.monitor_index) else {
+            return Ok(());
         };
 
-        let mask = tag_mask(tag_index);
-        let current_tags = self.clients.get(&focused).map(|c| c.tags).unwrap_or(0);
-        let new_tags = current_tags ^ mask;
-
-        if new_tags == 0 {
+        let vertical = vertical.unwrap_or(client.is_maximized_vertical);
+        let horizontal = horizontal.unwrap_or(client.is_maximized_horizontal);
+        if vertical == client.is_maximized_vertical && horizontal == client.is_maximized_horizontal
+        {
             return Ok(());
         }
 
-        if let Some(client) = self.clients.get_mut(&focused) {
-            client.tags = new_tags;
+        if client.maximize_restore.is_none() {
+            client.maximize_was_floating = client.is_floating;
+        }
+        let restore = client.maximize_restore.unwrap_or((
+            client.x_position,
+            client.y_position,
+            client.width,
+            client.height,
+        ));
+        let (mut x, mut y, mut width, mut height) = (
+            restore.0 as i32,
+            restore.1 as i32,
+            restore.2 as i32,
+            restore.3 as i32,
+        );
+        let border = 2 * client.border_width as i32;
+
+        if vertical {
+            y = monitor.window_area_y;
+            height = (monitor.window_area_height - border).max(1);
         }
-
-        if let Err(error) = self.save_client_tag(focused, new_tags) {
-            eprintln!("Failed to save client tag: {:?}", error);
+        if horizontal {
+            x = monitor.window_area_x;
+            width = (monitor.window_area_width - border).max(1);
+        }
+
+        client.is_maximized_vertical = vertical;
+        client.is_maximized_horizontal = horizontal;
+        let maximized = vertical || horizontal;
+        client.maximize_restore = maximized.then_some(restore);
+        client.is_floating = maximized || client.maximize_was_floating;
+        client.x_position = x as i16;
+        client.y_position = y as i16;
+        client.width = width as u16;
+        client.height = height as u16;
+        if client.is_floating {
+            self.floating_windows.insert(window);
+        } else {
+            self.floating_windows.remove(&window);
         }
 
-        self.focus(None)?;
+        self.connection.configure_window(
+            window,
+            &ConfigureWindowAux::new()
+                .x(x)
+                .y(y)
+                .width(width as u32)
+                .height(height as u32)
+                .stack_mode(StackMode::ABOVE),
+        )?;
+        self.update_net_wm_state(window)?;
         self.apply_layout()?;
-        self.update_bar()?;
-
         Ok(())
     }
 
-    pub fn cycle_focus(&mut self, direction: i32) -> WmResult<()> {
-        let visible = self.visible_windows();
-
-        if visible.is_empty() {
-            return Ok(());
-        }
-
-        let current = self
-            .monitors
-            .get(self.selected_monitor)
-            .and_then(|m| m.selected_client);
-
-        let next_window = if let Some(current) = current {
-            if let Some(current_index) = visible.iter().position(|&w| w == current) {
-                let next_index = if direction > 0 {
-                    (current_index + 1) % visible.len()
+    fn get_transient_parent(&self, window: Window) -> Option<Window> {
+        self.connection
+            .get_property(
+                false,
+                window,
+                AtomEnum::WM_TRANSIENT_FOR,
+                AtomEnum::WINDOW,
+                0,
+                1,
+            )
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .filter(|reply| !reply.value.is_empty())
+            .and_then(|reply| {
+                if reply.value.len() >= 4 {
+                    let parent_window = u32::from_ne_bytes([
+                        reply.value[0],
+                        reply.value[1],
+                        reply.value[2],
+                        reply.value[3],
+                    ]);
+                    Some(parent_window)
                 } else {
-                    (current_index + visible.len() - 1) % visible.len()
-                };
-                visible[next_index]
-            } else {
-                visible[0]
-            }
-        } else {
-            visible[0]
-        };
-
-        let is_tabbed = self.layout.name() == "tabbed";
-        if is_tabbed {
-            self.connection.configure_window(
-                next_window,
-                &ConfigureWindowAux::new().stack_mode(StackMode::ABOVE),
-            )?;
-        }
-
-        self.focus(Some(next_window))?;
-
-        if is_tabbed {
-            self.update_tab_bars()?;
-        }
-
-        Ok(())
+                    None
+                }
+            })
     }
 
-    fn grab_keys(&mut self) -> WmResult<()> {
-        self.keyboard_mapping = Some(keyboard::grab_keys(
-            &self.connection,
-            self.root,
-            &self.config.keybindings,
-            self.current_key,
-        )?);
-        Ok(())
-    }
+    fn get_window_class_instance(&self, window: Window) -> (String, String) {
+        let reply = self
+            .connection
+            .get_property(false, window, AtomEnum::WM_CLASS, AtomEnum::STRING, 0, 1024)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok());
 
-    fn kill_client(&self, window: Window) -> WmResult<()> {
-        if self.send_event(window, self.atoms.wm_delete_window)? {
-            self.connection.flush()?;
-        } else {
-            eprintln!(
-                "Window {} doesn't support WM_DELETE_WINDOW, killing forcefully",
-                window
-            );
-            self.connection.kill_client(window)?;
-            self.connection.flush()?;
+        if let Some(reply) = reply
+            && !reply.value.is_empty()
+            && let Ok(text) = std::str::from_utf8(&reply.value)
+        {
+            let parts: Vec<&str> = text.split('\0').collect();
+            let instance = parts.first().unwrap_or(&"").to_string();
+            let class = parts.get(1).unwrap_or(&"").to_string();
+            return (instance, class);
         }
-        Ok(())
-    }
 
-    fn send_event(&self, window: Window, protocol: Atom) -> WmResult<bool> {
-        let protocols_reply = self
-            .connection
-            .get_property(
-                false,
-                window,
-                self.atoms.wm_protocols,
-                AtomEnum::ATOM,
-                0,
-                100,
-            )?
-            .reply();
+        (String::new(), String::new())
+    }
 
-        let protocols_reply = match protocols_reply {
-            Ok(reply) => reply,
-            Err(_) => return Ok(false),
-        };
+    /// Applies matching window rules and returns the `take_focus` override of
+    /// the last rule that sets one.
+    fn apply_rules(&mut self, window: Window) -> WmResult<Option<bool>> {
+        let (instance, class) = self.get_window_class_instance(window);
+        let title = self
+            .clients
+            .get(&window)
+            .map(|c| c.name.clone())
+            .unwrap_or_default();
 
-        let protocols: Vec<Atom> = protocols_reply
-            .value
-            .chunks_exact(4)
-            .map(|chunk| u32::from_ne_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
-            .collect();
+        let mut rule_tags: Option<u32> = None;
+        let mut rule_floating: Option<bool> = None;
+        let mut rule_monitor: Option<usize> = None;
+        let mut rule_focus = false;
+        let mut rule_preset: Option<usize> = None;
+        let mut rule_take_focus: Option<bool> = None;
+        let mut rule_terminal: Option<bool> = None;
+        let mut rule_swallow: Option<bool> = None;
+        let mut title_rules = Vec::new();
 
-        if !protocols.contains(&protocol) {
-            return Ok(false);
+        for (rule_index, rule) in self.config.window_rules.iter().enumerate() {
+            if rule.matches(&class, &instance, &title) {
+                if rule.retag_on_title {
+                    title_rules.push(rule_index);
+                }
+                if rule.tags.is_some() {
+                    rule_tags = rule.tags;
+                }
+                if rule.is_floating.is_some() {
+                    rule_floating = rule.is_floating;
+                }
+                if rule.monitor.is_some() {
+                    rule_monitor = rule.monitor;
+                }
+                rule_focus = rule.focus.unwrap_or(false);
+                if rule.take_focus.is_some() {
+                    rule_take_focus = rule.take_focus;
+                }
+                if rule.is_terminal.is_some() {
+                    rule_terminal = rule.is_terminal;
+                }
+                if rule.swallow.is_some() {
+                    rule_swallow = rule.swallow;
+                }
+                if let Some(name) = &rule.float_preset {
+                    rule_preset = self
+                        .config
+                        .float_presets
+                        .iter()
+                        .position(|p| &p.name == name);
+                    if rule_preset.is_none() {
+                        eprintln!("Unknown float preset '{}' in window rule", name);
+                    }
+                }
+            }
         }
 
-        let event = x11rb::protocol::xproto::ClientMessageEvent {
-            response_type: x11rb::protocol::xproto::CLIENT_MESSAGE_EVENT,
-            format: 32,
-            sequence: 0,
-            window,
-            type_: self.atoms.wm_protocols,
-            data: x11rb::protocol::xproto::ClientMessageData::from([
-                protocol,
-                x11rb::CURRENT_TIME,
-                0,
-                0,
-                0,
-            ]),
-        };
+        if rule_preset.is_some() {
+            rule_floating = Some(true);
+        }
 
-        self.connection
-            .send_event(false, window, EventMask::NO_EVENT, event)?;
-        self.connection.flush()?;
-        Ok(true)
-    }
+        let is_terminal = rule_terminal
+            .unwrap_or_else(|| is_terminal_program(&self.config.terminal, &class, &instance));
 
-    fn set_urgent(&mut self, window: Window, urgent: bool) -> WmResult<()> {
         if let Some(client) = self.clients.get_mut(&window) {
-            client.is_urgent = urgent;
-        }
+            client.title_rules = title_rules;
+            client.is_terminal = is_terminal;
+            client.no_swallow = rule_swallow == Some(false);
 
-        let hints_reply = self
-            .connection
-            .get_property(false, window, AtomEnum::WM_HINTS, AtomEnum::WM_HINTS, 0, 9)?
-            .reply();
+            if let Some(is_floating) = rule_floating {
+                client.is_floating = is_floating;
+                if is_floating {
+                    self.floating_windows.insert(window);
+                } else {
+                    self.floating_windows.remove(&window);
+                }
+            }
 
-        if let Ok(hints) = hints_reply
-            && hints.value.len() >= 4
-        {
-            let mut flags = u32::from_ne_bytes([
-                hints.value[0],
-                hints.value[1],
-                hints.value[2],
-                hints.value[3],
-            ]);
+            if let Some(monitor_index) = rule_monitor
+                && monitor_index < self.monitors.len()
+            {
+                client.monitor_index = monitor_index;
+            }
 
-            if urgent {
-                flags |= 256;
-            } else {
-                flags &= !256;
+            if let Some(preset_index) = rule_preset
+                && let Some(monitor) = self.monitors.get(client.monitor_index)
+            {
+                let (x, y, width, height) = float_preset_geometry(
+                    &self.config.float_presets[preset_index],
+                    monitor,
+                    client.border_width as i32,
+                );
+                client.x_position = x as i16;
+                client.y_position = y as i16;
+                client.width = width as u16;
+                client.height = height as u16;
+                client.float_preset = Some(preset_index);
             }
 
-            let mut new_hints = hints.value.clone();
-            new_hints[0..4].copy_from_slice(&flags.to_ne_bytes());
+            if let Some(tags) = rule_tags {
+                client.tags = tags;
+                let monitor_index = client.monitor_index;
 
-            self.connection.change_property(
-                PropMode::REPLACE,
-                window,
-                AtomEnum::WM_HINTS,
-                AtomEnum::WM_HINTS,
-                32,
-                new_hints.len() as u32 / 4,
-                &new_hints,
-            )?;
+                let monitor_tagset = self
+                    .monitors
+                    .get(monitor_index)
+                    .map(|monitor| monitor.get_selected_tag())
+                    .unwrap_or(tag_mask(0));
+                let is_tag_focused = monitor_tagset & tags == tags;
+
+                if !is_tag_focused {
+                    let action = if rule_focus {
+                        HiddenTagAction::Switch
+                    } else {
+                        self.config.hidden_tag_action
+                    };
+                    self.place_on_hidden_tag(window, monitor_index, tags, action)?;
+                }
+            }
         }
 
-        Ok(())
+        Ok(rule_take_focus)
     }
 
-    fn get_window_atom_property(&self, window: Window, property: Atom) -> WmResult<Option<Atom>> {
-        let reply = self
-            .connection
-            .get_property(false, window, property, AtomEnum::ATOM, 0, 1)?
-            .reply();
+    /// Shows the bell and flags the window that rang it, when it is known
+    /// and not focused.
+    fn handle_bell(&mut self, window: Window) -> WmResult<()> {
+        let focused = self
+            .monitors
+            .get(self.selected_monitor)
+            .and_then(|m| m.selected_client);
 
-        match reply {
-            Ok(prop) if !prop.value.is_empty() && prop.value.len() >= 4 => {
-                let atom = u32::from_ne_bytes([
-                    prop.value[0],
-                    prop.value[1],
-                    prop.value[2],
-                    prop.value[3],
-                ]);
-                Ok(Some(atom))
+        if self.clients.contains_key(&window) && Some(window) != focused {
+            self.mark_activity(window);
+            if self.config.bell_urgent {
+                self.set_urgent(window, true)?;
+                self.update_bar()?;
             }
-            _ => Ok(None),
+            self.update_tab_bars()?;
         }
-    }
 
-    fn get_window_atom_list_property(&self, window: Window, property: Atom) -> WmResult<Vec<Atom>> {
-        let reply = self
-            .connection
-            .get_property(false, window, property, AtomEnum::ATOM, 0, 32)?
-            .reply();
+        if self.presentation_saved.is_some() {
+            return Ok(());
+        }
 
-        match reply {
-            Ok(prop) if !prop.value.is_empty() => {
-                let atoms: Vec<Atom> = prop
-                    .value
-                    .chunks_exact(4)
-                    .map(|chunk| u32::from_ne_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
-                    .collect();
-                Ok(atoms)
+        match self.config.visual_bell {
+            VisualBell::Off => {}
+            VisualBell::Border => {
+                if let Some(window) = focused {
+                    self.backend()
+                        .set_border_color(window, self.config.scheme_urgent.underline)?;
+                    self.connection.flush()?;
+                    self.bell_flash = Some((window, std::time::Instant::now()));
+                }
+            }
+            VisualBell::Bar => {
+                if let Some(monitor) = self.monitors.get(self.selected_monitor)
+                    && let Some(bar) = self.bars.get_mut(self.selected_monitor)
+                {
+                    bar.flash_tags(monitor.get_selected_tag());
+                }
             }
-            _ => Ok(Vec::new()),
         }
+        Ok(())
     }
 
-    fn fullscreen(&mut self) -> WmResult<()> {
-        let Some(focused_window) = self
+    /// Puts back the border color a window should have after a visual bell.
+    fn restore_border(&mut self, window: Window) -> WmResult<()> {
+        if !self.clients.contains_key(&window) {
+            return Ok(());
+        }
+        let focused = self
             .monitors
             .get(self.selected_monitor)
-            .and_then(|m| m.selected_client)
-        else {
-            return Ok(());
+            .and_then(|m| m.selected_client);
+        let color = if Some(window) == focused {
+            self.config.border_focused
+        } else {
+            self.config.border_unfocused
         };
-
-        let is_fullscreen = self.fullscreen_windows.contains(&focused_window);
-        self.set_window_fullscreen(focused_window, !is_fullscreen)?;
+        self.backend().set_border_color(window, color)?;
+        self.connection.flush()?;
         Ok(())
     }
 
-    fn set_window_fullscreen(&mut self, window: Window, fullscreen: bool) -> WmResult<()> {
-        let monitor_idx = self
-            .clients
-            .get(&window)
-            .map(|c| c.monitor_index)
-            .unwrap_or(self.selected_monitor);
-        let monitor = &self.monitors[monitor_idx];
-
-        if fullscreen && !self.fullscreen_windows.contains(&window) {
-            let bytes = self.atoms.net_wm_state_fullscreen.to_ne_bytes().to_vec();
-            self.connection.change_property(
-                PropMode::REPLACE,
-                window,
-                self.atoms.net_wm_state,
-                AtomEnum::ATOM,
-                32,
-                1,
-                &bytes,
-            )?;
-
-            if let Some(client) = self.clients.get_mut(&window) {
-                client.is_fullscreen = true;
-                client.old_state = client.is_floating;
-                client.old_border_width = client.border_width;
-                client.old_x_position = client.x_position;
-                client.old_y_position = client.y_position;
-                client.old_width = client.width;
-                client.old_height = client.height;
-                client.border_width = 0;
-                client.is_floating = true;
-            }
-
-            self.fullscreen_windows.insert(window);
-            self.floating_windows.insert(window);
-
-            self.connection.configure_window(
-                window,
-                &x11rb::protocol::xproto::ConfigureWindowAux::new()
-                    .border_width(0)
-                    .x(monitor.screen_info.x)
-                    .y(monitor.screen_info.y)
-                    .width(monitor.screen_info.width as u32)
-                    .height(monitor.screen_info.height as u32)
-                    .stack_mode(x11rb::protocol::xproto::StackMode::ABOVE),
-            )?;
-
-            self.connection.flush()?;
-        } else if !fullscreen && self.fullscreen_windows.contains(&window) {
-            self.connection.change_property(
-                PropMode::REPLACE,
-                window,
-                self.atoms.net_wm_state,
-                AtomEnum::ATOM,
-                32,
-                0,
-                &[],
-            )?;
+    /// Notes activity on a window unless it has the focus.
+    fn mark_activity(&mut self, window: Window) {
+        let focused = self
+            .monitors
+            .get(self.selected_monitor)
+            .and_then(|m| m.selected_client);
+        if Some(window) == focused {
+            return;
+        }
+        if let Some(client) = self.clients.get_mut(&window) {
+            client.has_activity = true;
+        }
+    }
 
-            self.fullscreen_windows.remove(&window);
+    /// Moves a client to the tags of a `retag_on_title` rule once a title
+    /// change makes the rule match, e.g. a browser tab joining a call.
+    fn retag_on_title(&mut self, window: Window) -> WmResult<()> {
+        if !self.config.window_rules.iter().any(|r| r.retag_on_title) {
+            return Ok(());
+        }
+        let Some(title) = self.clients.get(&window).map(|c| c.name.clone()) else {
+            return Ok(());
+        };
+        let (instance, class) = self.get_window_class_instance(window);
 
-            let (
-                was_floating,
-                restored_x,
-                restored_y,
-                restored_width,
-                restored_height,
-                restored_border,
-            ) = self
-                .clients
-                .get(&window)
-                .map(|client| {
-                    (
-                        client.old_state,
-                        client.old_x_position,
-                        client.old_y_position,
-                        client.old_width,
-                        client.old_height,
-                        client.old_border_width,
-                    )
-                })
-                .unwrap_or((false, 0, 0, 100, 100, 0));
+        let matched: Vec<usize> = self
+            .config
+            .window_rules
+            .iter()
+            .enumerate()
+            .filter(|(_, rule)| rule.retag_on_title && rule.matches(&class, &instance, &title))
+            .map(|(index, _)| index)
+            .collect();
 
-            if !was_floating {
-                self.floating_windows.remove(&window);
-            }
+        let Some(client) = self.clients.get_mut(&window) else {
+            return Ok(());
+        };
+        let newly_matched = matched
+            .iter()
+            .filter(|index| !client.title_rules.contains(index))
+            .map(|&index| &self.config.window_rules[index])
+            .filter_map(|rule| rule.tags.map(|tags| (tags, rule.focus.unwrap_or(false))))
+            .next_back();
+        client.title_rules = matched;
 
-            if let Some(client) = self.clients.get_mut(&window) {
-                client.is_fullscreen = false;
-                client.is_floating = client.old_state;
-                client.border_width = client.old_border_width;
-                client.x_position = client.old_x_position;
-                client.y_position = client.old_y_position;
-                client.width = client.old_width;
-                client.height = client.old_height;
-            }
+        let Some((tags, focus)) = newly_matched else {
+            return Ok(());
+        };
+        if client.tags == tags {
+            return Ok(());
+        }
+        client.tags = tags;
+        let monitor_index = client.monitor_index;
 
-            self.connection.configure_window(
-                window,
-                &ConfigureWindowAux::new()
-                    .x(restored_x as i32)
-                    .y(restored_y as i32)
-                    .width(restored_width as u32)
-                    .height(restored_height as u32)
-                    .border_width(restored_border as u32),
-            )?;
+        if let Err(error) = self.save_client_tag(window, tags) {
+            eprintln!("Failed to save client tag: {:?}", error);
+        }
 
-            self.apply_layout()?;
+        let is_tag_focused = self
+            .monitors
+            .get(monitor_index)
+            .is_some_and(|monitor| monitor.get_selected_tag() & tags == tags);
+        if !is_tag_focused {
+            let action = if focus {
+                HiddenTagAction::Switch
+            } else {
+                self.config.hidden_tag_action
+            };
+            self.place_on_hidden_tag(window, monitor_index, tags, action)?;
         }
 
+        self.focus(None)?;
+        self.apply_layout()?;
+        self.update_bar()?;
         Ok(())
     }
 
-    fn get_transient_parent(&self, window: Window) -> Option<Window> {
-        self.connection
-            .get_property(
-                false,
-                window,
-                AtomEnum::WM_TRANSIENT_FOR,
-                AtomEnum::WINDOW,
-                0,
-                1,
-            )
-            .ok()
-            .and_then(|cookie| cookie.reply().ok())
-            .filter(|reply| !reply.value.is_empty())
-            .and_then(|reply| {
-                if reply.value.len() >= 4 {
-                    let parent_window = u32::from_ne_bytes([
-                        reply.value[0],
-                        reply.value[1],
-                        reply.value[2],
-                        reply.value[3],
-                    ]);
-                    Some(parent_window)
-                } else {
-                    None
-                }
-            })
-    }
-
-    fn get_window_class_instance(&self, window: Window) -> (String, String) {
-        let reply = self
-            .connection
-            .get_property(false, window, AtomEnum::WM_CLASS, AtomEnum::STRING, 0, 1024)
-            .ok()
-            .and_then(|cookie| cookie.reply().ok());
+    /// Follows up on a new window that landed on tags not shown on its
+    /// monitor.
+    fn place_on_hidden_tag(
+        &mut self,
+        window: Window,
+        monitor_index: usize,
+        tags: TagMask,
+        action: HiddenTagAction,
+    ) -> WmResult<()> {
+        match action {
+            HiddenTagAction::Switch => self.view_tag(unmask_tag(tags))?,
+            HiddenTagAction::Urgent => self.set_urgent(window, true)?,
+            HiddenTagAction::Silent => {}
+        }
 
-        if let Some(reply) = reply
-            && !reply.value.is_empty()
-            && let Ok(text) = std::str::from_utf8(&reply.value)
+        if action != HiddenTagAction::Switch
+            && self.config.bar_tag_flash
+            && self.presentation_saved.is_none()
+            && let Some(bar) = self.bars.get_mut(monitor_index)
         {
-            let parts: Vec<&str> = text.split('\0').collect();
-            let instance = parts.first().unwrap_or(&"").to_string();
-            let class = parts.get(1).unwrap_or(&"").to_string();
-            return (instance, class);
+            bar.flash_tags(tags);
         }
+        Ok(())
+    }
 
-        (String::new(), String::new())
+    fn tiled_clients_on_tags(&self, monitor_index: usize, tags: TagMask, except: Window) -> usize {
+        self.clients
+            .values()
+            .filter(|c| {
+                c.window != except
+                    && c.monitor_index == monitor_index
+                    && c.tags & tags != 0
+                    && !c.is_floating
+            })
+            .count()
     }
 
-    fn apply_rules(&mut self, window: Window) -> WmResult<()> {
-        let (instance, class) = self.get_window_class_instance(window);
-        let title = self
-            .clients
-            .get(&window)
-            .map(|c| c.name.clone())
-            .unwrap_or_default();
+    /// Moves a new tiled window off a tag that already holds
+    /// `tag_client_limit` tiled clients, to the next tag with room or into
+    /// floating.
+    fn apply_tag_limit(&mut self, window: Window) -> WmResult<()> {
+        let limit = self.config.tag_client_limit;
+        let Some(client) = self.clients.get(&window) else {
+            return Ok(());
+        };
+        if limit == 0 || client.is_floating {
+            return Ok(());
+        }
+        let (monitor_index, tags) = (client.monitor_index, client.tags);
+        if self.tiled_clients_on_tags(monitor_index, tags, window) < limit {
+            return Ok(());
+        }
 
-        let mut rule_tags: Option<u32> = None;
-        let mut rule_floating: Option<bool> = None;
-        let mut rule_monitor: Option<usize> = None;
-        let mut rule_focus = false;
+        let num_tags = self.config.tags.len();
+        let next_tag = match self.config.tag_overflow {
+            TagOverflow::NextTag => (1..num_tags)
+                .map(|offset| (unmask_tag(tags) + offset) % num_tags)
+                .find(|&tag| {
+                    self.tiled_clients_on_tags(monitor_index, tag_mask(tag), window) < limit
+                }),
+            TagOverflow::Float => None,
+        };
 
-        for rule in &self.config.window_rules {
-            if rule.matches(&class, &instance, &title) {
-                if rule.tags.is_some() {
-                    rule_tags = rule.tags;
-                }
-                if rule.is_floating.is_some() {
-                    rule_floating = rule.is_floating;
-                }
-                if rule.monitor.is_some() {
-                    rule_monitor = rule.monitor;
+        match next_tag {
+            Some(tag) => {
+                if let Some(client) = self.clients.get_mut(&window) {
+                    client.tags = tag_mask(tag);
                 }
-                rule_focus = rule.focus.unwrap_or(false);
-            }
-        }
-
-        if let Some(client) = self.clients.get_mut(&window) {
-            if let Some(is_floating) = rule_floating {
-                client.is_floating = is_floating;
-                if is_floating {
-                    self.floating_windows.insert(window);
-                } else {
-                    self.floating_windows.remove(&window);
+                let is_shown = self
+                    .monitors
+                    .get(monitor_index)
+                    .is_some_and(|m| m.get_selected_tag() & tag_mask(tag) != 0);
+                if !is_shown {
+                    let action = self.config.hidden_tag_action;
+                    self.place_on_hidden_tag(window, monitor_index, tag_mask(tag), action)?;
                 }
             }
-
-            if let Some(monitor_index) = rule_monitor
-                && monitor_index < self.monitors.len()
-            {
-                client.monitor_index = monitor_index;
-            }
-
-            if let Some(tags) = rule_tags {
-                client.tags = tags;
-
-                if rule_focus {
-                    let tag_index = unmask_tag(tags);
-                    let monitor_tagset = self
-                        .monitors
-                        .get(client.monitor_index)
-                        .map(|monitor| monitor.get_selected_tag())
-                        .unwrap_or(tag_mask(0));
-                    let is_tag_focused = monitor_tagset & tags == tags;
-
-                    if !is_tag_focused {
-                        self.view_tag(tag_index)?;
-                    }
+            None => {
+                if let Some(client) = self.clients.get_mut(&window) {
+                    client.is_floating = true;
                 }
+                self.floating_windows.insert(window);
             }
         }
-
         Ok(())
     }
 
@@ -2191,14 +6349,30 @@ impl WindowManager {
         client.old_border_width = geometry.border_width;
         client.border_width = border_width as u16;
 
+        let (instance, class) = self.get_window_class_instance(window);
+        client.class = class;
+        client.instance = instance;
+
         self.clients.insert(window, client);
         self.update_window_title(window)?;
 
-        if !is_transient {
-            self.apply_rules(window)?;
-        }
+        let pinned_tag = if is_transient {
+            None
+        } else {
+            self.adopt_pinned_app(window)
+        };
+
+        let rule_take_focus = if is_transient {
+            None
+        } else {
+            let rule_take_focus = self.apply_rules(window)?;
+            self.apply_tag_limit(window)?;
+            self.adopt_block_popup(window);
+            self.adopt_scratchpad(window);
+            rule_take_focus
+        };
 
-        let client_monitor = self
+        let mut client_monitor = self
             .clients
             .get(&window)
             .map(|c| c.monitor_index)
@@ -2245,10 +6419,8 @@ impl WindowManager {
             window,
             &ConfigureWindowAux::new().border_width(border_width),
         )?;
-        self.connection.change_window_attributes(
-            window,
-            &ChangeWindowAttributesAux::new().border_pixel(self.config.border_unfocused),
-        )?;
+        self.backend()
+            .set_border_color(window, self.config.border_unfocused)?;
         self.send_configure_notify(window)?;
         self.update_window_type(window)?;
         self.update_size_hints(window)?;
@@ -2264,15 +6436,14 @@ impl WindowManager {
             ),
         )?;
 
-        let is_fixed = self
+        let floats_by_heuristic = self
             .clients
             .get(&window)
-            .map(|c| c.is_fixed)
-            .unwrap_or(false);
+            .is_some_and(|c| self.floats_by_heuristic(c));
         if let Some(c) = self.clients.get_mut(&window)
             && !c.is_floating
         {
-            c.is_floating = is_transient || is_fixed;
+            c.is_floating = is_transient || floats_by_heuristic;
             c.old_state = c.is_floating;
         }
 
@@ -2282,11 +6453,30 @@ impl WindowManager {
             .map(|c| c.is_floating)
             .unwrap_or(false)
         {
+            self.restore_floating_geometry(window);
             self.floating_windows.insert(window);
-            self.connection.configure_window(
-                window,
-                &ConfigureWindowAux::new().stack_mode(StackMode::ABOVE),
-            )?;
+            let stack_mode = if self.clients.get(&window).is_some_and(|c| c.is_below) {
+                StackMode::BELOW
+            } else {
+                StackMode::ABOVE
+            };
+            self.connection
+                .configure_window(window, &ConfigureWindowAux::new().stack_mode(stack_mode))?;
+        }
+
+        let terminal = if is_transient {
+            None
+        } else {
+            self.swallow_target(window)
+        };
+        if let Some((monitor_index, tags)) = terminal
+            .and_then(|terminal| self.clients.get(&terminal))
+            .map(|terminal| (terminal.monitor_index, terminal.tags))
+            && let Some(c) = self.clients.get_mut(&window)
+        {
+            c.monitor_index = monitor_index;
+            c.tags = tags;
+            client_monitor = monitor_index;
         }
 
         if self.layout.name() == "scrolling" {
@@ -2305,6 +6495,22 @@ impl WindowManager {
         self.attach_stack(window, client_monitor);
         self.windows.push(window);
 
+        if let Some(terminal) = terminal {
+            self.swallow(terminal, window)?;
+        }
+
+        let (x, y, w, h) = self
+            .clients
+            .get(&window)
+            .map(|c| {
+                (
+                    c.x_position as i32,
+                    c.y_position as i32,
+                    c.width as i32,
+                    c.height as i32,
+                )
+            })
+            .unwrap_or((x, y, w, h));
         let off_screen_x = x + 2 * self.screen.width_in_pixels as i32;
         self.connection.configure_window(
             window,
@@ -2321,25 +6527,41 @@ impl WindowManager {
         let final_tags = self.clients.get(&window).map(|c| c.tags).unwrap_or(tags);
         let _ = self.save_client_tag(window, final_tags);
 
-        if client_monitor == self.selected_monitor
-            && let Some(old_sel) = self
-                .monitors
-                .get(self.selected_monitor)
-                .and_then(|m| m.selected_client)
-        {
-            self.unfocus(old_sel, false)?;
-        }
+        // A monitor without a selection always picks up the new window.
+        let take_focus = rule_take_focus.unwrap_or(match self.config.focus_on_map {
+            FocusOnMap::Always => true,
+            FocusOnMap::Never => false,
+            FocusOnMap::SameTag => {
+                client_monitor == self.selected_monitor && self.is_visible(window)
+            }
+        }) || self.monitors[client_monitor].selected_client.is_none();
+
+        if take_focus {
+            if client_monitor == self.selected_monitor
+                && let Some(old_sel) = self
+                    .monitors
+                    .get(self.selected_monitor)
+                    .and_then(|m| m.selected_client)
+            {
+                self.unfocus(old_sel, false)?;
+            }
+
+            if let Some(m) = self.monitors.get_mut(client_monitor) {
+                m.selected_client = Some(window);
+            }
 
-        if let Some(m) = self.monitors.get_mut(client_monitor) {
-            m.selected_client = Some(window);
+            if self.layout.name() == "scrolling" {
+                self.scroll_to_window(window, true)?;
+            }
         }
 
-        if self.layout.name() == "scrolling" {
-            self.scroll_to_window(window, true)?;
+        if let Some(pin_index) = pinned_tag {
+            self.arrange_pinned(pin_index);
         }
 
         self.apply_layout()?;
-        self.connection.map_window(window)?;
+        self.fade_in(window)?;
+        self.backend().map_window(window)?;
         self.focus(None)?;
         self.update_bar()?;
 
@@ -2358,11 +6580,7 @@ impl WindowManager {
             .unwrap_or(false);
 
         if !never_focus {
-            self.connection.set_input_focus(
-                InputFocus::POINTER_ROOT,
-                window,
-                x11rb::CURRENT_TIME,
-            )?;
+            self.backend().set_input_focus(window)?;
 
             self.connection.change_property(
                 PropMode::REPLACE,
@@ -2444,17 +6662,11 @@ impl WindowManager {
 
         self.grabbuttons(window, false)?;
 
-        self.connection.change_window_attributes(
-            window,
-            &ChangeWindowAttributesAux::new().border_pixel(self.config.border_unfocused),
-        )?;
+        self.backend()
+            .set_border_color(window, self.config.border_unfocused)?;
 
         if reset_input_focus {
-            self.connection.set_input_focus(
-                InputFocus::POINTER_ROOT,
-                self.root,
-                x11rb::CURRENT_TIME,
-            )?;
+            self.backend().set_input_focus(self.root)?;
             self.connection
                 .delete_property(self.root, self.atoms.net_active_window)?;
         }
@@ -2477,7 +6689,7 @@ impl WindowManager {
 
             focus_client = None;
             while let Some(w) = current {
-                if self.is_visible(w) {
+                if self.is_visible(w) && !self.clients.get(&w).is_some_and(|c| c.is_below) {
                     focus_client = Some(w);
                     break;
                 }
@@ -2505,16 +6717,17 @@ impl WindowManager {
             if self.clients.get(&win).is_some_and(|c| c.is_urgent) {
                 self.set_urgent(win, false)?;
             }
+            if let Some(client) = self.clients.get_mut(&win) {
+                client.has_activity = false;
+            }
 
             self.detach_stack(win);
             self.attach_stack(win, monitor_idx);
 
             self.grabbuttons(win, true)?;
 
-            self.connection.change_window_attributes(
-                win,
-                &ChangeWindowAttributesAux::new().border_pixel(self.config.border_focused),
-            )?;
+            self.backend()
+                .set_border_color(win, self.config.border_focused)?;
 
             let never_focus = self
                 .clients
@@ -2523,11 +6736,7 @@ impl WindowManager {
                 .unwrap_or(false);
 
             if !never_focus {
-                self.connection.set_input_focus(
-                    InputFocus::POINTER_ROOT,
-                    win,
-                    x11rb::CURRENT_TIME,
-                )?;
+                self.backend().set_input_focus(win)?;
 
                 self.connection.change_property(
                     PropMode::REPLACE,
@@ -2548,11 +6757,7 @@ impl WindowManager {
 
             self.previous_focused = Some(win);
         } else {
-            self.connection.set_input_focus(
-                InputFocus::POINTER_ROOT,
-                self.root,
-                x11rb::CURRENT_TIME,
-            )?;
+            self.backend().set_input_focus(self.root)?;
 
             self.connection
                 .delete_property(self.root, self.atoms.net_active_window)?;
@@ -2575,8 +6780,11 @@ impl WindowManager {
 
         let mut windows_to_restack: Vec<Window> = Vec::new();
 
+        let is_below = |win: Window| self.clients.get(&win).is_some_and(|c| c.is_below);
+
         if let Some(selected) = monitor.selected_client
             && self.floating_windows.contains(&selected)
+            && !is_below(selected)
         {
             windows_to_restack.push(selected);
         }
@@ -2585,6 +6793,7 @@ impl WindowManager {
         while let Some(win) = current {
             if self.windows.contains(&win)
                 && self.floating_windows.contains(&win)
+                && !is_below(win)
                 && Some(win) != monitor.selected_client
             {
                 windows_to_restack.push(win);
@@ -2594,7 +6803,20 @@ impl WindowManager {
 
         current = monitor.stack_head;
         while let Some(win) = current {
-            if self.windows.contains(&win) && !self.floating_windows.contains(&win) {
+            if self.windows.contains(&win)
+                && !self.floating_windows.contains(&win)
+                && !is_below(win)
+            {
+                windows_to_restack.push(win);
+            }
+            current = self.clients.get(&win).and_then(|c| c.stack_next);
+        }
+
+        // Keep-below windows go under every other client, even the one
+        // that has focus.
+        current = monitor.stack_head;
+        while let Some(win) = current {
+            if self.windows.contains(&win) && is_below(win) {
                 windows_to_restack.push(win);
             }
             current = self.clients.get(&win).and_then(|c| c.stack_next);
@@ -2602,10 +6824,7 @@ impl WindowManager {
 
         for (i, &win) in windows_to_restack.iter().enumerate() {
             if i == 0 {
-                self.connection.configure_window(
-                    win,
-                    &ConfigureWindowAux::new().stack_mode(StackMode::ABOVE),
-                )?;
+                self.backend().raise_window(win)?;
             } else {
                 self.connection.configure_window(
                     win,
@@ -2616,6 +6835,114 @@ impl WindowManager {
             }
         }
 
+        self.lower_desktop_windows()?;
+        self.raise_autohide_windows()?;
+        self.raise_input_method_windows()?;
+        Ok(())
+    }
+
+    /// Input method candidate and preedit windows. `managed_type` also treats
+    /// popup menu windows that ask to be managed as belonging to an input
+    /// method, since a WM frame around them would only get in the way.
+    fn is_input_method_window(&self, window: Window, managed_type: bool) -> bool {
+        let (instance, class) = self.get_window_class_instance(window);
+        let is_input_method = [instance, class].iter().any(|name| {
+            let name = name.to_ascii_lowercase();
+            INPUT_METHOD_CLASSES
+                .iter()
+                .any(|prefix| name.starts_with(prefix))
+        });
+        if is_input_method {
+            return true;
+        }
+
+        managed_type
+            && matches!(
+                self.get_window_atom_property(window, self.atoms.net_wm_window_type),
+                Ok(Some(type_atom)) if type_atom == self.atoms.net_wm_window_type_popup_menu
+            )
+    }
+
+    /// Keeps input method windows above the focused client; they are never
+    /// managed, so restacking clients would otherwise bury them.
+    fn is_desktop_window(&self, window: Window) -> bool {
+        self.get_window_atom_property(window, self.atoms.net_wm_window_type)
+            .ok()
+            .flatten()
+            == Some(self.atoms.net_wm_window_type_desktop)
+    }
+
+    fn is_dock_window(&self, window: Window) -> bool {
+        self.get_window_atom_property(window, self.atoms.net_wm_window_type)
+            .ok()
+            .flatten()
+            == Some(self.atoms.net_wm_window_type_dock)
+    }
+
+    /// Starts tracking a mapped dock and keeps tiled windows out of the
+    /// space it reserves.
+    fn add_dock(&mut self, window: Window) -> WmResult<()> {
+        self.connection.change_window_attributes(
+            window,
+            &ChangeWindowAttributesAux::new().event_mask(EventMask::PROPERTY_CHANGE),
+        )?;
+        let strut = self.read_strut(window);
+        self.dock_windows.insert(window, strut);
+        self.update_struts();
+        Ok(())
+    }
+
+    /// The dock's `_NET_WM_STRUT_PARTIAL`, or its older `_NET_WM_STRUT`.
+    fn read_strut(&self, window: Window) -> Strut {
+        [self.atoms.net_wm_strut_partial, self.atoms.net_wm_strut]
+            .into_iter()
+            .find_map(|atom| {
+                let reply = self
+                    .connection
+                    .get_property(false, window, atom, AtomEnum::CARDINAL, 0, 12)
+                    .ok()?
+                    .reply()
+                    .ok()?;
+                let values: Vec<u32> = reply.value32()?.collect();
+                Strut::from_values(&values)
+            })
+            .unwrap_or_default()
+    }
+
+    /// Recomputes every monitor's window area from the docks' struts.
+    fn update_struts(&mut self) {
+        let struts: Vec<Strut> = self.dock_windows.values().copied().collect();
+        let root_width = self
+            .monitors
+            .iter()
+            .map(|m| m.screen_info.x + m.screen_info.width)
+            .max()
+            .unwrap_or(0);
+        let root_height = self
+            .monitors
+            .iter()
+            .map(|m| m.screen_info.y + m.screen_info.height)
+            .max()
+            .unwrap_or(0);
+        for monitor in &mut self.monitors {
+            monitor.apply_struts(&struts, root_width, root_height);
+        }
+    }
+
+    fn lower_desktop_windows(&self) -> WmResult<()> {
+        for &window in &self.desktop_windows {
+            self.connection.configure_window(
+                window,
+                &ConfigureWindowAux::new().stack_mode(StackMode::BELOW),
+            )?;
+        }
+        Ok(())
+    }
+
+    fn raise_input_method_windows(&self) -> WmResult<()> {
+        for &window in &self.input_method_windows {
+            self.backend().raise_window(window)?;
+        }
         Ok(())
     }
 
@@ -2899,12 +7226,17 @@ impl WindowManager {
             return Ok(());
         };
 
-        let monitor = self.monitors.get(monitor_idx).cloned();
-        let Some(monitor) = monitor else {
+        if monitor_idx >= self.monitors.len() {
             return Ok(());
-        };
+        }
 
-        let snap = 32;
+        let border = self
+            .clients
+            .get(&window)
+            .map(|c| c.border_width as i32 * 2)
+            .unwrap_or(0);
+        let (outer_width, outer_height) = (width as i32 + border, height as i32 + border);
+        let snap = self.config.snap_distance as i32;
         let is_normie = self.layout.name() == "normie";
 
         if !was_floating && !is_normie {
@@ -2926,6 +7258,7 @@ impl WindowManager {
 
         let pointer = self.connection.query_pointer(self.root)?.reply()?;
         let (start_x, start_y) = (pointer.root_x as i32, pointer.root_y as i32);
+        let targets = self.snap_targets(window, monitor_idx);
 
         let mut last_time = 0u32;
 
@@ -2939,28 +7272,13 @@ impl WindowManager {
                     }
                     last_time = e.time;
 
-                    let mut new_x = orig_x as i32 + (e.root_x as i32 - start_x);
-                    let mut new_y = orig_y as i32 + (e.root_y as i32 - start_y);
+                    let new_x = orig_x as i32 + (e.root_x as i32 - start_x);
+                    let new_y = orig_y as i32 + (e.root_y as i32 - start_y);
 
-                    if (monitor.window_area_x - new_x).abs() < snap {
-                        new_x = monitor.window_area_x;
-                    } else if ((monitor.window_area_x + monitor.window_area_width)
-                        - (new_x + width as i32))
-                        .abs()
-                        < snap
-                    {
-                        new_x = monitor.window_area_x + monitor.window_area_width - width as i32;
-                    }
-
-                    if (monitor.window_area_y - new_y).abs() < snap {
-                        new_y = monitor.window_area_y;
-                    } else if ((monitor.window_area_y + monitor.window_area_height)
-                        - (new_y + height as i32))
-                        .abs()
-                        < snap
-                    {
-                        new_y = monitor.window_area_y + monitor.window_area_height - height as i32;
-                    }
+                    let x_edges = snap_edges(&targets, true, (new_y, outer_height), snap);
+                    let y_edges = snap_edges(&targets, false, (new_x, outer_width), snap);
+                    let new_x = snap_position(new_x, outer_width, &x_edges, snap);
+                    let new_y = snap_position(new_y, outer_height, &y_edges, snap);
 
                     let should_resize = is_normie
                         || self
@@ -2990,6 +7308,7 @@ impl WindowManager {
         self.connection
             .ungrab_pointer(x11rb::CURRENT_TIME)?
             .check()?;
+        self.remember_floating_geometry(window);
 
         let final_client = self
             .clients
@@ -3027,7 +7346,44 @@ impl WindowManager {
             self.apply_layout()?;
         }
 
-        Ok(())
+        Ok(())
+    }
+
+    /// Outer rectangles a window being moved or resized snaps to: the
+    /// monitor's window area, then every other visible floating window on it.
+    fn snap_targets(&self, exclude: Window, monitor_idx: usize) -> Vec<(i32, i32, i32, i32)> {
+        let Some(monitor) = self.monitors.get(monitor_idx) else {
+            return Vec::new();
+        };
+
+        let mut targets = vec![(
+            monitor.window_area_x,
+            monitor.window_area_y,
+            monitor.window_area_width,
+            monitor.window_area_height,
+        )];
+
+        let tags = monitor.tagset[monitor.selected_tags_index];
+        let mut current = monitor.clients_head;
+        while let Some(win) = current {
+            let Some(c) = self.clients.get(&win) else {
+                break;
+            };
+            current = c.next;
+
+            if win == exclude || !c.is_floating || c.is_fullscreen || (c.tags & tags) == 0 {
+                continue;
+            }
+
+            let border = c.border_width as i32 * 2;
+            targets.push((
+                c.x_position as i32,
+                c.y_position as i32,
+                c.width as i32 + border,
+                c.height as i32 + border,
+            ));
+        }
+        targets
     }
 
     fn tiled_window_at(
@@ -3157,6 +7513,9 @@ impl WindowManager {
             self.toggle_floating()?;
         }
 
+        let snap = self.config.snap_distance as i32;
+        let targets = self.snap_targets(window, monitor_idx);
+
         self.connection.warp_pointer(
             x11rb::NONE,
             window,
@@ -3193,12 +7552,18 @@ impl WindowManager {
                     }
                     last_time = e.time;
 
-                    let new_width = ((e.root_x as i32 - orig_x as i32 - 2 * border_width as i32
-                        + 1)
-                    .max(1)) as u32;
-                    let new_height = ((e.root_y as i32 - orig_y as i32 - 2 * border_width as i32
-                        + 1)
-                    .max(1)) as u32;
+                    let border = 2 * border_width as i32;
+                    let (x, y) = (orig_x as i32, orig_y as i32);
+                    let right = (e.root_x as i32 + 1).max(x + border + 1);
+                    let bottom = (e.root_y as i32 + 1).max(y + border + 1);
+
+                    let x_edges = snap_edges(&targets, true, (y, bottom - y), snap);
+                    let y_edges = snap_edges(&targets, false, (x, right - x), snap);
+                    let right = snap_edge(right, &x_edges, snap).max(x + border + 1);
+                    let bottom = snap_edge(bottom, &y_edges, snap).max(y + border + 1);
+
+                    let new_width = (right - x - border) as u32;
+                    let new_height = (bottom - y - border) as u32;
 
                     let should_resize = is_normie
                         || self
@@ -3253,6 +7618,7 @@ impl WindowManager {
         self.connection
             .ungrab_pointer(x11rb::CURRENT_TIME)?
             .check()?;
+        self.remember_floating_geometry(window);
 
         let final_client_pos = self
             .clients
@@ -3281,6 +7647,10 @@ impl WindowManager {
 
     fn handle_event(&mut self, event: Event) -> WmResult<Control> {
         match event {
+            Event::ButtonPress(_) | Event::ButtonRelease(_) if self.click_pick.is_some() => {
+                self.handle_click_pick(&event)?;
+                return Ok(Control::Continue);
+            }
             Event::KeyPress(ref key_event) if key_event.event == self.overlay.window() => {
                 if self.overlay.is_visible()
                     && let Err(error) = self.overlay.hide(&self.connection)
@@ -3305,6 +7675,18 @@ impl WindowManager {
                 }
                 return Ok(Control::Continue);
             }
+            Event::ButtonPress(ref e) if e.event == self.info_overlay.window() => {
+                if let Err(error) = self.info_overlay.hide(&self.connection) {
+                    eprintln!("Failed to hide info overlay: {:?}", error);
+                }
+                return Ok(Control::Continue);
+            }
+            Event::Expose(ref e) if e.window == self.info_overlay.window() => {
+                if let Err(error) = self.info_overlay.draw(&self.connection, &self.font) {
+                    eprintln!("Failed to draw info overlay: {:?}", error);
+                }
+                return Ok(Control::Continue);
+            }
             Event::KeyPress(ref e) if e.event == self.keybind_overlay.window() => {
                 if self.keybind_overlay.is_visible()
                     && !self.keybind_overlay.should_suppress_input()
@@ -3348,16 +7730,60 @@ impl WindowManager {
                     return Ok(Control::Continue);
                 }
 
+                if self.is_input_method_window(event.window, true) {
+                    self.backend().map_window(event.window)?;
+                    self.input_method_windows.insert(event.window);
+                    self.raise_input_method_windows()?;
+                    return Ok(Control::Continue);
+                }
+
+                if self.is_desktop_window(event.window) {
+                    self.backend().map_window(event.window)?;
+                    self.desktop_windows.insert(event.window);
+                    self.lower_desktop_windows()?;
+                    return Ok(Control::Continue);
+                }
+
+                if self.is_dock_window(event.window) {
+                    self.backend().map_window(event.window)?;
+                    self.add_dock(event.window)?;
+                    self.place_bars()?;
+                    self.apply_layout()?;
+                    return Ok(Control::Continue);
+                }
+
                 if !self.windows.contains(&event.window) {
                     self.manage_window(event.window)?;
                 }
             }
+            Event::MapNotify(event)
+                if event.override_redirect && self.is_input_method_window(event.window, false) =>
+            {
+                self.input_method_windows.insert(event.window);
+                self.raise_input_method_windows()?;
+            }
             Event::UnmapNotify(event) => {
+                self.input_method_windows.remove(&event.window);
+                self.desktop_windows.remove(&event.window);
+                if self.dock_windows.remove(&event.window).is_some() {
+                    self.update_struts();
+                    self.place_bars()?;
+                    self.apply_layout()?;
+                }
                 if self.windows.contains(&event.window) && self.is_window_visible(event.window) {
                     self.remove_window(event.window, false)?;
                 }
             }
             Event::DestroyNotify(event) => {
+                self.swallowed
+                    .retain(|_, terminal| terminal.window != event.window);
+                self.input_method_windows.remove(&event.window);
+                self.desktop_windows.remove(&event.window);
+                if self.dock_windows.remove(&event.window).is_some() {
+                    self.update_struts();
+                    self.place_bars()?;
+                    self.apply_layout()?;
+                }
                 if self.windows.contains(&event.window) {
                     self.remove_window(event.window, true)?;
                 }
@@ -3367,6 +7793,18 @@ impl WindowManager {
                     return Ok(Control::Continue);
                 }
 
+                if (event.atom == self.atoms.net_wm_strut
+                    || event.atom == self.atoms.net_wm_strut_partial)
+                    && self.dock_windows.contains_key(&event.window)
+                {
+                    let strut = self.read_strut(event.window);
+                    self.dock_windows.insert(event.window, strut);
+                    self.update_struts();
+                    self.place_bars()?;
+                    self.apply_layout()?;
+                    return Ok(Control::Continue);
+                }
+
                 if !self.clients.contains_key(&event.window) {
                     return Ok(Control::Continue);
                 }
@@ -3398,9 +7836,20 @@ impl WindowManager {
 
                 if event.atom == self.atoms.wm_name || event.atom == self.atoms.net_wm_name {
                     let _ = self.update_window_title(event.window);
+                    self.mark_activity(event.window);
+                    self.retag_on_title(event.window)?;
                     if self.layout.name() == "tabbed" {
                         self.update_tab_bars()?;
                     }
+                    // The focused title feeds the bar and context blocks.
+                    if self
+                        .monitors
+                        .get(self.selected_monitor)
+                        .and_then(|m| m.selected_client)
+                        == Some(event.window)
+                    {
+                        self.update_bar()?;
+                    }
                 }
 
                 if event.atom == self.atoms.net_wm_window_type {
@@ -3413,6 +7862,14 @@ impl WindowManager {
                 {
                     return Ok(Control::Continue);
                 }
+                if let Some(&(monitor_index, _)) = self
+                    .bar_triggers
+                    .iter()
+                    .find(|(_, trigger)| trigger.window() == event.event)
+                {
+                    self.reveal_bars(monitor_index)?;
+                    return Ok(Control::Continue);
+                }
                 if self.windows.contains(&event.event) {
                     if let Some(client) = self.clients.get(&event.event)
                         && client.monitor_index != self.selected_monitor
@@ -3459,9 +7916,18 @@ impl WindowManager {
                     return Ok(Control::Continue);
                 };
 
+                // Escape leaves the active mode once no chord is pending.
+                if self.active_mode.is_some()
+                    && matches!(self.keychord_state, keyboard::handlers::KeychordState::Idle)
+                    && mapping.keycode_to_keysym(event.detail) == keyboard::keysyms::XK_ESCAPE
+                {
+                    self.exit_mode()?;
+                    return Ok(Control::Continue);
+                }
+
                 let result = keyboard::handle_key_press(
                     event,
-                    &self.config.keybindings,
+                    self.active_keybindings(),
                     &self.keychord_state,
                     mapping,
                 );
@@ -3475,41 +7941,9 @@ impl WindowManager {
 
                         match action {
                             KeyAction::Quit => return Ok(Control::Quit),
-                            KeyAction::Restart => match self.try_reload_config() {
-                                Ok(()) => {
-                                    self.gaps_enabled = self.config.gaps_enabled;
-                                    self.error_message = None;
-                                    if let Err(error) = self.overlay.hide(&self.connection) {
-                                        eprintln!(
-                                            "Failed to hide overlay after config reload: {:?}",
-                                            error
-                                        );
-                                    }
-                                    self.apply_layout()?;
-                                    self.update_bar()?;
-                                }
-                                Err(err) => {
-                                    eprintln!("Config reload error: {}", err);
-                                    self.error_message = Some(err.to_string());
-                                    let monitor = &self.monitors[self.selected_monitor];
-                                    let monitor_x = monitor.screen_info.x as i16;
-                                    let monitor_y = monitor.screen_info.y as i16;
-                                    let screen_width = monitor.screen_info.width as u16;
-                                    let screen_height = monitor.screen_info.height as u16;
-                                    match self.overlay.show_error(
-                                        &self.connection,
-                                        &self.font,
-                                        err,
-                                        monitor_x,
-                                        monitor_y,
-                                        screen_width,
-                                        screen_height,
-                                    ) {
-                                        Ok(()) => eprintln!("Error modal displayed"),
-                                        Err(e) => eprintln!("Failed to show error modal: {:?}", e),
-                                    }
-                                }
-                            },
+                            KeyAction::Restart => {
+                                let _ = self.reload()?;
+                            }
                             _ => self.handle_key_action(action, &arg)?,
                         }
                     }
@@ -3538,6 +7972,11 @@ impl WindowManager {
                 {
                     eprintln!("Failed to hide keybind overlay: {:?}", error);
                 }
+                if self.info_overlay.is_visible()
+                    && let Err(error) = self.info_overlay.hide(&self.connection)
+                {
+                    eprintln!("Failed to hide info overlay: {:?}", error);
+                }
 
                 let is_bar_click = self
                     .bars
@@ -3546,11 +7985,49 @@ impl WindowManager {
                     .find(|(_, bar)| bar.window() == event.event);
 
                 if let Some((monitor_index, bar)) = is_bar_click {
-                    if let Some(tag_index) = bar.handle_click(event.event_x) {
-                        if monitor_index != self.selected_monitor {
-                            self.selected_monitor = monitor_index;
+                    let dead_zone = self.config.bar_dead_zone as i16;
+                    let clicked_block = bar.block_at(event.event_x).filter(|&index| {
+                        self.block_handles_click(monitor_index, index, event.detail)
+                    });
+                    if let Some(index) = clicked_block {
+                        self.block_manager
+                            .click(bar.block_set(), index, event.detail);
+                        self.bars[monitor_index].handle_click(event.event_x, event.detail);
+                        self.update_bar()?;
+                        return Ok(Control::Continue);
+                    }
+                    let scroll = match ButtonIndex::from(event.detail) {
+                        ButtonIndex::M4 => Some(-1),
+                        ButtonIndex::M5 => Some(1),
+                        _ => None,
+                    };
+                    if let Some(direction) = scroll {
+                        if let Some(region) = bar.region_at(event.event_x) {
+                            self.handle_bar_scroll(monitor_index, region, direction)?;
+                        }
+                    } else if let Some(region) = bar.clicked_region(event.event_x, dead_zone) {
+                        if event.detail == u8::from(ButtonIndex::M1) {
+                            self.handle_bar_click(monitor_index, region)?;
+                        } else if let BarRegion::Tag(tag_index) = region {
+                            if monitor_index != self.selected_monitor {
+                                self.selected_monitor = monitor_index;
+                            }
+                            self.view_tag(tag_index)?;
                         }
-                        self.view_tag(tag_index)?;
+                    }
+                } else if let Some(bar) = self
+                    .second_bars
+                    .iter_mut()
+                    .flatten()
+                    .find(|bar| bar.window() == event.event)
+                {
+                    // Second bars only hold blocks; popups and bar click
+                    // bindings belong to the main bar.
+                    if let Some(index) = bar.block_at(event.event_x) {
+                        self.block_manager
+                            .click(bar.block_set(), index, event.detail);
+                        bar.handle_click(event.event_x, event.detail);
+                        self.update_bar()?;
                     }
                 } else {
                     let is_tab_bar_click = self
@@ -3591,10 +8068,7 @@ impl WindowManager {
                         if let Some(clicked_window) =
                             tab_bar.get_clicked_window(&visible_windows, event.event_x)
                         {
-                            self.connection.configure_window(
-                                clicked_window,
-                                &ConfigureWindowAux::new().stack_mode(StackMode::ABOVE),
-                            )?;
+                            self.backend().raise_window(clicked_window)?;
                             self.focus(Some(clicked_window))?;
                             self.update_tab_bars()?;
                         }
@@ -3651,7 +8125,11 @@ impl WindowManager {
                 }
             }
             Event::Expose(event) => {
-                for bar in &mut self.bars {
+                for bar in self
+                    .bars
+                    .iter_mut()
+                    .chain(self.second_bars.iter_mut().flatten())
+                {
                     if event.window == bar.window() {
                         bar.invalidate();
                         self.update_bar()?;
@@ -3667,34 +8145,41 @@ impl WindowManager {
             }
             Event::ConfigureRequest(event) => {
                 if let Some(client) = self.clients.get(&event.window) {
-                    let monitor = &self.monitors[client.monitor_index];
+                    let monitor_index = client.monitor_index;
                     let is_floating = client.is_floating;
                     let is_fullscreen = client.is_fullscreen;
+                    let is_below = client.is_below;
                     let has_layout = self.layout.name() != "normie";
 
-                    if event.value_mask.contains(ConfigWindow::BORDER_WIDTH) {
-                        if let Some(c) = self.clients.get_mut(&event.window) {
-                            c.border_width = event.border_width;
-                        }
-                    } else if is_fullscreen {
+                    let moves = event.value_mask.contains(ConfigWindow::X)
+                        || event.value_mask.contains(ConfigWindow::Y);
+                    let resizes = event.value_mask.contains(ConfigWindow::WIDTH)
+                        || event.value_mask.contains(ConfigWindow::HEIGHT);
+
+                    if is_fullscreen || (!is_floating && has_layout) || !(moves || resizes) {
+                        // Tiled and fullscreen clients keep the geometry the
+                        // layout gave them, and the border width is ours to
+                        // choose; just tell the client where it really is.
                         self.send_configure_notify(event.window)?;
-                    } else if is_floating || !has_layout {
+                    } else {
                         let mut x = client.x_position as i32;
                         let mut y = client.y_position as i32;
                         let mut w = client.width as i32;
                         let mut h = client.height as i32;
 
+                        // Coordinates in a ConfigureRequest are relative to
+                        // the root window, whichever monitor the client is on.
                         if event.value_mask.contains(ConfigWindow::X) {
                             if let Some(c) = self.clients.get_mut(&event.window) {
                                 c.old_x_position = c.x_position;
                             }
-                            x = monitor.screen_info.x + event.x as i32;
+                            x = event.x as i32;
                         }
                         if event.value_mask.contains(ConfigWindow::Y) {
                             if let Some(c) = self.clients.get_mut(&event.window) {
                                 c.old_y_position = c.y_position;
                             }
-                            y = monitor.screen_info.y + event.y as i32;
+                            y = event.y as i32;
                         }
                         if event.value_mask.contains(ConfigWindow::WIDTH) {
                             if let Some(c) = self.clients.get_mut(&event.window) {
@@ -3709,6 +8194,14 @@ impl WindowManager {
                             h = event.height as i32;
                         }
 
+                        if resizes {
+                            let (_, _, hinted_w, hinted_h, _) =
+                                self.apply_size_hints(event.window, x, y, w, h);
+                            w = hinted_w;
+                            h = hinted_h;
+                        }
+
+                        let monitor = &self.monitors[monitor_index];
                         let bw = self.config.border_width as i32;
                         let width_with_border = w + 2 * bw;
                         let height_with_border = h + 2 * bw;
@@ -3733,11 +8226,7 @@ impl WindowManager {
                             c.height = h as u16;
                         }
 
-                        let only_position_change = event.value_mask.contains(ConfigWindow::X)
-                            || event.value_mask.contains(ConfigWindow::Y);
-                        let no_size_change = !event.value_mask.contains(ConfigWindow::WIDTH)
-                            && !event.value_mask.contains(ConfigWindow::HEIGHT);
-                        if only_position_change && no_size_change {
+                        if moves && !resizes {
                             self.send_configure_notify(event.window)?;
                         }
 
@@ -3750,9 +8239,30 @@ impl WindowManager {
                                     .width(w as u32)
                                     .height(h as u32),
                             )?;
+                            if resizes {
+                                self.update_window_shape(
+                                    event.window,
+                                    w as u16,
+                                    h as u16,
+                                    bw as u16,
+                                )?;
+                            }
                         }
-                    } else {
-                        self.send_configure_notify(event.window)?;
+                    }
+
+                    // Restacking is left to the layout: a floating client may
+                    // only raise itself, and never relative to a sibling.
+                    // Keep-below clients stay where they are.
+                    if event.value_mask.contains(ConfigWindow::STACK_MODE)
+                        && !event.value_mask.contains(ConfigWindow::SIBLING)
+                        && event.stack_mode == StackMode::ABOVE
+                        && is_floating
+                        && !is_fullscreen
+                        && !is_below
+                        && self.is_visible(event.window)
+                    {
+                        self.backend().raise_window(event.window)?;
+                        self.raise_input_method_windows()?;
                     }
                 } else {
                     let mut aux = ConfigureWindowAux::new();
@@ -3771,10 +8281,12 @@ impl WindowManager {
                     if event.value_mask.contains(ConfigWindow::BORDER_WIDTH) {
                         aux = aux.border_width(event.border_width as u32);
                     }
-                    if event.value_mask.contains(ConfigWindow::SIBLING) {
+                    // Desktop windows stay at the bottom whatever they ask for.
+                    let is_desktop = self.desktop_windows.contains(&event.window);
+                    if event.value_mask.contains(ConfigWindow::SIBLING) && !is_desktop {
                         aux = aux.sibling(event.sibling);
                     }
-                    if event.value_mask.contains(ConfigWindow::STACK_MODE) {
+                    if event.value_mask.contains(ConfigWindow::STACK_MODE) && !is_desktop {
                         aux = aux.stack_mode(event.stack_mode);
                     }
                     self.connection.configure_window(event.window, &aux)?;
@@ -3782,6 +8294,20 @@ impl WindowManager {
                 self.connection.flush()?;
             }
             Event::ClientMessage(event) => {
+                if event.type_ == self.atoms.net_showing_desktop {
+                    let show = event.data.as_data32()[0] != 0;
+                    self.set_showing_desktop(show)?;
+                    return Ok(Control::Continue);
+                }
+
+                if event.type_ == self.atoms.net_current_desktop {
+                    let desktop = event.data.as_data32()[0] as usize;
+                    if desktop < self.config.tags.len() {
+                        self.view_tag(desktop)?;
+                    }
+                    return Ok(Control::Continue);
+                }
+
                 if !self.clients.contains_key(&event.window) {
                     return Ok(Control::Continue);
                 }
@@ -3804,7 +8330,54 @@ impl WindowManager {
                         self.set_window_fullscreen(event.window, fullscreen)?;
                         self.restack()?;
                     }
+
+                    let client_state = self
+                        .clients
+                        .get(&event.window)
+                        .map(|c| (c.is_maximized_vertical, c.is_maximized_horizontal));
+                    if let Some((is_vertical, is_horizontal)) = client_state {
+                        let apply = |current: bool| match data[0] {
+                            1 => Some(true),
+                            0 => Some(false),
+                            2 => Some(!current),
+                            _ => None,
+                        };
+                        let has = |atom: Atom| atom1 == atom || atom2 == atom;
+                        let vertical = has(self.atoms.net_wm_state_maximized_vert)
+                            .then(|| apply(is_vertical))
+                            .flatten();
+                        let horizontal = has(self.atoms.net_wm_state_maximized_horz)
+                            .then(|| apply(is_horizontal))
+                            .flatten();
+                        if vertical.is_some() || horizontal.is_some() {
+                            self.set_window_maximized(event.window, vertical, horizontal)?;
+                        }
+                    }
+
+                    if atom1 == self.atoms.net_wm_state_below
+                        || atom2 == self.atoms.net_wm_state_below
+                    {
+                        let is_below = self.clients.get(&event.window).is_some_and(|c| c.is_below);
+                        let below = match data[0] {
+                            1 => true,
+                            0 => false,
+                            2 => !is_below,
+                            _ => return Ok(Control::Continue),
+                        };
+                        self.set_window_below(event.window, below)?;
+                    }
+                } else if event.type_ == self.atoms.net_wm_desktop {
+                    self.set_client_desktop(event.window, event.data.as_data32()[0])?;
                 } else if event.type_ == self.atoms.net_active_window {
+                    // Pagers and window switchers ask with source 2; other
+                    // clients only get marked urgent.
+                    if event.data.as_data32()[0] == 2 {
+                        if let Err(e) = self.reveal_window(event.window)? {
+                            eprintln!("Failed to activate window: {}", e);
+                        }
+                        return Ok(Control::Continue);
+                    }
+
                     let selected_window = self
                         .monitors
                         .get(self.selected_monitor)
@@ -3843,12 +8416,36 @@ impl WindowManager {
                     }
                 }
             }
-            Event::MappingNotify(event) => {
-                if event.request == x11rb::protocol::xproto::Mapping::KEYBOARD {
-                    self.grab_keys()?;
-                }
+            Event::MappingNotify(event)
+                if event.request != x11rb::protocol::xproto::Mapping::POINTER =>
+            {
+                self.keymap_changed = true;
+            }
+            Event::XkbNewKeyboardNotify(_) | Event::XkbMapNotify(_) => {
+                self.keymap_changed = true;
+            }
+            Event::XkbBellNotify(event) => {
+                self.handle_bell(event.window)?;
+            }
+            Event::SelectionRequest(event) => {
+                self.handle_selection_request(event)?;
+            }
+            Event::SelectionClear(event) if event.selection == self.atoms.clipboard => {
+                self.clipboard_text = None;
+            }
+            Event::RandrScreenChangeNotify(_) | Event::RandrNotify(_) => {
+                self.handle_output_change()?;
             }
             Event::ConfigureNotify(event) => {
+                if self.clients.contains_key(&event.window) {
+                    self.update_window_shape(
+                        event.window,
+                        event.width,
+                        event.height,
+                        event.border_width,
+                    )?;
+                }
+
                 if event.window == self.root {
                     let old_width = self.screen.width_in_pixels;
                     let old_height = self.screen.height_in_pixels;
@@ -3910,26 +8507,12 @@ impl WindowManager {
                 let monitor = &self.monitors[monitor_index];
                 let border_width = self.config.border_width;
 
-                let gaps = if self.gaps_enabled {
-                    GapConfig {
-                        inner_horizontal: self.config.gap_inner_horizontal,
-                        inner_vertical: self.config.gap_inner_vertical,
-                        outer_horizontal: self.config.gap_outer_horizontal,
-                        outer_vertical: self.config.gap_outer_vertical,
-                    }
-                } else {
-                    GapConfig {
-                        inner_horizontal: 0,
-                        inner_vertical: 0,
-                        outer_horizontal: 0,
-                        outer_vertical: 0,
-                    }
-                };
+                let gaps = monitor.gaps.active();
 
-                let monitor_x = monitor.screen_info.x;
-                let monitor_y = monitor.screen_info.y;
-                let monitor_width = monitor.screen_info.width;
-                let monitor_height = monitor.screen_info.height;
+                let monitor_x = monitor.window_area_x;
+                let monitor_y = monitor.window_area_y;
+                let monitor_width = monitor.window_area_width;
+                let monitor_height = monitor.window_area_height;
                 let scroll_offset = monitor.scroll_offset;
 
                 let mut visible: Vec<Window> = Vec::new();
@@ -3943,18 +8526,11 @@ impl WindowManager {
                     }
                 }
 
-                let bar_height = if self.show_bar {
-                    self.bars
-                        .get(monitor_index)
-                        .map(|bar| bar.height() as u32)
-                        .unwrap_or(0)
-                } else {
-                    0
-                };
-                let usable_height = monitor_height.saturating_sub(bar_height as i32);
+                let (bar_top, bar_bottom) = self.bar_space(monitor_index);
+                let usable_height = monitor_height.saturating_sub((bar_top + bar_bottom) as i32);
                 let master_factor = monitor.master_factor;
                 let num_master = monitor.num_master;
-                let smartgaps_enabled = self.config.smartgaps_enabled;
+                let smartgaps_enabled = monitor.gaps.smart;
 
                 let geometries = self.layout.arrange(
                     &visible,
@@ -3990,7 +8566,7 @@ impl WindowManager {
                     } else {
                         geometry.x_coordinate + monitor_x
                     };
-                    let adjusted_y = geometry.y_coordinate + monitor_y + bar_height as i32;
+                    let adjusted_y = geometry.y_coordinate + monitor_y + bar_top as i32;
 
                     if let Some(client) = self.clients.get_mut(window) {
                         client.x_position = adjusted_x as i16;
@@ -4029,15 +8605,9 @@ impl WindowManager {
             let monitor = &self.monitors[monitor_index];
             let tags = monitor.tagset[monitor.selected_tags_index];
 
-            let has_visible_fullscreen = self.fullscreen_windows.iter().any(|&w| {
-                self.clients
-                    .get(&w)
-                    .is_some_and(|c| c.monitor_index == monitor_index && (c.tags & tags) != 0)
-            });
-
-            if has_visible_fullscreen {
-                if let Some(bar) = self.bars.get(monitor_index) {
-                    self.connection.unmap_window(bar.window())?;
+            if self.has_visible_fullscreen(monitor_index) {
+                for bar in self.monitor_bars(monitor_index) {
+                    self.backend().unmap_window(bar.window())?;
                 }
 
                 for &window in &self.fullscreen_windows {
@@ -4057,10 +8627,14 @@ impl WindowManager {
                         )?;
                     }
                 }
-            } else if self.show_bar
-                && let Some(bar) = self.bars.get(monitor_index)
-            {
-                self.connection.map_window(bar.window())?;
+            } else if self.bars_autohidden(monitor_index) {
+                for bar in self.monitor_bars(monitor_index) {
+                    self.backend().unmap_window(bar.window())?;
+                }
+            } else if monitor.show_bar {
+                for bar in self.monitor_bars(monitor_index) {
+                    self.backend().map_window(bar.window())?;
+                }
             }
         }
 
@@ -4069,34 +8643,18 @@ impl WindowManager {
         let is_tabbed = self.layout.name() == LayoutType::Tabbed.as_str();
 
         if is_tabbed {
-            let outer_horizontal = if self.gaps_enabled {
-                self.config.gap_outer_horizontal
-            } else {
-                0
-            };
-            let outer_vertical = if self.gaps_enabled {
-                self.config.gap_outer_vertical
-            } else {
-                0
-            };
-
             for monitor_index in 0..self.tab_bars.len() {
                 if let Some(monitor) = self.monitors.get(monitor_index) {
-                    let bar_height = if self.show_bar {
-                        self.bars
-                            .get(monitor_index)
-                            .map(|bar| bar.height() as f32)
-                            .unwrap_or(0.0)
-                    } else {
-                        0.0
-                    };
+                    let gaps = monitor.gaps.active();
+                    let (outer_horizontal, outer_vertical) =
+                        (gaps.outer_horizontal, gaps.outer_vertical);
+                    let (bar_top, _) = self.bar_space(monitor_index);
 
-                    let tab_bar_x = (monitor.screen_info.x + outer_horizontal as i32) as i16;
+                    let tab_bar_x = (monitor.window_area_x + outer_horizontal as i32) as i16;
                     let tab_bar_y =
-                        (monitor.screen_info.y as f32 + bar_height + outer_vertical as f32) as i16;
+                        (monitor.window_area_y + bar_top as i32 + outer_vertical as i32) as i16;
                     let tab_bar_width = monitor
-                        .screen_info
-                        .width
+                        .window_area_width
                         .saturating_sub(2 * outer_horizontal as i32)
                         as u16;
 
@@ -4383,16 +8941,49 @@ impl WindowManager {
         Ok(())
     }
 
+    /// Whether a new window should float because it can't be resized or is
+    /// too small to tile usefully. A matching rule with `floating = false`
+    /// keeps it tiled.
+    fn floats_by_heuristic(&self, client: &Client) -> bool {
+        let tiled_by_rule = self
+            .config
+            .window_rules
+            .iter()
+            .filter(|rule| rule.matches(&client.class, &client.instance, &client.name))
+            .filter_map(|rule| rule.is_floating)
+            .next_back()
+            == Some(false);
+        if tiled_by_rule {
+            return false;
+        }
+
+        let area = client.width as u32 * client.height as u32;
+        (self.config.float_fixed_size && client.is_fixed)
+            || (self.config.float_below_area > 0 && area < self.config.float_below_area)
+    }
+
     fn update_window_type(&mut self, window: Window) -> WmResult<()> {
-        if let Ok(state_atoms) = self.get_window_atom_list_property(window, self.atoms.net_wm_state)
-            && state_atoms.contains(&self.atoms.net_wm_state_fullscreen)
-        {
+        let state_atoms = self
+            .get_window_atom_list_property(window, self.atoms.net_wm_state)
+            .unwrap_or_default();
+        if state_atoms.contains(&self.atoms.net_wm_state_fullscreen) {
             self.set_window_fullscreen(window, true)?;
         }
 
+        let vertical = state_atoms.contains(&self.atoms.net_wm_state_maximized_vert);
+        let horizontal = state_atoms.contains(&self.atoms.net_wm_state_maximized_horz);
+        if vertical || horizontal {
+            self.set_window_maximized(window, Some(vertical), Some(horizontal))?;
+        }
+        if state_atoms.contains(&self.atoms.net_wm_state_below) {
+            self.set_window_below(window, true)?;
+        }
+
         if let Ok(Some(type_atom)) =
             self.get_window_atom_property(window, self.atoms.net_wm_window_type)
-            && type_atom == self.atoms.net_wm_window_type_dialog
+            && (type_atom == self.atoms.net_wm_window_type_dialog
+                || (self.config.float_utility
+                    && type_atom == self.atoms.net_wm_window_type_utility))
         {
             if let Some(client) = self.clients.get_mut(&window) {
                 client.is_floating = true;
@@ -4723,6 +9314,14 @@ impl WindowManager {
     fn remove_window(&mut self, window: Window, destroyed: bool) -> WmResult<()> {
         let initial_count = self.windows.len();
 
+        self.closing_windows.remove(&window);
+        self.block_popups.retain(|_, popup| *popup != window);
+        self.scratchpad_windows
+            .retain(|_, scratchpad| *scratchpad != window);
+        if self.fade_animations.cancel(window) && !destroyed {
+            self.set_opacity(window, 1.0)?;
+        }
+
         let focused = self
             .monitors
             .get(self.selected_monitor)
@@ -4739,7 +9338,10 @@ impl WindowManager {
             self.set_wm_state(window, 0)?;
         }
 
+        let terminal = self.unswallow(window)?;
+
         if self.clients.contains_key(&window) {
+            self.remember_floating_geometry(window);
             self.detach(window);
             self.detach_stack(window);
             self.clients.remove(&window);
@@ -4749,10 +9351,10 @@ impl WindowManager {
         self.floating_windows.remove(&window);
         self.update_client_list()?;
 
-        if self.windows.len() < initial_count {
+        if self.windows.len() < initial_count || terminal.is_some() {
             if focused == Some(window) {
                 let visible = self.visible_windows_on_monitor(self.selected_monitor);
-                if let Some(&new_win) = visible.last() {
+                if let Some(new_win) = terminal.or(visible.last().copied()) {
                     self.focus(Some(new_win))?;
                     if self.layout.name() == "scrolling" {
                         self.scroll_to_window(new_win, true)?;
@@ -4768,36 +9370,283 @@ impl WindowManager {
         Ok(())
     }
 
+    /// The terminal a new window swallows: the closest terminal among the
+    /// ancestors of the process that opened it. Floating windows, terminals
+    /// and windows whose rule turns swallowing off don't swallow.
+    fn swallow_target(&self, window: Window) -> Option<Window> {
+        if !self.config.swallow {
+            return None;
+        }
+        let client = self.clients.get(&window)?;
+        if client.is_terminal || client.no_swallow || client.is_floating {
+            return None;
+        }
+
+        let terminals: Vec<(Window, u32)> = self
+            .clients
+            .values()
+            .filter(|c| c.is_terminal && c.window != window)
+            .filter_map(|c| Some((c.window, self.get_window_pid(c.window)?)))
+            .collect();
+        if terminals.is_empty() {
+            return None;
+        }
+
+        let mut ancestor = crate::signal::parent_pid(self.get_window_pid(window)?);
+        while let Some(pid) = ancestor.filter(|&pid| pid > 1) {
+            if let Some(&(terminal, _)) = terminals.iter().find(|&&(_, p)| p == pid) {
+                return Some(terminal);
+            }
+            ancestor = crate::signal::parent_pid(pid);
+        }
+        None
+    }
+
+    /// Puts `window` in the terminal's place and hides the terminal until
+    /// the window closes.
+    fn swallow(&mut self, terminal: Window, window: Window) -> WmResult<()> {
+        let Some(monitor_index) = self.clients.get(&terminal).map(|c| c.monitor_index) else {
+            return Ok(());
+        };
+
+        self.detach(window);
+        self.insert_before(window, terminal, monitor_index);
+        self.detach(terminal);
+        self.detach_stack(terminal);
+        self.windows.retain(|&w| w != terminal);
+        self.floating_windows.remove(&terminal);
+        if let Some(client) = self.clients.remove(&terminal) {
+            self.swallowed.insert(window, client);
+        }
+
+        // Iconic keeps the terminal's WM_STATE, so a restart manages it again.
+        self.set_wm_state(terminal, 3)?;
+        self.backend().unmap_window(terminal)?;
+        self.update_client_list()?;
+        Ok(())
+    }
+
+    /// Brings back the terminal `window` swallowed, in the window's place
+    /// and on its tags.
+    fn unswallow(&mut self, window: Window) -> WmResult<Option<Window>> {
+        let Some(mut terminal) = self.swallowed.remove(&window) else {
+            return Ok(None);
+        };
+        let Some((monitor_index, tags)) =
+            self.clients.get(&window).map(|c| (c.monitor_index, c.tags))
+        else {
+            return Ok(None);
+        };
+
+        let terminal_window = terminal.window;
+        terminal.monitor_index = monitor_index;
+        terminal.tags = tags;
+        terminal.next = None;
+        terminal.stack_next = None;
+        if terminal.is_floating {
+            self.floating_windows.insert(terminal_window);
+        }
+        self.clients.insert(terminal_window, terminal);
+        self.insert_before(terminal_window, window, monitor_index);
+        self.attach_stack(terminal_window, monitor_index);
+        self.windows.push(terminal_window);
+
+        self.set_wm_state(terminal_window, 1)?;
+        self.backend().map_window(terminal_window)?;
+        Ok(Some(terminal_window))
+    }
+
     fn get_selected_monitor(&self) -> &Monitor {
         &self.monitors[self.selected_monitor]
     }
 
-    fn has_windows_on_tag(&self, monitor_number: usize, tag_index: usize) -> bool {
-        let Some(monitor) = self.monitors.get(monitor_number) else {
-            return false;
-        };
+    /// Whether any window on the monitor is on the tag, counting windows on
+    /// several tags for each of them, the same way the bar shows occupancy.
+    fn has_windows_on_tag(&self, monitor_index: usize, tag_index: usize) -> bool {
+        let (occupied_tags, _) = tag_occupancy(&self.clients, monitor_index);
+        occupied_tags & tag_mask(tag_index) != 0
+    }
 
-        let mut current = monitor.clients_head;
-        while let Some(window) = current {
-            let Some(client) = self.clients.get(&window) else {
-                break;
-            };
+    fn run_autostart_commands(&self) {
+        if self.config.systemd_autostart_units {
+            crate::systemd::import_environment();
+        }
+
+        for (index, command) in self.config.autostart.iter().enumerate() {
+            if self.config.systemd_autostart_units {
+                crate::systemd::spawn_transient_unit(command, index);
+            } else {
+                crate::signal::spawn_detached(command);
+            }
+            eprintln!("[autostart] Spawned: {}", command);
+        }
 
-            if unmask_tag(client.tags) == tag_index {
-                return true;
+        if !self.config.xdg_autostart {
+            return;
+        }
+
+        let offset = self.config.autostart.len();
+        for (index, entry) in crate::xdg_autostart::entries().iter().enumerate() {
+            if self.config.systemd_autostart_units {
+                crate::systemd::spawn_transient_unit(&entry.exec, offset + index);
+            } else {
+                crate::signal::spawn_detached(&entry.exec);
             }
-            current = client.next;
+            eprintln!("[autostart] Spawned XDG entry {}: {}", entry.name, entry.exec);
+        }
+    }
+
+    fn notify_session_ready(&self) {
+        if !self.config.systemd_notify {
+            return;
         }
 
-        false
+        crate::systemd::import_environment();
+        match crate::systemd::notify_ready() {
+            Ok(true) => eprintln!("[systemd] Notified READY=1"),
+            Ok(false) => eprintln!("[systemd] NOTIFY_SOCKET not set, skipping notification"),
+            Err(e) => eprintln!("[systemd] Failed to notify: {}", e),
+        }
     }
+}
 
-    fn run_autostart_commands(&self) {
-        for command in &self.config.autostart {
-            crate::signal::spawn_detached(command);
-            eprintln!("[autostart] Spawned: {}", command);
+/// What the click of a click pick is for.
+#[derive(Clone, Copy)]
+enum ClickPurpose {
+    PickColor,
+    Inspect,
+}
+
+/// Crosshair pointer grab waiting for a click, see `start_click_pick`.
+struct ClickPick {
+    purpose: ClickPurpose,
+    cursor: u64,
+    /// Left click waiting for its release.
+    click: Option<ButtonPressEvent>,
+}
+
+/// Output configuration last applied by oxwm.
+#[derive(Default)]
+struct OutputState {
+    /// Name of the active monitor profile.
+    profile: Option<String>,
+    connected: Vec<String>,
+}
+
+/// Grabs Mod + left/right click on the root window for moving and resizing,
+/// replacing any grabs made for a previous modkey.
+fn grab_modkey_buttons(
+    connection: &RustConnection,
+    root: Window,
+    modkey: KeyButMask,
+) -> WmResult<()> {
+    connection.ungrab_button(ButtonIndex::ANY, root, ModMask::ANY)?;
+
+    let ignore_modifiers = [
+        0,
+        u16::from(ModMask::LOCK),
+        u16::from(ModMask::M2),
+        u16::from(ModMask::LOCK | ModMask::M2),
+    ];
+
+    for &ignore_mask in &ignore_modifiers {
+        let grab_mask = u16::from(modkey) | ignore_mask;
+
+        connection.grab_button(
+            false,
+            root,
+            EventMask::BUTTON_PRESS | EventMask::BUTTON_RELEASE,
+            GrabMode::SYNC,
+            GrabMode::ASYNC,
+            x11rb::NONE,
+            x11rb::NONE,
+            ButtonIndex::M1,
+            grab_mask.into(),
+        )?;
+
+        connection.grab_button(
+            false,
+            root,
+            EventMask::BUTTON_PRESS | EventMask::BUTTON_RELEASE,
+            GrabMode::SYNC,
+            GrabMode::ASYNC,
+            x11rb::NONE,
+            x11rb::NONE,
+            ButtonIndex::M3,
+            grab_mask.into(),
+        )?;
+    }
+    Ok(())
+}
+
+/// Configures RandR outputs for the connected set: the first matching monitor
+/// profile, or otherwise the display setup.
+///
+/// Nothing is reapplied while the profile and connected outputs stay the
+/// same, so the change events caused by oxwm itself are ignored.
+fn configure_outputs(
+    connection: &RustConnection,
+    root: Window,
+    config: &Config,
+    lid_closed: Option<bool>,
+    state: &mut OutputState,
+) {
+    if config.monitor_profiles.is_empty() && config.display_setup.is_empty() {
+        return;
+    }
+
+    let connected = match crate::randr::connected_output_names(connection, root) {
+        Ok(connected) => connected,
+        Err(e) => {
+            eprintln!("Failed to query RandR outputs: {:?}", e);
+            return;
+        }
+    };
+    let connected_changed = connected != state.connected;
+    state.connected = connected;
+
+    let profile = config
+        .monitor_profiles
+        .iter()
+        .find(|profile| profile.matches(&state.connected, lid_closed));
+
+    match profile {
+        Some(profile) if state.profile.as_deref() != Some(profile.name.as_str()) => {
+            eprintln!("Activating monitor profile '{}'", profile.name);
+            if let Err(e) = crate::randr::apply_output_setups(connection, root, &profile.outputs) {
+                eprintln!(
+                    "Failed to apply monitor profile '{}': {:?}",
+                    profile.name, e
+                );
+            }
+        }
+        None if connected_changed || state.profile.is_some() => {
+            if let Err(e) =
+                crate::randr::apply_output_setups(connection, root, &config.display_setup)
+            {
+                eprintln!("Failed to apply display setup: {:?}", e);
+            }
         }
+        _ => {}
     }
+
+    state.profile = profile.map(|profile| profile.name.clone());
+}
+
+/// Returns `(x, y, width, height)` for a window sized by `preset` and
+/// centered in the monitor's window area.
+fn float_preset_geometry(
+    preset: &crate::FloatPreset,
+    monitor: &Monitor,
+    border_width: i32,
+) -> (i32, i32, i32, i32) {
+    let width =
+        ((monitor.window_area_width as f32 * preset.width) as i32 - 2 * border_width).max(1);
+    let height =
+        ((monitor.window_area_height as f32 * preset.height) as i32 - 2 * border_width).max(1);
+    let x = monitor.window_area_x + (monitor.window_area_width - width - 2 * border_width) / 2;
+    let y = monitor.window_area_y + (monitor.window_area_height - height - 2 * border_width) / 2;
+    (x, y, width, height)
 }
 
 fn define_cursor(display: *mut _XDisplay, window: u64, cursor: u64) {
@@ -4810,7 +9659,74 @@ fn open_display() -> *mut _XDisplay {
     unsafe { x11::xlib::XOpenDisplay(std::ptr::null()) }
 }
 
-fn create_cursor(display: *mut _XDisplay) -> u64 {
+fn create_cursor(display: *mut _XDisplay, shape: u32) -> u64 {
     // C has better C interop than rust.
-    unsafe { x11::xlib::XCreateFontCursor(display, 68) }
+    unsafe { x11::xlib::XCreateFontCursor(display, shape) }
+}
+
+fn free_cursor(display: *mut _XDisplay, cursor: u64) {
+    unsafe {
+        x11::xlib::XFreeCursor(display, cursor);
+    }
+}
+
+/// Edges along one axis that a window spanning `span` on the other axis can
+/// snap to. The monitor area always counts; other windows only when they sit
+/// beside the window rather than somewhere else on the screen.
+fn snap_edges(
+    targets: &[(i32, i32, i32, i32)],
+    horizontal: bool,
+    (start, length): (i32, i32),
+    snap: i32,
+) -> Vec<i32> {
+    let mut edges = Vec::new();
+    for (index, &(x, y, width, height)) in targets.iter().enumerate() {
+        let (edge, size, cross, cross_size) = if horizontal {
+            (x, width, y, height)
+        } else {
+            (y, height, x, width)
+        };
+
+        let beside = start < cross + cross_size + snap && cross < start + length + snap;
+        if index == 0 || beside {
+            edges.push(edge);
+            edges.push(edge + size);
+        }
+    }
+    edges
+}
+
+/// Moves a window starting at `position` so whichever of its edges lies
+/// closest to one of `edges`, within `snap` pixels, touches it.
+fn snap_position(position: i32, size: i32, edges: &[i32], snap: i32) -> i32 {
+    let mut best: Option<i32> = None;
+    for &edge in edges {
+        for offset in [edge - position, edge - (position + size)] {
+            if offset.abs() < snap && best.is_none_or(|b| offset.abs() < b.abs()) {
+                best = Some(offset);
+            }
+        }
+    }
+    position + best.unwrap_or(0)
+}
+
+/// Moves a single edge onto the closest of `edges` within `snap` pixels.
+fn snap_edge(position: i32, edges: &[i32], snap: i32) -> i32 {
+    edges
+        .iter()
+        .copied()
+        .filter(|edge| (edge - position).abs() < snap)
+        .min_by_key(|edge| (edge - position).abs())
+        .unwrap_or(position)
+}
+
+/// Whether a window's class or instance names the configured terminal
+/// program, such as "Alacritty" for `alacritty -e tmux`.
+fn is_terminal_program(terminal: &str, class: &str, instance: &str) -> bool {
+    let Some(program) = terminal.split_whitespace().next() else {
+        return false;
+    };
+    let program = program.rsplit('/').next().unwrap_or(program);
+    !program.is_empty()
+        && (class.eq_ignore_ascii_case(program) || instance.eq_ignore_ascii_case(program))
 }
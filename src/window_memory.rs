@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+
+const STATE_FILE: &str = "floating-geometry";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SavedGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Last floating geometry per WM_CLASS, persisted as one
+/// `class<TAB>x<TAB>y<TAB>width<TAB>height` line per entry.
+#[derive(Debug, Default)]
+pub struct WindowMemory {
+    path: Option<PathBuf>,
+    entries: HashMap<String, SavedGeometry>,
+}
+
+impl WindowMemory {
+    pub fn load() -> Self {
+        let path = dirs::state_dir()
+            .or_else(dirs::cache_dir)
+            .map(|dir| dir.join("oxwm").join(STATE_FILE));
+
+        let entries = path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .map(|contents| contents.lines().filter_map(parse_line).collect())
+            .unwrap_or_default();
+
+        Self { path, entries }
+    }
+
+    pub fn get(&self, class: &str) -> Option<SavedGeometry> {
+        self.entries.get(class).copied()
+    }
+
+    pub fn remember(&mut self, class: &str, geometry: SavedGeometry) -> io::Result<()> {
+        if class.is_empty() || self.entries.get(class) == Some(&geometry) {
+            return Ok(());
+        }
+
+        self.entries.insert(class.to_string(), geometry);
+        self.save()
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut classes: Vec<&String> = self.entries.keys().collect();
+        classes.sort();
+
+        let mut contents = String::new();
+        for class in classes {
+            let g = &self.entries[class];
+            contents.push_str(&format!(
+                "{}\t{}\t{}\t{}\t{}\n",
+                class, g.x, g.y, g.width, g.height
+            ));
+        }
+
+        std::fs::write(path, contents)
+    }
+}
+
+fn parse_line(line: &str) -> Option<(String, SavedGeometry)> {
+    let mut fields = line.split('\t');
+    let class = fields.next()?.to_string();
+    let geometry = SavedGeometry {
+        x: fields.next()?.parse().ok()?,
+        y: fields.next()?.parse().ok()?,
+        width: fields.next()?.parse().ok()?,
+        height: fields.next()?.parse().ok()?,
+    };
+    Some((class, geometry))
+}
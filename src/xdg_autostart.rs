@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Name matched against `OnlyShowIn` / `NotShowIn` when
+/// `$XDG_CURRENT_DESKTOP` is unset.
+pub const DESKTOP_NAME: &str = "OXWM";
+
+#[derive(Debug, Clone)]
+pub struct AutostartEntry {
+    pub name: String,
+    pub exec: String,
+}
+
+/// Collects the autostart entries that should run under oxwm.
+///
+/// Entries in the user directory shadow system entries with the same file
+/// name, as described by the XDG autostart specification.
+pub fn entries() -> Vec<AutostartEntry> {
+    let mut files: HashMap<String, PathBuf> = HashMap::new();
+
+    for dir in autostart_dirs().iter().rev() {
+        let Ok(read_dir) = std::fs::read_dir(dir) else {
+            continue;
+        };
+        for entry in read_dir.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "desktop")
+                && let Some(file_name) = path.file_name().and_then(|n| n.to_str())
+            {
+                files.insert(file_name.to_string(), path);
+            }
+        }
+    }
+
+    let mut names: Vec<&String> = files.keys().collect();
+    names.sort();
+
+    names
+        .into_iter()
+        .filter_map(|name| parse_entry(&files[name]))
+        .collect()
+}
+
+fn autostart_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    if let Some(config_dir) = dirs::config_dir() {
+        dirs.push(config_dir.join("autostart"));
+    }
+
+    let system_dirs = std::env::var("XDG_CONFIG_DIRS")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| "/etc/xdg".to_string());
+    for dir in system_dirs.split(':').filter(|d| !d.is_empty()) {
+        dirs.push(Path::new(dir).join("autostart"));
+    }
+
+    dirs
+}
+
+fn parse_entry(path: &Path) -> Option<AutostartEntry> {
+    let contents = std::fs::read_to_string(path).ok()?;
+    let mut in_desktop_entry = false;
+    let mut fields: HashMap<&str, &str> = HashMap::new();
+
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_desktop_entry = line == "[Desktop Entry]";
+            continue;
+        }
+        if !in_desktop_entry || line.starts_with('#') {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            fields.insert(key.trim(), value.trim());
+        }
+    }
+
+    if fields.get("Type").is_some_and(|t| *t != "Application")
+        || fields.get("Hidden").is_some_and(|v| *v == "true")
+        || fields
+            .get("X-GNOME-Autostart-enabled")
+            .is_some_and(|v| *v == "false")
+    {
+        return None;
+    }
+
+    if let Some(only) = fields.get("OnlyShowIn")
+        && !desktop_list_contains(only)
+    {
+        return None;
+    }
+
+    if let Some(not) = fields.get("NotShowIn")
+        && desktop_list_contains(not)
+    {
+        return None;
+    }
+
+    if let Some(try_exec) = fields.get("TryExec")
+        && !program_exists(try_exec)
+    {
+        return None;
+    }
+
+    let exec = strip_field_codes(fields.get("Exec")?);
+    if exec.is_empty() {
+        return None;
+    }
+
+    let name = fields
+        .get("Name")
+        .map(|n| n.to_string())
+        .unwrap_or_else(|| exec.clone());
+
+    Some(AutostartEntry { name, exec })
+}
+
+/// Whether any of the session's desktop names, the colon-separated
+/// `$XDG_CURRENT_DESKTOP`, is in the `;`-separated `list`.
+fn desktop_list_contains(list: &str) -> bool {
+    let current = std::env::var("XDG_CURRENT_DESKTOP").unwrap_or_default();
+    let names: Vec<&str> = if current.is_empty() {
+        vec![DESKTOP_NAME]
+    } else {
+        current.split(':').filter(|name| !name.is_empty()).collect()
+    };
+    list.split(';')
+        .any(|desktop| names.iter().any(|name| desktop.eq_ignore_ascii_case(name)))
+}
+
+fn program_exists(program: &str) -> bool {
+    if program.contains('/') {
+        return Path::new(program).is_file();
+    }
+
+    std::env::var_os("PATH").is_some_and(|path| {
+        std::env::split_paths(&path).any(|dir| dir.join(program).is_file())
+    })
+}
+
+/// Removes `%f`, `%U` and friends from an `Exec` value; `%%` becomes `%`.
+fn strip_field_codes(exec: &str) -> String {
+    let mut result = String::with_capacity(exec.len());
+    let mut chars = exec.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            result.push(c);
+            continue;
+        }
+        if let Some('%') = chars.next() {
+            result.push('%');
+        }
+    }
+
+    result.split_whitespace().collect::<Vec<_>>().join(" ")
+}
@@ -0,0 +1,35 @@
+use crate::errors::X11Error;
+use x11rb::connection::RequestConnection;
+use x11rb::protocol::xkb::{self, ConnectionExt as _};
+use x11rb::rust_connection::RustConnection;
+
+/// Asks the XKB extension for keyboard replacement and keymap change events
+/// on the core keyboard, so bindings can be re-grabbed after a new keyboard
+/// is plugged in or `setxkbmap` runs. The core `MappingNotify` is not always
+/// sent in those cases. Bell events are selected too, for the visual bell.
+pub fn select_events(connection: &RustConnection) -> Result<(), X11Error> {
+    if connection
+        .extension_information(xkb::X11_EXTENSION_NAME)?
+        .is_none()
+    {
+        return Ok(());
+    }
+
+    let version = connection.xkb_use_extension(1, 0)?.reply()?;
+    if !version.supported {
+        return Ok(());
+    }
+
+    let parts = xkb::MapPart::KEY_TYPES | xkb::MapPart::KEY_SYMS | xkb::MapPart::MODIFIER_MAP;
+    connection.xkb_select_events(
+        xkb::ID::USE_CORE_KBD.into(),
+        xkb::EventType::from(0u16),
+        xkb::EventType::NEW_KEYBOARD_NOTIFY
+            | xkb::EventType::MAP_NOTIFY
+            | xkb::EventType::BELL_NOTIFY,
+        parts,
+        parts,
+        &xkb::SelectEventsAux::new(),
+    )?;
+    Ok(())
+}
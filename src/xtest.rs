@@ -0,0 +1,95 @@
+use crate::errors::X11Error;
+use crate::keyboard::{KeyboardMapping, Keysym};
+use x11rb::connection::{Connection, RequestConnection};
+use x11rb::protocol::xproto::{
+    ConnectionExt as _, KEY_PRESS_EVENT, KEY_RELEASE_EVENT, KeyButMask, Keycode, ModMask, Window,
+};
+use x11rb::protocol::xtest::{self, ConnectionExt as _};
+use x11rb::rust_connection::RustConnection;
+
+/// Types `keysym` with `modifiers` held into whichever window has the input
+/// focus, as if it came from the keyboard.
+///
+/// Modifiers the user is still holding for the binding that triggered this
+/// are released first and pressed again afterwards, so they don't leak into
+/// the synthetic key. Returns false when the key or a modifier has no keycode
+/// in the current mapping.
+pub fn send_key(
+    connection: &RustConnection,
+    root: Window,
+    mapping: &KeyboardMapping,
+    modifiers: &[KeyButMask],
+    keysym: Keysym,
+) -> Result<bool, X11Error> {
+    if connection
+        .extension_information(xtest::X11_EXTENSION_NAME)?
+        .is_none()
+    {
+        eprintln!("XTEST extension not available, cannot send keys");
+        return Ok(false);
+    }
+
+    let setup = connection.setup();
+    let Some(keycode) = mapping.find_keycode(keysym, setup.min_keycode, setup.max_keycode) else {
+        return Ok(false);
+    };
+
+    let modifier_map = connection.get_modifier_mapping()?.reply()?;
+    let per_modifier = modifier_map.keycodes_per_modifier() as usize;
+    let modifier_keycodes = |index: usize| -> Vec<Keycode> {
+        modifier_map
+            .keycodes
+            .iter()
+            .skip(index * per_modifier)
+            .take(per_modifier)
+            .copied()
+            .filter(|&keycode| keycode != 0)
+            .collect()
+    };
+
+    let mut press = Vec::new();
+    for &modifier in modifiers {
+        let index = u16::from(modifier).trailing_zeros() as usize;
+        let Some(&modifier_keycode) = modifier_keycodes(index).first() else {
+            return Ok(false);
+        };
+        press.push(modifier_keycode);
+    }
+
+    // Lock and NumLock toggle on press, so they are never released and
+    // pressed again.
+    let toggles = [ModMask::LOCK, ModMask::M2].map(|m| u16::from(m).trailing_zeros() as usize);
+    let keymap = connection.query_keymap()?.reply()?;
+    let is_down = |keycode: Keycode| keymap.keys[keycode as usize / 8] & (1 << (keycode % 8)) != 0;
+    let held: Vec<Keycode> = (0..8)
+        .filter(|index| !toggles.contains(index))
+        .flat_map(modifier_keycodes)
+        .filter(|&keycode| is_down(keycode))
+        .collect();
+
+    let fake = |type_: u8, keycode: Keycode| {
+        connection.xtest_fake_input(type_, keycode, x11rb::CURRENT_TIME, root, 0, 0, 0)
+    };
+
+    // The binding's key grab stays active until its key is released, which
+    // would route the fake events back to us instead of the focused window.
+    connection.ungrab_keyboard(x11rb::CURRENT_TIME)?;
+
+    for &keycode in &held {
+        fake(KEY_RELEASE_EVENT, keycode)?;
+    }
+    for &keycode in &press {
+        fake(KEY_PRESS_EVENT, keycode)?;
+    }
+    fake(KEY_PRESS_EVENT, keycode)?;
+    fake(KEY_RELEASE_EVENT, keycode)?;
+    for &keycode in press.iter().rev() {
+        fake(KEY_RELEASE_EVENT, keycode)?;
+    }
+    for &keycode in &held {
+        fake(KEY_PRESS_EVENT, keycode)?;
+    }
+
+    connection.flush()?;
+    Ok(true)
+}
@@ -44,6 +44,8 @@ local colors = {
 -- Workspace tags - can be numbers, names, or icons (requires a Nerd Font)
 local tags = { "1", "2", "3", "4", "5", "6", "7", "8", "9" }
 -- local tags = { "", "󰊯", "", "", "󰙯", "󱇤", "", "󱘶", "󰧮" } -- Example of nerd font icon tags
+-- Tags can also set their own label color and width in pixels, for icons that clip
+-- local tags = { { label = "1", color = "#f7768e", width = 28 }, { label = "2", color = "#7aa2f7" }, "3" }
 
 -- Font for the status bar (use "fc-list" to see available fonts)
 local bar_font = "monospace:style=Bold:size=10"
@@ -56,7 +58,19 @@ local blocks = {
         interval = 5,
         color = colors.light_blue,
         underline = true,
+        -- popup = "htop", -- left-click opens htop in a floating terminal, click again to close
+        -- sensitive = true, -- hidden while presentation mode is on
+        -- focused_only = true, -- only shown and run on the focused monitor's bar
+        -- min_width = "5ch", align = "right", -- keep the width steady as numbers change (or pixels: min_width = 60)
+        -- on_right_click = "notify-send \"$(free -h)\"", -- also on_click, on_middle_click, on_scroll_up/down
+        -- signal = 10, -- `pkill -RTMIN+10 oxwm` refreshes it right away, as with dwmblocks
+        -- warning = 85, critical = 95, -- percent in use that turns the block to the urgent colors
+        -- warning_color = colors.red, -- or color the text from the warning level instead
     }),
+    -- CPU usage, "{cores}" draws a bar per core
+    -- oxwm.bar.block.cpu({ format = "CPU {}% {cores}", warning = 80, color = colors.green }),
+    -- CPU temperature from a thermal zone
+    -- oxwm.bar.block.temperature({ format = "{}°C", zone = "x86_pkg_temp", warning = 75, critical = 90, color = colors.blue }),
     oxwm.bar.block.static({
         text = " │  ",
         interval = 999999999,
@@ -69,7 +83,19 @@ local blocks = {
         interval = 999999999,
         color = colors.red,
         underline = true,
+        -- timeout = 2, -- kill the command after 2 seconds
+        -- error = "!", -- shown when the command fails or times out
+        -- a command printing a JSON object like {"text": "42%", "urgency": "warning"} sets color, urgency, min_width or segments
+        -- plain output can color parts with status2d markup, e.g. "^c#f7768e^CPU^d^ 42%"
     }),
+    -- Git branch of the focused terminal's working directory
+    -- oxwm.bar.block.context({
+    --     format = " {}",
+    --     command = "git -C \"$(readlink /proc/$(pgrep -P $OXWM_PID | head -n1)/cwd)\" branch --show-current",
+    --     classes = { "Alacritty", "kitty" },
+    --     color = colors.green,
+    --     underline = true,
+    -- }),
     oxwm.bar.block.static({
         text = " │  ",
         interval = 999999999,
@@ -82,6 +108,8 @@ local blocks = {
         interval = 1,
         color = colors.cyan,
         underline = true,
+        -- zones = { "local", "UTC", { tz = "America/New_York", label = "NYC" } }, -- click to cycle, {zone} shows the label
+        -- locale = "de_DE.UTF-8", -- month and day names
     }),
     -- Uncomment to add battery status (useful for laptops)
     -- oxwm.bar.block.battery({
@@ -92,6 +120,88 @@ local blocks = {
     --     interval = 30,
     --     color = colors.green,
     --     underline = true,
+    --     warning = 20,
+    --     critical = 10,
+    --     low_color = colors.red,
+    --     on_critical = "notify-send -u critical 'Battery critical' \"$OXWM_BATTERY% left\"",
+    -- }),
+    -- Time until the next event in a calendar exported to .ics
+    -- oxwm.bar.block.countdown({
+    --     format = " {title} in {}",
+    --     file = "~/.local/share/calendar.ics",
+    --     color = colors.lavender,
+    --     underline = true,
+    -- }),
+    -- Warns when systemd units have failed; click to list them
+    -- oxwm.bar.block.systemd({
+    --     format = "⚠ {}",
+    --     units = { "backup.service" },
+    --     user_units = { "syncthing.service" },
+    --     color = colors.red,
+    --     underline = true,
+    -- }),
+    -- Running Docker/Podman containers; hidden when no daemon is running
+    -- oxwm.bar.block.containers({
+    --     format = " {}/{total}",
+    --     color = colors.blue,
+    --     underline = true,
+    -- }),
+    -- Red microphone/camera indicators while they are in use
+    -- oxwm.bar.block.privacy({
+    --     microphone = "● MIC",
+    --     camera = "● CAM",
+    --     color = colors.red,
+    -- }),
+    -- GPU load and video memory
+    -- oxwm.bar.block.gpu({
+    --     format = "GPU {}% {used}/{total}GB",
+    --     device = "card0",
+    --     color = colors.cyan,
+    -- }),
+    -- Wi-Fi or Ethernet with download and upload rates
+    -- oxwm.bar.block.network({
+    --     format = "󰖩 {ssid} {signal}% {down}↓ {up}↑",
+    --     ethernet = "󰈀 {down}↓ {up}↑",
+    --     disconnected = "󰖪 offline",
+    --     interval = 2,
+    --     color = colors.light_blue,
+    -- }),
+    -- Volume, updated right away; scroll to change it, right click to mute
+    -- oxwm.bar.block.volume({
+    --     format = "󰕾 {}%",
+    --     muted = "󰝟 {}%",
+    --     color = colors.purple,
+    --     popup = "pulsemixer",
+    -- }),
+    -- Pending package updates, checked hourly; click to upgrade
+    -- oxwm.bar.block.updates({
+    --     format = "󰏗 {}",
+    --     backend = "pacman",
+    --     color = colors.blue,
+    --     on_click = terminal .. " -e sudo pacman -Syu",
+    -- }),
+    -- Wireless mouse, keyboard and headset batteries; click for device names
+    -- oxwm.bar.block.peripherals({
+    --     icons = { mouse = "󰍽", keyboard = "󰌌", headset = "󰋎" },
+    --     color = colors.lavender,
+    -- }),
+    -- WireGuard status; click to toggle the NetworkManager connection
+    -- oxwm.bar.block.vpn({
+    --     interface = "wg0",
+    --     connection = "wg0",
+    --     color = colors.green,
+    --     down_color = colors.red,
+    -- }),
+    -- Prices from a JSON API, refreshed every 5 minutes
+    -- oxwm.bar.block.http({
+    --     format = "BTC ${btc} ETH ${eth}",
+    --     sources = {
+    --         { name = "btc", url = "https://api.coingecko.com/api/v3/simple/price?ids=bitcoin,ethereum&vs_currencies=usd", path = "bitcoin.usd" },
+    --         { name = "eth", url = "https://api.coingecko.com/api/v3/simple/price?ids=bitcoin,ethereum&vs_currencies=usd", path = "ethereum.usd" },
+    --     },
+    --     decimals = 0,
+    --     color = colors.green,
+    --     underline = true,
     -- }),
 };
 
@@ -99,14 +209,20 @@ local blocks = {
 -- Basic Settings
 -------------------------------------------------------------------------------
 oxwm.set_terminal(terminal)
-oxwm.set_modkey(modkey) -- This is for Mod + mouse binds, such as drag/resize
+oxwm.set_modkey(modkey) -- This is for Mod + mouse binds, such as drag/resize, and bindings using "Mod"
 oxwm.set_tags(tags)
+-- Floating windows dragged or resized with the mouse snap to monitor edges and
+-- other floating windows within this many pixels (0 disables snapping)
+oxwm.set_snap(32)
+-- GUI programs started from a terminal take its place until they close
+oxwm.set_swallow(true)
 
 -------------------------------------------------------------------------------
 -- Layouts
 -------------------------------------------------------------------------------
 -- Set custom symbols for layouts (displayed in the status bar)
--- Available layouts: "tiling", "normie" (floating), "grid", "monocle", "tabbed"
+-- Available layouts: "tiling", "normie" (floating), "grid", "monocle", "tabbed",
+-- "scrolling", "spiral", "bstack" (bottom stack), "centeredmaster"
 oxwm.set_layout_symbol("tiling", "[T]")
 oxwm.set_layout_symbol("normie", "[F]")
 oxwm.set_layout_symbol("tabbed", "[=]")
@@ -122,6 +238,8 @@ oxwm.border.set_width(2)
 oxwm.border.set_focused_color(colors.blue)
 -- Color of unfocused window borders
 oxwm.border.set_unfocused_color(colors.grey)
+-- Rounded window corners in pixels (leave at 0 if your compositor rounds corners)
+-- oxwm.border.set_corner_radius(8)
 
 -- Smart Enabled = No border if 1 window
 oxwm.gaps.set_smart(enabled)
@@ -148,10 +266,83 @@ oxwm.rule.add({ instance = "gimp", floating = true })
 -- oxwm.rule.add({ class = "firefox", title = "Library", floating = true })  
 -- oxwm.rule.add({ class = "firefox", tag = 2 })  
 -- oxwm.rule.add({ instance = "mpv", floating = true })                      
+-- oxwm.rule.add({ class = "pavucontrol", preset = "medium" }) -- centered floating, 60% of the screen
+-- oxwm.rule.add({ class = "kitty", terminal = true }) -- programs started from kitty swallow it
+-- oxwm.rule.add({ class = "Xephyr", swallow = false }) -- never hide the terminal that started it
+-- oxwm.rule.add({ class = "Slack", take_focus = false }) -- never steal focus when opening
+-- oxwm.rule.add({ class = "firefox", title_match = "^Meet [–-]", tag = 8, retag_on_title = true }) -- follow calls to tag 8
+
+-- When a rule sends a window to a hidden tag: "silent", "switch" to it, or mark it "urgent"
+-- oxwm.rule.set_hidden_tag_action("urgent")
+
+-- Scratchpads: windows toggled with a key, centered and floating on whatever tag is viewed
+-- oxwm.scratchpad.add({ name = "term", command = "st -c scratchpad", class = "scratchpad", width = 70, height = 50 })
+-- oxwm.key.bind({ modkey }, "Grave", oxwm.scratchpad.toggle("term"))
+
+-- Whether new windows take focus: "always", "never" or "same_tag"
+-- oxwm.set_focus_on_map("same_tag")
+
+-- At most one tiled window per tag; extra windows open on the next free tag ("next") or floating ("float")
+-- oxwm.set_tag_limit(1, "next")
+
+-- Flash the focused border on the bell and mark background windows that ring it urgent
+-- oxwm.set_bell({ visual = "border", urgent = true })
+
+-- Fade windows in and out over 120ms (needs a compositor honoring _NET_WM_WINDOW_OPACITY)
+-- oxwm.set_fade(120)
+
+-- Float fixed-size and utility windows, plus anything opening smaller than 300x200
+-- oxwm.set_float_heuristics({ fixed_size = true, utility = true, min_area = 300 * 200 })
+
+-- Sizes used by rule presets and oxwm.client.cycle_float_preset() (percent of the work area)
+-- oxwm.set_float_presets({
+--     { name = "small", width = 40, height = 40 },
+--     { name = "medium", width = 60, height = 60 },
+--     { name = "large", width = 80, height = 80 },
+-- })
+
+-- Reopen floating windows where you left them (remembered per WM_CLASS)
+-- oxwm.set_remember_floating(true)
+
+-- Saving this file reloads it right away; turn off to reload only with Mod+Shift+R
+-- oxwm.set_auto_reload(false)
 
 -- To find window properties, use xprop and click on the window
 -- WM_CLASS(STRING) shows both instance and class (instance, class)
 
+-------------------------------------------------------------------------------
+-- Monitors
+-------------------------------------------------------------------------------
+-- Per-monitor settings keyed by RandR output name (see `xrandr --query`)
+-- oxwm.monitor.configure("eDP-1", { tag = 1, layout = "tiling" })
+-- oxwm.monitor.configure("DP-2", { tag = 2, bar_position = "bottom", blocks = { oxwm.bar.block.ram({ format = "{}", interval = 5, color = colors.cyan }) } })
+-- A second bar only on DP-2 (true uses the blocks of oxwm.bar.set_second_bar), or none on eDP-1
+-- oxwm.monitor.configure("DP-2", { second_bar = { oxwm.bar.block.cpu({ interval = 2, color = colors.green }) } })
+-- oxwm.monitor.configure("eDP-1", { second_bar = false })
+-- Tag 9 always lives on HDMI-1; viewing it elsewhere focuses HDMI-1 instead
+-- oxwm.monitor.configure("HDMI-1", { tags = { 9 } })
+-- oxwm.monitor.set_assigned_tag_focus(false) -- show it there but keep focus
+-- Switch both screens together: tag 1 on eDP-1 goes with tag 6 on DP-2
+-- oxwm.monitor.link_tags({ ["eDP-1"] = 1, ["DP-2"] = 6 })
+-- oxwm.key.bind({ modkey, "Control" }, "T", oxwm.monitor.toggle_tag_links())
+
+-- Profiles switch outputs automatically when docking or closing the lid
+-- oxwm.monitor.add_profile({
+--     name = "docked",
+--     lid = "closed",
+--     outputs = {
+--         { output = "eDP-1", enabled = false },
+--         { output = "DP-2", mode = "2560x1440", position = { 0, 0 }, primary = true, tags = { 1, 2, 3, 4, 5, 6, 7, 8, 9 } },
+--     },
+-- })
+-- oxwm.monitor.add_profile({ name = "mobile", outputs = { { output = "eDP-1", primary = true } } })
+
+-- Or let oxwm set up outputs itself instead of an xrandr script (startup and hotplug)
+-- oxwm.monitor.set_outputs({
+--     { output = "eDP-1", mode = "1920x1080", position = { 0, 0 }, primary = true },
+--     { output = "HDMI-1", mode = "1920x1080", position = { 1920, 0 }, rotation = "left", scale = 1.25 },
+-- })
+
 -------------------------------------------------------------------------------
 -- Status Bar Configuration
 -------------------------------------------------------------------------------
@@ -160,6 +351,11 @@ oxwm.bar.set_font(bar_font)
 
 -- Set your blocks here (defined above)
 oxwm.bar.set_blocks(blocks)
+-- Or show the root window's name, set by xsetroot or a dwm status script
+-- oxwm.bar.set_root_status(true)
+-- Or keep the tags and title on top and move the blocks to a second bar at the bottom
+-- oxwm.bar.set_blocks({})
+-- oxwm.bar.set_second_bar(blocks)
 
 -- Bar color schemes (for workspace tag display)
 -- Parameters: foreground, background, border
@@ -172,10 +368,48 @@ oxwm.bar.set_scheme_occupied(colors.cyan, colors.bg, colors.cyan)
 oxwm.bar.set_scheme_selected(colors.cyan, colors.bg, colors.purple)
 -- Urgent tags (windows requesting attention)
 oxwm.bar.set_scheme_urgent(colors.red, colors.bg, colors.red)
+-- Backgrounds can also be a two-stop vertical gradient, e.g. for the selected tag
+-- oxwm.bar.set_scheme_selected(colors.cyan, { "#2a2e3f", colors.bg }, colors.purple)
 
 -- Hide tags that have no windows and are not selected
 -- oxwm.bar.set_hide_vacant_tags(true)
 
+-- Indicator shown while a screen recorder is running
+-- oxwm.bar.set_recording_indicator({ text = "● REC", processes = { "obs", "simplescreenrecorder" } })
+
+-- Let clicks pass through the bar to the windows below (display-only bar)
+-- oxwm.bar.set_click_through(true)
+
+-- Hide the bar 1.5 seconds after the pointer leaves it; touch the screen edge to bring it back
+-- oxwm.bar.set_autohide(1500)
+
+-- Rounded corners on the bar and tab bar, in pixels
+-- oxwm.bar.set_corner_radius(6)
+
+-- Border and bottom shadow to separate the bar from windows of the same color
+-- oxwm.bar.set_border(1, colors.grey)
+-- oxwm.bar.set_shadow(4)
+
+-- Label each bar with its monitor on multi-monitor setups ("none", "number" or "name")
+-- oxwm.bar.set_monitor_label("name")
+-- Show the focused window's class before its title, e.g. "[firefox] Page Title"
+-- oxwm.bar.set_title_class("class", colors.grey)
+-- Count the windows on the viewed tags and on the monitor, e.g. "[2/5]"
+-- oxwm.bar.set_window_count("[{}/{total}]")
+-- Blink the tag that received a window opened in the background
+-- oxwm.bar.set_tag_flash(true)
+-- Blink tags with urgent windows twice a second for up to 10 seconds
+-- oxwm.bar.set_urgent_blink({ interval = 500, duration = 10000 })
+-- Dot on tabs of background windows with new activity (title change or bell)
+-- oxwm.bar.set_activity_color("#e0af68")
+-- Click the layout symbol to cycle layouts, double-click a tag to add it to the view,
+-- and ignore clicks within 2px of the edges between tags
+-- oxwm.bar.on_click("layout", oxwm.layout.cycle())
+-- oxwm.bar.on_double_click("tag", oxwm.tag.toggleview(0))
+-- oxwm.bar.set_dead_zone(2)
+-- Scroll over the tags to cycle through tags with windows only
+-- oxwm.bar.set_scroll_skip_empty(true)
+
 -------------------------------------------------------------------------------
 -- Keybindings
 -------------------------------------------------------------------------------
@@ -190,6 +424,10 @@ oxwm.bar.set_scheme_urgent(colors.red, colors.bg, colors.red)
 -- Basic window management
 
 oxwm.key.bind({ modkey }, "Return", oxwm.spawn_terminal())
+-- Open a terminal in the focused window's working directory
+oxwm.key.bind({ modkey, "Shift" }, "Return", oxwm.spawn_terminal_in_cwd())
+-- Spawn bindings can set a working directory and extra environment variables
+-- oxwm.key.bind({ modkey }, "E", oxwm.spawn({ "code", "." }, { cwd = "~/projects", env = { GTK_THEME = "Adwaita:dark" } }))
 -- Launch Dmenu
 oxwm.key.bind({ modkey }, "D", oxwm.spawn({ "sh", "-c", "dmenu_run -l 10" }))
 -- Copy screenshot to clipboard
@@ -202,10 +440,23 @@ oxwm.key.bind({ modkey, "Shift" }, "Slash", oxwm.show_keybinds())
 -- Window state toggles
 oxwm.key.bind({ modkey, "Shift" }, "F", oxwm.client.toggle_fullscreen())
 oxwm.key.bind({ modkey, "Shift" }, "Space", oxwm.client.toggle_floating())
+-- Cycle a floating window through the small/medium/large presets
+oxwm.key.bind({ modkey, "Shift" }, "C", oxwm.client.cycle_float_preset())
+-- oxwm.key.bind({ modkey, "Shift" }, "M", oxwm.client.toggle_maximize("vertical"))
+-- Send Ctrl+C to the focused window
+-- oxwm.key.bind({ modkey }, "Y", oxwm.client.send_key({ "Control" }, "C"))
+
+-- Stash the focused window out of the way and pop it back later
+-- oxwm.key.bind({ modkey }, "Minus", oxwm.client.stash())
+-- oxwm.key.bind({ modkey, "Shift" }, "Minus", oxwm.client.pop_stash())
+-- Click a window to see its class, instance and other details for writing rules
+-- oxwm.key.bind({ modkey, "Shift" }, "I", oxwm.client.inspect("click"))
 
 -- Layout management
 oxwm.key.bind({ modkey }, "F", oxwm.layout.set("normie"))
 oxwm.key.bind({ modkey }, "C", oxwm.layout.set("tiling"))
+-- oxwm.key.bind({ modkey, "Shift" }, "B", oxwm.layout.set("bstack"))
+-- oxwm.key.bind({ modkey, "Shift" }, "U", oxwm.layout.set("centeredmaster"))
 -- Cycle through layouts
 oxwm.key.bind({ modkey }, "N", oxwm.layout.cycle())
 
@@ -220,6 +471,32 @@ oxwm.key.bind({ modkey }, "P", oxwm.inc_num_master(-1))
 
 -- Gaps toggle
 oxwm.key.bind({ modkey }, "A", oxwm.toggle_gaps())
+-- Grow/shrink gaps
+-- oxwm.key.bind({ modkey, "Control" }, "Equal", oxwm.inc_gaps(2))
+-- oxwm.key.bind({ modkey, "Control" }, "Minus", oxwm.inc_gaps(-2))
+
+-- Release all keybindings (except this one) for apps that need global hotkeys
+-- oxwm.key.bind({ modkey, "Shift" }, "Escape", oxwm.toggle_passthrough())
+
+-- Hide the windows on the current tag to show the desktop, again to restore them
+-- oxwm.key.bind({ modkey }, "D", oxwm.toggle_show_desktop())
+
+-- Presentation mode for screen sharing: no screen blanking, locking or flashes, sensitive blocks hidden
+-- oxwm.set_presentation({ on_enter = "dunstctl set-paused true", on_exit = "dunstctl set-paused false" })
+-- oxwm.key.bind({ modkey, "Shift" }, "F12", oxwm.toggle_presentation())
+
+-- Click a pixel to copy its color to the clipboard
+-- oxwm.key.bind({ modkey, "Control" }, "C", oxwm.pick_color())
+
+-- Bindings written with the generic "Mod" follow the modkey, which can be swapped
+-- at runtime (e.g. Super <-> Alt). Bindings with an explicit "Mod4" stay put.
+-- oxwm.key.bind({ "Mod", "Shift" }, "M", oxwm.swap_modkey("Mod1"))
+
+-- Run several actions from one binding, or pick one based on the focused window
+-- oxwm.key.bind({ modkey, "Control" }, "3", oxwm.chain({ oxwm.tag.move_to(2), oxwm.tag.view(2) }))
+-- Send the focused window to a new tag, removed again once it is empty
+-- oxwm.key.bind({ modkey, "Shift" }, "N", oxwm.tag.move_to("+"))
+-- oxwm.key.bind({ modkey }, "Z", oxwm.when("floating", oxwm.client.toggle_floating(), oxwm.client.toggle_fullscreen()))
 
 -- Window manager controls
 oxwm.key.bind({ modkey, "Shift" }, "Q", oxwm.quit())
@@ -243,6 +520,20 @@ oxwm.key.bind({ modkey, "Shift" }, "Comma", oxwm.monitor.tag(-1))
 oxwm.key.bind({ modkey, "Shift" }, "Period", oxwm.monitor.tag(1))
 
 -- Workspace (tag) navigation
+-- Cycle through the tags that have windows
+-- oxwm.key.bind({ modkey }, "Right", oxwm.tag.view_next_occupied())
+-- oxwm.key.bind({ modkey }, "Left", oxwm.tag.view_previous_occupied())
+-- Move the pointer to the focused window when switching tags
+-- oxwm.tag.set_warp_pointer(true)
+-- Pin apps to a tag; Mod+Shift+P starts any that are missing and tiles them in order
+-- oxwm.tag.pin(1, {
+--     { command = "code", class = "Code" },
+--     { command = terminal, class = "Alacritty" },
+-- }, { layout = "tiling", on_view = true })
+-- oxwm.key.bind({ modkey, "Shift" }, "P", oxwm.tag.spawn_pinned())
+-- Save and restore the current tag's window arrangement
+-- oxwm.key.bind({ modkey, "Control", "Shift" }, "S", oxwm.tag.save_snapshot("coding"))
+-- oxwm.key.bind({ modkey, "Control" }, "S", oxwm.tag.restore_snapshot("coding"))
 -- Switch to workspace N (tags are 0-indexed, so tag "1" is index 0)
 oxwm.key.bind({ modkey }, "1", oxwm.tag.view(0))
 oxwm.key.bind({ modkey }, "2", oxwm.tag.view(1))
@@ -300,6 +591,16 @@ oxwm.key.chord({
     { {},         "T" }
 }, oxwm.spawn_terminal())
 
+-------------------------------------------------------------------------------
+-- Advanced: Modes
+-------------------------------------------------------------------------------
+-- A mode swaps in its own keybindings until Escape is pressed, like i3 modes.
+-- The mode name is shown in the bar while it is active.
+-- oxwm.key.bind({ modkey }, "R", oxwm.mode.enter("resize"))
+-- oxwm.mode.bind("resize", {}, "H", oxwm.set_master_factor(-5))
+-- oxwm.mode.bind("resize", {}, "L", oxwm.set_master_factor(5))
+-- oxwm.mode.bind("resize", {}, "Return", oxwm.mode.exit())
+
 -------------------------------------------------------------------------------
 -- Autostart
 -------------------------------------------------------------------------------
@@ -310,3 +611,40 @@ oxwm.key.chord({
 -- oxwm.autostart("feh --bg-scale ~/wallpaper.jpg") 
 -- oxwm.autostart("dunst")
 -- oxwm.autostart("nm-applet")
+
+-- Run .desktop entries from ~/.config/autostart and /etc/xdg/autostart
+-- oxwm.set_xdg_autostart(true)
+
+-- Mirror tags, layout and focused title into a file or FIFO for external bars
+-- oxwm.set_status_file("~/.cache/oxwm/status")
+
+-- External tools can query OXWM over the IPC socket at $XDG_RUNTIME_DIR/oxwm.sock
+-- (or $OXWM_SOCKET). Send one JSON request or bare command per line, e.g.:
+--   echo get_tree | socat - UNIX-CONNECT:$XDG_RUNTIME_DIR/oxwm.sock
+-- get_tree returns monitors -> tags -> clients with geometry, state and focus
+-- view_tag <index> switches the focused monitor to a tag (0-based)
+-- create_tag [name] appends a tag and views it; it is removed again once empty
+-- set_tag_note <index> [note] shows a note such as "deploy in progress" next to the
+-- layout symbol while the tag is viewed, kept across restarts; no note clears it
+
+-- The same commands are served on the session bus as org.oxwm.WM, which also
+-- emits FocusChanged and TagsChanged signals:
+--   gdbus call --session -d org.oxwm.WM -o /org/oxwm/WM -m org.oxwm.WM.Command get_tree
+
+-- Expose the same protocol over TCP, guarded by a token sent as "auth <token>"
+-- oxwm.set_remote_control({ address = "0.0.0.0:7878", token = "change-me" })
+
+-- systemd user-session integration
+-- Notify systemd when OXWM is ready (for a Type=notify user service)
+-- oxwm.systemd.set_notify(true)
+-- Run autostart commands as transient user units (logs go to the journal)
+-- oxwm.systemd.set_autostart_units(true)
+-- Run a locker on loginctl lock-session and blank the monitors
+-- oxwm.systemd.set_locker("i3lock -n", true)
+-- oxwm.key.bind({ modkey, "Control" }, "L", oxwm.systemd.lock())
+
+-- Power supply hooks (battery blocks also refresh immediately on changes)
+-- oxwm.power.on_ac("brightnessctl set 100%")
+-- oxwm.power.on_battery("brightnessctl set 40%")
+-- oxwm.power.on_low_battery(15, "notify-send 'Battery low' \"$OXWM_BATTERY% left\"")
+-- oxwm.power.on_low_battery(5, "notify-send -u critical 'Battery critical'")
@@ -18,42 +18,127 @@ oxwm = {}
 
 ---Spawn a command
 ---@param cmd string|string[] Command to spawn (string or array of strings)
+---@param opts {cwd: string?, env: table<string, string>?}? Working directory ("~" is expanded) and extra environment variables
 ---@return table Action table for keybinding
-function oxwm.spawn(cmd) end
+function oxwm.spawn(cmd, opts) end
 
 ---Spawn the configured terminal emulator
 ---@return table Action table for keybinding
 function oxwm.spawn_terminal() end
 
+---Spawn the configured terminal emulator in the focused window's working directory
+---Resolved through the window's _NET_WM_PID; falls back to the default directory
+---@return table Action table for keybinding
+function oxwm.spawn_terminal_in_cwd() end
+
 ---Set the terminal emulator
 ---@param terminal string Terminal command (e.g., "st", "alacritty")
 function oxwm.set_terminal(terminal) end
 
 ---Set the modifier key
+---Bindings written with the generic "Mod" modifier use this key, as do Mod + mouse binds.
 ---@param modkey string Modifier key ("Mod1", "Mod4", "Shift", "Control")
 function oxwm.set_modkey(modkey) end
 
 ---Set workspace tags
----@param tags string[] Array of tag names
+---A tag may be a table to color its label or fix its width, e.g. for Nerd Font icons
+---whose measured width is off: { label = "󰊯", color = "#7aa2f7", width = 28 }
+---The color is used unless the tag is urgent.
+---@param tags (string|{label: string, color?: string|integer, width?: integer})[] Array of tag names
 function oxwm.set_tags(tags) end
 
 ---Enable or disable automatic tiling of new windows
 ---@param enabled boolean Enable or disable auto-tiling
 function oxwm.auto_tile(enabled) end
 
+---Set how close a dragged or resized floating window must come to a monitor
+---edge or another floating window before it snaps to it
+---@param pixels integer Snap distance in pixels (default 32, 0 disables snapping)
+function oxwm.set_snap(pixels) end
+
+---Enable or disable window swallowing
+---A tiled window opened by a program started from a terminal takes the terminal's place until it closes.
+---The configured terminal counts as a terminal; rules with terminal = true add others.
+---@param enabled boolean Enable or disable swallowing (default true)
+function oxwm.set_swallow(enabled) end
+
+---Tune which new windows float instead of tiling
+---Dialogs always float; a rule with floating = false keeps a window tiled.
+---@param options {fixed_size: boolean?, utility: boolean?, min_area: integer?} fixed_size floats windows whose size hints forbid resizing (default true), utility floats _NET_WM_WINDOW_TYPE_UTILITY windows (default true), min_area floats windows opening smaller than this many pixels (default 0, off)
+function oxwm.set_float_heuristics(options) end
+
 ---Set layout symbol override
----@param name string Layout name (e.g., "tiling", "normie", "tabbed", "grid", "monocle")
+---@param name string Layout name (e.g., "tiling", "normie", "tabbed", "grid", "monocle", "spiral", "bstack", "centeredmaster")
 ---@param symbol string Symbol to display (e.g., "[T]", "[F]", "[=]")
 function oxwm.set_layout_symbol(name, symbol) end
 
+---Set the size presets used for centered floating windows
+---Defaults are small (40%), medium (60%) and large (80%)
+---@param presets {name: string, width: number, height: number}[] Presets with width/height as percentages of the monitor's work area
+function oxwm.set_float_presets(presets) end
+
+---Remember the last floating size/position per WM_CLASS and restore it when the
+---application opens a floating window again (stored in ~/.local/state/oxwm)
+---@param enabled boolean Enable or disable floating geometry memory
+function oxwm.set_remember_floating(enabled) end
+
+---Reload the config as soon as it, or a Lua file next to it, is saved (default true)
+---@param enabled boolean Enable or disable automatic reloading
+function oxwm.set_auto_reload(enabled) end
+
+---Choose whether newly opened windows take the focus
+---"same_tag" only focuses windows that open on a tag visible on the focused monitor.
+---Window rules can override this with take_focus.
+---@param policy "always"|"never"|"same_tag" Focus policy (default "always")
+function oxwm.set_focus_on_map(policy) end
+
+---Limit how many tiled windows a tag holds
+---New windows on a full tag go to the next tag with room (handled like a rule sending
+---them to that tag), or open floating.
+---@param limit integer Maximum tiled windows per tag (0 for no limit)
+---@param overflow? "next"|"float" Where extra windows go (default "next")
+function oxwm.set_tag_limit(limit, overflow) end
+
+---Configure how the X11 bell is shown
+---@param options {visual: "none"|"border"|"bar"?, urgent: boolean?} visual flashes the focused window border or the selected tags in the bar (default "none"); urgent marks an unfocused window that rang the bell urgent (default false)
+function oxwm.set_bell(options) end
+
+---Configure presentation mode (see oxwm.toggle_presentation)
+---@param options {indicator: string?, on_enter: string?, on_exit: string?} indicator is shown in the bar while presenting (default "PRES", "" hides it); on_enter/on_exit are shell commands run when it starts/ends, e.g. to pause notifications
+function oxwm.set_presentation(options) end
+
+---Fade windows in when they map or a tag switch shows them, and out before closing
+---Sets _NET_WM_WINDOW_OPACITY, so a compositor such as picom is required to see the effect
+---@param duration_ms integer Fade duration in milliseconds (0 disables, the default)
+function oxwm.set_fade(duration_ms) end
+
 ---Window rule module
 ---@class oxwm.rule
 oxwm.rule = {}
 
 ---Add a window rule
----@param rule {class: string?, instance: string?, title: string?, role: string?, floating: boolean?, tag: integer?, fullscreen: boolean?, focus: boolean?} Rule configuration
+---@param rule {class: string?, instance: string?, title: string?, title_match: string?, retag_on_title: boolean?, role: string?, floating: boolean?, tag: integer?, fullscreen: boolean?, focus: boolean?, take_focus: boolean?, preset: string?, terminal: boolean?, swallow: boolean?} Rule configuration (title_match is a regex on the title; retag_on_title moves the window to the rule's tag whenever a title change makes the rule match; preset floats and centers the window using a named float preset; take_focus overrides oxwm.set_focus_on_map; terminal marks windows programs started from them can swallow; swallow = false keeps a window from swallowing its terminal)
 function oxwm.rule.add(rule) end
 
+---Choose what happens when a rule sends a new window to a tag that isn't shown
+---Rules with focus = true always switch to the tag.
+---@param action "silent"|"switch"|"urgent" Leave the view alone, view the tag, or mark the window urgent (default "silent")
+function oxwm.rule.set_hidden_tag_action(action) end
+
+---Scratchpad module
+---@class oxwm.scratchpad
+oxwm.scratchpad = {}
+
+---Define a named scratchpad: a window toggled with a key, shown centered and floating on the viewed tags
+---The command runs on the first toggle; its window is recognized by class, instance or title
+---@param scratchpad {name: string, command: string, class: string?, instance: string?, title: string?, width: number?, height: number?} Scratchpad configuration (width and height are percentages of the screen, default 60)
+function oxwm.scratchpad.add(scratchpad) end
+
+---Show or hide a scratchpad on the focused monitor, launching it if needed
+---@param name string Scratchpad name
+---@return table Action table for keybinding
+function oxwm.scratchpad.toggle(name) end
+
 ---Quit the window manager
 ---@return table Action table for keybinding
 function oxwm.quit() end
@@ -66,10 +151,70 @@ function oxwm.restart() end
 ---@return table Action table for keybinding
 function oxwm.recompile() end
 
----Toggle gaps on/off
+---Toggle gaps on/off for the viewed tag
 ---@return table Action table for keybinding
 function oxwm.toggle_gaps() end
 
+---Grow (positive) or shrink (negative) the viewed tag's inner and outer gaps until the config is reloaded
+---@param delta integer Pixels to add
+---@return table Action table for keybinding
+function oxwm.inc_gaps(delta) end
+
+---Grow (positive) or shrink (negative) the viewed tag's gaps between windows
+---@param delta integer Pixels to add
+---@return table Action table for keybinding
+function oxwm.inc_inner_gaps(delta) end
+
+---Grow (positive) or shrink (negative) the viewed tag's gaps at the screen edges
+---@param delta integer Pixels to add
+---@return table Action table for keybinding
+function oxwm.inc_outer_gaps(delta) end
+
+---Toggle smart gaps for the viewed tag, which drop the outer gaps while a single window is tiled
+---@return table Action table for keybinding
+function oxwm.toggle_smart_gaps() end
+
+---Toggle keybinding passthrough
+---Releases all of oxwm's key grabs (except the binding for this action) so applications
+---that need raw global hotkeys receive them. The bar shows "PASS" while active.
+---@return table Action table for keybinding
+function oxwm.toggle_passthrough() end
+
+---Hide every window shown on the focused monitor to reveal the desktop; press again to bring them back
+---Also follows _NET_SHOWING_DESKTOP requests from pagers and docks
+---@return table Action table for keybinding
+function oxwm.toggle_show_desktop() end
+
+---Toggle presentation mode for screen sharing: suspends the screen saver and DPMS, ignores
+---logind lock requests, stops bell and urgent tag flashes and hides blocks marked sensitive
+---@return table Action table for keybinding
+function oxwm.toggle_presentation() end
+
+---Pick a color from the screen: left-click any pixel to copy its color as #rrggbb to the clipboard
+---and show it in the bar for a few seconds; any other button cancels
+---@return table Action table for keybinding
+function oxwm.pick_color() end
+
+---Swap the modkey at runtime and re-grab every binding that uses "Mod"
+---Running it again switches back to the modkey set with oxwm.set_modkey.
+---@param modifier string Modifier to switch to ("Mod1", "Mod4", ...)
+---@return table Action table for keybinding
+function oxwm.swap_modkey(modifier) end
+
+---Run several actions in order from one binding
+---Example: oxwm.chain({ oxwm.tag.move_to(2), oxwm.tag.view(2) })
+---@param actions table[] Action tables, run first to last
+---@return table Action table for keybinding
+function oxwm.chain(actions) end
+
+---Run one of two actions depending on the focused window or current tag
+---Example: oxwm.when("floating", oxwm.client.toggle_floating(), oxwm.client.toggle_fullscreen())
+---@param condition "focused"|"floating"|"fullscreen"|"maximized"|"empty"|string Condition; "class:<name>" matches the focused window's class or instance
+---@param action table Action to run when the condition holds
+---@param otherwise? table Action to run otherwise (default: nothing)
+---@return table Action table for keybinding
+function oxwm.when(condition, action, otherwise) end
+
 ---Show keybind overlay
 ---@return table Action table for keybinding
 function oxwm.show_keybinds() end
@@ -89,7 +234,7 @@ function oxwm.inc_num_master(delta) end
 oxwm.key = {}
 
 ---Bind a key combination to an action
----@param modifiers string|string[] Modifier keys (e.g., {"Mod4"}, {"Mod4", "Shift"})
+---@param modifiers string|string[] Modifier keys (e.g., {"Mod"}, {"Mod4", "Shift"}); "Mod" follows oxwm.set_modkey
 ---@param key string Key name (e.g., "Return", "Q", "1")
 ---@param action table Action returned by oxwm functions
 function oxwm.key.bind(modifiers, key, action) end
@@ -99,6 +244,28 @@ function oxwm.key.bind(modifiers, key, action) end
 ---@param action table Action returned by oxwm functions
 function oxwm.key.chord(keys, action) end
 
+---Mode module
+---A mode replaces the normal keybindings with its own until Escape is pressed
+---or oxwm.mode.exit() runs. The active mode's name is shown in the bar.
+---@class oxwm.mode
+oxwm.mode = {}
+
+---Bind a key inside a mode, creating the mode on first use
+---@param name string Mode name (e.g., "resize")
+---@param modifiers string|string[] Modifier keys, same as oxwm.key.bind
+---@param key string Key name (e.g., "H", "Return")
+---@param action table Action returned by oxwm functions
+function oxwm.mode.bind(name, modifiers, key, action) end
+
+---Switch to a mode
+---@param name string Mode name
+---@return table Action table for keybinding
+function oxwm.mode.enter(name) end
+
+---Leave the active mode and return to the normal keybindings
+---@return table Action table for keybinding
+function oxwm.mode.exit() end
+
 ---Gap configuration module
 ---@class oxwm.gaps
 oxwm.gaps = {}
@@ -143,6 +310,11 @@ function oxwm.border.set_focused_color(color) end
 ---@param color string|integer Color as hex string ("#666666", "0x666666") or integer
 function oxwm.border.set_unfocused_color(color) end
 
+---Round the corners of client windows (XShape); fullscreen windows stay square
+---Leave at 0 if your compositor already rounds corners
+---@param radius integer Corner radius in pixels (0 disables)
+function oxwm.border.set_corner_radius(radius) end
+
 ---Client/window management module
 ---@class oxwm.client
 oxwm.client = {}
@@ -169,6 +341,37 @@ function oxwm.client.focus_stack(dir) end
 ---@return table Action table for keybinding
 function oxwm.client.move_stack(dir) end
 
+---Cycle the focused floating window through the float size presets (centered)
+---@return table Action table for keybinding
+function oxwm.client.cycle_float_preset() end
+
+---Toggle the maximized state of the focused window (floats it over the work area)
+---@param direction? "vertical"|"horizontal"|"both" Direction to maximize (default "both")
+---@return table Action table for keybinding
+function oxwm.client.toggle_maximize(direction) end
+
+---Send a key press to the focused window through XTest, e.g. to give an app a
+---hotkey it only listens for while focused. Modifiers held for the triggering
+---binding are released while the key is sent.
+---@param modifiers string[] Modifiers to hold (e.g., {"Control"}, {} for none)
+---@param key string Key name (e.g., "C", "XF86AudioPlay")
+---@return table Action table for keybinding
+function oxwm.client.send_key(modifiers, key) end
+
+---Hide the focused window on the monitor's stash, out of every tag
+---@return table Action table for keybinding
+function oxwm.client.stash() end
+
+---Bring back the most recently stashed window in front of the focused window, on the current tag
+---@return table Action table for keybinding
+function oxwm.client.pop_stash() end
+
+---Show the class, instance, title, type, tags, monitor, state and size hints of a window in a popup
+---(also printed to stdout), with a matching oxwm.rule.add line to start a rule from
+---@param target? "focused"|"click" Inspect the focused window (default) or the next window clicked
+---@return table Action table for keybinding
+function oxwm.client.inspect(target) end
+
 ---Monitor management module
 ---@class oxwm.monitor
 oxwm.monitor = {}
@@ -183,6 +386,49 @@ function oxwm.monitor.focus(dir) end
 ---@return table Action table for keybinding
 function oxwm.monitor.tag(dir) end
 
+---Configure the monitor driven by a RandR output (see `xrandr --query` for names)
+---Settings follow the output even when monitors are reordered by hotplugging
+---@param output string Output name, e.g. "eDP-1" or "DP-2"
+---Tags listed in tags (1-based) always live on this monitor: viewing one from another monitor
+---shows it here (see oxwm.monitor.set_assigned_tag_focus), and windows moved to one follow it here
+---second_bar turns this monitor's second bar (see oxwm.bar.set_second_bar) on or off, or gives it its own blocks
+---@param config {tag: integer?, layout: string?, bar_position: "top"|"bottom"?, blocks: table[]?, second_bar: boolean|table[]?, tags: integer[]?} Tag viewed at startup (1-based), default layout, bar position, blocks shown on this monitor's bar, second bar, and tags assigned to this monitor
+function oxwm.monitor.configure(output, config) end
+
+---Whether viewing a tag assigned to another monitor focuses that monitor (default true)
+---When false the tag is shown on its monitor and focus stays where it is
+---@param enabled boolean
+function oxwm.monitor.set_assigned_tag_focus(enabled) end
+
+---Link tags across monitors: viewing one of them on its monitor switches the other
+---monitors to their linked tag, so both screens change context together
+---@param views table<string, integer> Output name to tag index (1-based), e.g. { ["eDP-1"] = 1, ["DP-2"] = 6 }
+function oxwm.monitor.link_tags(views) end
+
+---Turn linked tags off or back on
+---@return table Action table for keybinding
+function oxwm.monitor.toggle_tag_links() end
+
+---@class oxwm.OutputSetup
+---@field output string RandR output name, e.g. "eDP-1"
+---@field enabled boolean? Turn the output on or off (default true)
+---@field mode string? Resolution like "2560x1440" (default: current or preferred mode)
+---@field position integer[]? Top-left corner { x, y } (default: right of the other outputs)
+---@field rotation "normal"|"left"|"right"|"inverted"? Output rotation (default "normal")
+---@field scale number? Framebuffer pixels per output pixel, like `xrandr --scale` (default 1.0)
+---@field primary boolean? Make this the primary output
+---@field tags integer[]? Move clients on these tags to this output when the profile activates
+
+---Add a monitor profile, activated automatically when exactly the listed outputs are connected
+---Replaces autorandr for simple docked/mobile setups; the first matching profile wins
+---@param profile {name: string, lid: "open"|"closed"?, outputs: oxwm.OutputSetup[]} Profile name, optional lid state to match, and output settings
+function oxwm.monitor.add_profile(profile) end
+
+---Configure outputs at startup and whenever outputs are connected or disconnected
+---Used when no monitor profile matches; outputs that are not connected are skipped
+---@param outputs oxwm.OutputSetup[] Output settings (the tags field is ignored here)
+function oxwm.monitor.set_outputs(outputs) end
+
 ---Layout management module
 ---@class oxwm.layout
 oxwm.layout = {}
@@ -192,7 +438,7 @@ oxwm.layout = {}
 function oxwm.layout.cycle() end
 
 ---Set specific layout
----@param name string Layout name (e.g., "tiling", "normie", "tabbed", "grid", "monocle", "scrolling")
+---@param name string Layout name (e.g., "tiling", "normie", "tabbed", "grid", "monocle", "scrolling", "spiral", "bstack", "centeredmaster")
 ---@return table Action table for keybinding
 function oxwm.layout.set(name) end
 
@@ -221,19 +467,33 @@ function oxwm.tag.view_next() end
 ---@return table Action table for keybinding
 function oxwm.tag.view_previous() end
 
----View/switch to next non-empty tag
+---View/switch to next non-empty tag, skipping tags without windows on the focused monitor
+---(a window on several tags occupies each of them, as shown in the bar)
 ---@return table Action table for keybinding
 function oxwm.tag.view_next_nonempty() end
 
----View/switch to previous non-empty tag
+---View/switch to previous non-empty tag, skipping tags without windows on the focused monitor
 ---@return table Action table for keybinding
 function oxwm.tag.view_previous_nonempty() end
 
+---Alias of oxwm.tag.view_next_nonempty
+---@return table Action table for keybinding
+function oxwm.tag.view_next_occupied() end
+
+---Alias of oxwm.tag.view_previous_nonempty
+---@return table Action table for keybinding
+function oxwm.tag.view_previous_occupied() end
+
 ---Move focused window to tag
----@param index integer Tag index (0-based)
+---@param index integer|"+" Tag index (0-based), or "+" for a new tag (see oxwm.tag.move_to_new)
 ---@return table Action table for keybinding
 function oxwm.tag.move_to(index) end
 
+---Move focused window to a newly created tag, appended after the configured ones
+---Created tags are removed again once they hold no windows and are not viewed
+---@return table Action table for keybinding
+function oxwm.tag.move_to_new() end
+
 ---Toggle viewing a tag (allows viewing multiple tags at once)
 ---@param index integer Tag index (0-based)
 ---@return table Action table for keybinding
@@ -248,6 +508,32 @@ function oxwm.tag.toggletag(index) end
 ---@param enabled boolean Enable or disable tag_back_and_forth
 function oxwm.tag.set_back_and_forth(enabled) end
 
+---Warp the pointer to the focused window after switching tags (useful with focus-follows-mouse)
+---@param enabled boolean Enable or disable pointer warping
+function oxwm.tag.set_warp_pointer(enabled) end
+
+---Pin applications to a tag. Missing apps are started by oxwm.tag.spawn_pinned()
+---(or on the first view of the tag with on_view) and tiled in the listed order, first as master.
+---Example: oxwm.tag.pin(1, { { command = "code", class = "Code" }, { command = "alacritty", class = "Alacritty" } }, { layout = "tiling" })
+---@param index integer Tag index (0-based)
+---@param apps table[] List of { command = string, class = string }
+---@param options? { layout?: string, on_view?: boolean }
+function oxwm.tag.pin(index, apps, options) end
+
+---Start the missing pinned apps of the current tag and restore its layout
+---@return table Action table for keybinding
+function oxwm.tag.spawn_pinned() end
+
+---Save the current tag's layout, master settings and window order/geometry as a named snapshot
+---@param name string Snapshot name (stored under $XDG_STATE_HOME/oxwm/snapshots)
+---@return table Action table for keybinding
+function oxwm.tag.save_snapshot(name) end
+
+---Restore a named snapshot on the current tag, reordering and resizing windows with matching classes
+---@param name string Snapshot name
+---@return table Action table for keybinding
+function oxwm.tag.restore_snapshot(name) end
+
 ---Status bar configuration module
 ---@class oxwm.bar
 oxwm.bar = {}
@@ -270,56 +556,180 @@ function oxwm.bar.add_block(format, command, arg, interval, color, underline) en
 ---@param blocks table[] Array of block configurations created with oxwm.bar.block.*
 function oxwm.bar.set_blocks(blocks) end
 
+---Add a second bar showing only blocks, on the edge opposite the main bar, to every monitor
+---e.g. tags and title on top with oxwm.bar.set_blocks({}), and the blocks on the bottom
+---Clicks on its blocks work as on the main bar; popups only open from the main bar
+---@param blocks table[]|nil Array of block configurations, or nil for no second bar (default)
+function oxwm.bar.set_second_bar(blocks) end
+
 ---Block constructors module
+---Every block also accepts popup: a terminal program (e.g. "htop") opened as a centered floating
+---window when the block is left-clicked, and closed when it is clicked again,
+---and sensitive: true to hide the block while presentation mode is on.
+---on_click, on_middle_click, on_right_click, on_scroll_up and on_scroll_down take commands
+---spawned when the block is clicked, with the button (1-5) in $BLOCK_BUTTON as in dwmblocks.
+---Clicking a shell block also reruns its command with $BLOCK_BUTTON set.
+---focused_only: true shows and runs a block only on the focused monitor's bar
+---min_width: least width in pixels (40) or digits ("4ch"), so changing numbers don't shift the bar;
+---align: "left" (default), "center" or "right" places narrower text within it
+---signal: n (1-30) refreshes the block right away on SIGRTMIN+n, as in dwmblocks: `pkill -RTMIN+n oxwm`
+---Block text, including the root window name status, takes status2d markup: ^c#rrggbb^ colors the
+---text after it, ^b#rrggbb^ its background, ^d^ resets both, ^rx,y,w,h^ draws a rectangle and ^fn^
+---skips n pixels, e.g. "^c#f7768e^ ^d^42%"
 ---@class oxwm.bar.block
 oxwm.bar.block = {}
 
 ---Create a RAM usage block
----@param config {format: string, interval: integer, color: string|integer, underline: boolean} Block configuration
+---@param config {format: string, interval: integer, warning: integer?, critical: integer?, warning_color: string|integer?, color: string|integer, underline: boolean, background: string|integer|table?, popup: string?, sensitive: boolean?} Block configuration ("{used}", "{total}", "{percent}" and "{swap_used}", "{swap_total}", "{swap_percent}"; warning and critical are percent in use and default to 85 and 95, warning_color defaults to the urgent scheme)
 ---@return table Block configuration
 function oxwm.bar.block.ram(config) end
 
+---Create a CPU usage block from /proc/stat
+---@param config {format: string?, interval: integer?, warning: integer?, critical: integer?, warning_color: string|integer?, color: string|integer, underline: boolean, background: string|integer|table?, popup: string?, sensitive: boolean?} Block configuration ("{}" is the percent of all cores, "{cores}" a bar per core; format defaults to "CPU {}%", interval to 2, warning and critical to 80 and 95)
+---@return table Block configuration
+function oxwm.bar.block.cpu(config) end
+
+---Create a temperature block for a thermal zone
+---@param config {format: string?, zone: integer|string?, interval: integer?, warning: integer?, critical: integer?, warning_color: string|integer?, color: string|integer, underline: boolean, background: string|integer|table?, popup: string?, sensitive: boolean?} Block configuration ("{}" is degrees Celsius; zone is a thermal_zone number or type such as "x86_pkg_temp" and defaults to the CPU's; format defaults to "{}°C", warning and critical to 75 and 90)
+---@return table Block configuration
+function oxwm.bar.block.temperature(config) end
+
+---Create a GPU usage block, reading amdgpu's sysfs counters or NVIDIA's NVML through nvidia-smi
+---Format placeholders: {} (utilization %), {used} and {total} (VRAM in GB), {percent} (VRAM %)
+---@param config {format: string?, device: string?, interval: integer?, color: string|integer, underline: boolean, background: string|integer|table?, popup: string?, sensitive: boolean?} Block configuration (device is "card0" for amdgpu or "nvidia0"/"0" for NVIDIA, defaulting to the first GPU found; format defaults to "GPU {}%", interval to 2)
+---@return table Block configuration
+function oxwm.bar.block.gpu(config) end
+
 ---Create a date/time block
----@param config {format: string, date_format: string, interval: integer, color: string|integer, underline: boolean} Block configuration (format is display template with {}, date_format is strftime format)
+---zones lists timezones cycled by clicking the block (right click goes back, middle click returns
+---to the first): IANA names like "UTC" or "America/New_York", "local", or {tz = "...", label = "..."}.
+---{zone} in format shows the zone's label. locale (e.g. "de_DE.UTF-8") sets month and day names.
+---@param config {format: string, date_format: string, interval: integer, color: string|integer, underline: boolean, background: string|integer|table?, popup: string?, sensitive: boolean?, zones: (string|{tz: string, label: string?})[]?, locale: string?} Block configuration (format is display template with {}, date_format is strftime format)
 ---@return table Block configuration
 function oxwm.bar.block.datetime(config) end
 
 ---Create a shell command block
----@param config {format: string, command: string, interval: integer, color: string|integer, underline: boolean} Block configuration
+---If the command fails or runs longer than timeout seconds it is killed, the failure is logged and
+---the block shows error (default "!") in place of the output; an empty error hides the block instead
+---Output that is a JSON object is read as structured output: {"text", "color", "urgency", "min_width",
+---"segments": [{"text", "color"}]}. urgency "warning" or "critical" draws the block with the urgent
+---scheme; min_width (pixels) pads the block so changing numbers don't shift the bar
+---@param config {format: string, command: string, interval: integer, color: string|integer, underline: boolean, background: string|integer|table?, popup: string?, sensitive: boolean?, timeout: integer?, error: string?} Block configuration
 ---@return table Block configuration
 function oxwm.bar.block.shell(config) end
 
+---Create a block showing details of the focused window
+---The command runs whenever focus or the focused window's title changes, with OXWM_CLASS,
+---OXWM_INSTANCE, OXWM_TITLE and OXWM_PID set; the block is hidden when the output is empty
+---or the focused window's class is not in classes
+---@param config {format: string, command: string, classes: string[]?, timeout: integer?, color: string|integer, underline: boolean, background: string|integer|table?, popup: string?, sensitive: boolean?} Block configuration
+---@return table Block configuration
+function oxwm.bar.block.context(config) end
+
 ---Create a static text block
----@param config {format: string, text: string, interval: integer, color: string|integer, underline: boolean} Block configuration
+---@param config {format: string, text: string, interval: integer, color: string|integer, underline: boolean, background: string|integer|table?, popup: string?, sensitive: boolean?} Block configuration
 ---@return table Block configuration
 function oxwm.bar.block.static(config) end
 
----Create a battery status block
----@param config {format: string, charging: string, discharging: string, full: string, interval: integer, color: string|integer, underline: boolean, battery_name: string} Block configuration
+---Create a battery status block, read from /sys/class/power_supply
+---A discharging battery at or below `warning` percent is drawn in low_color (or the urgent scheme), and at or below `critical` percent as critical.
+---on_critical runs once each time the battery falls to `critical`, with the charge in $OXWM_BATTERY.
+---@param config {format: string, charging: string, discharging: string, full: string, interval: integer, color: string|integer, underline: boolean, battery_name: string, warning: integer?, critical: integer?, low_color: string|integer?, on_critical: string?, background: string|integer|table?, popup: string?, sensitive: boolean?} Block configuration (warning defaults to 20, critical to 10)
 ---@return table Block configuration
 function oxwm.bar.block.battery(config) end
 
+---Create a block counting down to the next event in file: an .ics calendar, or a plain file with
+---one "YYYY-MM-DD HH:MM Title" line per event. {} is the time left and {title} the event's title.
+---The block turns to a warning warning minutes before the event (default 15), critical critical
+---minutes before (default 5), and is hidden when no event is ahead. The file is read again when it
+---changes; recurring events aren't expanded.
+---@param config {format: string, file: string, interval: integer?, color: string|integer, underline: boolean, warning: integer?, critical: integer?, background: string|integer|table?, popup: string?, sensitive: boolean?} Block configuration (interval defaults to 60)
+---@return table Block configuration
+function oxwm.bar.block.countdown(config) end
+
+---Create a block showing failed systemd units, asked over D-Bus. {} is the number of failed units
+---and {units} their names. units lists system units and user_units units of the user's service
+---manager; with neither, every failed unit counts. The block is critical while any unit has failed
+---and hidden otherwise. Clicking it lists the failed units in a terminal unless popup is set.
+---@param config {format: string, units: string[]?, user_units: string[]?, interval: integer?, color: string|integer, underline: boolean, background: string|integer|table?, popup: string?, sensitive: boolean?} Block configuration (interval defaults to 10)
+---@return table Block configuration
+function oxwm.bar.block.systemd(config) end
+
+---Create a block counting Docker or Podman containers: {} is the number running and {total} the number
+---created. socket is the daemon's API socket; without it $DOCKER_HOST, /var/run/docker.sock and the
+---rootless and rootful Podman sockets are tried. The block is hidden while no daemon answers.
+---@param config {format: string, socket: string?, interval: integer?, color: string|integer, underline: boolean, background: string|integer|table?, popup: string?, sensitive: boolean?} Block configuration (interval defaults to 10)
+---@return table Block configuration
+function oxwm.bar.block.containers(config) end
+
+---Create a block showing values from JSON fetched over HTTP with curl, e.g. prices. url and path
+---give the value shown for {}; path is like "bitcoin.usd" or "$.data[0].price". sources lists more
+---values as { name = "eth", url = "...", path = "..." }, each shown for {name}; sources sharing a url
+---fetch it once. decimals rounds numbers. The last values stay shown while a fetch fails.
+---@param config {format: string, url: string?, path: string?, sources: {name: string, url: string, path: string}[]?, timeout: integer?, decimals: integer?, interval: integer?, color: string|integer, underline: boolean, background: string|integer|table?, popup: string?, sensitive: boolean?} Block configuration (interval defaults to 300, timeout to 10)
+---@return table Block configuration
+function oxwm.bar.block.http(config) end
+
+---Create a block that shows microphone while a program records audio (a PulseAudio or PipeWire
+---source output) and camera while a program has a /dev/video* device open. {} is the indicators in
+---use and {apps} the programs using them. The block is drawn critical, in the urgent colors, and is
+---hidden while neither is in use.
+---@param config {format: string?, microphone: string?, camera: string?, interval: integer?, color: string|integer, underline: boolean, background: string|integer|table?, popup: string?, sensitive: boolean?} Block configuration (interval defaults to 2, microphone to "MIC", camera to "CAM")
+---@return table Block configuration
+function oxwm.bar.block.privacy(config) end
+
+---Create a network block showing the SSID and signal strength of Wi-Fi, and download and upload rates
+---Reads sysfs and nl80211 directly rather than running iw or ip.
+---@param config {format: string?, ethernet: string?, disconnected: string?, interface: string?, interval: integer?, color: string|integer, underline: boolean, background: string|integer|table?, popup: string?, sensitive: boolean?} Block configuration (format is used for Wi-Fi and defaults to "{ssid} {signal}%", ethernet for other links and defaults to "{}", disconnected to "offline" and an empty string hides the block; "{}" is the interface, "{down}" and "{up}" the rates; interface defaults to the one of the default route)
+---@return table Block configuration
+function oxwm.bar.block.network(config) end
+
+---Create a volume block for the default PulseAudio or PipeWire sink, using pactl
+---Updates as soon as the volume changes, e.g. from media keys. Scrolling on the block changes the volume and a right click toggles mute.
+---@param config {format: string?, muted: string?, step: integer?, max: integer?, interval: integer?, color: string|integer, underline: boolean, background: string|integer|table?, popup: string?, sensitive: boolean?} Block configuration ("{}" is the volume in percent; format defaults to "VOL {}%", muted to "MUTE", step to 5, max to 100)
+---@return table Block configuration
+function oxwm.bar.block.volume(config) end
+
+---Create a pending package updates block, hidden while the system is up to date
+---The count is cached in ~/.cache/oxwm, and checks never run more often than every 5 minutes.
+---@param config {format: string?, backend: "pacman"|"apt"|"dnf"?, command: string?, interval: integer?, color: string|integer, underline: boolean, background: string|integer|table?, popup: string?, sensitive: boolean?} Block configuration ("{}" is the count; backend defaults to the first package manager installed; command replaces the backend and prints one line per update; format defaults to "UPD {}", interval to 3600)
+---@return table Block configuration
+function oxwm.bar.block.updates(config) end
+
+---Create a block with the battery levels of wireless mice, keyboards, headsets and other peripherals, asked from UPower
+---Shows an icon and the charge per device, hidden while none are connected; a left click adds or removes the device names.
+---@param config {format: string?, icons: {mouse: string?, keyboard: string?, headset: string?, gamepad: string?, other: string?}?, warning: integer?, interval: integer?, color: string|integer, underline: boolean, background: string|integer|table?, popup: string?, sensitive: boolean?} Block configuration ("{}" is the device list; icons default to "M", "K", "H", "G" and "B"; a device at or below warning percent, default 20, turns the block to a warning; interval defaults to 60)
+---@return table Block configuration
+function oxwm.bar.block.peripherals(config) end
+
+---Create a VPN status block, clicked to bring the VPN up or down
+---Watches `interface` (e.g. "wg0") or the NetworkManager `connection`; with neither, any WireGuard or tun interface that is up.
+---A left click runs `toggle` with $VPN_STATE set to "up" or "down", or else activates or deactivates `connection`.
+---@param config {format: string?, format_down: string?, interface: string?, connection: string?, toggle: string?, down_color: string|integer?, interval: integer?, color: string|integer, underline: boolean, background: string|integer|table?, sensitive: boolean?} Block configuration ("{}" is the VPN name; format defaults to "VPN {}", format_down to "VPN off", interval to 5; without down_color a down VPN uses the urgent scheme)
+---@return table Block configuration
+function oxwm.bar.block.vpn(config) end
+
 ---Set normal tag color scheme (unselected, no windows)
 ---@param foreground string|integer Foreground color
----@param background string|integer Background color
+---@param background string|integer|table Background color, or { top, bottom } for a vertical gradient
 ---@param underline string|integer Underline color
 function oxwm.bar.set_scheme_normal(foreground, background, underline) end
 
 ---Set occupied tag color scheme (unselected, has windows)
 ---@param foreground string|integer Foreground color
----@param background string|integer Background color
+---@param background string|integer|table Background color, or { top, bottom } for a vertical gradient
 ---@param underline string|integer Underline color
 function oxwm.bar.set_scheme_occupied(foreground, background, underline) end
 
 ---Set selected tag color scheme (currently selected tag)
 ---@param foreground string|integer Foreground color
----@param background string|integer Background color
+---@param background string|integer|table Background color, or { top, bottom } for a vertical gradient
 ---@param underline string|integer Underline color
 function oxwm.bar.set_scheme_selected(foreground, background, underline) end
 
 ---Set urgent tag color scheme (tags with urgent windows)
 ---@param foreground string|integer Foreground color
----@param background string|integer Background color
+---@param background string|integer|table Background color, or { top, bottom } for a vertical gradient
 ---@param underline string|integer Underline color
 function oxwm.bar.set_scheme_urgent(foreground, background, underline) end
 
@@ -327,6 +737,162 @@ function oxwm.bar.set_scheme_urgent(foreground, background, underline) end
 ---@param hide boolean Whether to hide vacant tags
 function oxwm.bar.set_hide_vacant_tags(hide) end
 
+---Show the root window's name (as set by `xsetroot -name` or a dwm status
+---script) in the status area instead of the blocks from set_blocks. The bar
+---redraws whenever the name changes.
+---@param enabled boolean Whether to show the root window's name
+function oxwm.bar.set_root_status(enabled) end
+
+---systemd user-session integration module
+---@class oxwm.systemd
+oxwm.systemd = {}
+
+---Send READY=1 to systemd once the window manager is initialized
+---Only has an effect when oxwm runs as a Type=notify user service
+---@param enabled boolean Enable or disable readiness notification
+function oxwm.systemd.set_notify(enabled) end
+
+---Launch autostart commands as transient systemd user units (via systemd-run)
+---Their logs go to the journal and they can be managed with systemctl --user
+---@param enabled boolean Enable or disable transient units for autostart
+function oxwm.systemd.set_autostart_units(enabled) end
+
+---Run a screen locker when logind asks the session to lock (loginctl lock-session, idle or suspend hooks)
+---Lock requests are followed through dbus-monitor; an empty command disables the integration
+---@param command string Locker command run through sh -c (e.g. "i3lock -n")
+---@param blank boolean? Also blank all monitors when locking (default false)
+function oxwm.systemd.set_locker(command, blank) end
+
+---Lock the session with the configured locker
+---@return table Action table for keybinding
+function oxwm.systemd.lock() end
+
+---Power supply hooks, driven by UPower change signals
+---Hooks run through sh -c with OXWM_POWER_SOURCE ("ac" or "battery") and OXWM_BATTERY (percent) set
+---@class oxwm.power
+oxwm.power = {}
+
+---Run a command when the AC adapter is plugged in
+---@param command string Shell command to run
+function oxwm.power.on_ac(command) end
+
+---Run a command when the AC adapter is unplugged
+---@param command string Shell command to run
+function oxwm.power.on_battery(command) end
+
+---Run a command once when the battery drops to a percentage while discharging
+---Can be called several times for multiple thresholds
+---@param percent integer Threshold in percent (1-100)
+---@param command string Shell command to run
+function oxwm.power.on_low_battery(percent, command) end
+
+---Configure the screen recording indicator shown in the bar
+---The indicator is shown while any of the listed processes is running; set text to "" to disable
+---With `grabs`, it is also shown while a client grabs the keyboard or pointer. X can't report grabs, so oxwm briefly grabs both itself every 2 seconds:
+---the focused window sees a focus out and in each time, keys typed at that moment can be lost, and open menus or drags count as grabs
+---@param config {text: string?, processes: string[]?, grabs: boolean?} Indicator text (default "REC"), recorder process names, and whether client grabs count (default false)
+function oxwm.bar.set_recording_indicator(config) end
+
+---Make the bar ignore mouse input so clicks go to the windows below it
+---Tag and layout clicks on the bar stop working while enabled
+---@param enabled boolean Enable or disable click-through
+function oxwm.bar.set_click_through(enabled) end
+
+---Round the corners of the bar and tab bar (XShape)
+---@param radius integer Corner radius in pixels (0 disables)
+function oxwm.bar.set_corner_radius(radius) end
+
+---Draw a border around the bar
+---@param width integer Border width in pixels (0 disables)
+---@param color? string|integer Border color as hex string or integer (default "#444444")
+function oxwm.bar.set_border(width, color) end
+
+---Draw a shadow gradient along the bottom edge of the bar
+---@param size integer Shadow height in pixels (0 disables)
+function oxwm.bar.set_shadow(size) end
+
+---Show which monitor a bar belongs to at its left edge (only with more than one monitor)
+---@param label "none"|"number"|"name" Nothing, the monitor number, or the RandR output name (e.g. "eDP-1")
+function oxwm.bar.set_monitor_label(label) end
+
+---Put the focused window's WM_CLASS before its title, e.g. "[firefox] Page Title"
+---@param show "none"|"class"|"instance" Nothing (default), the class, or the instance
+---@param color? string|integer Color of the class; the title color when omitted
+function oxwm.bar.set_title_class(show, color) end
+
+---Show window counts beside the layout symbol, e.g. "[2/5]"
+---`{}` is replaced by the windows on the viewed tags and `{total}` by all windows on the monitor
+---@param format string|boolean Format, true for "[{}/{total}]", or false to hide the counts (default)
+function oxwm.bar.set_window_count(format) end
+
+---Blink a tag in the urgent colors when a rule places a new window on it while it isn't shown
+---@param enabled boolean Enable or disable the flash (default false)
+function oxwm.bar.set_tag_flash(enabled) end
+
+---Blink the background of tags with urgent windows in the urgent colors until they are viewed
+---@param config {interval: integer?, duration: integer?}|boolean Milliseconds the background stays on and then off (default 500), and milliseconds after which the tag stops blinking and stays urgent (default 0, blink until viewed); true for the defaults, false to turn blinking off (default)
+function oxwm.bar.set_urgent_blink(config) end
+
+---Mark tabs of unfocused windows whose title changed or that rang the bell with a small dot
+---Weaker than urgency and cleared when the window is focused
+---@param color string|integer|nil Dot color, or nil to disable (default)
+function oxwm.bar.set_activity_color(color) end
+
+---Toggle outlines around the clickable bar regions, labeled T<n> for tags, L for the layout
+---symbol, I for the indicator, W for the title and B<n> for blocks; the extents are also printed to stderr
+---@return table Action table for keybinding
+function oxwm.bar.toggle_debug() end
+
+---Run an action when a bar region is left-clicked, replacing the default (tags are viewed,
+---blocks toggle their popup). oxwm.tag.view/toggleview/move_to/toggletag act on the clicked tag.
+---Example: oxwm.bar.on_click("layout", oxwm.layout.cycle())
+---@param region "tag"|"layout"|"indicator"|"title"|"block"
+---@param action table Action table
+function oxwm.bar.on_click(region, action) end
+
+---Run an action when a bar region is double-clicked. The single click on that region is then
+---delayed by the double-click time to tell the two apart.
+---Example: oxwm.bar.on_double_click("tag", oxwm.tag.toggleview(0))
+---@param region "tag"|"layout"|"indicator"|"title"|"block"
+---@param action table Action table
+function oxwm.bar.on_double_click(region, action) end
+
+---Ignore clicks within this many pixels of the edge of a bar region, to avoid switching to the
+---neighbouring tag by accident
+---@param pixels integer Dead zone width (default 0)
+function oxwm.bar.set_dead_zone(pixels) end
+
+---Set the longest gap between the two clicks of a double click on the bar
+---@param ms integer Milliseconds (default 300)
+function oxwm.bar.set_double_click_time(ms) end
+
+---Hide the bars once the pointer has been off them for a while; they come back when the
+---pointer touches their screen edge, and stay while a keychord is pending or a tag is urgent.
+---Hidden bars leave their space to the windows and are drawn over them when shown.
+---@param ms integer Milliseconds before hiding, or 0 to always show the bars (default)
+function oxwm.bar.set_autohide(ms) end
+
+---Scrolling over the tags in the bar views the next/previous tag, and over the title focuses the
+---next/previous window; this makes tag scrolling skip tags without windows
+---@param enabled boolean Skip empty tags (default false)
+function oxwm.bar.set_scroll_skip_empty(enabled) end
+
 ---Add an autostart command
 ---@param cmd string Command to run at startup
 function oxwm.autostart(cmd) end
+
+---Run XDG autostart entries (~/.config/autostart and /etc/xdg/autostart) at startup
+---Entries honor Hidden, TryExec, OnlyShowIn and NotShowIn (desktop name "OXWM")
+---@param enabled boolean Enable or disable XDG autostart
+function oxwm.set_xdg_autostart(enabled) end
+
+---Write WM state to a file or FIFO on every change, one line per monitor:
+---`<monitor>\t<tags>\t<layout>\t<title>` (focused monitor prefixed with `*`,
+---tags prefixed with `#` viewed, `!` urgent, `:` occupied, `.` empty)
+---@param path string|nil Path to write to (a FIFO created with mkfifo, or a regular file); nil disables
+function oxwm.set_status_file(path) end
+
+---Also expose the IPC protocol on a TCP port, e.g. for remote debugging or a phone widget.
+---Each connection must first send the line `auth <token>`, and is dropped if it sends anything else or takes over 10 seconds
+---@param options {address: string?, token: string}|nil Listen address (default "127.0.0.1:7878") and required token; nil disables
+function oxwm.set_remote_control(options) end